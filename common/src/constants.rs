@@ -11,6 +11,14 @@ pub const DEFAULT_MEMORY_SIZE: u64 = 10 * 1024 * 1024;
 pub const DEFAULT_STACK_SIZE: u64 = 4096;
 pub const DEFAULT_MAX_INPUT_SIZE: u64 = 4096;
 pub const DEFAULT_MAX_OUTPUT_SIZE: u64 = 4096;
+pub const DEFAULT_MAX_BYTECODE_SIZE: u64 = 1 << 20;
+
+// Syscalls are made with `ecall`, passing the syscall number in a7 and the arguments in a0, a1, ...
+// They may only have side effects on the host, not on the guest's registers or memory, so that an
+// `ecall` can be proven as a no-op. Guest inputs and outputs go through the memory-mapped I/O
+// regions instead (see `JoltDevice`), which the proof binds.
+/// Logs `a1` bytes of UTF-8 starting at address `a0` on the host.
+pub const SYSCALL_LOG: u64 = 1;
 
 pub const fn virtual_register_index(index: u64) -> u64 {
     index + VIRTUAL_REGISTER_COUNT
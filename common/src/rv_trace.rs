@@ -1,6 +1,9 @@
 use std::str::FromStr;
 
-use crate::constants::{MEMORY_OPS_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT};
+use crate::constants::{
+    DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
+    MEMORY_OPS_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use serde::{Deserialize, Serialize};
 use strum::EnumCount;
@@ -112,19 +115,23 @@ impl From<&RVTraceRow> for [MemoryOp; MEMORY_OPS_PER_INSTRUCTION] {
                 | RV32IM::SLTIU
                 | RV32IM::JALR
                 | RV32IM::VIRTUAL_MOVE
-                | RV32IM::VIRTUAL_MOVSIGN => [
+                | RV32IM::VIRTUAL_MOVSIGN
+                | RV32IM::ADDIW
+                | RV32IM::SLLIW
+                | RV32IM::SRLIW
+                | RV32IM::SRAIW => [
                     rs1_read(),
                     MemoryOp::noop_read(),
                     rd_write(),
                     MemoryOp::noop_read(),
                 ],
-                RV32IM::LW => [
+                RV32IM::LW | RV32IM::LD | RV32IM::LWU => [
                     rs1_read(),
                     MemoryOp::noop_read(),
                     rd_write(),
                     MemoryOp::Read(rs1_offset()),
                 ],
-                RV32IM::FENCE => [
+                RV32IM::FENCE | RV32IM::ECALL => [
                     MemoryOp::noop_read(),
                     MemoryOp::noop_read(),
                     MemoryOp::noop_write(),
@@ -133,7 +140,7 @@ impl From<&RVTraceRow> for [MemoryOp; MEMORY_OPS_PER_INSTRUCTION] {
                 _ => unreachable!("{val:?}"),
             },
             RV32InstructionFormat::S => match val.instruction.opcode {
-                RV32IM::SW => [
+                RV32IM::SW | RV32IM::SD => [
                     rs1_read(),
                     rs2_read(),
                     MemoryOp::noop_write(),
@@ -429,6 +436,24 @@ pub enum RV32IM {
     VIRTUAL_ASSERT_EQ,
     VIRTUAL_ASSERT_VALID_DIV0,
     VIRTUAL_ASSERT_HALFWORD_ALIGNMENT,
+    // RV64 instructions, which are traced but not yet provable (see `is_rv64`)
+    ADDW,
+    SUBW,
+    SLLW,
+    SRLW,
+    SRAW,
+    ADDIW,
+    SLLIW,
+    SRLIW,
+    SRAIW,
+    MULW,
+    DIVW,
+    DIVUW,
+    REMW,
+    REMUW,
+    LD,
+    LWU,
+    SD,
 }
 
 impl FromStr for RV32IM {
@@ -486,6 +511,23 @@ impl FromStr for RV32IM {
             "REMU" => Ok(Self::REMU),
             "FENCE" => Ok(Self::FENCE),
             "UNIMPL" => Ok(Self::UNIMPL),
+            "ADDW" => Ok(Self::ADDW),
+            "SUBW" => Ok(Self::SUBW),
+            "SLLW" => Ok(Self::SLLW),
+            "SRLW" => Ok(Self::SRLW),
+            "SRAW" => Ok(Self::SRAW),
+            "ADDIW" => Ok(Self::ADDIW),
+            "SLLIW" => Ok(Self::SLLIW),
+            "SRLIW" => Ok(Self::SRLIW),
+            "SRAIW" => Ok(Self::SRAIW),
+            "MULW" => Ok(Self::MULW),
+            "DIVW" => Ok(Self::DIVW),
+            "DIVUW" => Ok(Self::DIVUW),
+            "REMW" => Ok(Self::REMW),
+            "REMUW" => Ok(Self::REMUW),
+            "LD" => Ok(Self::LD),
+            "LWU" => Ok(Self::LWU),
+            "SD" => Ok(Self::SD),
             _ => Err("Could not match instruction to RV32IM set.".to_string()),
         }
     }
@@ -502,6 +544,31 @@ pub enum RV32InstructionFormat {
 }
 
 impl RV32IM {
+    /// Whether this is an RV64-only instruction. These are traced by the emulator, but Jolt's
+    /// lookups, R1CS constraints and memory model are still 32-bit, so they cannot be proven.
+    pub fn is_rv64(&self) -> bool {
+        matches!(
+            self,
+            RV32IM::ADDW
+                | RV32IM::SUBW
+                | RV32IM::SLLW
+                | RV32IM::SRLW
+                | RV32IM::SRAW
+                | RV32IM::ADDIW
+                | RV32IM::SLLIW
+                | RV32IM::SRLIW
+                | RV32IM::SRAIW
+                | RV32IM::MULW
+                | RV32IM::DIVW
+                | RV32IM::DIVUW
+                | RV32IM::REMW
+                | RV32IM::REMUW
+                | RV32IM::LD
+                | RV32IM::LWU
+                | RV32IM::SD
+        )
+    }
+
     #[rustfmt::skip] // keep matches pretty
     pub fn instruction_type(&self) -> RV32InstructionFormat {
         match self {
@@ -523,7 +590,17 @@ impl RV32IM {
             RV32IM::DIV    |
             RV32IM::DIVU   |
             RV32IM::REM    |
-            RV32IM::REMU => RV32InstructionFormat::R,
+            RV32IM::REMU   |
+            RV32IM::ADDW   |
+            RV32IM::SUBW   |
+            RV32IM::SLLW   |
+            RV32IM::SRLW   |
+            RV32IM::SRAW   |
+            RV32IM::MULW   |
+            RV32IM::DIVW   |
+            RV32IM::DIVUW  |
+            RV32IM::REMW   |
+            RV32IM::REMUW => RV32InstructionFormat::R,
 
             RV32IM::ADDI         |
             RV32IM::XORI         |
@@ -534,21 +611,29 @@ impl RV32IM {
             RV32IM::SRAI         |
             RV32IM::SLTI         |
             RV32IM::FENCE        |
+            RV32IM::ECALL        |
             RV32IM::SLTIU        |
             RV32IM::VIRTUAL_MOVE |
             RV32IM::VIRTUAL_ASSERT_HALFWORD_ALIGNMENT |
-            RV32IM::VIRTUAL_MOVSIGN => RV32InstructionFormat::I,
+            RV32IM::VIRTUAL_MOVSIGN |
+            RV32IM::ADDIW        |
+            RV32IM::SLLIW        |
+            RV32IM::SRLIW        |
+            RV32IM::SRAIW => RV32InstructionFormat::I,
 
             RV32IM::LB  |
             RV32IM::LH  |
             RV32IM::LW  |
             RV32IM::LBU |
             RV32IM::LHU |
+            RV32IM::LD  |
+            RV32IM::LWU |
             RV32IM::JALR => RV32InstructionFormat::I,
 
             RV32IM::SB |
             RV32IM::SH |
-            RV32IM::SW => RV32InstructionFormat::S,
+            RV32IM::SW |
+            RV32IM::SD => RV32InstructionFormat::S,
 
             RV32IM::BEQ  |
             RV32IM::BNE  |
@@ -568,7 +653,6 @@ impl RV32IM {
 
             RV32IM::JAL => RV32InstructionFormat::UJ,
 
-            RV32IM::ECALL  |
             RV32IM::EBREAK |
             RV32IM::UNIMPL => unimplemented!(),
         }
@@ -592,11 +676,19 @@ pub struct JoltDevice {
 
 impl JoltDevice {
     pub fn new(max_input_size: u64, max_output_size: u64) -> Self {
+        Self::from_config(&MemoryConfig {
+            max_input_size,
+            max_output_size,
+            ..Default::default()
+        })
+    }
+
+    pub fn from_config(memory_config: &MemoryConfig) -> Self {
         Self {
             inputs: Vec::new(),
             outputs: Vec::new(),
             panic: false,
-            memory_layout: MemoryLayout::new(max_input_size, max_output_size),
+            memory_layout: MemoryLayout::from_config(memory_config),
         }
     }
 
@@ -672,12 +764,36 @@ impl JoltDevice {
     }
 }
 
+/// The sizes of a guest's memory regions, from which the linker script, the tracer and the
+/// memory checker all derive the guest's address space (see [`MemoryLayout`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// The size of RAM, starting at `RAM_START_ADDRESS`, which holds the program, its stack
+    /// and its heap
+    pub ram_size: u64,
+    pub stack_size: u64,
+    pub max_input_size: u64,
+    pub max_output_size: u64,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            ram_size: DEFAULT_MEMORY_SIZE,
+            stack_size: DEFAULT_STACK_SIZE,
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+        }
+    }
+}
+
 #[derive(
     Debug, Clone, PartialEq, Serialize, Deserialize, CanonicalSerialize, CanonicalDeserialize,
 )]
 pub struct MemoryLayout {
     pub max_input_size: u64,
     pub max_output_size: u64,
+    pub ram_size: u64,
     pub input_start: u64,
     pub input_end: u64,
     pub output_start: u64,
@@ -687,10 +803,19 @@ pub struct MemoryLayout {
 }
 
 impl MemoryLayout {
-    pub fn new(mut max_input_size: u64, mut max_output_size: u64) -> Self {
+    pub fn new(max_input_size: u64, max_output_size: u64) -> Self {
+        Self::from_config(&MemoryConfig {
+            max_input_size,
+            max_output_size,
+            ..Default::default()
+        })
+    }
+
+    pub fn from_config(memory_config: &MemoryConfig) -> Self {
         // Must be word-aligned
-        max_input_size = max_input_size.next_multiple_of(4);
-        max_output_size = max_output_size.next_multiple_of(4);
+        let max_input_size = memory_config.max_input_size.next_multiple_of(4);
+        let max_output_size = memory_config.max_output_size.next_multiple_of(4);
+        let ram_size = memory_config.ram_size.next_multiple_of(4);
 
         // Adds 8 to account for panic bit and termination bit
         // (they each occupy one full 4-byte word)
@@ -710,6 +835,7 @@ impl MemoryLayout {
         Self {
             max_input_size,
             max_output_size,
+            ram_size,
             input_start,
             input_end,
             output_start,
@@ -718,4 +844,10 @@ impl MemoryLayout {
             termination,
         }
     }
+
+    /// The number of memory cells that memory checking covers: the registers, followed by the
+    /// words of the I/O region and of RAM.
+    pub fn max_memory_address(&self) -> u64 {
+        REGISTER_COUNT + (RAM_START_ADDRESS + self.ram_size - self.input_start) / 4
+    }
 }
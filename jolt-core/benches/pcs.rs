@@ -0,0 +1,98 @@
+use ark_bn254::{Bn254, Fr};
+use criterion::{BenchmarkId, Criterion, Throughput};
+use jolt_core::field::JoltField;
+use jolt_core::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use jolt_core::poly::commitment::zeromorph::Zeromorph;
+use jolt_core::poly::dense_mlpoly::DensePolynomial;
+use jolt_core::utils::prover_config::ProverConfig;
+use jolt_core::utils::transcript::{KeccakTranscript, Transcript};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+type PCS = Zeromorph<Bn254, KeccakTranscript>;
+
+const MIN_NUM_VARS: usize = 10;
+/// Can be lowered with the `JOLT_BENCH_MAX_NUM_VARS` environment variable, as generating
+/// the setup for 2^24 evaluations alone takes a while.
+const MAX_NUM_VARS: usize = 24;
+
+fn num_vars_range() -> impl Iterator<Item = usize> {
+    let max_num_vars = std::env::var("JOLT_BENCH_MAX_NUM_VARS")
+        .map(|max| max.parse().expect("invalid JOLT_BENCH_MAX_NUM_VARS"))
+        .unwrap_or(MAX_NUM_VARS);
+    (MIN_NUM_VARS..=max_num_vars).step_by(2)
+}
+
+/// The thread counts to benchmark with: rayon's global pool and, with the
+/// `single-core-benches` feature, a single thread.
+fn thread_configs() -> Vec<(&'static str, ProverConfig)> {
+    let mut configs = vec![("multicore", ProverConfig::default())];
+    if cfg!(feature = "single-core-benches") {
+        let single_core = ProverConfig {
+            num_threads: Some(1),
+            ..Default::default()
+        };
+        configs.push(("single-core", single_core));
+    }
+    configs
+}
+
+fn benchmark_zeromorph(c: &mut Criterion) {
+    let max_num_vars = num_vars_range().last().unwrap();
+    let setup = PCS::setup(&[CommitShape::new(1 << max_num_vars, BatchType::Big)]);
+    let mut rng = ChaCha20Rng::seed_from_u64(111111u64);
+
+    let mut commit_group = c.benchmark_group("Zeromorph::commit");
+    commit_group.sample_size(10);
+    for num_vars in num_vars_range() {
+        let poly = DensePolynomial::<Fr>::random(num_vars, &mut rng);
+        commit_group.throughput(Throughput::Elements(1 << num_vars));
+        for (label, config) in thread_configs() {
+            commit_group.bench_with_input(BenchmarkId::new(label, num_vars), &poly, |b, poly| {
+                config.install(|| b.iter(|| PCS::commit(poly, &setup)));
+            });
+        }
+    }
+    commit_group.finish();
+
+    let mut prove_group = c.benchmark_group("Zeromorph::prove");
+    prove_group.sample_size(10);
+    for num_vars in num_vars_range() {
+        let poly = DensePolynomial::<Fr>::random(num_vars, &mut rng);
+        let point: Vec<Fr> = (0..num_vars).map(|_| Fr::random(&mut rng)).collect();
+        prove_group.throughput(Throughput::Elements(1 << num_vars));
+        for (label, config) in thread_configs() {
+            prove_group.bench_with_input(BenchmarkId::new(label, num_vars), &poly, |b, poly| {
+                config.install(|| {
+                    b.iter(|| {
+                        let mut transcript = KeccakTranscript::new(b"bench");
+                        PCS::prove(&setup, poly, &point, &mut transcript)
+                    })
+                });
+            });
+        }
+    }
+    prove_group.finish();
+
+    let mut verify_group = c.benchmark_group("Zeromorph::verify");
+    for num_vars in num_vars_range() {
+        let poly = DensePolynomial::<Fr>::random(num_vars, &mut rng);
+        let point: Vec<Fr> = (0..num_vars).map(|_| Fr::random(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+        let commitment = PCS::commit(&poly, &setup);
+        let proof = PCS::prove(&setup, &poly, &point, &mut KeccakTranscript::new(b"bench"));
+        verify_group.bench_function(BenchmarkId::from_parameter(num_vars), |b| {
+            b.iter(|| {
+                let mut transcript = KeccakTranscript::new(b"bench");
+                PCS::verify(&proof, &setup, &mut transcript, &point, &eval, &commitment).unwrap()
+            })
+        });
+    }
+    verify_group.finish();
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    benchmark_zeromorph(&mut criterion);
+    criterion.final_summary();
+}
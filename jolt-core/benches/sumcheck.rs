@@ -0,0 +1,86 @@
+use ark_bn254::Fr;
+use criterion::{BenchmarkId, Criterion, Throughput};
+use jolt_core::field::JoltField;
+use jolt_core::poly::dense_mlpoly::DensePolynomial;
+use jolt_core::poly::eq_poly::EqPolynomial;
+use jolt_core::subprotocols::sumcheck::SumcheckInstanceProof;
+use jolt_core::utils::prover_config::ProverConfig;
+use jolt_core::utils::transcript::{KeccakTranscript, Transcript};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+/// The thread counts to benchmark with: rayon's global pool and, with the
+/// `single-core-benches` feature, a single thread.
+fn thread_configs() -> Vec<(&'static str, ProverConfig)> {
+    let mut configs = vec![("multicore", ProverConfig::default())];
+    if cfg!(feature = "single-core-benches") {
+        let single_core = ProverConfig {
+            num_threads: Some(1),
+            ..Default::default()
+        };
+        configs.push(("single-core", single_core));
+    }
+    configs
+}
+
+/// Proves the sum of the product of `degree` random polynomials over `num_vars` variables,
+/// i.e. `num_vars` rounds of degree `degree`.
+fn benchmark_sumcheck(c: &mut Criterion, degree: usize) {
+    let mut rng = ChaCha20Rng::seed_from_u64(111111u64);
+    let mut group = c.benchmark_group(format!("Sumcheck::prove_arbitrary degree {degree}"));
+    group.sample_size(10);
+    for num_vars in (12..=22).step_by(2) {
+        let polys: Vec<DensePolynomial<Fr>> = (0..degree)
+            .map(|_| DensePolynomial::random(num_vars, &mut rng))
+            .collect();
+        let claim: Fr = (0..1 << num_vars)
+            .map(|i| polys.iter().map(|poly| poly[i]).product::<Fr>())
+            .sum();
+        group.throughput(Throughput::Elements(1 << num_vars));
+        for (label, config) in thread_configs() {
+            group.bench_with_input(BenchmarkId::new(label, num_vars), &polys, |b, polys| {
+                config.install(|| {
+                    b.iter_with_setup(
+                        || polys.clone(),
+                        |mut polys| {
+                            let mut transcript = KeccakTranscript::new(b"bench");
+                            SumcheckInstanceProof::prove_arbitrary(
+                                &claim,
+                                num_vars,
+                                &mut polys,
+                                |vals: &[Fr]| vals.iter().product(),
+                                degree,
+                                &mut transcript,
+                            )
+                        },
+                    )
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn benchmark_eq_evals(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(111111u64);
+    let mut group = c.benchmark_group("EqPolynomial::evals");
+    group.sample_size(10);
+    for num_vars in (10..=24).step_by(2) {
+        let r: Vec<Fr> = (0..num_vars).map(|_| Fr::random(&mut rng)).collect();
+        group.throughput(Throughput::Elements(1 << num_vars));
+        for (label, config) in thread_configs() {
+            group.bench_with_input(BenchmarkId::new(label, num_vars), &r, |b, r| {
+                config.install(|| b.iter(|| EqPolynomial::evals(r)));
+            });
+        }
+    }
+    group.finish();
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    benchmark_sumcheck(&mut criterion, 2);
+    benchmark_sumcheck(&mut criterion, 3);
+    benchmark_eq_evals(&mut criterion);
+    criterion.final_summary();
+}
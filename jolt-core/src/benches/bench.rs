@@ -1,15 +1,19 @@
-use crate::field::JoltField;
 use crate::host;
 use crate::jolt::vm::rv32i_vm::{RV32IJoltVM, C, M};
-use crate::jolt::vm::Jolt;
-use crate::poly::commitment::commitment_scheme::CommitmentScheme;
-use crate::poly::commitment::hyperkzg::HyperKZG;
-use crate::poly::commitment::hyrax::HyraxScheme;
-use crate::poly::commitment::zeromorph::Zeromorph;
-use crate::utils::transcript::{KeccakTranscript, Transcript};
-use ark_bn254::{Bn254, Fr, G1Projective};
+use crate::jolt::vm::{Jolt, JoltPreprocessing};
+use crate::poly::commitment::dynamic::{DynCommitmentScheme, PCSKind};
+use crate::utils::transcript::KeccakTranscript;
+use ark_bn254::Fr;
+use common::rv_trace::{ELFInstruction, MemoryLayout};
 use serde::Serialize;
 
+// A single instantiation of the prover serves every scheme
+type PCS = DynCommitmentScheme<KeccakTranscript>;
+
+const MAX_BYTECODE_SIZE: usize = 1 << 20;
+const MAX_MEMORY_ADDRESS: usize = 1 << 20;
+const MAX_TRACE_LENGTH: usize = 1 << 22;
+
 #[derive(Debug, Copy, Clone, clap::ValueEnum)]
 pub enum PCSType {
     Hyrax,
@@ -17,6 +21,16 @@ pub enum PCSType {
     HyperKZG,
 }
 
+impl From<PCSType> for PCSKind {
+    fn from(pcs_type: PCSType) -> Self {
+        match pcs_type {
+            PCSType::Hyrax => PCSKind::Hyrax,
+            PCSType::Zeromorph => PCSKind::Zeromorph,
+            PCSType::HyperKZG => PCSKind::HyperKZG,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, clap::ValueEnum)]
 pub enum BenchType {
     Fibonacci,
@@ -33,73 +47,47 @@ pub fn benchmarks(
     _memory_size: Option<usize>,
     _bytecode_size: Option<usize>,
 ) -> Vec<(tracing::Span, Box<dyn FnOnce()>)> {
-    match pcs_type {
-        PCSType::Hyrax => match bench_type {
-            BenchType::Sha2 => {
-                sha2::<Fr, HyraxScheme<G1Projective, KeccakTranscript>, KeccakTranscript>()
-            }
-            BenchType::Sha3 => {
-                sha3::<Fr, HyraxScheme<G1Projective, KeccakTranscript>, KeccakTranscript>()
-            }
-            BenchType::Sha2Chain => {
-                sha2chain::<Fr, HyraxScheme<G1Projective, KeccakTranscript>, KeccakTranscript>()
-            }
-            BenchType::Fibonacci => {
-                fibonacci::<Fr, HyraxScheme<G1Projective, KeccakTranscript>, KeccakTranscript>()
-            }
-            _ => panic!("BenchType does not have a mapping"),
-        },
-        PCSType::Zeromorph => match bench_type {
-            BenchType::Sha2 => sha2::<Fr, Zeromorph<Bn254, KeccakTranscript>, KeccakTranscript>(),
-            BenchType::Sha3 => sha3::<Fr, Zeromorph<Bn254, KeccakTranscript>, KeccakTranscript>(),
-            BenchType::Sha2Chain => {
-                sha2chain::<Fr, Zeromorph<Bn254, KeccakTranscript>, KeccakTranscript>()
-            }
-            BenchType::Fibonacci => {
-                fibonacci::<Fr, Zeromorph<Bn254, KeccakTranscript>, KeccakTranscript>()
-            }
-            _ => panic!("BenchType does not have a mapping"),
-        },
-        PCSType::HyperKZG => match bench_type {
-            BenchType::Sha2 => sha2::<Fr, HyperKZG<Bn254, KeccakTranscript>, KeccakTranscript>(),
-            BenchType::Sha3 => sha3::<Fr, HyperKZG<Bn254, KeccakTranscript>, KeccakTranscript>(),
-            BenchType::Sha2Chain => {
-                sha2chain::<Fr, HyperKZG<Bn254, KeccakTranscript>, KeccakTranscript>()
-            }
-            BenchType::Fibonacci => {
-                fibonacci::<Fr, HyperKZG<Bn254, KeccakTranscript>, KeccakTranscript>()
-            }
-            _ => panic!("BenchType does not have a mapping"),
-        },
-        _ => panic!("PCS Type does not have a mapping"),
+    let kind = pcs_type.into();
+    match bench_type {
+        BenchType::Sha2 => sha2(kind),
+        BenchType::Sha3 => sha3(kind),
+        BenchType::Sha2Chain => sha2chain(kind),
+        BenchType::Fibonacci => fibonacci(kind),
+        _ => panic!("BenchType does not have a mapping"),
     }
 }
 
-fn fibonacci<F, PCS, ProofTranscript>() -> Vec<(tracing::Span, Box<dyn FnOnce()>)>
-where
-    F: JoltField,
-    PCS: CommitmentScheme<ProofTranscript, Field = F>,
-    ProofTranscript: Transcript,
-{
-    prove_example::<u32, PCS, F, ProofTranscript>("fibonacci-guest", &9u32)
+fn fibonacci(kind: PCSKind) -> Vec<(tracing::Span, Box<dyn FnOnce()>)> {
+    prove_example::<u32>("fibonacci-guest", &9u32, kind)
 }
 
-fn sha2<F, PCS, ProofTranscript>() -> Vec<(tracing::Span, Box<dyn FnOnce()>)>
-where
-    F: JoltField,
-    PCS: CommitmentScheme<ProofTranscript, Field = F>,
-    ProofTranscript: Transcript,
-{
-    prove_example::<Vec<u8>, PCS, F, ProofTranscript>("sha2-guest", &vec![5u8; 2048])
+fn sha2(kind: PCSKind) -> Vec<(tracing::Span, Box<dyn FnOnce()>)> {
+    prove_example::<Vec<u8>>("sha2-guest", &vec![5u8; 2048], kind)
 }
 
-fn sha3<F, PCS, ProofTranscript>() -> Vec<(tracing::Span, Box<dyn FnOnce()>)>
-where
-    F: JoltField,
-    PCS: CommitmentScheme<ProofTranscript, Field = F>,
-    ProofTranscript: Transcript,
-{
-    prove_example::<Vec<u8>, PCS, F, ProofTranscript>("sha3-guest", &vec![5u8; 2048])
+fn sha3(kind: PCSKind) -> Vec<(tracing::Span, Box<dyn FnOnce()>)> {
+    prove_example::<Vec<u8>>("sha3-guest", &vec![5u8; 2048], kind)
+}
+
+/// Preprocesses the program with a setup of the scheme `kind`.
+fn preprocess(
+    bytecode: Vec<ELFInstruction>,
+    memory_layout: MemoryLayout,
+    memory_init: Vec<(u64, u8)>,
+    kind: PCSKind,
+) -> JoltPreprocessing<C, Fr, PCS, KeccakTranscript> {
+    let shapes = <RV32IJoltVM as Jolt<Fr, PCS, C, M, KeccakTranscript>>::commitment_shapes(
+        MAX_BYTECODE_SIZE,
+        MAX_MEMORY_ADDRESS,
+        MAX_TRACE_LENGTH,
+    );
+    RV32IJoltVM::preprocess_with_setup(
+        bytecode,
+        memory_layout,
+        memory_init,
+        MAX_BYTECODE_SIZE,
+        PCS::setup_for(kind, &shapes),
+    )
 }
 
 #[allow(dead_code)]
@@ -113,39 +101,29 @@ fn serialize_and_print_size(name: &str, item: &impl ark_serialize::CanonicalSeri
     println!("{:<30} : {:.3} MB", name, file_size_mb);
 }
 
-fn prove_example<T: Serialize, PCS, F, ProofTranscript>(
+fn prove_example<T: Serialize>(
     example_name: &str,
     input: &T,
-) -> Vec<(tracing::Span, Box<dyn FnOnce()>)>
-where
-    F: JoltField,
-    PCS: CommitmentScheme<ProofTranscript, Field = F>,
-    ProofTranscript: Transcript,
-{
+    kind: PCSKind,
+) -> Vec<(tracing::Span, Box<dyn FnOnce()>)> {
     let mut tasks = Vec::new();
     let mut program = host::Program::new(example_name);
     program.set_input(input);
 
     let task = move || {
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
-
-        let preprocessing: crate::jolt::vm::JoltPreprocessing<C, F, PCS, ProofTranscript> =
-            RV32IJoltVM::preprocess(
-                bytecode.clone(),
-                io_device.memory_layout.clone(),
-                memory_init,
-                1 << 20,
-                1 << 20,
-                1 << 22,
-            );
+        let (io_device, trace) = program.trace().unwrap();
+
+        let preprocessing =
+            preprocess(bytecode, io_device.memory_layout.clone(), memory_init, kind);
 
         let (jolt_proof, jolt_commitments, _) =
-            <RV32IJoltVM as Jolt<_, PCS, C, M, ProofTranscript>>::prove(
+            <RV32IJoltVM as Jolt<_, PCS, C, M, KeccakTranscript>>::prove(
                 io_device,
                 trace,
                 preprocessing.clone(),
-            );
+            )
+            .expect("proving failed");
 
         println!("Proof sizing:");
         serialize_and_print_size("jolt_commitments", &jolt_commitments);
@@ -178,12 +156,7 @@ where
     tasks
 }
 
-fn sha2chain<F, PCS, ProofTranscript>() -> Vec<(tracing::Span, Box<dyn FnOnce()>)>
-where
-    F: JoltField,
-    PCS: CommitmentScheme<ProofTranscript, Field = F>,
-    ProofTranscript: Transcript,
-{
+fn sha2chain(kind: PCSKind) -> Vec<(tracing::Span, Box<dyn FnOnce()>)> {
     let mut tasks = Vec::new();
     let mut program = host::Program::new("sha2-chain-guest");
     program.set_input(&[5u8; 32]);
@@ -191,24 +164,18 @@ where
 
     let task = move || {
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
-
-        let preprocessing: crate::jolt::vm::JoltPreprocessing<C, F, PCS, ProofTranscript> =
-            RV32IJoltVM::preprocess(
-                bytecode.clone(),
-                io_device.memory_layout.clone(),
-                memory_init,
-                1 << 20,
-                1 << 20,
-                1 << 22,
-            );
+        let (io_device, trace) = program.trace().unwrap();
+
+        let preprocessing =
+            preprocess(bytecode, io_device.memory_layout.clone(), memory_init, kind);
 
         let (jolt_proof, jolt_commitments, _) =
-            <RV32IJoltVM as Jolt<_, PCS, C, M, ProofTranscript>>::prove(
+            <RV32IJoltVM as Jolt<_, PCS, C, M, KeccakTranscript>>::prove(
                 io_device,
                 trace,
                 preprocessing.clone(),
-            );
+            )
+            .expect("proving failed");
         let verification_result =
             RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments, None);
         assert!(
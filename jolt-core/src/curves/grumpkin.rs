@@ -0,0 +1,66 @@
+//! The Grumpkin curve, `y^2 = x^3 - 17` over the scalar field of BN254, whose group order is
+//! the base field modulus of BN254. BN254 and Grumpkin thus form a 2-cycle of curves
+//! (see [`super::Bn254GrumpkinCycle`]).
+
+use ark_bn254::{Fq, Fr};
+use ark_ec::{
+    models::CurveConfig,
+    short_weierstrass::{self as sw, SWCurveConfig},
+};
+use ark_ff::{MontFp, Zero};
+
+pub type Affine = sw::Affine<GrumpkinConfig>;
+pub type Projective = sw::Projective<GrumpkinConfig>;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct GrumpkinConfig;
+
+impl CurveConfig for GrumpkinConfig {
+    type BaseField = Fr;
+    type ScalarField = Fq;
+
+    /// COFACTOR = 1
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// COFACTOR_INV = COFACTOR^{-1} mod r = 1
+    const COFACTOR_INV: Fq = MontFp!("1");
+}
+
+impl SWCurveConfig for GrumpkinConfig {
+    /// COEFF_A = 0
+    const COEFF_A: Fr = MontFp!("0");
+
+    /// COEFF_B = -17
+    const COEFF_B: Fr = MontFp!("-17");
+
+    /// AFFINE_GENERATOR_COEFFS = (G1_GENERATOR_X, G1_GENERATOR_Y)
+    const GENERATOR: Affine = Affine::new_unchecked(G_GENERATOR_X, G_GENERATOR_Y);
+
+    #[inline(always)]
+    fn mul_by_a(_: Self::BaseField) -> Self::BaseField {
+        Self::BaseField::zero()
+    }
+}
+
+/// G_GENERATOR_X = 1
+pub const G_GENERATOR_X: Fr = MontFp!("1");
+
+/// G_GENERATOR_Y = sqrt(-16)
+pub const G_GENERATOR_Y: Fr =
+    MontFp!("17631683881184975370165255887551781615748388533673675138860");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineRepr;
+    use ark_ff::PrimeField;
+
+    #[test]
+    fn generator() {
+        let generator = Affine::generator();
+        assert!(generator.is_on_curve());
+        assert!(generator.is_in_correct_subgroup_assuming_on_curve());
+        // The group order is the BN254 base field modulus
+        assert!(generator.mul_bigint(Fq::MODULUS).is_zero());
+    }
+}
@@ -0,0 +1,87 @@
+//! Curve cycles, for recursive and aggregating configurations in which each curve's proof
+//! system commits to (and verifies) the other's group elements natively.
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::Zero;
+
+use crate::field::JoltField;
+use crate::poly::commitment::hyperkzg::HyperKZG;
+use crate::poly::commitment::hyrax::HyraxScheme;
+
+pub mod grumpkin;
+
+/// A 2-cycle of elliptic curves: the scalar field of each curve is the base field of the
+/// other. Group elements of one curve are therefore vectors of scalars of the other curve,
+/// and can be committed to or operated on without non-native field arithmetic.
+pub trait CurveCycle {
+    type PrimaryScalar: JoltField + PrimeField;
+    type SecondaryScalar: JoltField + PrimeField;
+    type Primary: CurveGroup<ScalarField = Self::PrimaryScalar, BaseField = Self::SecondaryScalar>;
+    type Secondary: CurveGroup<ScalarField = Self::SecondaryScalar, BaseField = Self::PrimaryScalar>;
+}
+
+/// BN254 (primary) and Grumpkin (secondary).
+pub struct Bn254GrumpkinCycle;
+
+impl CurveCycle for Bn254GrumpkinCycle {
+    type PrimaryScalar = ark_bn254::Fr;
+    type SecondaryScalar = ark_bn254::Fq;
+    type Primary = ark_bn254::G1Projective;
+    type Secondary = grumpkin::Projective;
+}
+
+/// The commitment scheme used on BN254 in the BN254/Grumpkin cycle.
+pub type Bn254PCS<ProofTranscript> = HyperKZG<ark_bn254::Bn254, ProofTranscript>;
+/// The commitment scheme used on Grumpkin in the BN254/Grumpkin cycle; Grumpkin is not
+/// pairing-friendly, so this is a transparent scheme.
+pub type GrumpkinPCS<ProofTranscript> = HyraxScheme<grumpkin::Projective, ProofTranscript>;
+
+/// Returns the affine coordinates of `point`, which are scalars of the other curve of the
+/// cycle. The point at infinity is mapped to `(0, 0)`, as in `Transcript::append_point`.
+pub fn point_to_scalars<G: CurveGroup>(point: &G) -> [G::BaseField; 2] {
+    match point.into_affine().xy() {
+        Some((x, y)) => [*x, *y],
+        None => [G::BaseField::zero(), G::BaseField::zero()],
+    }
+}
+
+/// Embeds a scalar of one curve of the cycle into the scalar field of the other, by
+/// reducing its canonical integer representation. This is injective if the source field
+/// is the smaller of the two (e.g. from BN254's scalar field into Grumpkin's).
+pub fn embed_scalar<F: PrimeField, T: PrimeField>(scalar: &F) -> T {
+    T::from_le_bytes_mod_order(&scalar.into_bigint().to_bytes_le())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::transcript::{KeccakTranscript, Transcript};
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn bn254_grumpkin_cycle() {
+        type Cycle = Bn254GrumpkinCycle;
+        let mut rng = test_rng();
+
+        // BN254 points are pairs of Grumpkin scalars and vice versa
+        let p = <Cycle as CurveCycle>::Primary::rand(&mut rng);
+        let [x, y] = point_to_scalars(&p);
+        let p_affine = p.into_affine();
+        assert_eq!((&x, &y), p_affine.xy().unwrap());
+        let q = <Cycle as CurveCycle>::Secondary::rand(&mut rng);
+        let [x, y] = point_to_scalars(&q);
+        assert_eq!((&x, &y), q.into_affine().xy().unwrap());
+
+        // BN254's scalar field embeds injectively into Grumpkin's
+        let s = <Cycle as CurveCycle>::PrimaryScalar::rand(&mut rng);
+        let embedded: <Cycle as CurveCycle>::SecondaryScalar = embed_scalar(&s);
+        assert_eq!(embed_scalar::<_, ark_bn254::Fr>(&embedded), s);
+
+        // Transcripts handle Grumpkin points and scalars like BN254 ones
+        let mut transcript = KeccakTranscript::new(b"cycle");
+        transcript.append_point(&q);
+        transcript.append_scalar(&embedded);
+        let _: <Cycle as CurveCycle>::SecondaryScalar = transcript.challenge_scalar();
+    }
+}
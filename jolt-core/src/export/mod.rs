@@ -0,0 +1,2 @@
+//! Artifacts derived from Jolt's proofs and keys for use outside of Rust.
+pub mod solidity;
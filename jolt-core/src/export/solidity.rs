@@ -0,0 +1,335 @@
+//! Generates a Solidity contract verifying Zeromorph openings against a fixed verifier key.
+//!
+//! The contract mirrors [`Zeromorph::verify`]: it replays the Keccak Fiat-Shamir transcript
+//! with `FiatShamirTranscript.sol`, computes the scalars of `eval_and_quotient_scalars`, and
+//! performs the pairing check with the BN254 precompiles. The verifier key is baked in as
+//! constants. The generated file imports the libraries of `jolt-evm-verifier/src/subprotocols`
+//! and is meant to be written to `jolt-evm-verifier/src`. Proofs are passed as the
+//! [`ZeromorphProofSol`](crate::utils::sol_types::ZeromorphProofSol) calldata struct.
+//!
+//! [`Zeromorph::verify`]: crate::poly::commitment::zeromorph::Zeromorph::verify
+use std::path::Path;
+
+use alloy_primitives::U256;
+use ark_bn254::{Bn254, Fq, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::poly::commitment::zeromorph::ZeromorphVerifierKey;
+use crate::utils::sol_types::g1_into_uint256;
+
+const ZEROMORPH_VERIFIER_TEMPLATE: &str = r#"// SPDX-License-Identifier: MIT
+// Generated by jolt-core's `export::solidity` from a Zeromorph verifier key, do not edit.
+
+pragma solidity >=0.8.21;
+
+import {Transcript, FiatShamirTranscript} from "./subprotocols/FiatShamirTranscript.sol";
+import {MODULUS, Fr, FrLib} from "./subprotocols/Fr.sol";
+
+struct ZeromorphProof {
+    uint256 pi_x;
+    uint256 pi_y;
+    uint256 q_hat_com_x;
+    uint256 q_hat_com_y;
+    uint256 q_hat_degree_check_com_x;
+    uint256 q_hat_degree_check_com_y;
+    uint256[] q_k_com; // G1 points represented pairwise
+}
+
+// Verifies Zeromorph openings of commitments to multilinear polynomials in NUM_VARS variables,
+// as in https://github.com/a16z/jolt/blob/main/jolt-core/src/poly/commitment/zeromorph.rs
+contract ZeromorphVerifier {
+    using FiatShamirTranscript for Transcript;
+    using FrLib for Fr;
+
+    uint256 constant NUM_VARS = __NUM_VARS__;
+
+    uint256 constant VK_G1_X = __G1_X__;
+    uint256 constant VK_G1_Y = __G1_Y__;
+    // Note - These are elements of an elliptic curve over the extension field and so each has
+    //        a c0 and c1 representing c0 + c1 X where X is the extending element
+    // -[X^(N_max - 2^NUM_VARS)]_2
+    uint256 constant VK_NEG_SHIFT_G2_X_C0 = __NEG_SHIFT_G2_X_C0__;
+    uint256 constant VK_NEG_SHIFT_G2_X_C1 = __NEG_SHIFT_G2_X_C1__;
+    uint256 constant VK_NEG_SHIFT_G2_Y_C0 = __NEG_SHIFT_G2_Y_C0__;
+    uint256 constant VK_NEG_SHIFT_G2_Y_C1 = __NEG_SHIFT_G2_Y_C1__;
+    // [X^(N_max - 2^NUM_VARS)]_2, against which the degree of q_hat is checked
+    uint256 constant VK_DEGREE_SHIFT_G2_X_C0 = __DEGREE_SHIFT_G2_X_C0__;
+    uint256 constant VK_DEGREE_SHIFT_G2_X_C1 = __DEGREE_SHIFT_G2_X_C1__;
+    uint256 constant VK_DEGREE_SHIFT_G2_Y_C0 = __DEGREE_SHIFT_G2_Y_C0__;
+    uint256 constant VK_DEGREE_SHIFT_G2_Y_C1 = __DEGREE_SHIFT_G2_Y_C1__;
+    // [tau]_2
+    uint256 constant VK_BETA_G2_X_C0 = __BETA_G2_X_C0__;
+    uint256 constant VK_BETA_G2_X_C1 = __BETA_G2_X_C1__;
+    uint256 constant VK_BETA_G2_Y_C0 = __BETA_G2_Y_C0__;
+    uint256 constant VK_BETA_G2_Y_C1 = __BETA_G2_Y_C1__;
+    // [1]_2
+    uint256 constant VK_G2_X_C0 = __G2_X_C0__;
+    uint256 constant VK_G2_X_C1 = __G2_X_C1__;
+    uint256 constant VK_G2_Y_C0 = __G2_Y_C0__;
+    uint256 constant VK_G2_Y_C1 = __G2_Y_C1__;
+
+    /// Verifies the opening of the commitment C to eval at point
+    /// @param c_x The x coordinate of the commitment
+    /// @param c_y The y coordinate of the commitment
+    /// @param point The point which is opened
+    /// @param eval The claimed evaluation of the polynomial at point
+    /// @param proof The proof of the opening
+    /// @param transcript The fiat shamir transcript we are sourcing deterministic randoms from
+    function verify(
+        uint256 c_x,
+        uint256 c_y,
+        uint256[] memory point,
+        uint256 eval,
+        ZeromorphProof memory proof,
+        Transcript memory transcript
+    ) public view returns (bool) {
+        uint256 n = point.length;
+        require(n == NUM_VARS && proof.q_k_com.length == 2 * n, "bad length");
+        require(eval < MODULUS, "eval out of range");
+
+        transcript.append_bytes32("Zeromorph");
+        for (uint256 i = 0; i < proof.q_k_com.length; i += 2) {
+            transcript.append_point(proof.q_k_com[i], proof.q_k_com[i + 1]);
+        }
+        Fr y = Fr.wrap(transcript.challenge_scalar(MODULUS));
+        transcript.append_point(proof.q_hat_com_x, proof.q_hat_com_y);
+        Fr x = Fr.wrap(transcript.challenge_scalar(MODULUS));
+        Fr z = Fr.wrap(transcript.challenge_scalar(MODULUS));
+
+        // squares[k] = x^(2^k) and vs[k] = (x^(2^n) - 1) / (x^(2^k) - 1)
+        Fr[] memory squares = new Fr[](n + 1);
+        squares[0] = x;
+        for (uint256 k = 1; k <= n; k++) {
+            squares[k] = squares[k - 1] * squares[k - 1];
+        }
+        Fr[] memory vs = new Fr[](n + 1);
+        Fr v_numer = squares[n] - Fr.wrap(1);
+        for (uint256 k = 0; k <= n; k++) {
+            vs[k] = v_numer.div(squares[k] - Fr.wrap(1));
+        }
+        Fr[] memory y_powers = new Fr[](n);
+        if (n > 0) {
+            y_powers[0] = Fr.wrap(1);
+        }
+        for (uint256 k = 1; k < n; k++) {
+            y_powers[k] = y_powers[k - 1] * y;
+        }
+
+        // C_{zeta,Z} = C_q_hat + z * C + eval_scalar * eval * [1]_1 + sum_k q_scalar_k * C_q_k
+        (uint256 acc_x, uint256 acc_y) = ec_scalar_mul(c_x, c_y, z.unwrap());
+        (acc_x, acc_y) = ec_add(acc_x, acc_y, proof.q_hat_com_x, proof.q_hat_com_y);
+        Fr eval_scalar = Fr.wrap(0) - vs[0] * z;
+        (uint256 temp_x, uint256 temp_y) = ec_scalar_mul(VK_G1_X, VK_G1_Y, (eval_scalar * Fr.wrap(eval)).unwrap());
+        (acc_x, acc_y) = ec_add(acc_x, acc_y, temp_x, temp_y);
+        // offset = x^(2^n - 2^k), accumulated from k = n - 1 down
+        Fr offset = Fr.wrap(1);
+        for (uint256 i = 0; i < n; i++) {
+            uint256 k = n - 1 - i;
+            offset = offset * squares[k];
+            Fr u = FrLib.from(point[n - 1 - k]);
+            Fr q_scalar = Fr.wrap(0) - y_powers[k] * offset - z * (squares[k] * vs[k + 1] - u * vs[k]);
+            (temp_x, temp_y) = ec_scalar_mul(proof.q_k_com[2 * k], proof.q_k_com[2 * k + 1], q_scalar.unwrap());
+            (acc_x, acc_y) = ec_add(acc_x, acc_y, temp_x, temp_y);
+        }
+
+        // e(C_{zeta,Z}, -[X^(N_max - 2^n)]_2) * e(pi, [tau]_2) * e(-x * pi, [1]_2) == 1
+        (temp_x, temp_y) = ec_scalar_mul(proof.pi_x, proof.pi_y, MODULUS - x.unwrap());
+        if (!pairing(acc_x, acc_y, proof.pi_x, proof.pi_y, temp_x, temp_y)) {
+            return false;
+        }
+
+        // e(C_q, [X^(N_max - 2^n)]_2) * e(-[X^(N_max - 2^n) * q_hat]_1, [1]_2) == 1
+        (temp_x, temp_y) =
+            ec_scalar_mul(proof.q_hat_degree_check_com_x, proof.q_hat_degree_check_com_y, MODULUS - 1);
+        return degree_check_pairing(proof.q_hat_com_x, proof.q_hat_com_y, temp_x, temp_y);
+    }
+
+    /// Calculates nP where P is on the G1 curve of our ethereum precompile pairing
+    /// Requires that the types be properly checked before calling
+    /// @param p_x The x of the point Q
+    /// @param p_y The y of the point Q
+    /// @param n The scalar
+    function ec_scalar_mul(uint256 p_x, uint256 p_y, uint256 n) internal view returns (uint256 x_new, uint256 y_new) {
+        bool success;
+        assembly ("memory-safe") {
+            let prev_frm := mload(0x40)
+            mstore(0, p_x)
+            mstore(0x20, p_y)
+            mstore(0x40, n)
+            success := staticcall(gas(), 7, 0, 96, 0, 64)
+            mstore(0x40, prev_frm)
+            x_new := mload(0)
+            y_new := mload(0x20)
+        }
+        require(success, "failing ec mul");
+    }
+
+    /// Calculates P + Q where P and Q are on the G1 curve of our ethereum precompile pairing
+    /// Requires that the types be properly checked before calling
+    /// @param p_x The x of the point P
+    /// @param p_y The y of the point P
+    /// @param q_x The x of the point Q
+    /// @param q_y The y of the point Q
+    function ec_add(uint256 p_x, uint256 p_y, uint256 q_x, uint256 q_y)
+        internal
+        view
+        returns (uint256 x_new, uint256 y_new)
+    {
+        bool success;
+        assembly ("memory-safe") {
+            let prev_frm := mload(0x40)
+            mstore(0, p_x)
+            mstore(0x20, p_y)
+            mstore(0x40, q_x)
+            mstore(0x60, q_y)
+            success := staticcall(gas(), 6, 0, 128, 0, 64)
+            mstore(0x40, prev_frm)
+            mstore(0x60, 0)
+            x_new := mload(0)
+            y_new := mload(0x20)
+        }
+        require(success, "failing ec add");
+    }
+
+    /// Checks that e(A, -VK_NEG_SHIFT_G2) e(B, VK_BETA_G2) e(C, VK_G2) = 1
+    function pairing(uint256 a_x, uint256 a_y, uint256 b_x, uint256 b_y, uint256 c_x, uint256 c_y)
+        internal
+        view
+        returns (bool valid)
+    {
+        uint256[18] memory input = [
+            a_x,
+            a_y,
+            VK_NEG_SHIFT_G2_X_C1,
+            VK_NEG_SHIFT_G2_X_C0,
+            VK_NEG_SHIFT_G2_Y_C1,
+            VK_NEG_SHIFT_G2_Y_C0,
+            b_x,
+            b_y,
+            VK_BETA_G2_X_C1,
+            VK_BETA_G2_X_C0,
+            VK_BETA_G2_Y_C1,
+            VK_BETA_G2_Y_C0,
+            c_x,
+            c_y,
+            VK_G2_X_C1,
+            VK_G2_X_C0,
+            VK_G2_Y_C1,
+            VK_G2_Y_C0
+        ];
+        bool success;
+        assembly ("memory-safe") {
+            success := staticcall(gas(), 8, input, 576, 0, 32)
+            valid := mload(0)
+        }
+        require(success, "failing pairing");
+    }
+
+    /// Checks that e(A, VK_DEGREE_SHIFT_G2) e(B, VK_G2) = 1
+    function degree_check_pairing(uint256 a_x, uint256 a_y, uint256 b_x, uint256 b_y)
+        internal
+        view
+        returns (bool valid)
+    {
+        uint256[12] memory input = [
+            a_x,
+            a_y,
+            VK_DEGREE_SHIFT_G2_X_C1,
+            VK_DEGREE_SHIFT_G2_X_C0,
+            VK_DEGREE_SHIFT_G2_Y_C1,
+            VK_DEGREE_SHIFT_G2_Y_C0,
+            b_x,
+            b_y,
+            VK_G2_X_C1,
+            VK_G2_X_C0,
+            VK_G2_Y_C1,
+            VK_G2_Y_C0
+        ];
+        bool success;
+        assembly ("memory-safe") {
+            success := staticcall(gas(), 8, input, 384, 0, 32)
+            valid := mload(0)
+        }
+        require(success, "failing pairing");
+    }
+}
+"#;
+
+/// The Solidity source of a contract verifying openings of `num_vars`-variate polynomials
+/// against `vk`, which must have been trimmed to `2^num_vars` coefficients.
+pub fn zeromorph_verifier_contract(vk: &ZeromorphVerifierKey<Bn254>, num_vars: usize) -> String {
+    let (g1_x, g1_y) = g1_into_uint256(&vk.kzg_vk.g1);
+    let mut replacements = vec![
+        ("__NUM_VARS__".to_string(), num_vars.to_string()),
+        ("__G1_X__".to_string(), g1_x.to_string()),
+        ("__G1_Y__".to_string(), g1_y.to_string()),
+    ];
+    let degree_shift = *vk
+        .tau_N_sub_2_m
+        .get(num_vars)
+        .expect("the key is too small for num_vars");
+    for (name, point) in [
+        ("NEG_SHIFT_G2", -vk.tau_N_max_sub_2_N),
+        ("DEGREE_SHIFT_G2", degree_shift),
+        ("BETA_G2", vk.kzg_vk.beta_g2),
+        ("G2", vk.kzg_vk.g2),
+    ] {
+        replacements.extend(
+            g2_coordinates(&point)
+                .into_iter()
+                .map(|(coordinate, value)| (format!("__{name}_{coordinate}__"), value.to_string())),
+        );
+    }
+
+    replacements.into_iter().fold(
+        ZEROMORPH_VERIFIER_TEMPLATE.to_string(),
+        |source, (placeholder, value)| source.replace(&placeholder, &value),
+    )
+}
+
+/// Writes [`zeromorph_verifier_contract`] to `path`.
+pub fn write_zeromorph_verifier_contract(
+    vk: &ZeromorphVerifierKey<Bn254>,
+    num_vars: usize,
+    path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    std::fs::write(path, zeromorph_verifier_contract(vk, num_vars))
+}
+
+fn g2_coordinates(point: &G2Affine) -> [(&'static str, U256); 4] {
+    [
+        ("X_C0", fq_into_uint256(&point.x.c0)),
+        ("X_C1", fq_into_uint256(&point.x.c1)),
+        ("Y_C0", fq_into_uint256(&point.y.c0)),
+        ("Y_C1", fq_into_uint256(&point.y.c1)),
+    ]
+}
+
+fn fq_into_uint256(value: &Fq) -> U256 {
+    U256::from_be_slice(&value.into_bigint().to_bytes_be())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::commitment::zeromorph::ZeromorphSRS;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn zeromorph_contract() {
+        const NUM_VARS: usize = 4;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << NUM_VARS);
+        let (_, vk) = srs.trim(1 << NUM_VARS);
+
+        let source = zeromorph_verifier_contract(&vk, NUM_VARS);
+        assert!(!source.contains("__"));
+        assert!(source.contains("uint256 constant NUM_VARS = 4;"));
+        assert!(source.contains(&format!(
+            "VK_BETA_G2_Y_C1 = {};",
+            fq_into_uint256(&vk.kzg_vk.beta_g2.y.c1)
+        )));
+        assert_eq!(source.matches('{').count(), source.matches('}').count());
+    }
+}
@@ -1,6 +1,6 @@
-use std::fmt::{Debug, Display};
-use std::hash::Hash;
-use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::fmt::{Debug, Display};
+use core::hash::Hash;
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{One, Zero};
@@ -44,6 +44,17 @@ pub trait JoltField:
 
     fn from_u64(n: u64) -> Option<Self>;
     fn from_i64(val: i64) -> Self;
+    fn from_i128(val: i128) -> Self {
+        let magnitude = val.unsigned_abs();
+        let two_pow_64 = Self::from_u64(u64::MAX).unwrap() + Self::one();
+        let result = Self::from_u64((magnitude >> 64) as u64).unwrap() * two_pow_64
+            + Self::from_u64(magnitude as u64).unwrap();
+        if val < 0 {
+            -result
+        } else {
+            result
+        }
+    }
     fn square(&self) -> Self;
     fn from_bytes(bytes: &[u8]) -> Self;
     fn inverse(&self) -> Option<Self>;
@@ -93,4 +104,7 @@ where
 }
 
 pub mod ark;
+#[cfg(feature = "binius")]
 pub mod binius;
+#[cfg(feature = "std")]
+pub mod small;
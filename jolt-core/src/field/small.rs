@@ -0,0 +1,270 @@
+//! Small prime fields, for provers whose commitments are hash-based rather than pairing-based.
+//!
+//! Goldilocks (`2^64 - 2^32 + 1`) and BabyBear (`2^31 - 2^27 + 1`) elements fit in a machine
+//! word, but the fields are too small for sum-check challenges to be sound. The sum-checks and
+//! lookup arguments instead run over their extensions: `GoldilocksExt2` of degree 2 and
+//! `BabyBearExt4` of degree 4, with the witness still in the base field.
+use ark_ff::fields::{Fp2, Fp2Config, Fp4, Fp4Config, Fp64, MontBackend, MontConfig};
+use ark_ff::{Field, MontFp, PrimeField, UniformRand};
+
+use super::{FieldOps, JoltField};
+
+#[derive(MontConfig)]
+#[modulus = "18446744069414584321"]
+#[generator = "7"]
+pub struct GoldilocksConfig;
+pub type Goldilocks = Fp64<MontBackend<GoldilocksConfig, 1>>;
+
+#[derive(MontConfig)]
+#[modulus = "2013265921"]
+#[generator = "31"]
+pub struct BabyBearConfig;
+pub type BabyBear = Fp64<MontBackend<BabyBearConfig, 1>>;
+
+/// `Goldilocks[u] / (u^2 - 7)`
+pub struct GoldilocksExt2Config;
+pub type GoldilocksExt2 = Fp2<GoldilocksExt2Config>;
+
+impl Fp2Config for GoldilocksExt2Config {
+    type Fp = Goldilocks;
+    const NONRESIDUE: Goldilocks = MontFp!("7");
+    const FROBENIUS_COEFF_FP2_C1: &'static [Goldilocks] = &[
+        MontFp!("1"),
+        // 7^((p - 1) / 2)
+        MontFp!("18446744069414584320"),
+    ];
+}
+
+/// `BabyBear[u] / (u^2 - 11)`, the intermediate field of the quartic extension
+pub struct BabyBearExt2Config;
+pub type BabyBearExt2 = Fp2<BabyBearExt2Config>;
+
+impl Fp2Config for BabyBearExt2Config {
+    type Fp = BabyBear;
+    const NONRESIDUE: BabyBear = MontFp!("11");
+    const FROBENIUS_COEFF_FP2_C1: &'static [BabyBear] = &[
+        MontFp!("1"),
+        // 11^((p - 1) / 2)
+        MontFp!("2013265920"),
+    ];
+}
+
+/// `BabyBearExt2[v] / (v^2 - u)`, i.e. `BabyBear[v] / (v^4 - 11)`
+pub struct BabyBearExt4Config;
+pub type BabyBearExt4 = Fp4<BabyBearExt4Config>;
+
+impl Fp4Config for BabyBearExt4Config {
+    type Fp2Config = BabyBearExt2Config;
+    const NONRESIDUE: BabyBearExt2 = BabyBearExt2::new(MontFp!("0"), MontFp!("1"));
+    // 11^((p^i - 1) / 4) for i = 0..4
+    const FROBENIUS_COEFF_FP4_C1: &'static [BabyBear] = &[
+        MontFp!("1"),
+        MontFp!("1728404513"),
+        MontFp!("2013265920"),
+        MontFp!("284861408"),
+    ];
+}
+
+/// Implements `JoltField` for a word-sized prime field. Unlike the large fields, integers are
+/// reduced into the field rather than rejected when they exceed the modulus.
+macro_rules! impl_small_prime_field {
+    ($field:ty) => {
+        impl FieldOps for $field {}
+        impl<'a, 'b> FieldOps<&'b $field, $field> for &'a $field {}
+        impl<'b> FieldOps<&'b $field, $field> for $field {}
+
+        impl JoltField for $field {
+            const NUM_BYTES: usize = 16;
+
+            fn random<R: rand_core::RngCore>(rng: &mut R) -> Self {
+                <Self as UniformRand>::rand(rng)
+            }
+
+            fn from_u64(n: u64) -> Option<Self> {
+                Some(<Self as From<u64>>::from(n))
+            }
+
+            fn from_i64(val: i64) -> Self {
+                <Self as From<i64>>::from(val)
+            }
+
+            fn from_i128(val: i128) -> Self {
+                <Self as From<i128>>::from(val)
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                Some(self.into_bigint().0[0])
+            }
+
+            fn square(&self) -> Self {
+                <Self as Field>::square(self)
+            }
+
+            fn inverse(&self) -> Option<Self> {
+                <Self as Field>::inverse(self)
+            }
+
+            fn from_bytes(bytes: &[u8]) -> Self {
+                assert_eq!(bytes.len(), Self::NUM_BYTES);
+                <$field>::from_le_bytes_mod_order(bytes)
+            }
+        }
+    };
+}
+
+/// Implements `JoltField` for an extension of a small prime field of degree `$degree`.
+/// Integers are embedded in the base field, and `from_bytes` reduces 16 bytes per coefficient.
+macro_rules! impl_small_extension_field {
+    ($field:ty, $base:ty, $degree:expr) => {
+        impl FieldOps for $field {}
+        impl<'a, 'b> FieldOps<&'b $field, $field> for &'a $field {}
+        impl<'b> FieldOps<&'b $field, $field> for $field {}
+
+        impl JoltField for $field {
+            const NUM_BYTES: usize = $degree * <$base as JoltField>::NUM_BYTES;
+
+            fn random<R: rand_core::RngCore>(rng: &mut R) -> Self {
+                <Self as UniformRand>::rand(rng)
+            }
+
+            fn from_u64(n: u64) -> Option<Self> {
+                <$base as JoltField>::from_u64(n).map(Self::from_base_prime_field)
+            }
+
+            fn from_i64(val: i64) -> Self {
+                Self::from_base_prime_field(<$base as JoltField>::from_i64(val))
+            }
+
+            fn from_i128(val: i128) -> Self {
+                Self::from_base_prime_field(<$base as JoltField>::from_i128(val))
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                let mut coeffs = self.to_base_prime_field_elements();
+                let constant = coeffs.next().unwrap();
+                if coeffs.all(|coeff| coeff == <$base>::from(0u64)) {
+                    <$base as JoltField>::to_u64(&constant)
+                } else {
+                    None
+                }
+            }
+
+            fn square(&self) -> Self {
+                <Self as Field>::square(self)
+            }
+
+            fn inverse(&self) -> Option<Self> {
+                <Self as Field>::inverse(self)
+            }
+
+            fn from_bytes(bytes: &[u8]) -> Self {
+                assert_eq!(bytes.len(), Self::NUM_BYTES);
+                let coeffs: Vec<$base> = bytes
+                    .chunks(<$base as JoltField>::NUM_BYTES)
+                    .map(<$base as JoltField>::from_bytes)
+                    .collect();
+                Self::from_base_prime_field_elems(&coeffs).unwrap()
+            }
+        }
+    };
+}
+
+impl_small_prime_field!(Goldilocks);
+impl_small_prime_field!(BabyBear);
+impl_small_extension_field!(GoldilocksExt2, Goldilocks, 2);
+impl_small_extension_field!(BabyBearExt4, BabyBear, 4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::dense_mlpoly::DensePolynomial;
+    use crate::poly::unipoly::UniPoly;
+    use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+    use crate::utils::transcript::{KeccakTranscript, Transcript};
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    fn extension_sumcheck<F: JoltField>() {
+        const NUM_VARS: usize = 5;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let polys: Vec<DensePolynomial<F>> = (0..3)
+            .map(|_| DensePolynomial::random(NUM_VARS, &mut rng))
+            .collect();
+        let claim: F = (0..1 << NUM_VARS)
+            .map(|i| polys[0][i] * polys[1][i] * polys[2][i])
+            .sum();
+
+        let mut prover_transcript = KeccakTranscript::new(b"test");
+        let (proof, r_prover, final_evals) = SumcheckInstanceProof::prove_arbitrary(
+            &claim,
+            NUM_VARS,
+            &mut polys.clone(),
+            |vals: &[F]| vals[0] * vals[1] * vals[2],
+            3,
+            &mut prover_transcript,
+        );
+
+        let mut verifier_transcript = KeccakTranscript::new(b"test");
+        let (output_claim, r) = proof
+            .verify(claim, NUM_VARS, 3, &mut verifier_transcript)
+            .unwrap();
+        assert_eq!(r, r_prover);
+        assert_eq!(output_claim, final_evals.iter().product::<F>());
+        for (poly, eval) in polys.iter().zip(final_evals) {
+            assert_eq!(poly.evaluate(&r), eval);
+        }
+    }
+
+    #[test]
+    fn extension_fields() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let x = <BabyBearExt4 as JoltField>::random(&mut rng);
+        assert_eq!(
+            x * JoltField::inverse(&x).unwrap(),
+            BabyBearExt4::from(1u64)
+        );
+        // v^4 = 11
+        let v = BabyBearExt4::new(BabyBearExt2::from(0u64), BabyBearExt2::from(1u64));
+        assert_eq!(
+            Field::square(&Field::square(&v)),
+            <BabyBearExt4 as JoltField>::from_u64(11).unwrap()
+        );
+        assert_eq!(<BabyBearExt4 as JoltField>::from_i64(-1).to_u64(), None);
+        assert_eq!(
+            <BabyBearExt4 as JoltField>::from_u64(1 << 40)
+                .unwrap()
+                .to_u64(),
+            Some((1 << 40) % 2013265921)
+        );
+
+        let y = <GoldilocksExt2 as JoltField>::random(&mut rng);
+        assert_eq!(
+            y * JoltField::inverse(&y).unwrap(),
+            GoldilocksExt2::from(1u64)
+        );
+        let u = GoldilocksExt2::new(Goldilocks::from(0u64), Goldilocks::from(1u64));
+        assert_eq!(Field::square(&u), GoldilocksExt2::from(7u64));
+    }
+
+    #[test]
+    fn extension_field_sumcheck() {
+        extension_sumcheck::<GoldilocksExt2>();
+        extension_sumcheck::<BabyBearExt4>();
+    }
+
+    #[test]
+    fn goldilocks_fft() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let a = UniPoly::<Goldilocks>::from_coeff(
+            (0..100).map(|_| JoltField::random(&mut rng)).collect(),
+        );
+        let b = UniPoly::<Goldilocks>::from_coeff(
+            (0..80).map(|_| JoltField::random(&mut rng)).collect(),
+        );
+        let point = Goldilocks::from(3u64);
+        assert_eq!(
+            a.mul_fft(&b).evaluate(&point),
+            a.evaluate(&point) * b.evaluate(&point)
+        );
+    }
+}
@@ -3,22 +3,23 @@
 use core::str::FromStr;
 use std::{
     fs::{self, File},
-    io::{self, Read, Write},
-    path::PathBuf,
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
     process::Command,
 };
 
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use postcard;
 use rayon::prelude::*;
 use serde::Serialize;
+use sha3::{Digest, Sha3_256};
 
 use common::{
-    constants::{
-        DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
-    },
-    rv_trace::JoltDevice,
+    constants::RAM_START_ADDRESS,
+    rv_trace::{JoltDevice, MemoryConfig, MemoryLayout},
 };
-pub use tracer::ELFInstruction;
+use tracer::RVTraceRow;
+pub use tracer::{CycleLimitExceeded, ELFInstruction, Profile};
 
 use crate::{
     field::JoltField,
@@ -27,10 +28,17 @@ use crate::{
             div::DIVInstruction, divu::DIVUInstruction, lb::LBInstruction, lbu::LBUInstruction,
             lh::LHInstruction, lhu::LHUInstruction, mulh::MULHInstruction,
             mulhsu::MULHSUInstruction, rem::REMInstruction, remu::REMUInstruction,
-            sb::SBInstruction, sh::SHInstruction, VirtualInstructionSequence,
+            sb::SBInstruction, sh::SHInstruction, JoltInstructionSet, VirtualInstructionSequence,
+        },
+        trace::file::TraceWriter,
+        vm::{
+            bytecode::{eliminate_dead_code, parse_jump_target_hints},
+            rv32i_vm::{RV32IJoltVM, C, RV32I},
+            Jolt, JoltPreprocessing, JoltTraceStep,
         },
-        vm::{bytecode::BytecodeRow, rv32i_vm::RV32I, JoltTraceStep},
     },
+    poly::commitment::commitment_scheme::CommitmentScheme,
+    utils::transcript::Transcript,
 };
 
 use self::analyze::ProgramSummary;
@@ -46,11 +54,12 @@ pub struct Program {
     guest: String,
     func: Option<String>,
     input: Vec<u8>,
-    memory_size: u64,
-    stack_size: u64,
-    max_input_size: u64,
-    max_output_size: u64,
+    memory_config: MemoryConfig,
     std: bool,
+    eliminate_dead_code: bool,
+    jump_target_hints: Option<PathBuf>,
+    strip_symbols: bool,
+    max_cycles: Option<usize>,
     pub elf: Option<PathBuf>,
 }
 
@@ -60,11 +69,12 @@ impl Program {
             guest: guest.to_string(),
             func: None,
             input: Vec::new(),
-            memory_size: DEFAULT_MEMORY_SIZE,
-            stack_size: DEFAULT_STACK_SIZE,
-            max_input_size: DEFAULT_MAX_INPUT_SIZE,
-            max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+            memory_config: MemoryConfig::default(),
             std: false,
+            eliminate_dead_code: false,
+            jump_target_hints: None,
+            strip_symbols: true,
+            max_cycles: None,
             elf: None,
         }
     }
@@ -82,20 +92,48 @@ impl Program {
         self.input.append(&mut serialized);
     }
 
+    /// Appends input that is already serialized, e.g. by a host not written in Rust.
+    pub fn set_input_bytes(&mut self, bytes: &[u8]) {
+        self.input.extend_from_slice(bytes);
+    }
+
     pub fn set_memory_size(&mut self, len: u64) {
-        self.memory_size = len;
+        self.memory_config.ram_size = len;
     }
 
     pub fn set_stack_size(&mut self, len: u64) {
-        self.stack_size = len;
+        self.memory_config.stack_size = len;
     }
 
     pub fn set_max_input_size(&mut self, size: u64) {
-        self.max_input_size = size;
+        self.memory_config.max_input_size = size;
     }
 
     pub fn set_max_output_size(&mut self, size: u64) {
-        self.max_output_size = size;
+        self.memory_config.max_output_size = size;
+    }
+
+    /// The guest's address space, as the tracer and the memory checker see it.
+    pub fn memory_layout(&self) -> MemoryLayout {
+        MemoryLayout::from_config(&self.memory_config)
+    }
+
+    /// If enabled, `decode` strips instructions unreachable from the entrypoint
+    /// from the bytecode. See `bytecode::eliminate_dead_code`.
+    pub fn set_eliminate_dead_code(&mut self, eliminate_dead_code: bool) {
+        self.eliminate_dead_code = eliminate_dead_code;
+    }
+
+    /// Sets the path to a file listing additional (indirect) jump targets to be
+    /// treated as reachable by dead code elimination, one address per line.
+    pub fn set_jump_target_hints(&mut self, path: impl Into<PathBuf>) {
+        self.jump_target_hints = Some(path.into());
+    }
+
+    /// Stops tracing a guest that has not terminated after `max_cycles` RISC-V instructions,
+    /// which otherwise runs forever.
+    pub fn set_max_cycles(&mut self, max_cycles: usize) {
+        self.max_cycles = Some(max_cycles);
     }
 
     #[tracing::instrument(skip_all, name = "Program::build")]
@@ -108,6 +146,11 @@ impl Program {
 
             self.save_linker();
 
+            let strip = if self.strip_symbols {
+                "strip=symbols"
+            } else {
+                "strip=none"
+            };
             let rust_flags = [
                 "-C",
                 &format!("link-arg=-T{}", self.linker_path()),
@@ -116,7 +159,7 @@ impl Program {
                 "-C",
                 "panic=abort",
                 "-C",
-                "strip=symbols",
+                strip,
                 "-C",
                 "opt-level=z",
             ];
@@ -177,70 +220,215 @@ impl Program {
             File::open(elf).unwrap_or_else(|_| panic!("could not open elf file: {:?}", elf));
         let mut elf_contents = Vec::new();
         elf_file.read_to_end(&mut elf_contents).unwrap();
-        tracer::decode(&elf_contents)
+        let (bytecode, memory_init) = tracer::decode(&elf_contents);
+        if !self.eliminate_dead_code {
+            return (bytecode, memory_init);
+        }
+
+        let jump_target_hints = match &self.jump_target_hints {
+            Some(path) => {
+                let contents = fs::read_to_string(path)
+                    .unwrap_or_else(|_| panic!("could not read jump target hints: {:?}", path));
+                parse_jump_target_hints(&contents)
+                    .unwrap_or_else(|e| panic!("invalid jump target hints in {:?}: {}", path, e))
+            }
+            None => vec![],
+        };
+        let bytecode = eliminate_dead_code(
+            bytecode,
+            &memory_init,
+            RAM_START_ADDRESS,
+            &jump_target_hints,
+        );
+        (bytecode, memory_init)
     }
 
-    // TODO(moodlezoup): Make this generic over InstructionSet
     #[tracing::instrument(skip_all, name = "Program::trace")]
-    pub fn trace(&mut self) -> (JoltDevice, Vec<JoltTraceStep<RV32I>>) {
-        self.build();
-        let elf = self.elf.clone().unwrap();
-        let (raw_trace, io_device) =
-            tracer::trace(&elf, &self.input, self.max_input_size, self.max_output_size);
+    pub fn trace(&mut self) -> Result<(JoltDevice, Vec<JoltTraceStep<RV32I>>), CycleLimitExceeded> {
+        self.trace_with::<RV32I>()
+    }
+
+    /// Traces the program, mapping each executed instruction onto a lookup in the
+    /// given `InstructionSet`. Instructions that have no corresponding lookup in the
+    /// instruction set (e.g. loads and stores) are traced without one. Fails if the guest runs
+    /// past the limit set with `set_max_cycles`.
+    pub fn trace_with<InstructionSet>(
+        &mut self,
+    ) -> Result<(JoltDevice, Vec<JoltTraceStep<InstructionSet>>), CycleLimitExceeded>
+    where
+        InstructionSet: JoltInstructionSet + for<'a> TryFrom<&'a RVTraceRow>,
+    {
+        let (raw_trace, io_device) = self.raw_trace()?;
 
         let trace: Vec<_> = raw_trace
             .into_par_iter()
-            .flat_map(|row| match row.instruction.opcode {
-                tracer::RV32IM::MULH => MULHInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::MULHSU => MULHSUInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::DIV => DIVInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::DIVU => DIVUInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::REM => REMInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::REMU => REMUInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::SH => SHInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::SB => SBInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::LBU => LBUInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::LHU => LHUInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::LB => LBInstruction::<32>::virtual_trace(row),
-                tracer::RV32IM::LH => LHInstruction::<32>::virtual_trace(row),
-                _ => vec![row],
-            })
-            .map(|row| {
-                let instruction_lookup = if let Ok(jolt_instruction) = RV32I::try_from(&row) {
-                    Some(jolt_instruction)
-                } else {
-                    // Instruction does not use lookups
-                    None
-                };
-
-                JoltTraceStep {
-                    instruction_lookup,
-                    bytecode_row: BytecodeRow::from_instruction::<RV32I>(&row.instruction),
-                    memory_ops: (&row).into(),
-                    circuit_flags: row.instruction.to_circuit_flags(),
-                }
-            })
+            .flat_map(expand_virtual_sequence)
+            .map(|row| JoltTraceStep::from_row(&row))
             .collect();
 
-        (io_device, trace)
+        Ok((io_device, trace))
     }
 
-    pub fn trace_analyze<F: JoltField>(mut self) -> ProgramSummary {
+    /// Traces the program into a [trace file](crate::jolt::trace::file) at `path`, from which
+    /// the prover can read the trace without holding it in memory (see [`Jolt::prove_trace`]).
+    pub fn trace_to_file<Q: AsRef<Path>>(&mut self, path: Q) -> io::Result<JoltDevice> {
+        let (raw_trace, io_device) = self.raw_trace().map_err(io::Error::other)?;
+        let mut writer = TraceWriter::new(BufWriter::new(File::create(path)?))?;
+        for row in raw_trace.into_iter().flat_map(expand_virtual_sequence) {
+            writer.append(&row)?;
+        }
+        writer.finish()?;
+        Ok(io_device)
+    }
+
+    /// The number of Jolt cycles the program takes, counting the virtual sequence each
+    /// instruction expands to in `trace`, without the cost of building the Jolt trace.
+    pub fn cycles_used(&mut self) -> Result<usize, CycleLimitExceeded> {
+        let (raw_trace, _) = self.raw_trace()?;
+        Ok(raw_trace
+            .iter()
+            .map(|row| virtual_sequence(row.instruction.opcode).map_or(1, |(length, _)| length))
+            .sum())
+    }
+
+    fn raw_trace(&mut self) -> Result<(Vec<RVTraceRow>, JoltDevice), CycleLimitExceeded> {
         self.build();
         let elf = self.elf.as_ref().unwrap();
-        let (raw_trace, _) =
-            tracer::trace(elf, &self.input, self.max_input_size, self.max_output_size);
+        tracer::trace_with_max_cycles(elf, &self.input, &self.memory_config, self.max_cycles)
+    }
+
+    pub fn trace_analyze<F: JoltField>(mut self) -> Result<ProgramSummary, CycleLimitExceeded> {
+        let (raw_trace, _) = self.raw_trace()?;
 
         let (bytecode, memory_init) = self.decode();
-        let (io_device, processed_trace) = self.trace();
+        let (io_device, processed_trace) = self.trace()?;
 
-        ProgramSummary {
+        Ok(ProgramSummary {
             raw_trace,
             bytecode,
             memory_init,
             io_device,
             processed_trace,
+        })
+    }
+
+    /// Profiles the program, attributing each instruction it retires to its call stack. Note
+    /// that an instruction may take several Jolt cycles (see `trace`), so the profile counts
+    /// RISC-V instructions rather than proof cost exactly. The guest is built with its
+    /// symbols, which are otherwise stripped.
+    pub fn profile(&mut self) -> Result<Profile, CycleLimitExceeded> {
+        self.strip_symbols = false;
+        let (raw_trace, _) = self.raw_trace()?;
+        let elf = self.elf.as_ref().unwrap();
+        let elf_contents =
+            fs::read(elf).unwrap_or_else(|_| panic!("could not open elf file: {:?}", elf));
+        Ok(Profile::new(&elf_contents, &raw_trace))
+    }
+
+    /// Runs the program under GDB, which should connect to `address` with `target remote`.
+    /// The guest is built with its symbols, which are otherwise stripped.
+    pub fn debug(&mut self, address: &str) -> std::io::Result<JoltDevice> {
+        self.strip_symbols = false;
+        self.build();
+        let elf = self.elf.as_ref().unwrap();
+        tracing::info!("Waiting for GDB on {address} to debug {}", elf.display());
+        tracer::debug(elf, &self.input, &self.memory_config, address)
+    }
+
+    /// Loads the program's preprocessing from `cache_dir` if it was cached there, or else
+    /// preprocesses the program and caches the result. The cache is keyed by a hash of the ELF,
+    /// the memory configuration, the bounds and the commitment scheme, so a change to any of
+    /// them misses the cache.
+    #[tracing::instrument(skip_all, name = "Program::load_or_preprocess")]
+    pub fn load_or_preprocess<F, PCS, ProofTranscript>(
+        &mut self,
+        cache_dir: impl AsRef<Path>,
+        max_bytecode_size: usize,
+        max_trace_length: usize,
+    ) -> io::Result<JoltPreprocessing<C, F, PCS, ProofTranscript>>
+    where
+        F: JoltField,
+        PCS: CommitmentScheme<ProofTranscript, Field = F>,
+        PCS::Setup: CanonicalSerialize + CanonicalDeserialize,
+        ProofTranscript: Transcript,
+    {
+        let memory_layout = self.memory_layout();
+        let max_memory_address = memory_layout.max_memory_address() as usize;
+        let key = self.preprocessing_key::<PCS, ProofTranscript>(
+            max_bytecode_size,
+            max_memory_address,
+            max_trace_length,
+        )?;
+        let path = cache_dir.as_ref().join(format!("{key}.preprocessing"));
+        if let Ok(bytes) = fs::read(&path) {
+            // The cache is only written below, so it is not validated again. A cache that fails
+            // to deserialize (e.g. from an older version) is overwritten.
+            if let Ok(preprocessing) =
+                JoltPreprocessing::deserialize_uncompressed_unchecked(&*bytes)
+            {
+                return Ok(preprocessing);
+            }
+        }
+
+        let (bytecode, memory_init) = self.decode();
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            memory_layout,
+            memory_init,
+            max_bytecode_size,
+            max_memory_address,
+            max_trace_length,
+        );
+        let mut bytes = Vec::with_capacity(preprocessing.uncompressed_size());
+        preprocessing
+            .serialize_uncompressed(&mut bytes)
+            .map_err(io::Error::other)?;
+        fs::create_dir_all(&cache_dir)?;
+        // Renamed into place so that a concurrent run never reads a partially written cache
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp_path, bytes)?;
+        fs::rename(&temp_path, &path)?;
+        Ok(preprocessing)
+    }
+
+    /// The hex-encoded hash that `load_or_preprocess` keys the cache by.
+    fn preprocessing_key<PCS, ProofTranscript>(
+        &mut self,
+        max_bytecode_size: usize,
+        max_memory_address: usize,
+        max_trace_length: usize,
+    ) -> io::Result<String>
+    where
+        PCS: CommitmentScheme<ProofTranscript>,
+        ProofTranscript: Transcript,
+    {
+        self.build();
+        let mut hasher = Sha3_256::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update(PCS::protocol_name());
+        hasher.update(fs::read(self.elf.as_ref().unwrap())?);
+        if self.eliminate_dead_code {
+            hasher.update(b"eliminate_dead_code");
+            if let Some(path) = &self.jump_target_hints {
+                hasher.update(fs::read(path)?);
+            }
+        }
+        for value in [
+            self.memory_config.ram_size,
+            self.memory_config.stack_size,
+            self.memory_config.max_input_size,
+            self.memory_config.max_output_size,
+            max_bytecode_size as u64,
+            max_memory_address as u64,
+            max_trace_length as u64,
+        ] {
+            hasher.update(value.to_le_bytes());
         }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect())
     }
 
     fn save_linker(&self) {
@@ -250,8 +438,8 @@ impl Program {
         }
 
         let linker_script = LINKER_SCRIPT_TEMPLATE
-            .replace("{MEMORY_SIZE}", &self.memory_size.to_string())
-            .replace("{STACK_SIZE}", &self.stack_size.to_string());
+            .replace("{MEMORY_SIZE}", &self.memory_config.ram_size.to_string())
+            .replace("{STACK_SIZE}", &self.memory_config.stack_size.to_string());
 
         let mut file = File::create(linker_path).expect("could not create linker file");
         file.write_all(linker_script.as_bytes())
@@ -263,6 +451,43 @@ impl Program {
     }
 }
 
+/// The rows of the virtual sequence that `row`'s instruction is proven with, or `row` itself.
+/// The length of the virtual sequence an instruction is proven as, and the function expanding
+/// its trace row into the sequence, for the instructions Jolt does not prove directly.
+fn virtual_sequence(opcode: tracer::RV32IM) -> Option<(usize, fn(RVTraceRow) -> Vec<RVTraceRow>)> {
+    fn sequence<I: VirtualInstructionSequence>(
+    ) -> Option<(usize, fn(RVTraceRow) -> Vec<RVTraceRow>)> {
+        Some((I::SEQUENCE_LENGTH, I::virtual_trace))
+    }
+
+    match opcode {
+        tracer::RV32IM::MULH => sequence::<MULHInstruction<32>>(),
+        tracer::RV32IM::MULHSU => sequence::<MULHSUInstruction<32>>(),
+        tracer::RV32IM::DIV => sequence::<DIVInstruction<32>>(),
+        tracer::RV32IM::DIVU => sequence::<DIVUInstruction<32>>(),
+        tracer::RV32IM::REM => sequence::<REMInstruction<32>>(),
+        tracer::RV32IM::REMU => sequence::<REMUInstruction<32>>(),
+        tracer::RV32IM::SH => sequence::<SHInstruction<32>>(),
+        tracer::RV32IM::SB => sequence::<SBInstruction<32>>(),
+        tracer::RV32IM::LBU => sequence::<LBUInstruction<32>>(),
+        tracer::RV32IM::LHU => sequence::<LHUInstruction<32>>(),
+        tracer::RV32IM::LB => sequence::<LBInstruction<32>>(),
+        tracer::RV32IM::LH => sequence::<LHInstruction<32>>(),
+        _ => None,
+    }
+}
+
+fn expand_virtual_sequence(row: RVTraceRow) -> Vec<RVTraceRow> {
+    match virtual_sequence(row.instruction.opcode) {
+        Some((_, expand)) => expand(row),
+        None if row.instruction.opcode.is_rv64() => panic!(
+            "{:?} is an RV64 instruction, which Jolt cannot yet prove",
+            row.instruction.opcode
+        ),
+        None => vec![row],
+    }
+}
+
 const LINKER_SCRIPT_TEMPLATE: &str = r#"
 MEMORY {
   program (rwx) : ORIGIN = 0x80000000, LENGTH = {MEMORY_SIZE}
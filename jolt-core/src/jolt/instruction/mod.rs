@@ -1,19 +1,16 @@
-use enum_dispatch::enum_dispatch;
 use fixedbitset::*;
 use rand::prelude::StdRng;
 use serde::Serialize;
 use std::marker::Sync;
 use std::ops::Range;
 use strum::{EnumCount, IntoEnumIterator};
-use tracer::{RVTraceRow, RegisterState};
 
 use crate::field::JoltField;
 use crate::jolt::subtable::LassoSubtable;
 use crate::utils::instruction_utils::chunk_operand;
-use common::rv_trace::ELFInstruction;
+use common::rv_trace::{ELFInstruction, RVTraceRow, RegisterState};
 use std::fmt::Debug;
 
-#[enum_dispatch]
 pub trait JoltInstruction: Clone + Debug + Send + Sync + Serialize {
     fn operands(&self) -> (u64, u64);
     /// Combines `vals` according to the instruction's "collation" polynomial `g`.
@@ -71,7 +68,16 @@ pub trait JoltInstruction: Clone + Debug + Send + Sync + Serialize {
 }
 
 pub trait JoltInstructionSet:
-    JoltInstruction + IntoEnumIterator + EnumCount + for<'a> TryFrom<&'a ELFInstruction> + Send + Sync
+    JoltInstruction
+    + Copy
+    + PartialEq
+    + Default
+    + IntoEnumIterator
+    + EnumCount
+    + for<'a> TryFrom<&'a ELFInstruction>
+    + Send
+    + Sync
+    + 'static
 {
     fn enum_index(instruction: &Self) -> usize {
         // Discriminant: https://doc.rust-lang.org/reference/items/enumerations.html#pointer-casting
@@ -147,6 +153,164 @@ pub trait VirtualInstructionSequence {
     fn sequence_output(x: u64, y: u64) -> u64;
 }
 
+#[doc(hidden)]
+pub mod __private {
+    pub use rand::{prelude::StdRng, RngCore};
+    pub use strum;
+}
+
+/// Generates an enum out of a list of JoltInstruction types. All JoltInstruction methods
+/// are callable on the enum type, which also implements `JoltInstructionSet` and `From<T>`
+/// for each of the listed instruction types.
+///
+/// This is the registration point for instruction sets: downstream crates can invoke this
+/// macro with their own `JoltInstruction` types (alongside any of the ones provided here)
+/// and use the resulting enum as the `InstructionSet` of a `Jolt` implementation. The
+/// generated enum derives `serde::Serialize` and `serde::Deserialize`, so the invoking crate
+/// must depend on `serde`. It must also implement `TryFrom<&ELFInstruction>` and
+/// `TryFrom<&RVTraceRow>` for the enum to map decoded/traced instructions onto lookups.
+/// Each instruction gets an instruction flag in the R1CS; to use the RV32IM constraints,
+/// the set must include the instructions of `RV32IMInstructionSet` (see
+/// `JoltRV32IMConstraints`).
+#[macro_export]
+macro_rules! instruction_set {
+    ($enum_name:ident, $($alias:ident: $struct:ty),+ $(,)?) => {
+        #[allow(non_camel_case_types)]
+        #[repr(u8)]
+        #[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        pub enum $enum_name {
+            $($alias($struct)),+
+        }
+
+        $(
+            impl From<$struct> for $enum_name {
+                fn from(instruction: $struct) -> Self {
+                    $enum_name::$alias(instruction)
+                }
+            }
+        )+
+
+        impl $crate::jolt::instruction::JoltInstruction for $enum_name {
+            fn operands(&self) -> (u64, u64) {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.operands()),+
+                }
+            }
+
+            fn combine_lookups<F: $crate::field::JoltField>(
+                &self,
+                vals: &[F],
+                c: usize,
+                m: usize,
+            ) -> F {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.combine_lookups(vals, c, m)),+
+                }
+            }
+
+            fn g_poly_degree(&self, c: usize) -> usize {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.g_poly_degree(c)),+
+                }
+            }
+
+            fn subtables<F: $crate::field::JoltField>(
+                &self,
+                c: usize,
+                m: usize,
+            ) -> Vec<(
+                Box<dyn $crate::jolt::subtable::LassoSubtable<F>>,
+                $crate::jolt::instruction::SubtableIndices,
+            )> {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.subtables(c, m)),+
+                }
+            }
+
+            fn to_indices(&self, c: usize, log_m: usize) -> Vec<usize> {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.to_indices(c, log_m)),+
+                }
+            }
+
+            fn lookup_entry(&self) -> u64 {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.lookup_entry()),+
+                }
+            }
+
+            fn operand_chunks(&self, c: usize, log_m: usize) -> (Vec<u64>, Vec<u64>) {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.operand_chunks(c, log_m)),+
+                }
+            }
+
+            fn random(&self, rng: &mut $crate::jolt::instruction::__private::StdRng) -> Self {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.random(rng).into()),+
+                }
+            }
+
+            fn slice_values<'a, F: $crate::field::JoltField>(
+                &self,
+                vals: &'a [F],
+                c: usize,
+                m: usize,
+            ) -> Vec<&'a [F]> {
+                match self {
+                    $($enum_name::$alias(instruction) => instruction.slice_values(vals, c, m)),+
+                }
+            }
+        }
+
+        impl $crate::jolt::instruction::__private::strum::EnumCount for $enum_name {
+            const COUNT: usize = [$(stringify!($alias)),+].len();
+        }
+
+        impl $crate::jolt::instruction::__private::strum::IntoEnumIterator for $enum_name {
+            type Iterator = std::iter::Map<std::ops::Range<usize>, fn(usize) -> Self>;
+
+            fn iter() -> Self::Iterator {
+                fn nth_variant(index: usize) -> $enum_name {
+                    let mut variant_index = 0;
+                    $(
+                        if index == variant_index {
+                            return $enum_name::$alias(<$struct>::default());
+                        }
+                        variant_index += 1;
+                    )+
+                    unreachable!("{} has only {} variants", stringify!($enum_name), variant_index)
+                }
+                (0..<Self as $crate::jolt::instruction::__private::strum::EnumCount>::COUNT)
+                    .map(nth_variant as fn(usize) -> Self)
+            }
+        }
+
+        impl $crate::jolt::instruction::JoltInstructionSet for $enum_name {}
+
+        impl $enum_name {
+            pub fn random_instruction(
+                rng: &mut $crate::jolt::instruction::__private::StdRng,
+            ) -> Self {
+                use $crate::jolt::instruction::__private::strum::{EnumCount, IntoEnumIterator};
+                use $crate::jolt::instruction::__private::RngCore;
+                use $crate::jolt::instruction::JoltInstruction;
+                let index = rng.next_u64() as usize % $enum_name::COUNT;
+                let instruction = $enum_name::iter().nth(index).unwrap();
+                instruction.random(rng)
+            }
+        }
+
+        // Need a default so that we can derive EnumIter on `JoltR1CSInputs`
+        impl Default for $enum_name {
+            fn default() -> Self {
+                use $crate::jolt::instruction::__private::strum::IntoEnumIterator;
+                $enum_name::iter().next().unwrap()
+            }
+        }
+    };
+}
+
 pub mod add;
 pub mod and;
 pub mod beq;
@@ -1,10 +1,8 @@
 use crate::field::JoltField;
-use enum_dispatch::enum_dispatch;
 use std::any::TypeId;
 use std::marker::Sync;
 use strum::{EnumCount, IntoEnumIterator};
 
-#[enum_dispatch]
 pub trait LassoSubtable<F: JoltField>: 'static + Sync {
     /// Returns the TypeId of this subtable.
     /// The `Jolt` trait has associated enum types `InstructionSet` and `Subtables`.
@@ -29,6 +27,105 @@ pub trait JoltSubtableSet<F: JoltField>:
     }
 }
 
+/// Generates an enum out of a list of LassoSubtable types. All LassoSubtable methods
+/// are callable on the enum type, which also implements `JoltSubtableSet` and `From<T>`
+/// for each of the listed subtable types.
+///
+/// Like `instruction_set!`, this can be invoked by downstream crates to register their own
+/// subtables. The subtable set of a `Jolt` implementation must contain every subtable
+/// returned by `JoltInstruction::subtables` for every instruction in its instruction set.
+#[macro_export]
+macro_rules! subtable_enum {
+    ($enum_name:ident, $($alias:ident: $struct:ty),+ $(,)?) => {
+        #[allow(non_camel_case_types)]
+        #[repr(u8)]
+        pub enum $enum_name<F: $crate::field::JoltField> { $($alias($struct)),+ }
+
+        $(
+            impl<F: $crate::field::JoltField> From<$struct> for $enum_name<F> {
+                fn from(subtable: $struct) -> Self {
+                    $enum_name::$alias(subtable)
+                }
+            }
+        )+
+
+        impl<F: $crate::field::JoltField> $crate::jolt::subtable::LassoSubtable<F>
+            for $enum_name<F>
+        {
+            fn subtable_id(&self) -> $crate::jolt::subtable::SubtableId {
+                match self {
+                    $($enum_name::$alias(subtable) => subtable.subtable_id()),+
+                }
+            }
+
+            fn materialize(&self, m: usize) -> Vec<F> {
+                match self {
+                    $($enum_name::$alias(subtable) => subtable.materialize(m)),+
+                }
+            }
+
+            fn evaluate_mle(&self, point: &[F]) -> F {
+                match self {
+                    $($enum_name::$alias(subtable) => subtable.evaluate_mle(point)),+
+                }
+            }
+        }
+
+        impl<F: $crate::field::JoltField> From<$crate::jolt::subtable::SubtableId>
+            for $enum_name<F>
+        {
+          fn from(subtable_id: $crate::jolt::subtable::SubtableId) -> Self {
+            $(
+              if subtable_id == std::any::TypeId::of::<$struct>() {
+                $enum_name::from(<$struct>::new())
+              } else
+            )+
+            { panic!("Unexpected subtable id {:?}", subtable_id) } // TODO(moodlezoup): better error handling
+          }
+        }
+
+        impl<F: $crate::field::JoltField> From<$enum_name<F>> for usize {
+            fn from(subtable: $enum_name<F>) -> usize {
+                // Discriminant: https://doc.rust-lang.org/reference/items/enumerations.html#pointer-casting
+                let byte = unsafe { *(&subtable as *const $enum_name<F> as *const u8) };
+                byte as usize
+            }
+        }
+
+        impl<F: $crate::field::JoltField> $crate::jolt::instruction::__private::strum::EnumCount
+            for $enum_name<F>
+        {
+            const COUNT: usize = [$(stringify!($alias)),+].len();
+        }
+
+        impl<F: $crate::field::JoltField>
+            $crate::jolt::instruction::__private::strum::IntoEnumIterator for $enum_name<F>
+        {
+            type Iterator = std::iter::Map<std::ops::Range<usize>, fn(usize) -> Self>;
+
+            fn iter() -> Self::Iterator {
+                fn nth_variant<F: $crate::field::JoltField>(index: usize) -> $enum_name<F> {
+                    let mut variant_index = 0;
+                    $(
+                        if index == variant_index {
+                            return $enum_name::from(<$struct>::new());
+                        }
+                        variant_index += 1;
+                    )+
+                    unreachable!("{} has only {} variants", stringify!($enum_name), variant_index)
+                }
+                (0..<Self as $crate::jolt::instruction::__private::strum::EnumCount>::COUNT)
+                    .map(nth_variant::<F> as fn(usize) -> Self)
+            }
+        }
+
+        impl<F: $crate::field::JoltField> $crate::jolt::subtable::JoltSubtableSet<F>
+            for $enum_name<F>
+        {
+        }
+    };
+}
+
 pub mod and;
 pub mod div_by_zero;
 pub mod eq;
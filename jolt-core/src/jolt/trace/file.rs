@@ -0,0 +1,369 @@
+//! An on-disk format for execution traces, which the prover maps into memory rather than reads,
+//! so that it can prove traces bigger than RAM (see [`Jolt::prove_trace`]).
+//!
+//! A trace file is the magic bytes `JOLTTRCE`, a `u32` version and the `u32` size of a record,
+//! followed by a [`TraceRecord`] per step of the trace, after the expansion of virtual
+//! sequences. The records are accessed in place, so all integers are in the byte order of the
+//! machine that wrote the file (little-endian on all of Jolt's hosts).
+//!
+//! [`Jolt::prove_trace`]: crate::jolt::vm::Jolt::prove_trace
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use bytemuck::{Pod, Zeroable};
+use common::rv_trace::{ELFInstruction, MemoryState, RVTraceRow, RegisterState, RV32IM};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::jolt::instruction::JoltInstructionSet;
+use crate::jolt::vm::{JoltTrace, JoltTraceStep};
+
+const MAGIC: &[u8; 8] = b"JOLTTRCE";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+
+const HAS_RS1: u16 = 1 << 0;
+const HAS_RS2: u16 = 1 << 1;
+const HAS_RD: u16 = 1 << 2;
+const HAS_IMM: u16 = 1 << 3;
+const HAS_VIRTUAL_SEQUENCE_REMAINING: u16 = 1 << 4;
+const HAS_RS1_VAL: u16 = 1 << 5;
+const HAS_RS2_VAL: u16 = 1 << 6;
+const HAS_RD_POST_VAL: u16 = 1 << 7;
+const HAS_MEMORY_READ: u16 = 1 << 8;
+const HAS_MEMORY_WRITE: u16 = 1 << 9;
+const HAS_ADVICE_VALUE: u16 = 1 << 10;
+
+#[derive(Debug, Error)]
+pub enum TraceFileError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Not a trace file")]
+    InvalidMagic,
+    #[error("Unsupported trace file version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Records have size {0}, expected {1}")]
+    InvalidRecordSize(u32, usize),
+    #[error("The records take {0} bytes, which is not a whole number of records")]
+    Truncated(usize),
+    #[error("Invalid opcode {1} at step {0}")]
+    InvalidOpcode(usize, u8),
+}
+
+/// A step of the trace, i.e. an [`RVTraceRow`], in a fixed-size layout. `flags` records which
+/// of the row's optional fields are present; absent fields are zero.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct TraceRecord {
+    pub address: u64,
+    pub imm: i64,
+    pub rs1_val: u64,
+    pub rs2_val: u64,
+    pub rd_post_val: u64,
+    pub memory_address: u64,
+    /// The value read, or the value before a write
+    pub memory_pre_value: u64,
+    /// The value after a write
+    pub memory_post_value: u64,
+    pub advice_value: u64,
+    pub virtual_sequence_remaining: u32,
+    pub opcode: u8,
+    pub rs1: u8,
+    pub rs2: u8,
+    pub rd: u8,
+    pub flags: u16,
+    pub padding: [u8; 6],
+}
+
+impl From<&RVTraceRow> for TraceRecord {
+    fn from(row: &RVTraceRow) -> Self {
+        let instruction = &row.instruction;
+        let mut record = TraceRecord {
+            address: instruction.address,
+            opcode: instruction.opcode as u8,
+            ..Default::default()
+        };
+        let mut set = |flag: u16, present: bool| {
+            if present {
+                record.flags |= flag;
+            }
+        };
+        set(HAS_RS1, instruction.rs1.is_some());
+        set(HAS_RS2, instruction.rs2.is_some());
+        set(HAS_RD, instruction.rd.is_some());
+        set(HAS_IMM, instruction.imm.is_some());
+        set(
+            HAS_VIRTUAL_SEQUENCE_REMAINING,
+            instruction.virtual_sequence_remaining.is_some(),
+        );
+        set(HAS_RS1_VAL, row.register_state.rs1_val.is_some());
+        set(HAS_RS2_VAL, row.register_state.rs2_val.is_some());
+        set(HAS_RD_POST_VAL, row.register_state.rd_post_val.is_some());
+        set(HAS_ADVICE_VALUE, row.advice_value.is_some());
+
+        // Registers (including virtual ones) are indexed by `u8`s
+        record.rs1 = instruction.rs1.unwrap_or(0) as u8;
+        record.rs2 = instruction.rs2.unwrap_or(0) as u8;
+        record.rd = instruction.rd.unwrap_or(0) as u8;
+        record.imm = instruction.imm.unwrap_or(0);
+        record.virtual_sequence_remaining =
+            instruction.virtual_sequence_remaining.unwrap_or(0) as u32;
+        record.rs1_val = row.register_state.rs1_val.unwrap_or(0);
+        record.rs2_val = row.register_state.rs2_val.unwrap_or(0);
+        record.rd_post_val = row.register_state.rd_post_val.unwrap_or(0);
+        record.advice_value = row.advice_value.unwrap_or(0);
+        match row.memory_state {
+            Some(MemoryState::Read { address, value }) => {
+                record.flags |= HAS_MEMORY_READ;
+                record.memory_address = address;
+                record.memory_pre_value = value;
+            }
+            Some(MemoryState::Write {
+                address,
+                pre_value,
+                post_value,
+            }) => {
+                record.flags |= HAS_MEMORY_WRITE;
+                record.memory_address = address;
+                record.memory_pre_value = pre_value;
+                record.memory_post_value = post_value;
+            }
+            None => {}
+        }
+        record
+    }
+}
+
+impl From<&TraceRecord> for RVTraceRow {
+    /// Panics if the record's opcode is invalid, which [`TraceFile::open`] rules out.
+    fn from(record: &TraceRecord) -> Self {
+        let get = |flag: u16, value: u64| (record.flags & flag != 0).then_some(value);
+        let memory_state = if record.flags & HAS_MEMORY_READ != 0 {
+            Some(MemoryState::Read {
+                address: record.memory_address,
+                value: record.memory_pre_value,
+            })
+        } else if record.flags & HAS_MEMORY_WRITE != 0 {
+            Some(MemoryState::Write {
+                address: record.memory_address,
+                pre_value: record.memory_pre_value,
+                post_value: record.memory_post_value,
+            })
+        } else {
+            None
+        };
+        RVTraceRow {
+            instruction: ELFInstruction {
+                address: record.address,
+                opcode: RV32IM::from_repr(record.opcode).expect("invalid opcode"),
+                rs1: get(HAS_RS1, record.rs1 as u64),
+                rs2: get(HAS_RS2, record.rs2 as u64),
+                rd: get(HAS_RD, record.rd as u64),
+                imm: (record.flags & HAS_IMM != 0).then_some(record.imm),
+                virtual_sequence_remaining: (record.flags & HAS_VIRTUAL_SEQUENCE_REMAINING != 0)
+                    .then_some(record.virtual_sequence_remaining as usize),
+            },
+            register_state: RegisterState {
+                rs1_val: get(HAS_RS1_VAL, record.rs1_val),
+                rs2_val: get(HAS_RS2_VAL, record.rs2_val),
+                rd_post_val: get(HAS_RD_POST_VAL, record.rd_post_val),
+            },
+            memory_state,
+            advice_value: get(HAS_ADVICE_VALUE, record.advice_value),
+        }
+    }
+}
+
+/// Writes a trace file, one row at a time.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_ne_bytes())?;
+        writer.write_all(&(std::mem::size_of::<TraceRecord>() as u32).to_ne_bytes())?;
+        Ok(Self { writer })
+    }
+
+    pub fn append(&mut self, row: &RVTraceRow) -> io::Result<()> {
+        self.writer
+            .write_all(bytemuck::bytes_of(&TraceRecord::from(row)))
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// A trace file mapped into memory. The file must not be modified while it is open.
+pub struct TraceFile {
+    mmap: Mmap,
+}
+
+impl TraceFile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TraceFileError> {
+        let file = File::open(path)?;
+        // Safety: as documented, the file must not be modified while it is mapped
+        let mmap = unsafe { Mmap::map(&file)? };
+        if mmap.len() < HEADER_SIZE || &mmap[..MAGIC.len()] != MAGIC {
+            return Err(TraceFileError::InvalidMagic);
+        }
+        let version = u32::from_ne_bytes(mmap[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(TraceFileError::UnsupportedVersion(version));
+        }
+        let record_size = u32::from_ne_bytes(mmap[12..16].try_into().unwrap());
+        if record_size as usize != std::mem::size_of::<TraceRecord>() {
+            return Err(TraceFileError::InvalidRecordSize(
+                record_size,
+                std::mem::size_of::<TraceRecord>(),
+            ));
+        }
+        let records_size = mmap.len() - HEADER_SIZE;
+        if records_size % std::mem::size_of::<TraceRecord>() != 0 {
+            return Err(TraceFileError::Truncated(records_size));
+        }
+
+        let trace_file = Self { mmap };
+        if let Some((index, record)) = trace_file
+            .records()
+            .par_iter()
+            .enumerate()
+            .find_first(|(_, record)| RV32IM::from_repr(record.opcode).is_none())
+        {
+            return Err(TraceFileError::InvalidOpcode(index, record.opcode));
+        }
+        Ok(trace_file)
+    }
+
+    /// The records of the trace, read in place from the mapped file.
+    pub fn records(&self) -> &[TraceRecord] {
+        // The mapping is page-aligned and the header's size a multiple of the records'
+        // alignment, so the records are aligned
+        bytemuck::cast_slice(&self.mmap[HEADER_SIZE..])
+    }
+
+    pub fn len(&self) -> usize {
+        self.records().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records().is_empty()
+    }
+}
+
+impl<InstructionSet> JoltTrace<InstructionSet> for TraceFile
+where
+    InstructionSet: JoltInstructionSet + for<'a> TryFrom<&'a RVTraceRow>,
+{
+    fn len(&self) -> usize {
+        TraceFile::len(self)
+    }
+
+    fn step(&self, index: usize) -> Cow<'_, JoltTraceStep<InstructionSet>> {
+        Cow::Owned(JoltTraceStep::from_row(&RVTraceRow::from(
+            &self.records()[index],
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jolt::vm::rv32i_vm::RV32I;
+    use std::io::BufWriter;
+
+    fn rows() -> Vec<RVTraceRow> {
+        let instruction = |opcode, rs1, rs2, rd, imm| ELFInstruction {
+            address: 0x80000000,
+            opcode,
+            rs1,
+            rs2,
+            rd,
+            imm,
+            virtual_sequence_remaining: None,
+        };
+        vec![
+            RVTraceRow {
+                instruction: instruction(RV32IM::ADD, Some(1), Some(2), Some(3), None),
+                register_state: RegisterState {
+                    rs1_val: Some(5),
+                    rs2_val: Some(7),
+                    rd_post_val: Some(12),
+                },
+                memory_state: None,
+                advice_value: None,
+            },
+            RVTraceRow {
+                instruction: ELFInstruction {
+                    virtual_sequence_remaining: Some(2),
+                    ..instruction(RV32IM::SW, Some(2), Some(4), None, Some(-8))
+                },
+                register_state: RegisterState {
+                    rs1_val: Some(0x80001008),
+                    rs2_val: Some(9),
+                    rd_post_val: None,
+                },
+                memory_state: Some(MemoryState::Write {
+                    address: 0x80001000,
+                    pre_value: 1,
+                    post_value: 9,
+                }),
+                advice_value: Some(3),
+            },
+            RVTraceRow {
+                instruction: instruction(RV32IM::LW, Some(2), None, Some(5), Some(0)),
+                register_state: RegisterState {
+                    rs1_val: Some(0x80001000),
+                    rs2_val: None,
+                    rd_post_val: Some(9),
+                },
+                memory_state: Some(MemoryState::Read {
+                    address: 0x80001000,
+                    value: 9,
+                }),
+                advice_value: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn trace_file_round_trip() {
+        let rows = rows();
+        let path = std::env::temp_dir().join(format!("jolt-trace-{}.bin", std::process::id()));
+        let mut writer = TraceWriter::new(BufWriter::new(File::create(&path).unwrap())).unwrap();
+        for row in rows.iter() {
+            writer.append(row).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let trace_file = TraceFile::open(&path).unwrap();
+        assert_eq!(trace_file.len(), rows.len());
+        for (record, row) in trace_file.records().iter().zip(rows.iter()) {
+            assert_eq!(&RVTraceRow::from(record), row);
+        }
+        for (index, row) in rows.iter().enumerate() {
+            let step = JoltTrace::<RV32I>::step(&trace_file, index);
+            let expected = JoltTraceStep::<RV32I>::from_row(row);
+            assert_eq!(step.instruction_lookup, expected.instruction_lookup);
+            assert_eq!(step.bytecode_row, expected.bytecode_row);
+            assert_eq!(step.memory_ops, expected.memory_ops);
+            assert_eq!(step.circuit_flags, expected.circuit_flags);
+        }
+
+        // A truncated file
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(
+            TraceFile::open(&path),
+            Err(TraceFileError::Truncated(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1 +1,3 @@
+#[cfg(feature = "prover")]
+pub mod file;
 pub mod rv;
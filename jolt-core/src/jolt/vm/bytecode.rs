@@ -3,10 +3,9 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::rngs::StdRng;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 #[cfg(test)]
 use std::collections::HashSet;
-use tracer::RV32IM;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::field::JoltField;
 use crate::jolt::instruction::JoltInstructionSet;
@@ -16,11 +15,13 @@ use crate::lasso::memory_checking::{
 use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
 use crate::poly::eq_poly::EqPolynomial;
 use common::constants::{BYTES_PER_INSTRUCTION, RAM_START_ADDRESS};
-use common::rv_trace::ELFInstruction;
+use common::rv_trace::{ELFInstruction, RV32IM};
 
-use rayon::prelude::*;
+use crate::utils::par::{self, prelude::*};
 
-use super::{JoltPolynomials, JoltTraceStep};
+use super::{JoltPolynomials, JoltTrace};
+use crate::utils::errors::JoltError;
+use crate::utils::thread::{transpose_chunks_mut, unsafe_allocate_zero_vec};
 use crate::utils::transcript::Transcript;
 use crate::{
     lasso::memory_checking::{MemoryCheckingProof, MemoryCheckingProver, MemoryCheckingVerifier},
@@ -217,7 +218,193 @@ pub fn random_bytecode_trace(
     trace
 }
 
-#[derive(Clone)]
+/// Removes instructions that are unreachable from `entrypoint` from the given (ELF) bytecode,
+/// shrinking the bytecode polynomials for guests that link in a large library surface
+/// but only execute a small part of it.
+///
+/// Direct jumps, branches, and fall-through are followed exactly. Indirect jumps (`JALR`)
+/// are handled conservatively: any instruction whose address may be materialized in a
+/// register is treated as a potential jump target. This includes
+///   - return addresses, i.e. the instruction following any `JAL`/`JALR` that links,
+///   - addresses within the 12-bit immediate range of a reachable `AUIPC` or `LUI`
+///     (covering `auipc`/`lui` + `addi`/`jalr` address computations),
+///   - addresses computed from an `AUIPC` or `LUI` by the `ADDI`s following it in straight-line
+///     code, and the targets of `JALR`s through them, however many offsets are stacked,
+///   - addresses stored as aligned 32-bit words in `memory_init` (e.g. function pointers
+///     and jump tables in .rodata/.data), and
+///   - the provided `jump_target_hints`, for targets the above does not capture (e.g.
+///     jump tables stored as relative offsets, or addresses computed across branches or
+///     spilled to the stack).
+///
+/// If the guest nevertheless executes an instruction that was eliminated, witness
+/// generation will fail to find it in the bytecode; soundness is unaffected, since the
+/// R1CS constraints tie the program counter to the committed bytecode.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "bytecode::eliminate_dead_code")
+)]
+pub fn eliminate_dead_code(
+    mut bytecode: Vec<ELFInstruction>,
+    memory_init: &[(u64, u8)],
+    entrypoint: u64,
+    jump_target_hints: &[u64],
+) -> Vec<ELFInstruction> {
+    // Bytecode may already have been expanded into virtual sequences, in which case
+    // multiple instructions share the same ELF address. We only need the first one
+    // to determine control flow.
+    let mut instructions: BTreeMap<u64, &ELFInstruction> = BTreeMap::new();
+    for instruction in bytecode.iter() {
+        instructions
+            .entry(instruction.address)
+            .or_insert(instruction);
+    }
+
+    let mut worklist: Vec<u64> = vec![entrypoint];
+    worklist.extend_from_slice(jump_target_hints);
+    worklist.extend(address_taken_words(memory_init));
+
+    let mut reachable: BTreeSet<u64> = BTreeSet::new();
+    while let Some(address) = worklist.pop() {
+        let Some(instruction) = instructions.get(&address) else {
+            continue;
+        };
+        if !reachable.insert(address) {
+            continue;
+        }
+
+        let imm = instruction.imm.unwrap_or(0);
+        let links = instruction.rd.unwrap_or(0) != 0;
+        let fall_through = address.wrapping_add(BYTES_PER_INSTRUCTION as u64);
+        match instruction.opcode {
+            RV32IM::JAL => {
+                worklist.push(address.wrapping_add_signed(imm));
+                if links {
+                    worklist.push(fall_through);
+                }
+            }
+            RV32IM::JALR => {
+                if links {
+                    worklist.push(fall_through);
+                }
+            }
+            RV32IM::BEQ | RV32IM::BNE | RV32IM::BLT | RV32IM::BGE | RV32IM::BLTU | RV32IM::BGEU => {
+                worklist.push(address.wrapping_add_signed(imm));
+                worklist.push(fall_through);
+            }
+            RV32IM::AUIPC | RV32IM::LUI => {
+                let base = if instruction.opcode == RV32IM::AUIPC {
+                    address.wrapping_add_signed(imm) as u32
+                } else {
+                    imm as u32
+                } as u64;
+                worklist.extend(
+                    instructions
+                        .range(base.saturating_sub(1 << 11)..base + (1 << 11))
+                        .map(|(target, _)| *target),
+                );
+                worklist.extend(computed_addresses(
+                    &instructions,
+                    fall_through,
+                    instruction.rd.unwrap_or(0),
+                    base,
+                ));
+                worklist.push(fall_through);
+            }
+            // Unrecognized instructions (e.g. data interleaved with code) trap.
+            RV32IM::UNIMPL => {}
+            _ => worklist.push(fall_through),
+        }
+    }
+
+    bytecode.retain(|instruction| reachable.contains(&instruction.address));
+    bytecode
+}
+
+/// Follows the straight-line code from `address`, where register `rd` holds `value` (set by an
+/// `AUIPC` or `LUI`), and returns the addresses that `ADDI`s compute from it, along with the
+/// target of a `JALR` through one of them. Stops at the first control-flow instruction.
+fn computed_addresses(
+    instructions: &BTreeMap<u64, &ELFInstruction>,
+    mut address: u64,
+    rd: u64,
+    value: u64,
+) -> Vec<u64> {
+    let mut registers: BTreeMap<u64, u64> = BTreeMap::new();
+    if rd != 0 {
+        registers.insert(rd, value);
+    }
+    let mut addresses = vec![];
+    while let Some(instruction) = instructions.get(&address) {
+        if registers.is_empty() {
+            break;
+        }
+        let imm = instruction.imm.unwrap_or(0);
+        let rs1_value = instruction.rs1.and_then(|rs1| registers.get(&rs1).copied());
+        match instruction.opcode {
+            RV32IM::JALR => {
+                if let Some(value) = rs1_value {
+                    addresses.push(value.wrapping_add_signed(imm) as u32 as u64 & !1);
+                }
+                break;
+            }
+            RV32IM::JAL
+            | RV32IM::BEQ
+            | RV32IM::BNE
+            | RV32IM::BLT
+            | RV32IM::BGE
+            | RV32IM::BLTU
+            | RV32IM::BGEU
+            | RV32IM::UNIMPL => break,
+            _ => {
+                let rd = instruction.rd.unwrap_or(0);
+                match rs1_value {
+                    Some(value) if instruction.opcode == RV32IM::ADDI && rd != 0 => {
+                        let value = value.wrapping_add_signed(imm) as u32 as u64;
+                        addresses.push(value);
+                        registers.insert(rd, value);
+                    }
+                    _ => {
+                        registers.remove(&rd);
+                    }
+                }
+            }
+        }
+        address = address.wrapping_add(BYTES_PER_INSTRUCTION as u64);
+    }
+    addresses
+}
+
+/// Returns the 32-bit words stored at word-aligned addresses in `memory_init`.
+fn address_taken_words(memory_init: &[(u64, u8)]) -> Vec<u64> {
+    let memory: BTreeMap<u64, u8> = memory_init.iter().copied().collect();
+    memory
+        .keys()
+        .filter(|address| *address % 4 == 0)
+        .filter_map(|address| {
+            let mut bytes = [0u8; 4];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = *memory.get(&(address + i as u64))?;
+            }
+            Some(u32::from_le_bytes(bytes) as u64)
+        })
+        .collect()
+}
+
+/// Parses a jump-target hint file for `eliminate_dead_code`: one address per line,
+/// either in hex (`0x`-prefixed) or decimal. Empty lines and `#` comments are ignored.
+pub fn parse_jump_target_hints(contents: &str) -> Result<Vec<u64>, std::num::ParseIntError> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => line.parse::<u64>(),
+        })
+        .collect()
+}
+
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct BytecodePreprocessing<F: JoltField> {
     /// Size of the (padded) bytecode.
     code_size: usize,
@@ -233,13 +420,20 @@ pub struct BytecodePreprocessing<F: JoltField> {
 }
 
 impl<F: JoltField> BytecodePreprocessing<F> {
-    #[tracing::instrument(skip_all, name = "BytecodePreprocessing::preprocess")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BytecodePreprocessing::preprocess")
+    )]
     pub fn preprocess(mut bytecode: Vec<BytecodeRow>) -> Self {
         let mut virtual_address_map = BTreeMap::new();
         let mut virtual_address = 1; // Account for no-op instruction prepended to bytecode
         for instruction in bytecode.iter_mut() {
             assert!(instruction.address >= RAM_START_ADDRESS as usize);
-            assert!(instruction.address % BYTES_PER_INSTRUCTION == 0);
+            assert!(
+                instruction.address % BYTES_PER_INSTRUCTION == 0,
+                "Instruction at {:#x} is not word-aligned; compressed (RVC) instructions cannot yet be proven",
+                instruction.address
+            );
             // Compress instruction address for more efficient commitment:
             instruction.address =
                 1 + (instruction.address - RAM_START_ADDRESS as usize) / BYTES_PER_INSTRUCTION;
@@ -303,66 +497,87 @@ where
     PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 {
-    #[tracing::instrument(skip_all, name = "BytecodePolynomials::new")]
+    /// Fails if a step of the `trace` executes an instruction that is not in the preprocessed
+    /// bytecode, e.g. because the trace is of a different program.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BytecodePolynomials::new")
+    )]
     pub fn generate_witness<InstructionSet: JoltInstructionSet>(
         preprocessing: &BytecodePreprocessing<F>,
-        trace: &mut Vec<JoltTraceStep<InstructionSet>>,
-    ) -> BytecodePolynomials<F> {
+        trace: &(impl JoltTrace<InstructionSet> + ?Sized),
+    ) -> Result<BytecodePolynomials<F>, JoltError> {
         let num_ops = trace.len();
 
         let mut a_read_write_usize: Vec<usize> = vec![0; num_ops];
+        // address || bitflags || rd || rs1 || rs2 || imm
+        let mut v_read_write: Vec<Vec<F>> =
+            (0..6).map(|_| unsafe_allocate_zero_vec(num_ops)).collect();
+
+        // Each thread looks up the virtual addresses and values of a range of steps; if several
+        // steps have unknown addresses, the error reports the first of them.
+        let chunk_size = num_ops.div_ceil(par::current_num_threads()).max(1);
+        transpose_chunks_mut(&mut v_read_write, chunk_size)
+            .into_par_iter()
+            .zip(a_read_write_usize.par_chunks_mut(chunk_size))
+            .enumerate()
+            .map(|(chunk_index, (mut values, addresses))| {
+                for (offset, virtual_address) in addresses.iter_mut().enumerate() {
+                    let step_index = chunk_index * chunk_size + offset;
+                    let step = trace.step(step_index);
+                    let row = &step.bytecode_row;
+                    let unknown_address = || JoltError::UnknownBytecodeAddress {
+                        step: step_index,
+                        address: row.address,
+                    };
+                    let mut compressed_address = row.address;
+                    if !row.address.is_zero() {
+                        if row.address < RAM_START_ADDRESS as usize
+                            || row.address % BYTES_PER_INSTRUCTION != 0
+                        {
+                            return Err(unknown_address());
+                        }
+                        // Compress instruction address for more efficient commitment:
+                        compressed_address =
+                            1 + (row.address - RAM_START_ADDRESS as usize) / BYTES_PER_INSTRUCTION;
+                    }
+
+                    *virtual_address = *preprocessing
+                        .virtual_address_map
+                        .get(&(
+                            compressed_address,
+                            row.virtual_sequence_remaining.unwrap_or(0),
+                        ))
+                        .ok_or_else(unknown_address)?;
+
+                    values[0][offset] = F::from_u64(compressed_address as u64).unwrap();
+                    values[1][offset] = F::from_u64(row.bitflags).unwrap();
+                    values[2][offset] = F::from_u64(row.rd).unwrap();
+                    values[3][offset] = F::from_u64(row.rs1).unwrap();
+                    values[4][offset] = F::from_u64(row.rs2).unwrap();
+                    values[5][offset] = F::from_i64(row.imm);
+                }
+                Ok(())
+            })
+            .collect::<Vec<Result<(), JoltError>>>()
+            .into_iter()
+            .collect::<Result<(), JoltError>>()?;
+
         let mut read_cts: Vec<usize> = vec![0; num_ops];
         let mut final_cts: Vec<usize> = vec![0; preprocessing.code_size];
-
-        for (step_index, step) in trace.iter_mut().enumerate() {
-            if !step.bytecode_row.address.is_zero() {
-                assert!(step.bytecode_row.address >= RAM_START_ADDRESS as usize);
-                assert!(step.bytecode_row.address % BYTES_PER_INSTRUCTION == 0);
-                // Compress instruction address for more efficient commitment:
-                step.bytecode_row.address = 1
-                    + (step.bytecode_row.address - RAM_START_ADDRESS as usize)
-                        / BYTES_PER_INSTRUCTION;
-            }
-
-            let virtual_address = preprocessing
-                .virtual_address_map
-                .get(&(
-                    step.bytecode_row.address,
-                    step.bytecode_row.virtual_sequence_remaining.unwrap_or(0),
-                ))
-                .unwrap();
-            a_read_write_usize[step_index] = *virtual_address;
-            let counter = final_cts[*virtual_address];
-            read_cts[step_index] = counter;
-            final_cts[*virtual_address] = counter + 1;
+        for (virtual_address, read_ct) in a_read_write_usize.iter().zip(read_cts.iter_mut()) {
+            *read_ct = final_cts[*virtual_address];
+            final_cts[*virtual_address] += 1;
         }
 
         let a_read_write = DensePolynomial::from_usize(&a_read_write_usize);
 
-        let mut address = vec![];
-        let mut bitflags = vec![];
-        let mut rd = vec![];
-        let mut rs1 = vec![];
-        let mut rs2 = vec![];
-        let mut imm = vec![];
-
-        for step in trace {
-            address.push(F::from_u64(step.bytecode_row.address as u64).unwrap());
-            bitflags.push(F::from_u64(step.bytecode_row.bitflags).unwrap());
-            rd.push(F::from_u64(step.bytecode_row.rd).unwrap());
-            rs1.push(F::from_u64(step.bytecode_row.rs1).unwrap());
-            rs2.push(F::from_u64(step.bytecode_row.rs2).unwrap());
-            imm.push(F::from_i64(step.bytecode_row.imm));
-        }
-
-        let v_read_write = [
-            DensePolynomial::new(address),
-            DensePolynomial::new(bitflags),
-            DensePolynomial::new(rd),
-            DensePolynomial::new(rs1),
-            DensePolynomial::new(rs2),
-            DensePolynomial::new(imm),
-        ];
+        let v_read_write: [DensePolynomial<F>; 6] = v_read_write
+            .into_iter()
+            .map(DensePolynomial::new)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
         let t_read: DensePolynomial<F> = DensePolynomial::from_usize(&read_cts);
         let t_final: DensePolynomial<F> = DensePolynomial::from_usize(&final_cts);
 
@@ -440,17 +655,20 @@ where
             assert_eq!(set_difference.len(), 0);
         }
 
-        BytecodeStuff {
+        Ok(BytecodeStuff {
             a_read_write,
             v_read_write,
             t_read,
             t_final,
             a_init_final: None,
             v_init_final: None,
-        }
+        })
     }
 
-    #[tracing::instrument(skip_all, name = "BytecodePolynomials::validate_bytecode")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BytecodePolynomials::validate_bytecode")
+    )]
     pub fn validate_bytecode(bytecode: &[BytecodeRow], trace: &[BytecodeRow]) {
         let mut bytecode_map: BTreeMap<usize, &BytecodeRow> = BTreeMap::new();
 
@@ -506,7 +724,10 @@ where
         result - *tau
     }
 
-    #[tracing::instrument(skip_all, name = "BytecodePolynomials::compute_leaves")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BytecodePolynomials::compute_leaves")
+    )]
     fn compute_leaves(
         preprocessing: &BytecodePreprocessing<F>,
         polynomials: &Self::Polynomials,
@@ -709,7 +930,10 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{jolt::vm::rv32i_vm::RV32I, poly::commitment::hyrax::HyraxScheme};
+    use crate::{
+        jolt::vm::{rv32i_vm::RV32I, JoltTraceStep},
+        poly::commitment::hyrax::HyraxScheme,
+    };
 
     use super::*;
     use crate::utils::transcript::KeccakTranscript;
@@ -739,6 +963,96 @@ mod tests {
         index * BYTES_PER_INSTRUCTION + RAM_START_ADDRESS as usize
     }
 
+    fn elf_instruction(index: usize, opcode: RV32IM, rd: u64, imm: i64) -> ELFInstruction {
+        ELFInstruction {
+            address: to_ram_address(index) as u64,
+            opcode,
+            rs1: None,
+            rs2: None,
+            rd: Some(rd),
+            imm: Some(imm),
+            virtual_sequence_remaining: None,
+        }
+    }
+
+    #[test]
+    fn dead_code_elimination() {
+        let entrypoint = RAM_START_ADDRESS;
+        let bytecode = vec![
+            // 0: call 3
+            elf_instruction(0, RV32IM::JAL, 1, 12),
+            // 1: jump over 2
+            elf_instruction(1, RV32IM::JAL, 0, 8),
+            // 2: unreachable
+            elf_instruction(2, RV32IM::ADD, 5, 0),
+            // 3: halt
+            elf_instruction(3, RV32IM::JAL, 0, 0),
+            // 4: unreachable, data interleaved with code
+            elf_instruction(4, RV32IM::UNIMPL, 0, 0),
+            // 5: only reachable through a function pointer in memory
+            elf_instruction(5, RV32IM::JALR, 0, 0),
+            // 6: only reachable through a hint
+            elf_instruction(6, RV32IM::JALR, 0, 0),
+        ];
+        let function_pointer = (to_ram_address(5) as u32).to_le_bytes();
+        let memory_init: Vec<(u64, u8)> = function_pointer
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| (to_ram_address(100) as u64 + i as u64, *byte))
+            .collect();
+
+        let live = eliminate_dead_code(bytecode.clone(), &memory_init, entrypoint, &[]);
+        let live_indices: Vec<_> = live
+            .iter()
+            .map(|instruction| bytecode.iter().position(|i| i == instruction).unwrap())
+            .collect();
+        assert_eq!(live_indices, vec![0, 1, 3, 5]);
+
+        let hint = to_ram_address(6) as u64;
+        let live = eliminate_dead_code(bytecode.clone(), &[], entrypoint, &[hint]);
+        let live_indices: Vec<_> = live
+            .iter()
+            .map(|instruction| bytecode.iter().position(|i| i == instruction).unwrap())
+            .collect();
+        assert_eq!(live_indices, vec![0, 1, 3, 6]);
+    }
+
+    #[test]
+    fn stacked_address_offsets() {
+        let entrypoint = RAM_START_ADDRESS;
+        let with_rs1 = |index, opcode, rd, rs1, imm| ELFInstruction {
+            rs1: Some(rs1),
+            ..elf_instruction(index, opcode, rd, imm)
+        };
+        let bytecode = vec![
+            // 0: auipc t0, 0
+            elf_instruction(0, RV32IM::AUIPC, 5, 0),
+            // 1, 2: two offsets of 2040, beyond the reach of a single immediate
+            with_rs1(1, RV32IM::ADDI, 5, 5, 2040),
+            with_rs1(2, RV32IM::ADDI, 5, 5, 2040),
+            // 3: jalr zero, 8(t0), jumping to 1022
+            with_rs1(3, RV32IM::JALR, 0, 5, 8),
+            // 600: unreachable
+            elf_instruction(600, RV32IM::ADD, 6, 0),
+            // 1022: halt
+            elf_instruction(1022, RV32IM::JAL, 0, 0),
+        ];
+
+        let live = eliminate_dead_code(bytecode.clone(), &[], entrypoint, &[]);
+        let live_indices: Vec<_> = live
+            .iter()
+            .map(|instruction| bytecode.iter().position(|i| i == instruction).unwrap())
+            .collect();
+        assert_eq!(live_indices, vec![0, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn jump_target_hints() {
+        let hints = parse_jump_target_hints("0x80000010\n\n# comment\n2147483668 # trailing\n");
+        assert_eq!(hints, Ok(vec![0x80000010, 0x80000014]));
+        assert!(parse_jump_target_hints("main").is_err());
+    }
+
     #[test]
     fn bytecode_stuff_ordering() {
         let program = vec![
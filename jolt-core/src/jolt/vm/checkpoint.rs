@@ -0,0 +1,179 @@
+//! Checkpoints of the prover's progress, so that a long proving job that crashes or is
+//! preempted (e.g. on a spot instance) can resume where it left off instead of starting over.
+//!
+//! [`Jolt::prove_with_checkpoints`](super::Jolt::prove_with_checkpoints) saves a checkpoint
+//! after the commit phase and after each of the stages (see [`super::pipeline`]), and skips
+//! every phase whose checkpoint it finds. Resuming regenerates the witness polynomials, which
+//! is cheap compared to committing to them and proving:
+//!
+//! ```ignore
+//! let checkpoints = ProverCheckpoints::new("/mnt/scratch/fib-checkpoints")?;
+//! let (proof, commitments, _) = RV32IJoltVM::prove_with_checkpoints(
+//!     io_device,
+//!     &trace,
+//!     preprocessing,
+//!     TracePadding::default(),
+//!     StageScheduling::default(),
+//!     Some(&checkpoints),
+//! )?;
+//! checkpoints.clear()?;
+//! ```
+//!
+//! Checkpoints are tagged with a challenge derived from the Fiat-Shamir preamble, which binds
+//! the program I/O and trace length, and checkpoints with another tag are rejected. The tag
+//! does not bind the trace itself, so a checkpoint directory must not be shared by jobs
+//! proving different traces of the same length and I/O.
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use thiserror::Error;
+
+use crate::field::JoltField;
+use crate::poly::opening_proof::ProverOpeningAccumulator;
+use crate::utils::errors::JoltError;
+use crate::utils::transcript::Transcript;
+
+pub(crate) const COMMITMENTS_PHASE: &[u8] = b"commitments";
+
+const EXTENSION: &str = "checkpoint";
+
+#[derive(Error, Debug)]
+pub enum CheckpointError {
+    #[error("Checkpoint I/O failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed checkpoint: {0}")]
+    Serialization(#[from] SerializationError),
+    #[error("Checkpoint {0:?} belongs to another proving job")]
+    JobMismatch(PathBuf),
+}
+
+/// A directory of prover checkpoints.
+#[derive(Clone, Debug)]
+pub struct ProverCheckpoints {
+    dir: PathBuf,
+}
+
+impl ProverCheckpoints {
+    /// Checkpoints in `dir`, which is created if it does not exist.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Removes all checkpoints, e.g. once the proof is done.
+    pub fn clear(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, phase: &[u8]) -> PathBuf {
+        self.dir
+            .join(String::from_utf8_lossy(phase).as_ref())
+            .with_extension(EXTENSION)
+    }
+}
+
+/// The checkpoints of one proving job, identified by `job`. Without a directory, nothing is
+/// saved or loaded.
+pub(crate) struct JobCheckpoints<'a, F: JoltField> {
+    checkpoints: Option<&'a ProverCheckpoints>,
+    job: F,
+}
+
+impl<'a, F: JoltField> JobCheckpoints<'a, F> {
+    pub(crate) fn new(checkpoints: Option<&'a ProverCheckpoints>, job: F) -> Self {
+        Self { checkpoints, job }
+    }
+
+    /// Loads the result of `phase` if it was checkpointed, and otherwise computes it with
+    /// `run` and checkpoints it.
+    pub(crate) fn load_or_run<T: CanonicalSerialize + CanonicalDeserialize>(
+        &self,
+        phase: &[u8],
+        run: impl FnOnce() -> Result<T, JoltError>,
+    ) -> Result<T, JoltError> {
+        let Some(checkpoints) = self.checkpoints else {
+            return run();
+        };
+        let path = checkpoints.path(phase);
+        if path.exists() {
+            let mut reader = BufReader::new(File::open(&path).map_err(CheckpointError::from)?);
+            let job = F::deserialize_compressed(&mut reader).map_err(CheckpointError::from)?;
+            if job != self.job {
+                return Err(CheckpointError::JobMismatch(path).into());
+            }
+            return Ok(T::deserialize_compressed(&mut reader).map_err(CheckpointError::from)?);
+        }
+
+        let result = run()?;
+        self.save(&path, &result)?;
+        Ok(result)
+    }
+
+    /// Writes the checkpoint to a temporary file that is then renamed to `path`, so that a
+    /// job killed while writing it leaves no partial checkpoint behind.
+    fn save<T: CanonicalSerialize>(&self, path: &Path, value: &T) -> Result<(), CheckpointError> {
+        let temporary_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&temporary_path)?);
+        self.job.serialize_compressed(&mut writer)?;
+        value.serialize_compressed(&mut writer)?;
+        writer.into_inner().map_err(io::Error::from)?.sync_all()?;
+        fs::rename(temporary_path, path)?;
+        Ok(())
+    }
+}
+
+/// The result of one of the prover's stages: its proof, the openings it accumulated, and the
+/// challenge its transcript fork is joined by.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub(crate) struct StageCheckpoint<P, F, ProofTranscript>
+where
+    P: CanonicalSerialize + CanonicalDeserialize,
+    F: JoltField,
+    ProofTranscript: Transcript,
+{
+    pub proof: P,
+    pub openings: ProverOpeningAccumulator<F, ProofTranscript>,
+    pub challenge: F,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn resumes_from_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("jolt-checkpoints-{}", std::process::id()));
+        let checkpoints = ProverCheckpoints::new(&dir).unwrap();
+        let job = JobCheckpoints::new(Some(&checkpoints), Fr::from(1u64));
+
+        let first: u64 = job.load_or_run(b"phase", || Ok(7)).unwrap();
+        let resumed: u64 = job
+            .load_or_run(b"phase", || panic!("checkpointed phase was rerun"))
+            .unwrap();
+        assert_eq!((first, resumed), (7, 7));
+
+        let other_job = JobCheckpoints::new(Some(&checkpoints), Fr::from(2u64));
+        assert!(matches!(
+            other_job.load_or_run(b"phase", || Ok(7u64)),
+            Err(JoltError::Checkpoint(CheckpointError::JobMismatch(_)))
+        ));
+
+        checkpoints.clear().unwrap();
+        let rerun: u64 = job.load_or_run(b"phase", || Ok(8)).unwrap();
+        assert_eq!(rerun, 8);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+//! Distributing the commit phase of the prover across machines.
+//!
+//! Committing to the witness polynomials is the prover's bottleneck for large traces, and each
+//! polynomial is committed to independently. A coordinator can therefore split the committed
+//! polynomials into shards, have a worker machine commit to each shard, and assemble the
+//! workers' commitments before it finishes the proof (including all of its Fiat-Shamir
+//! challenges) itself.
+//!
+//! Each worker regenerates the witness from its own copy of the trace (e.g. a
+//! [`TraceFile`](crate::jolt::trace::file::TraceFile)) and runs
+//! [`Jolt::commit_shard`](super::Jolt::commit_shard). The coordinator reaches its workers
+//! through a [`CommitmentWorkers`] implementation over whatever transport it uses, sending
+//! each a [`CommitmentShard`] and receiving its serialized [`ShardCommitments`]:
+//!
+//! ```ignore
+//! // On worker `index`:
+//! let commitments = RV32IJoltVM::commit_shard(&io_device, &trace, &preprocessing, padding, shard)?;
+//! commitments.serialize_compressed(&mut connection)?;
+//!
+//! // On the coordinator:
+//! let (proof, commitments, _) = RV32IJoltVM::prove_distributed(
+//!     io_device, &trace, preprocessing, padding, scheduling, None, Some(&workers),
+//! )?;
+//! ```
+//!
+//! Only the commit phase is distributed. The sum-checks and grand products of the later stages
+//! depend on challenges derived from the commitments and from one another, and are proven by the
+//! coordinator; sharding the layers of the grand products across workers is not supported.
+use std::ops::Range;
+
+use crate::utils::par::prelude::*;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use thiserror::Error;
+
+use crate::lasso::memory_checking::{Initializable, StructuredPolynomialData};
+use crate::poly::commitment::commitment_scheme::CommitmentScheme;
+use crate::utils::transcript::Transcript;
+
+use super::{JoltCommitments, JoltPreprocessing};
+
+#[derive(Error, Debug)]
+pub enum DistributedError {
+    #[error("Worker for shard {shard} failed: {message}")]
+    Worker { shard: usize, message: String },
+    #[error("Got commitments of shard {0:?}, which is not one of the requested shards")]
+    UnexpectedShard(CommitmentShard),
+    #[error("Got no commitments of shard {0}")]
+    MissingShard(usize),
+    #[error("Shard {0} has the wrong number of commitments")]
+    ShardLength(usize),
+}
+
+/// One of `num_shards` shards of the committed polynomials. The read-write and the init-final
+/// polynomials (see [`StructuredPolynomialData`]) are each split into `num_shards` contiguous
+/// ranges of nearly equal length, and shard `index` consists of the `index`-th range of each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentShard {
+    pub index: usize,
+    pub num_shards: usize,
+}
+
+impl CommitmentShard {
+    pub fn new(index: usize, num_shards: usize) -> Self {
+        assert!(index < num_shards, "shard {index} of {num_shards}");
+        Self { index, num_shards }
+    }
+
+    /// The shard's range of `len` polynomials.
+    pub(crate) fn range(&self, len: usize) -> Range<usize> {
+        len * self.index / self.num_shards..len * (self.index + 1) / self.num_shards
+    }
+}
+
+/// A worker's commitments to the polynomials of its shard, in order.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ShardCommitments<Commitment: CanonicalSerialize + CanonicalDeserialize> {
+    pub shard: CommitmentShard,
+    pub read_write: Vec<Commitment>,
+    pub init_final: Vec<Commitment>,
+}
+
+/// The coordinator's connection to its workers.
+pub trait CommitmentWorkers<Commitment: CanonicalSerialize + CanonicalDeserialize>: Sync {
+    /// The number of shards to split the committed polynomials into.
+    fn num_shards(&self) -> usize;
+
+    /// Has a worker commit to `shard`, using [`Jolt::commit_shard`](super::Jolt::commit_shard),
+    /// and returns its commitments. Called concurrently for all shards.
+    fn commit_shard(
+        &self,
+        shard: CommitmentShard,
+    ) -> Result<ShardCommitments<Commitment>, DistributedError>;
+}
+
+/// Commits to the witness by having `workers` commit to each of its shards.
+pub(crate) fn commit_with_workers<const C: usize, PCS, ProofTranscript>(
+    workers: &dyn CommitmentWorkers<PCS::Commitment>,
+    preprocessing: &JoltPreprocessing<C, PCS::Field, PCS, ProofTranscript>,
+) -> Result<JoltCommitments<PCS, ProofTranscript>, DistributedError>
+where
+    PCS: CommitmentScheme<ProofTranscript>,
+    ProofTranscript: Transcript,
+{
+    let num_shards = workers.num_shards();
+    let shards = (0..num_shards)
+        .into_par_iter()
+        .map(|index| workers.commit_shard(CommitmentShard::new(index, num_shards)))
+        .collect::<Result<Vec<_>, _>>()?;
+    assemble_commitments(preprocessing, shards)
+}
+
+/// Assembles the commitments of every shard into the commitments to the whole witness.
+pub fn assemble_commitments<const C: usize, PCS, ProofTranscript>(
+    preprocessing: &JoltPreprocessing<C, PCS::Field, PCS, ProofTranscript>,
+    shards: Vec<ShardCommitments<PCS::Commitment>>,
+) -> Result<JoltCommitments<PCS, ProofTranscript>, DistributedError>
+where
+    PCS: CommitmentScheme<ProofTranscript>,
+    ProofTranscript: Transcript,
+{
+    let num_shards = shards.len();
+    if num_shards == 0 {
+        return Err(DistributedError::MissingShard(0));
+    }
+    let mut shards = shards;
+    shards.sort_by_key(|commitments| commitments.shard.index);
+
+    let mut commitments = JoltCommitments::<PCS, ProofTranscript>::initialize(preprocessing);
+    let num_read_write = commitments.read_write_values().len();
+    let num_init_final = commitments.init_final_values().len();
+    let mut read_write = Vec::with_capacity(num_read_write);
+    let mut init_final = Vec::with_capacity(num_init_final);
+    for (index, shard_commitments) in shards.into_iter().enumerate() {
+        let shard = shard_commitments.shard;
+        if shard != CommitmentShard::new(index, num_shards) {
+            return Err(DistributedError::UnexpectedShard(shard));
+        }
+        if shard_commitments.read_write.len() != shard.range(num_read_write).len()
+            || shard_commitments.init_final.len() != shard.range(num_init_final).len()
+        {
+            return Err(DistributedError::ShardLength(index));
+        }
+        read_write.extend(shard_commitments.read_write);
+        init_final.extend(shard_commitments.init_final);
+    }
+
+    for (dest, src) in commitments
+        .read_write_values_mut()
+        .into_iter()
+        .zip(read_write)
+    {
+        *dest = src;
+    }
+    for (dest, src) in commitments
+        .init_final_values_mut()
+        .into_iter()
+        .zip(init_final)
+    {
+        *dest = src;
+    }
+    Ok(commitments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shards_partition_polynomials() {
+        for num_shards in 1..8 {
+            for len in [0, 1, 5, 64, 100] {
+                let ranges: Vec<Range<usize>> = (0..num_shards)
+                    .map(|index| CommitmentShard::new(index, num_shards).range(len))
+                    .collect();
+                assert_eq!(ranges[0].start, 0);
+                assert_eq!(ranges[num_shards - 1].end, len);
+                for (range, next) in ranges.iter().zip(ranges.iter().skip(1)) {
+                    assert_eq!(range.end, next.start);
+                    assert!(range.len().abs_diff(next.len()) <= 1);
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,14 @@
 use crate::poly::opening_proof::{ProverOpeningAccumulator, VerifierOpeningAccumulator};
 use crate::subprotocols::grand_product::BatchedGrandProduct;
 use crate::subprotocols::sparse_grand_product::ToggledBatchedGrandProduct;
-use crate::utils::thread::{drop_in_background_thread, unsafe_allocate_zero_vec};
+use crate::utils::par::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use crate::utils::par::{self, prelude::*};
+use crate::utils::thread::{
+    drop_in_background_thread, transpose_chunks_mut, unsafe_allocate_zero_vec,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use itertools::{interleave, Itertools};
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
-use rayon::prelude::*;
 use std::marker::PhantomData;
-use tracing::trace_span;
 
 use crate::field::JoltField;
 use crate::jolt::instruction::{JoltInstructionSet, SubtableIndices};
@@ -31,7 +32,7 @@ use crate::{
     utils::{errors::ProofVerifyError, math::Math, transcript::AppendToTranscript},
 };
 
-use super::{JoltCommitments, JoltPolynomials, JoltTraceStep};
+use super::{JoltCommitments, JoltPolynomials, JoltTrace};
 
 #[derive(Debug, Default, CanonicalSerialize, CanonicalDeserialize)]
 pub struct InstructionLookupStuff<T: CanonicalSerialize + CanonicalDeserialize> {
@@ -183,7 +184,10 @@ where
         t * gamma.square() + v * *gamma + a - *tau
     }
 
-    #[tracing::instrument(skip_all, name = "InstructionLookups::compute_leaves")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "InstructionLookups::compute_leaves")
+    )]
     fn compute_leaves(
         preprocessing: &InstructionLookupsPreprocessing<C, F>,
         polynomials: &Self::Polynomials,
@@ -591,7 +595,7 @@ pub struct PrimarySumcheck<F: JoltField, ProofTranscript: Transcript> {
     _marker: PhantomData<ProofTranscript>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct InstructionLookupsPreprocessing<const C: usize, F: JoltField> {
     subtable_to_memory_indices: Vec<Vec<usize>>, // Vec<Range<usize>>?
     instruction_to_memory_indices: Vec<Vec<usize>>,
@@ -602,7 +606,10 @@ pub struct InstructionLookupsPreprocessing<const C: usize, F: JoltField> {
 }
 
 impl<const C: usize, F: JoltField> InstructionLookupsPreprocessing<C, F> {
-    #[tracing::instrument(skip_all, name = "InstructionLookups::preprocess")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "InstructionLookups::preprocess")
+    )]
     pub fn preprocess<const M: usize, InstructionSet, Subtables>() -> Self
     where
         InstructionSet: JoltInstructionSet,
@@ -658,8 +665,20 @@ impl<const C: usize, F: JoltField> InstructionLookupsPreprocessing<C, F> {
         }
     }
 
+    /// The preprocessing without the materialized subtables, which only the prover uses.
+    pub fn without_subtables(&self) -> Self {
+        Self {
+            num_memories: self.num_memories,
+            materialized_subtables: vec![],
+            subtable_to_memory_indices: self.subtable_to_memory_indices.clone(),
+            memory_to_subtable_index: self.memory_to_subtable_index.clone(),
+            memory_to_dimension_index: self.memory_to_dimension_index.clone(),
+            instruction_to_memory_indices: self.instruction_to_memory_indices.clone(),
+        }
+    }
+
     /// Materializes all subtables used by this Jolt instance.
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     fn materialize_subtables<const M: usize, Subtables>() -> Vec<Vec<F>>
     where
         Subtables: JoltSubtableSet<F>,
@@ -684,7 +703,10 @@ where
     const NUM_SUBTABLES: usize = Subtables::COUNT;
     const NUM_INSTRUCTIONS: usize = InstructionSet::COUNT;
 
-    #[tracing::instrument(skip_all, name = "InstructionLookups::prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "InstructionLookups::prove")
+    )]
     pub fn prove<'a>(
         generators: &PCS::Setup,
         polynomials: &'a JoltPolynomials<F>,
@@ -841,14 +863,27 @@ where
     }
 
     /// Constructs the polynomials used in the primary sumcheck and memory checking.
-    #[tracing::instrument(skip_all, name = "InstructionLookups::polynomialize")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "InstructionLookups::polynomialize")
+    )]
     pub fn generate_witness(
         preprocessing: &InstructionLookupsPreprocessing<C, F>,
-        ops: &Vec<JoltTraceStep<InstructionSet>>,
+        ops: &(impl JoltTrace<InstructionSet> + ?Sized),
     ) -> InstructionLookupPolynomials<F> {
         let m: usize = ops.len().next_power_of_two();
 
         let subtable_lookup_indices: Vec<Vec<usize>> = Self::subtable_lookup_indices(ops);
+        // The index of each step's instruction in `InstructionSet`, if it does a lookup
+        let instruction_indices: Vec<Option<usize>> = (0..ops.len())
+            .into_par_iter()
+            .map(|j| {
+                ops.step(j)
+                    .instruction_lookup
+                    .as_ref()
+                    .map(InstructionSet::enum_index)
+            })
+            .collect();
 
         let polys: Vec<(DensePolynomial<F>, DensePolynomial<F>, DensePolynomial<F>)> = (0
             ..preprocessing.num_memories)
@@ -862,10 +897,10 @@ where
                 let mut read_cts_i = vec![0usize; m];
                 let mut subtable_lookups = vec![F::zero(); m];
 
-                for (j, op) in ops.iter().enumerate() {
-                    if let Some(instr) = &op.instruction_lookup {
-                        let memories_used = &preprocessing.instruction_to_memory_indices
-                            [InstructionSet::enum_index(instr)];
+                for (j, instruction_index) in instruction_indices.iter().enumerate() {
+                    if let Some(instruction_index) = instruction_index {
+                        let memories_used =
+                            &preprocessing.instruction_to_memory_indices[*instruction_index];
                         if memories_used.contains(&memory_index) {
                             let memory_address = access_sequence[j];
                             debug_assert!(memory_address < M);
@@ -912,11 +947,17 @@ where
 
         let mut instruction_flag_bitvectors: Vec<Vec<u64>> =
             vec![vec![0u64; m]; Self::NUM_INSTRUCTIONS];
-        for (j, op) in ops.iter().enumerate() {
-            if let Some(instr) = &op.instruction_lookup {
-                instruction_flag_bitvectors[InstructionSet::enum_index(instr)][j] = 1;
-            }
-        }
+        let chunk_size = m.div_ceil(par::current_num_threads());
+        transpose_chunks_mut(&mut instruction_flag_bitvectors, chunk_size)
+            .into_par_iter()
+            .zip(instruction_indices.par_chunks(chunk_size))
+            .for_each(|(mut flag_chunks, instruction_indices)| {
+                for (j, instruction_index) in instruction_indices.iter().enumerate() {
+                    if let Some(instruction_index) = instruction_index {
+                        flag_chunks[*instruction_index][j] = 1;
+                    }
+                }
+            });
 
         let instruction_flag_polys: Vec<DensePolynomial<F>> = instruction_flag_bitvectors
             .par_iter()
@@ -956,7 +997,10 @@ where
     /// - `degree`: Degree of the inner sumcheck polynomial. Corresponds to number of evaluation points per round.
     /// - `transcript`: Fiat-shamir transcript.
     #[allow(clippy::too_many_arguments)]
-    #[tracing::instrument(skip_all, name = "InstructionLookups::prove_primary_sumcheck")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "InstructionLookups::prove_primary_sumcheck")
+    )]
     fn prove_primary_sumcheck(
         preprocessing: &InstructionLookupsPreprocessing<C, F>,
         num_rounds: usize,
@@ -999,9 +1043,9 @@ where
         let r_j = Self::update_primary_sumcheck_transcript(round_uni_poly, transcript);
         random_vars.push(r_j);
 
-        let _bind_span = trace_span!("BindPolys");
-        let _bind_enter = _bind_span.enter();
-        rayon::join(
+        #[cfg(feature = "tracing")]
+        let _bind_enter = tracing::trace_span!("BindPolys").entered();
+        par::join(
             || eq_poly.bound_poly_var_top(&r_j),
             || lookup_outputs_poly.bound_poly_var_top_many_ones(&r_j),
         );
@@ -1013,8 +1057,8 @@ where
             .par_iter()
             .map(|poly| poly.new_poly_from_bound_poly_var_top(&r_j))
             .collect();
+        #[cfg(feature = "tracing")]
         drop(_bind_enter);
-        drop(_bind_span);
 
         for _round in 1..num_rounds {
             let round_uni_poly = Self::primary_sumcheck_inner_loop(
@@ -1030,9 +1074,9 @@ where
             random_vars.push(r_j);
 
             // Bind all polys
-            let _bind_span = trace_span!("BindPolys");
-            let _bind_enter = _bind_span.enter();
-            rayon::join(
+            #[cfg(feature = "tracing")]
+            let _bind_enter = tracing::trace_span!("BindPolys").entered();
+            par::join(
                 || eq_poly.bound_poly_var_top(&r_j),
                 || lookup_outputs_poly.bound_poly_var_top_many_ones(&r_j),
             );
@@ -1043,8 +1087,8 @@ where
                 .par_iter_mut()
                 .for_each(|poly| poly.bound_poly_var_top_many_ones(&r_j));
 
+            #[cfg(feature = "tracing")]
             drop(_bind_enter);
-            drop(_bind_span);
         } // End rounds
 
         // Pass evaluations at point r back in proof:
@@ -1069,7 +1113,10 @@ where
         )
     }
 
-    #[tracing::instrument(skip_all, name = "InstructionLookups::primary_sumcheck_inner_loop")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "InstructionLookups::primary_sumcheck_inner_loop")
+    )]
     fn primary_sumcheck_inner_loop(
         preprocessing: &InstructionLookupsPreprocessing<C, F>,
         eq_poly: &DensePolynomial<F>,
@@ -1284,27 +1331,26 @@ where
 
     /// Converts each instruction in `ops` into its corresponding subtable lookup indices.
     /// The output is `C` vectors, each of length `m`.
-    fn subtable_lookup_indices(ops: &[JoltTraceStep<InstructionSet>]) -> Vec<Vec<usize>> {
+    fn subtable_lookup_indices(ops: &(impl JoltTrace<InstructionSet> + ?Sized)) -> Vec<Vec<usize>> {
         let m = ops.len().next_power_of_two();
         let log_M = M.log_2();
-        let chunked_indices: Vec<Vec<usize>> = ops
-            .iter()
-            .map(|op| {
-                if let Some(instr) = &op.instruction_lookup {
-                    instr.to_indices(C, log_M)
-                } else {
-                    vec![0; C]
+        let mut subtable_lookup_indices: Vec<Vec<usize>> = vec![vec![0; m]; C];
+        let chunk_size = m.div_ceil(par::current_num_threads());
+        transpose_chunks_mut(&mut subtable_lookup_indices, chunk_size)
+            .into_par_iter()
+            .enumerate()
+            .for_each(|(chunk_index, mut access_sequences)| {
+                let start = chunk_index * chunk_size;
+                let end = (start + chunk_size).min(ops.len());
+                for j in start..end {
+                    if let Some(instr) = &ops.step(j).instruction_lookup {
+                        let indices = instr.to_indices(C, log_M);
+                        for (access_sequence, index) in access_sequences.iter_mut().zip(indices) {
+                            access_sequence[j - start] = index;
+                        }
+                    }
                 }
-            })
-            .collect();
-
-        let mut subtable_lookup_indices: Vec<Vec<usize>> = Vec::with_capacity(C);
-        for i in 0..C {
-            let mut access_sequence: Vec<usize> =
-                chunked_indices.iter().map(|chunks| chunks[i]).collect();
-            access_sequence.resize(m, 0);
-            subtable_lookup_indices.push(access_sequence);
-        }
+            });
         subtable_lookup_indices
     }
 
@@ -1318,12 +1364,15 @@ where
         vec![read_write_shape, init_final_shape]
     }
 
-    #[tracing::instrument(skip_all, name = "InstructionLookupsProof::compute_lookup_outputs")]
-    fn compute_lookup_outputs(instructions: &Vec<JoltTraceStep<InstructionSet>>) -> Vec<F> {
-        instructions
-            .par_iter()
-            .map(|op| {
-                if let Some(instr) = &op.instruction_lookup {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "InstructionLookupsProof::compute_lookup_outputs")
+    )]
+    fn compute_lookup_outputs(instructions: &(impl JoltTrace<InstructionSet> + ?Sized)) -> Vec<F> {
+        (0..instructions.len())
+            .into_par_iter()
+            .map(|index| {
+                if let Some(instr) = &instructions.step(index).instruction_lookup {
                     F::from_u64(instr.lookup_entry()).unwrap()
                 } else {
                     F::zero()
@@ -5,11 +5,18 @@ use crate::field::JoltField;
 use crate::poly::opening_proof::{
     ProverOpeningAccumulator, ReducedOpeningProof, VerifierOpeningAccumulator,
 };
+use crate::r1cs::builder::CombinedUniformBuilder;
 use crate::r1cs::constraints::R1CSConstraints;
 use crate::r1cs::spartan::{self, UniformSpartanProof};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use crate::utils::par::{self, prelude::*};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use common::rv_trace::{MemoryLayout, NUM_CIRCUIT_FLAGS};
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use strum::EnumCount;
 use timestamp_range_check::TimestampRangeCheckStuff;
@@ -26,21 +33,29 @@ use crate::jolt::{
 use crate::lasso::memory_checking::{
     Initializable, MemoryCheckingProver, MemoryCheckingVerifier, StructuredPolynomialData,
 };
-use crate::poly::commitment::commitment_scheme::{BatchType, CommitmentScheme};
+use crate::poly::buffer_pool::BufferPool;
+use crate::poly::commitment::commitment_scheme::{
+    AggregatableCommitmentScheme, BatchType, CommitShape, CommitmentScheme,
+};
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::r1cs::inputs::{ConstraintInput, R1CSPolynomials, R1CSProof, R1CSStuff};
-use crate::utils::errors::ProofVerifyError;
+use crate::utils::errors::{JoltError, ProofVerifyError};
+use crate::utils::field_encoding::{append_bytes, ToFieldElements};
+use crate::utils::prover_config::ProverConfig;
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::{AppendToTranscript, Transcript};
 use common::{
     constants::MEMORY_OPS_PER_INSTRUCTION,
-    rv_trace::{ELFInstruction, JoltDevice, MemoryOp},
+    rv_trace::{ELFInstruction, JoltDevice, MemoryOp, RVTraceRow, RV32IM},
 };
 
 use self::bytecode::{BytecodePreprocessing, BytecodeProof, BytecodeRow, BytecodeStuff};
+use self::checkpoint::{JobCheckpoints, ProverCheckpoints, StageCheckpoint};
+use self::distributed::{CommitmentShard, CommitmentWorkers, ShardCommitments};
 use self::instruction_lookups::{
     InstructionLookupStuff, InstructionLookupsPreprocessing, InstructionLookupsProof,
 };
+use self::pipeline::StageScheduling;
 use self::read_write_memory::{
     ReadWriteMemoryPolynomials, ReadWriteMemoryPreprocessing, ReadWriteMemoryProof,
     ReadWriteMemoryStuff,
@@ -68,6 +83,230 @@ where
     pub memory_layout: MemoryLayout,
 }
 
+/// Preprocessing is serializable for commitment schemes whose setup is, so that it can be cached
+/// across runs (see [`crate::host::Program::load_or_preprocess`]).
+impl<const C: usize, F, PCS, ProofTranscript> CanonicalSerialize
+    for JoltPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    PCS::Setup: CanonicalSerialize,
+    ProofTranscript: Transcript,
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.generators.serialize_with_mode(&mut writer, compress)?;
+        self.instruction_lookups
+            .serialize_with_mode(&mut writer, compress)?;
+        self.bytecode.serialize_with_mode(&mut writer, compress)?;
+        self.read_write_memory
+            .serialize_with_mode(&mut writer, compress)?;
+        self.memory_layout
+            .serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.generators.serialized_size(compress)
+            + self.instruction_lookups.serialized_size(compress)
+            + self.bytecode.serialized_size(compress)
+            + self.read_write_memory.serialized_size(compress)
+            + self.memory_layout.serialized_size(compress)
+    }
+}
+
+impl<const C: usize, F, PCS, ProofTranscript> Valid
+    for JoltPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    PCS::Setup: Valid,
+    ProofTranscript: Transcript,
+{
+    fn check(&self) -> Result<(), SerializationError> {
+        self.generators.check()?;
+        self.instruction_lookups.check()?;
+        self.bytecode.check()?;
+        self.read_write_memory.check()?;
+        self.memory_layout.check()
+    }
+}
+
+impl<const C: usize, F, PCS, ProofTranscript> CanonicalDeserialize
+    for JoltPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    PCS::Setup: CanonicalDeserialize,
+    ProofTranscript: Transcript,
+{
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self {
+            generators: PCS::Setup::deserialize_with_mode(&mut reader, compress, validate)?,
+            instruction_lookups: InstructionLookupsPreprocessing::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            bytecode: BytecodePreprocessing::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            read_write_memory: ReadWriteMemoryPreprocessing::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            memory_layout: MemoryLayout::deserialize_with_mode(&mut reader, compress, validate)?,
+        })
+    }
+}
+
+impl<const C: usize, F, PCS, ProofTranscript> JoltPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    /// The verifier's part of the preprocessing, without the prover's part of the commitment
+    /// scheme's setup and the materialized subtables. It is much smaller than the preprocessing,
+    /// so it is what should be distributed to verifiers.
+    pub fn verifier_preprocessing(&self) -> JoltVerifierPreprocessing<C, F, PCS, ProofTranscript> {
+        JoltVerifierPreprocessing {
+            generators: PCS::verifier_setup(&self.generators),
+            instruction_lookups: self.instruction_lookups.without_subtables(),
+            bytecode: self.bytecode.clone(),
+            read_write_memory: self.read_write_memory.clone(),
+            memory_layout: self.memory_layout.clone(),
+        }
+    }
+}
+
+/// What the verifier needs of the preprocessing; see [`JoltPreprocessing::verifier_preprocessing`].
+#[derive(Clone)]
+pub struct JoltVerifierPreprocessing<const C: usize, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    pub generators: PCS::Setup,
+    pub instruction_lookups: InstructionLookupsPreprocessing<C, F>,
+    pub bytecode: BytecodePreprocessing<F>,
+    pub read_write_memory: ReadWriteMemoryPreprocessing,
+    pub memory_layout: MemoryLayout,
+}
+
+/// Keeps the full setup, which the verifier's debug checks in tests commit with.
+impl<const C: usize, F, PCS, ProofTranscript> From<JoltPreprocessing<C, F, PCS, ProofTranscript>>
+    for JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    fn from(preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>) -> Self {
+        Self {
+            generators: preprocessing.generators,
+            instruction_lookups: preprocessing.instruction_lookups.without_subtables(),
+            bytecode: preprocessing.bytecode,
+            read_write_memory: preprocessing.read_write_memory,
+            memory_layout: preprocessing.memory_layout,
+        }
+    }
+}
+
+impl<const C: usize, F, PCS, ProofTranscript> CanonicalSerialize
+    for JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    PCS::Setup: CanonicalSerialize,
+    ProofTranscript: Transcript,
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.generators.serialize_with_mode(&mut writer, compress)?;
+        self.instruction_lookups
+            .serialize_with_mode(&mut writer, compress)?;
+        self.bytecode.serialize_with_mode(&mut writer, compress)?;
+        self.read_write_memory
+            .serialize_with_mode(&mut writer, compress)?;
+        self.memory_layout
+            .serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.generators.serialized_size(compress)
+            + self.instruction_lookups.serialized_size(compress)
+            + self.bytecode.serialized_size(compress)
+            + self.read_write_memory.serialized_size(compress)
+            + self.memory_layout.serialized_size(compress)
+    }
+}
+
+impl<const C: usize, F, PCS, ProofTranscript> Valid
+    for JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    PCS::Setup: Valid,
+    ProofTranscript: Transcript,
+{
+    fn check(&self) -> Result<(), SerializationError> {
+        self.generators.check()?;
+        self.instruction_lookups.check()?;
+        self.bytecode.check()?;
+        self.read_write_memory.check()?;
+        self.memory_layout.check()
+    }
+}
+
+impl<const C: usize, F, PCS, ProofTranscript> CanonicalDeserialize
+    for JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    PCS::Setup: CanonicalDeserialize,
+    ProofTranscript: Transcript,
+{
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self {
+            generators: PCS::Setup::deserialize_with_mode(&mut reader, compress, validate)?,
+            instruction_lookups: InstructionLookupsPreprocessing::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            bytecode: BytecodePreprocessing::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            read_write_memory: ReadWriteMemoryPreprocessing::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            memory_layout: MemoryLayout::deserialize_with_mode(&mut reader, compress, validate)?,
+        })
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct JoltTraceStep<InstructionSet: JoltInstructionSet> {
     pub instruction_lookup: Option<InstructionSet>,
@@ -100,10 +339,155 @@ impl<InstructionSet: JoltInstructionSet> JoltTraceStep<InstructionSet> {
         }
     }
 
-    fn pad(trace: &mut Vec<Self>) {
-        let unpadded_length = trace.len();
-        let padded_length = unpadded_length.next_power_of_two();
-        trace.resize(padded_length, Self::no_op());
+    /// The step of an executed instruction, after the expansion of virtual sequences. Its
+    /// instruction lookup is `None` if the instruction does not use lookups.
+    pub fn from_row(row: &RVTraceRow) -> Self
+    where
+        InstructionSet: for<'a> TryFrom<&'a RVTraceRow>,
+    {
+        JoltTraceStep {
+            instruction_lookup: InstructionSet::try_from(row).ok(),
+            bytecode_row: BytecodeRow::from_instruction::<InstructionSet>(&row.instruction),
+            memory_ops: row.into(),
+            circuit_flags: row.instruction.to_circuit_flags(),
+        }
+    }
+}
+
+/// An execution trace to prove, from which the prover reads its witness one step at a time:
+/// the steps themselves, or a [`TraceFile`] mapped from disk, for traces bigger than RAM.
+///
+/// [`TraceFile`]: crate::jolt::trace::file::TraceFile
+pub trait JoltTrace<InstructionSet: JoltInstructionSet>: Sync {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn step(&self, index: usize) -> Cow<'_, JoltTraceStep<InstructionSet>>;
+
+    fn steps(&self) -> impl Iterator<Item = Cow<'_, JoltTraceStep<InstructionSet>>> + '_ {
+        (0..self.len()).map(|index| self.step(index))
+    }
+}
+
+impl<InstructionSet: JoltInstructionSet> JoltTrace<InstructionSet>
+    for [JoltTraceStep<InstructionSet>]
+{
+    fn len(&self) -> usize {
+        <[_]>::len(self)
+    }
+
+    fn step(&self, index: usize) -> Cow<'_, JoltTraceStep<InstructionSet>> {
+        Cow::Borrowed(&self[index])
+    }
+}
+
+impl<InstructionSet: JoltInstructionSet> JoltTrace<InstructionSet>
+    for Vec<JoltTraceStep<InstructionSet>>
+{
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn step(&self, index: usize) -> Cow<'_, JoltTraceStep<InstructionSet>> {
+        Cow::Borrowed(&self[index])
+    }
+}
+
+/// A trace padded with no-ops to `padded_length` steps, without copying it.
+struct PaddedTrace<'a, T: ?Sized> {
+    trace: &'a T,
+    padded_length: usize,
+}
+
+impl<'a, T: ?Sized> PaddedTrace<'a, T> {
+    fn new<InstructionSet: JoltInstructionSet>(trace: &'a T, padded_length: usize) -> Self
+    where
+        T: JoltTrace<InstructionSet>,
+    {
+        assert!(padded_length.is_power_of_two());
+        assert!(padded_length >= trace.len());
+        Self {
+            trace,
+            padded_length,
+        }
+    }
+}
+
+impl<InstructionSet, T> JoltTrace<InstructionSet> for PaddedTrace<'_, T>
+where
+    InstructionSet: JoltInstructionSet,
+    T: JoltTrace<InstructionSet> + ?Sized,
+{
+    fn len(&self) -> usize {
+        self.padded_length
+    }
+
+    fn step(&self, index: usize) -> Cow<'_, JoltTraceStep<InstructionSet>> {
+        assert!(index < self.padded_length);
+        if index < self.trace.len() {
+            self.trace.step(index)
+        } else {
+            Cow::Owned(JoltTraceStep::no_op())
+        }
+    }
+}
+
+/// Determines how the execution trace is padded (with no-ops) before proving.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TracePadding {
+    /// Pads the trace to the next power of two. The proof reveals the exact
+    /// trace length.
+    #[default]
+    NextPowerOfTwo,
+    /// Pads the trace to the next power of two that is at least `min_length`, and
+    /// only reveals this padded length in the proof. The read-write memory is likewise
+    /// padded to at least `min_memory_size` words, so that the size of the memory
+    /// polynomials doesn't reveal how much memory the execution touched.
+    ///
+    /// For applications where the execution length itself may leak information about
+    /// private inputs, setting `min_length` and `min_memory_size` to the
+    /// `max_trace_length` and `max_memory_address` used in preprocessing hides both
+    /// entirely (at the cost of always proving a trace and memory of that size).
+    ///
+    /// These are the only dimensions of a proof that depend on the execution: Jolt proves
+    /// a trace as a single proof, so there is no chunk count or carried state to hide.
+    Hidden {
+        min_length: usize,
+        min_memory_size: usize,
+    },
+}
+
+impl TracePadding {
+    /// Returns the length to which a trace of length `trace_length` is padded.
+    pub fn padded_length(&self, trace_length: usize) -> usize {
+        match self {
+            TracePadding::NextPowerOfTwo => trace_length.next_power_of_two(),
+            TracePadding::Hidden { min_length } => {
+                trace_length.max(*min_length).next_power_of_two()
+            }
+        }
+    }
+
+    /// Returns the trace length to be included in the proof (and transcript) for a
+    /// trace of length `trace_length`.
+    pub fn revealed_length(&self, trace_length: usize) -> usize {
+        match self {
+            TracePadding::NextPowerOfTwo => trace_length,
+            TracePadding::Hidden { .. } => self.padded_length(trace_length),
+        }
+    }
+
+    /// Returns the minimum size (in words) of the read-write memory witness.
+    pub fn min_memory_size(&self) -> usize {
+        match self {
+            TracePadding::NextPowerOfTwo => 0,
+            TracePadding::Hidden {
+                min_memory_size, ..
+            } => *min_memory_size,
+        }
     }
 }
 
@@ -131,10 +515,29 @@ pub struct JoltProof<
     pub read_write_memory: ReadWriteMemoryProof<F, PCS, ProofTranscript>,
     pub instruction_lookups:
         InstructionLookupsProof<C, M, F, PCS, InstructionSet, Subtables, ProofTranscript>,
-    pub r1cs: UniformSpartanProof<C, I, F, ProofTranscript>,
+    pub r1cs: UniformSpartanProof<C, I, F, PCS, ProofTranscript>,
     pub opening_proof: ReducedOpeningProof<F, PCS, ProofTranscript>,
 }
 
+impl<const C: usize, const M: usize, I, F, PCS, InstructionSet, Subtables, ProofTranscript>
+    ToFieldElements<F> for JoltProof<C, M, I, F, PCS, InstructionSet, Subtables, ProofTranscript>
+where
+    I: ConstraintInput,
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    InstructionSet: JoltInstructionSet,
+    Subtables: JoltSubtableSet<F>,
+    ProofTranscript: Transcript,
+{
+    /// The sub-proofs are generic over the commitment scheme, whose proofs need not be
+    /// encodable, so the proof is encoded as its canonical serialization, in limbs.
+    fn append_field_elements(&self, out: &mut Vec<F>) {
+        let mut bytes = vec![];
+        self.serialize_compressed(&mut bytes).unwrap();
+        append_bytes(&bytes, out);
+    }
+}
+
 #[derive(Default, CanonicalSerialize, CanonicalDeserialize)]
 pub struct JoltStuff<T: CanonicalSerialize + CanonicalDeserialize + Sync> {
     pub(crate) bytecode: BytecodeStuff<T>,
@@ -229,7 +632,10 @@ impl<
 }
 
 impl<F: JoltField> JoltPolynomials<F> {
-    #[tracing::instrument(skip_all, name = "JoltPolynomials::commit")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "JoltPolynomials::commit")
+    )]
     pub fn commit<const C: usize, PCS, ProofTranscript>(
         &self,
         preprocessing: &JoltPreprocessing<C, F, PCS, ProofTranscript>,
@@ -238,33 +644,61 @@ impl<F: JoltField> JoltPolynomials<F> {
         PCS: CommitmentScheme<ProofTranscript, Field = F>,
         ProofTranscript: Transcript,
     {
-        let mut commitments = JoltCommitments::<PCS, ProofTranscript>::initialize(preprocessing);
+        let commitments = self.commit_shard(preprocessing, CommitmentShard::new(0, 1));
+        distributed::assemble_commitments(preprocessing, vec![commitments]).unwrap()
+    }
 
+    /// Commits to the polynomials of `shard` (see [`distributed`]).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "JoltPolynomials::commit_shard")
+    )]
+    pub fn commit_shard<const C: usize, PCS, ProofTranscript>(
+        &self,
+        preprocessing: &JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        shard: CommitmentShard,
+    ) -> ShardCommitments<PCS::Commitment>
+    where
+        PCS: CommitmentScheme<ProofTranscript, Field = F>,
+        ProofTranscript: Transcript,
+    {
         let trace_polys = self.read_write_values();
-        let trace_comitments =
-            PCS::batch_commit_polys_ref(&trace_polys, &preprocessing.generators, BatchType::Big);
-        commitments
-            .read_write_values_mut()
-            .into_iter()
-            .zip(trace_comitments.into_iter())
-            .for_each(|(dest, src)| *dest = src);
-
-        commitments.bytecode.t_final =
-            PCS::commit(&self.bytecode.t_final, &preprocessing.generators);
-        (
-            commitments.read_write_memory.v_final,
-            commitments.read_write_memory.t_final,
-        ) = rayon::join(
-            || PCS::commit(&self.read_write_memory.v_final, &preprocessing.generators),
-            || PCS::commit(&self.read_write_memory.t_final, &preprocessing.generators),
+        let trace_polys = &trace_polys[shard.range(trace_polys.len())];
+        let read_write =
+            PCS::batch_commit_polys_ref(trace_polys, &preprocessing.generators, BatchType::Big);
+
+        // The init-final polynomials are bytecode t_final, memory v_final and t_final, which are
+        // committed to individually, followed by the instruction lookups' final_cts, whose
+        // small entries are committed to in a batch
+        let init_final_polys = self.init_final_values();
+        let range = shard.range(init_final_polys.len());
+        let first_final_cts = init_final_polys.len() - self.instruction_lookups.final_cts.len();
+        let (individual_range, final_cts_range) = (
+            range.start.min(first_final_cts)..range.end.min(first_final_cts),
+            range.start.max(first_final_cts)..range.end.max(first_final_cts),
         );
-        commitments.instruction_lookups.final_cts = PCS::batch_commit_polys(
-            &self.instruction_lookups.final_cts,
-            &preprocessing.generators,
-            BatchType::Big,
+        let (mut init_final, final_cts): (Vec<PCS::Commitment>, Vec<PCS::Commitment>) = par::join(
+            || {
+                init_final_polys[individual_range]
+                    .par_iter()
+                    .map(|poly| PCS::commit(poly, &preprocessing.generators))
+                    .collect()
+            },
+            || {
+                PCS::batch_commit_polys_ref(
+                    &init_final_polys[final_cts_range],
+                    &preprocessing.generators,
+                    BatchType::SmallScalars,
+                )
+            },
         );
+        init_final.extend(final_cts);
 
-        commitments
+        ShardCommitments {
+            shard,
+            read_write,
+            init_final,
+        }
     }
 }
 
@@ -278,7 +712,10 @@ where
     type Subtables: JoltSubtableSet<F>;
     type Constraints: R1CSConstraints<C, F>;
 
-    #[tracing::instrument(skip_all, name = "Jolt::preprocess")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Jolt::preprocess")
+    )]
     fn preprocess(
         bytecode: Vec<ELFInstruction>,
         memory_layout: MemoryLayout,
@@ -287,6 +724,24 @@ where
         max_memory_address: usize,
         max_trace_length: usize,
     ) -> JoltPreprocessing<C, F, PCS, ProofTranscript> {
+        let commitment_shapes =
+            Self::commitment_shapes(max_bytecode_size, max_memory_address, max_trace_length);
+        Self::preprocess_with_setup(
+            bytecode,
+            memory_layout,
+            memory_init,
+            max_bytecode_size,
+            PCS::setup(&commitment_shapes),
+        )
+    }
+
+    /// The shapes of the polynomials committed to in proofs of executions within the given
+    /// bounds, which the commitment scheme's setup must support.
+    fn commitment_shapes(
+        max_bytecode_size: usize,
+        max_memory_address: usize,
+        max_trace_length: usize,
+    ) -> Vec<CommitShape> {
         let bytecode_commitment_shapes = BytecodeProof::<F, PCS, ProofTranscript>::commit_shapes(
             max_bytecode_size,
             max_trace_length,
@@ -308,6 +763,29 @@ where
             ProofTranscript,
         >::commitment_shapes(max_trace_length);
 
+        [
+            bytecode_commitment_shapes,
+            ram_commitment_shapes,
+            timestamp_range_check_commitment_shapes,
+            instruction_lookups_commitment_shapes,
+        ]
+        .concat()
+    }
+
+    /// Like [`Jolt::preprocess`], but with a setup of the commitment scheme obtained elsewhere,
+    /// e.g. from the SRS of a powers-of-tau ceremony. The setup must support
+    /// [`Jolt::commitment_shapes`] for the bounds the program is proven within.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Jolt::preprocess_with_setup")
+    )]
+    fn preprocess_with_setup(
+        bytecode: Vec<ELFInstruction>,
+        memory_layout: MemoryLayout,
+        memory_init: Vec<(u64, u8)>,
+        max_bytecode_size: usize,
+        generators: PCS::Setup,
+    ) -> JoltPreprocessing<C, F, PCS, ProofTranscript> {
         let instruction_lookups_preprocessing = InstructionLookupsPreprocessing::preprocess::<
             M,
             Self::InstructionSet,
@@ -319,33 +797,31 @@ where
         let bytecode_rows: Vec<BytecodeRow> = bytecode
             .into_iter()
             .flat_map(|instruction| match instruction.opcode {
-                tracer::RV32IM::MULH => MULHInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::MULHSU => MULHSUInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::DIV => DIVInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::DIVU => DIVUInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::REM => REMInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::REMU => REMUInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::SH => SHInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::SB => SBInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::LBU => LBUInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::LHU => LHUInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::LB => LBInstruction::<32>::virtual_sequence(instruction),
-                tracer::RV32IM::LH => LHInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::MULH => MULHInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::MULHSU => MULHSUInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::DIV => DIVInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::DIVU => DIVUInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::REM => REMInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::REMU => REMUInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::SH => SHInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::SB => SBInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::LBU => LBUInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::LHU => LHUInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::LB => LBInstruction::<32>::virtual_sequence(instruction),
+                RV32IM::LH => LHInstruction::<32>::virtual_sequence(instruction),
                 _ => vec![instruction],
             })
             .map(|instruction| BytecodeRow::from_instruction::<Self::InstructionSet>(&instruction))
             .collect();
+        assert!(
+            bytecode_rows.len() <= max_bytecode_size,
+            "the program has {} instructions (after expanding virtual sequences), more than \
+             max_bytecode_size = {}",
+            bytecode_rows.len(),
+            max_bytecode_size
+        );
         let bytecode_preprocessing = BytecodePreprocessing::<F>::preprocess(bytecode_rows);
 
-        let commitment_shapes = [
-            bytecode_commitment_shapes,
-            ram_commitment_shapes,
-            timestamp_range_check_commitment_shapes,
-            instruction_lookups_commitment_shapes,
-        ]
-        .concat();
-        let generators = PCS::setup(&commitment_shapes);
-
         JoltPreprocessing {
             generators,
             memory_layout,
@@ -355,30 +831,196 @@ where
         }
     }
 
-    #[tracing::instrument(skip_all, name = "Jolt::prove")]
+    /// Proves the execution `trace` of the preprocessed program. Fails if `program_io` does not
+    /// fit the preprocessing's memory layout, or the trace is not of the preprocessed program.
     fn prove(
         program_io: JoltDevice,
-        mut trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        trace: Vec<JoltTraceStep<Self::InstructionSet>>,
         preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
-    ) -> (
-        JoltProof<
-            C,
-            M,
-            <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
-            F,
-            PCS,
-            Self::InstructionSet,
-            Self::Subtables,
-            ProofTranscript,
-        >,
-        JoltCommitments<PCS, ProofTranscript>,
-        Option<ProverDebugInfo<F, ProofTranscript>>,
-    ) {
-        let trace_length = trace.len();
-        let padded_trace_length = trace_length.next_power_of_two();
-        println!("Trace length: {}", trace_length);
+    ) -> Result<
+        (
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+            Option<ProverDebugInfo<F, ProofTranscript>>,
+        ),
+        JoltError,
+    > {
+        Self::prove_with_padding(program_io, trace, preprocessing, TracePadding::default())
+    }
 
-        JoltTraceStep::pad(&mut trace);
+    fn prove_with_padding(
+        program_io: JoltDevice,
+        trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        padding: TracePadding,
+    ) -> Result<
+        (
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+            Option<ProverDebugInfo<F, ProofTranscript>>,
+        ),
+        JoltError,
+    > {
+        Self::prove_with_options(
+            program_io,
+            trace,
+            preprocessing,
+            padding,
+            StageScheduling::default(),
+        )
+    }
+
+    /// Proves with the given trace `padding`, scheduling the prover's stages according to
+    /// `scheduling`. The proof does not depend on the scheduling.
+    fn prove_with_options(
+        program_io: JoltDevice,
+        trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        padding: TracePadding,
+        scheduling: StageScheduling,
+    ) -> Result<
+        (
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+            Option<ProverDebugInfo<F, ProofTranscript>>,
+        ),
+        JoltError,
+    > {
+        Self::prove_trace(program_io, &trace, preprocessing, padding, scheduling)
+    }
+
+    /// Proves `trace`, which may be a [`TraceFile`](crate::jolt::trace::file::TraceFile)
+    /// mapped from disk rather than held in memory, with the given `padding` and `scheduling`
+    /// (see [`Jolt::prove_with_options`]). The trace is padded without being copied.
+    fn prove_trace<T: JoltTrace<Self::InstructionSet> + ?Sized>(
+        program_io: JoltDevice,
+        trace: &T,
+        preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        padding: TracePadding,
+        scheduling: StageScheduling,
+    ) -> Result<
+        (
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+            Option<ProverDebugInfo<F, ProofTranscript>>,
+        ),
+        JoltError,
+    > {
+        Self::prove_with_checkpoints(program_io, trace, preprocessing, padding, scheduling, None)
+    }
+
+    /// Like [`Jolt::prove_trace`], but saves the prover's progress to `checkpoints` after the
+    /// commit phase and after each stage, and resumes from the checkpoints a previous run of
+    /// the same job left there (see [`checkpoint`]). The proof is the same as without
+    /// checkpoints.
+    fn prove_with_checkpoints<T: JoltTrace<Self::InstructionSet> + ?Sized>(
+        program_io: JoltDevice,
+        trace: &T,
+        preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        padding: TracePadding,
+        scheduling: StageScheduling,
+        checkpoints: Option<&ProverCheckpoints>,
+    ) -> Result<
+        (
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+            Option<ProverDebugInfo<F, ProofTranscript>>,
+        ),
+        JoltError,
+    > {
+        Self::prove_distributed(
+            program_io,
+            trace,
+            preprocessing,
+            padding,
+            scheduling,
+            checkpoints,
+            None,
+        )
+    }
+
+    /// Like [`Jolt::prove_with_checkpoints`], but if `workers` are given, has them commit to
+    /// the witness polynomials instead of committing to them locally (see [`distributed`]).
+    /// The proof is the same as if the polynomials were committed to locally.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Jolt::prove")
+    )]
+    fn prove_distributed<T: JoltTrace<Self::InstructionSet> + ?Sized>(
+        program_io: JoltDevice,
+        trace: &T,
+        preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        padding: TracePadding,
+        scheduling: StageScheduling,
+        checkpoints: Option<&ProverCheckpoints>,
+        workers: Option<&dyn CommitmentWorkers<PCS::Commitment>>,
+    ) -> Result<
+        (
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+            Option<ProverDebugInfo<F, ProofTranscript>>,
+        ),
+        JoltError,
+    > {
+        let padded_trace_length = padding.padded_length(trace.len());
+        let trace_length = padding.revealed_length(trace.len());
+        // Only the revealed length is logged, so that `TracePadding::Hidden` hides the real one
+        #[cfg(feature = "tracing")]
+        tracing::info!(trace_length, padded_trace_length, "Proving trace");
 
         let mut transcript = ProofTranscript::new(b"Jolt transcript");
         Self::fiat_shamir_preamble(
@@ -387,6 +1029,236 @@ where
             &program_io.memory_layout,
             trace_length,
         );
+        let checkpoints = JobCheckpoints::new(
+            checkpoints,
+            transcript.fork(b"checkpoints").challenge_scalar::<F>(),
+        );
+
+        let (jolt_polynomials, r1cs_builder) =
+            Self::generate_witness(&program_io, trace, &preprocessing, padding)?;
+        let spartan_key = spartan::UniformSpartanProof::<
+            C,
+            <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+            F,
+            PCS,
+            ProofTranscript,
+        >::setup(&r1cs_builder, padded_trace_length);
+
+        let jolt_commitments =
+            checkpoints.load_or_run(checkpoint::COMMITMENTS_PHASE, || match workers {
+                None => Ok(jolt_polynomials.commit::<C, PCS, ProofTranscript>(&preprocessing)),
+                Some(workers) => Ok(distributed::commit_with_workers(workers, &preprocessing)?),
+            })?;
+
+        transcript.append_scalar(&spartan_key.digest());
+
+        jolt_commitments
+            .read_write_values()
+            .iter()
+            .for_each(|value| value.append_to_transcript(&mut transcript));
+        jolt_commitments
+            .init_final_values()
+            .iter()
+            .for_each(|value| value.append_to_transcript(&mut transcript));
+
+        let prove_bytecode = || {
+            checkpoints.load_or_run(pipeline::BYTECODE_STAGE, || {
+                let mut transcript = transcript.fork(pipeline::BYTECODE_STAGE);
+                let mut openings = ProverOpeningAccumulator::for_scheme::<PCS>();
+                let proof = BytecodeProof::prove_memory_checking(
+                    &preprocessing.generators,
+                    &preprocessing.bytecode,
+                    &jolt_polynomials.bytecode,
+                    &jolt_polynomials,
+                    &mut openings,
+                    &mut transcript,
+                );
+                let challenge = pipeline::fork_challenge(&mut transcript);
+                Ok(StageCheckpoint {
+                    proof,
+                    openings,
+                    challenge,
+                })
+            })
+        };
+        let prove_instruction_lookups = || {
+            checkpoints.load_or_run(pipeline::INSTRUCTION_LOOKUPS_STAGE, || {
+                let mut transcript = transcript.fork(pipeline::INSTRUCTION_LOOKUPS_STAGE);
+                let mut openings = ProverOpeningAccumulator::for_scheme::<PCS>();
+                let proof = InstructionLookupsProof::prove(
+                    &preprocessing.generators,
+                    &jolt_polynomials,
+                    &preprocessing.instruction_lookups,
+                    &mut openings,
+                    &mut transcript,
+                );
+                let challenge = pipeline::fork_challenge(&mut transcript);
+                Ok(StageCheckpoint {
+                    proof,
+                    openings,
+                    challenge,
+                })
+            })
+        };
+        let prove_memory = || {
+            checkpoints.load_or_run(pipeline::READ_WRITE_MEMORY_STAGE, || {
+                let mut transcript = transcript.fork(pipeline::READ_WRITE_MEMORY_STAGE);
+                let mut openings = ProverOpeningAccumulator::for_scheme::<PCS>();
+                let proof = ReadWriteMemoryProof::prove(
+                    &preprocessing.generators,
+                    &preprocessing.read_write_memory,
+                    &jolt_polynomials,
+                    &program_io,
+                    &mut openings,
+                    &mut transcript,
+                );
+                let challenge = pipeline::fork_challenge(&mut transcript);
+                Ok(StageCheckpoint {
+                    proof,
+                    openings,
+                    challenge,
+                })
+            })
+        };
+        let prove_r1cs = || {
+            checkpoints.load_or_run(pipeline::R1CS_STAGE, || {
+                let mut transcript = transcript.fork(pipeline::R1CS_STAGE);
+                let mut openings = ProverOpeningAccumulator::for_scheme::<PCS>();
+                let proof = UniformSpartanProof::<
+                    C,
+                    <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                    F,
+                    PCS,
+                    ProofTranscript,
+                >::prove(
+                    &r1cs_builder,
+                    &spartan_key,
+                    &preprocessing.generators,
+                    &jolt_polynomials,
+                    ProverConfig::current().mask_round_polys,
+                    ProverConfig::current().univariate_skip,
+                    &mut openings,
+                    &mut transcript,
+                )?;
+                let challenge = pipeline::fork_challenge(&mut transcript);
+                Ok(StageCheckpoint {
+                    proof,
+                    openings,
+                    challenge,
+                })
+            })
+        };
+
+        let (bytecode, instruction_lookups, read_write_memory, r1cs) = pipeline::run_stages(
+            scheduling,
+            prove_bytecode,
+            prove_instruction_lookups,
+            prove_memory,
+            prove_r1cs,
+        );
+        let (bytecode, instruction_lookups, read_write_memory, r1cs) =
+            (bytecode?, instruction_lookups?, read_write_memory?, r1cs?);
+
+        let mut opening_accumulator: ProverOpeningAccumulator<F, ProofTranscript> =
+            ProverOpeningAccumulator::for_scheme::<PCS>();
+        opening_accumulator.extend(bytecode.openings);
+        opening_accumulator.extend(instruction_lookups.openings);
+        opening_accumulator.extend(read_write_memory.openings);
+        opening_accumulator.extend(r1cs.openings);
+        pipeline::join_challenges(
+            &mut transcript,
+            [
+                bytecode.challenge,
+                instruction_lookups.challenge,
+                read_write_memory.challenge,
+                r1cs.challenge,
+            ],
+        );
+
+        // Batch-prove all openings
+        let opening_proof =
+            opening_accumulator.reduce_and_prove::<PCS>(&preprocessing.generators, &mut transcript);
+
+        drop_in_background_thread(jolt_polynomials);
+        BufferPool::<F>::global().clear();
+
+        let jolt_proof = JoltProof {
+            trace_length,
+            program_io,
+            bytecode: bytecode.proof,
+            read_write_memory: read_write_memory.proof,
+            instruction_lookups: instruction_lookups.proof,
+            r1cs: r1cs.proof,
+            opening_proof,
+        };
+
+        #[cfg(test)]
+        let debug_info = Some(ProverDebugInfo {
+            transcript,
+            opening_accumulator,
+        });
+        #[cfg(not(test))]
+        let debug_info = None;
+        Ok((jolt_proof, jolt_commitments, debug_info))
+    }
+
+    /// Commits to `shard` of the witness polynomials of `trace`, as a worker of a
+    /// [`Jolt::prove_distributed`] coordinator proving the same trace with the same `padding`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Jolt::commit_shard")
+    )]
+    fn commit_shard<T: JoltTrace<Self::InstructionSet> + ?Sized>(
+        program_io: &JoltDevice,
+        trace: &T,
+        preprocessing: &JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        padding: TracePadding,
+        shard: CommitmentShard,
+    ) -> Result<ShardCommitments<PCS::Commitment>, JoltError> {
+        let (jolt_polynomials, _) =
+            Self::generate_witness(program_io, trace, preprocessing, padding)?;
+        let commitments = jolt_polynomials.commit_shard(preprocessing, shard);
+        drop_in_background_thread(jolt_polynomials);
+        Ok(commitments)
+    }
+
+    /// Generates the witness polynomials of `trace` padded according to `padding`, along
+    /// with the R1CS constraints they satisfy.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Jolt::generate_witness")
+    )]
+    fn generate_witness<T: JoltTrace<Self::InstructionSet> + ?Sized>(
+        program_io: &JoltDevice,
+        trace: &T,
+        preprocessing: &JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        padding: TracePadding,
+    ) -> Result<
+        (
+            JoltPolynomials<F>,
+            CombinedUniformBuilder<C, F, <Self::Constraints as R1CSConstraints<C, F>>::Inputs>,
+        ),
+        JoltError,
+    > {
+        let memory_layout = &program_io.memory_layout;
+        if *memory_layout != preprocessing.memory_layout {
+            return Err(JoltError::MemoryLayoutMismatch);
+        }
+        if program_io.inputs.len() > memory_layout.max_input_size as usize {
+            return Err(JoltError::InputTooLarge(
+                program_io.inputs.len(),
+                memory_layout.max_input_size as usize,
+            ));
+        }
+        if program_io.outputs.len() > memory_layout.max_output_size as usize {
+            return Err(JoltError::OutputTooLarge(
+                program_io.outputs.len(),
+                memory_layout.max_output_size as usize,
+            ));
+        }
+
+        let padded_trace_length = padding.padded_length(trace.len());
+        let trace = PaddedTrace::new::<Self::InstructionSet>(trace, padded_trace_length);
 
         let instruction_polynomials =
             InstructionLookupsProof::<
@@ -400,16 +1272,17 @@ where
             >::generate_witness(&preprocessing.instruction_lookups, &trace);
 
         let (memory_polynomials, read_timestamps) = ReadWriteMemoryPolynomials::generate_witness(
-            &program_io,
+            program_io,
             &preprocessing.read_write_memory,
             &trace,
+            padding.min_memory_size(),
         );
 
-        let (bytecode_polynomials, range_check_polys) = rayon::join(
+        let (bytecode_polynomials, range_check_polys) = par::join(
             || {
                 BytecodeProof::<F, PCS, ProofTranscript>::generate_witness(
                     &preprocessing.bytecode,
-                    &mut trace,
+                    &trace,
                 )
             },
             || {
@@ -418,18 +1291,12 @@ where
                 )
             },
         );
+        let bytecode_polynomials = bytecode_polynomials?;
 
         let r1cs_builder = Self::Constraints::construct_constraints(
             padded_trace_length,
             program_io.memory_layout.input_start,
         );
-        let spartan_key = spartan::UniformSpartanProof::<
-            C,
-            <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
-            F,
-            ProofTranscript,
-        >::setup(&r1cs_builder, padded_trace_length);
-
         let r1cs_polynomials = R1CSPolynomials::new::<
             C,
             M,
@@ -446,92 +1313,193 @@ where
         };
 
         r1cs_builder.compute_aux(&mut jolt_polynomials);
+        Ok((jolt_polynomials, r1cs_builder))
+    }
 
-        let jolt_commitments = jolt_polynomials.commit::<C, PCS, ProofTranscript>(&preprocessing);
-
-        transcript.append_scalar(&spartan_key.vk_digest);
-
-        jolt_commitments
-            .read_write_values()
-            .iter()
-            .for_each(|value| value.append_to_transcript(&mut transcript));
-        jolt_commitments
-            .init_final_values()
-            .iter()
-            .for_each(|value| value.append_to_transcript(&mut transcript));
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn verify(
+        preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        proof: JoltProof<
+            C,
+            M,
+            <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+            F,
+            PCS,
+            Self::InstructionSet,
+            Self::Subtables,
+            ProofTranscript,
+        >,
+        commitments: JoltCommitments<PCS, ProofTranscript>,
+        _debug_info: Option<ProverDebugInfo<F, ProofTranscript>>,
+    ) -> Result<(), ProofVerifyError> {
+        Self::verify_with_verifier_preprocessing(
+            preprocessing.into(),
+            proof,
+            commitments,
+            _debug_info,
+        )
+    }
 
-        let mut opening_accumulator: ProverOpeningAccumulator<F, ProofTranscript> =
-            ProverOpeningAccumulator::new();
+    /// Verifies a proof with only the verifier's part of the preprocessing (see
+    /// [`JoltPreprocessing::verifier_preprocessing`]).
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn verify_with_verifier_preprocessing(
+        mut preprocessing: JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>,
+        proof: JoltProof<
+            C,
+            M,
+            <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+            F,
+            PCS,
+            Self::InstructionSet,
+            Self::Subtables,
+            ProofTranscript,
+        >,
+        commitments: JoltCommitments<PCS, ProofTranscript>,
+        _debug_info: Option<ProverDebugInfo<F, ProofTranscript>>,
+    ) -> Result<(), ProofVerifyError> {
+        let (opening_accumulator, opening_proof, mut transcript) =
+            Self::verify_openings(&mut preprocessing, proof, commitments, _debug_info)?;
 
-        let bytecode_proof = BytecodeProof::prove_memory_checking(
+        // Batch-verify all openings
+        opening_accumulator.reduce_and_verify(
             &preprocessing.generators,
-            &preprocessing.bytecode,
-            &jolt_polynomials.bytecode,
-            &jolt_polynomials,
-            &mut opening_accumulator,
+            &opening_proof,
             &mut transcript,
-        );
+        )
+    }
 
-        let instruction_proof = InstructionLookupsProof::prove(
-            &preprocessing.generators,
-            &jolt_polynomials,
-            &preprocessing.instruction_lookups,
-            &mut opening_accumulator,
-            &mut transcript,
-        );
+    /// Verifies many independent proofs made with the same preprocessing. The final opening
+    /// check of each proof is deferred to a single accumulator, so that e.g. for the KZG-based
+    /// schemes all of the proofs' pairing checks are verified with one multi-pairing.
+    /// `rng` must be unpredictable to the provers.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn verify_aggregated<R: RngCore + CryptoRng>(
+        preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+        proofs: Vec<(
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+        )>,
+        rng: &mut R,
+    ) -> Result<(), ProofVerifyError>
+    where
+        PCS: AggregatableCommitmentScheme<ProofTranscript>,
+    {
+        let mut preprocessing = JoltVerifierPreprocessing::from(preprocessing);
+        let mut pcs_accumulator = PCS::accumulator(&preprocessing.generators);
+        for (proof, commitments) in proofs {
+            Self::verify_deferred(&mut preprocessing, proof, commitments, &mut pcs_accumulator)?;
+        }
+        PCS::verify_accumulator(pcs_accumulator, rng)
+    }
 
-        let memory_proof = ReadWriteMemoryProof::prove(
-            &preprocessing.generators,
-            &preprocessing.read_write_memory,
-            &jolt_polynomials,
-            &program_io,
-            &mut opening_accumulator,
-            &mut transcript,
-        );
+    /// Verifies many independent proofs, possibly of different programs. Each proof comes with
+    /// the index of its program's verifier preprocessing in `preprocessing`, and must have the
+    /// inputs, outputs and panic flag of the corresponding entry of `program_io`. The final
+    /// opening checks of the proofs of each program are deferred to a single accumulator (see
+    /// [`Jolt::verify_deferred`]), so that e.g. for the KZG-based schemes they are verified
+    /// with one multi-pairing per program rather than one per proof. The other checks, such as
+    /// the final checks of the sum-checks, are field operations and are done per proof.
+    /// `rng` must be unpredictable to the provers. An error does not identify the invalid
+    /// proof, which verifying the proofs individually does.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn batch_verify<R: RngCore + CryptoRng>(
+        preprocessing: &mut [JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>],
+        proofs: Vec<(
+            usize,
+            JoltProof<
+                C,
+                M,
+                <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
+                F,
+                PCS,
+                Self::InstructionSet,
+                Self::Subtables,
+                ProofTranscript,
+            >,
+            JoltCommitments<PCS, ProofTranscript>,
+        )>,
+        program_io: &[JoltDevice],
+        rng: &mut R,
+    ) -> Result<(), ProofVerifyError>
+    where
+        PCS: AggregatableCommitmentScheme<ProofTranscript>,
+    {
+        if proofs.len() != program_io.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                proofs.len(),
+                program_io.len(),
+            ));
+        }
+        let mut pcs_accumulators: Vec<_> = preprocessing
+            .iter()
+            .map(|preprocessing| PCS::accumulator(&preprocessing.generators))
+            .collect();
+        for ((index, proof, commitments), expected_io) in proofs.into_iter().zip(program_io) {
+            if proof.program_io.inputs != expected_io.inputs
+                || proof.program_io.outputs != expected_io.outputs
+                || proof.program_io.panic != expected_io.panic
+            {
+                return Err(ProofVerifyError::ProgramIOMismatch);
+            }
+            Self::verify_deferred(
+                &mut preprocessing[index],
+                proof,
+                commitments,
+                &mut pcs_accumulators[index],
+            )?;
+        }
+        pcs_accumulators
+            .into_iter()
+            .try_for_each(|pcs_accumulator| PCS::verify_accumulator(pcs_accumulator, rng))
+    }
 
-        let spartan_proof = UniformSpartanProof::<
+    /// Same as [`Jolt::verify_with_verifier_preprocessing`], but defers the final opening
+    /// check to `pcs_accumulator` (see [`AggregatableCommitmentScheme::accumulator`]), so that
+    /// it can be verified together with the checks of other proofs or protocols, e.g. in a
+    /// single multi-pairing. The proof is only verified once the accumulator is.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn verify_deferred(
+        preprocessing: &mut JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>,
+        proof: JoltProof<
             C,
+            M,
             <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
             F,
+            PCS,
+            Self::InstructionSet,
+            Self::Subtables,
             ProofTranscript,
-        >::prove::<PCS>(
-            &r1cs_builder,
-            &spartan_key,
-            &jolt_polynomials,
-            &mut opening_accumulator,
+        >,
+        commitments: JoltCommitments<PCS, ProofTranscript>,
+        pcs_accumulator: &mut PCS::Accumulator,
+    ) -> Result<(), ProofVerifyError>
+    where
+        PCS: AggregatableCommitmentScheme<ProofTranscript>,
+    {
+        let (opening_accumulator, opening_proof, mut transcript) =
+            Self::verify_openings(preprocessing, proof, commitments, None)?;
+        opening_accumulator.reduce_and_verify_deferred(
+            &preprocessing.generators,
+            &opening_proof,
             &mut transcript,
+            pcs_accumulator,
         )
-        .expect("r1cs proof failed");
-
-        // Batch-prove all openings
-        let opening_proof =
-            opening_accumulator.reduce_and_prove::<PCS>(&preprocessing.generators, &mut transcript);
-
-        drop_in_background_thread(jolt_polynomials);
-
-        let jolt_proof = JoltProof {
-            trace_length,
-            program_io,
-            bytecode: bytecode_proof,
-            read_write_memory: memory_proof,
-            instruction_lookups: instruction_proof,
-            r1cs: spartan_proof,
-            opening_proof,
-        };
-
-        #[cfg(test)]
-        let debug_info = Some(ProverDebugInfo {
-            transcript,
-            opening_accumulator,
-        });
-        #[cfg(not(test))]
-        let debug_info = None;
-        (jolt_proof, jolt_commitments, debug_info)
     }
 
-    #[tracing::instrument(skip_all)]
-    fn verify(
-        mut preprocessing: JoltPreprocessing<C, F, PCS, ProofTranscript>,
+    /// Performs all of the verifier's checks except for the batched opening proof, returning
+    /// the accumulated openings, the proof of them, and the transcript to verify it against.
+    fn verify_openings(
+        preprocessing: &mut JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>,
         proof: JoltProof<
             C,
             M,
@@ -544,7 +1512,19 @@ where
         >,
         commitments: JoltCommitments<PCS, ProofTranscript>,
         _debug_info: Option<ProverDebugInfo<F, ProofTranscript>>,
-    ) -> Result<(), ProofVerifyError> {
+    ) -> Result<
+        (
+            VerifierOpeningAccumulator<F, PCS, ProofTranscript>,
+            ReducedOpeningProof<F, PCS, ProofTranscript>,
+            ProofTranscript,
+        ),
+        ProofVerifyError,
+    > {
+        // The memory layout is not part of the transcript, so it must be the preprocessing's
+        if proof.program_io.memory_layout != preprocessing.memory_layout {
+            return Err(ProofVerifyError::MemoryLayoutMismatch);
+        }
+
         let mut transcript = ProofTranscript::new(b"Jolt transcript");
         let mut opening_accumulator: VerifierOpeningAccumulator<F, PCS, ProofTranscript> =
             VerifierOpeningAccumulator::new();
@@ -567,11 +1547,11 @@ where
         let memory_start = preprocessing.memory_layout.input_start;
         let r1cs_builder =
             Self::Constraints::construct_constraints(padded_trace_length, memory_start);
-        let spartan_key = spartan::UniformSpartanProof::<C, _, F, ProofTranscript>::setup(
+        let spartan_key = spartan::UniformSpartanProof::<C, _, F, PCS, ProofTranscript>::setup(
             &r1cs_builder,
             padded_trace_length,
         );
-        transcript.append_scalar(&spartan_key.vk_digest);
+        transcript.append_scalar(&spartan_key.digest());
 
         let r1cs_proof = R1CSProof {
             key: spartan_key,
@@ -588,22 +1568,25 @@ where
             .iter()
             .for_each(|value| value.append_to_transcript(&mut transcript));
 
+        let mut bytecode_transcript = transcript.fork(pipeline::BYTECODE_STAGE);
         Self::verify_bytecode(
             &preprocessing.bytecode,
             &preprocessing.generators,
             proof.bytecode,
             &commitments,
             &mut opening_accumulator,
-            &mut transcript,
+            &mut bytecode_transcript,
         )?;
+        let mut instruction_transcript = transcript.fork(pipeline::INSTRUCTION_LOOKUPS_STAGE);
         Self::verify_instruction_lookups(
             &preprocessing.instruction_lookups,
             &preprocessing.generators,
             proof.instruction_lookups,
             &commitments,
             &mut opening_accumulator,
-            &mut transcript,
+            &mut instruction_transcript,
         )?;
+        let mut memory_transcript = transcript.fork(pipeline::READ_WRITE_MEMORY_STAGE);
         Self::verify_memory(
             &mut preprocessing.read_write_memory,
             &preprocessing.generators,
@@ -612,26 +1595,30 @@ where
             &commitments,
             proof.program_io,
             &mut opening_accumulator,
-            &mut transcript,
+            &mut memory_transcript,
         )?;
+        let mut r1cs_transcript = transcript.fork(pipeline::R1CS_STAGE);
         Self::verify_r1cs(
             r1cs_proof,
             &commitments,
             &mut opening_accumulator,
-            &mut transcript,
+            &mut r1cs_transcript,
         )?;
 
-        // Batch-verify all openings
-        opening_accumulator.reduce_and_verify(
-            &preprocessing.generators,
-            &proof.opening_proof,
+        pipeline::join_transcripts::<F, _>(
             &mut transcript,
-        )?;
+            [
+                bytecode_transcript,
+                instruction_transcript,
+                memory_transcript,
+                r1cs_transcript,
+            ],
+        );
 
-        Ok(())
+        Ok((opening_accumulator, proof.opening_proof, transcript))
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     fn verify_instruction_lookups<'a>(
         preprocessing: &InstructionLookupsPreprocessing<C, F>,
         generators: &PCS::Setup,
@@ -658,7 +1645,7 @@ where
         )
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     fn verify_bytecode<'a>(
         preprocessing: &BytecodePreprocessing<F>,
         generators: &PCS::Setup,
@@ -679,7 +1666,7 @@ where
     }
 
     #[allow(clippy::too_many_arguments)]
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     fn verify_memory<'a>(
         preprocessing: &mut ReadWriteMemoryPreprocessing,
         generators: &PCS::Setup,
@@ -690,8 +1677,11 @@ where
         opening_accumulator: &mut VerifierOpeningAccumulator<F, PCS, ProofTranscript>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
-        assert!(program_io.inputs.len() <= memory_layout.max_input_size as usize);
-        assert!(program_io.outputs.len() <= memory_layout.max_output_size as usize);
+        if program_io.inputs.len() > memory_layout.max_input_size as usize
+            || program_io.outputs.len() > memory_layout.max_output_size as usize
+        {
+            return Err(ProofVerifyError::InputTooLarge);
+        }
         // pair the memory layout with the program io from the proof
         preprocessing.program_io = Some(JoltDevice {
             inputs: program_io.inputs,
@@ -710,12 +1700,13 @@ where
         )
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     fn verify_r1cs<'a>(
         proof: R1CSProof<
             C,
             <Self::Constraints as R1CSConstraints<C, F>>::Inputs,
             F,
+            PCS,
             ProofTranscript,
         >,
         commitments: &'a JoltCommitments<PCS, ProofTranscript>,
@@ -747,7 +1738,11 @@ where
 }
 
 pub mod bytecode;
+pub mod checkpoint;
+pub mod distributed;
 pub mod instruction_lookups;
+pub mod pipeline;
+pub mod proof_format;
 pub mod read_write_memory;
 pub mod rv32i_vm;
 pub mod timestamp_range_check;
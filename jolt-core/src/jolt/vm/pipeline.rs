@@ -0,0 +1,129 @@
+//! Scheduling of the prover's independent stages.
+//!
+//! Once the witness polynomials are committed to, the bytecode, instruction lookups,
+//! read-write memory and R1CS proofs only share the transcript state. Each stage therefore
+//! runs on its own fork of the transcript (and its own opening accumulator), which lets the
+//! prover run them concurrently. Afterwards the forks are joined back into the main
+//! transcript in a fixed order, so the proof is the same regardless of how the stages were
+//! scheduled:
+//!
+//! 1. commit phase: the commitments are appended to the main transcript;
+//! 2. stage phase: each stage is proven against `transcript.fork(label)`;
+//! 3. join phase: a challenge squeezed from each fork is appended to the main transcript,
+//!    in stage order, before the batched opening proof.
+//!
+//! The verifier replays the stages sequentially on the same forks.
+use crate::utils::par::{self, ThreadPool};
+
+use crate::field::JoltField;
+use crate::utils::prover_config::ProverConfig;
+use crate::utils::transcript::Transcript;
+
+pub(crate) const BYTECODE_STAGE: &[u8] = b"bytecode stage";
+pub(crate) const INSTRUCTION_LOOKUPS_STAGE: &[u8] = b"instruction lookups stage";
+pub(crate) const READ_WRITE_MEMORY_STAGE: &[u8] = b"read-write memory stage";
+pub(crate) const R1CS_STAGE: &[u8] = b"r1cs stage";
+
+const NUM_STAGES: usize = 4;
+
+/// Determines how the prover schedules its (independent) stages.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StageScheduling {
+    /// Proves the stages one after another, each using the current thread pool.
+    #[default]
+    Sequential,
+    /// Proves the stages concurrently, each on its own thread pool with an equal share
+    /// of the current pool's threads. This improves wall-clock time on many-core machines,
+    /// where a single stage does not saturate every core.
+    Concurrent,
+}
+
+/// Runs the four stage closures according to `scheduling` and returns their results in
+/// stage order.
+pub(crate) fn run_stages<A, B, C, D>(
+    scheduling: StageScheduling,
+    bytecode: impl FnOnce() -> A + Send,
+    instruction_lookups: impl FnOnce() -> B + Send,
+    read_write_memory: impl FnOnce() -> C + Send,
+    r1cs: impl FnOnce() -> D + Send,
+) -> (A, B, C, D)
+where
+    A: Send,
+    B: Send,
+    C: Send,
+    D: Send,
+{
+    match scheduling {
+        StageScheduling::Sequential => (
+            bytecode(),
+            instruction_lookups(),
+            read_write_memory(),
+            r1cs(),
+        ),
+        StageScheduling::Concurrent => {
+            let threads_per_stage = (par::current_num_threads() / NUM_STAGES).max(1);
+            // The stage pools run under the config of the pool they were split from
+            let config = ProverConfig::current();
+            let pools: Vec<ThreadPool> = (0..NUM_STAGES)
+                .map(|_| config.thread_pool(threads_per_stage))
+                .collect();
+
+            std::thread::scope(|scope| {
+                let bytecode = scope.spawn(|| pools[0].install(bytecode));
+                let instruction_lookups = scope.spawn(|| pools[1].install(instruction_lookups));
+                let read_write_memory = scope.spawn(|| pools[2].install(read_write_memory));
+                let r1cs = pools[3].install(r1cs);
+                (
+                    bytecode.join().unwrap(),
+                    instruction_lookups.join().unwrap(),
+                    read_write_memory.join().unwrap(),
+                    r1cs,
+                )
+            })
+        }
+    }
+}
+
+/// Joins the stages' transcript forks (in stage order) back into `transcript`.
+pub(crate) fn join_transcripts<F: JoltField, ProofTranscript: Transcript>(
+    transcript: &mut ProofTranscript,
+    forks: [ProofTranscript; NUM_STAGES],
+) {
+    join_challenges(
+        transcript,
+        forks.map(|mut fork| fork_challenge::<F, _>(&mut fork)),
+    );
+}
+
+/// The challenge by which a stage's transcript fork is joined back into the main transcript.
+/// A stage can squeeze it as soon as it is done, so that the fork need not be kept (e.g. in a
+/// prover checkpoint).
+pub(crate) fn fork_challenge<F: JoltField, ProofTranscript: Transcript>(
+    fork: &mut ProofTranscript,
+) -> F {
+    fork.challenge_scalar()
+}
+
+/// Joins the stages' fork challenges (see [`fork_challenge`]), in stage order, into
+/// `transcript`.
+pub(crate) fn join_challenges<F: JoltField, ProofTranscript: Transcript>(
+    transcript: &mut ProofTranscript,
+    challenges: [F; NUM_STAGES],
+) {
+    for challenge in challenges {
+        transcript.append_scalar(&challenge);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_results_in_order() {
+        for scheduling in [StageScheduling::Sequential, StageScheduling::Concurrent] {
+            let results = run_stages(scheduling, || 0, || "one", || 2u64, || vec![3]);
+            assert_eq!(results, (0, "one", 2, vec![3]));
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! A canonical, versioned binary format for full RV32I proofs.
+//!
+//! A proof is laid out as a header followed by its components, each of which is the compressed
+//! canonical serialization of one part of the proof. All integers are little-endian:
+//!
+//! | field                   | encoding                                            |
+//! |-------------------------|-----------------------------------------------------|
+//! | magic                   | `b"JOLT"`                                           |
+//! | format version          | `u16`, currently [`PROOF_FORMAT_VERSION`]           |
+//! | PCS identifier          | `u8` length, then `CommitmentScheme::protocol_name` |
+//! | curve identifier        | `u8` length, then [`CurveIdentifier::CURVE_ID`]     |
+//! | number of components    | `u8`                                                |
+//! | component lengths       | one `u64` per component                             |
+//! | components              | concatenated, in the order of [`PROOF_COMPONENTS`]  |
+//!
+//! Readers reject proofs of another version, PCS or curve rather than misinterpreting them.
+//! Any change to the serialization of a component must bump [`PROOF_FORMAT_VERSION`].
+use std::fmt;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use thiserror::Error;
+
+use crate::field::JoltField;
+use crate::poly::commitment::commitment_scheme::CommitmentScheme;
+use crate::poly::commitment::hyperkzg::HyperKZG;
+
+use super::rv32i_vm::{ProofTranscript, RV32IHyperKZGProof, RV32IJoltProof};
+
+const MAGIC: &[u8; 4] = b"JOLT";
+
+pub const PROOF_FORMAT_VERSION: u16 = 2;
+
+/// The components of a proof, in the order in which they are laid out
+pub const PROOF_COMPONENTS: [&str; 8] = [
+    "trace_length",
+    "program_io",
+    "commitments",
+    "bytecode",
+    "read_write_memory",
+    "instruction_lookups",
+    "r1cs",
+    "opening_proof",
+];
+
+/// Names the curve a proof's commitments are over, for the header of the proof format.
+pub trait CurveIdentifier {
+    const CURVE_ID: &'static str;
+}
+
+impl CurveIdentifier for Bn254 {
+    const CURVE_ID: &'static str = "bn254";
+}
+
+impl CurveIdentifier for Bls12_381 {
+    const CURVE_ID: &'static str = "bls12-381";
+}
+
+#[derive(Error, Debug)]
+pub enum ProofFormatError {
+    #[error("Not a Jolt proof")]
+    InvalidMagic,
+    #[error(
+        "Unsupported proof format version {0}, expected {}",
+        PROOF_FORMAT_VERSION
+    )]
+    UnsupportedVersion(u16),
+    #[error("Proof is for PCS {found:?}, expected {expected:?}")]
+    PcsMismatch { expected: String, found: String },
+    #[error("Proof is over curve {found:?}, expected {expected:?}")]
+    CurveMismatch { expected: String, found: String },
+    #[error("Proof has {0} components, expected {}", PROOF_COMPONENTS.len())]
+    ComponentCountMismatch(usize),
+    #[error("Proof is truncated")]
+    Truncated,
+    #[error("Proof has {0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("Component {0} has {1} bytes left over after deserialization")]
+    ComponentLengthMismatch(&'static str, usize),
+    #[error("Failed to deserialize component {0}: {1}")]
+    Component(&'static str, SerializationError),
+}
+
+/// The number of bytes each part of a serialized proof takes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofSizeBreakdown {
+    /// The header, including the component lengths
+    pub header: usize,
+    /// Each component in [`PROOF_COMPONENTS`] with its length
+    pub components: Vec<(&'static str, usize)>,
+}
+
+impl ProofSizeBreakdown {
+    pub fn total(&self) -> usize {
+        self.header + self.components.iter().map(|(_, len)| len).sum::<usize>()
+    }
+}
+
+impl fmt::Display for ProofSizeBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        let percent = |len: usize| 100.0 * len as f64 / total as f64;
+        writeln!(
+            f,
+            "{:<20} {:>10} bytes ({:>5.1}%)",
+            "header",
+            self.header,
+            percent(self.header)
+        )?;
+        for (name, len) in self.components.iter() {
+            writeln!(
+                f,
+                "{:<20} {:>10} bytes ({:>5.1}%)",
+                name,
+                len,
+                percent(*len)
+            )?;
+        }
+        write!(f, "{:<20} {:>10} bytes", "total", total)
+    }
+}
+
+impl<P: Pairing + CurveIdentifier> RV32IHyperKZGProof<P>
+where
+    P::ScalarField: JoltField,
+{
+    fn pcs_id() -> &'static [u8] {
+        <HyperKZG<P, ProofTranscript> as CommitmentScheme<ProofTranscript>>::protocol_name()
+    }
+
+    fn header(component_lengths: &[usize]) -> Vec<u8> {
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&PROOF_FORMAT_VERSION.to_le_bytes());
+        for id in [Self::pcs_id(), P::CURVE_ID.as_bytes()] {
+            header.push(id.len() as u8);
+            header.extend_from_slice(id);
+        }
+        header.push(component_lengths.len() as u8);
+        for len in component_lengths {
+            header.extend_from_slice(&(*len as u64).to_le_bytes());
+        }
+        header
+    }
+
+    fn serialize_components(&self) -> Vec<Vec<u8>> {
+        let proof = &self.proof;
+        let components: [&dyn ErasedSerialize; 8] = [
+            &proof.trace_length,
+            &proof.program_io,
+            &self.commitments,
+            &proof.bytecode,
+            &proof.read_write_memory,
+            &proof.instruction_lookups,
+            &proof.r1cs,
+            &proof.opening_proof,
+        ];
+        components
+            .iter()
+            .map(|component| component.to_compressed_bytes())
+            .collect()
+    }
+
+    /// Serializes the proof in the versioned format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let components = self.serialize_components();
+        let lengths: Vec<usize> = components.iter().map(Vec::len).collect();
+        let mut bytes = Self::header(&lengths);
+        for component in components {
+            bytes.extend(component);
+        }
+        bytes
+    }
+
+    /// Deserializes a proof in the versioned format, checking that it is of this version, PCS
+    /// and curve.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofFormatError> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(ProofFormatError::InvalidMagic);
+        }
+        let version = u16::from_le_bytes(reader.take(2)?.try_into().unwrap());
+        if version != PROOF_FORMAT_VERSION {
+            return Err(ProofFormatError::UnsupportedVersion(version));
+        }
+        let pcs_id = reader.take_id()?;
+        if pcs_id != Self::pcs_id() {
+            return Err(ProofFormatError::PcsMismatch {
+                expected: String::from_utf8_lossy(Self::pcs_id()).into_owned(),
+                found: String::from_utf8_lossy(pcs_id).into_owned(),
+            });
+        }
+        let curve_id = reader.take_id()?;
+        if curve_id != P::CURVE_ID.as_bytes() {
+            return Err(ProofFormatError::CurveMismatch {
+                expected: P::CURVE_ID.to_string(),
+                found: String::from_utf8_lossy(curve_id).into_owned(),
+            });
+        }
+        let num_components = reader.take(1)?[0] as usize;
+        if num_components != PROOF_COMPONENTS.len() {
+            return Err(ProofFormatError::ComponentCountMismatch(num_components));
+        }
+        let mut lengths = [0usize; PROOF_COMPONENTS.len()];
+        for len in lengths.iter_mut() {
+            *len = u64::from_le_bytes(reader.take(8)?.try_into().unwrap()) as usize;
+        }
+
+        let mut components = PROOF_COMPONENTS.iter().zip(lengths);
+        let mut next = || {
+            let (name, len) = components.next().unwrap();
+            Ok::<_, ProofFormatError>((*name, reader.take(len)?))
+        };
+        let trace_length = deserialize_component(next()?)?;
+        let program_io = deserialize_component(next()?)?;
+        let commitments = deserialize_component(next()?)?;
+        let bytecode = deserialize_component(next()?)?;
+        let read_write_memory = deserialize_component(next()?)?;
+        let instruction_lookups = deserialize_component(next()?)?;
+        let r1cs = deserialize_component(next()?)?;
+        let opening_proof = deserialize_component(next()?)?;
+        if !reader.bytes.is_empty() {
+            return Err(ProofFormatError::TrailingBytes(reader.bytes.len()));
+        }
+
+        let proof: RV32IJoltProof<P::ScalarField, HyperKZG<P, ProofTranscript>, ProofTranscript> =
+            RV32IJoltProof {
+                trace_length,
+                program_io,
+                bytecode,
+                read_write_memory,
+                instruction_lookups,
+                r1cs,
+                opening_proof,
+            };
+        Ok(Self { proof, commitments })
+    }
+
+    /// The number of bytes each component of the proof takes in the versioned format.
+    pub fn size_breakdown(&self) -> ProofSizeBreakdown {
+        let lengths: Vec<usize> = self.serialize_components().iter().map(Vec::len).collect();
+        ProofSizeBreakdown {
+            header: Self::header(&lengths).len(),
+            components: PROOF_COMPONENTS.into_iter().zip(lengths).collect(),
+        }
+    }
+}
+
+/// Object-safe compressed serialization, so that the heterogeneous components can be iterated.
+trait ErasedSerialize {
+    fn to_compressed_bytes(&self) -> Vec<u8>;
+}
+
+impl<T: CanonicalSerialize> ErasedSerialize for T {
+    fn to_compressed_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+}
+
+fn deserialize_component<T: CanonicalDeserialize>(
+    (name, mut bytes): (&'static str, &[u8]),
+) -> Result<T, ProofFormatError> {
+    let component = T::deserialize_compressed(&mut bytes)
+        .map_err(|err| ProofFormatError::Component(name, err))?;
+    if !bytes.is_empty() {
+        return Err(ProofFormatError::ComponentLengthMismatch(name, bytes.len()));
+    }
+    Ok(component)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProofFormatError> {
+        if self.bytes.len() < len {
+            return Err(ProofFormatError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_id(&mut self) -> Result<&'a [u8], ProofFormatError> {
+        let len = self.take(1)?[0] as usize;
+        self.take(len)
+    }
+}
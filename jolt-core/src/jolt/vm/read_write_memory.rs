@@ -4,8 +4,9 @@ use crate::lasso::memory_checking::{
     ExogenousOpenings, Initializable, StructuredPolynomialData, VerifierComputedOpening,
 };
 use crate::poly::opening_proof::{ProverOpeningAccumulator, VerifierOpeningAccumulator};
-use crate::utils::thread::unsafe_allocate_zero_vec;
-use rayon::prelude::*;
+use crate::utils::par::{self, prelude::*};
+use crate::utils::thread::{transpose_chunks_mut, unsafe_allocate_zero_vec};
+use std::collections::HashMap;
 #[cfg(test)]
 use std::collections::HashSet;
 use std::marker::PhantomData;
@@ -29,9 +30,9 @@ use common::constants::{
 use common::rv_trace::{JoltDevice, MemoryLayout, MemoryOp};
 
 use super::{timestamp_range_check::TimestampValidityProof, JoltCommitments};
-use super::{JoltPolynomials, JoltStuff, JoltTraceStep};
+use super::{JoltPolynomials, JoltStuff, JoltTrace};
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ReadWriteMemoryPreprocessing {
     min_bytecode_address: u64,
     bytecode_words: Vec<u32>,
@@ -44,7 +45,10 @@ pub struct ReadWriteMemoryPreprocessing {
 }
 
 impl ReadWriteMemoryPreprocessing {
-    #[tracing::instrument(skip_all, name = "ReadWriteMemoryPreprocessing::preprocess")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ReadWriteMemoryPreprocessing::preprocess")
+    )]
     pub fn preprocess(memory_init: Vec<(u64, u8)>) -> Self {
         let min_bytecode_address = memory_init
             .iter()
@@ -103,6 +107,73 @@ const RS2: usize = 1;
 const RD: usize = 2;
 const RAM: usize = 3;
 
+// Indices of the per-step values computed in `ReadWriteMemoryPolynomials::generate_witness`
+const A_RAM: usize = 0;
+const V_WRITE_RD: usize = 1;
+const V_WRITE_RAM: usize = 2;
+const V_READ: [usize; MEMORY_OPS_PER_INSTRUCTION] = [3, 4, 5, 6];
+const T_READ: [usize; MEMORY_OPS_PER_INSTRUCTION] = [7, 8, 9, 10];
+const NUM_STEP_VALUES: usize = 11;
+
+/// The memory accesses of a chunk of the trace, as far as they can be resolved without knowing
+/// the memory state at the start of the chunk.
+#[derive(Default)]
+struct ChunkAccesses {
+    /// For each address the chunk accessed, the value it last wrote there (if any) and the
+    /// timestamp of its last access.
+    last_accesses: HashMap<usize, (Option<u64>, u64)>,
+    /// Accesses that read the value their address held at the start of the chunk.
+    deferred_reads: Vec<DeferredRead>,
+}
+
+struct DeferredRead {
+    offset: usize,
+    slot: usize,
+    address: usize,
+    /// Whether this is the chunk's first access to `address`, whose read timestamp is also
+    /// that of the start of the chunk.
+    first_access: bool,
+    /// Whether the access only reads, so that its write value is its read value.
+    is_read: bool,
+}
+
+impl ChunkAccesses {
+    /// Records an access to `address` by the given `slot` of the step at `offset` into the
+    /// chunk, filling in its read value and timestamp in `values` if they are already known.
+    fn access(
+        &mut self,
+        values: &mut [&mut [u64]],
+        offset: usize,
+        slot: usize,
+        address: usize,
+        v_new: Option<u64>,
+        timestamp: u64,
+    ) {
+        let last_access = self.last_accesses.get(&address).copied();
+        let v_old = last_access.and_then(|(v, _)| v);
+        if let Some((_, t)) = last_access {
+            values[T_READ[slot]][offset] = t;
+        }
+        match v_old {
+            Some(v) => {
+                values[V_READ[slot]][offset] = v;
+                if slot == RAM && v_new.is_none() {
+                    values[V_WRITE_RAM][offset] = v;
+                }
+            }
+            None => self.deferred_reads.push(DeferredRead {
+                offset,
+                slot,
+                address,
+                first_access: last_access.is_none(),
+                is_read: v_new.is_none(),
+            }),
+        }
+        self.last_accesses
+            .insert(address, (v_new.or(v_old), timestamp));
+    }
+}
+
 #[derive(Default, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ReadWriteMemoryStuff<T: CanonicalSerialize + CanonicalDeserialize> {
     /// Read/write addresses. For offline memory checking, each read is paired with a "virtual" write
@@ -246,11 +317,15 @@ fn map_to_polys<F: JoltField, const N: usize>(vals: [&[u64]; N]) -> [DensePolyno
 }
 
 impl<F: JoltField> ReadWriteMemoryPolynomials<F> {
-    #[tracing::instrument(skip_all, name = "ReadWriteMemory::new")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ReadWriteMemory::new")
+    )]
     pub fn generate_witness<InstructionSet: JoltInstructionSet>(
         program_io: &JoltDevice,
         preprocessing: &ReadWriteMemoryPreprocessing,
-        trace: &[JoltTraceStep<InstructionSet>],
+        trace: &(impl JoltTrace<InstructionSet> + ?Sized),
+        min_memory_size: usize,
     ) -> (Self, [Vec<u64>; MEMORY_OPS_PER_INSTRUCTION]) {
         assert!(program_io.inputs.len() <= program_io.memory_layout.max_input_size as usize);
         assert!(program_io.outputs.len() <= program_io.memory_layout.max_output_size as usize);
@@ -258,16 +333,18 @@ impl<F: JoltField> ReadWriteMemoryPolynomials<F> {
         let m = trace.len();
         assert!(m.is_power_of_two());
 
-        let max_trace_address = trace
-            .iter()
-            .map(|step| match step.memory_ops[RAM] {
+        let max_trace_address = (0..m)
+            .into_par_iter()
+            .map(|i| match trace.step(i).memory_ops[RAM] {
                 MemoryOp::Read(a) => remap_address(a, &program_io.memory_layout),
                 MemoryOp::Write(a, _) => remap_address(a, &program_io.memory_layout),
             })
             .max()
             .unwrap();
 
-        let memory_size = max_trace_address.next_power_of_two() as usize;
+        let memory_size = max_trace_address
+            .max(min_memory_size as u64)
+            .next_power_of_two() as usize;
         let mut v_init: Vec<u64> = vec![0; memory_size];
         // Copy bytecode
         let mut v_init_index = memory_address_to_witness_index(
@@ -294,153 +371,156 @@ impl<F: JoltField> ReadWriteMemoryPolynomials<F> {
             v_init_index += 1;
         }
 
-        #[cfg(test)]
-        let mut init_tuples: HashSet<(usize, u64, u64)> = HashSet::new();
-        #[cfg(test)]
-        {
-            for (a, v) in v_init.iter().enumerate() {
-                init_tuples.insert((a, *v, 0u64));
-            }
-        }
-        #[cfg(test)]
-        let mut read_tuples: HashSet<(usize, u64, u64)> = HashSet::new();
-        #[cfg(test)]
-        let mut write_tuples: HashSet<(usize, u64, u64)> = HashSet::new();
-
-        let mut a_ram: Vec<u64> = Vec::with_capacity(m);
-
-        let mut v_read_rs1: Vec<u64> = Vec::with_capacity(m);
-        let mut v_read_rs2: Vec<u64> = Vec::with_capacity(m);
-        let mut v_read_rd: Vec<u64> = Vec::with_capacity(m);
-        let mut v_read_ram: Vec<u64> = Vec::with_capacity(m);
-
-        let mut t_read_rs1: Vec<u64> = Vec::with_capacity(m);
-        let mut t_read_rs2: Vec<u64> = Vec::with_capacity(m);
-        let mut t_read_rd: Vec<u64> = Vec::with_capacity(m);
-        let mut t_read_ram: Vec<u64> = Vec::with_capacity(m);
+        // Every step reads rs1 and rs2, writes rd, and reads or writes RAM, in that order, at
+        // its index as timestamp. Each access reads the value and timestamp left by the last
+        // access to its address, so the trace is split into chunks that are processed in
+        // parallel, each tracking the addresses it has accessed so far. Reads of addresses a
+        // chunk has not yet written are deferred until the memory state at the start of the
+        // chunk is known.
+        let mut step_values: Vec<Vec<u64>> = (0..NUM_STEP_VALUES).map(|_| vec![0; m]).collect();
+        let chunk_size = m.div_ceil(par::current_num_threads());
 
-        let mut v_write_rd: Vec<u64> = Vec::with_capacity(m);
-        let mut v_write_ram: Vec<u64> = Vec::with_capacity(m);
+        #[cfg(feature = "tracing")]
+        let _enter = tracing::span!(tracing::Level::DEBUG, "memory_trace_processing").entered();
 
-        let mut t_final = vec![0; memory_size];
-        let mut v_final = v_init.clone();
-
-        let span = tracing::span!(tracing::Level::DEBUG, "memory_trace_processing");
-        let _enter = span.enter();
-
-        for (i, step) in trace.iter().enumerate() {
-            let timestamp = i as u64;
-
-            match step.memory_ops[RS1] {
-                MemoryOp::Read(a) => {
-                    assert!(a < REGISTER_COUNT);
-                    let a = a as usize;
-                    let v = v_final[a];
-
-                    #[cfg(test)]
-                    {
-                        read_tuples.insert((a, v, t_final[a]));
-                        write_tuples.insert((a, v, timestamp));
+        let chunks: Vec<ChunkAccesses> = transpose_chunks_mut(&mut step_values, chunk_size)
+            .into_par_iter()
+            .enumerate()
+            .map(|(chunk_index, mut values)| {
+                let start = chunk_index * chunk_size;
+                let mut chunk = ChunkAccesses::default();
+                for offset in 0..values[A_RAM].len() {
+                    let step = trace.step(start + offset);
+                    let timestamp = (start + offset) as u64;
+
+                    for slot in [RS1, RS2] {
+                        match step.memory_ops[slot] {
+                            MemoryOp::Read(a) => {
+                                assert!(a < REGISTER_COUNT);
+                                chunk.access(
+                                    &mut values,
+                                    offset,
+                                    slot,
+                                    a as usize,
+                                    None,
+                                    timestamp,
+                                );
+                            }
+                            MemoryOp::Write(a, v) => {
+                                panic!("Unexpected rs{} MemoryOp::Write({}, {})", slot + 1, a, v)
+                            }
+                        }
                     }
 
-                    v_read_rs1.push(v);
-                    t_read_rs1.push(t_final[a]);
-                    t_final[a] = timestamp;
-                }
-                MemoryOp::Write(a, v) => {
-                    panic!("Unexpected rs1 MemoryOp::Write({}, {})", a, v);
-                }
-            };
-
-            match step.memory_ops[RS2] {
-                MemoryOp::Read(a) => {
-                    assert!(a < REGISTER_COUNT);
-                    let a = a as usize;
-                    let v = v_final[a];
-
-                    #[cfg(test)]
-                    {
-                        read_tuples.insert((a, v, t_final[a]));
-                        write_tuples.insert((a, v, timestamp));
+                    match step.memory_ops[RD] {
+                        MemoryOp::Read(a) => {
+                            panic!("Unexpected rd MemoryOp::Read({})", a)
+                        }
+                        MemoryOp::Write(a, v_new) => {
+                            assert!(a < REGISTER_COUNT);
+                            values[V_WRITE_RD][offset] = v_new;
+                            chunk.access(
+                                &mut values,
+                                offset,
+                                RD,
+                                a as usize,
+                                Some(v_new),
+                                timestamp,
+                            );
+                        }
                     }
 
-                    v_read_rs2.push(v);
-                    t_read_rs2.push(t_final[a]);
-                    t_final[a] = timestamp;
-                }
-                MemoryOp::Write(a, v) => {
-                    panic!("Unexpected rs2 MemoryOp::Write({}, {})", a, v)
+                    let (a, v_new) = match step.memory_ops[RAM] {
+                        MemoryOp::Read(a) => (a, None),
+                        MemoryOp::Write(a, v_new) => (a, Some(v_new)),
+                    };
+                    debug_assert!(a % 4 == 0);
+                    let remapped_a = remap_address(a, &program_io.memory_layout);
+                    values[A_RAM][offset] = remapped_a;
+                    chunk.access(
+                        &mut values,
+                        offset,
+                        RAM,
+                        remapped_a as usize,
+                        v_new,
+                        timestamp,
+                    );
                 }
-            };
+                chunk
+            })
+            .collect();
 
-            match step.memory_ops[RD] {
-                MemoryOp::Read(a) => {
-                    panic!("Unexpected rd MemoryOp::Read({})", a)
-                }
-                MemoryOp::Write(a, v_new) => {
-                    assert!(a < REGISTER_COUNT);
-                    let a = a as usize;
-                    let v_old = v_final[a];
-
-                    #[cfg(test)]
-                    {
-                        read_tuples.insert((a, v_old, t_final[a]));
-                        write_tuples.insert((a, v_new, timestamp));
+        // Replay the chunks' last accesses in order, recording the state of the addresses each
+        // chunk accesses as of its start.
+        let mut t_final = vec![0; memory_size];
+        let mut v_final = v_init.clone();
+        let start_states: Vec<HashMap<usize, (u64, u64)>> = chunks
+            .iter()
+            .map(|chunk| {
+                let start_state = chunk
+                    .last_accesses
+                    .keys()
+                    .map(|&a| (a, (v_final[a], t_final[a])))
+                    .collect();
+                for (&a, &(v, t)) in chunk.last_accesses.iter() {
+                    if let Some(v) = v {
+                        v_final[a] = v;
                     }
-
-                    v_read_rd.push(v_old);
-                    t_read_rd.push(t_final[a]);
-                    v_write_rd.push(v_new);
-                    v_final[a] = v_new;
-                    t_final[a] = timestamp;
+                    t_final[a] = t;
                 }
-            };
-
-            match step.memory_ops[RAM] {
-                MemoryOp::Read(a) => {
-                    debug_assert!(a % 4 == 0);
-                    let remapped_a = remap_address(a, &program_io.memory_layout) as usize;
-                    let v = v_final[remapped_a];
+                start_state
+            })
+            .collect();
 
-                    #[cfg(test)]
-                    {
-                        read_tuples.insert((remapped_a, v, t_final[remapped_a]));
-                        write_tuples.insert((remapped_a, v, timestamp));
+        transpose_chunks_mut(&mut step_values, chunk_size)
+            .into_par_iter()
+            .zip(chunks.par_iter().zip(start_states.par_iter()))
+            .for_each(|(mut values, (chunk, start_state))| {
+                for read in chunk.deferred_reads.iter() {
+                    let (v, t) = start_state[&read.address];
+                    values[V_READ[read.slot]][read.offset] = v;
+                    if read.first_access {
+                        values[T_READ[read.slot]][read.offset] = t;
                     }
-
-                    a_ram.push(remapped_a as u64);
-                    v_read_ram.push(v);
-                    t_read_ram.push(t_final[remapped_a]);
-                    v_write_ram.push(v);
-                    t_final[remapped_a] = timestamp;
-                }
-                MemoryOp::Write(a, v_new) => {
-                    debug_assert!(a % 4 == 0);
-                    let remapped_a = remap_address(a, &program_io.memory_layout) as usize;
-                    let v_old = v_final[remapped_a];
-
-                    #[cfg(test)]
-                    {
-                        read_tuples.insert((remapped_a, v_old, t_final[remapped_a]));
-                        write_tuples.insert((remapped_a, v_new, timestamp));
+                    if read.slot == RAM && read.is_read {
+                        values[V_WRITE_RAM][read.offset] = v;
                     }
-
-                    a_ram.push(remapped_a as u64);
-                    v_read_ram.push(v_old);
-                    t_read_ram.push(t_final[remapped_a]);
-                    v_write_ram.push(v_new);
-                    v_final[remapped_a] = v_new;
-                    t_final[remapped_a] = timestamp;
                 }
-            }
-        }
+            });
 
+        #[cfg(feature = "tracing")]
         drop(_enter);
-        drop(span);
 
         #[cfg(test)]
         {
+            let mut init_tuples: HashSet<(usize, u64, u64)> = HashSet::new();
+            let mut read_tuples: HashSet<(usize, u64, u64)> = HashSet::new();
+            let mut write_tuples: HashSet<(usize, u64, u64)> = HashSet::new();
             let mut final_tuples: HashSet<(usize, u64, u64)> = HashSet::new();
+            for (a, v) in v_init.iter().enumerate() {
+                init_tuples.insert((a, *v, 0u64));
+            }
+            for (i, step) in trace.steps().enumerate() {
+                let register = |slot: usize| match step.memory_ops[slot] {
+                    MemoryOp::Read(a) | MemoryOp::Write(a, _) => a as usize,
+                };
+                let addresses = [
+                    register(RS1),
+                    register(RS2),
+                    register(RD),
+                    step_values[A_RAM][i] as usize,
+                ];
+                for (slot, a) in addresses.into_iter().enumerate() {
+                    let v_read = step_values[V_READ[slot]][i];
+                    let v_write = match slot {
+                        RD => step_values[V_WRITE_RD][i],
+                        RAM => step_values[V_WRITE_RAM][i],
+                        _ => v_read,
+                    };
+                    read_tuples.insert((a, v_read, step_values[T_READ[slot]][i]));
+                    write_tuples.insert((a, v_write, i as u64));
+                }
+            }
             for (a, (v, t)) in v_final.iter().zip(t_final.iter()).enumerate() {
                 final_tuples.insert((a, *v, *t));
             }
@@ -451,6 +531,9 @@ impl<F: JoltField> ReadWriteMemoryPolynomials<F> {
             assert_eq!(set_difference.len(), 0);
         }
 
+        let [a_ram, v_write_rd, v_write_ram, v_read_rs1, v_read_rs2, v_read_rd, v_read_ram, t_read_rs1, t_read_rs2, t_read_rd, t_read_ram]: [Vec<u64>; NUM_STEP_VALUES] =
+            step_values.try_into().unwrap();
+
         let [a_ram, v_read_rd, v_read_rs1, v_read_rs2, v_read_ram, v_write_rd, v_write_ram, v_final, t_read_rd_poly, t_read_rs1_poly, t_read_rs2_poly, t_read_ram_poly, t_final, v_init] =
             map_to_polys([
                 &a_ram,
@@ -528,7 +611,10 @@ where
         t * gamma.square() + v * *gamma + a - *tau
     }
 
-    #[tracing::instrument(skip_all, name = "ReadWriteMemory::compute_leaves")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ReadWriteMemory::compute_leaves")
+    )]
     fn compute_leaves<'a>(
         _: &Self::Preprocessing,
         polynomials: &Self::Polynomials,
@@ -1067,7 +1153,10 @@ where
     PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 {
-    #[tracing::instrument(skip_all, name = "ReadWriteMemoryProof::prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ReadWriteMemoryProof::prove")
+    )]
     pub fn prove<'a>(
         generators: &PCS::Setup,
         preprocessing: &ReadWriteMemoryPreprocessing,
@@ -9,16 +9,11 @@ use crate::jolt::subtable::right_is_zero::RightIsZeroSubtable;
 use crate::poly::commitment::hyperkzg::HyperKZG;
 use crate::r1cs::constraints::JoltRV32IMConstraints;
 use crate::r1cs::inputs::JoltR1CSInputs;
-use ark_bn254::{Bn254, Fr};
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use enum_dispatch::enum_dispatch;
-use rand::{prelude::StdRng, RngCore};
-use serde::{Deserialize, Serialize};
-use std::any::TypeId;
-use strum::{EnumCount, IntoEnumIterator};
-use strum_macros::{EnumCount as EnumCountMacro, EnumIter};
-
-use super::{Jolt, JoltCommitments, JoltProof};
+
+use super::{Jolt, JoltCommitments, JoltProof, JoltVerifierPreprocessing};
 use crate::jolt::instruction::{
     add::ADDInstruction, and::ANDInstruction, beq::BEQInstruction, bge::BGEInstruction,
     bgeu::BGEUInstruction, bne::BNEInstruction, mul::MULInstruction, mulhu::MULHUInstruction,
@@ -26,82 +21,17 @@ use crate::jolt::instruction::{
     sltu::SLTUInstruction, sra::SRAInstruction, srl::SRLInstruction, sub::SUBInstruction,
     virtual_advice::ADVICEInstruction, virtual_assert_lte::ASSERTLTEInstruction,
     virtual_assert_valid_signed_remainder::AssertValidSignedRemainderInstruction,
-    virtual_movsign::MOVSIGNInstruction, xor::XORInstruction, JoltInstruction, JoltInstructionSet,
-    SubtableIndices,
+    virtual_movsign::MOVSIGNInstruction, xor::XORInstruction,
 };
 use crate::jolt::subtable::{
     and::AndSubtable, eq::EqSubtable, eq_abs::EqAbsSubtable, identity::IdentitySubtable,
     left_is_zero::LeftIsZeroSubtable, left_msb::LeftMSBSubtable, lt_abs::LtAbsSubtable,
     ltu::LtuSubtable, or::OrSubtable, right_msb::RightMSBSubtable, sign_extend::SignExtendSubtable,
     sll::SllSubtable, sra_sign::SraSignSubtable, srl::SrlSubtable,
-    truncate_overflow::TruncateOverflowSubtable, xor::XorSubtable, JoltSubtableSet, LassoSubtable,
-    SubtableId,
+    truncate_overflow::TruncateOverflowSubtable, xor::XorSubtable,
 };
 use crate::poly::commitment::commitment_scheme::CommitmentScheme;
-
-/// Generates an enum out of a list of JoltInstruction types. All JoltInstruction methods
-/// are callable on the enum type via enum_dispatch.
-macro_rules! instruction_set {
-    ($enum_name:ident, $($alias:ident: $struct:ty),+) => {
-        #[allow(non_camel_case_types)]
-        #[repr(u8)]
-        #[derive(Copy, Clone, Debug, PartialEq, EnumIter, EnumCountMacro, Serialize, Deserialize)]
-        #[enum_dispatch(JoltInstruction)]
-        pub enum $enum_name {
-            $($alias($struct)),+
-        }
-        impl JoltInstructionSet for $enum_name {}
-        impl $enum_name {
-            pub fn random_instruction(rng: &mut StdRng) -> Self {
-                let index = rng.next_u64() as usize % $enum_name::COUNT;
-                let instruction = $enum_name::iter()
-                    .enumerate()
-                    .filter(|(i, _)| *i == index)
-                    .map(|(_, x)| x)
-                    .next()
-                    .unwrap();
-                instruction.random(rng)
-            }
-        }
-        // Need a default so that we can derive EnumIter on `JoltR1CSInputs`
-        impl Default for $enum_name {
-            fn default() -> Self {
-                $enum_name::iter().collect::<Vec<_>>()[0]
-            }
-        }
-    };
-}
-
-/// Generates an enum out of a list of LassoSubtable types. All LassoSubtable methods
-/// are callable on the enum type via enum_dispatch.
-macro_rules! subtable_enum {
-    ($enum_name:ident, $($alias:ident: $struct:ty),+) => {
-        #[allow(non_camel_case_types)]
-        #[repr(u8)]
-        #[enum_dispatch(LassoSubtable<F>)]
-        #[derive(EnumCountMacro, EnumIter)]
-        pub enum $enum_name<F: JoltField> { $($alias($struct)),+ }
-        impl<F: JoltField> From<SubtableId> for $enum_name<F> {
-          fn from(subtable_id: SubtableId) -> Self {
-            $(
-              if subtable_id == TypeId::of::<$struct>() {
-                $enum_name::from(<$struct>::new())
-              } else
-            )+
-            { panic!("Unexpected subtable id {:?}", subtable_id) } // TODO(moodlezoup): better error handling
-          }
-        }
-
-        impl<F: JoltField> From<$enum_name<F>> for usize {
-            fn from(subtable: $enum_name<F>) -> usize {
-                // Discriminant: https://doc.rust-lang.org/reference/items/enumerations.html#pointer-casting
-                let byte = unsafe { *(&subtable as *const $enum_name<F> as *const u8) };
-                byte as usize
-            }
-        }
-        impl<F: JoltField> JoltSubtableSet<F> for $enum_name<F> {}
-    };
-}
+use crate::{instruction_set, subtable_enum};
 
 const WORD_SIZE: usize = 32;
 
@@ -227,33 +157,79 @@ pub trait Serializable: CanonicalSerialize + CanonicalDeserialize + Sized {
 }
 
 pub type ProofTranscript = KeccakTranscript;
+/// The commitment scheme used by the SDK. The prover and verifier are generic over
+/// [`CommitmentScheme`](crate::poly::commitment::commitment_scheme::CommitmentScheme), so
+/// e.g. `Zeromorph<Bn254, ProofTranscript>` can be swapped in here without further changes.
 pub type PCS = HyperKZG<Bn254, ProofTranscript>;
+/// A proof of RV32I execution committed with HyperKZG over the pairing engine `P`, e.g.
+/// `RV32IHyperKZGProof<Bls12_381>` for ~128-bit security where an EVM verifier is not needed.
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct JoltHyperKZGProof {
-    pub proof: RV32IJoltProof<Fr, PCS, ProofTranscript>,
-    pub commitments: JoltCommitments<PCS, ProofTranscript>,
+pub struct RV32IHyperKZGProof<P: Pairing>
+where
+    P::ScalarField: JoltField,
+{
+    pub proof: RV32IJoltProof<P::ScalarField, HyperKZG<P, ProofTranscript>, ProofTranscript>,
+    pub commitments: JoltCommitments<HyperKZG<P, ProofTranscript>, ProofTranscript>,
 }
 
-impl Serializable for JoltHyperKZGProof {}
+impl<P: Pairing> Serializable for RV32IHyperKZGProof<P> where P::ScalarField: JoltField {}
+
+/// The proof produced by the SDK, over BN254 so that it can be verified on the EVM.
+pub type JoltHyperKZGProof = RV32IHyperKZGProof<Bn254>;
+
+impl<const C: usize, F, PCS, ProofTranscript> Serializable
+    for JoltVerifierPreprocessing<C, F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    PCS::Setup: CanonicalSerialize + CanonicalDeserialize,
+    ProofTranscript: Transcript,
+{
+}
 
 // ==================== TEST ====================
 
 #[cfg(test)]
 mod tests {
     use ark_bn254::{Bn254, Fr, G1Projective};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use common::rv_trace::JoltDevice;
 
     use std::collections::HashSet;
 
     use crate::field::JoltField;
     use crate::host;
     use crate::jolt::instruction::JoltInstruction;
-    use crate::jolt::vm::rv32i_vm::{Jolt, RV32IJoltVM, C, M};
-    use crate::poly::commitment::commitment_scheme::CommitmentScheme;
+    use crate::jolt::vm::checkpoint::ProverCheckpoints;
+    use crate::jolt::vm::distributed::{
+        CommitmentShard, CommitmentWorkers, DistributedError, ShardCommitments,
+    };
+    use crate::jolt::vm::pipeline::StageScheduling;
+    use crate::jolt::vm::proof_format::ProofFormatError;
+    use crate::jolt::vm::rv32i_vm::{Jolt, RV32IJoltVM, C, M, RV32I};
+    use crate::jolt::vm::rv32i_vm::{JoltHyperKZGProof, RV32IHyperKZGProof, Serializable, PCS};
+    use crate::jolt::vm::{
+        JoltPreprocessing, JoltTraceStep, JoltVerifierPreprocessing, TracePadding,
+    };
+    use crate::poly::commitment::commitment_scheme::{
+        AggregatableCommitmentScheme, CommitmentScheme,
+    };
+    use crate::poly::commitment::dory::Dory;
     use crate::poly::commitment::hyperkzg::HyperKZG;
     use crate::poly::commitment::hyrax::HyraxScheme;
+    use crate::poly::commitment::ligero::Ligero;
     use crate::poly::commitment::mock::MockCommitScheme;
     use crate::poly::commitment::zeromorph::Zeromorph;
+    use crate::poly::opening_proof::ProverOpeningAccumulator;
+    use crate::r1cs::inputs::JoltR1CSInputs;
+    use crate::r1cs::spartan::UniformSpartanProof;
+    use crate::test_utils::assert_byte_flips_rejected;
+    use crate::utils::errors::{JoltError, ProofVerifyError};
+    use crate::utils::prover_config::ProverConfig;
     use crate::utils::transcript::{KeccakTranscript, Transcript};
+    use crate::utils::transcript_snapshots::assert_transcript_snapshot;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
     use std::sync::{LazyLock, Mutex};
     use strum::{EnumCount, IntoEnumIterator};
 
@@ -305,7 +281,7 @@ mod tests {
         let mut program = host::Program::new("fibonacci-guest");
         program.set_input(&9u32);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
+        let (io_device, trace) = program.trace().unwrap();
         drop(artifact_guard);
 
         let preprocessing = RV32IJoltVM::preprocess(
@@ -321,7 +297,8 @@ mod tests {
                 io_device,
                 trace,
                 preprocessing.clone(),
-            );
+            )
+            .unwrap();
         let verification_result =
             RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
         assert!(
@@ -356,6 +333,871 @@ mod tests {
         fib_e2e::<Fr, HyperKZG<Bn254, KeccakTranscript>, KeccakTranscript>();
     }
 
+    #[test]
+    fn fib_e2e_masked() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let config = ProverConfig {
+            mask_round_polys: true,
+            ..Default::default()
+        };
+        // The masks are opened through the batched opening proof along with the witness
+        let (proof, commitments, debug_info) = config.install(|| {
+            <RV32IJoltVM as Jolt<
+                Fr,
+                HyperKZG<Bn254, KeccakTranscript>,
+                C,
+                M,
+                KeccakTranscript,
+            >>::prove(io_device, trace, preprocessing.clone())
+            .unwrap()
+        });
+        assert!(proof.r1cs.masks.is_some());
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_univariate_skip() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let config = ProverConfig {
+            univariate_skip: true,
+            ..Default::default()
+        };
+        let (proof, commitments, debug_info) = config.install(|| {
+            <RV32IJoltVM as Jolt<
+                Fr,
+                HyperKZG<Bn254, KeccakTranscript>,
+                C,
+                M,
+                KeccakTranscript,
+            >>::prove(io_device, trace, preprocessing.clone())
+            .unwrap()
+        });
+        assert!(proof.r1cs.outer_univariate_skip.is_some());
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_cached_preprocessing() {
+        let cache_dir = std::env::temp_dir().join("jolt_fib_e2e_cached_preprocessing_test");
+        let _ = std::fs::remove_dir_all(&cache_dir);
+
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let preprocessed: JoltPreprocessing<C, Fr, PCS, KeccakTranscript> = program
+            .load_or_preprocess(&cache_dir, 1 << 20, 1 << 20)
+            .unwrap();
+        let loaded: JoltPreprocessing<C, Fr, PCS, KeccakTranscript> = program
+            .load_or_preprocess(&cache_dir, 1 << 20, 1 << 20)
+            .unwrap();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+
+        let mut preprocessed_bytes = Vec::new();
+        preprocessed
+            .serialize_uncompressed(&mut preprocessed_bytes)
+            .unwrap();
+        let mut loaded_bytes = Vec::new();
+        loaded.serialize_uncompressed(&mut loaded_bytes).unwrap();
+        assert_eq!(preprocessed_bytes, loaded_bytes);
+
+        let (proof, commitments, debug_info) =
+            <RV32IJoltVM as Jolt<Fr, PCS, C, M, KeccakTranscript>>::prove(
+                io_device,
+                trace,
+                loaded.clone(),
+            )
+            .unwrap();
+        let verification_result = RV32IJoltVM::verify(loaded, proof, commitments, debug_info);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_verifier_preprocessing() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing: JoltPreprocessing<C, Fr, PCS, KeccakTranscript> =
+            RV32IJoltVM::preprocess(
+                bytecode,
+                io_device.memory_layout.clone(),
+                memory_init,
+                1 << 20,
+                1 << 20,
+                1 << 20,
+            );
+        let verifier_preprocessing = preprocessing.verifier_preprocessing();
+        let verifier_key = verifier_preprocessing.serialize_to_bytes().unwrap();
+        assert!(verifier_key.len() < preprocessing.compressed_size() / 10);
+
+        let (proof, commitments, _) =
+            <RV32IJoltVM as Jolt<Fr, PCS, C, M, KeccakTranscript>>::prove(
+                io_device,
+                trace,
+                preprocessing,
+            )
+            .unwrap();
+        let verifier_preprocessing =
+            JoltVerifierPreprocessing::deserialize_from_bytes(&verifier_key).unwrap();
+        let verification_result = RV32IJoltVM::verify_with_verifier_preprocessing(
+            verifier_preprocessing,
+            proof,
+            commitments,
+            None,
+        );
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_aggregated() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        let (bytecode, memory_init) = program.decode();
+        let traces: Vec<_> = [9u32, 10u32]
+            .iter()
+            .map(|n| {
+                let mut program = program.clone();
+                program.set_input(n);
+                program.trace().unwrap()
+            })
+            .collect();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            traces[0].0.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let proof_bytes: Vec<_> = traces
+            .into_iter()
+            .map(|(io_device, trace)| {
+                let (proof, commitments, _) =
+                    <RV32IJoltVM as Jolt<
+                        Fr,
+                        HyperKZG<Bn254, KeccakTranscript>,
+                        C,
+                        M,
+                        KeccakTranscript,
+                    >>::prove(io_device, trace, preprocessing.clone())
+                    .unwrap();
+                JoltHyperKZGProof { proof, commitments }.to_bytes()
+            })
+            .collect();
+        let proofs = || {
+            proof_bytes.iter().map(|bytes| {
+                let proof = JoltHyperKZGProof::from_bytes(bytes).unwrap();
+                (proof.proof, proof.commitments)
+            })
+        };
+
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let verification_result =
+            RV32IJoltVM::verify_aggregated(preprocessing.clone(), proofs().collect(), &mut rng);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+
+        // The same, deferring each proof's pairing check to an accumulator held by the caller
+        let mut verifier_preprocessing = JoltVerifierPreprocessing::from(preprocessing);
+        let mut accumulator =
+            HyperKZG::<Bn254, KeccakTranscript>::accumulator(&verifier_preprocessing.generators);
+        for (proof, commitments) in proofs() {
+            RV32IJoltVM::verify_deferred(
+                &mut verifier_preprocessing,
+                proof,
+                commitments,
+                &mut accumulator,
+            )
+            .unwrap();
+        }
+        assert_eq!(accumulator.len(), 2);
+        assert!(accumulator.verify(&mut rng).is_ok());
+
+        // The same, as proofs of two programs with separate verifier keys
+        let mut verifier_keys = vec![
+            preprocessing.verifier_preprocessing(),
+            preprocessing.verifier_preprocessing(),
+        ];
+        let batch = || {
+            proofs()
+                .enumerate()
+                .map(|(index, (proof, commitments))| (index, proof, commitments))
+                .collect::<Vec<_>>()
+        };
+        let mut program_io: Vec<JoltDevice> = proofs().map(|(proof, _)| proof.program_io).collect();
+        let verification_result =
+            RV32IJoltVM::batch_verify(&mut verifier_keys, batch(), &program_io, &mut rng);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+        program_io.swap(0, 1);
+        assert!(matches!(
+            RV32IJoltVM::batch_verify(&mut verifier_keys, batch(), &program_io, &mut rng),
+            Err(ProofVerifyError::ProgramIOMismatch)
+        ));
+    }
+
+    #[test]
+    fn fib_e2e_proof_format() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let (proof, commitments, _) =
+            <RV32IJoltVM as Jolt<Fr, PCS, C, M, KeccakTranscript>>::prove(
+                io_device,
+                trace,
+                preprocessing.clone(),
+            )
+            .unwrap();
+        let proof = JoltHyperKZGProof { proof, commitments };
+
+        let bytes = proof.to_bytes();
+        assert_eq!(proof.size_breakdown().total(), bytes.len());
+        let decoded = JoltHyperKZGProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(
+            RV32IJoltVM::verify(preprocessing, decoded.proof, decoded.commitments, None).is_ok()
+        );
+
+        // Proofs of another version or curve, or cut short, are rejected
+        let mut other_version = bytes.clone();
+        other_version[4] += 1;
+        assert!(matches!(
+            JoltHyperKZGProof::from_bytes(&other_version),
+            Err(ProofFormatError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            RV32IHyperKZGProof::<ark_bls12_381::Bls12_381>::from_bytes(&bytes),
+            Err(ProofFormatError::CurveMismatch { .. })
+        ));
+        assert!(matches!(
+            JoltHyperKZGProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofFormatError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn fib_e2e_byte_flips_rejected() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let (proof, commitments, _) =
+            <RV32IJoltVM as Jolt<Fr, PCS, C, M, KeccakTranscript>>::prove(
+                io_device,
+                trace,
+                preprocessing.clone(),
+            )
+            .unwrap();
+        let bytes = JoltHyperKZGProof { proof, commitments }
+            .serialize_to_bytes()
+            .unwrap();
+
+        let verifier_preprocessing = preprocessing.verifier_preprocessing();
+        assert_byte_flips_rejected(&bytes, 16, |bytes| {
+            JoltHyperKZGProof::deserialize_from_bytes(bytes).is_ok_and(|proof| {
+                RV32IJoltVM::verify_with_verifier_preprocessing(
+                    verifier_preprocessing.clone(),
+                    proof.proof,
+                    proof.commitments,
+                    None,
+                )
+                .is_ok()
+            })
+        });
+    }
+
+    #[test]
+    fn fib_e2e_memory_layout_mismatch() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let (mut proof, commitments, _) = <RV32IJoltVM as Jolt<
+            Fr,
+            MockCommitScheme<Fr, KeccakTranscript>,
+            C,
+            M,
+            KeccakTranscript,
+        >>::prove(io_device, trace, preprocessing.clone())
+        .unwrap();
+        proof.program_io.memory_layout.ram_size += 4;
+        assert!(matches!(
+            RV32IJoltVM::verify(preprocessing, proof, commitments, None),
+            Err(ProofVerifyError::MemoryLayoutMismatch)
+        ));
+    }
+
+    #[test]
+    fn fib_e2e_invalid_inputs() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let prove = |io_device, trace| {
+            <RV32IJoltVM as Jolt<
+                Fr,
+                MockCommitScheme<Fr, KeccakTranscript>,
+                C,
+                M,
+                KeccakTranscript,
+            >>::prove(io_device, trace, preprocessing.clone())
+        };
+
+        let mut oversized_io = io_device.clone();
+        let max_input_size = oversized_io.memory_layout.max_input_size as usize;
+        oversized_io.inputs = vec![0; max_input_size + 1];
+        assert!(matches!(
+            prove(oversized_io, trace.clone()),
+            Err(JoltError::InputTooLarge(..))
+        ));
+
+        let mut foreign_trace = trace;
+        foreign_trace[3].bytecode_row.address = 4;
+        assert!(matches!(
+            prove(io_device, foreign_trace),
+            Err(JoltError::UnknownBytecodeAddress {
+                step: 3,
+                address: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn fib_e2e_bls12_381() {
+        fib_e2e::<
+            ark_bls12_381::Fr,
+            HyperKZG<ark_bls12_381::Bls12_381, KeccakTranscript>,
+            KeccakTranscript,
+        >();
+    }
+
+    #[test]
+    fn fib_e2e_dory() {
+        fib_e2e::<Fr, Dory<Bn254, KeccakTranscript>, KeccakTranscript>();
+    }
+
+    #[test]
+    fn fib_e2e_ligero() {
+        fib_e2e::<Fr, Ligero<Fr, KeccakTranscript>, KeccakTranscript>();
+    }
+
+    #[test]
+    fn fib_e2e_hidden_trace_length() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let padding = TracePadding::Hidden {
+            min_length: 1 << 16,
+            min_memory_size: 1 << 20,
+        };
+        assert!(trace.len() < 1 << 16);
+        let (proof, commitments, debug_info) =
+            <RV32IJoltVM as Jolt<
+                Fr,
+                MockCommitScheme<Fr, KeccakTranscript>,
+                C,
+                M,
+                KeccakTranscript,
+            >>::prove_with_padding(io_device, trace, preprocessing.clone(), padding)
+            .unwrap();
+        assert_eq!(proof.trace_length, 1 << 16);
+        // One GKR layer per variable of the (padded) memory
+        assert_eq!(
+            proof
+                .read_write_memory
+                .memory_checking_proof
+                .init_final_grand_product
+                .gkr_layers
+                .len(),
+            20
+        );
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_concurrent_stages() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let prove = |scheduling| {
+            <RV32IJoltVM as Jolt<
+                Fr,
+                MockCommitScheme<Fr, KeccakTranscript>,
+                C,
+                M,
+                KeccakTranscript,
+            >>::prove_with_options(
+                io_device.clone(),
+                trace.clone(),
+                preprocessing.clone(),
+                TracePadding::default(),
+                scheduling,
+            )
+            .unwrap()
+        };
+        let (sequential_proof, _, _) = prove(StageScheduling::Sequential);
+        let (proof, commitments, debug_info) = prove(StageScheduling::Concurrent);
+
+        let mut sequential_bytes = vec![];
+        sequential_proof
+            .serialize_compressed(&mut sequential_bytes)
+            .unwrap();
+        let mut concurrent_bytes = vec![];
+        proof.serialize_compressed(&mut concurrent_bytes).unwrap();
+        assert!(
+            sequential_bytes == concurrent_bytes,
+            "proof depends on stage scheduling"
+        );
+
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_resumes_from_checkpoints() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let dir = std::env::temp_dir().join(format!("jolt-fib-checkpoints-{}", std::process::id()));
+        let checkpoints = ProverCheckpoints::new(&dir).unwrap();
+        let prove = |checkpoints| {
+            <RV32IJoltVM as Jolt<
+                Fr,
+                MockCommitScheme<Fr, KeccakTranscript>,
+                C,
+                M,
+                KeccakTranscript,
+            >>::prove_with_checkpoints(
+                io_device.clone(),
+                &trace,
+                preprocessing.clone(),
+                TracePadding::default(),
+                StageScheduling::default(),
+                checkpoints,
+            )
+            .unwrap()
+        };
+        let (expected_proof, _, _) = prove(None);
+        prove(Some(&checkpoints));
+        // Resume as if the job had been preempted during the R1CS stage
+        std::fs::remove_file(dir.join("r1cs stage.checkpoint")).unwrap();
+        let (proof, commitments, debug_info) = prove(Some(&checkpoints));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut expected_bytes = vec![];
+        expected_proof
+            .serialize_compressed(&mut expected_bytes)
+            .unwrap();
+        let mut resumed_bytes = vec![];
+        proof.serialize_compressed(&mut resumed_bytes).unwrap();
+        assert!(expected_bytes == resumed_bytes, "resumed proof differs");
+
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_distributed_commitments() {
+        type TestPCS = MockCommitScheme<Fr, KeccakTranscript>;
+
+        /// Workers that commit to their shards on this machine, passing their commitments
+        /// through a serialization round trip as a remote worker's would.
+        struct LocalWorkers<'a> {
+            io_device: &'a JoltDevice,
+            trace: &'a [JoltTraceStep<RV32I>],
+            preprocessing: &'a JoltPreprocessing<C, Fr, TestPCS, KeccakTranscript>,
+            num_shards: usize,
+        }
+
+        impl CommitmentWorkers<<TestPCS as CommitmentScheme<KeccakTranscript>>::Commitment>
+            for LocalWorkers<'_>
+        {
+            fn num_shards(&self) -> usize {
+                self.num_shards
+            }
+
+            fn commit_shard(
+                &self,
+                shard: CommitmentShard,
+            ) -> Result<
+                ShardCommitments<<TestPCS as CommitmentScheme<KeccakTranscript>>::Commitment>,
+                DistributedError,
+            > {
+                let worker_error = |message: String| DistributedError::Worker {
+                    shard: shard.index,
+                    message,
+                };
+                let commitments =
+                    <RV32IJoltVM as Jolt<Fr, TestPCS, C, M, KeccakTranscript>>::commit_shard(
+                        self.io_device,
+                        self.trace,
+                        self.preprocessing,
+                        TracePadding::default(),
+                        shard,
+                    )
+                    .map_err(|error| worker_error(error.to_string()))?;
+                let mut bytes = vec![];
+                commitments
+                    .serialize_compressed(&mut bytes)
+                    .map_err(|error| worker_error(error.to_string()))?;
+                ShardCommitments::deserialize_compressed(bytes.as_slice())
+                    .map_err(|error| worker_error(error.to_string()))
+            }
+        }
+
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let workers = LocalWorkers {
+            io_device: &io_device,
+            trace: &trace,
+            preprocessing: &preprocessing,
+            num_shards: 3,
+        };
+        let prove = |workers: Option<&dyn CommitmentWorkers<_>>| {
+            <RV32IJoltVM as Jolt<Fr, TestPCS, C, M, KeccakTranscript>>::prove_distributed(
+                io_device.clone(),
+                &trace,
+                preprocessing.clone(),
+                TracePadding::default(),
+                StageScheduling::default(),
+                None,
+                workers,
+            )
+            .unwrap()
+        };
+        let (expected_proof, expected_commitments, _) = prove(None);
+        let (proof, commitments, debug_info) = prove(Some(&workers));
+
+        fn to_bytes(value: &impl CanonicalSerialize) -> Vec<u8> {
+            let mut bytes = vec![];
+            value.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        }
+        assert!(
+            to_bytes(&expected_commitments) == to_bytes(&commitments),
+            "distributed commitments differ"
+        );
+        assert!(
+            to_bytes(&expected_proof) == to_bytes(&proof),
+            "distributed proof differs"
+        );
+
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_cycle_limit() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (_, trace) = program.trace().unwrap();
+        assert_eq!(program.cycles_used().unwrap(), trace.len());
+
+        program.set_max_cycles(10);
+        assert_eq!(program.trace().unwrap_err().max_cycles, 10);
+        assert!(program.profile().is_err());
+        drop(artifact_guard);
+    }
+
+    #[test]
+    fn fib_e2e_seeded_proofs_reproducible() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        let config = ProverConfig {
+            seed: Some([42; 32]),
+            ..Default::default()
+        };
+        let prove = || {
+            let (proof, _, _) = config.install(|| {
+                <RV32IJoltVM as Jolt<
+                    Fr,
+                    MockCommitScheme<Fr, KeccakTranscript>,
+                    C,
+                    M,
+                    KeccakTranscript,
+                >>::prove(io_device.clone(), trace.clone(), preprocessing.clone())
+                .unwrap()
+            });
+            let mut bytes = vec![];
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        assert!(prove() == prove(), "seeded proofs differ");
+    }
+
+    #[test]
+    fn fib_spartan_transcript_snapshot() {
+        type ProofPCS = HyperKZG<Bn254, KeccakTranscript>;
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing: JoltPreprocessing<C, Fr, ProofPCS, KeccakTranscript> =
+            RV32IJoltVM::preprocess(
+                bytecode.clone(),
+                io_device.memory_layout.clone(),
+                memory_init,
+                1 << 20,
+                1 << 20,
+                1 << 20,
+            );
+        let padded_trace_length = trace.len().next_power_of_two();
+        let (polynomials, r1cs_builder) =
+            <RV32IJoltVM as Jolt<Fr, ProofPCS, C, M, KeccakTranscript>>::generate_witness(
+                &io_device,
+                &trace,
+                &preprocessing,
+                padded_trace_length,
+            )
+            .unwrap();
+        let key = UniformSpartanProof::<C, JoltR1CSInputs, Fr, ProofPCS, KeccakTranscript>::setup(
+            &r1cs_builder,
+            padded_trace_length,
+        );
+
+        let mut transcript = KeccakTranscript::new(b"snapshot_spartan");
+        let mut openings = ProverOpeningAccumulator::new();
+        UniformSpartanProof::<C, JoltR1CSInputs, Fr, ProofPCS, KeccakTranscript>::prove(
+            &r1cs_builder,
+            &key,
+            &preprocessing.generators,
+            &polynomials,
+            false,
+            &mut openings,
+            &mut transcript,
+        )
+        .unwrap();
+        assert_transcript_snapshot("spartan", &transcript);
+    }
+
+    #[test]
+    fn fib_e2e_transcript_snapshot() {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            io_device.memory_layout.clone(),
+            memory_init,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
+        // Fixes any randomness the prover appends to the transcript (e.g. the sum-check masks)
+        let config = ProverConfig {
+            seed: Some([42; 32]),
+            ..Default::default()
+        };
+        let (_, _, debug_info) = config.install(|| {
+            <RV32IJoltVM as Jolt<
+                Fr,
+                HyperKZG<Bn254, KeccakTranscript>,
+                C,
+                M,
+                KeccakTranscript,
+            >>::prove(io_device, trace, preprocessing)
+            .unwrap()
+        });
+        assert_transcript_snapshot("jolt_proof", &debug_info.unwrap().transcript);
+    }
+
     // TODO(sragss): Finish Binius.
     // #[test]
     // fn fib_e2e_binius() {
@@ -371,7 +1213,7 @@ mod tests {
         program.set_input(&234u32);
         program.set_input(&345u32);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
+        let (io_device, trace) = program.trace().unwrap();
 
         let preprocessing = RV32IJoltVM::preprocess(
             bytecode.clone(),
@@ -388,7 +1230,8 @@ mod tests {
                 C,
                 M,
                 KeccakTranscript,
-            >>::prove(io_device, trace, preprocessing.clone());
+            >>::prove(io_device, trace, preprocessing.clone())
+            .unwrap();
         let verification_result =
             RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments, debug_info);
         assert!(
@@ -406,7 +1249,7 @@ mod tests {
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
+        let (io_device, trace) = program.trace().unwrap();
         drop(guard);
 
         let preprocessing = RV32IJoltVM::preprocess(
@@ -424,7 +1267,8 @@ mod tests {
                 C,
                 M,
                 KeccakTranscript,
-            >>::prove(io_device, trace, preprocessing.clone());
+            >>::prove(io_device, trace, preprocessing.clone())
+            .unwrap();
 
         let verification_result =
             RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments, debug_info);
@@ -442,7 +1286,7 @@ mod tests {
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
+        let (io_device, trace) = program.trace().unwrap();
         drop(guard);
 
         let preprocessing = RV32IJoltVM::preprocess(
@@ -461,7 +1305,8 @@ mod tests {
             KeccakTranscript,
         >>::prove(
             io_device, trace, preprocessing.clone()
-        );
+        )
+        .unwrap();
 
         let verification_result =
             RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments, debug_info);
@@ -479,7 +1324,7 @@ mod tests {
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
+        let (io_device, trace) = program.trace().unwrap();
         drop(guard);
 
         let preprocessing = RV32IJoltVM::preprocess(
@@ -498,7 +1343,8 @@ mod tests {
             KeccakTranscript,
         >>::prove(
             io_device, trace, preprocessing.clone()
-        );
+        )
+        .unwrap();
 
         let verification_result =
             RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments, debug_info);
@@ -513,7 +1359,7 @@ mod tests {
     fn memory_ops_e2e_hyperkzg() {
         let mut program = host::Program::new("memory-ops-guest");
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace) = program.trace();
+        let (io_device, trace) = program.trace().unwrap();
 
         let preprocessing = RV32IJoltVM::preprocess(
             bytecode.clone(),
@@ -531,7 +1377,8 @@ mod tests {
             KeccakTranscript,
         >>::prove(
             io_device, trace, preprocessing.clone()
-        );
+        )
+        .unwrap();
 
         let verification_result =
             RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments, debug_info);
@@ -549,7 +1396,7 @@ mod tests {
         let mut program = host::Program::new("fibonacci-guest");
         program.set_input(&9u32);
         let (bytecode, memory_init) = program.decode();
-        let (mut io_device, mut trace) = program.trace();
+        let (mut io_device, mut trace) = program.trace().unwrap();
         trace.truncate(100);
         io_device.outputs[0] = 0; // change the output to 0
         drop(artifact_guard);
@@ -570,7 +1417,8 @@ mod tests {
             KeccakTranscript,
         >>::prove(
             io_device, trace, preprocessing.clone()
-        );
+        )
+        .unwrap();
         let _verification_result =
             RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
     }
@@ -582,7 +1430,7 @@ mod tests {
         let mut program = host::Program::new("fibonacci-guest");
         program.set_input(&1u8); // change input to 1 so that termination bit equal true
         let (bytecode, memory_init) = program.decode();
-        let (mut io_device, trace) = program.trace();
+        let (mut io_device, trace) = program.trace().unwrap();
         let memory_layout = io_device.memory_layout.clone();
         drop(artifact_guard);
 
@@ -609,7 +1457,8 @@ mod tests {
             KeccakTranscript,
         >>::prove(
             io_device, trace, preprocessing.clone()
-        );
+        )
+        .unwrap();
         let _verification_result =
             RV32IJoltVM::verify(preprocessing, proof, commitments, debug_info);
     }
@@ -8,11 +8,11 @@ use crate::subprotocols::grand_product::{
     BatchedGrandProductProof,
 };
 use crate::utils::math::Math;
+use crate::utils::par::prelude::*;
 use crate::utils::thread::drop_in_background_thread;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use common::constants::MEMORY_OPS_PER_INSTRUCTION;
 use itertools::interleave;
-use rayon::prelude::*;
 #[cfg(test)]
 use std::collections::HashSet;
 
@@ -125,7 +125,10 @@ where
     PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 {
-    #[tracing::instrument(skip_all, name = "TimestampRangeCheckWitness::new")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "TimestampRangeCheckWitness::new")
+    )]
     pub fn generate_witness(
         read_timestamps: &[Vec<u64>; MEMORY_OPS_PER_INSTRUCTION],
     ) -> TimestampRangeCheckPolynomials<F> {
@@ -274,7 +277,10 @@ where
         a * gamma + t - *tau
     }
 
-    #[tracing::instrument(skip_all, name = "RangeCheckPolynomials::compute_leaves")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "RangeCheckPolynomials::compute_leaves")
+    )]
     /// For these timestamp range check polynomials, the init/final polynomials are the
     /// the same length as the read/write polynomials. This is because the init/final polynomials
     /// are determined by the range (0..N) that we are checking for, which in this case is
@@ -608,7 +614,10 @@ where
     PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 {
-    #[tracing::instrument(skip_all, name = "TimestampValidityProof::prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "TimestampValidityProof::prove")
+    )]
     pub fn prove<'a>(
         generators: &PCS::Setup,
         polynomials: &'a TimestampRangeCheckPolynomials<F>,
@@ -673,7 +682,10 @@ where
         }
     }
 
-    #[tracing::instrument(skip_all, name = "TimestampValidityProof::prove_grand_products")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "TimestampValidityProof::prove_grand_products")
+    )]
     fn prove_grand_products(
         polynomials: &TimestampRangeCheckPolynomials<F>,
         jolt_polynomials: &JoltPolynomials<F>,
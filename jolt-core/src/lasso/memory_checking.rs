@@ -17,9 +17,9 @@ use crate::{
 };
 
 use crate::field::JoltField;
+use crate::utils::par::prelude::*;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use itertools::interleave;
-use rayon::prelude::*;
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct MultisetHashes<F: JoltField> {
@@ -224,7 +224,10 @@ where
     /// The data associated with each memory slot. A triple (a, v, t) by default.
     type MemoryTuple: Copy + Clone = (F, F, F);
 
-    #[tracing::instrument(skip_all, name = "MemoryCheckingProver::prove_memory_checking")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "MemoryCheckingProver::prove_memory_checking")
+    )]
     /// Generates a memory checking proof for the given committed polynomials.
     fn prove_memory_checking(
         pcs_setup: &PCS::Setup,
@@ -281,7 +284,10 @@ where
         }
     }
 
-    #[tracing::instrument(skip_all, name = "MemoryCheckingProver::prove_grand_products")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "MemoryCheckingProver::prove_grand_products")
+    )]
     /// Proves the grand products for the memory checking multisets (init, read, write, final).
     fn prove_grand_products(
         preprocessing: &Self::Preprocessing,
@@ -404,7 +410,10 @@ where
 
     /// Constructs a batched grand product circuit for the read and write multisets associated
     /// with the given leaves. Also returns the corresponding multiset hashes for each memory.
-    #[tracing::instrument(skip_all, name = "MemoryCheckingProver::read_write_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "MemoryCheckingProver::read_write_grand_product")
+    )]
     fn read_write_grand_product(
         _preprocessing: &Self::Preprocessing,
         _polynomials: &Self::Polynomials,
@@ -421,7 +430,10 @@ where
 
     /// Constructs a batched grand product circuit for the init and final multisets associated
     /// with the given leaves. Also returns the corresponding multiset hashes for each memory.
-    #[tracing::instrument(skip_all, name = "MemoryCheckingProver::init_final_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "MemoryCheckingProver::init_final_grand_product")
+    )]
     fn init_final_grand_product(
         _preprocessing: &Self::Preprocessing,
         _polynomials: &Self::Polynomials,
@@ -1,3 +1,4 @@
+use crate::utils::par::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use crate::{
     field::JoltField,
     jolt::vm::{JoltCommitments, JoltPolynomials, ProverDebugInfo},
@@ -7,7 +8,6 @@ use crate::{
     },
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use std::marker::{PhantomData, Sync};
 
 use super::memory_checking::{
@@ -112,7 +112,10 @@ where
         t * gamma.square() + v * *gamma + a - *tau
     }
 
-    #[tracing::instrument(skip_all, name = "Surge::compute_leaves")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Surge::compute_leaves")
+    )]
     fn compute_leaves(
         preprocessing: &Self::Preprocessing,
         polynomials: &Self::Polynomials,
@@ -330,7 +333,10 @@ where
     F: JoltField,
     Instruction: JoltInstruction + Default + Sync,
 {
-    #[tracing::instrument(skip_all, name = "Surge::preprocess")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Surge::preprocess")
+    )]
     pub fn preprocess() -> Self {
         let instruction = Instruction::default();
 
@@ -386,7 +392,10 @@ where
         std::cmp::max(num_read_write_generators, num_init_final_generators)
     }
 
-    #[tracing::instrument(skip_all, name = "Surge::prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Surge::prove")
+    )]
     pub fn prove(
         preprocessing: &SurgePreprocessing<F, Instruction, C, M>,
         generators: &PCS::Setup,
@@ -394,7 +403,7 @@ where
     ) -> (Self, Option<ProverDebugInfo<F, ProofTranscript>>) {
         let mut transcript = ProofTranscript::new(b"Surge transcript");
         let mut opening_accumulator: ProverOpeningAccumulator<F, ProofTranscript> =
-            ProverOpeningAccumulator::new();
+            ProverOpeningAccumulator::for_scheme::<PCS>();
         let protocol_name = Self::protocol_name();
         transcript.append_message(protocol_name);
 
@@ -550,7 +559,10 @@ where
         )
     }
 
-    #[tracing::instrument(skip_all, name = "Surge::construct_polys")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Surge::construct_polys")
+    )]
     fn generate_witness(
         preprocessing: &SurgePreprocessing<F, Instruction, C, M>,
         ops: &[Instruction],
@@ -635,7 +647,10 @@ where
         }
     }
 
-    #[tracing::instrument(skip_all, name = "Surge::compute_primary_sumcheck_claim")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Surge::compute_primary_sumcheck_claim")
+    )]
     fn compute_primary_sumcheck_claim(polys: &SurgePolynomials<F>, eq: &DensePolynomial<F>) -> F {
         let g_operands = &polys.E_polys;
         let hypercube_size = g_operands[0].len();
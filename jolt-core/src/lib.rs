@@ -11,6 +11,9 @@
 #![allow(clippy::len_without_is_empty)]
 #![allow(type_alias_bounds)]
 #![allow(clippy::too_long_first_doc_paragraph)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 #[cfg(feature = "host")]
 pub mod benches;
@@ -18,11 +21,27 @@ pub mod benches;
 #[cfg(feature = "host")]
 pub mod host;
 
+#[cfg(feature = "std")]
+pub mod curves;
+#[cfg(feature = "evm")]
+pub mod export;
 pub mod field;
+#[cfg(feature = "std")]
 pub mod jolt;
+#[cfg(feature = "std")]
 pub mod lasso;
+#[cfg(feature = "std")]
 pub mod msm;
+#[cfg(feature = "std")]
 pub mod poly;
+#[cfg(feature = "std")]
 pub mod r1cs;
+#[cfg(feature = "std")]
 pub mod subprotocols;
+#[cfg(all(feature = "std", any(test, feature = "test-utils")))]
+pub mod test_utils;
 pub mod utils;
+#[cfg(feature = "verifier")]
+pub mod verifier;
+#[cfg(feature = "std")]
+pub mod wasm;
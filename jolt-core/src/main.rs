@@ -5,6 +5,8 @@ use jolt_core::benches::{
     sum_timer::CumulativeTimingLayer,
 };
 
+use jolt_core::utils::metrics::{CountingAllocator, MetricsLayer};
+
 use std::any::Any;
 
 use tracing_chrome::ChromeLayerBuilder;
@@ -12,6 +14,9 @@ use tracing_flame::FlameLayer;
 use tracing_subscriber::{self, fmt::format::FmtSpan, prelude::*};
 use tracing_texray::TeXRayLayer;
 
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
 /// Search for a pattern in a file and display the lines that contain it.
 #[derive(Parser, Debug)]
 struct Cli {
@@ -72,6 +77,7 @@ enum Format {
     Flamegraph,
     Chrome,
     Sum,
+    Metrics,
 }
 
 fn main() {
@@ -115,6 +121,15 @@ fn trace(args: TraceArgs) {
         }
     }
 
+    let mut metrics = None;
+    if let Some(format) = &args.format {
+        if format.contains(&Format::Metrics) {
+            let (metrics_layer, handle) = MetricsLayer::new();
+            layers.push(metrics_layer.boxed());
+            metrics = Some(handle);
+        }
+    }
+
     tracing_subscriber::registry().with(layers).init();
     for (span, bench) in benchmarks(args.pcs, args.name, args.num_cycles, None, None).into_iter() {
         span.to_owned().in_scope(|| {
@@ -122,4 +137,13 @@ fn trace(args: TraceArgs) {
             tracing::info!("Bench Complete");
         });
     }
+
+    if let Some(metrics) = metrics {
+        let report = metrics.report();
+        std::fs::write("metrics.json", report.to_json()).unwrap();
+        std::fs::write("metrics-trace.json", report.to_chrome_trace()).unwrap();
+        println!(
+            "Wrote per-span metrics to metrics.json and their Chrome trace to metrics-trace.json."
+        );
+    }
 }
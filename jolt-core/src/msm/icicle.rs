@@ -0,0 +1,132 @@
+//! GPU MSM backend built on icicle, enabled with the `icicle` feature.
+//!
+//! Only BN254 G1 MSMs with full-width scalars are offloaded, since these dominate the cost of
+//! the KZG-based commitments; everything else, and every MSM when no CUDA device is present,
+//! runs on the CPU.
+use std::any::{Any, TypeId};
+use std::sync::OnceLock;
+
+use crate::utils::par::prelude::*;
+use ark_bn254::{Fq, Fr, G1Affine, G1Projective};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use icicle_bn254::curve::{BaseField, CurveCfg, ScalarField};
+use icicle_core::curve::{Affine, Projective};
+use icicle_core::msm::{msm as icicle_msm, MSMConfig};
+use icicle_core::traits::FieldImpl;
+use icicle_runtime::memory::HostSlice;
+
+use super::VariableBaseMSM;
+
+/// Below this size, copying the inputs to the device costs more than the MSM itself.
+const MIN_GPU_MSM_SIZE: usize = 1 << 12;
+
+static DEVICE_AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+/// Loads the icicle backend and selects the first CUDA device, if any. Returns whether a device
+/// is available; the result is cached, so this is cheap to call repeatedly.
+pub fn icicle_init() -> bool {
+    *DEVICE_AVAILABLE.get_or_init(|| {
+        if icicle_runtime::load_backend_from_env_or_default().is_err() {
+            return false;
+        }
+        let device = icicle_runtime::Device::new("CUDA", 0);
+        icicle_runtime::is_device_available(&device) && icicle_runtime::set_device(&device).is_ok()
+    })
+}
+
+/// Computes the MSM on the GPU if `V` is BN254 G1, the MSM is large enough to be worth
+/// offloading and a device is available. Returns `None` otherwise, in which case the caller
+/// should fall back to the CPU.
+pub(super) fn try_msm<V: VariableBaseMSM>(
+    bases: &[V::MulBase],
+    scalars: &[V::ScalarField],
+) -> Option<V> {
+    if TypeId::of::<V>() != TypeId::of::<G1Projective>()
+        || bases.len() < MIN_GPU_MSM_SIZE
+        || !icicle_init()
+    {
+        return None;
+    }
+    // SAFETY: `V` is `G1Projective`, so its `MulBase` and `ScalarField` are `G1Affine` and `Fr`.
+    let (bases, scalars) = unsafe {
+        (
+            &*(bases as *const [V::MulBase] as *const [G1Affine]),
+            &*(scalars as *const [V::ScalarField] as *const [Fr]),
+        )
+    };
+    let result = bn254_msm(bases, scalars)?;
+    (&result as &dyn Any).downcast_ref::<V>().copied()
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "icicle::bn254_msm")
+)]
+fn bn254_msm(bases: &[G1Affine], scalars: &[Fr]) -> Option<G1Projective> {
+    let bases: Vec<Affine<CurveCfg>> = bases.par_iter().map(affine_from_ark).collect();
+    let scalars: Vec<ScalarField> = scalars.par_iter().map(field_from_ark).collect();
+
+    let mut result = vec![Projective::<CurveCfg>::zero(); 1];
+    icicle_msm(
+        HostSlice::from_slice(&scalars),
+        HostSlice::from_slice(&bases),
+        &MSMConfig::default(),
+        HostSlice::from_mut_slice(&mut result),
+    )
+    .ok()?;
+    Some(projective_to_ark(&result[0]))
+}
+
+/// Converts an arkworks field element to icicle's little-endian canonical representation.
+fn field_from_ark<T: PrimeField, I: FieldImpl>(value: &T) -> I {
+    I::from_bytes_le(&value.into_bigint().to_bytes_le())
+}
+
+fn field_to_ark<T: PrimeField, I: FieldImpl>(value: &I) -> T {
+    T::from_le_bytes_mod_order(&value.to_bytes_le())
+}
+
+fn affine_from_ark(point: &G1Affine) -> Affine<CurveCfg> {
+    // Both libraries encode the point at infinity as (0, 0)
+    Affine::<CurveCfg> {
+        x: field_from_ark::<Fq, BaseField>(&point.x),
+        y: field_from_ark::<Fq, BaseField>(&point.y),
+    }
+}
+
+/// icicle uses homogeneous projective coordinates, whereas arkworks uses Jacobian ones, so the
+/// result goes through affine coordinates.
+fn projective_to_ark(point: &Projective<CurveCfg>) -> G1Projective {
+    let z: Fq = field_to_ark(&point.z);
+    if z.is_zero() {
+        return G1Projective::zero();
+    }
+    let z_inv = z.inverse().unwrap();
+    let x: Fq = field_to_ark(&point.x);
+    let y: Fq = field_to_ark(&point.y);
+    G1Affine::new_unchecked(x * z_inv, y * z_inv).into_group()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn matches_cpu_msm() {
+        let mut rng = test_rng();
+        let n = MIN_GPU_MSM_SIZE + 3;
+        let bases: Vec<G1Affine> = (0..n).map(|_| G1Affine::rand(&mut rng)).collect();
+        let scalars: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+
+        let expected = <G1Projective as ark_ec::VariableBaseMSM>::msm(&bases, &scalars).unwrap();
+        // Falls back to the CPU when no device is present
+        let result = <G1Projective as VariableBaseMSM>::msm(&bases, &scalars).unwrap();
+        assert_eq!(result, expected);
+
+        if icicle_init() {
+            assert_eq!(bn254_msm(&bases, &scalars).unwrap(), expected);
+        }
+    }
+}
@@ -1,8 +1,15 @@
+use crate::utils::par::{self, prelude::*};
 use ark_ec::{CurveGroup, ScalarMul};
 use ark_ff::{prelude::*, PrimeField};
 use ark_std::cmp::Ordering;
 use ark_std::vec::Vec;
-use rayon::prelude::*;
+
+use crate::utils::prover_config::ProverConfig;
+
+#[cfg(feature = "icicle")]
+pub mod icicle;
+pub mod precomputed;
+pub mod straus;
 
 impl<G: CurveGroup> VariableBaseMSM for G {}
 
@@ -10,6 +17,8 @@ impl<G: CurveGroup> VariableBaseMSM for G {}
 /// known small element sized MSMs.
 pub trait VariableBaseMSM: ScalarMul {
     fn msm(bases: &[Self::MulBase], scalars: &[Self::ScalarField]) -> Result<Self, usize> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("msm", size = bases.len()).entered();
         (bases.len() == scalars.len())
             .then(|| {
                 let max_num_bits = scalars
@@ -37,6 +46,10 @@ pub trait VariableBaseMSM: ScalarMul {
                         }
                     }
                     _ => {
+                        #[cfg(feature = "icicle")]
+                        if let Some(result) = icicle::try_msm::<Self>(bases, scalars) {
+                            return result;
+                        }
                         let scalars = scalars
                             .par_iter()
                             .map(|s| s.into_bigint())
@@ -65,17 +78,16 @@ fn map_field_elements_to_u64<V: VariableBaseMSM>(field_elements: &[V::ScalarFiel
 }
 
 // Compute msm using windowed non-adjacent form
-#[tracing::instrument(skip_all, name = "msm_bigint_wnaf")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "msm_bigint_wnaf")
+)]
 fn msm_bigint_wnaf<V: VariableBaseMSM>(
     bases: &[V::MulBase],
     scalars: &[<V::ScalarField as PrimeField>::BigInt],
     max_num_bits: usize,
 ) -> V {
-    let c = if bases.len() < 32 {
-        3
-    } else {
-        ln_without_floats(bases.len()) + 2
-    };
+    let c = window_bits(bases.len());
 
     let num_bits = max_num_bits;
     let digits_count = num_bits.div_ceil(c);
@@ -133,11 +145,7 @@ fn msm_bigint<V: VariableBaseMSM>(
 ) -> V {
     let scalars_and_bases_iter = scalars.iter().zip(bases).filter(|(s, _)| !s.is_zero());
 
-    let c = if bases.len() < 32 {
-        3
-    } else {
-        ln_without_floats(bases.len()) + 2
-    };
+    let c = window_bits(bases.len());
 
     let one = V::ScalarField::one().into_bigint();
 
@@ -265,17 +273,16 @@ fn make_digits_bigint(
 }
 
 // Compute msm using windowed non-adjacent form
-#[tracing::instrument(skip_all, name = "msm_u64_wnaf")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "msm_u64_wnaf")
+)]
 fn msm_u64_wnaf<V: VariableBaseMSM>(
     bases: &[V::MulBase],
     scalars: &[u64],
     max_num_bits: usize,
 ) -> V {
-    let c = if bases.len() < 32 {
-        3
-    } else {
-        ln_without_floats(bases.len()) + 2
-    };
+    let c = window_bits(bases.len());
 
     let digits_count = max_num_bits.div_ceil(c);
     let scalar_digits = scalars
@@ -328,11 +335,7 @@ fn msm_u64_wnaf<V: VariableBaseMSM>(
 
 /// Optimized implementation of multi-scalar multiplication.
 fn msm_u64<V: VariableBaseMSM>(bases: &[V::MulBase], scalars: &[u64], max_num_bits: usize) -> V {
-    let c = if bases.len() < 32 {
-        3
-    } else {
-        ln_without_floats(bases.len()) + 2
-    };
+    let c = window_bits(bases.len());
 
     let zero = V::zero();
 
@@ -414,36 +417,48 @@ fn msm_u64<V: VariableBaseMSM>(bases: &[V::MulBase], scalars: &[u64], max_num_bi
             })
 }
 
-#[tracing::instrument(skip_all, name = "msm_binary")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "msm_binary")
+)]
 fn msm_binary<V: VariableBaseMSM>(bases: &[V::MulBase], scalars: &[u64]) -> V {
     scalars
-        .iter()
-        .zip(bases)
+        .par_iter()
+        .zip(bases.par_iter())
         .filter(|(&scalar, _base)| scalar != 0)
         .map(|(_scalar, base)| base)
-        .fold(V::zero(), |sum, base| sum + base)
+        .fold(V::zero, |sum, base| sum + base)
+        .reduce(V::zero, |a, b| a + b)
 }
 
-#[tracing::instrument(skip_all, name = "msm_small")]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "msm_small"))]
 fn msm_small<V: VariableBaseMSM>(bases: &[V::MulBase], scalars: &[u64], max_num_bits: usize) -> V {
     let num_buckets: usize = 1 << max_num_bits;
-    // Assign things to buckets based on the scalar
-    let mut buckets: Vec<V> = vec![V::zero(); num_buckets];
+    // One set of buckets per thread
+    let chunk_size = scalars.len().div_ceil(par::current_num_threads()).max(1);
     scalars
-        .iter()
-        .zip(bases)
-        .filter(|(&scalar, _base)| scalar != 0)
-        .for_each(|(&scalar, base)| {
-            buckets[scalar as usize] += base;
-        });
-
-    let mut result = V::zero();
-    let mut running_sum = V::zero();
-    buckets.iter().skip(1).rev().for_each(|bucket| {
-        running_sum += bucket;
-        result += running_sum;
-    });
-    result
+        .par_chunks(chunk_size)
+        .zip(bases.par_chunks(chunk_size))
+        .map(|(scalars, bases)| {
+            // Assign things to buckets based on the scalar
+            let mut buckets: Vec<V> = vec![V::zero(); num_buckets];
+            scalars
+                .iter()
+                .zip(bases)
+                .filter(|(&scalar, _base)| scalar != 0)
+                .for_each(|(&scalar, base)| {
+                    buckets[scalar as usize] += base;
+                });
+
+            let mut result = V::zero();
+            let mut running_sum = V::zero();
+            buckets.iter().skip(1).rev().for_each(|bucket| {
+                running_sum += bucket;
+                result += running_sum;
+            });
+            result
+        })
+        .reduce(V::zero, |a, b| a + b)
 }
 
 fn make_digits_u64(scalar: u64, w: usize, num_bits: usize) -> impl Iterator<Item = i64> {
@@ -472,6 +487,18 @@ fn make_digits_u64(scalar: u64, w: usize, num_bits: usize) -> impl Iterator<Item
     })
 }
 
+/// The Pippenger window size for an MSM of `num_bases` bases, unless the [`ProverConfig`]
+/// in effect fixes it.
+fn window_bits(num_bases: usize) -> usize {
+    ProverConfig::current().msm_window_bits.unwrap_or_else(|| {
+        if num_bases < 32 {
+            3
+        } else {
+            ln_without_floats(num_bases) + 2
+        }
+    })
+}
+
 /// The result of this function is only approximately `ln(a)`
 /// [`Explanation of usage`]
 ///
@@ -0,0 +1,258 @@
+//! MSMs against a fixed set of bases, e.g. the G1 powers of an SRS, which the prover commits to
+//! dozens of polynomials against.
+//!
+//! Each base `G_i` is expanded once into the table `2^(j * c) * G_i` for every window `j` of `c`
+//! bits, so that an MSM needs no doublings: every signed digit of every scalar adds a table entry
+//! to one of `2^(c - 1)` buckets. The buckets are summed with batch-affine additions, sharing a
+//! single field inversion across all pairs added in a round.
+use crate::utils::par::{self, prelude::*};
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{batch_inversion, Field, PrimeField, Zero};
+use std::fmt::Debug;
+
+use super::make_digits_bigint;
+
+/// An MSM over precomputed bases, usable without naming the underlying curve model.
+pub trait PrecomputedMSM<G: CurveGroup>: Debug + Send + Sync {
+    fn num_bases(&self) -> usize;
+
+    /// Computes `sum_i scalars[i] * bases[offset + i]`.
+    fn msm(&self, offset: usize, scalars: &[G::ScalarField]) -> G;
+
+    fn batch_msm(&self, scalars: &[&[G::ScalarField]]) -> Vec<G> {
+        scalars.par_iter().map(|s| self.msm(0, s)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PrecomputedBases<C: SWCurveConfig> {
+    window_bits: usize,
+    num_windows: usize,
+    /// `tables[i * num_windows + j] = 2^(j * window_bits) * bases[i]`
+    tables: Vec<Affine<C>>,
+}
+
+impl<C: SWCurveConfig> PrecomputedBases<C> {
+    /// Precomputes the tables for `bases`. They take `ceil(256 / window_bits)` times the memory
+    /// of the bases; larger windows shrink the tables, at the cost of more buckets.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "PrecomputedBases::new")
+    )]
+    pub fn new(bases: &[Affine<C>], window_bits: usize) -> Self {
+        assert!((2..=20).contains(&window_bits));
+        // One extra bit absorbs the carry of the signed digits
+        let num_windows = (C::ScalarField::MODULUS_BIT_SIZE as usize + 1).div_ceil(window_bits);
+        let tables = bases
+            .par_chunks(1 << 10)
+            .flat_map_iter(|chunk| {
+                let multiples: Vec<Projective<C>> = chunk
+                    .iter()
+                    .flat_map(|base| {
+                        std::iter::successors(Some(base.into_group()), |multiple| {
+                            let mut multiple = *multiple;
+                            for _ in 0..window_bits {
+                                multiple.double_in_place();
+                            }
+                            Some(multiple)
+                        })
+                        .take(num_windows)
+                    })
+                    .collect();
+                Projective::normalize_batch(&multiples)
+            })
+            .collect();
+        Self {
+            window_bits,
+            num_windows,
+            tables,
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "PrecomputedBases::msm_chunk")
+    )]
+    fn msm_chunk(&self, offset: usize, scalars: &[C::ScalarField]) -> Projective<C> {
+        let num_bits = self.num_windows * self.window_bits;
+        let mut buckets: Vec<Vec<Affine<C>>> = vec![vec![]; 1 << (self.window_bits - 1)];
+        for (i, scalar) in scalars.iter().enumerate() {
+            let scalar = scalar.into_bigint();
+            let table = &self.tables[(offset + i) * self.num_windows..][..self.num_windows];
+            for (digit, entry) in make_digits_bigint(&scalar, self.window_bits, num_bits).zip(table)
+            {
+                match digit {
+                    0 => (),
+                    d if d > 0 => buckets[(d - 1) as usize].push(*entry),
+                    d => buckets[(-d - 1) as usize].push(-*entry),
+                }
+            }
+        }
+
+        let mut running_sum = Projective::zero();
+        let mut result = Projective::zero();
+        for bucket in reduce_buckets(buckets).iter().rev() {
+            running_sum += bucket;
+            result += running_sum;
+        }
+        result
+    }
+}
+
+impl<C: SWCurveConfig> PrecomputedMSM<Projective<C>> for PrecomputedBases<C> {
+    fn num_bases(&self) -> usize {
+        self.tables.len() / self.num_windows
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "PrecomputedBases::msm")
+    )]
+    fn msm(&self, offset: usize, scalars: &[C::ScalarField]) -> Projective<C> {
+        assert!(
+            offset + scalars.len() <= self.num_bases(),
+            "not enough precomputed bases (req: {} from offset {}, length: {})",
+            scalars.len(),
+            offset,
+            self.num_bases()
+        );
+        let chunk_size = scalars
+            .len()
+            .div_ceil(par::current_num_threads())
+            .max(1 << 10);
+        scalars
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| self.msm_chunk(offset + i * chunk_size, chunk))
+            .sum()
+    }
+}
+
+/// Sums the points of each bucket by pairwise additions in rounds, batching the inversions of
+/// each round across all buckets.
+fn reduce_buckets<C: SWCurveConfig>(mut buckets: Vec<Vec<Affine<C>>>) -> Vec<Affine<C>> {
+    let mut inverses = vec![];
+    loop {
+        inverses.clear();
+        for bucket in buckets.iter() {
+            for pair in bucket.chunks_exact(2) {
+                inverses.push(denominator(&pair[0], &pair[1]));
+            }
+        }
+        if inverses.is_empty() {
+            break;
+        }
+        // Zero denominators, of additions which need no inversion, are skipped
+        batch_inversion(&mut inverses);
+
+        let mut inverses = inverses.iter();
+        for bucket in buckets.iter_mut() {
+            let len = bucket.len();
+            for k in 0..len / 2 {
+                let sum =
+                    add_with_inverse(&bucket[2 * k], &bucket[2 * k + 1], inverses.next().unwrap());
+                bucket[k] = sum;
+            }
+            if len % 2 == 1 {
+                bucket[len / 2] = bucket[len - 1];
+            }
+            bucket.truncate(len.div_ceil(2));
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|bucket| bucket.first().copied().unwrap_or_else(Affine::identity))
+        .collect()
+}
+
+/// The denominator of the slope of the line through `p` and `q`, or zero if their sum is
+/// trivial.
+fn denominator<C: SWCurveConfig>(p: &Affine<C>, q: &Affine<C>) -> C::BaseField {
+    if p.infinity || q.infinity {
+        C::BaseField::zero()
+    } else if p.x != q.x {
+        q.x - p.x
+    } else if p.y == q.y {
+        p.y.double()
+    } else {
+        C::BaseField::zero()
+    }
+}
+
+/// Adds `p` and `q` given the inverse of `denominator(p, q)`.
+fn add_with_inverse<C: SWCurveConfig>(
+    p: &Affine<C>,
+    q: &Affine<C>,
+    inverse: &C::BaseField,
+) -> Affine<C> {
+    if p.infinity {
+        return *q;
+    }
+    if q.infinity {
+        return *p;
+    }
+    let slope = if p.x != q.x {
+        (q.y - p.y) * inverse
+    } else if p.y == q.y && !p.y.is_zero() {
+        (p.x.square() * C::BaseField::from(3u64) + C::COEFF_A) * inverse
+    } else {
+        return Affine::identity();
+    };
+    let x = slope.square() - p.x - q.x;
+    let y = slope * (p.x - x) - p.y;
+    Affine::new_unchecked(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msm::VariableBaseMSM;
+    use ark_bn254::{Fr, G1Affine, G1Projective};
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn matches_variable_base_msm() {
+        let mut rng = test_rng();
+        let n = 3000;
+        let mut bases: Vec<G1Affine> = (0..n).map(|_| G1Affine::rand(&mut rng)).collect();
+        // Repeated, negated and zero bases exercise the special cases of the affine additions
+        bases[1] = bases[0];
+        bases[2] = -bases[0];
+        bases[3] = G1Affine::zero();
+        let mut scalars: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        scalars[4] = Fr::zero();
+        scalars[5] = -Fr::from(1u64);
+
+        for window_bits in [4, 7, 12] {
+            let precomputed = PrecomputedBases::new(&bases, window_bits);
+            assert_eq!(precomputed.num_bases(), n);
+            assert_eq!(
+                precomputed.msm(0, &scalars),
+                <G1Projective as VariableBaseMSM>::msm(&bases, &scalars).unwrap()
+            );
+            assert_eq!(
+                precomputed.msm(10, &scalars[..100]),
+                <G1Projective as VariableBaseMSM>::msm(&bases[10..110], &scalars[..100]).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn batch_msm() {
+        let mut rng = test_rng();
+        let bases: Vec<G1Affine> = (0..64).map(|_| G1Affine::rand(&mut rng)).collect();
+        let precomputed = PrecomputedBases::new(&bases, 5);
+        let scalars: Vec<Vec<Fr>> = (1..5)
+            .map(|i| (0..16 * i).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        let slices: Vec<&[Fr]> = scalars.iter().map(|s| s.as_slice()).collect();
+
+        for (result, scalars) in precomputed.batch_msm(&slices).iter().zip(slices) {
+            assert_eq!(
+                *result,
+                <G1Projective as VariableBaseMSM>::msm(&bases[..scalars.len()], scalars).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Straus' method for MSMs with few bases, e.g. commitments to tiny polynomials.
+//!
+//! Each base `G_i` is expanded into its multiples `G_i, 2 * G_i, .., 2^(c - 1) * G_i`, and the
+//! signed `c`-bit digits of all scalars are accumulated in a single chain of doublings shared by
+//! every base. Unlike bucketing, no `2^c` buckets have to be summed per window, which dominates
+//! the cost of a small MSM.
+use ark_ff::{PrimeField, Zero};
+use ark_std::cmp::Ordering;
+
+use super::{make_digits_bigint, VariableBaseMSM};
+
+const WINDOW_BITS: usize = 4;
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "msm_straus")
+)]
+pub fn msm_straus<V: VariableBaseMSM>(
+    bases: &[V::MulBase],
+    scalars: &[V::ScalarField],
+) -> Result<V, usize> {
+    if bases.len() != scalars.len() {
+        return Err(bases.len().min(scalars.len()));
+    }
+    // One extra bit absorbs the carry of the signed digits
+    let num_windows = (V::ScalarField::MODULUS_BIT_SIZE as usize + 1).div_ceil(WINDOW_BITS);
+    let num_multiples = 1 << (WINDOW_BITS - 1);
+
+    // `tables[i * num_multiples + k] = (k + 1) * bases[i]`
+    let multiples: Vec<V> = bases
+        .iter()
+        .flat_map(|base| {
+            std::iter::successors(Some(V::zero() + base), move |multiple| {
+                Some(*multiple + base)
+            })
+            .take(num_multiples)
+        })
+        .collect();
+    let tables = V::batch_convert_to_mul_base(&multiples);
+    let digits: Vec<Vec<i64>> = scalars
+        .iter()
+        .map(|scalar| {
+            let scalar = scalar.into_bigint();
+            make_digits_bigint(&scalar, WINDOW_BITS, num_windows * WINDOW_BITS).collect()
+        })
+        .collect();
+
+    let mut result = V::zero();
+    for window in (0..num_windows).rev() {
+        for _ in 0..WINDOW_BITS {
+            result.double_in_place();
+        }
+        for (digits, table) in digits.iter().zip(tables.chunks(num_multiples)) {
+            let digit = digits[window];
+            match digit.cmp(&0) {
+                Ordering::Greater => result += &table[(digit - 1) as usize],
+                Ordering::Less => result -= &table[(-digit - 1) as usize],
+                Ordering::Equal => (),
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fr, G1Affine, G1Projective};
+    use ark_ec::AffineRepr;
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn matches_variable_base_msm() {
+        let mut rng = test_rng();
+        for n in [1, 5, 128] {
+            let bases: Vec<G1Affine> = (0..n).map(|_| G1Affine::rand(&mut rng)).collect();
+            let mut scalars: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+            scalars[0] = -Fr::from(1u64);
+            scalars[n / 2] = Fr::zero();
+            assert_eq!(
+                msm_straus::<G1Projective>(&bases, &scalars).unwrap(),
+                <G1Projective as VariableBaseMSM>::msm(&bases, &scalars).unwrap()
+            );
+        }
+        assert!(msm_straus::<G1Projective>(&[], &[]).unwrap().is_zero());
+        assert!(msm_straus::<G1Projective>(&[G1Affine::zero()], &[]).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! A pool of evaluation buffers, recycled across the rounds of sum-checks and across proof
+//! stages instead of being allocated and freed. Freeing a large buffer unmaps its pages, which
+//! is slow enough that the prover used to drop them on another thread.
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::utils::par::{self, prelude::*};
+
+use crate::field::JoltField;
+use crate::utils::prover_config::ProverConfig;
+use crate::utils::thread::{drop_in_background_thread, unsafe_allocate_zero_vec};
+
+/// Number of buffers the global pools retain
+const GLOBAL_POOL_CAPACITY: usize = 16;
+
+pub struct BufferPool<F: JoltField> {
+    buffers: Mutex<Vec<Vec<F>>>,
+    max_buffers: usize,
+}
+
+impl<F: JoltField> BufferPool<F> {
+    /// A pool retaining at most `max_buffers` buffers.
+    pub fn new(max_buffers: usize) -> Self {
+        Self {
+            buffers: Mutex::new(vec![]),
+            max_buffers,
+        }
+    }
+
+    /// The pool shared by the prover for field `F`.
+    pub fn global() -> &'static Self {
+        static POOLS: OnceLock<Mutex<HashMap<TypeId, &'static (dyn Any + Send + Sync)>>> =
+            OnceLock::new();
+        let mut pools = POOLS.get_or_init(Default::default).lock().unwrap();
+        let pool = *pools.entry(TypeId::of::<F>()).or_insert_with(|| {
+            Box::leak(Box::new(Self::new(GLOBAL_POOL_CAPACITY))) as &'static (dyn Any + Send + Sync)
+        });
+        pool.downcast_ref().unwrap()
+    }
+
+    /// A zeroed buffer of length `len`, reusing the smallest pooled buffer that fits, if any.
+    pub fn take(&self, len: usize) -> Vec<F> {
+        let recycled = {
+            let mut buffers = self.buffers.lock().unwrap();
+            let best_fit = buffers
+                .iter()
+                .enumerate()
+                .filter(|(_, buffer)| buffer.capacity() >= len)
+                .min_by_key(|(_, buffer)| buffer.capacity())
+                .map(|(i, _)| i);
+            best_fit.map(|i| buffers.swap_remove(i))
+        };
+        match recycled {
+            Some(mut buffer) => {
+                buffer.clear();
+                buffer.par_extend(par::iter::repeatn(F::zero(), len));
+                buffer
+            }
+            None => unsafe_allocate_zero_vec(len),
+        }
+    }
+
+    /// Returns `buffer` to the pool. If the pool is full, the smallest buffer is freed instead.
+    /// If the pooled buffers exceed the [`ProverConfig::max_memory_bytes`] in effect, the
+    /// largest ones are freed until they don't.
+    pub fn recycle(&self, buffer: Vec<F>) {
+        if buffer.capacity() == 0 {
+            return;
+        }
+        let mut buffers = self.buffers.lock().unwrap();
+        buffers.push(buffer);
+        if buffers.len() > self.max_buffers {
+            let (smallest, _) = buffers
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, buffer)| buffer.capacity())
+                .unwrap();
+            drop_in_background_thread(buffers.swap_remove(smallest));
+        }
+        if let Some(max_memory_bytes) = ProverConfig::current().max_memory_bytes {
+            let buffer_bytes = |buffer: &Vec<F>| buffer.capacity() * std::mem::size_of::<F>();
+            let mut pooled_bytes: usize = buffers.iter().map(buffer_bytes).sum();
+            while pooled_bytes > max_memory_bytes {
+                let (largest, _) = buffers
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, buffer)| buffer.capacity())
+                    .unwrap();
+                let buffer = buffers.swap_remove(largest);
+                pooled_bytes -= buffer_bytes(&buffer);
+                drop_in_background_thread(buffer);
+            }
+        }
+    }
+
+    /// Frees all the pooled buffers, e.g. at the end of a proof.
+    pub fn clear(&self) {
+        let buffers = std::mem::take(&mut *self.buffers.lock().unwrap());
+        drop_in_background_thread(buffers);
+    }
+
+    /// Number of buffers currently pooled
+    pub fn len(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn take_recycle() {
+        let pool = BufferPool::<Fr>::new(2);
+        let mut buffer = pool.take(8);
+        assert_eq!(buffer, vec![Fr::from(0u64); 8]);
+        buffer[3] = Fr::from(5u64);
+        let ptr = buffer.as_ptr();
+        pool.recycle(buffer);
+        assert_eq!(pool.len(), 1);
+
+        // A smaller buffer reuses the allocation, zeroed
+        let buffer = pool.take(4);
+        assert_eq!(buffer.as_ptr(), ptr);
+        assert_eq!(buffer, vec![Fr::from(0u64); 4]);
+        assert!(pool.is_empty());
+
+        // A full pool frees its smallest buffer
+        pool.recycle(buffer);
+        pool.recycle(vec![Fr::from(0u64); 16]);
+        pool.recycle(vec![Fr::from(0u64); 2]);
+        assert_eq!(pool.len(), 2);
+        assert!(pool.take(2).capacity() >= 8);
+
+        pool.clear();
+        assert!(pool.is_empty());
+        assert!(std::ptr::eq(
+            BufferPool::<Fr>::global(),
+            BufferPool::<Fr>::global()
+        ));
+    }
+
+    #[test]
+    fn memory_budget() {
+        let config = ProverConfig {
+            max_memory_bytes: Some(12 * std::mem::size_of::<Fr>()),
+            ..Default::default()
+        };
+        let pool = BufferPool::<Fr>::new(4);
+        config.install(|| {
+            pool.recycle(vec![Fr::from(0u64); 4]);
+            pool.recycle(vec![Fr::from(0u64); 8]);
+            assert_eq!(pool.len(), 2);
+            // Over budget: the largest buffer is freed
+            pool.recycle(vec![Fr::from(0u64); 2]);
+            assert_eq!(pool.len(), 2);
+            assert_eq!(pool.take(3).capacity(), 4);
+            assert_eq!(pool.take(1).capacity(), 2);
+        });
+    }
+}
@@ -14,7 +14,7 @@ pub struct Binius128Scheme<ProofTranscript: Transcript> {
     _phantom: PhantomData<ProofTranscript>,
 }
 
-#[derive(Default, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Default, Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct BiniusCommitment {}
 
 impl AppendToTranscript for BiniusCommitment {
@@ -98,6 +98,18 @@ impl<ProofTranscript: Transcript> CommitmentScheme<ProofTranscript>
         todo!()
     }
 
+    fn verify_linear_combination(
+        _proof: &Self::Proof,
+        _setup: &Self::Setup,
+        _transcript: &mut ProofTranscript,
+        _opening_point: &[Self::Field],
+        _opening: &Self::Field,
+        _commitments: &[&Self::Commitment],
+        _coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        todo!()
+    }
+
     fn protocol_name() -> &'static [u8] {
         b"binius_commit"
     }
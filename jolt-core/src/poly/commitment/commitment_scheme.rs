@@ -1,4 +1,5 @@
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand_core::{CryptoRng, RngCore};
 use std::fmt::Debug;
 
 use crate::utils::transcript::Transcript;
@@ -12,6 +13,8 @@ use crate::{
 pub struct CommitShape {
     pub input_length: usize,
     pub batch_type: BatchType,
+    /// The number of polynomials of this shape committed to together
+    pub batch_size: usize,
 }
 
 impl CommitShape {
@@ -19,13 +22,60 @@ impl CommitShape {
         Self {
             input_length,
             batch_type,
+            batch_size: 1,
         }
     }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn size_class(&self) -> SizeClass {
+        SizeClass::of(self.input_length)
+    }
+}
+
+/// Polynomials are committed to differently depending on their length: a batch typically mixes
+/// many tiny polynomials with a few huge ones, and no single MSM algorithm suits both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SizeClass {
+    /// At most `TINY_INPUT_LENGTH` evaluations. Too few for bucketing to pay off, so these are
+    /// committed to with Straus' method.
+    Tiny,
+    /// Committed to with precomputed tables of the generators when the setup provides them,
+    /// and with a bucketed MSM otherwise.
+    Big,
+}
+
+impl SizeClass {
+    pub const TINY_INPUT_LENGTH: usize = 1 << 7;
+
+    pub fn of(input_length: usize) -> Self {
+        if input_length <= Self::TINY_INPUT_LENGTH {
+            Self::Tiny
+        } else {
+            Self::Big
+        }
+    }
+
+    /// Splits the indices of `evals` by the size class of the polynomial at each index.
+    pub fn group<F>(evals: &[&[F]]) -> Vec<(Self, Vec<usize>)> {
+        let (tiny, big): (Vec<usize>, Vec<usize>) =
+            (0..evals.len()).partition(|&i| Self::of(evals[i].len()) == Self::Tiny);
+        [(Self::Tiny, tiny), (Self::Big, big)]
+            .into_iter()
+            .filter(|(_, indices)| !indices.is_empty())
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum BatchType {
     Big,
+    /// Like `Big`, for polynomials whose evaluations are known to be small integers (e.g. counts
+    /// or indices), which are committed to with MSMs specialized to the width of their scalars.
+    SmallScalars,
     Small,
     SurgeInitFinal,
     SurgeReadWrite,
@@ -36,6 +86,7 @@ pub trait CommitmentScheme<ProofTranscript: Transcript>: Clone + Sync + Send + '
     type Field: JoltField + Sized;
     type Setup: Clone + Sync + Send;
     type Commitment: Default
+        + Clone
         + Debug
         + Sync
         + Send
@@ -47,6 +98,11 @@ pub trait CommitmentScheme<ProofTranscript: Transcript>: Clone + Sync + Send + '
     type BatchedProof: Sync + Send + CanonicalSerialize + CanonicalDeserialize;
 
     fn setup(shapes: &[CommitShape]) -> Self::Setup;
+    /// The part of `setup` that verification uses, which schemes with a large prover key can
+    /// strip it from so that the verifier's setup is small to distribute.
+    fn verifier_setup(setup: &Self::Setup) -> Self::Setup {
+        setup.clone()
+    }
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment;
     fn batch_commit(
         evals: &[&[Self::Field]],
@@ -54,6 +110,21 @@ pub trait CommitmentScheme<ProofTranscript: Transcript>: Clone + Sync + Send + '
         batch_type: BatchType,
     ) -> Vec<Self::Commitment>;
     fn commit_slice(evals: &[Self::Field], setup: &Self::Setup) -> Self::Commitment;
+    /// Commits to the polynomial with `len` evaluations, which are yielded chunk-by-chunk by
+    /// `chunks`, so that callers need not hold all of them in memory at once. The default
+    /// implementation collects the chunks and falls back to `commit_slice`.
+    fn commit_streaming<I>(len: usize, chunks: I, setup: &Self::Setup) -> Self::Commitment
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[Self::Field]>,
+    {
+        let mut evals = Vec::with_capacity(len);
+        for chunk in chunks {
+            evals.extend_from_slice(chunk.as_ref());
+        }
+        assert_eq!(evals.len(), len, "streamed chunks do not add up to `len`");
+        Self::commit_slice(&evals, setup)
+    }
     fn batch_commit_polys(
         polys: &[DensePolynomial<Self::Field>],
         setup: &Self::Setup,
@@ -71,14 +142,11 @@ pub trait CommitmentScheme<ProofTranscript: Transcript>: Clone + Sync + Send + '
         Self::batch_commit(&slices, setup, batch_type)
     }
 
-    /// Homomorphically combines multiple commitments into a single commitment, computed as a
-    /// linear combination with the given coefficients.
-    fn combine_commitments(
-        _commitments: &[&Self::Commitment],
-        _coeffs: &[Self::Field],
-    ) -> Self::Commitment {
-        todo!("`combine_commitments` should be on a separate `AdditivelyHomomorphic` trait")
-    }
+    /// Whether [`CommitmentScheme::prove_linear_combination`] needs the committed polynomials
+    /// themselves, rather than any polynomial equal to their linear combination. This is the
+    /// case for schemes that aren't [`AdditivelyHomomorphic`], whose proofs open each of the
+    /// commitments, so the opening accumulator retains the polynomials it is given.
+    const OPENS_EACH_COMMITMENT: bool = false;
 
     fn prove(
         setup: &Self::Setup,
@@ -113,5 +181,75 @@ pub trait CommitmentScheme<ProofTranscript: Transcript>: Clone + Sync + Send + '
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError>;
 
+    /// Proves the evaluation at `opening_point` of the linear combination of `polynomials` with
+    /// the given coefficients. Polynomials with fewer variables than `opening_point` are padded
+    /// with zeros, i.e. their evaluations are a prefix of the combination's.
+    fn prove_linear_combination(
+        setup: &Self::Setup,
+        polynomials: &[&DensePolynomial<Self::Field>],
+        coeffs: &[Self::Field],
+        opening_point: &[Self::Field],
+        transcript: &mut ProofTranscript,
+    ) -> Self::Proof {
+        let poly = DensePolynomial::linear_combination(polynomials, coeffs);
+        Self::prove(setup, &poly, opening_point, transcript)
+    }
+
+    /// Verifies a proof of [`CommitmentScheme::prove_linear_combination`], given the
+    /// commitments to the combined polynomials.
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError>;
+
     fn protocol_name() -> &'static [u8];
 }
+
+/// A commitment scheme whose commitments can be combined homomorphically: the commitment to a
+/// linear combination of polynomials is the same linear combination of their commitments.
+pub trait AdditivelyHomomorphic<ProofTranscript: Transcript>:
+    CommitmentScheme<ProofTranscript>
+{
+    /// Homomorphically combines multiple commitments into a single commitment, computed as a
+    /// linear combination with the given coefficients.
+    fn combine_commitments(
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Self::Commitment;
+}
+
+/// A commitment scheme whose verifier ends with a check that can be deferred to an
+/// accumulator, so that the openings of many independent proofs are verified together
+/// (e.g. with a single multi-pairing).
+pub trait AggregatableCommitmentScheme<ProofTranscript: Transcript>:
+    AdditivelyHomomorphic<ProofTranscript>
+{
+    type Accumulator;
+
+    /// Returns an empty accumulator for openings verified against `setup`.
+    fn accumulator(setup: &Self::Setup) -> Self::Accumulator;
+
+    /// Same as [`CommitmentScheme::verify`], but defers the final check to `accumulator`.
+    /// The proof is only verified once the accumulator is.
+    fn verify_deferred(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitment: &Self::Commitment,
+        accumulator: &mut Self::Accumulator,
+    ) -> Result<(), ProofVerifyError>;
+
+    /// Verifies all of the checks deferred to `accumulator`. `rng` must be unpredictable to
+    /// the prover(s) of the accumulated openings.
+    fn verify_accumulator<R: RngCore + CryptoRng>(
+        accumulator: Self::Accumulator,
+        rng: &mut R,
+    ) -> Result<(), ProofVerifyError>;
+}
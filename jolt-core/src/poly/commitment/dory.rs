@@ -0,0 +1,842 @@
+//! This module implements the Dory polynomial commitment scheme (<https://eprint.iacr.org/2020/1274>).
+//!
+//! Dory has a transparent setup: the generators are sampled by hashing, so there is no trapdoor.
+//! A polynomial is viewed as a matrix `M` whose rows are `2^(m/2)` evaluations wide, where `m` is
+//! the maximum number of variables the setup supports. As every polynomial is arranged in rows of
+//! the same width, padding a polynomial with zeros does not change its commitment, so the
+//! commitments to polynomials of different sizes can be combined homomorphically.
+//! The commitment is the inner-pairing product `C = <T, Γ2>`, where `T_i = <M_i, Γ1>` are the
+//! Pedersen commitments to the rows of `M`. Opening proofs and verification are logarithmic in the
+//! size of the polynomial: the verifier only needs `O(log n)` precomputed pairings of the generators.
+//!
+//! To open `M` at a point `r`, split `r` into row and column variables, so that
+//! `f(r) = L^T M R` with `L`, `R` the respective eq-tables. The prover sets `v1 = T` and
+//! `v2 = v * H2`, where `v = L^T M`. Then it proves, with the Dory-Reduce argument,
+//! knowledge of `v1 ∈ G1^m`, `v2 ∈ G2^m` such that
+//!
+//! ```text
+//! C  = <v1, v2>    D1 = <v1, Γ2>    D2 = <Γ1, v2>    E1 = <v1, L>    E2 = <R, v2>
+//! ```
+//!
+//! where `D1` is the commitment, `E2 = f(r) * H2`, and `D2 = e(E1, H2)` (as `<v, Γ1> = <L, T>`).
+//! Each round halves `m`, until the prover can send `v1` and `v2` in the clear.
+use std::marker::PhantomData;
+
+use super::commitment_scheme::{AdditivelyHomomorphic, BatchType, CommitShape, CommitmentScheme};
+use super::pedersen::PedersenGenerators;
+use crate::field::JoltField;
+use crate::msm::VariableBaseMSM;
+use crate::poly::dense_mlpoly::DensePolynomial;
+use crate::poly::eq_poly::EqPolynomial;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::math::Math;
+use crate::utils::mul_0_1_optimized;
+use crate::utils::par::prelude::*;
+use crate::utils::transcript::{AppendToTranscript, Transcript};
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{One, Zero};
+
+/// Splits `point` into the row and column variables of a matrix with `2^col_vars` columns.
+/// A polynomial with fewer than `col_vars` variables is a single (zero-padded) row, so its
+/// missing top column variables are zero.
+fn split_point<F: JoltField>(point: &[F], col_vars: usize) -> (Vec<F>, Vec<F>) {
+    if point.len() >= col_vars {
+        let (row_point, col_point) = point.split_at(point.len() - col_vars);
+        (row_point.to_vec(), col_point.to_vec())
+    } else {
+        let col_point = std::iter::repeat(F::zero())
+            .take(col_vars - point.len())
+            .chain(point.iter().copied())
+            .collect();
+        (vec![], col_point)
+    }
+}
+
+fn append_gt<P: Pairing, ProofTranscript: Transcript>(
+    transcript: &mut ProofTranscript,
+    gt: &PairingOutput<P>,
+) {
+    let mut buf = vec![];
+    gt.serialize_compressed(&mut buf).unwrap();
+    transcript.append_bytes(&buf);
+}
+
+#[derive(Clone)]
+pub struct DorySetup<P: Pairing> {
+    /// Γ1: the generators for the row commitments
+    pub g1_generators: Vec<P::G1Affine>,
+    /// Γ2: the generators for the commitment to the row commitments
+    pub g2_generators: Vec<P::G2Affine>,
+    /// H2: the generator the opened row combination is encoded with
+    pub h2: P::G2Affine,
+    /// `chi[j] = <Γ1[..2^j], Γ2[..2^j]>`
+    pub chi: Vec<PairingOutput<P>>,
+    /// `delta_1r[j - 1] = <Γ1[2^(j-1)..2^j], Γ2[..2^(j-1)]>`
+    pub delta_1r: Vec<PairingOutput<P>>,
+    /// `delta_2r[j - 1] = <Γ1[..2^(j-1)], Γ2[2^(j-1)..2^j]>`
+    pub delta_2r: Vec<PairingOutput<P>>,
+}
+
+impl<P: Pairing> DorySetup<P> {
+    /// Samples generators for polynomials with up to `max_num_vars` variables, and precomputes
+    /// the pairings the verifier needs. Only `chi`, `delta_1r`, `delta_2r`, `h2` and the first
+    /// generators are used by the verifier.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "DorySetup::new")
+    )]
+    pub fn new(max_num_vars: usize) -> Self {
+        let max_col_vars = max_num_vars.div_ceil(2);
+        let n = max_col_vars.pow2();
+
+        let g1_generators = P::G1::normalize_batch(
+            &PedersenGenerators::<P::G1>::new(n, b"Jolt Dory G1 generators").generators,
+        );
+        let g2_generators = P::G2::normalize_batch(
+            &PedersenGenerators::<P::G2>::new(n + 1, b"Jolt Dory G2 generators").generators,
+        );
+        // H2 is sampled along with Γ2, but must not be one of its elements
+        let h2 = g2_generators[n];
+        let g2_generators = g2_generators[..n].to_vec();
+
+        let chi = (0..=max_col_vars)
+            .into_par_iter()
+            .map(|j| P::multi_pairing(&g1_generators[..j.pow2()], &g2_generators[..j.pow2()]))
+            .collect();
+        let (delta_1r, delta_2r) = (1..=max_col_vars)
+            .into_par_iter()
+            .map(|j| {
+                let half = (j - 1).pow2();
+                (
+                    P::multi_pairing(&g1_generators[half..2 * half], &g2_generators[..half]),
+                    P::multi_pairing(&g1_generators[..half], &g2_generators[half..2 * half]),
+                )
+            })
+            .unzip();
+
+        Self {
+            g1_generators,
+            g2_generators,
+            h2,
+            chi,
+            delta_1r,
+            delta_2r,
+        }
+    }
+
+    /// The maximum number of variables of a polynomial this setup can commit to.
+    pub fn max_num_vars(&self) -> usize {
+        2 * self.num_col_vars()
+    }
+
+    /// The number of column variables of every committed matrix.
+    pub fn num_col_vars(&self) -> usize {
+        self.chi.len() - 1
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DoryCommitment<P: Pairing>(pub PairingOutput<P>);
+
+impl<P: Pairing> Default for DoryCommitment<P> {
+    fn default() -> Self {
+        Self(PairingOutput::zero())
+    }
+}
+
+impl<P: Pairing> AppendToTranscript for DoryCommitment<P> {
+    fn append_to_transcript<ProofTranscript: Transcript>(&self, transcript: &mut ProofTranscript) {
+        transcript.append_message(b"poly_commitment_begin");
+        append_gt(transcript, &self.0);
+        transcript.append_message(b"poly_commitment_end");
+    }
+}
+
+/// The prover's first message of a Dory-Reduce round, sent before the challenge β.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DoryReduceFirstMessage<P: Pairing> {
+    pub d1_left: PairingOutput<P>,
+    pub d1_right: PairingOutput<P>,
+    pub d2_left: PairingOutput<P>,
+    pub d2_right: PairingOutput<P>,
+    pub e1_beta: P::G1Affine,
+    pub e2_beta: P::G2Affine,
+}
+
+/// The prover's second message of a Dory-Reduce round, sent before the challenge α.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DoryReduceSecondMessage<P: Pairing> {
+    pub c_plus: PairingOutput<P>,
+    pub c_minus: PairingOutput<P>,
+    pub e1_plus: P::G1Affine,
+    pub e1_minus: P::G1Affine,
+    pub e2_plus: P::G2Affine,
+    pub e2_minus: P::G2Affine,
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DoryProof<P: Pairing> {
+    /// `<L, T>`, the row commitments combined by the row eq-table
+    pub e1: P::G1Affine,
+    /// `<T, v * H2>`
+    pub c: PairingOutput<P>,
+    pub first_messages: Vec<DoryReduceFirstMessage<P>>,
+    pub second_messages: Vec<DoryReduceSecondMessage<P>>,
+    /// The fully folded `v1`
+    pub v1: P::G1Affine,
+    /// The fully folded `v2`
+    pub v2: P::G2Affine,
+}
+
+#[derive(Clone)]
+pub struct Dory<P: Pairing, ProofTranscript: Transcript> {
+    _phantom: PhantomData<(P, ProofTranscript)>,
+}
+
+impl<P, ProofTranscript> Dory<P, ProofTranscript>
+where
+    P: Pairing,
+    P::ScalarField: JoltField,
+    ProofTranscript: Transcript,
+{
+    pub fn protocol_name() -> &'static [u8] {
+        b"Dory"
+    }
+
+    /// Computes the row commitments `T_i = <M_i, Γ1>` of the non-zero rows of `M`.
+    fn row_commitments(evals: &[P::ScalarField], setup: &DorySetup<P>) -> Vec<P::G1> {
+        let n = setup.num_col_vars().pow2();
+        assert!(
+            evals.len() <= n * n,
+            "Polynomial of size {} exceeds the maximum size {} of the Dory setup",
+            evals.len(),
+            n * n
+        );
+        evals
+            .par_chunks(n)
+            .map(|row| VariableBaseMSM::msm(&setup.g1_generators[..row.len()], row).unwrap())
+            .collect()
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Dory::commit")
+    )]
+    pub fn commit(evals: &[P::ScalarField], setup: &DorySetup<P>) -> DoryCommitment<P> {
+        let row_commitments = Self::row_commitments(evals, setup);
+        DoryCommitment(P::multi_pairing(
+            P::G1::normalize_batch(&row_commitments),
+            &setup.g2_generators[..row_commitments.len()],
+        ))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Dory::open")
+    )]
+    pub fn open(
+        setup: &DorySetup<P>,
+        poly: &DensePolynomial<P::ScalarField>,
+        point: &[P::ScalarField],
+        transcript: &mut ProofTranscript,
+    ) -> DoryProof<P> {
+        transcript.append_message(Self::protocol_name());
+        assert_eq!(poly.get_num_vars(), point.len());
+
+        let col_vars = setup.num_col_vars();
+        let n = col_vars.pow2();
+        let g1 = &setup.g1_generators[..n];
+        let g2 = &setup.g2_generators[..n];
+
+        let (row_point, col_point) = split_point(point, col_vars);
+        let mut s2 = EqPolynomial::evals(&row_point);
+        let mut s1 = EqPolynomial::evals(&col_point);
+        s2.resize(n, P::ScalarField::zero());
+
+        // v = L^T M
+        let v = poly
+            .evals_ref()
+            .par_chunks(n)
+            .zip(s2.par_iter())
+            .map(|(row, l_i)| {
+                row.iter()
+                    .map(|x| mul_0_1_optimized(l_i, x))
+                    .collect::<Vec<_>>()
+            })
+            .reduce(
+                || vec![P::ScalarField::zero(); n],
+                |mut acc, row| {
+                    acc.iter_mut().zip(row).for_each(|(x, y)| *x += y);
+                    acc
+                },
+            );
+
+        let mut v1 = Self::row_commitments(poly.evals_ref(), setup);
+        v1.resize(n, P::G1::zero());
+        let mut v2: Vec<P::G2> = v.par_iter().map(|v_j| setup.h2 * v_j).collect();
+
+        let mut v1_affine = P::G1::normalize_batch(&v1);
+        let mut v2_affine = P::G2::normalize_batch(&v2);
+        let e1 = P::G1::msm(&v1_affine, &s2).unwrap().into_affine();
+        let c = P::multi_pairing(&v1_affine, &v2_affine);
+        transcript.append_point(&e1.into_group());
+        append_gt(transcript, &c);
+
+        let mut first_messages = Vec::with_capacity(col_vars);
+        let mut second_messages = Vec::with_capacity(col_vars);
+        for j in (1..=col_vars).rev() {
+            let half = (j - 1).pow2();
+            let (g1_left, g2_left) = (&g1[..half], &g2[..half]);
+
+            let (v1_left, v1_right) = v1_affine.split_at(half);
+            let (v2_left, v2_right) = v2_affine.split_at(half);
+            let first_message = DoryReduceFirstMessage {
+                d1_left: P::multi_pairing(v1_left, g2_left),
+                d1_right: P::multi_pairing(v1_right, g2_left),
+                d2_left: P::multi_pairing(g1_left, v2_left),
+                d2_right: P::multi_pairing(g1_left, v2_right),
+                e1_beta: P::G1::msm(&g1[..2 * half], &s2).unwrap().into_affine(),
+                e2_beta: P::G2::msm(&g2[..2 * half], &s1).unwrap().into_affine(),
+            };
+            Self::append_first_message(transcript, &first_message);
+            first_messages.push(first_message);
+
+            let beta: P::ScalarField = transcript.challenge_scalar();
+            let beta_inv = JoltField::inverse(&beta).unwrap();
+            v1.par_iter_mut()
+                .zip(&g1[..2 * half])
+                .for_each(|(v, g)| *v += *g * beta);
+            v2.par_iter_mut()
+                .zip(&g2[..2 * half])
+                .for_each(|(v, g)| *v += *g * beta_inv);
+            v1_affine = P::G1::normalize_batch(&v1);
+            v2_affine = P::G2::normalize_batch(&v2);
+
+            let (v1_left, v1_right) = v1_affine.split_at(half);
+            let (v2_left, v2_right) = v2_affine.split_at(half);
+            let (s1_left, s1_right) = s1.split_at(half);
+            let (s2_left, s2_right) = s2.split_at(half);
+            let second_message = DoryReduceSecondMessage {
+                c_plus: P::multi_pairing(v1_left, v2_right),
+                c_minus: P::multi_pairing(v1_right, v2_left),
+                e1_plus: P::G1::msm(v1_left, s2_right).unwrap().into_affine(),
+                e1_minus: P::G1::msm(v1_right, s2_left).unwrap().into_affine(),
+                e2_plus: P::G2::msm(v2_right, s1_left).unwrap().into_affine(),
+                e2_minus: P::G2::msm(v2_left, s1_right).unwrap().into_affine(),
+            };
+            Self::append_second_message(transcript, &second_message);
+            second_messages.push(second_message);
+
+            let alpha: P::ScalarField = transcript.challenge_scalar();
+            let alpha_inv = JoltField::inverse(&alpha).unwrap();
+            // v1' = α v1_L + v1_R, v2' = α^{-1} v2_L + v2_R
+            v1 = v1_affine[..half]
+                .par_iter()
+                .zip(&v1_affine[half..])
+                .map(|(left, right)| *left * alpha + right)
+                .collect();
+            v2 = v2_affine[..half]
+                .par_iter()
+                .zip(&v2_affine[half..])
+                .map(|(left, right)| *left * alpha_inv + right)
+                .collect();
+            // s1' = α s1_L + s1_R, s2' = α^{-1} s2_L + s2_R
+            s1 = s1_left
+                .iter()
+                .zip(s1_right)
+                .map(|(left, right)| *left * alpha + right)
+                .collect();
+            s2 = s2_left
+                .iter()
+                .zip(s2_right)
+                .map(|(left, right)| *left * alpha_inv + right)
+                .collect();
+            v1_affine = P::G1::normalize_batch(&v1);
+            v2_affine = P::G2::normalize_batch(&v2);
+        }
+
+        let (v1, v2) = (v1_affine[0], v2_affine[0]);
+        transcript.append_point(&v1.into_group());
+        transcript.append_point(&v2.into_group());
+        // The challenge `d` of the verifier's final check, drawn to keep the transcripts in sync
+        let _d: P::ScalarField = transcript.challenge_scalar();
+
+        DoryProof {
+            e1,
+            c,
+            first_messages,
+            second_messages,
+            v1,
+            v2,
+        }
+    }
+
+    pub fn verify(
+        proof: &DoryProof<P>,
+        setup: &DorySetup<P>,
+        transcript: &mut ProofTranscript,
+        point: &[P::ScalarField],
+        opening: &P::ScalarField,
+        commitment: &DoryCommitment<P>,
+    ) -> Result<(), ProofVerifyError> {
+        transcript.append_message(Self::protocol_name());
+
+        let col_vars = setup.num_col_vars();
+        if point.len() > setup.max_num_vars() {
+            return Err(ProofVerifyError::KeyLengthError(
+                setup.max_num_vars(),
+                point.len(),
+            ));
+        }
+        if proof.first_messages.len() != col_vars || proof.second_messages.len() != col_vars {
+            return Err(ProofVerifyError::InvalidInputLength(
+                col_vars,
+                proof.first_messages.len(),
+            ));
+        }
+
+        transcript.append_point(&proof.e1.into_group());
+        append_gt(transcript, &proof.c);
+
+        let mut c = proof.c;
+        let mut d1 = commitment.0;
+        let mut d2 = P::pairing(proof.e1, setup.h2);
+        let mut e1 = proof.e1.into_group();
+        let mut e2 = setup.h2 * opening;
+
+        // The folded s1 and s2 are products of one factor per round, as the eq-tables are
+        // tensor products. The row eq-table is padded with zeros to as many rows as columns.
+        let (row_point, s1_point) = split_point(point, col_vars);
+        let s2_point: Vec<P::ScalarField> = std::iter::repeat(P::ScalarField::zero())
+            .take(col_vars - row_point.len())
+            .chain(row_point)
+            .collect();
+        let mut s1 = P::ScalarField::one();
+        let mut s2 = P::ScalarField::one();
+
+        for (round, (first_message, second_message)) in proof
+            .first_messages
+            .iter()
+            .zip(proof.second_messages.iter())
+            .enumerate()
+        {
+            let j = col_vars - round;
+
+            Self::append_first_message(transcript, first_message);
+            let beta: P::ScalarField = transcript.challenge_scalar();
+            let beta_inv = JoltField::inverse(&beta).ok_or(ProofVerifyError::InternalError)?;
+            c += setup.chi[j] + d2 * beta + d1 * beta_inv;
+            e1 += first_message.e1_beta * beta;
+            e2 += first_message.e2_beta * beta_inv;
+
+            Self::append_second_message(transcript, second_message);
+            let alpha: P::ScalarField = transcript.challenge_scalar();
+            let alpha_inv = JoltField::inverse(&alpha).ok_or(ProofVerifyError::InternalError)?;
+            c += second_message.c_plus * alpha + second_message.c_minus * alpha_inv;
+            e1 += second_message.e1_plus * alpha + second_message.e1_minus * alpha_inv;
+            e2 += second_message.e2_plus * alpha + second_message.e2_minus * alpha_inv;
+            d1 = first_message.d1_left * alpha
+                + first_message.d1_right
+                + setup.chi[j - 1] * (alpha * beta)
+                + setup.delta_1r[j - 1] * beta;
+            d2 = first_message.d2_left * alpha_inv
+                + first_message.d2_right
+                + setup.chi[j - 1] * (alpha_inv * beta_inv)
+                + setup.delta_2r[j - 1] * beta_inv;
+
+            let (r1, r2) = (s1_point[round], s2_point[round]);
+            s1 *= alpha * (P::ScalarField::one() - r1) + r1;
+            s2 *= alpha_inv * (P::ScalarField::one() - r2) + r2;
+        }
+
+        if e1 != proof.v1 * s2 || e2 != proof.v2 * s1 {
+            return Err(ProofVerifyError::InternalError);
+        }
+
+        transcript.append_point(&proof.v1.into_group());
+        transcript.append_point(&proof.v2.into_group());
+        let d: P::ScalarField = transcript.challenge_scalar();
+        let d_inv = JoltField::inverse(&d).ok_or(ProofVerifyError::InternalError)?;
+
+        // e(v1 + d Γ1, v2 + d^{-1} Γ2) == C + χ + d D2 + d^{-1} D1 checks C = e(v1, v2),
+        // D1 = e(v1, Γ2) and D2 = e(Γ1, v2) at once.
+        let lhs = P::pairing(
+            setup.g1_generators[0] * d + proof.v1,
+            setup.g2_generators[0] * d_inv + proof.v2,
+        );
+        if lhs == c + setup.chi[0] + d2 * d + d1 * d_inv {
+            Ok(())
+        } else {
+            Err(ProofVerifyError::InternalError)
+        }
+    }
+
+    fn append_first_message(transcript: &mut ProofTranscript, message: &DoryReduceFirstMessage<P>) {
+        append_gt(transcript, &message.d1_left);
+        append_gt(transcript, &message.d1_right);
+        append_gt(transcript, &message.d2_left);
+        append_gt(transcript, &message.d2_right);
+        transcript.append_point(&message.e1_beta.into_group());
+        transcript.append_point(&message.e2_beta.into_group());
+    }
+
+    fn append_second_message(
+        transcript: &mut ProofTranscript,
+        message: &DoryReduceSecondMessage<P>,
+    ) {
+        append_gt(transcript, &message.c_plus);
+        append_gt(transcript, &message.c_minus);
+        transcript.append_point(&message.e1_plus.into_group());
+        transcript.append_point(&message.e1_minus.into_group());
+        transcript.append_point(&message.e2_plus.into_group());
+        transcript.append_point(&message.e2_minus.into_group());
+    }
+}
+
+impl<P: Pairing, ProofTranscript: Transcript> CommitmentScheme<ProofTranscript>
+    for Dory<P, ProofTranscript>
+where
+    P::ScalarField: JoltField,
+{
+    type Field = P::ScalarField;
+    type Setup = DorySetup<P>;
+    type Commitment = DoryCommitment<P>;
+    type Proof = DoryProof<P>;
+    type BatchedProof = DoryProof<P>;
+
+    fn setup(shapes: &[CommitShape]) -> Self::Setup {
+        let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
+        DorySetup::new(max_len.next_power_of_two().log_2())
+    }
+
+    fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
+        Self::commit(poly.evals_ref(), setup)
+    }
+
+    fn batch_commit(
+        evals: &[&[Self::Field]],
+        setup: &Self::Setup,
+        _batch_type: BatchType,
+    ) -> Vec<Self::Commitment> {
+        evals
+            .iter()
+            .map(|evals| Self::commit(evals, setup))
+            .collect()
+    }
+
+    fn commit_slice(evals: &[Self::Field], setup: &Self::Setup) -> Self::Commitment {
+        Self::commit(evals, setup)
+    }
+
+    fn prove(
+        setup: &Self::Setup,
+        poly: &DensePolynomial<Self::Field>,
+        opening_point: &[Self::Field],
+        transcript: &mut ProofTranscript,
+    ) -> Self::Proof {
+        Self::open(setup, poly, opening_point, transcript)
+    }
+
+    fn batch_prove(
+        setup: &Self::Setup,
+        polynomials: &[&DensePolynomial<Self::Field>],
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        _batch_type: BatchType,
+        transcript: &mut ProofTranscript,
+    ) -> Self::BatchedProof {
+        transcript.append_scalars(openings);
+        let rho_powers: Vec<Self::Field> = transcript.challenge_scalar_powers(polynomials.len());
+
+        let mut rlc_poly = vec![Self::Field::zero(); polynomials[0].len()];
+        for (coeff, poly) in rho_powers.iter().zip(polynomials.iter()) {
+            rlc_poly
+                .par_iter_mut()
+                .zip(poly.evals_ref().par_iter())
+                .for_each(|(rlc, eval)| *rlc += mul_0_1_optimized(eval, coeff));
+        }
+
+        Self::open(
+            setup,
+            &DensePolynomial::new(rlc_poly),
+            opening_point,
+            transcript,
+        )
+    }
+
+    fn verify(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitment: &Self::Commitment,
+    ) -> Result<(), ProofVerifyError> {
+        Self::verify(proof, setup, transcript, opening_point, opening, commitment)
+    }
+
+    fn batch_verify(
+        batch_proof: &Self::BatchedProof,
+        setup: &Self::Setup,
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        commitments: &[&Self::Commitment],
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        if commitments.len() != openings.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                commitments.len(),
+                openings.len(),
+            ));
+        }
+        transcript.append_scalars(openings);
+        let rho_powers: Vec<Self::Field> = transcript.challenge_scalar_powers(commitments.len());
+
+        let batched_opening = rho_powers
+            .iter()
+            .zip(openings.iter())
+            .map(|(coeff, opening)| *coeff * opening)
+            .sum();
+        let batched_commitment =
+            <Self as AdditivelyHomomorphic<ProofTranscript>>::combine_commitments(
+                commitments,
+                &rho_powers,
+            );
+        Self::verify(
+            batch_proof,
+            setup,
+            transcript,
+            opening_point,
+            &batched_opening,
+            &batched_commitment,
+        )
+    }
+
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        let commitment = <Self as AdditivelyHomomorphic<ProofTranscript>>::combine_commitments(
+            commitments,
+            coeffs,
+        );
+        <Self as CommitmentScheme<ProofTranscript>>::verify(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            &commitment,
+        )
+    }
+
+    fn protocol_name() -> &'static [u8] {
+        b"Dory"
+    }
+}
+
+impl<P: Pairing, ProofTranscript: Transcript> AdditivelyHomomorphic<ProofTranscript>
+    for Dory<P, ProofTranscript>
+where
+    P::ScalarField: JoltField,
+{
+    fn combine_commitments(
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Self::Commitment {
+        DoryCommitment(
+            commitments
+                .iter()
+                .zip(coeffs.iter())
+                .map(|(commitment, coeff)| commitment.0 * coeff)
+                .sum(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::{Bn254, Fr};
+    use ark_std::UniformRand;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn dory_commit_prove_verify() {
+        let setup = DorySetup::<Bn254>::new(7);
+        for num_vars in [0, 1, 4, 5, 7] {
+            let mut rng = ChaCha20Rng::seed_from_u64(num_vars as u64);
+            let poly = DensePolynomial::<Fr>::random(num_vars, &mut rng);
+            let point: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+            let eval = poly.evaluate(&point);
+            let commitment = Dory::<Bn254, KeccakTranscript>::commit(poly.evals_ref(), &setup);
+
+            let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+            let proof = Dory::<Bn254, KeccakTranscript>::open(
+                &setup,
+                &poly,
+                &point,
+                &mut prover_transcript,
+            );
+
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            Dory::<Bn254, KeccakTranscript>::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &eval,
+                &commitment,
+            )
+            .unwrap();
+            let p_transcipt_squeeze: Fr = prover_transcript.challenge_scalar();
+            let v_transcipt_squeeze: Fr = verifier_transcript.challenge_scalar();
+            assert_eq!(p_transcipt_squeeze, v_transcipt_squeeze);
+
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            assert!(Dory::<Bn254, KeccakTranscript>::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &(eval + Fr::one()),
+                &commitment,
+            )
+            .is_err());
+
+            let other_poly = DensePolynomial::<Fr>::random(num_vars, &mut rng);
+            let other_commitment =
+                Dory::<Bn254, KeccakTranscript>::commit(other_poly.evals_ref(), &setup);
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            assert!(Dory::<Bn254, KeccakTranscript>::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &eval,
+                &other_commitment,
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn dory_batch_prove_verify() {
+        const NUM_VARS: usize = 6;
+        let mut rng = ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let setup =
+            <Dory<Bn254, KeccakTranscript> as CommitmentScheme<KeccakTranscript>>::setup(&[
+                CommitShape::new(1 << NUM_VARS, BatchType::Small),
+            ]);
+
+        let polys: Vec<_> = (0..3)
+            .map(|_| DensePolynomial::<Fr>::random(NUM_VARS, &mut rng))
+            .collect();
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let openings: Vec<Fr> = polys.iter().map(|poly| poly.evaluate(&point)).collect();
+        let commitments: Vec<_> = polys
+            .iter()
+            .map(|poly| Dory::<Bn254, KeccakTranscript>::commit(poly.evals_ref(), &setup))
+            .collect();
+        let polys_refs: Vec<_> = polys.iter().collect();
+        let commitments_refs: Vec<_> = commitments.iter().collect();
+
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let proof = Dory::<Bn254, KeccakTranscript>::batch_prove(
+            &setup,
+            &polys_refs,
+            &point,
+            &openings,
+            BatchType::Small,
+            &mut prover_transcript,
+        );
+
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        Dory::<Bn254, KeccakTranscript>::batch_verify(
+            &proof,
+            &setup,
+            &point,
+            &openings,
+            &commitments_refs,
+            &mut verifier_transcript,
+        )
+        .unwrap();
+
+        let mut bad_openings = openings.clone();
+        bad_openings[2] += Fr::one();
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        assert!(Dory::<Bn254, KeccakTranscript>::batch_verify(
+            &proof,
+            &setup,
+            &point,
+            &bad_openings,
+            &commitments_refs,
+            &mut verifier_transcript,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn dory_combine_different_sizes() {
+        const NUM_VARS: usize = 7;
+        let mut rng = ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let setup = DorySetup::<Bn254>::new(NUM_VARS);
+
+        let small = DensePolynomial::<Fr>::random(3, &mut rng);
+        let large = DensePolynomial::<Fr>::random(NUM_VARS, &mut rng);
+        let mut padded_small = small.evals();
+        padded_small.resize(1 << NUM_VARS, Fr::zero());
+        let small_commitment = Dory::<Bn254, KeccakTranscript>::commit(small.evals_ref(), &setup);
+        assert_eq!(
+            small_commitment,
+            Dory::<Bn254, KeccakTranscript>::commit(&padded_small, &setup)
+        );
+        let large_commitment = Dory::<Bn254, KeccakTranscript>::commit(large.evals_ref(), &setup);
+
+        // The opening accumulator combines polynomials of different sizes by zero-padding the
+        // smaller ones
+        let coeff = Fr::rand(&mut rng);
+        let joint_poly = DensePolynomial::new(
+            large
+                .evals_ref()
+                .iter()
+                .zip(padded_small.iter())
+                .map(|(large, small)| *large + coeff * small)
+                .collect(),
+        );
+        let joint_commitment = <Dory<Bn254, KeccakTranscript> as AdditivelyHomomorphic<
+            KeccakTranscript,
+        >>::combine_commitments(
+            &[&large_commitment, &small_commitment], &[Fr::one(), coeff]
+        );
+
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = joint_poly.evaluate(&point);
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let proof = Dory::<Bn254, KeccakTranscript>::open(
+            &setup,
+            &joint_poly,
+            &point,
+            &mut prover_transcript,
+        );
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        Dory::<Bn254, KeccakTranscript>::verify(
+            &proof,
+            &setup,
+            &mut verifier_transcript,
+            &point,
+            &eval,
+            &joint_commitment,
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,613 @@
+//! A commitment scheme chosen at runtime among the BN254 schemes, so that a single binary can
+//! produce (and verify) proofs with any of them. Setups, commitments and proofs are enums over
+//! the underlying schemes' types, and every operation dispatches on the variant of its inputs.
+use std::marker::PhantomData;
+
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+
+use super::commitment_scheme::{AdditivelyHomomorphic, BatchType, CommitShape, CommitmentScheme};
+use super::hyperkzg::{HyperKZG, HyperKZGCommitment};
+use super::hyrax::{HyraxCommitment, HyraxScheme};
+use super::zeromorph::{Zeromorph, ZeromorphCommitment};
+use crate::poly::dense_mlpoly::DensePolynomial;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::transcript::{AppendToTranscript, Transcript};
+
+type HyraxPCS<ProofTranscript> = HyraxScheme<G1Projective, ProofTranscript>;
+type ZeromorphPCS<ProofTranscript> = Zeromorph<Bn254, ProofTranscript>;
+type HyperKZGPCS<ProofTranscript> = HyperKZG<Bn254, ProofTranscript>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PCSKind {
+    Hyrax = 0,
+    Zeromorph = 1,
+    HyperKZG = 2,
+}
+
+impl PCSKind {
+    fn from_u8(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Self::Hyrax),
+            1 => Some(Self::Zeromorph),
+            2 => Some(Self::HyperKZG),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DynCommitmentScheme<ProofTranscript: Transcript> {
+    _marker: PhantomData<ProofTranscript>,
+}
+
+impl<ProofTranscript: Transcript> DynCommitmentScheme<ProofTranscript> {
+    /// Sets up the scheme `kind` for the given shapes. [`CommitmentScheme::setup`] sets up
+    /// HyperKZG, the SDK's default scheme; all other operations dispatch on their inputs.
+    pub fn setup_for(kind: PCSKind, shapes: &[CommitShape]) -> DynSetup<ProofTranscript> {
+        match kind {
+            PCSKind::Hyrax => DynSetup::Hyrax(HyraxPCS::<ProofTranscript>::setup(shapes)),
+            PCSKind::Zeromorph => {
+                DynSetup::Zeromorph(ZeromorphPCS::<ProofTranscript>::setup(shapes))
+            }
+            PCSKind::HyperKZG => DynSetup::HyperKZG(HyperKZGPCS::<ProofTranscript>::setup(shapes)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum DynSetup<ProofTranscript: Transcript> {
+    Hyrax(<HyraxPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::Setup),
+    Zeromorph(<ZeromorphPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::Setup),
+    HyperKZG(<HyperKZGPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::Setup),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynCommitment {
+    Hyrax(HyraxCommitment<G1Projective>),
+    Zeromorph(ZeromorphCommitment<Bn254>),
+    HyperKZG(HyperKZGCommitment<Bn254>),
+}
+
+impl<ProofTranscript: Transcript> DynSetup<ProofTranscript> {
+    pub fn kind(&self) -> PCSKind {
+        match self {
+            Self::Hyrax(_) => PCSKind::Hyrax,
+            Self::Zeromorph(_) => PCSKind::Zeromorph,
+            Self::HyperKZG(_) => PCSKind::HyperKZG,
+        }
+    }
+}
+
+impl DynCommitment {
+    pub fn kind(&self) -> PCSKind {
+        match self {
+            Self::Hyrax(_) => PCSKind::Hyrax,
+            Self::Zeromorph(_) => PCSKind::Zeromorph,
+            Self::HyperKZG(_) => PCSKind::HyperKZG,
+        }
+    }
+}
+
+impl Default for DynCommitment {
+    fn default() -> Self {
+        Self::Hyrax(HyraxCommitment::default())
+    }
+}
+
+impl AppendToTranscript for DynCommitment {
+    fn append_to_transcript<ProofTranscript: Transcript>(&self, transcript: &mut ProofTranscript) {
+        match self {
+            Self::Hyrax(commitment) => commitment.append_to_transcript(transcript),
+            Self::Zeromorph(commitment) => commitment.append_to_transcript(transcript),
+            Self::HyperKZG(commitment) => commitment.append_to_transcript(transcript),
+        }
+    }
+}
+
+pub enum DynProof<ProofTranscript: Transcript> {
+    Hyrax(<HyraxPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::Proof),
+    Zeromorph(<ZeromorphPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::Proof),
+    HyperKZG(<HyperKZGPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::Proof),
+}
+
+pub enum DynBatchedProof<ProofTranscript: Transcript> {
+    Hyrax(<HyraxPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::BatchedProof),
+    Zeromorph(<ZeromorphPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::BatchedProof),
+    HyperKZG(<HyperKZGPCS<ProofTranscript> as CommitmentScheme<ProofTranscript>>::BatchedProof),
+}
+
+/// Serializes the variant as a one-byte `PCSKind` tag followed by its contents.
+macro_rules! impl_tagged_serialization {
+    ($name:ident $(<$transcript:ident>)?) => {
+        impl$(<$transcript: Transcript>)? CanonicalSerialize for $name$(<$transcript>)? {
+            fn serialize_with_mode<W: Write>(
+                &self,
+                mut writer: W,
+                compress: Compress,
+            ) -> Result<(), SerializationError> {
+                match self {
+                    Self::Hyrax(inner) => {
+                        (PCSKind::Hyrax as u8).serialize_with_mode(&mut writer, compress)?;
+                        inner.serialize_with_mode(writer, compress)
+                    }
+                    Self::Zeromorph(inner) => {
+                        (PCSKind::Zeromorph as u8).serialize_with_mode(&mut writer, compress)?;
+                        inner.serialize_with_mode(writer, compress)
+                    }
+                    Self::HyperKZG(inner) => {
+                        (PCSKind::HyperKZG as u8).serialize_with_mode(&mut writer, compress)?;
+                        inner.serialize_with_mode(writer, compress)
+                    }
+                }
+            }
+
+            fn serialized_size(&self, compress: Compress) -> usize {
+                1 + match self {
+                    Self::Hyrax(inner) => inner.serialized_size(compress),
+                    Self::Zeromorph(inner) => inner.serialized_size(compress),
+                    Self::HyperKZG(inner) => inner.serialized_size(compress),
+                }
+            }
+        }
+
+        impl$(<$transcript: Transcript>)? Valid for $name$(<$transcript>)? {
+            fn check(&self) -> Result<(), SerializationError> {
+                match self {
+                    Self::Hyrax(inner) => inner.check(),
+                    Self::Zeromorph(inner) => inner.check(),
+                    Self::HyperKZG(inner) => inner.check(),
+                }
+            }
+        }
+
+        impl$(<$transcript: Transcript>)? CanonicalDeserialize for $name$(<$transcript>)? {
+            fn deserialize_with_mode<R: Read>(
+                mut reader: R,
+                compress: Compress,
+                validate: Validate,
+            ) -> Result<Self, SerializationError> {
+                let tag = u8::deserialize_with_mode(&mut reader, compress, validate)?;
+                Ok(match PCSKind::from_u8(tag) {
+                    Some(PCSKind::Hyrax) => Self::Hyrax(
+                        CanonicalDeserialize::deserialize_with_mode(reader, compress, validate)?,
+                    ),
+                    Some(PCSKind::Zeromorph) => Self::Zeromorph(
+                        CanonicalDeserialize::deserialize_with_mode(reader, compress, validate)?,
+                    ),
+                    Some(PCSKind::HyperKZG) => Self::HyperKZG(
+                        CanonicalDeserialize::deserialize_with_mode(reader, compress, validate)?,
+                    ),
+                    None => return Err(SerializationError::InvalidData),
+                })
+            }
+        }
+    };
+}
+
+impl_tagged_serialization!(DynCommitment);
+impl_tagged_serialization!(DynProof<ProofTranscript>);
+impl_tagged_serialization!(DynBatchedProof<ProofTranscript>);
+
+/// Unwraps the commitments of the given variant, or returns `None` if any is of another scheme.
+macro_rules! unwrap_commitments {
+    ($commitments:expr, $variant:ident) => {
+        $commitments
+            .iter()
+            .map(|commitment| match commitment {
+                DynCommitment::$variant(inner) => Some(inner),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+}
+
+impl<ProofTranscript: Transcript> CommitmentScheme<ProofTranscript>
+    for DynCommitmentScheme<ProofTranscript>
+{
+    type Field = Fr;
+    type Setup = DynSetup<ProofTranscript>;
+    type Commitment = DynCommitment;
+    type Proof = DynProof<ProofTranscript>;
+    type BatchedProof = DynBatchedProof<ProofTranscript>;
+
+    fn setup(shapes: &[CommitShape]) -> Self::Setup {
+        Self::setup_for(PCSKind::HyperKZG, shapes)
+    }
+
+    fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
+        match setup {
+            DynSetup::Hyrax(setup) => {
+                DynCommitment::Hyrax(HyraxPCS::<ProofTranscript>::commit(poly, setup))
+            }
+            DynSetup::Zeromorph(setup) => {
+                DynCommitment::Zeromorph(ZeromorphPCS::<ProofTranscript>::commit(poly, setup))
+            }
+            DynSetup::HyperKZG(setup) => {
+                DynCommitment::HyperKZG(HyperKZGPCS::<ProofTranscript>::commit(poly, setup))
+            }
+        }
+    }
+
+    fn batch_commit(
+        evals: &[&[Self::Field]],
+        setup: &Self::Setup,
+        batch_type: BatchType,
+    ) -> Vec<Self::Commitment> {
+        match setup {
+            DynSetup::Hyrax(setup) => {
+                HyraxPCS::<ProofTranscript>::batch_commit(evals, setup, batch_type)
+                    .into_iter()
+                    .map(DynCommitment::Hyrax)
+                    .collect()
+            }
+            DynSetup::Zeromorph(setup) => {
+                ZeromorphPCS::<ProofTranscript>::batch_commit(evals, setup, batch_type)
+                    .into_iter()
+                    .map(DynCommitment::Zeromorph)
+                    .collect()
+            }
+            DynSetup::HyperKZG(setup) => {
+                HyperKZGPCS::<ProofTranscript>::batch_commit(evals, setup, batch_type)
+                    .into_iter()
+                    .map(DynCommitment::HyperKZG)
+                    .collect()
+            }
+        }
+    }
+
+    fn commit_slice(evals: &[Self::Field], setup: &Self::Setup) -> Self::Commitment {
+        match setup {
+            DynSetup::Hyrax(setup) => {
+                DynCommitment::Hyrax(HyraxPCS::<ProofTranscript>::commit_slice(evals, setup))
+            }
+            DynSetup::Zeromorph(setup) => DynCommitment::Zeromorph(
+                ZeromorphPCS::<ProofTranscript>::commit_slice(evals, setup),
+            ),
+            DynSetup::HyperKZG(setup) => {
+                DynCommitment::HyperKZG(HyperKZGPCS::<ProofTranscript>::commit_slice(evals, setup))
+            }
+        }
+    }
+
+    fn commit_streaming<I>(len: usize, chunks: I, setup: &Self::Setup) -> Self::Commitment
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[Self::Field]>,
+    {
+        match setup {
+            DynSetup::Hyrax(setup) => DynCommitment::Hyrax(
+                HyraxPCS::<ProofTranscript>::commit_streaming(len, chunks, setup),
+            ),
+            DynSetup::Zeromorph(setup) => DynCommitment::Zeromorph(
+                ZeromorphPCS::<ProofTranscript>::commit_streaming(len, chunks, setup),
+            ),
+            DynSetup::HyperKZG(setup) => DynCommitment::HyperKZG(
+                HyperKZGPCS::<ProofTranscript>::commit_streaming(len, chunks, setup),
+            ),
+        }
+    }
+
+    fn prove(
+        setup: &Self::Setup,
+        poly: &DensePolynomial<Self::Field>,
+        opening_point: &[Self::Field],
+        transcript: &mut ProofTranscript,
+    ) -> Self::Proof {
+        match setup {
+            DynSetup::Hyrax(setup) => DynProof::Hyrax(HyraxPCS::<ProofTranscript>::prove(
+                setup,
+                poly,
+                opening_point,
+                transcript,
+            )),
+            DynSetup::Zeromorph(setup) => DynProof::Zeromorph(
+                ZeromorphPCS::<ProofTranscript>::prove(setup, poly, opening_point, transcript),
+            ),
+            DynSetup::HyperKZG(setup) => DynProof::HyperKZG(HyperKZGPCS::<ProofTranscript>::prove(
+                setup,
+                poly,
+                opening_point,
+                transcript,
+            )),
+        }
+    }
+
+    fn batch_prove(
+        setup: &Self::Setup,
+        polynomials: &[&DensePolynomial<Self::Field>],
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        batch_type: BatchType,
+        transcript: &mut ProofTranscript,
+    ) -> Self::BatchedProof {
+        match setup {
+            DynSetup::Hyrax(setup) => {
+                DynBatchedProof::Hyrax(HyraxPCS::<ProofTranscript>::batch_prove(
+                    setup,
+                    polynomials,
+                    opening_point,
+                    openings,
+                    batch_type,
+                    transcript,
+                ))
+            }
+            DynSetup::Zeromorph(setup) => {
+                DynBatchedProof::Zeromorph(ZeromorphPCS::<ProofTranscript>::batch_prove(
+                    setup,
+                    polynomials,
+                    opening_point,
+                    openings,
+                    batch_type,
+                    transcript,
+                ))
+            }
+            DynSetup::HyperKZG(setup) => {
+                DynBatchedProof::HyperKZG(HyperKZGPCS::<ProofTranscript>::batch_prove(
+                    setup,
+                    polynomials,
+                    opening_point,
+                    openings,
+                    batch_type,
+                    transcript,
+                ))
+            }
+        }
+    }
+
+    fn verify(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitment: &Self::Commitment,
+    ) -> Result<(), ProofVerifyError> {
+        match (proof, setup, commitment) {
+            (DynProof::Hyrax(proof), DynSetup::Hyrax(setup), DynCommitment::Hyrax(commitment)) => {
+                HyraxPCS::<ProofTranscript>::verify(
+                    proof,
+                    setup,
+                    transcript,
+                    opening_point,
+                    opening,
+                    commitment,
+                )
+            }
+            (
+                DynProof::Zeromorph(proof),
+                DynSetup::Zeromorph(setup),
+                DynCommitment::Zeromorph(commitment),
+            ) => ZeromorphPCS::<ProofTranscript>::verify(
+                proof,
+                setup,
+                transcript,
+                opening_point,
+                opening,
+                commitment,
+            ),
+            (
+                DynProof::HyperKZG(proof),
+                DynSetup::HyperKZG(setup),
+                DynCommitment::HyperKZG(commitment),
+            ) => HyperKZGPCS::<ProofTranscript>::verify(
+                proof,
+                setup,
+                transcript,
+                opening_point,
+                opening,
+                commitment,
+            ),
+            // The proof, setup or commitment is of another scheme
+            _ => Err(ProofVerifyError::InternalError),
+        }
+    }
+
+    fn batch_verify(
+        batch_proof: &Self::BatchedProof,
+        setup: &Self::Setup,
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        commitments: &[&Self::Commitment],
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        match (batch_proof, setup) {
+            (DynBatchedProof::Hyrax(proof), DynSetup::Hyrax(setup)) => {
+                HyraxPCS::<ProofTranscript>::batch_verify(
+                    proof,
+                    setup,
+                    opening_point,
+                    openings,
+                    &unwrap_commitments!(commitments, Hyrax)
+                        .ok_or(ProofVerifyError::InternalError)?,
+                    transcript,
+                )
+            }
+            (DynBatchedProof::Zeromorph(proof), DynSetup::Zeromorph(setup)) => {
+                ZeromorphPCS::<ProofTranscript>::batch_verify(
+                    proof,
+                    setup,
+                    opening_point,
+                    openings,
+                    &unwrap_commitments!(commitments, Zeromorph)
+                        .ok_or(ProofVerifyError::InternalError)?,
+                    transcript,
+                )
+            }
+            (DynBatchedProof::HyperKZG(proof), DynSetup::HyperKZG(setup)) => {
+                HyperKZGPCS::<ProofTranscript>::batch_verify(
+                    proof,
+                    setup,
+                    opening_point,
+                    openings,
+                    &unwrap_commitments!(commitments, HyperKZG)
+                        .ok_or(ProofVerifyError::InternalError)?,
+                    transcript,
+                )
+            }
+            _ => Err(ProofVerifyError::InternalError),
+        }
+    }
+
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        // Commitments of another scheme than the setup's cannot be combined, let alone opened
+        if commitments.is_empty() || commitments.len() != coeffs.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                coeffs.len(),
+                commitments.len(),
+            ));
+        }
+        if commitments
+            .iter()
+            .any(|commitment| commitment.kind() != setup.kind())
+        {
+            return Err(ProofVerifyError::InternalError);
+        }
+        let commitment = <Self as AdditivelyHomomorphic<ProofTranscript>>::combine_commitments(
+            commitments,
+            coeffs,
+        );
+        <Self as CommitmentScheme<ProofTranscript>>::verify(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            &commitment,
+        )
+    }
+
+    fn protocol_name() -> &'static [u8] {
+        b"Jolt DynCommitmentScheme"
+    }
+}
+
+impl<ProofTranscript: Transcript> AdditivelyHomomorphic<ProofTranscript>
+    for DynCommitmentScheme<ProofTranscript>
+{
+    fn combine_commitments(
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Self::Commitment {
+        // Verification checks the commitments' schemes before combining them, so that a proof
+        // mixing schemes is rejected rather than panicking here
+        const MIXED: &str = "cannot combine commitments of different schemes";
+        let first = commitments
+            .first()
+            .expect("cannot combine an empty list of commitments");
+        match first {
+            DynCommitment::Hyrax(_) => {
+                DynCommitment::Hyrax(HyraxPCS::<ProofTranscript>::combine_commitments(
+                    &unwrap_commitments!(commitments, Hyrax).expect(MIXED),
+                    coeffs,
+                ))
+            }
+            DynCommitment::Zeromorph(_) => {
+                DynCommitment::Zeromorph(ZeromorphPCS::<ProofTranscript>::combine_commitments(
+                    &unwrap_commitments!(commitments, Zeromorph).expect(MIXED),
+                    coeffs,
+                ))
+            }
+            DynCommitment::HyperKZG(_) => {
+                DynCommitment::HyperKZG(HyperKZGPCS::<ProofTranscript>::combine_commitments(
+                    &unwrap_commitments!(commitments, HyperKZG).expect(MIXED),
+                    coeffs,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::JoltField;
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_std::{test_rng, One};
+
+    type PCS = DynCommitmentScheme<KeccakTranscript>;
+
+    #[test]
+    fn prove_verify_each_scheme() {
+        let mut rng = test_rng();
+        const NUM_VARS: usize = 8;
+        let poly = DensePolynomial::<Fr>::random(NUM_VARS, &mut rng);
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::random(&mut rng)).collect();
+        let opening = poly.evaluate(&point);
+
+        let mut setups = vec![];
+        for kind in [PCSKind::Hyrax, PCSKind::Zeromorph, PCSKind::HyperKZG] {
+            let setup = PCS::setup_for(kind, &[CommitShape::new(1 << NUM_VARS, BatchType::Small)]);
+            let commitment = PCS::commit(&poly, &setup);
+
+            let mut prover_transcript = KeccakTranscript::new(b"dyn");
+            let proof = PCS::prove(&setup, &poly, &point, &mut prover_transcript);
+            // Proofs and commitments round-trip through their tagged serialization
+            let mut bytes = vec![];
+            proof.serialize_compressed(&mut bytes).unwrap();
+            let proof = DynProof::deserialize_compressed(&bytes[..]).unwrap();
+            let mut bytes = vec![];
+            commitment.serialize_compressed(&mut bytes).unwrap();
+            assert_eq!(bytes[0], kind as u8);
+            assert_eq!(
+                DynCommitment::deserialize_compressed(&bytes[..]).unwrap(),
+                commitment
+            );
+
+            let mut verifier_transcript = KeccakTranscript::new(b"dyn");
+            assert!(PCS::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &opening,
+                &commitment
+            )
+            .is_ok());
+            setups.push((setup, commitment, proof));
+        }
+
+        // Mixing schemes is an error rather than a panic
+        let (hyrax_setup, _, hyrax_proof) = &setups[0];
+        let (_, zeromorph_commitment, _) = &setups[1];
+        let mut verifier_transcript = KeccakTranscript::new(b"dyn");
+        assert!(PCS::verify(
+            hyrax_proof,
+            hyrax_setup,
+            &mut verifier_transcript,
+            &point,
+            &opening,
+            zeromorph_commitment
+        )
+        .is_err());
+
+        // So is a linear combination mixing schemes, or of no commitments at all
+        let (hyperkzg_setup, hyperkzg_commitment, hyperkzg_proof) = &setups[2];
+        for commitments in [vec![hyperkzg_commitment, zeromorph_commitment], vec![]] {
+            let coeffs = vec![Fr::one(); commitments.len()];
+            let mut verifier_transcript = KeccakTranscript::new(b"dyn");
+            assert!(PCS::verify_linear_combination(
+                hyperkzg_proof,
+                hyperkzg_setup,
+                &mut verifier_transcript,
+                &point,
+                &opening,
+                &commitments,
+                &coeffs
+            )
+            .is_err());
+        }
+    }
+}
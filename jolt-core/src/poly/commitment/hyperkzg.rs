@@ -8,31 +8,43 @@
 //! (2) HyperKZG is specialized to use KZG as the univariate commitment scheme, so it includes several optimizations (both during the transformation of multilinear-to-univariate claims
 //! and within the KZG commitment scheme implementation itself).
 use super::{
-    commitment_scheme::{BatchType, CommitmentScheme},
+    commitment_scheme::{
+        AdditivelyHomomorphic, AggregatableCommitmentScheme, BatchType, CommitmentScheme, SizeClass,
+    },
     kzg,
-    kzg::{KZGProverKey, KZGVerifierKey, UnivariateKZG},
+    kzg::{
+        KZGProverKey, KZGVerifierKey, PairingAccumulator, PreparedKZGVerifierKey, UnivariateKZG,
+    },
+    ptau::PtauError,
 };
 use crate::field;
 use crate::poly::commitment::commitment_scheme::CommitShape;
 use crate::utils::mul_0_1_optimized;
+use crate::utils::par;
+use crate::utils::par::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelIterator,
+};
 use crate::utils::thread::unsafe_allocate_zero_vec;
 use crate::utils::transcript::Transcript;
+use crate::verifier::hyperkzg::hyperkzg_pairing_check;
 use crate::{
     msm::VariableBaseMSM,
     poly::{commitment::kzg::SRS, dense_mlpoly::DensePolynomial, unipoly::UniPoly},
     utils::{errors::ProofVerifyError, transcript::AppendToTranscript},
 };
+use ark_bn254::Bn254;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
 use ark_std::{One, Zero};
 use rand_chacha::ChaCha20Rng;
 use rand_core::{CryptoRng, RngCore, SeedableRng};
-use rayon::iter::{
-    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
-    IntoParallelRefMutIterator, ParallelIterator,
-};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
 use std::{marker::PhantomData, sync::Arc};
-use tracing::trace_span;
 
 pub struct HyperKZGSRS<P: Pairing>(Arc<SRS<P>>);
 
@@ -41,25 +53,106 @@ impl<P: Pairing> HyperKZGSRS<P> {
         Self(Arc::new(SRS::setup(rng, max_degree, 2)))
     }
 
+    pub fn from_srs(srs: SRS<P>) -> Self {
+        Self(Arc::new(srs))
+    }
+
+    /// Writes the SRS to a file that [`HyperKZGSRS::load`] can read lazily.
+    pub fn save<Q: AsRef<Path>>(&self, path: Q) -> Result<(), SerializationError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.0.write_powers(&mut writer)?;
+        Ok(writer.flush()?)
+    }
+
+    /// Loads from an SRS file only the powers needed to commit to the given shapes, the same
+    /// way `HyperKZG::setup` sizes the SRS it generates.
+    pub fn load<Q: AsRef<Path>>(
+        path: Q,
+        shapes: &[CommitShape],
+    ) -> Result<Self, SerializationError> {
+        let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
+        Ok(Self::from_srs(SRS::load_prefix(path, max_len, 2)?))
+    }
+
     pub fn trim(self, max_degree: usize) -> (HyperKZGProverKey<P>, HyperKZGVerifierKey<P>) {
         let (kzg_pk, kzg_vk) = SRS::trim(self.0, max_degree);
         (HyperKZGProverKey { kzg_pk }, HyperKZGVerifierKey { kzg_vk })
     }
 }
 
-#[derive(Clone, Debug)]
+impl HyperKZGSRS<Bn254> {
+    /// Loads the SRS from a `.ptau` file of a powers-of-tau ceremony rather than generating it
+    /// from an RNG, so that the trapdoor is not known to anyone.
+    pub fn from_ptau<Q: AsRef<Path>>(path: Q, max_degree: usize) -> Result<Self, PtauError> {
+        Ok(Self::from_srs(SRS::from_ptau(path, max_degree, 2)?))
+    }
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyperKZGProverKey<P: Pairing> {
     pub kzg_pk: KZGProverKey<P>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyperKZGVerifierKey<P: Pairing> {
     pub kzg_vk: KZGVerifierKey<P>,
 }
 
-#[derive(Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+impl<P: Pairing> HyperKZGVerifierKey<P> {
+    pub fn prepare(&self) -> PreparedHyperKZGVerifierKey<P> {
+        PreparedHyperKZGVerifierKey {
+            kzg_vk: self.kzg_vk.prepare(),
+        }
+    }
+}
+
+/// A [`HyperKZGVerifierKey`] with its G2 elements prepared for pairing, for reuse across
+/// verifications.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PreparedHyperKZGVerifierKey<P: Pairing> {
+    pub kzg_vk: PreparedKZGVerifierKey<P>,
+}
+
+impl<P: Pairing> PreparedHyperKZGVerifierKey<P> {
+    pub fn pairing_accumulator(&self) -> PairingAccumulator<P> {
+        self.kzg_vk.pairing_accumulator()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct HyperKZGCommitment<P: Pairing>(pub P::G1Affine);
 
+/// See [`kzg::serialize_commitment`].
+impl<P: Pairing> CanonicalSerialize for HyperKZGCommitment<P> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        kzg::serialize_commitment(&self.0, writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        kzg::commitment_serialized_size(&self.0, compress)
+    }
+}
+
+impl<P: Pairing> Valid for HyperKZGCommitment<P> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.0.check()
+    }
+}
+
+impl<P: Pairing> CanonicalDeserialize for HyperKZGCommitment<P> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        kzg::deserialize_commitment(reader, compress, validate).map(Self)
+    }
+}
+
 impl<P: Pairing> Default for HyperKZGCommitment<P> {
     fn default() -> Self {
         Self(P::G1Affine::zero())
@@ -195,88 +288,6 @@ where
     (w, v)
 }
 
-// vk is hashed in transcript already, so we do not add it here
-fn kzg_verify_batch<P: Pairing, ProofTranscript: Transcript>(
-    vk: &HyperKZGVerifierKey<P>,
-    C: &[P::G1Affine],
-    W: &[P::G1Affine],
-    u: &[P::ScalarField],
-    v: &[Vec<P::ScalarField>],
-    transcript: &mut ProofTranscript,
-) -> bool
-where
-    <P as Pairing>::ScalarField: field::JoltField,
-{
-    let k = C.len();
-    let t = u.len();
-
-    transcript.append_scalars(&v.iter().flatten().cloned().collect::<Vec<P::ScalarField>>());
-    let q_powers: Vec<P::ScalarField> = transcript.challenge_scalar_powers(k);
-
-    transcript.append_points(&W.iter().map(|g| g.into_group()).collect::<Vec<P::G1>>());
-    let d_0: P::ScalarField = transcript.challenge_scalar();
-    let d_1 = d_0 * d_0;
-
-    assert_eq!(t, 3);
-    assert_eq!(W.len(), 3);
-    // We write a special case for t=3, since this what is required for
-    // hyperkzg. Following the paper directly, we must compute:
-    // let L0 = C_B - vk.G * B_u[0] + W[0] * u[0];
-    // let L1 = C_B - vk.G * B_u[1] + W[1] * u[1];
-    // let L2 = C_B - vk.G * B_u[2] + W[2] * u[2];
-    // let R0 = -W[0];
-    // let R1 = -W[1];
-    // let R2 = -W[2];
-    // let L = L0 + L1*d_0 + L2*d_1;
-    // let R = R0 + R1*d_0 + R2*d_1;
-    //
-    // We group terms to reduce the number of scalar mults (to seven):
-    // In Rust, we could use MSMs for these, and speed up verification.
-    //
-    // Note, that while computing L, the intermediate computation of C_B together with computing
-    // L0, L1, L2 can be replaced by single MSM of C with the powers of q multiplied by (1 + d_0 + d_1)
-    // with additionally concatenated inputs for scalars/bases.
-
-    let q_power_multiplier: P::ScalarField = P::ScalarField::one() + d_0 + d_1;
-
-    let q_powers_multiplied: Vec<P::ScalarField> = q_powers
-        .par_iter()
-        .map(|q_power| *q_power * q_power_multiplier)
-        .collect();
-
-    // Compute the batched openings
-    // compute B(u_i) = v[i][0] + q*v[i][1] + ... + q^(t-1) * v[i][t-1]
-    let B_u = v
-        .into_par_iter()
-        .map(|v_i| {
-            v_i.into_par_iter()
-                .zip(q_powers.par_iter())
-                .map(|(a, b)| *a * *b)
-                .sum()
-        })
-        .collect::<Vec<P::ScalarField>>();
-
-    let L = <P::G1 as VariableBaseMSM>::msm(
-        &[&C[..k], &[W[0], W[1], W[2], vk.kzg_vk.g1]].concat(),
-        &[
-            &q_powers_multiplied[..k],
-            &[
-                u[0],
-                (u[1] * d_0),
-                (u[2] * d_1),
-                -(B_u[0] + d_0 * B_u[1] + d_1 * B_u[2]),
-            ],
-        ]
-        .concat(),
-    )
-    .unwrap();
-
-    let R = W[0] + W[1] * d_0 + W[2] * d_1;
-
-    // Check that e(L, vk.H) == e(R, vk.tau_H)
-    P::multi_pairing([L, -R], [vk.kzg_vk.g2, vk.kzg_vk.beta_g2]).is_zero()
-}
-
 #[derive(Clone)]
 pub struct HyperKZG<P: Pairing, ProofTranscript: Transcript> {
     _phantom: PhantomData<(P, ProofTranscript)>,
@@ -305,7 +316,10 @@ where
         )?))
     }
 
-    #[tracing::instrument(skip_all, name = "HyperKZG::open")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyperKZG::open")
+    )]
     pub fn open(
         pk: &HyperKZGProverKey<P>,
         poly: &DensePolynomial<P::ScalarField>,
@@ -367,59 +381,78 @@ where
         pi: &HyperKZGProof<P>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
-        let y = P_of_x;
-
-        let ell = point.len();
-
-        let mut com = pi.com.clone();
-
-        // we do not need to add x to the transcript, because in our context x was
-        // obtained from the transcript
-        transcript.append_points(&com.iter().map(|g| g.into_group()).collect::<Vec<P::G1>>());
-        let r: <P as Pairing>::ScalarField = transcript.challenge_scalar();
-
-        if r == P::ScalarField::zero() || C.0 == P::G1Affine::zero() {
-            return Err(ProofVerifyError::InternalError);
-        }
-        com.insert(0, C.0); // set com_0 = C, shifts other commitments to the right
-
-        let u = vec![r, -r, r * r];
-
-        // Setup vectors (Y, ypos, yneg) from pi.v
-        let v = &pi.v;
-        if v.len() != 3 {
-            return Err(ProofVerifyError::InternalError);
-        }
-        if v[0].len() != ell || v[1].len() != ell || v[2].len() != ell {
-            return Err(ProofVerifyError::InternalError);
-        }
-        let ypos = &v[0];
-        let yneg = &v[1];
-        let mut Y = v[2].to_vec();
-        Y.push(*y);
-
-        // Check consistency of (Y, ypos, yneg)
-        let two = P::ScalarField::from(2u64);
-        for i in 0..ell {
-            if two * r * Y[i + 1]
-                != r * (P::ScalarField::one() - point[ell - i - 1]) * (ypos[i] + yneg[i])
-                    + point[ell - i - 1] * (ypos[i] - yneg[i])
-            {
-                return Err(ProofVerifyError::InternalError);
-            }
-            // Note that we don't make any checks about Y[0] here, but our batching
-            // check below requires it
-        }
+        Self::verify_prepared(&vk.prepare(), C, point, P_of_x, pi, transcript)
+    }
 
-        // Check commitments to (Y, ypos, yneg) are valid
-        if !kzg_verify_batch(vk, &com, &pi.w, &u, &pi.v, transcript) {
-            return Err(ProofVerifyError::InternalError);
+    /// Same as [`HyperKZG::verify`], with a verifier key prepared ahead of time
+    /// (see [`HyperKZGVerifierKey::prepare`]).
+    pub fn verify_prepared(
+        vk: &PreparedHyperKZGVerifierKey<P>,
+        C: &HyperKZGCommitment<P>,
+        point: &[P::ScalarField],
+        P_of_x: &P::ScalarField,
+        pi: &HyperKZGProof<P>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        let pairing_check = Self::verify_inner(vk, C, point, P_of_x, pi, transcript)?;
+        if P::multi_pairing(
+            pairing_check,
+            [
+                vk.kzg_vk.g2_prepared.clone(),
+                vk.kzg_vk.beta_g2_prepared.clone(),
+            ],
+        )
+        .is_zero()
+        {
+            Ok(())
+        } else {
+            Err(ProofVerifyError::InternalError)
         }
+    }
 
+    /// Same as [`HyperKZG::verify_prepared`], but defers the final pairing check to
+    /// `accumulator` (see [`PreparedHyperKZGVerifierKey::pairing_accumulator`]). The proof
+    /// is only verified once the accumulator is.
+    pub fn verify_deferred(
+        vk: &PreparedHyperKZGVerifierKey<P>,
+        C: &HyperKZGCommitment<P>,
+        point: &[P::ScalarField],
+        P_of_x: &P::ScalarField,
+        pi: &HyperKZGProof<P>,
+        transcript: &mut ProofTranscript,
+        accumulator: &mut PairingAccumulator<P>,
+    ) -> Result<(), ProofVerifyError> {
+        let pairing_check = Self::verify_inner(vk, C, point, P_of_x, pi, transcript)?;
+        accumulator.accumulate(pairing_check.to_vec());
         Ok(())
     }
 
-    #[tracing::instrument(skip_all, name = "HyperKZG::batch_open")]
+    /// Performs all of the verifier's checks except for the final pairing check, whose
+    /// G1 elements are returned.
+    fn verify_inner(
+        vk: &PreparedHyperKZGVerifierKey<P>,
+        C: &HyperKZGCommitment<P>,
+        point: &[P::ScalarField],
+        P_of_x: &P::ScalarField,
+        pi: &HyperKZGProof<P>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<[P::G1; 2], ProofVerifyError> {
+        hyperkzg_pairing_check::<P, ProofTranscript>(
+            &vk.kzg_vk.vk.g1,
+            &C.0,
+            point,
+            P_of_x,
+            &pi.com,
+            &pi.w,
+            &pi.v,
+            transcript,
+        )
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyperKZG::batch_open")
+    )]
     fn batch_open(
         pk: &HyperKZGProverKey<P>,
         polynomials: &[&DensePolynomial<P::ScalarField>],
@@ -444,9 +477,9 @@ where
             .map(|(scalar, eval)| *scalar * *eval)
             .sum();
 
-        let span = trace_span!("f_batched");
-        let enter = span.enter();
-        let num_chunks = rayon::current_num_threads().next_power_of_two();
+        #[cfg(feature = "tracing")]
+        let enter = tracing::trace_span!("f_batched").entered();
+        let num_chunks = par::current_num_threads().next_power_of_two();
         let chunk_size = n / num_chunks;
         let f_batched = (0..num_chunks)
             .into_par_iter()
@@ -463,8 +496,8 @@ where
                 chunk
             })
             .collect::<Vec<_>>();
+        #[cfg(feature = "tracing")]
         drop(enter);
-        drop(span);
 
         let poly = DensePolynomial::new(f_batched);
         HyperKZG::<P, ProofTranscript>::open(pk, &poly, point, &batched_evaluation, transcript)
@@ -472,7 +505,7 @@ where
     }
 
     fn batch_verify(
-        vk: &HyperKZGVerifierKey<P>,
+        vk: &PreparedHyperKZGVerifierKey<P>,
         commitments: &[&HyperKZGCommitment<P>],
         point: &[P::ScalarField],
         evals: &[P::ScalarField],
@@ -493,7 +526,7 @@ where
                 (batched_evaluation, batched_commitment)
             },
         );
-        HyperKZG::<P, ProofTranscript>::verify(
+        HyperKZG::<P, ProofTranscript>::verify_prepared(
             vk,
             &HyperKZGCommitment(batched_commitment.into_affine()),
             point,
@@ -510,7 +543,7 @@ where
     <P as Pairing>::ScalarField: field::JoltField,
 {
     type Field = P::ScalarField;
-    type Setup = (HyperKZGProverKey<P>, HyperKZGVerifierKey<P>);
+    type Setup = (HyperKZGProverKey<P>, PreparedHyperKZGVerifierKey<P>);
     type Commitment = HyperKZGCommitment<P>;
     type Proof = HyperKZGProof<P>;
     type BatchedProof = HyperKZGProof<P>;
@@ -518,12 +551,23 @@ where
     fn setup(shapes: &[CommitShape]) -> Self::Setup {
         let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
 
-        HyperKZGSRS(Arc::new(SRS::setup(
+        let (pk, vk) = HyperKZGSRS(Arc::new(SRS::setup(
             &mut ChaCha20Rng::from_seed(*b"HyperKZG_POLY_COMMITMENT_SCHEMEE"),
             max_len,
             2,
         )))
-        .trim(max_len)
+        .trim(max_len);
+        let pk = HyperKZGProverKey {
+            kzg_pk: pk.kzg_pk.with_default_precomputed_bases(shapes),
+        };
+        (pk, vk.prepare())
+    }
+
+    fn verifier_setup(setup: &Self::Setup) -> Self::Setup {
+        let pk = HyperKZGProverKey {
+            kzg_pk: KZGProverKey::empty(),
+        };
+        (pk, setup.1.clone())
     }
 
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
@@ -541,31 +585,36 @@ where
         gens: &Self::Setup,
         batch_type: BatchType,
     ) -> Vec<Self::Commitment> {
-        // TODO: assert lengths are valid
-        evals
-            .par_iter()
-            .map(|evals| {
-                assert!(
-                    gens.0.kzg_pk.g1_powers().len() >= evals.len(),
-                    "COMMIT KEY LENGTH ERROR {}, {}",
-                    gens.0.kzg_pk.g1_powers().len(),
-                    evals.len()
-                );
-                match batch_type {
-                    BatchType::GrandProduct => HyperKZGCommitment(
-                        UnivariateKZG::commit_slice_with_mode(
-                            &gens.0.kzg_pk,
-                            evals,
-                            kzg::CommitMode::GrandProduct,
-                        )
-                        .unwrap(),
-                    ),
-                    _ => HyperKZGCommitment(
-                        UnivariateKZG::commit_slice(&gens.0.kzg_pk, evals).unwrap(),
-                    ),
-                }
-            })
-            .collect::<Vec<_>>()
+        let mut commitments: Vec<Self::Commitment> = (0..evals.len())
+            .map(|_| HyperKZGCommitment::default())
+            .collect();
+        for (size_class, indices) in SizeClass::group(evals) {
+            let mode = match (&batch_type, size_class) {
+                (BatchType::GrandProduct, _) => kzg::CommitMode::GrandProduct,
+                (_, SizeClass::Tiny) => kzg::CommitMode::Straus,
+                (BatchType::SmallScalars, SizeClass::Big) => kzg::CommitMode::SmallScalars,
+                (_, SizeClass::Big) => kzg::CommitMode::Default,
+            };
+            let group_commitments: Vec<Self::Commitment> = indices
+                .par_iter()
+                .map(|&i| {
+                    assert!(
+                        gens.0.kzg_pk.g1_powers().len() >= evals[i].len(),
+                        "COMMIT KEY LENGTH ERROR {}, {}",
+                        gens.0.kzg_pk.g1_powers().len(),
+                        evals[i].len()
+                    );
+                    HyperKZGCommitment(
+                        UnivariateKZG::commit_slice_with_mode(&gens.0.kzg_pk, evals[i], mode)
+                            .unwrap(),
+                    )
+                })
+                .collect();
+            for (i, commitment) in indices.into_iter().zip(group_commitments) {
+                commitments[i] = commitment;
+            }
+        }
+        commitments
     }
 
     fn commit_slice(evals: &[Self::Field], setup: &Self::Setup) -> Self::Commitment {
@@ -600,18 +649,6 @@ where
         )
     }
 
-    fn combine_commitments(
-        commitments: &[&Self::Commitment],
-        coeffs: &[Self::Field],
-    ) -> Self::Commitment {
-        let combined_commitment: P::G1 = commitments
-            .iter()
-            .zip(coeffs.iter())
-            .map(|(commitment, coeff)| commitment.0 * coeff)
-            .sum();
-        HyperKZGCommitment(combined_commitment.into_affine())
-    }
-
     fn verify(
         proof: &Self::Proof,
         setup: &Self::Setup,
@@ -620,7 +657,7 @@ where
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
         commitment: &Self::Commitment,
     ) -> Result<(), ProofVerifyError> {
-        HyperKZG::<P, ProofTranscript>::verify(
+        HyperKZG::<P, ProofTranscript>::verify_prepared(
             &setup.1,
             commitment,
             opening_point,
@@ -648,11 +685,95 @@ where
         )
     }
 
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        let commitment = <Self as AdditivelyHomomorphic<ProofTranscript>>::combine_commitments(
+            commitments,
+            coeffs,
+        );
+        <Self as CommitmentScheme<ProofTranscript>>::verify(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            &commitment,
+        )
+    }
+
     fn protocol_name() -> &'static [u8] {
         b"hyperkzg"
     }
 }
 
+impl<P: Pairing, ProofTranscript: Transcript> AdditivelyHomomorphic<ProofTranscript>
+    for HyperKZG<P, ProofTranscript>
+where
+    <P as Pairing>::ScalarField: field::JoltField,
+{
+    fn combine_commitments(
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Self::Commitment {
+        // A single MSM, so that batching many openings costs the verifier one MSM. Commitments
+        // to zero polynomials do not contribute to it.
+        let (bases, coeffs): (Vec<_>, Vec<_>) = commitments
+            .iter()
+            .zip(coeffs.iter())
+            .filter(|(commitment, _)| !commitment.0.is_zero())
+            .map(|(commitment, coeff)| (commitment.0, *coeff))
+            .unzip();
+        let combined_commitment = <P::G1 as VariableBaseMSM>::msm(&bases, &coeffs).unwrap();
+        HyperKZGCommitment(combined_commitment.into_affine())
+    }
+}
+
+impl<P: Pairing, ProofTranscript: Transcript> AggregatableCommitmentScheme<ProofTranscript>
+    for HyperKZG<P, ProofTranscript>
+where
+    <P as Pairing>::ScalarField: field::JoltField,
+{
+    type Accumulator = PairingAccumulator<P>;
+
+    fn accumulator(setup: &Self::Setup) -> Self::Accumulator {
+        setup.1.pairing_accumulator()
+    }
+
+    fn verify_deferred(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitment: &Self::Commitment,
+        accumulator: &mut Self::Accumulator,
+    ) -> Result<(), ProofVerifyError> {
+        HyperKZG::<P, ProofTranscript>::verify_deferred(
+            &setup.1,
+            commitment,
+            opening_point,
+            opening,
+            proof,
+            transcript,
+            accumulator,
+        )
+    }
+
+    fn verify_accumulator<R: RngCore + CryptoRng>(
+        accumulator: Self::Accumulator,
+        rng: &mut R,
+    ) -> Result<(), ProofVerifyError> {
+        accumulator.verify(rng)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -712,6 +833,149 @@ mod tests {
         assert!(test_inner(point, eval).is_err());
     }
 
+    #[test]
+    fn combine_commitments() {
+        type PCS = HyperKZG<Bn254, KeccakTranscript>;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let srs = HyperKZGSRS::setup(&mut rng, 1 << 4);
+        let (pk, _) = srs.trim(1 << 4);
+
+        let polys: Vec<_> = (0..5)
+            .map(|_| DensePolynomial::<Fr>::random(4, &mut rng))
+            .collect();
+        let coeffs: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let combined = DensePolynomial::new(
+            (0..1 << 4)
+                .map(|i| {
+                    polys
+                        .iter()
+                        .zip(coeffs.iter())
+                        .map(|(p, c)| *c * p[i])
+                        .sum()
+                })
+                .collect(),
+        );
+
+        let commitments: Vec<_> = polys
+            .iter()
+            .map(|poly| PCS::commit(&pk, poly).unwrap())
+            .collect();
+        assert_eq!(
+            PCS::combine_commitments(&commitments.iter().collect::<Vec<_>>(), &coeffs),
+            PCS::commit(&pk, &combined).unwrap()
+        );
+    }
+
+    #[test]
+    fn zero_commitments() {
+        type PCS = HyperKZG<Bn254, KeccakTranscript>;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let srs = HyperKZGSRS::setup(&mut rng, 1 << 8);
+        let (pk, vk) = srs.trim(1 << 8);
+        let setup = (pk, vk.prepare());
+
+        let zero = vec![Fr::zero(); 1 << 8];
+        let nonzero: Vec<Fr> = (0..1 << 8).map(|_| Fr::rand(&mut rng)).collect();
+        let commitments = PCS::batch_commit(&[&zero[..], &nonzero[..]], &setup, BatchType::Big);
+        assert_eq!(commitments[0], HyperKZGCommitment::default());
+
+        // A commitment to zero takes a single byte, and has no other encoding
+        let mut bytes = vec![];
+        commitments[0].serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes, [1]);
+        assert_eq!(
+            HyperKZGCommitment::deserialize_compressed(&bytes[..]).unwrap(),
+            commitments[0]
+        );
+        let mut full_encoding = vec![0];
+        commitments[0]
+            .0
+            .serialize_compressed(&mut full_encoding)
+            .unwrap();
+        assert!(HyperKZGCommitment::<Bn254>::deserialize_compressed(&full_encoding[..]).is_err());
+
+        let mut bytes = vec![];
+        commitments[1].serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), commitments[1].compressed_size());
+        assert_eq!(
+            HyperKZGCommitment::deserialize_compressed(&bytes[..]).unwrap(),
+            commitments[1]
+        );
+
+        let coeffs = [Fr::rand(&mut rng), Fr::rand(&mut rng)];
+        assert_eq!(
+            PCS::combine_commitments(&[&commitments[0], &commitments[1]], &coeffs).0,
+            (commitments[1].0 * coeffs[1]).into_affine()
+        );
+    }
+
+    #[test]
+    fn batch_commit_mixed_sizes() {
+        type PCS = HyperKZG<Bn254, KeccakTranscript>;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let srs = HyperKZGSRS::setup(&mut rng, 1 << 9);
+        let (pk, vk) = srs.trim(1 << 9);
+        // The tables only cover the big batch of 2^8 evaluations, so the polynomial with 2^9
+        // evaluations falls back to the bucketed MSM
+        let shapes = [
+            CommitShape::new(1 << 3, BatchType::Small).with_batch_size(10),
+            CommitShape::new(1 << 8, BatchType::Big).with_batch_size(2),
+        ];
+        let precomputed_pk = HyperKZGProverKey {
+            kzg_pk: pk
+                .kzg_pk
+                .clone()
+                .with_precomputed_bases_for_shapes(&shapes, 6),
+        };
+
+        let polys: Vec<_> = [2, 8, 3, 9, 8]
+            .into_iter()
+            .map(|num_vars| DensePolynomial::<Fr>::random(num_vars, &mut rng))
+            .collect();
+        let evals: Vec<&[Fr]> = polys.iter().map(|poly| poly.evals_ref()).collect();
+        let expected: Vec<_> = polys
+            .iter()
+            .map(|poly| PCS::commit(&pk, poly).unwrap())
+            .collect();
+        for pk in [pk, precomputed_pk] {
+            let setup = (pk, vk.prepare());
+            assert_eq!(PCS::batch_commit(&evals, &setup, BatchType::Big), expected);
+        }
+    }
+
+    #[test]
+    fn setup_precomputes_bases() {
+        type PCS = HyperKZG<Bn254, KeccakTranscript>;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let shapes = [
+            CommitShape::new(1 << 3, BatchType::Small).with_batch_size(10),
+            CommitShape::new(1 << 8, BatchType::Big).with_batch_size(2),
+        ];
+        let setup = PCS::setup(&shapes);
+        assert_eq!(setup.0.kzg_pk.num_precomputed_bases(), 1 << 8);
+
+        let polys: Vec<_> = [2, 8, 3, 8]
+            .into_iter()
+            .map(|num_vars| DensePolynomial::<Fr>::random(num_vars, &mut rng))
+            .collect();
+        let evals: Vec<&[Fr]> = polys.iter().map(|poly| poly.evals_ref()).collect();
+        // Straus' MSM does not use the tables
+        let expected: Vec<_> = evals
+            .iter()
+            .map(|evals| {
+                HyperKZGCommitment(
+                    UnivariateKZG::commit_slice_with_mode(
+                        &setup.0.kzg_pk,
+                        evals,
+                        kzg::CommitMode::Straus,
+                    )
+                    .unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(PCS::batch_commit(&evals, &setup, BatchType::Big), expected);
+    }
+
     #[test]
     fn test_hyperkzg_small() {
         let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
@@ -809,4 +1073,35 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn hyperkzg_load_srs_prefix() {
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let path = std::env::temp_dir().join("jolt_hyperkzg_load_srs_prefix_test.srs");
+        HyperKZGSRS::<Bn254>::setup(&mut rng, 1 << 8)
+            .save(&path)
+            .unwrap();
+
+        const NUM_VARS: usize = 5;
+        let srs = HyperKZGSRS::<Bn254>::load(
+            &path,
+            &[
+                CommitShape::new(1 << 3, BatchType::Small),
+                CommitShape::new(1 << NUM_VARS, BatchType::Big),
+            ],
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(srs.0.g1_powers.len(), (1 << NUM_VARS) + 1);
+        let (pk, vk) = srs.trim(1 << NUM_VARS);
+
+        let poly = DensePolynomial::random(NUM_VARS, &mut rng);
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+        let C = HyperKZG::<_, KeccakTranscript>::commit(&pk, &poly).unwrap();
+        let mut tr = KeccakTranscript::new(b"TestEval");
+        let proof = HyperKZG::open(&pk, &poly, &point, &eval, &mut tr).unwrap();
+        let mut verifier_tr = KeccakTranscript::new(b"TestEval");
+        assert!(HyperKZG::verify(&vk, &C, &point, &eval, &proof, &mut verifier_tr).is_ok());
+    }
 }
@@ -1,19 +1,18 @@
 use std::marker::PhantomData;
 
-use super::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use super::commitment_scheme::{AdditivelyHomomorphic, BatchType, CommitShape, CommitmentScheme};
 use super::pedersen::{PedersenCommitment, PedersenGenerators};
 use crate::field::JoltField;
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::eq_poly::EqPolynomial;
 use crate::utils::errors::ProofVerifyError;
 use crate::utils::math::Math;
+use crate::utils::par::{self, prelude::*};
 use crate::utils::transcript::{AppendToTranscript, Transcript};
 use crate::utils::{compute_dotproduct, mul_0_1_optimized};
 use ark_ec::CurveGroup;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use num_integer::Roots;
-use rayon::prelude::*;
-use tracing::trace_span;
 
 use crate::msm::VariableBaseMSM;
 
@@ -29,6 +28,7 @@ const SURGE_RATIO_FINAL: usize = 4;
 pub fn batch_type_to_ratio(batch_type: &BatchType) -> usize {
     match batch_type {
         BatchType::Big => TRACE_LEN_R1CS_POLYS_BATCH_RATIO,
+        BatchType::SmallScalars => TRACE_LEN_R1CS_POLYS_BATCH_RATIO,
         BatchType::GrandProduct => TRACE_LEN_R1CS_POLYS_BATCH_RATIO,
         BatchType::Small => 1,
         BatchType::SurgeReadWrite => SURGE_RATIO_READ_WRITE,
@@ -84,6 +84,13 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
     fn commit_slice(eval_slice: &[Self::Field], generators: &Self::Setup) -> Self::Commitment {
         HyraxCommitment::commit_slice(eval_slice, generators)
     }
+    fn commit_streaming<I>(len: usize, chunks: I, generators: &Self::Setup) -> Self::Commitment
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[Self::Field]>,
+    {
+        HyraxCommitment::commit_streaming(len, chunks, generators)
+    }
     fn prove(
         _setup: &Self::Setup,
         poly: &DensePolynomial<Self::Field>,
@@ -109,39 +116,6 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
             transcript,
         )
     }
-    fn combine_commitments(
-        commitments: &[&Self::Commitment],
-        coeffs: &[Self::Field],
-    ) -> Self::Commitment {
-        let max_size = commitments
-            .iter()
-            .map(|commitment| commitment.row_commitments.len())
-            .max()
-            .unwrap();
-
-        let row_commitments = coeffs
-            .par_iter()
-            .zip(commitments.par_iter())
-            .map(|(coeff, commitment)| {
-                commitment
-                    .row_commitments
-                    .iter()
-                    .map(|row_commitment| *row_commitment * coeff)
-                    .collect()
-            })
-            .reduce(
-                || vec![G::zero(); max_size],
-                |running, new| {
-                    running
-                        .iter()
-                        .zip(new.iter())
-                        .map(|(r, n)| *r + n)
-                        .collect()
-                },
-            );
-        HyraxCommitment { row_commitments }
-    }
-
     fn verify(
         proof: &Self::Proof,
         generators: &Self::Setup,
@@ -161,7 +135,10 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
             1,
         )
     }
-    #[tracing::instrument(skip_all, name = "HyraxScheme::batch_verify")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxScheme::batch_verify")
+    )]
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
         generators: &Self::Setup,
@@ -179,11 +156,45 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
             transcript,
         )
     }
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        let commitment = <Self as AdditivelyHomomorphic<ProofTranscript>>::combine_commitments(
+            commitments,
+            coeffs,
+        );
+        <Self as CommitmentScheme<ProofTranscript>>::verify(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            &commitment,
+        )
+    }
+
     fn protocol_name() -> &'static [u8] {
         b"Jolt BatchedHyraxOpeningProof"
     }
 }
 
+impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
+    AdditivelyHomomorphic<ProofTranscript> for HyraxScheme<G, ProofTranscript>
+{
+    fn combine_commitments(
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Self::Commitment {
+        HyraxCommitment::linear_combination(commitments, coeffs)
+    }
+}
+
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyraxGenerators<G: CurveGroup> {
     pub gens: PedersenGenerators<G>,
@@ -195,7 +206,10 @@ pub struct HyraxCommitment<G: CurveGroup> {
 }
 
 impl<F: JoltField, G: CurveGroup<ScalarField = F>> HyraxCommitment<G> {
-    #[tracing::instrument(skip_all, name = "HyraxCommitment::commit")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxCommitment::commit")
+    )]
     pub fn commit(
         poly: &DensePolynomial<G::ScalarField>,
         generators: &PedersenGenerators<G>,
@@ -203,7 +217,10 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> HyraxCommitment<G> {
         Self::commit_slice(poly.evals_ref(), generators)
     }
 
-    #[tracing::instrument(skip_all, name = "HyraxCommitment::commit_slice")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxCommitment::commit_slice")
+    )]
     pub fn commit_slice(eval_slice: &[G::ScalarField], generators: &PedersenGenerators<G>) -> Self {
         let n = eval_slice.len();
         let ell = n.log_2();
@@ -219,7 +236,53 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> HyraxCommitment<G> {
         Self { row_commitments }
     }
 
-    #[tracing::instrument(skip_all, name = "HyraxCommitment::batch_commit")]
+    /// Commits to the polynomial with `len` evaluations, which are yielded chunk-by-chunk by
+    /// `chunks`. Only the evaluations of a partially streamed row are buffered.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxCommitment::commit_streaming")
+    )]
+    pub fn commit_streaming<I>(len: usize, chunks: I, generators: &PedersenGenerators<G>) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[G::ScalarField]>,
+    {
+        let (L_size, R_size) = matrix_dimensions(len.log_2(), 1);
+        assert_eq!(L_size * R_size, len);
+
+        let gens = CurveGroup::normalize_batch(&generators.generators[..R_size]);
+        let mut row_commitments = Vec::with_capacity(L_size);
+        let mut pending_row: Vec<G::ScalarField> = Vec::with_capacity(R_size);
+        for chunk in chunks {
+            let mut chunk = chunk.as_ref();
+            if !pending_row.is_empty() {
+                let num_missing = std::cmp::min(R_size - pending_row.len(), chunk.len());
+                pending_row.extend_from_slice(&chunk[..num_missing]);
+                chunk = &chunk[num_missing..];
+                if pending_row.len() == R_size {
+                    row_commitments.push(PedersenCommitment::commit_vector(&pending_row, &gens));
+                    pending_row.clear();
+                }
+            }
+            let num_full_rows = chunk.len() / R_size;
+            row_commitments.par_extend(
+                chunk[..num_full_rows * R_size]
+                    .par_chunks(R_size)
+                    .map(|row| PedersenCommitment::commit_vector(row, &gens)),
+            );
+            pending_row.extend_from_slice(&chunk[num_full_rows * R_size..]);
+        }
+        assert!(
+            pending_row.is_empty() && row_commitments.len() == L_size,
+            "streamed chunks do not add up to `len`"
+        );
+        Self { row_commitments }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxCommitment::batch_commit")
+    )]
     pub fn batch_commit(
         batch: &[&[G::ScalarField]],
         generators: &PedersenGenerators<G>,
@@ -248,6 +311,37 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> HyraxCommitment<G> {
             })
             .collect()
     }
+
+    /// Computes `sum_i coeffs[i] * commitments[i]` row by row, as a commitment to the same
+    /// linear combination of the committed polynomials. Commitments with fewer rows contribute
+    /// nothing to the trailing rows.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxCommitment::linear_combination")
+    )]
+    pub fn linear_combination(commitments: &[&Self], coeffs: &[G::ScalarField]) -> Self {
+        assert_eq!(commitments.len(), coeffs.len());
+        let num_rows = commitments
+            .iter()
+            .map(|commitment| commitment.row_commitments.len())
+            .max()
+            .unwrap();
+
+        let row_commitments = (0..num_rows)
+            .into_par_iter()
+            .map(|i| {
+                let (rows, coeffs): (Vec<G>, Vec<G::ScalarField>) = commitments
+                    .iter()
+                    .zip(coeffs.iter())
+                    .filter_map(|(commitment, coeff)| {
+                        commitment.row_commitments.get(i).map(|row| (*row, *coeff))
+                    })
+                    .unzip();
+                VariableBaseMSM::msm(&G::normalize_batch(&rows), &coeffs).unwrap()
+            })
+            .collect();
+        Self { row_commitments }
+    }
 }
 
 impl<G: CurveGroup> AppendToTranscript for HyraxCommitment<G> {
@@ -277,7 +371,10 @@ where
         b"Hyrax opening proof"
     }
 
-    #[tracing::instrument(skip_all, name = "HyraxOpeningProof::prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxOpeningProof::prove")
+    )]
     pub fn prove(
         poly: &DensePolynomial<G::ScalarField>,
         opening_point: &[G::ScalarField], // point at which the polynomial is evaluated
@@ -318,6 +415,24 @@ where
 
         // compute L and R
         let (L_size, R_size) = matrix_dimensions(opening_point.len(), ratio);
+        if commitment.row_commitments.len() != L_size {
+            return Err(ProofVerifyError::InvalidInputLength(
+                L_size,
+                commitment.row_commitments.len(),
+            ));
+        }
+        if self.vector_matrix_product.len() != R_size {
+            return Err(ProofVerifyError::InvalidInputLength(
+                R_size,
+                self.vector_matrix_product.len(),
+            ));
+        }
+        if pedersen_generators.generators.len() < R_size {
+            return Err(ProofVerifyError::KeyLengthError(
+                pedersen_generators.generators.len(),
+                R_size,
+            ));
+        }
         let eq: EqPolynomial<_> = EqPolynomial::new(opening_point.to_vec());
         let (L, R) = eq.compute_factored_evals(L_size);
 
@@ -340,7 +455,10 @@ where
         }
     }
 
-    #[tracing::instrument(skip_all, name = "HyraxOpeningProof::vector_matrix_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "HyraxOpeningProof::vector_matrix_product")
+    )]
     fn vector_matrix_product(
         poly: &DensePolynomial<G::ScalarField>,
         L: &[G::ScalarField],
@@ -377,7 +495,10 @@ pub struct BatchedHyraxOpeningProof<G: CurveGroup, ProofTranscript: Transcript>
 impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
     BatchedHyraxOpeningProof<G, ProofTranscript>
 {
-    #[tracing::instrument(skip_all, name = "BatchedHyraxOpeningProof::prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedHyraxOpeningProof::prove")
+    )]
     pub fn prove(
         polynomials: &[&DensePolynomial<G::ScalarField>],
         opening_point: &[G::ScalarField],
@@ -393,12 +514,16 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
 
         let rlc_coefficients: Vec<_> = transcript.challenge_vector(polynomials.len());
 
-        let _span = trace_span!("Compute RLC of polynomials");
-        let _enter = _span.enter();
+        #[cfg(feature = "tracing")]
+        let _enter = tracing::trace_span!("Compute RLC of polynomials").entered();
 
         let poly_len = polynomials[0].len();
+        assert!(
+            polynomials.iter().all(|poly| poly.len() == poly_len),
+            "batched polynomials must have the same length"
+        );
 
-        let num_chunks = rayon::current_num_threads().next_power_of_two();
+        let num_chunks = par::current_num_threads().next_power_of_two();
         let chunk_size = poly_len / num_chunks;
 
         let rlc_poly = if chunk_size > 0 {
@@ -435,8 +560,8 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
                 )
         };
 
+        #[cfg(feature = "tracing")]
         drop(_enter);
-        drop(_span);
 
         let ratio = batch_type_to_ratio(&batch_type);
         let joint_proof = HyraxOpeningProof::prove(
@@ -453,7 +578,10 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
         }
     }
 
-    #[tracing::instrument(skip_all, name = "BatchedHyraxOpeningProof::verify")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedHyraxOpeningProof::verify")
+    )]
     pub fn verify(
         &self,
         pedersen_generators: &PedersenGenerators<G>,
@@ -462,17 +590,23 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
         commitments: &[&HyraxCommitment<G>],
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
-        assert_eq!(openings.len(), commitments.len());
-        let (L_size, _R_size) = matrix_dimensions(opening_point.len(), self.ratio);
-        commitments.iter().enumerate().for_each(|(i, commitment)| {
-            assert_eq!(
-                L_size,
+        if openings.is_empty() || openings.len() != commitments.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                commitments.len(),
+                openings.len(),
+            ));
+        }
+        // The joint opening proof checks that the row count matches the claimed ratio
+        let num_rows = commitments[0].row_commitments.len();
+        if let Some(commitment) = commitments
+            .iter()
+            .find(|commitment| commitment.row_commitments.len() != num_rows)
+        {
+            return Err(ProofVerifyError::InvalidInputLength(
+                num_rows,
                 commitment.row_commitments.len(),
-                "Row commitment {}/{} wrong length.",
-                i,
-                commitments.len()
-            )
-        });
+            ));
+        }
 
         let protocol_name = Self::protocol_name();
         transcript.append_message(protocol_name);
@@ -483,37 +617,14 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>, ProofTranscript: Transcript>
         let rlc_coefficients: Vec<_> = transcript.challenge_vector(openings.len());
 
         let rlc_eval = compute_dotproduct(&rlc_coefficients, openings);
-
-        let rlc_commitment = rlc_coefficients
-            .par_iter()
-            .zip(commitments.par_iter())
-            .map(|(coeff, commitment)| {
-                commitment
-                    .row_commitments
-                    .iter()
-                    .map(|row_commitment| *row_commitment * coeff)
-                    .collect()
-            })
-            .reduce(
-                || vec![G::zero(); L_size],
-                |running, new| {
-                    debug_assert_eq!(running.len(), new.len());
-                    running
-                        .iter()
-                        .zip(new.iter())
-                        .map(|(r, n)| *r + n)
-                        .collect()
-                },
-            );
+        let rlc_commitment = HyraxCommitment::linear_combination(commitments, &rlc_coefficients);
 
         self.joint_proof.verify(
             pedersen_generators,
             transcript,
             opening_point,
             &rlc_eval,
-            &HyraxCommitment {
-                row_commitments: rlc_commitment,
-            },
+            &rlc_commitment,
             self.ratio,
         )
     }
@@ -528,6 +639,7 @@ mod tests {
     use super::*;
     use crate::utils::transcript::{KeccakTranscript, Transcript};
     use ark_bn254::{Fr, G1Projective};
+    use ark_std::One;
 
     #[test]
     fn check_polynomial_commit() {
@@ -535,6 +647,132 @@ mod tests {
         check_polynomial_commit_helper::<Fr, G1Projective, 4>();
     }
 
+    #[test]
+    fn check_polynomial_commit_grumpkin() {
+        use crate::curves::grumpkin;
+        check_polynomial_commit_helper::<ark_bn254::Fq, grumpkin::Projective, 1>();
+        check_polynomial_commit_helper::<ark_bn254::Fq, grumpkin::Projective, 4>();
+    }
+
+    #[test]
+    fn commit_streaming() {
+        let mut rng = ark_std::test_rng();
+        let poly = DensePolynomial::<Fr>::random(7, &mut rng);
+        let generators: PedersenGenerators<G1Projective> =
+            PedersenGenerators::new(1 << 4, b"test-streaming");
+
+        let commitment = HyraxCommitment::commit(&poly, &generators);
+        // Chunks that straddle the rows of the matrix
+        let evals = poly.evals_ref();
+        let chunks = [&evals[..5], &evals[5..6], &evals[6..40], &evals[40..]];
+        assert_eq!(
+            HyraxCommitment::commit_streaming(evals.len(), chunks, &generators),
+            commitment
+        );
+        assert_eq!(
+            HyraxCommitment::commit_streaming(evals.len(), evals.chunks(16), &generators),
+            commitment
+        );
+    }
+
+    #[test]
+    fn batch_prove_verify() {
+        type Hyrax = HyraxScheme<G1Projective, KeccakTranscript>;
+        let mut rng = ark_std::test_rng();
+        const NUM_VARS: usize = 10;
+
+        for batch_type in [
+            BatchType::Big,
+            BatchType::SmallScalars,
+            BatchType::Small,
+            BatchType::SurgeInitFinal,
+            BatchType::SurgeReadWrite,
+            BatchType::GrandProduct,
+        ] {
+            let setup = Hyrax::setup(&[CommitShape::new(1 << NUM_VARS, batch_type.clone())]);
+            let polys: Vec<DensePolynomial<Fr>> = (0..5)
+                .map(|_| DensePolynomial::random(NUM_VARS, &mut rng))
+                .collect();
+            let poly_refs: Vec<&DensePolynomial<Fr>> = polys.iter().collect();
+            let commitments = Hyrax::batch_commit_polys(&polys, &setup, batch_type.clone());
+            let commitment_refs: Vec<&HyraxCommitment<G1Projective>> = commitments.iter().collect();
+            let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::random(&mut rng)).collect();
+            let mut openings: Vec<Fr> = polys.iter().map(|poly| poly.evaluate(&point)).collect();
+
+            let mut prover_transcript = KeccakTranscript::new(b"test-batch");
+            let proof = Hyrax::batch_prove(
+                &setup,
+                &poly_refs,
+                &point,
+                &openings,
+                batch_type,
+                &mut prover_transcript,
+            );
+
+            let mut verifier_transcript = KeccakTranscript::new(b"test-batch");
+            assert!(Hyrax::batch_verify(
+                &proof,
+                &setup,
+                &point,
+                &openings,
+                &commitment_refs,
+                &mut verifier_transcript,
+            )
+            .is_ok());
+
+            openings[2] += Fr::one();
+            let mut verifier_transcript = KeccakTranscript::new(b"test-batch");
+            assert!(Hyrax::batch_verify(
+                &proof,
+                &setup,
+                &point,
+                &openings,
+                &commitment_refs,
+                &mut verifier_transcript,
+            )
+            .is_err());
+
+            // Commitments of the wrong shape are rejected rather than panicking
+            let mut verifier_transcript = KeccakTranscript::new(b"test-batch");
+            let truncated = HyraxCommitment {
+                row_commitments: commitments[0].row_commitments[1..].to_vec(),
+            };
+            assert!(Hyrax::batch_verify(
+                &proof,
+                &setup,
+                &point,
+                &openings[..1],
+                &[&truncated],
+                &mut verifier_transcript,
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn combine_commitments() {
+        type Hyrax = HyraxScheme<G1Projective, KeccakTranscript>;
+        let mut rng = ark_std::test_rng();
+        let setup = Hyrax::setup(&[CommitShape::new(1 << 6, BatchType::Small)]);
+        let a = DensePolynomial::<Fr>::random(6, &mut rng);
+        let b = DensePolynomial::<Fr>::random(6, &mut rng);
+        let coeffs = [Fr::random(&mut rng), Fr::random(&mut rng)];
+        let combined = DensePolynomial::new(
+            a.evals_ref()
+                .iter()
+                .zip(b.evals_ref())
+                .map(|(a, b)| coeffs[0] * a + coeffs[1] * b)
+                .collect(),
+        );
+        assert_eq!(
+            Hyrax::combine_commitments(
+                &[&Hyrax::commit(&a, &setup), &Hyrax::commit(&b, &setup)],
+                &coeffs
+            ),
+            Hyrax::commit(&combined, &setup)
+        );
+    }
+
     fn check_polynomial_commit_helper<
         F: JoltField,
         G: CurveGroup<ScalarField = F>,
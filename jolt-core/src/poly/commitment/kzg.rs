@@ -1,21 +1,41 @@
 use crate::field::JoltField;
+use crate::msm::precomputed::{PrecomputedBases, PrecomputedMSM};
+use crate::msm::straus::msm_straus;
 use crate::msm::VariableBaseMSM;
 use crate::poly::unipoly::UniPoly;
 use crate::utils::errors::ProofVerifyError;
+use crate::utils::par::{self, prelude::*};
+use crate::utils::transcript::Transcript;
+use crate::verifier::pairing::{kzg_pairing_check, verify_kzg_opening};
+use ark_bn254::Bn254;
 use ark_ec::scalar_mul::fixed_base::FixedBase;
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
-use ark_ff::PrimeField;
+use ark_ff::{FftField, Field, PrimeField};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
 use ark_std::{One, UniformRand, Zero};
 use rand_core::{CryptoRng, RngCore};
-use rayon::prelude::*;
+use std::any::Any;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
+use std::path::Path;
 use std::sync::Arc;
 
-#[derive(Clone, Debug)]
+use super::commitment_scheme::{CommitShape, SizeClass};
+use super::ptau::{self, PtauError};
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct SRS<P: Pairing> {
     pub g1_powers: Vec<P::G1Affine>,
     pub g2_powers: Vec<P::G2Affine>,
     pub g_products: Vec<P::G1Affine>,
+    /// `[L_0(beta)]_1, ..., [L_{n-1}(beta)]_1` for the Lagrange basis polynomials of the
+    /// multiplicative subgroup of size `n`. Empty unless the SRS was extended with
+    /// [`SRS::with_lagrange_basis`].
+    pub g1_lagrange_powers: Vec<P::G1Affine>,
 }
 
 impl<P: Pairing> SRS<P> {
@@ -35,7 +55,7 @@ impl<P: Pairing> SRS<P> {
         let g1_table = FixedBase::get_window_table(scalar_bits, g1_window_size, g1);
         let g2_table = FixedBase::get_window_table(scalar_bits, g2_window_size, g2);
 
-        let (g1_powers_projective, g2_powers_projective) = rayon::join(
+        let (g1_powers_projective, g2_powers_projective) = par::join(
             || {
                 let beta_powers: Vec<P::ScalarField> = (0..=num_g1_powers)
                     .scan(beta, |acc, _| {
@@ -58,14 +78,19 @@ impl<P: Pairing> SRS<P> {
             },
         );
 
-        let (g1_powers, g2_powers) = rayon::join(
+        let (g1_powers, g2_powers) = par::join(
             || P::G1::normalize_batch(&g1_powers_projective),
             || P::G2::normalize_batch(&g2_powers_projective),
         );
+        Self::from_powers(g1_powers, g2_powers)
+    }
 
+    /// Builds an SRS from the given G1 and G2 powers of the trapdoor, e.g. the output of a
+    /// trusted setup ceremony.
+    pub fn from_powers(g1_powers: Vec<P::G1Affine>, g2_powers: Vec<P::G2Affine>) -> Self {
         // Precompute a commitment to each power-of-two length vector of ones, which is just the sum of each power-of-two length prefix of the SRS
         let num_powers = (g1_powers.len() as f64).log2().floor() as usize + 1;
-        let all_ones_coeffs: Vec<P::ScalarField> = vec![P::ScalarField::one(); num_g1_powers + 1];
+        let all_ones_coeffs: Vec<P::ScalarField> = vec![P::ScalarField::one(); g1_powers.len()];
         let powers_of_2 = (0..num_powers).into_par_iter().map(|i| 1usize << i);
         let g_products = powers_of_2
             .map(|power| {
@@ -79,9 +104,42 @@ impl<P: Pairing> SRS<P> {
             g1_powers,
             g2_powers,
             g_products,
+            g1_lagrange_powers: vec![],
         }
     }
 
+    /// Derives the Lagrange-form G1 powers for the multiplicative subgroup of size `domain_size`
+    /// from the monomial G1 powers, so that polynomials given by their evaluations over that
+    /// subgroup can be committed to directly (see [`UnivariateKZG::commit_lagrange`]).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SRS::with_lagrange_basis")
+    )]
+    pub fn with_lagrange_basis(mut self, domain_size: usize) -> Self {
+        assert!(
+            domain_size.is_power_of_two(),
+            "domain size must be a power of two"
+        );
+        assert!(
+            domain_size <= self.g1_powers.len(),
+            "SRS length is less than domain size"
+        );
+        let omega = P::ScalarField::get_root_of_unity(domain_size as u64)
+            .expect("scalar field has no subgroup of the given size");
+
+        // L_i(X) = (1/n) ∑ⱼ ω^(-ij) Xʲ, so [L_i(beta)]_1 is the inverse DFT of the [betaʲ]_1
+        let mut lagrange_powers: Vec<P::G1> = self.g1_powers[..domain_size]
+            .par_iter()
+            .map(|g| g.into_group())
+            .collect();
+        g1_fft::<P>(&mut lagrange_powers, omega.inverse().unwrap());
+        let n_inverse = P::ScalarField::from(domain_size as u64).inverse().unwrap();
+        lagrange_powers.par_iter_mut().for_each(|g| *g *= n_inverse);
+
+        self.g1_lagrange_powers = P::G1::normalize_batch(&lagrange_powers);
+        self
+    }
+
     pub fn trim(params: Arc<Self>, max_degree: usize) -> (KZGProverKey<P>, KZGVerifierKey<P>) {
         assert!(!params.g1_powers.is_empty(), "max_degree is 0");
         assert!(
@@ -97,6 +155,141 @@ impl<P: Pairing> SRS<P> {
     }
 }
 
+impl<P: Pairing> SRS<P> {
+    /// Serializes the G1 and G2 powers uncompressed, so that [`SRS::read_prefix`] can later
+    /// deserialize only as many of them as are needed.
+    pub fn write_powers<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.g1_powers.serialize_uncompressed(&mut writer)?;
+        self.g2_powers.serialize_uncompressed(&mut writer)
+    }
+
+    /// Loads the first `num_g1_powers + 1` G1 powers and `num_g2_powers + 1` G2 powers of an SRS
+    /// file written by [`SRS::write_powers`], without reading the rest of it. This makes a large
+    /// SRS file cheap to use for small polynomials. With the `prover` feature, the file is
+    /// mapped into memory rather than read through a buffer; it must not be modified while it
+    /// is loaded.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SRS::load_prefix")
+    )]
+    pub fn load_prefix<Q: AsRef<Path>>(
+        path: Q,
+        num_g1_powers: usize,
+        num_g2_powers: usize,
+    ) -> Result<Self, SerializationError> {
+        let file = File::open(path)?;
+        #[cfg(feature = "prover")]
+        {
+            // Safety: as documented, the file must not be modified while it is mapped
+            let mmap = unsafe { memmap2::Mmap::map(&file)? };
+            Self::read_prefix(
+                &mut std::io::Cursor::new(&mmap[..]),
+                num_g1_powers,
+                num_g2_powers,
+            )
+        }
+        #[cfg(not(feature = "prover"))]
+        Self::read_prefix(&mut BufReader::new(file), num_g1_powers, num_g2_powers)
+    }
+
+    pub fn read_prefix<R: Read + Seek>(
+        reader: &mut R,
+        num_g1_powers: usize,
+        num_g2_powers: usize,
+    ) -> Result<Self, SerializationError> {
+        let g1_powers = read_points_prefix(reader, num_g1_powers + 1)?;
+        let g2_powers = read_points_prefix(reader, num_g2_powers + 1)?;
+        Ok(Self::from_powers(g1_powers, g2_powers))
+    }
+}
+
+/// Deserializes the first `len` points of an uncompressed `Vec` of points, and seeks past the
+/// remaining ones.
+fn read_points_prefix<R: Read + Seek, A: AffineRepr>(
+    reader: &mut R,
+    len: usize,
+) -> Result<Vec<A>, SerializationError> {
+    let total_len = u64::deserialize_uncompressed(&mut *reader)? as usize;
+    if total_len < len {
+        return Err(SerializationError::InvalidData);
+    }
+    let point_size = A::zero().uncompressed_size();
+    let mut buf = vec![0u8; len * point_size];
+    reader.read_exact(&mut buf)?;
+    let points = buf
+        .par_chunks(point_size)
+        .map(|mut bytes| A::deserialize_uncompressed(&mut bytes))
+        .collect::<Result<Vec<_>, _>>()?;
+    reader.seek(SeekFrom::Current(((total_len - len) * point_size) as i64))?;
+    Ok(points)
+}
+
+impl SRS<Bn254> {
+    /// Loads `[tau^i]_1` for `i <= num_g1_powers` and `[tau^i]_2` for `i <= num_g2_powers` from
+    /// a snarkjs / perpetual powers-of-tau `.ptau` file (see [`ptau`]), as an alternative to
+    /// generating them with [`SRS::setup`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SRS::from_ptau")
+    )]
+    pub fn from_ptau<Q: AsRef<Path>>(
+        path: Q,
+        num_g1_powers: usize,
+        num_g2_powers: usize,
+    ) -> Result<Self, PtauError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::from_ptau_reader(&mut reader, num_g1_powers, num_g2_powers)
+    }
+
+    pub fn from_ptau_reader<R: Read + Seek>(
+        reader: &mut R,
+        num_g1_powers: usize,
+        num_g2_powers: usize,
+    ) -> Result<Self, PtauError> {
+        let (g1_powers, g2_powers) =
+            ptau::read_powers(reader, num_g1_powers + 1, num_g2_powers + 1)?;
+        Ok(Self::from_powers(g1_powers, g2_powers))
+    }
+}
+
+/// In-place radix-2 DFT of `values` with respect to `omega`, a primitive root of unity of order
+/// `values.len()`.
+fn g1_fft<P: Pairing>(values: &mut [P::G1], omega: P::ScalarField) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let mut half_size = 1;
+    while half_size < n {
+        let omega_m = omega.pow([(n / (2 * half_size)) as u64]);
+        let twiddles: Vec<P::ScalarField> =
+            std::iter::successors(Some(P::ScalarField::one()), |w| Some(*w * omega_m))
+                .take(half_size)
+                .collect();
+        values.par_chunks_mut(2 * half_size).for_each(|chunk| {
+            let (lo, hi) = chunk.split_at_mut(half_size);
+            for ((x, y), w) in lo.iter_mut().zip(hi.iter_mut()).zip(twiddles.iter()) {
+                let t = *y * w;
+                *y = *x - t;
+                *x += t;
+            }
+        });
+        half_size *= 2;
+    }
+}
+
+/// The window size of the tables [`KZGProverKey::with_default_precomputed_bases`] computes,
+/// which take `ceil(257 / 12) = 22` times the memory of the powers they cover.
+pub const PRECOMPUTED_WINDOW_BITS: usize = 12;
+
 #[derive(Clone, Debug)]
 pub struct KZGProverKey<P: Pairing> {
     srs: Arc<SRS<P>>,
@@ -104,6 +297,8 @@ pub struct KZGProverKey<P: Pairing> {
     offset: usize,
     // max size of srs
     supported_size: usize,
+    // window tables over `g1_powers`, used instead of `VariableBaseMSM` when present
+    precomputed: Option<Arc<dyn PrecomputedMSM<P::G1>>>,
 }
 
 impl<P: Pairing> KZGProverKey<P> {
@@ -119,21 +314,326 @@ impl<P: Pairing> KZGProverKey<P> {
             srs,
             offset,
             supported_size,
+            precomputed: None,
+        }
+    }
+
+    /// A key that supports no polynomials, standing in for the prover key where only the
+    /// verifier key is needed.
+    pub fn empty() -> Self {
+        let srs = SRS {
+            g1_powers: vec![],
+            g2_powers: vec![],
+            g_products: vec![],
+            g1_lagrange_powers: vec![],
+        };
+        Self::new(Arc::new(srs), 0, 0)
+    }
+
+    /// Precomputes window tables over the key's G1 powers, which commitments then reuse.
+    /// Worthwhile when committing to many polynomials against the same key, at the cost of
+    /// `ceil(256 / window_bits)` times the memory of the powers. See [`PrecomputedBases`].
+    pub fn with_precomputed_bases<C>(self, window_bits: usize) -> Self
+    where
+        C: SWCurveConfig,
+        P: Pairing<G1 = Projective<C>, G1Affine = Affine<C>>,
+    {
+        let num_bases = self.supported_size;
+        self.with_precomputed_prefix(num_bases, window_bits)
+    }
+
+    /// Like [`Self::with_precomputed_bases`], but only tabulates the powers that the big
+    /// polynomials of `shapes` are committed to against; tiny ones do not use the tables. No
+    /// tables are computed unless the shapes have at least two big polynomials between them,
+    /// since computing the tables costs about as much as a single commitment.
+    pub fn with_precomputed_bases_for_shapes<C>(
+        self,
+        shapes: &[CommitShape],
+        window_bits: usize,
+    ) -> Self
+    where
+        C: SWCurveConfig,
+        P: Pairing<G1 = Projective<C>, G1Affine = Affine<C>>,
+    {
+        let big_shapes = shapes
+            .iter()
+            .filter(|shape| shape.size_class() == SizeClass::Big);
+        let num_big_polys: usize = big_shapes.clone().map(|shape| shape.batch_size).sum();
+        let num_bases = big_shapes.map(|shape| shape.input_length).max();
+        match num_bases {
+            Some(num_bases) if num_big_polys > 1 => {
+                self.with_precomputed_prefix(num_bases.min(self.supported_size), window_bits)
+            }
+            _ => self,
+        }
+    }
+
+    /// Like [`Self::with_precomputed_bases_for_shapes`], with [`PRECOMPUTED_WINDOW_BITS`], for
+    /// keys over any pairing, as the commitment schemes' setups are generic. The tables need
+    /// the short Weierstrass model of G1, so they are only computed over BN254, the curve Jolt
+    /// commits over; keys over other pairings are returned unchanged.
+    pub fn with_default_precomputed_bases(self, shapes: &[CommitShape]) -> Self {
+        let mut key = Some(self);
+        if let Some(bn254_key) =
+            (&mut key as &mut dyn Any).downcast_mut::<Option<KZGProverKey<Bn254>>>()
+        {
+            *bn254_key = bn254_key.take().map(|bn254_key| {
+                bn254_key.with_precomputed_bases_for_shapes(shapes, PRECOMPUTED_WINDOW_BITS)
+            });
         }
+        key.unwrap()
+    }
+
+    /// The number of G1 powers with precomputed tables, if any.
+    pub fn num_precomputed_bases(&self) -> usize {
+        self.precomputed
+            .as_ref()
+            .map_or(0, |precomputed| precomputed.num_bases())
+    }
+
+    fn with_precomputed_prefix<C>(mut self, num_bases: usize, window_bits: usize) -> Self
+    where
+        C: SWCurveConfig,
+        P: Pairing<G1 = Projective<C>, G1Affine = Affine<C>>,
+    {
+        self.precomputed = Some(Arc::new(PrecomputedBases::new(
+            &self.g1_powers()[..num_bases],
+            window_bits,
+        )));
+        self
     }
 
     pub fn g1_powers(&self) -> &[P::G1Affine] {
         &self.srs.g1_powers[self.offset..self.offset + self.supported_size]
     }
+
+    pub fn g1_lagrange_powers(&self) -> &[P::G1Affine] {
+        &self.srs.g1_lagrange_powers
+    }
+
+    /// The commitments to the all-ones vectors of power-of-two lengths over the key's window
+    /// of the SRS. Those precomputed in the SRS start at its first power, so they are only
+    /// reused by keys without an offset.
+    fn window_g_products(&self) -> Vec<P::G1Affine> {
+        if self.offset == 0 {
+            return self
+                .srs
+                .g_products
+                .iter()
+                .enumerate()
+                .take_while(|(i, _)| 1 << i <= self.supported_size)
+                .map(|(_, g)| *g)
+                .collect();
+        }
+        let mut sum = P::G1::zero();
+        let mut g_products = vec![];
+        for (i, g) in self.g1_powers().iter().enumerate() {
+            sum += g;
+            if (i + 1).is_power_of_two() {
+                g_products.push(sum);
+            }
+        }
+        P::G1::normalize_batch(&g_products)
+    }
+
+    /// The Lagrange-form powers, if they are derived from powers within the key's window (see
+    /// [`SRS::with_lagrange_basis`]).
+    fn window_lagrange_powers(&self) -> &[P::G1Affine] {
+        let lagrange_powers = &self.srs.g1_lagrange_powers;
+        if self.offset == 0 && lagrange_powers.len() <= self.supported_size {
+            lagrange_powers
+        } else {
+            &[]
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Only the window of the SRS the key covers is serialized, along with the tables derived
+/// from the powers in that window. A deserialized key thus owns an SRS starting at its offset.
+impl<P: Pairing> CanonicalSerialize for KZGProverKey<P> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.g1_powers()
+            .serialize_with_mode(&mut writer, compress)?;
+        self.window_g_products()
+            .serialize_with_mode(&mut writer, compress)?;
+        self.window_lagrange_powers()
+            .serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.g1_powers().serialized_size(compress)
+            + self.window_g_products().serialized_size(compress)
+            + self.window_lagrange_powers().serialized_size(compress)
+    }
+}
+
+impl<P: Pairing> Valid for KZGProverKey<P> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.srs.check()
+    }
+}
+
+impl<P: Pairing> CanonicalDeserialize for KZGProverKey<P> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let srs = SRS {
+            g1_powers: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            g2_powers: vec![],
+            g_products: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            g1_lagrange_powers: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        let supported_size = srs.g1_powers.len();
+        Ok(Self::new(Arc::new(srs), 0, supported_size))
+    }
+}
+
+#[derive(Clone, Copy, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct KZGVerifierKey<P: Pairing> {
     pub g1: P::G1Affine,
     pub g2: P::G2Affine,
     pub beta_g2: P::G2Affine,
 }
 
+impl<P: Pairing> KZGVerifierKey<P> {
+    /// Precomputes the pairing-prepared forms of the key's G2 elements. The result can be
+    /// reused across verifications, so the G2 line coefficients are only computed once.
+    pub fn prepare(&self) -> PreparedKZGVerifierKey<P> {
+        PreparedKZGVerifierKey {
+            vk: *self,
+            g2_prepared: self.g2.into(),
+            beta_g2_prepared: self.beta_g2.into(),
+        }
+    }
+}
+
+/// A [`KZGVerifierKey`] with its G2 elements prepared for pairing.
+#[derive(Clone, Debug)]
+pub struct PreparedKZGVerifierKey<P: Pairing> {
+    pub vk: KZGVerifierKey<P>,
+    pub g2_prepared: P::G2Prepared,
+    pub beta_g2_prepared: P::G2Prepared,
+}
+
+/// Only the unprepared key is serialized; the G2 elements are prepared again on
+/// deserialization.
+impl<P: Pairing> CanonicalSerialize for PreparedKZGVerifierKey<P> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.vk.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.vk.serialized_size(compress)
+    }
+}
+
+impl<P: Pairing> Valid for PreparedKZGVerifierKey<P> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.vk.check()
+    }
+}
+
+impl<P: Pairing> CanonicalDeserialize for PreparedKZGVerifierKey<P> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(KZGVerifierKey::deserialize_with_mode(reader, compress, validate)?.prepare())
+    }
+}
+
+impl<P: Pairing> PreparedKZGVerifierKey<P> {
+    /// Returns an empty [`PairingAccumulator`] over the G2 elements `[g2, beta_g2]`.
+    pub fn pairing_accumulator(&self) -> PairingAccumulator<P> {
+        PairingAccumulator::new(vec![
+            self.g2_prepared.clone(),
+            self.beta_g2_prepared.clone(),
+        ])
+    }
+}
+
+/// Defers pairing checks so that they can be verified with a single multi-pairing.
+///
+/// Each accumulated check is of the form `∏ⱼ e(Aⱼ, Bⱼ) == 1`, where the G2 elements `Bⱼ` are
+/// fixed by the accumulator (typically the G2 elements of a verifier key) and the G1 elements
+/// `Aⱼ` are provided per check. [`PairingAccumulator::verify`] takes a random linear combination
+/// of the checks, so all of them are verified with one Miller loop per G2 element and a single
+/// final exponentiation, instead of a full multi-pairing per check.
+#[derive(Clone, Debug)]
+pub struct PairingAccumulator<P: Pairing> {
+    g2_prepared: Vec<P::G2Prepared>,
+    checks: Vec<Vec<P::G1>>,
+}
+
+impl<P: Pairing> PairingAccumulator<P> {
+    pub fn new(g2_prepared: Vec<P::G2Prepared>) -> Self {
+        Self {
+            g2_prepared,
+            checks: vec![],
+        }
+    }
+
+    /// Defers the check `∏ⱼ e(g1[j], Bⱼ) == 1`.
+    pub fn accumulate(&mut self, g1: Vec<P::G1>) {
+        assert_eq!(
+            g1.len(),
+            self.g2_prepared.len(),
+            "pairing check does not match the accumulator's G2 elements"
+        );
+        self.checks.push(g1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.checks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// Verifies all accumulated checks at once. `rng` must be unpredictable to the prover(s)
+    /// of the accumulated checks; it is not used if at most one check was accumulated.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "PairingAccumulator::verify")
+    )]
+    pub fn verify<R: RngCore + CryptoRng>(self, rng: &mut R) -> Result<(), ProofVerifyError> {
+        if self.checks.is_empty() {
+            return Ok(());
+        }
+        let weights: Vec<P::ScalarField> = std::iter::once(P::ScalarField::one())
+            .chain(std::iter::repeat_with(|| P::ScalarField::rand(rng)))
+            .take(self.checks.len())
+            .collect();
+        let g1: Vec<P::G1> = (0..self.g2_prepared.len())
+            .into_par_iter()
+            .map(|j| {
+                self.checks
+                    .iter()
+                    .zip(weights.iter())
+                    .map(|(check, weight)| check[j] * weight)
+                    .sum()
+            })
+            .collect();
+        if P::multi_pairing(g1, self.g2_prepared).is_zero() {
+            Ok(())
+        } else {
+            Err(ProofVerifyError::InternalError)
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum CommitMode {
     Default,
@@ -141,6 +641,67 @@ pub enum CommitMode {
     // This mode uses a precomputed commitment, G, to save some compute.
     // Where G is the commitment to the all-ones vector of length 2^k```
     GrandProduct,
+    // For polynomials with a handful of coefficients, for which bucketing does not pay off
+    // (see `SizeClass::Tiny`).
+    Straus,
+    // For polynomials with small coefficients (see `BatchType::SmallScalars`), which skips the
+    // precomputed tables: those cost a table addition per window of a full-width scalar, while
+    // the MSM over the powers sums the bases of 0/1 coefficients and buckets those of small ones.
+    SmallScalars,
+}
+
+/// Serializes a commitment as a flag byte, followed by the point unless it is the identity, i.e.
+/// the commitment to the zero polynomial. Witnesses contain many all-zero polynomials (unused
+/// flags, padding), whose commitments thus take a single byte each.
+pub(crate) fn serialize_commitment<G: AffineRepr, W: Write>(
+    point: &G,
+    mut writer: W,
+    compress: Compress,
+) -> Result<(), SerializationError> {
+    let is_zero = point.is_zero();
+    is_zero.serialize_with_mode(&mut writer, compress)?;
+    if !is_zero {
+        point.serialize_with_mode(writer, compress)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn commitment_serialized_size<G: AffineRepr>(point: &G, compress: Compress) -> usize {
+    if point.is_zero() {
+        1
+    } else {
+        1 + point.serialized_size(compress)
+    }
+}
+
+/// Reads a commitment written by [`serialize_commitment`], rejecting an identity written out
+/// in full so that every commitment has a single encoding.
+pub(crate) fn deserialize_commitment<G: AffineRepr, R: Read>(
+    mut reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<G, SerializationError> {
+    if bool::deserialize_with_mode(&mut reader, compress, validate)? {
+        return Ok(G::zero());
+    }
+    let point = G::deserialize_with_mode(reader, compress, validate)?;
+    if point.is_zero() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(point)
+}
+
+/// A batched opening of several polynomials, each at its own set of points, following
+/// [BDFG20](https://eprint.iacr.org/2020/081) (Section 4, a.k.a. "shplonk"). The proof is two
+/// group elements and its verification a single pairing check, however many points there are.
+#[derive(Clone, Copy, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultiPointOpeningProof<P: Pairing> {
+    /// `[h]_1` for `h = Σᵢ γ^i (fᵢ - rᵢ) / Z_{Sᵢ}`, where `rᵢ` interpolates `fᵢ` over its
+    /// points `Sᵢ` and `Z_{Sᵢ}` vanishes on them.
+    pub w: P::G1Affine,
+    /// The KZG opening proof at `z` of `L = Σᵢ γ^i Z_{T∖Sᵢ}(z) (fᵢ - rᵢ(z)) - Z_T(z) h`, which
+    /// vanishes at `z`. `T` is the union of the `Sᵢ`.
+    pub w_prime: P::G1Affine,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
@@ -152,7 +713,10 @@ impl<P: Pairing> UnivariateKZG<P>
 where
     <P as Pairing>::ScalarField: JoltField,
 {
-    #[tracing::instrument(skip_all, name = "KZG::commit_offset")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit_offset")
+    )]
     pub fn commit_offset(
         pk: &KZGProverKey<P>,
         poly: &UniPoly<P::ScalarField>,
@@ -161,7 +725,10 @@ where
         Self::commit_inner(pk, &poly.coeffs, offset, CommitMode::Default)
     }
 
-    #[tracing::instrument(skip_all, name = "KZG::commit")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit")
+    )]
     pub fn commit(
         pk: &KZGProverKey<P>,
         poly: &UniPoly<P::ScalarField>,
@@ -169,7 +736,10 @@ where
         Self::commit_inner(pk, &poly.coeffs, 0, CommitMode::Default)
     }
 
-    #[tracing::instrument(skip_all, name = "KZG::commit_with_mode")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit_with_mode")
+    )]
     pub fn commit_with_mode(
         pk: &KZGProverKey<P>,
         poly: &UniPoly<P::ScalarField>,
@@ -178,7 +748,10 @@ where
         Self::commit_inner(pk, &poly.coeffs, 0, mode)
     }
 
-    #[tracing::instrument(skip_all, name = "KZG::commit_slice")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit_slice")
+    )]
     pub fn commit_slice(
         pk: &KZGProverKey<P>,
         coeffs: &[P::ScalarField],
@@ -186,7 +759,10 @@ where
         Self::commit_inner(pk, coeffs, 0, CommitMode::Default)
     }
 
-    #[tracing::instrument(skip_all, name = "KZG::commit_slice_with_mode")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit_slice_with_mode")
+    )]
     pub fn commit_slice_with_mode(
         pk: &KZGProverKey<P>,
         coeffs: &[P::ScalarField],
@@ -195,8 +771,66 @@ where
         Self::commit_inner(pk, coeffs, 0, mode)
     }
 
+    /// Commits to the polynomial whose evaluations over the multiplicative subgroup of size
+    /// `pk.g1_lagrange_powers().len()` are `evals` (padded with zeros), without converting it to
+    /// coefficient form. Requires an SRS extended with [`SRS::with_lagrange_basis`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit_lagrange")
+    )]
+    pub fn commit_lagrange(
+        pk: &KZGProverKey<P>,
+        evals: &[P::ScalarField],
+    ) -> Result<P::G1Affine, ProofVerifyError> {
+        let lagrange_powers = pk.g1_lagrange_powers();
+        if lagrange_powers.len() < evals.len() {
+            return Err(ProofVerifyError::KeyLengthError(
+                lagrange_powers.len(),
+                evals.len(),
+            ));
+        }
+        let c = <P::G1 as VariableBaseMSM>::msm(&lagrange_powers[..evals.len()], evals).unwrap();
+        Ok(c.into_affine())
+    }
+
+    /// Commits to the polynomial whose coefficients are yielded chunk-by-chunk by `chunks`,
+    /// accumulating one MSM per chunk rather than materializing all of the coefficients.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit_streaming")
+    )]
+    pub fn commit_streaming<I>(
+        pk: &KZGProverKey<P>,
+        chunks: I,
+    ) -> Result<P::G1Affine, ProofVerifyError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[P::ScalarField]>,
+    {
+        let g1_powers = pk.g1_powers();
+        let mut offset = 0;
+        let mut commitment = P::G1::zero();
+        for chunk in chunks {
+            let coeffs = chunk.as_ref();
+            if g1_powers.len() < offset + coeffs.len() {
+                return Err(ProofVerifyError::KeyLengthError(
+                    g1_powers.len(),
+                    offset + coeffs.len(),
+                ));
+            }
+            commitment +=
+                <P::G1 as VariableBaseMSM>::msm(&g1_powers[offset..offset + coeffs.len()], coeffs)
+                    .unwrap();
+            offset += coeffs.len();
+        }
+        Ok(commitment.into_affine())
+    }
+
     #[inline]
-    #[tracing::instrument(skip_all, name = "KZG::commit_inner")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::commit_inner")
+    )]
     fn commit_inner(
         pk: &KZGProverKey<P>,
         coeffs: &[P::ScalarField],
@@ -209,9 +843,19 @@ where
                 coeffs.len(),
             ));
         }
+        // Polynomials that are identically zero (e.g. unused flags, or padding) need no MSM
+        if coeffs[offset..].par_iter().all(Zero::is_zero) {
+            return Ok(P::G1Affine::zero());
+        }
 
         match mode {
             CommitMode::Default => {
+                if let Some(precomputed) = &pk.precomputed {
+                    // The tables may only cover a prefix of the powers
+                    if coeffs.len() <= precomputed.num_bases() {
+                        return Ok(precomputed.msm(offset, &coeffs[offset..]).into_affine());
+                    }
+                }
                 let c = <P::G1 as VariableBaseMSM>::msm(
                     &pk.g1_powers()[offset..coeffs.len()],
                     &coeffs[offset..],
@@ -254,10 +898,24 @@ where
                 let final_commitment = pk.srs.g_products[num_powers] + non_one_commitment;
                 Ok(final_commitment.into_affine())
             }
+            CommitMode::SmallScalars => {
+                let c = <P::G1 as VariableBaseMSM>::msm(
+                    &pk.g1_powers()[offset..coeffs.len()],
+                    &coeffs[offset..],
+                )
+                .unwrap();
+                Ok(c.into_affine())
+            }
+            CommitMode::Straus => {
+                let c =
+                    msm_straus::<P::G1>(&pk.g1_powers()[offset..coeffs.len()], &coeffs[offset..])
+                        .unwrap();
+                Ok(c.into_affine())
+            }
         }
     }
 
-    #[tracing::instrument(skip_all, name = "KZG::open")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "KZG::open"))]
     pub fn open(
         pk: &KZGProverKey<P>,
         poly: &UniPoly<P::ScalarField>,
@@ -266,14 +924,12 @@ where
     where
         <P as Pairing>::ScalarField: JoltField,
     {
-        let divisor = UniPoly::from_coeff(vec![-*point, P::ScalarField::one()]);
-        let (witness_poly, _) = poly.divide_with_remainder(&divisor).unwrap();
+        let (witness_poly, evaluation) = poly.divide_by_linear(point);
         let proof = <P::G1 as VariableBaseMSM>::msm(
             &pk.g1_powers()[..witness_poly.coeffs.len()],
             witness_poly.coeffs.as_slice(),
         )
         .unwrap();
-        let evaluation = poly.evaluate(point);
         Ok((proof.into_affine(), evaluation))
     }
 
@@ -284,15 +940,258 @@ where
         proof: &P::G1Affine,
         evaluation: &P::ScalarField,
     ) -> Result<bool, ProofVerifyError> {
-        Ok(P::multi_pairing(
-            [
-                commitment.into_group() - vk.g1.into_group() * evaluation,
-                -proof.into_group(),
-            ],
-            [vk.g2, (vk.beta_g2.into_group() - (vk.g2 * point)).into()],
+        Self::verify_prepared(&vk.prepare(), commitment, point, proof, evaluation)
+    }
+
+    pub fn verify_prepared(
+        vk: &PreparedKZGVerifierKey<P>,
+        commitment: &P::G1Affine,
+        point: &P::ScalarField,
+        proof: &P::G1Affine,
+        evaluation: &P::ScalarField,
+    ) -> Result<bool, ProofVerifyError> {
+        Ok(verify_kzg_opening::<P>(
+            &vk.vk.g1,
+            vk.g2_prepared.clone(),
+            vk.beta_g2_prepared.clone(),
+            commitment,
+            point,
+            proof,
+            evaluation,
+        ))
+    }
+
+    /// Same as [`UnivariateKZG::verify_prepared`], but defers the pairing check to `accumulator`
+    /// (see [`PreparedKZGVerifierKey::pairing_accumulator`]).
+    pub fn verify_deferred(
+        vk: &PreparedKZGVerifierKey<P>,
+        commitment: &P::G1Affine,
+        point: &P::ScalarField,
+        proof: &P::G1Affine,
+        evaluation: &P::ScalarField,
+        accumulator: &mut PairingAccumulator<P>,
+    ) {
+        accumulator
+            .accumulate(Self::pairing_check(vk, commitment, point, proof, evaluation).to_vec());
+    }
+
+    fn pairing_check(
+        vk: &PreparedKZGVerifierKey<P>,
+        commitment: &P::G1Affine,
+        point: &P::ScalarField,
+        proof: &P::G1Affine,
+        evaluation: &P::ScalarField,
+    ) -> [P::G1; 2] {
+        kzg_pairing_check::<P>(&vk.vk.g1, commitment, point, proof, evaluation)
+    }
+
+    /// Opens each of `polys[i]` at all of `points[i]` (which must be distinct) with a single
+    /// [`MultiPointOpeningProof`], returning the proof and the evaluations `evals[i][j]` of
+    /// `polys[i]` at `points[i][j]`. The commitments to `polys` are expected to have been
+    /// appended to `transcript` already.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "KZG::open_multi_point")
+    )]
+    pub fn open_multi_point<ProofTranscript: Transcript>(
+        pk: &KZGProverKey<P>,
+        polys: &[&UniPoly<P::ScalarField>],
+        points: &[Vec<P::ScalarField>],
+        transcript: &mut ProofTranscript,
+    ) -> Result<(MultiPointOpeningProof<P>, Vec<Vec<P::ScalarField>>), ProofVerifyError> {
+        if polys.len() != points.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                polys.len(),
+                points.len(),
+            ));
+        }
+        let evals: Vec<Vec<P::ScalarField>> = polys
+            .par_iter()
+            .zip(points.par_iter())
+            .map(|(poly, points)| points.iter().map(|point| poly.evaluate(point)).collect())
+            .collect();
+        let all_points = Self::append_multi_point_claims(points, &evals, transcript)?;
+        let gammas: Vec<P::ScalarField> = transcript.challenge_scalar_powers(polys.len());
+
+        // (fᵢ - rᵢ) / Z_{Sᵢ} is the quotient of fᵢ by Z_{Sᵢ}, i.e. by each X - s for s in Sᵢ
+        let quotients: Vec<UniPoly<P::ScalarField>> = polys
+            .par_iter()
+            .zip(points.par_iter())
+            .map(|(poly, points)| {
+                points.iter().fold((*poly).clone(), |quotient, point| {
+                    quotient.divide_by_linear(point).0
+                })
+            })
+            .collect();
+        let mut h = UniPoly::from_coeff(vec![P::ScalarField::zero()]);
+        for (quotient, gamma) in quotients.into_iter().zip(gammas.iter()) {
+            h += &(quotient * gamma);
+        }
+        let w = Self::commit(pk, &h)?;
+        transcript.append_point(&w.into_group());
+        let z: P::ScalarField = transcript.challenge_scalar();
+
+        let mut l = h * -vanishing_eval(&all_points, &z);
+        for (((poly, points), evals), gamma) in polys
+            .iter()
+            .zip(points.iter())
+            .zip(evals.iter())
+            .zip(gammas.iter())
+        {
+            let r_z = interpolate_eval(points, evals, &z).ok_or(ProofVerifyError::InternalError)?;
+            let scalar = *gamma * complement_vanishing_eval(&all_points, points, &z);
+            l += &((*poly).clone() * scalar);
+            l[0] -= scalar * r_z;
+        }
+        let (w_prime, _) = Self::open(pk, &l, &z)?;
+
+        Ok((MultiPointOpeningProof { w, w_prime }, evals))
+    }
+
+    /// Verifies a [`MultiPointOpeningProof`] that each of `commitments[i]` evaluates to
+    /// `evals[i][j]` at `points[i][j]`.
+    pub fn verify_multi_point<ProofTranscript: Transcript>(
+        vk: &PreparedKZGVerifierKey<P>,
+        commitments: &[P::G1Affine],
+        points: &[Vec<P::ScalarField>],
+        evals: &[Vec<P::ScalarField>],
+        proof: &MultiPointOpeningProof<P>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        let pairing_check =
+            Self::multi_point_pairing_check(vk, commitments, points, evals, proof, transcript)?;
+        if P::multi_pairing(
+            pairing_check,
+            [vk.g2_prepared.clone(), vk.beta_g2_prepared.clone()],
         )
-        .is_zero())
+        .is_zero()
+        {
+            Ok(())
+        } else {
+            Err(ProofVerifyError::InternalError)
+        }
     }
+
+    /// Same as [`UnivariateKZG::verify_multi_point`], but defers the pairing check to
+    /// `accumulator` (see [`PreparedKZGVerifierKey::pairing_accumulator`]).
+    pub fn verify_multi_point_deferred<ProofTranscript: Transcript>(
+        vk: &PreparedKZGVerifierKey<P>,
+        commitments: &[P::G1Affine],
+        points: &[Vec<P::ScalarField>],
+        evals: &[Vec<P::ScalarField>],
+        proof: &MultiPointOpeningProof<P>,
+        transcript: &mut ProofTranscript,
+        accumulator: &mut PairingAccumulator<P>,
+    ) -> Result<(), ProofVerifyError> {
+        let pairing_check =
+            Self::multi_point_pairing_check(vk, commitments, points, evals, proof, transcript)?;
+        accumulator.accumulate(pairing_check.to_vec());
+        Ok(())
+    }
+
+    /// The G1 elements of the pairing check of a [`MultiPointOpeningProof`], against `[1]_2`
+    /// and `[beta]_2`.
+    ///
+    /// With `F = Σᵢ γ^i Z_{T∖Sᵢ}(z) (Cᵢ - [rᵢ(z)]_1) - Z_T(z) W = [L]_1`, the check is that of a
+    /// KZG opening of `F` to 0 at `z`: e(F + z * W', [1]_2) * e(-W', [beta]_2) == 1.
+    fn multi_point_pairing_check<ProofTranscript: Transcript>(
+        vk: &PreparedKZGVerifierKey<P>,
+        commitments: &[P::G1Affine],
+        points: &[Vec<P::ScalarField>],
+        evals: &[Vec<P::ScalarField>],
+        proof: &MultiPointOpeningProof<P>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<[P::G1; 2], ProofVerifyError> {
+        if commitments.len() != points.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                commitments.len(),
+                points.len(),
+            ));
+        }
+        let all_points = Self::append_multi_point_claims(points, evals, transcript)?;
+        let gammas: Vec<P::ScalarField> = transcript.challenge_scalar_powers(commitments.len());
+        transcript.append_point(&proof.w.into_group());
+        let z: P::ScalarField = transcript.challenge_scalar();
+
+        let mut bases = commitments.to_vec();
+        let mut scalars = Vec::with_capacity(commitments.len() + 2);
+        let mut eval_scalar = P::ScalarField::zero();
+        for ((points, evals), gamma) in points.iter().zip(evals.iter()).zip(gammas.iter()) {
+            let r_z = interpolate_eval(points, evals, &z).ok_or(ProofVerifyError::InternalError)?;
+            let scalar = *gamma * complement_vanishing_eval(&all_points, points, &z);
+            eval_scalar -= scalar * r_z;
+            scalars.push(scalar);
+        }
+        bases.extend([vk.vk.g1, proof.w]);
+        scalars.extend([eval_scalar, -vanishing_eval(&all_points, &z)]);
+        let f = <P::G1 as VariableBaseMSM>::msm(&bases, &scalars).unwrap();
+
+        Ok([f + proof.w_prime * z, -proof.w_prime.into_group()])
+    }
+
+    /// Appends the points and evaluations of a multi-point opening to `transcript`, checking
+    /// that they match up, and returns the union of the points.
+    fn append_multi_point_claims<ProofTranscript: Transcript>(
+        points: &[Vec<P::ScalarField>],
+        evals: &[Vec<P::ScalarField>],
+        transcript: &mut ProofTranscript,
+    ) -> Result<Vec<P::ScalarField>, ProofVerifyError> {
+        if points.len() != evals.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                points.len(),
+                evals.len(),
+            ));
+        }
+        transcript.append_message(b"kzg_multi_point");
+        let mut all_points = vec![];
+        for (points, evals) in points.iter().zip(evals.iter()) {
+            if points.len() != evals.len() {
+                return Err(ProofVerifyError::InvalidInputLength(
+                    points.len(),
+                    evals.len(),
+                ));
+            }
+            transcript.append_scalars(points);
+            transcript.append_scalars(evals);
+            for point in points {
+                if !all_points.contains(point) {
+                    all_points.push(*point);
+                }
+            }
+        }
+        Ok(all_points)
+    }
+}
+
+/// `Z_S(z) = ∏_{s ∈ S} (z - s)`.
+fn vanishing_eval<F: JoltField>(points: &[F], z: &F) -> F {
+    points.iter().map(|point| *z - point).product()
+}
+
+/// `Z_{T∖S}(z)`, for `S` a subset of `T`.
+fn complement_vanishing_eval<F: JoltField>(all_points: &[F], points: &[F], z: &F) -> F {
+    all_points
+        .iter()
+        .filter(|point| !points.contains(*point))
+        .map(|point| *z - point)
+        .product()
+}
+
+/// Evaluates at `z` the polynomial of degree less than `points.len()` taking the values `evals`
+/// at `points`, by Lagrange interpolation. Returns `None` if the points are not distinct.
+fn interpolate_eval<F: JoltField>(points: &[F], evals: &[F], z: &F) -> Option<F> {
+    let mut result = F::zero();
+    for (j, (point, eval)) in points.iter().zip(evals.iter()).enumerate() {
+        let (numerator, denominator) = points
+            .iter()
+            .enumerate()
+            .filter(|(k, _)| *k != j)
+            .fold((F::one(), F::one()), |(num, den), (_, other)| {
+                (num * (*z - other), den * (*point - other))
+            });
+        result += *eval * numerator * denominator.inverse()?;
+    }
+    Some(result)
 }
 
 #[cfg(test)]
@@ -338,4 +1237,177 @@ mod test {
         // This test uses the grand product optimization and ensures only powers of 2 are used for degree generation
         run_kzg_test(|rng| 1 << rng.gen_range(1..8), CommitMode::GrandProduct)
     }
+
+    #[test]
+    fn kzg_commit_lagrange() -> Result<(), ProofVerifyError> {
+        const DOMAIN_SIZE: usize = 16;
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let pp = Arc::new(
+            SRS::<Bn254>::setup(&mut rng, DOMAIN_SIZE, 2).with_lagrange_basis(DOMAIN_SIZE),
+        );
+        let (ck, _) = SRS::trim(pp, DOMAIN_SIZE);
+
+        let p = UniPoly::random::<ChaCha20Rng>(DOMAIN_SIZE - 1, &mut rng);
+        let omega = Fr::get_root_of_unity(DOMAIN_SIZE as u64).unwrap();
+        let evals: Vec<Fr> = std::iter::successors(Some(Fr::one()), |w| Some(*w * omega))
+            .take(DOMAIN_SIZE)
+            .map(|w| p.evaluate(&w))
+            .collect();
+        assert_eq!(
+            UnivariateKZG::<Bn254>::commit_lagrange(&ck, &evals)?,
+            UnivariateKZG::<Bn254>::commit(&ck, &p)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn kzg_commit_streaming() -> Result<(), ProofVerifyError> {
+        const DEGREE: usize = 37;
+        let mut rng = ChaCha20Rng::from_seed([1; 32]);
+        let pp = Arc::new(SRS::<Bn254>::setup(&mut rng, DEGREE, 2));
+        let (ck, _) = SRS::trim(pp, DEGREE);
+
+        let p = UniPoly::random::<ChaCha20Rng>(DEGREE, &mut rng);
+        let chunks = [
+            &p.coeffs[..5],
+            &p.coeffs[5..5],
+            &p.coeffs[5..32],
+            &p.coeffs[32..],
+        ];
+        assert_eq!(
+            UnivariateKZG::<Bn254>::commit_streaming(&ck, chunks)?,
+            UnivariateKZG::<Bn254>::commit(&ck, &p)?
+        );
+        assert!(UnivariateKZG::<Bn254>::commit_streaming(&ck, [&p.coeffs, &p.coeffs]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn kzg_commit_precomputed_bases() -> Result<(), ProofVerifyError> {
+        const DEGREE: usize = 300;
+        let mut rng = ChaCha20Rng::from_seed([3; 32]);
+        let pp = Arc::new(SRS::<Bn254>::setup(&mut rng, DEGREE, 2));
+        let (ck, _) = SRS::trim(pp, DEGREE);
+        let precomputed_ck = ck.clone().with_precomputed_bases(6);
+
+        for degree in [1, 17, DEGREE] {
+            let p = UniPoly::random::<ChaCha20Rng>(degree, &mut rng);
+            assert_eq!(
+                UnivariateKZG::<Bn254>::commit(&precomputed_ck, &p)?,
+                UnivariateKZG::<Bn254>::commit(&ck, &p)?
+            );
+            assert_eq!(
+                UnivariateKZG::<Bn254>::commit_offset(&precomputed_ck, &p, degree / 2)?,
+                UnivariateKZG::<Bn254>::commit_offset(&ck, &p, degree / 2)?
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn kzg_commit_small_scalars() -> Result<(), ProofVerifyError> {
+        const DEGREE: usize = 1 << 10;
+        let mut rng = ChaCha20Rng::from_seed([5; 32]);
+        let pp = Arc::new(SRS::<Bn254>::setup(&mut rng, DEGREE, 2));
+        let (ck, _) = SRS::trim(pp, DEGREE);
+        let precomputed_ck = ck.clone().with_precomputed_bases(6);
+
+        // 0/1, 10-bit and 32-bit coefficients
+        for max in [2u64, 1 << 10, 1 << 32] {
+            let coeffs: Vec<Fr> = (0..DEGREE)
+                .map(|_| Fr::from(rng.gen_range(0..max)))
+                .collect();
+            let expected = UnivariateKZG::<Bn254>::commit_slice(&ck, &coeffs)?;
+            for ck in [&ck, &precomputed_ck] {
+                assert_eq!(
+                    UnivariateKZG::<Bn254>::commit_slice_with_mode(
+                        ck,
+                        &coeffs,
+                        CommitMode::SmallScalars
+                    )?,
+                    expected
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn kzg_multi_point_opening() -> Result<(), ProofVerifyError> {
+        use crate::utils::transcript::KeccakTranscript;
+
+        const DEGREE: usize = 40;
+        let mut rng = ChaCha20Rng::from_seed([4; 32]);
+        let pp = Arc::new(SRS::<Bn254>::setup(&mut rng, DEGREE, 2));
+        let (ck, vk) = SRS::trim(pp, DEGREE);
+        let vk = vk.prepare();
+
+        let polys: Vec<UniPoly<Fr>> = [DEGREE, 7, 1]
+            .into_iter()
+            .map(|degree| UniPoly::random::<ChaCha20Rng>(degree, &mut rng))
+            .collect();
+        let commitments = polys
+            .iter()
+            .map(|poly| UnivariateKZG::<Bn254>::commit(&ck, poly))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Overlapping sets of points, of different sizes
+        let (a, b, c) = (Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let points = vec![vec![a, b], vec![b, c, a], vec![c]];
+
+        let (proof, evals) = UnivariateKZG::<Bn254>::open_multi_point(
+            &ck,
+            &polys.iter().collect::<Vec<_>>(),
+            &points,
+            &mut KeccakTranscript::new(b"test"),
+        )?;
+        assert_eq!(evals[1][1], polys[1].evaluate(&c));
+        let verify = |evals: &[Vec<Fr>]| {
+            UnivariateKZG::verify_multi_point(
+                &vk,
+                &commitments,
+                &points,
+                evals,
+                &proof,
+                &mut KeccakTranscript::new(b"test"),
+            )
+        };
+        verify(&evals)?;
+
+        let mut bad_evals = evals.clone();
+        bad_evals[1][2] += Fr::one();
+        assert!(verify(&bad_evals).is_err());
+        assert!(verify(&evals[..2]).is_err());
+
+        let mut accumulator = vk.pairing_accumulator();
+        UnivariateKZG::verify_multi_point_deferred(
+            &vk,
+            &commitments,
+            &points,
+            &evals,
+            &proof,
+            &mut KeccakTranscript::new(b"test"),
+            &mut accumulator,
+        )?;
+        accumulator.verify(&mut rng)
+    }
+
+    #[test]
+    fn srs_read_prefix() -> Result<(), SerializationError> {
+        let mut rng = ChaCha20Rng::from_seed([2; 32]);
+        let srs = SRS::<Bn254>::setup(&mut rng, 32, 16);
+        let mut buf = vec![];
+        srs.write_powers(&mut buf)?;
+
+        let prefix = SRS::<Bn254>::read_prefix(&mut std::io::Cursor::new(&buf), 8, 4)?;
+        assert_eq!(prefix.g1_powers, srs.g1_powers[..9]);
+        assert_eq!(prefix.g2_powers, srs.g2_powers[..5]);
+        assert_eq!(prefix.g_products, srs.g_products[..prefix.g_products.len()]);
+
+        let full = SRS::<Bn254>::read_prefix(&mut std::io::Cursor::new(&buf), 32, 16)?;
+        assert_eq!(full.g1_powers, srs.g1_powers);
+        assert_eq!(full.g2_powers, srs.g2_powers);
+
+        assert!(SRS::<Bn254>::read_prefix(&mut std::io::Cursor::new(&buf), 33, 0).is_err());
+        Ok(())
+    }
 }
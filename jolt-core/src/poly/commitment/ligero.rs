@@ -0,0 +1,702 @@
+//! This module implements a Ligero-style polynomial commitment scheme, which only relies on
+//! a collision-resistant hash function and is thus plausibly post-quantum secure.
+//!
+//! A polynomial is viewed as a matrix `M` whose rows have the length fixed by the setup, so
+//! that a smaller polynomial's matrix consists of the first rows of the matrix of the same
+//! polynomial padded with zeros. Each row is encoded with a Reed–Solomon code, and the
+//! commitment is the root of a Merkle tree whose leaves are the hashes of the columns of the
+//! encoded matrix.
+//!
+//! To open `M` at a point `r`, split `r` into row and column variables, so that
+//! `f(r) = L^T M R` with `L`, `R` the respective eq-tables. The prover sends `u = γ^T M` for a
+//! random `γ` (the proximity test) and `v = L^T M` (the evaluation), then opens a number of
+//! random columns of the encoded matrix. The verifier checks that the encodings of `u` and `v`
+//! agree with the opened columns combined by `γ` and `L`, and that `f(r) = <v, R>`.
+//!
+//! Merkle roots cannot be combined homomorphically, so Ligero does not implement
+//! [`AdditivelyHomomorphic`]. A linear combination of polynomials is instead opened by opening
+//! the same columns of each of them, and the opening accumulator retains the polynomials it
+//! reduces to a single opening (see [`CommitmentScheme::OPENS_EACH_COMMITMENT`]).
+//!
+//! [`AdditivelyHomomorphic`]: super::commitment_scheme::AdditivelyHomomorphic
+use std::marker::PhantomData;
+
+use super::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use super::reed_solomon;
+use crate::field::JoltField;
+use crate::poly::dense_mlpoly::DensePolynomial;
+use crate::poly::eq_poly::EqPolynomial;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::math::Math;
+use crate::utils::merkle_tree::{hash_leaf, Digest32, MerklePath, MerkleTree};
+use crate::utils::mul_0_1_optimized;
+use crate::utils::par::prelude::*;
+use crate::utils::transcript::{AppendToTranscript, Transcript};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+/// The inverse rate of the Reed–Solomon code the rows are encoded with.
+const BLOWUP: usize = 4;
+/// The number of columns opened per proof. The code has relative distance `3/4`, so each
+/// opened column catches a prover whose rows are `1/4`-far from the code with probability at
+/// least `1/4`, and `(3/4)^309 < 2^-128`.
+const NUM_COLUMN_OPENINGS: usize = 309;
+
+/// Computes `coeffs^T M` for the matrix `M` with rows of length `row_len`.
+fn combine_rows<F: JoltField>(evals: &[F], coeffs: &[F], row_len: usize) -> Vec<F> {
+    evals
+        .par_chunks(row_len)
+        .zip(coeffs.par_iter())
+        .fold(
+            || vec![F::zero(); row_len],
+            |mut acc, (row, coeff)| {
+                acc.iter_mut()
+                    .zip(row)
+                    .for_each(|(x, y)| *x += mul_0_1_optimized(coeff, y));
+                acc
+            },
+        )
+        .reduce(
+            || vec![F::zero(); row_len],
+            |mut acc, row| {
+                acc.iter_mut().zip(row).for_each(|(x, y)| *x += y);
+                acc
+            },
+        )
+}
+
+/// The setup of the Ligero scheme, which only fixes the length of the matrices' rows.
+#[derive(Clone, Debug)]
+pub struct LigeroSetup {
+    /// The number of column variables, i.e. the matrices' rows have `2^col_vars` entries
+    pub col_vars: usize,
+}
+
+impl LigeroSetup {
+    /// Returns a setup for polynomials with at most `max_num_vars` variables, whose matrices
+    /// have at least as many columns as rows.
+    pub fn new(max_num_vars: usize) -> Self {
+        Self {
+            col_vars: max_num_vars.div_ceil(2),
+        }
+    }
+
+    fn row_len(&self) -> usize {
+        self.col_vars.pow2()
+    }
+
+    /// The number of row variables of the matrix of a polynomial with `num_vars` variables.
+    fn row_vars(&self, num_vars: usize) -> usize {
+        num_vars.saturating_sub(self.col_vars)
+    }
+}
+
+/// The rows of a polynomial's matrix encoded with the Reed–Solomon code, and the Merkle tree
+/// over its columns.
+struct EncodedMatrix<F: JoltField> {
+    rows: Vec<Vec<F>>,
+    tree: MerkleTree,
+}
+
+impl<F: JoltField + PrimeField> EncodedMatrix<F> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "EncodedMatrix::new")
+    )]
+    fn new(evals: &[F], setup: &LigeroSetup) -> Self {
+        let row_len = setup.row_len();
+        // A polynomial with fewer evaluations than a row is padded with zeros
+        let rows: Vec<Vec<F>> = evals
+            .par_chunks(row_len)
+            .map(|row| {
+                let mut row = row.to_vec();
+                row.resize(row_len, F::zero());
+                reed_solomon::encode(&row, BLOWUP)
+            })
+            .collect();
+        let leaves = (0..row_len * BLOWUP)
+            .into_par_iter()
+            .map(|j| hash_leaf(&Self::column(&rows, j)))
+            .collect();
+        Self {
+            rows,
+            tree: MerkleTree::new(leaves),
+        }
+    }
+
+    fn column(rows: &[Vec<F>], index: usize) -> Vec<F> {
+        rows.iter().map(|row| row[index]).collect()
+    }
+
+    fn open_column(&self, index: usize) -> LigeroColumnOpening<F> {
+        LigeroColumnOpening {
+            values: Self::column(&self.rows, index),
+            path: self.tree.path(index),
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LigeroCommitment {
+    /// The root of the Merkle tree over the columns of the encoded matrix
+    pub root: Digest32,
+}
+
+impl AppendToTranscript for LigeroCommitment {
+    fn append_to_transcript<ProofTranscript: Transcript>(&self, transcript: &mut ProofTranscript) {
+        transcript.append_message(b"poly_commitment_begin");
+        transcript.append_bytes(&self.root);
+        transcript.append_message(b"poly_commitment_end");
+    }
+}
+
+/// An opened column of an encoded matrix, with its authentication path.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LigeroColumnOpening<F: JoltField> {
+    pub values: Vec<F>,
+    pub path: MerklePath,
+}
+
+/// An opening proof for a linear combination of one or more polynomials, at the same point.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LigeroProof<F: JoltField> {
+    /// `u = γ^T M`, the rows combined by the random coefficients of the proximity test
+    pub proximity_row: Vec<F>,
+    /// `v = L^T M`, the rows combined by the row eq-table
+    pub evaluation_row: Vec<F>,
+    /// `columns[k][q]` is the `q`-th opened column of the `k`-th polynomial
+    pub columns: Vec<Vec<LigeroColumnOpening<F>>>,
+}
+
+/// The Ligero commitment scheme. Its commitments are Merkle roots, so unlike the
+/// elliptic-curve schemes they cannot be combined homomorphically: polynomials are instead
+/// batched by opening the same columns of each of them.
+#[derive(Clone)]
+pub struct Ligero<F: JoltField, ProofTranscript: Transcript> {
+    _phantom: PhantomData<(F, ProofTranscript)>,
+}
+
+impl<F, ProofTranscript> Ligero<F, ProofTranscript>
+where
+    F: JoltField + PrimeField,
+    ProofTranscript: Transcript,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Ligero::commit")
+    )]
+    fn commit_evals(evals: &[F], setup: &LigeroSetup) -> LigeroCommitment {
+        LigeroCommitment {
+            root: EncodedMatrix::new(evals, setup).tree.root(),
+        }
+    }
+
+    /// Samples the indices of the columns to open from the transcript.
+    fn column_indices(transcript: &mut ProofTranscript, codeword_len: usize) -> Vec<usize> {
+        (0..NUM_COLUMN_OPENINGS)
+            .map(|_| {
+                let challenge: F = transcript.challenge_scalar();
+                (challenge.into_bigint().as_ref()[0] as usize) % codeword_len
+            })
+            .collect()
+    }
+
+    /// Opens the linear combination of the polynomials with the given coefficients at
+    /// `point`. Polynomials with fewer variables than `point` are padded with zeros. The
+    /// encoded matrices are recomputed, as the commitments only retain the Merkle roots.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Ligero::open")
+    )]
+    fn open(
+        setup: &LigeroSetup,
+        polynomials: &[&DensePolynomial<F>],
+        coeffs: &[F],
+        point: &[F],
+        transcript: &mut ProofTranscript,
+    ) -> LigeroProof<F> {
+        transcript.append_message(<Self as CommitmentScheme<ProofTranscript>>::protocol_name());
+        assert_eq!(polynomials.len(), coeffs.len());
+        assert!(polynomials
+            .iter()
+            .all(|poly| poly.get_num_vars() <= point.len()));
+
+        let row_vars = setup.row_vars(point.len());
+        let row_len = setup.row_len();
+
+        let mut rlc_poly = DensePolynomial::linear_combination(polynomials, coeffs).Z;
+        rlc_poly.resize(row_vars.pow2() * row_len, F::zero());
+
+        let gamma: Vec<F> = transcript.challenge_vector(row_vars.pow2());
+        let proximity_row = combine_rows(&rlc_poly, &gamma, row_len);
+        let evaluation_row =
+            combine_rows(&rlc_poly, &EqPolynomial::evals(&point[..row_vars]), row_len);
+        transcript.append_scalars(&proximity_row);
+        transcript.append_scalars(&evaluation_row);
+
+        let indices = Self::column_indices(transcript, row_len * BLOWUP);
+        let columns = polynomials
+            .par_iter()
+            .map(|poly| {
+                let matrix = EncodedMatrix::new(poly.evals_ref(), setup);
+                indices
+                    .iter()
+                    .map(|&index| matrix.open_column(index))
+                    .collect()
+            })
+            .collect();
+
+        LigeroProof {
+            proximity_row,
+            evaluation_row,
+            columns,
+        }
+    }
+
+    /// Verifies that `opening` is the evaluation at `point` of the linear combination of the
+    /// committed polynomials with the given coefficients.
+    fn verify_opening(
+        proof: &LigeroProof<F>,
+        setup: &LigeroSetup,
+        transcript: &mut ProofTranscript,
+        point: &[F],
+        opening: &F,
+        commitments: &[&LigeroCommitment],
+        coeffs: &[F],
+    ) -> Result<(), ProofVerifyError> {
+        transcript.append_message(<Self as CommitmentScheme<ProofTranscript>>::protocol_name());
+
+        let row_vars = setup.row_vars(point.len());
+        let (num_rows, row_len) = (row_vars.pow2(), setup.row_len());
+        if proof.proximity_row.len() != row_len || proof.evaluation_row.len() != row_len {
+            return Err(ProofVerifyError::InvalidInputLength(
+                row_len,
+                proof.evaluation_row.len(),
+            ));
+        }
+        if proof.columns.len() != commitments.len() || coeffs.len() != commitments.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                commitments.len(),
+                proof.columns.len(),
+            ));
+        }
+        // The columns of a polynomial with fewer rows than the combination are padded with
+        // zeros, but all of its opened columns must have the same length
+        if proof.columns.iter().any(|columns| {
+            columns.len() != NUM_COLUMN_OPENINGS
+                || columns.iter().any(|column| {
+                    column.values.len() != columns[0].values.len()
+                        || column.values.len() > num_rows
+                        || column.path.siblings.len() != (row_len * BLOWUP).log_2()
+                })
+        }) {
+            return Err(ProofVerifyError::InternalError);
+        }
+
+        let gamma: Vec<F> = transcript.challenge_vector(num_rows);
+        let row_eq = EqPolynomial::evals(&point[..row_vars]);
+        transcript.append_scalars(&proof.proximity_row);
+        transcript.append_scalars(&proof.evaluation_row);
+        let indices = Self::column_indices(transcript, row_len * BLOWUP);
+
+        let encoded_proximity_row = reed_solomon::encode(&proof.proximity_row, BLOWUP);
+        let encoded_evaluation_row = reed_solomon::encode(&proof.evaluation_row, BLOWUP);
+
+        for (q, &index) in indices.iter().enumerate() {
+            let mut proximity_eval = F::zero();
+            let mut evaluation_eval = F::zero();
+            for ((commitment, columns), coeff) in
+                commitments.iter().zip(proof.columns.iter()).zip(coeffs)
+            {
+                let column = &columns[q];
+                if !column
+                    .path
+                    .verify(&commitment.root, index, hash_leaf(&column.values))
+                {
+                    return Err(ProofVerifyError::InternalError);
+                }
+                let gamma_dot: F = column.values.iter().zip(&gamma).map(|(x, g)| *x * g).sum();
+                let eq_dot: F = column.values.iter().zip(&row_eq).map(|(x, l)| *x * l).sum();
+                proximity_eval += gamma_dot * coeff;
+                evaluation_eval += eq_dot * coeff;
+            }
+            if proximity_eval != encoded_proximity_row[index]
+                || evaluation_eval != encoded_evaluation_row[index]
+            {
+                return Err(ProofVerifyError::InternalError);
+            }
+        }
+
+        // If `point` has fewer variables than a row has, only a prefix of the row is evaluated
+        let col_eq = EqPolynomial::evals(&point[row_vars..]);
+        let eval: F = proof
+            .evaluation_row
+            .iter()
+            .zip(col_eq.iter())
+            .map(|(v, r)| *v * r)
+            .sum();
+        if eval == *opening {
+            Ok(())
+        } else {
+            Err(ProofVerifyError::InternalError)
+        }
+    }
+}
+
+impl<F, ProofTranscript> CommitmentScheme<ProofTranscript> for Ligero<F, ProofTranscript>
+where
+    F: JoltField + PrimeField,
+    ProofTranscript: Transcript,
+{
+    type Field = F;
+    type Setup = LigeroSetup;
+    type Commitment = LigeroCommitment;
+    type Proof = LigeroProof<F>;
+    type BatchedProof = LigeroProof<F>;
+
+    const OPENS_EACH_COMMITMENT: bool = true;
+
+    fn setup(shapes: &[CommitShape]) -> Self::Setup {
+        let max_len = shapes
+            .iter()
+            .map(|shape| shape.input_length.next_power_of_two())
+            .max()
+            .unwrap_or(1);
+        LigeroSetup::new(max_len.log_2())
+    }
+
+    fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
+        Self::commit_evals(poly.evals_ref(), setup)
+    }
+
+    fn batch_commit(
+        evals: &[&[Self::Field]],
+        setup: &Self::Setup,
+        _batch_type: BatchType,
+    ) -> Vec<Self::Commitment> {
+        evals
+            .par_iter()
+            .map(|evals| Self::commit_evals(evals, setup))
+            .collect()
+    }
+
+    fn commit_slice(evals: &[Self::Field], setup: &Self::Setup) -> Self::Commitment {
+        Self::commit_evals(evals, setup)
+    }
+
+    fn prove(
+        setup: &Self::Setup,
+        poly: &DensePolynomial<Self::Field>,
+        opening_point: &[Self::Field],
+        transcript: &mut ProofTranscript,
+    ) -> Self::Proof {
+        Self::open(setup, &[poly], &[F::one()], opening_point, transcript)
+    }
+
+    fn batch_prove(
+        setup: &Self::Setup,
+        polynomials: &[&DensePolynomial<Self::Field>],
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        _batch_type: BatchType,
+        transcript: &mut ProofTranscript,
+    ) -> Self::BatchedProof {
+        transcript.append_scalars(openings);
+        let rho_powers: Vec<Self::Field> = transcript.challenge_scalar_powers(polynomials.len());
+        Self::open(setup, polynomials, &rho_powers, opening_point, transcript)
+    }
+
+    fn verify(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitment: &Self::Commitment,
+    ) -> Result<(), ProofVerifyError> {
+        Self::verify_opening(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            &[commitment],
+            &[F::one()],
+        )
+    }
+
+    fn batch_verify(
+        batch_proof: &Self::BatchedProof,
+        setup: &Self::Setup,
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        commitments: &[&Self::Commitment],
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        if commitments.len() != openings.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                commitments.len(),
+                openings.len(),
+            ));
+        }
+        transcript.append_scalars(openings);
+        let rho_powers: Vec<Self::Field> = transcript.challenge_scalar_powers(commitments.len());
+
+        let batched_opening = rho_powers
+            .iter()
+            .zip(openings.iter())
+            .map(|(coeff, opening)| *coeff * opening)
+            .sum();
+        Self::verify_opening(
+            batch_proof,
+            setup,
+            transcript,
+            opening_point,
+            &batched_opening,
+            commitments,
+            &rho_powers,
+        )
+    }
+
+    fn prove_linear_combination(
+        setup: &Self::Setup,
+        polynomials: &[&DensePolynomial<Self::Field>],
+        coeffs: &[Self::Field],
+        opening_point: &[Self::Field],
+        transcript: &mut ProofTranscript,
+    ) -> Self::Proof {
+        Self::open(setup, polynomials, coeffs, opening_point, transcript)
+    }
+
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        Self::verify_opening(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            commitments,
+            coeffs,
+        )
+    }
+
+    fn protocol_name() -> &'static [u8] {
+        b"Ligero"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::commitment::commitment_scheme::BatchType;
+    use crate::poly::opening_proof::{ProverOpeningAccumulator, VerifierOpeningAccumulator};
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+    use ark_std::{One, UniformRand};
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    type TestLigero = Ligero<Fr, KeccakTranscript>;
+
+    fn test_setup(num_vars: usize) -> LigeroSetup {
+        TestLigero::setup(&[CommitShape::new(num_vars.pow2(), BatchType::Big)])
+    }
+
+    #[test]
+    fn ligero_commit_prove_verify() {
+        for num_vars in [0, 1, 4, 5, 8] {
+            let mut rng = ChaCha20Rng::seed_from_u64(num_vars as u64);
+            let setup = test_setup(num_vars);
+            let poly = DensePolynomial::<Fr>::random(num_vars, &mut rng);
+            let point: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+            let eval = poly.evaluate(&point);
+            let commitment = TestLigero::commit(&poly, &setup);
+
+            let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+            let proof = TestLigero::prove(&setup, &poly, &point, &mut prover_transcript);
+
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            TestLigero::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &eval,
+                &commitment,
+            )
+            .unwrap();
+            let p_transcipt_squeeze: Fr = prover_transcript.challenge_scalar();
+            let v_transcipt_squeeze: Fr = verifier_transcript.challenge_scalar();
+            assert_eq!(p_transcipt_squeeze, v_transcipt_squeeze);
+
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            assert!(TestLigero::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &(eval + Fr::one()),
+                &commitment,
+            )
+            .is_err());
+
+            let other_poly = DensePolynomial::<Fr>::random(num_vars, &mut rng);
+            let other_commitment = TestLigero::commit(&other_poly, &setup);
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            assert!(TestLigero::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &eval,
+                &other_commitment,
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn ligero_rejects_tampered_column() {
+        const NUM_VARS: usize = 6;
+        let mut rng = ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let setup = test_setup(NUM_VARS);
+        let poly = DensePolynomial::<Fr>::random(NUM_VARS, &mut rng);
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+        let commitment = TestLigero::commit(&poly, &setup);
+
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let mut proof = TestLigero::prove(&setup, &poly, &point, &mut prover_transcript);
+        proof.columns[0][0].values[0] += Fr::one();
+
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        assert!(TestLigero::verify(
+            &proof,
+            &setup,
+            &mut verifier_transcript,
+            &point,
+            &eval,
+            &commitment,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ligero_batch_prove_verify() {
+        const NUM_VARS: usize = 6;
+        let mut rng = ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let setup = test_setup(NUM_VARS);
+
+        let polys: Vec<_> = (0..3)
+            .map(|_| DensePolynomial::<Fr>::random(NUM_VARS, &mut rng))
+            .collect();
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let openings: Vec<Fr> = polys.iter().map(|poly| poly.evaluate(&point)).collect();
+        let commitments = TestLigero::batch_commit_polys(&polys, &setup, BatchType::Big);
+        let polys_refs: Vec<_> = polys.iter().collect();
+        let commitments_refs: Vec<_> = commitments.iter().collect();
+
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let proof = TestLigero::batch_prove(
+            &setup,
+            &polys_refs,
+            &point,
+            &openings,
+            BatchType::Big,
+            &mut prover_transcript,
+        );
+
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        TestLigero::batch_verify(
+            &proof,
+            &setup,
+            &point,
+            &openings,
+            &commitments_refs,
+            &mut verifier_transcript,
+        )
+        .unwrap();
+
+        let mut bad_openings = openings.clone();
+        bad_openings[2] += Fr::one();
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        assert!(TestLigero::batch_verify(
+            &proof,
+            &setup,
+            &point,
+            &bad_openings,
+            &commitments_refs,
+            &mut verifier_transcript,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ligero_opening_accumulator() {
+        // Polynomials of different sizes, smaller and larger than a row, opened at different
+        // points and reduced to a single opening of their linear combination
+        const NUM_VARS: [usize; 3] = [2, 5, 8];
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let setup = test_setup(NUM_VARS[2]);
+
+        let polys: Vec<_> = NUM_VARS
+            .iter()
+            .map(|&num_vars| DensePolynomial::<Fr>::random(num_vars, &mut rng))
+            .collect();
+        let commitments = TestLigero::batch_commit_polys(&polys, &setup, BatchType::Big);
+        let points: Vec<Vec<Fr>> = NUM_VARS
+            .iter()
+            .map(|&num_vars| (0..num_vars).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        let claims: Vec<Fr> = polys
+            .iter()
+            .zip(points.iter())
+            .map(|(poly, point)| poly.evaluate(point))
+            .collect();
+
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let mut prover_accumulator =
+            ProverOpeningAccumulator::<Fr, KeccakTranscript>::for_scheme::<TestLigero>();
+        for ((poly, point), claim) in polys.iter().zip(points.iter()).zip(claims.iter()) {
+            prover_accumulator.append(
+                &[poly],
+                DensePolynomial::new(EqPolynomial::evals(point)),
+                point.clone(),
+                &[claim],
+                &mut prover_transcript,
+            );
+        }
+        let proof =
+            prover_accumulator.reduce_and_prove::<TestLigero>(&setup, &mut prover_transcript);
+
+        let verify = |claims: &[Fr]| {
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            let mut verifier_accumulator =
+                VerifierOpeningAccumulator::<Fr, TestLigero, KeccakTranscript>::new();
+            for ((commitment, point), claim) in
+                commitments.iter().zip(points.iter()).zip(claims.iter())
+            {
+                verifier_accumulator.append(
+                    &[commitment],
+                    point.clone(),
+                    &[claim],
+                    &mut verifier_transcript,
+                );
+            }
+            verifier_accumulator.reduce_and_verify(&setup, &proof, &mut verifier_transcript)
+        };
+        verify(&claims).unwrap();
+
+        let mut bad_claims = claims.clone();
+        bad_claims[0] += Fr::one();
+        assert!(verify(&bad_claims).is_err());
+    }
+}
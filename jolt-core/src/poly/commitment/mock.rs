@@ -11,14 +11,14 @@ use crate::{
     },
 };
 
-use super::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use super::commitment_scheme::{AdditivelyHomomorphic, BatchType, CommitShape, CommitmentScheme};
 
 #[derive(Clone)]
 pub struct MockCommitScheme<F: JoltField, ProofTranscript: Transcript> {
     _marker: PhantomData<(F, ProofTranscript)>,
 }
 
-#[derive(CanonicalSerialize, CanonicalDeserialize, Default, Debug, PartialEq)]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Default, Clone, Debug, PartialEq)]
 pub struct MockCommitment<F: JoltField> {
     poly: DensePolynomial<F>,
 }
@@ -94,27 +94,6 @@ where
         }
     }
 
-    fn combine_commitments(
-        commitments: &[&Self::Commitment],
-        coeffs: &[Self::Field],
-    ) -> Self::Commitment {
-        let max_size = commitments
-            .iter()
-            .map(|comm| comm.poly.len())
-            .max()
-            .unwrap();
-        let mut poly = DensePolynomial::new(vec![Self::Field::zero(); max_size]);
-        for (commitment, coeff) in commitments.iter().zip(coeffs.iter()) {
-            poly.Z
-                .iter_mut()
-                .zip(commitment.poly.Z.iter())
-                .for_each(|(a, b)| {
-                    *a += *coeff * b;
-                });
-        }
-        MockCommitment { poly }
-    }
-
     fn verify(
         proof: &Self::Proof,
         _setup: &Self::Setup,
@@ -146,7 +125,58 @@ where
         Ok(())
     }
 
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        let commitment = <Self as AdditivelyHomomorphic<ProofTranscript>>::combine_commitments(
+            commitments,
+            coeffs,
+        );
+        <Self as CommitmentScheme<ProofTranscript>>::verify(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            &commitment,
+        )
+    }
+
     fn protocol_name() -> &'static [u8] {
         b"mock_commit"
     }
 }
+
+impl<F, ProofTranscript> AdditivelyHomomorphic<ProofTranscript>
+    for MockCommitScheme<F, ProofTranscript>
+where
+    F: JoltField,
+    ProofTranscript: Transcript,
+{
+    fn combine_commitments(
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Self::Commitment {
+        let max_size = commitments
+            .iter()
+            .map(|comm| comm.poly.len())
+            .max()
+            .unwrap();
+        let mut poly = DensePolynomial::new(vec![Self::Field::zero(); max_size]);
+        for (commitment, coeff) in commitments.iter().zip(coeffs.iter()) {
+            poly.Z
+                .iter_mut()
+                .zip(commitment.poly.Z.iter())
+                .for_each(|(a, b)| {
+                    *a += *coeff * b;
+                });
+        }
+        MockCommitment { poly }
+    }
+}
@@ -1,9 +1,16 @@
+#[cfg(feature = "binius")]
 pub mod binius;
 pub mod commitment_scheme;
+pub mod dory;
+pub mod dynamic;
 pub mod hyperkzg;
 pub mod hyrax;
 pub mod kzg;
+pub mod ligero;
 pub mod pedersen;
+pub mod ptau;
+pub mod reed_solomon;
+pub mod srs;
 pub mod zeromorph;
 
 #[cfg(test)]
@@ -1,20 +1,40 @@
 use ark_ec::CurveGroup;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
 use ark_std::rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use sha3::digest::{ExtendableOutput, Update};
 use sha3::Shake256;
-use std::io::Read;
+use std::io::{Read, Write};
 
 use crate::msm::VariableBaseMSM;
 
-#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+/// Generators for Pedersen (and Hyrax) commitments, derived from a domain-separation label so
+/// that anyone can re-derive them.
+///
+/// The generators are the successive points of a try-and-increment hash-to-curve over a
+/// ChaCha20 stream, seeded with the first 32 bytes of `SHAKE256(label || G)` (`G` being the
+/// curve's standard generator, compressed): a base field element `x` and a sign are drawn from
+/// the stream, and if `x` is the abscissa of a curve point, the point of that sign, times the
+/// cofactor, is the next generator; otherwise another `x` is drawn. This is `UniformRand` for
+/// the curve's points. No one knows the discrete logarithms of the generators with respect to
+/// one another, and the first `n` generators for a label do not depend on how many are derived.
+///
+/// Only the label and the number of generators are serialized: a verifier key holding the
+/// generators thus does not ship them, and the generators are derived again on
+/// deserialization.
+#[derive(Clone)]
 pub struct PedersenGenerators<G: CurveGroup> {
     pub generators: Vec<G>,
+    label: Vec<u8>,
 }
 
 impl<G: CurveGroup> PedersenGenerators<G> {
-    #[tracing::instrument(skip_all, name = "PedersenGenerators::new")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "PedersenGenerators::new")
+    )]
     pub fn new(len: usize, label: &[u8]) -> Self {
         let mut shake = Shake256::default();
         shake.update(label);
@@ -32,7 +52,10 @@ impl<G: CurveGroup> PedersenGenerators<G> {
             generators.push(G::rand(&mut rng));
         }
 
-        Self { generators }
+        Self {
+            generators,
+            label: label.to_vec(),
+        }
     }
 
     pub fn clone_n(&self, n: usize) -> PedersenGenerators<G> {
@@ -45,17 +68,54 @@ impl<G: CurveGroup> PedersenGenerators<G> {
         let slice = &self.generators[..n];
         PedersenGenerators {
             generators: slice.into(),
+            label: self.label.clone(),
         }
     }
 }
 
+impl<G: CurveGroup> CanonicalSerialize for PedersenGenerators<G> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.label.serialize_with_mode(&mut writer, compress)?;
+        (self.generators.len() as u64).serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.label.serialized_size(compress) + 0u64.serialized_size(compress)
+    }
+}
+
+impl<G: CurveGroup> Valid for PedersenGenerators<G> {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl<G: CurveGroup> CanonicalDeserialize for PedersenGenerators<G> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let label = Vec::<u8>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let len = u64::deserialize_with_mode(reader, compress, validate)?;
+        Ok(Self::new(len as usize, &label))
+    }
+}
+
 pub trait PedersenCommitment<G: CurveGroup>: Sized {
     fn commit(&self, gens: &PedersenGenerators<G>) -> G;
     fn commit_vector(inputs: &[Self], bases: &[G::Affine]) -> G;
 }
 
 impl<G: CurveGroup> PedersenCommitment<G> for G::ScalarField {
-    #[tracing::instrument(skip_all, name = "PedersenCommitment::commit")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "PedersenCommitment::commit")
+    )]
     fn commit(&self, gens: &PedersenGenerators<G>) -> G {
         assert_eq!(gens.generators.len(), 1);
         gens.generators[0] * self
@@ -66,3 +126,30 @@ impl<G: CurveGroup> PedersenCommitment<G> for G::ScalarField {
         VariableBaseMSM::msm(bases, inputs).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::G1Projective;
+
+    #[test]
+    fn generators_rederived_from_label() {
+        let generators = PedersenGenerators::<G1Projective>::new(16, b"test-label");
+        assert_eq!(
+            generators.clone_n(5).generators,
+            PedersenGenerators::<G1Projective>::new(5, b"test-label").generators
+        );
+        assert_ne!(
+            generators.generators,
+            PedersenGenerators::<G1Projective>::new(16, b"other-label").generators
+        );
+
+        let mut bytes = vec![];
+        generators.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), generators.compressed_size());
+        assert!(bytes.len() < G1Projective::default().compressed_size());
+        let decoded =
+            PedersenGenerators::<G1Projective>::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded.generators, generators.generators);
+    }
+}
@@ -0,0 +1,294 @@
+//! Parsing of the `.ptau` files produced by the snarkjs and perpetual powers-of-tau ceremonies,
+//! so that the KZG-based schemes can be instantiated from a real trusted setup.
+//!
+//! A `.ptau` file starts with the magic bytes `ptau`, a `u32` version and a `u32` number of
+//! sections, each of which is a `u32` id, a `u64` size and its data. The header section holds
+//! the size `n8` and value of the base field modulus, and the `power` of the ceremony. The
+//! `tauG1` section holds `[tau^i]_1` for `i < 2^(power + 1) - 1`, and the `tauG2` section holds
+//! `[tau^i]_2` for `i < 2^power`. All integers are little-endian, and the points are affine with
+//! their coordinates in little-endian Montgomery form.
+use std::io::{Read, Seek, SeekFrom};
+
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInt, PrimeField};
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"ptau";
+const HEADER_SECTION: u32 = 1;
+const TAU_G1_SECTION: u32 = 2;
+const TAU_G2_SECTION: u32 = 3;
+/// The size in bytes of a BN254 base field element.
+const N8: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum PtauError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a .ptau file")]
+    InvalidMagic,
+    #[error("Missing section {0}")]
+    MissingSection(u32),
+    #[error("Section {0} has length {1}, expected at least {2}")]
+    InvalidSectionLength(u32, u64, u64),
+    #[error("Unsupported curve: only BN254 ceremonies are supported")]
+    UnsupportedCurve,
+    #[error("The ceremony has {0} powers, but {1} were requested")]
+    InsufficientPowers(usize, usize),
+    #[error("Invalid curve point at index {0}")]
+    InvalidPoint(usize),
+}
+
+/// Reads `[tau^i]_1` for `i < num_g1_powers` and `[tau^i]_2` for `i < num_g2_powers` from a
+/// BN254 `.ptau` file. Only the requested prefixes of the sections are read.
+pub fn read_powers<R: Read + Seek>(
+    reader: &mut R,
+    num_g1_powers: usize,
+    num_g2_powers: usize,
+) -> Result<(Vec<G1Affine>, Vec<G2Affine>), PtauError> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(PtauError::InvalidMagic);
+    }
+    let _version = read_u32(reader)?;
+    let num_sections = read_u32(reader)?;
+
+    // (position, size) of each section of interest
+    let mut sections = [None; 4];
+    for _ in 0..num_sections {
+        let id = read_u32(reader)?;
+        let size = read_u64(reader)?;
+        let position = reader.stream_position()?;
+        if let Some(section) = sections.get_mut(id as usize) {
+            *section = Some((position, size));
+        }
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    let section = |id: u32| sections[id as usize].ok_or(PtauError::MissingSection(id));
+
+    let (header_position, _) = section(HEADER_SECTION)?;
+    reader.seek(SeekFrom::Start(header_position))?;
+    let n8 = read_u32(reader)? as usize;
+    if n8 != N8 {
+        return Err(PtauError::UnsupportedCurve);
+    }
+    let mut modulus = [0u8; N8];
+    reader.read_exact(&mut modulus)?;
+    if BigInt::<4>::new(to_limbs(&modulus)) != Fq::MODULUS {
+        return Err(PtauError::UnsupportedCurve);
+    }
+    let power = read_u32(reader)?;
+
+    let max_g1_powers = (1usize << (power + 1)) - 1;
+    let max_g2_powers = 1usize << power;
+    if num_g1_powers > max_g1_powers {
+        return Err(PtauError::InsufficientPowers(max_g1_powers, num_g1_powers));
+    }
+    if num_g2_powers > max_g2_powers {
+        return Err(PtauError::InsufficientPowers(max_g2_powers, num_g2_powers));
+    }
+
+    let g1_powers = read_section(
+        reader,
+        TAU_G1_SECTION,
+        section(TAU_G1_SECTION)?,
+        num_g1_powers,
+        2,
+        |buf| {
+            let point = G1Affine::new_unchecked(read_fq(&buf[..N8])?, read_fq(&buf[N8..])?);
+            (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve())
+                .then_some(point)
+        },
+    )?;
+    let g2_powers = read_section(
+        reader,
+        TAU_G2_SECTION,
+        section(TAU_G2_SECTION)?,
+        num_g2_powers,
+        4,
+        |buf| {
+            let x = Fq2::new(read_fq(&buf[..N8])?, read_fq(&buf[N8..2 * N8])?);
+            let y = Fq2::new(read_fq(&buf[2 * N8..3 * N8])?, read_fq(&buf[3 * N8..])?);
+            let point = G2Affine::new_unchecked(x, y);
+            (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve())
+                .then_some(point)
+        },
+    )?;
+    Ok((g1_powers, g2_powers))
+}
+
+/// Reads the first `num_points` points of a section, each made of `num_coords` base field
+/// elements. `parse` returns `None` if the point is invalid.
+fn read_section<R, A, F>(
+    reader: &mut R,
+    id: u32,
+    (position, size): (u64, u64),
+    num_points: usize,
+    num_coords: usize,
+    parse: F,
+) -> Result<Vec<A>, PtauError>
+where
+    R: Read + Seek,
+    A: AffineRepr,
+    F: Fn(&[u8]) -> Option<A>,
+{
+    let point_size = num_coords * N8;
+    let len = (num_points * point_size) as u64;
+    if size < len {
+        return Err(PtauError::InvalidSectionLength(id, size, len));
+    }
+    reader.seek(SeekFrom::Start(position))?;
+    let mut buf = vec![0u8; point_size];
+    (0..num_points)
+        .map(|i| {
+            reader.read_exact(&mut buf)?;
+            // The point at infinity is encoded as all zeros
+            if buf.iter().all(|byte| *byte == 0) {
+                return Ok(A::zero());
+            }
+            parse(&buf).ok_or(PtauError::InvalidPoint(i))
+        })
+        .collect()
+}
+
+fn to_limbs(bytes: &[u8]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+/// Reads a base field element from its little-endian Montgomery form.
+fn read_fq(bytes: &[u8]) -> Option<Fq> {
+    let montgomery = BigInt::<4>::new(to_limbs(bytes));
+    (montgomery < Fq::MODULUS).then(|| Fq::new_unchecked(montgomery))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, PtauError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, PtauError> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ark_ec::CurveGroup;
+    use ark_std::{test_rng, UniformRand};
+    use std::io::Cursor;
+
+    fn write_fq(buf: &mut Vec<u8>, x: &Fq) {
+        for limb in x.0 .0.iter() {
+            buf.extend_from_slice(&limb.to_le_bytes());
+        }
+    }
+
+    fn write_section(buf: &mut Vec<u8>, id: u32, data: &[u8]) {
+        buf.extend_from_slice(&id.to_le_bytes());
+        buf.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        buf.extend_from_slice(data);
+    }
+
+    /// Serializes the given powers in the `.ptau` format, padding the sections to `2^power`.
+    pub(crate) fn write_ptau(
+        power: u32,
+        g1_powers: &[G1Affine],
+        g2_powers: &[G2Affine],
+    ) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend_from_slice(&(N8 as u32).to_le_bytes());
+        for limb in Fq::MODULUS.0.iter() {
+            header.extend_from_slice(&limb.to_le_bytes());
+        }
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&power.to_le_bytes());
+
+        let mut tau_g1 = vec![];
+        for point in g1_powers {
+            write_fq(&mut tau_g1, &point.x);
+            write_fq(&mut tau_g1, &point.y);
+        }
+        tau_g1.resize(((1 << (power + 1)) - 1) * 2 * N8, 0);
+        let mut tau_g2 = vec![];
+        for point in g2_powers {
+            write_fq(&mut tau_g2, &point.x.c0);
+            write_fq(&mut tau_g2, &point.x.c1);
+            write_fq(&mut tau_g2, &point.y.c0);
+            write_fq(&mut tau_g2, &point.y.c1);
+        }
+        tau_g2.resize((1 << power) * 4 * N8, 0);
+
+        let mut buf = MAGIC.to_vec();
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.extend_from_slice(&3u32.to_le_bytes());
+        // Sections need not be in order
+        write_section(&mut buf, TAU_G2_SECTION, &tau_g2);
+        write_section(&mut buf, HEADER_SECTION, &header);
+        write_section(&mut buf, TAU_G1_SECTION, &tau_g1);
+        buf
+    }
+
+    #[test]
+    fn read_written_powers() {
+        let mut rng = test_rng();
+        let tau = ark_bn254::Fr::rand(&mut rng);
+        let powers: Vec<ark_bn254::Fr> = std::iter::successors(Some(tau), |x| Some(*x * tau))
+            .take(8)
+            .collect();
+        let g1_powers: Vec<G1Affine> = powers
+            .iter()
+            .map(|x| (G1Affine::generator() * x).into_affine())
+            .collect();
+        let g2_powers: Vec<G2Affine> = powers
+            .iter()
+            .map(|x| (G2Affine::generator() * x).into_affine())
+            .collect();
+        let ptau = write_ptau(3, &g1_powers, &g2_powers);
+
+        let (g1, g2) = read_powers(&mut Cursor::new(&ptau), 8, 5).unwrap();
+        assert_eq!(g1, g1_powers);
+        assert_eq!(g2, g2_powers[..5]);
+
+        // Beyond the written powers, the G1 section is padded with points at infinity
+        let (g1, _) = read_powers(&mut Cursor::new(&ptau), 15, 0).unwrap();
+        assert!(g1[8..].iter().all(|point| point.is_zero()));
+
+        assert!(matches!(
+            read_powers(&mut Cursor::new(&ptau), 16, 0),
+            Err(PtauError::InsufficientPowers(15, 16))
+        ));
+        assert!(matches!(
+            read_powers(&mut Cursor::new(&ptau), 0, 9),
+            Err(PtauError::InsufficientPowers(8, 9))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(
+            read_powers(&mut Cursor::new(b"zkey\x01\x00\x00\x00"), 1, 1),
+            Err(PtauError::InvalidMagic)
+        ));
+
+        let mut rng = test_rng();
+        let g1_powers = vec![G1Affine::rand(&mut rng)];
+        let g2_powers = vec![G2Affine::rand(&mut rng)];
+        let mut ptau = write_ptau(1, &g1_powers, &g2_powers);
+        // Corrupt the y coordinate of the first G1 point, which follows the magic, version,
+        // section count, G2 section and G1 section prelude
+        let offset = 12 + (12 + 2 * 4 * N8) + (12 + 4 + N8 + 8) + 12 + N8;
+        ptau[offset] ^= 1;
+        assert!(matches!(
+            read_powers(&mut Cursor::new(&ptau), 1, 1),
+            Err(PtauError::InvalidPoint(0))
+        ));
+    }
+}
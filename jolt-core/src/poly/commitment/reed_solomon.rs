@@ -0,0 +1,96 @@
+//! Reed–Solomon encoding over the multiplicative subgroups of an FFT-friendly field.
+//!
+//! A message of length `n` is read as the coefficients of a univariate polynomial of degree
+//! less than `n`, and its codeword is the evaluation of that polynomial over the subgroup of
+//! size `n * blowup`. The code is linear, so the encoding of a linear combination of messages
+//! is the same linear combination of their codewords.
+use ark_ff::PrimeField;
+
+/// Encodes `message` into a codeword of length `message.len() * blowup`.
+pub fn encode<F: PrimeField>(message: &[F], blowup: usize) -> Vec<F> {
+    assert!(message.len().is_power_of_two());
+    assert!(blowup.is_power_of_two());
+    let codeword_len = message.len() * blowup;
+    let omega = F::get_root_of_unity(codeword_len as u64)
+        .expect("scalar field has no subgroup of the given size");
+
+    let mut codeword = message.to_vec();
+    codeword.resize(codeword_len, F::zero());
+    fft(&mut codeword, omega);
+    codeword
+}
+
+/// In-place radix-2 DFT of `values` with respect to `omega`, a primitive root of unity of order
+/// `values.len()`.
+fn fft<F: PrimeField>(values: &mut [F], omega: F) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let mut half_size = 1;
+    while half_size < n {
+        let omega_m = omega.pow([(n / (2 * half_size)) as u64]);
+        let twiddles: Vec<F> = std::iter::successors(Some(F::one()), |w| Some(*w * omega_m))
+            .take(half_size)
+            .collect();
+        values.chunks_mut(2 * half_size).for_each(|chunk| {
+            let (lo, hi) = chunk.split_at_mut(half_size);
+            for ((x, y), w) in lo.iter_mut().zip(hi.iter_mut()).zip(twiddles.iter()) {
+                let t = *y * w;
+                *y = *x - t;
+                *x += t;
+            }
+        });
+        half_size *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::{FftField, Field};
+    use ark_std::{test_rng, UniformRand, Zero};
+
+    #[test]
+    fn encode_matches_naive_evaluation() {
+        let mut rng = test_rng();
+        let message: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let codeword = encode(&message, 4);
+        assert_eq!(codeword.len(), 32);
+
+        let omega = Fr::get_root_of_unity(32).unwrap();
+        for (i, eval) in codeword.iter().enumerate() {
+            let x = omega.pow([i as u64]);
+            let expected = message
+                .iter()
+                .rev()
+                .fold(Fr::zero(), |acc, coeff| acc * x + coeff);
+            assert_eq!(*eval, expected);
+        }
+    }
+
+    #[test]
+    fn encode_is_linear() {
+        let mut rng = test_rng();
+        let a: Vec<Fr> = (0..16).map(|_| Fr::rand(&mut rng)).collect();
+        let b: Vec<Fr> = (0..16).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let combined: Vec<Fr> = a.iter().zip(b.iter()).map(|(a, b)| *a * r + b).collect();
+
+        let expected: Vec<Fr> = encode(&a, 2)
+            .iter()
+            .zip(encode(&b, 2).iter())
+            .map(|(a, b)| *a * r + b)
+            .collect();
+        assert_eq!(encode(&combined, 2), expected);
+    }
+}
@@ -0,0 +1,355 @@
+//! A multi-party powers-of-tau ceremony, so that the SRS of the KZG-based schemes can come from a
+//! setup whose trapdoor no single party knows, rather than from a seeded RNG.
+//!
+//! The ceremony starts from [`PowersOfTau::new`], in which `τ = 1`. Each participant calls
+//! [`contribute`] on the latest powers: it samples a secret `x`, multiplies `τ` by `x`, and
+//! proves knowledge of `x` in the manner of Bowe, Gabizon and Miers (ePrint 2017/1050). As long
+//! as one participant discards their `x`, the final `τ` is unknown. Anyone can then check the
+//! whole ceremony with [`verify_chain`] and build the SRS from its output:
+//!
+//! ```ignore
+//! let mut contributions = vec![];
+//! let mut powers = PowersOfTau::<Bn254>::new(max_degree, 2);
+//! for participant in participants {
+//!     let contribution = contribute(&powers, &mut participant.rng);
+//!     powers = contribution.powers.clone();
+//!     contributions.push(contribution);
+//! }
+//! let srs = verify_chain(max_degree, 2, &contributions, &mut rng)?.clone().into_srs();
+//! let setup = HyperKZGSRS::from_srs(srs);
+//! ```
+use crate::utils::par::{self, prelude::*};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{One, UniformRand, Zero};
+use rand_core::{CryptoRng, RngCore};
+use thiserror::Error;
+
+use super::kzg::SRS;
+use super::pedersen::PedersenGenerators;
+use crate::field::JoltField;
+use crate::msm::VariableBaseMSM;
+use crate::utils::transcript::{KeccakTranscript, Transcript};
+
+#[derive(Debug, Error)]
+pub enum SrsError {
+    #[error("The ceremony has no contributions")]
+    NoContributions,
+    #[error("The contribution has {0} G1 and {1} G2 powers, expected {2} and {3}")]
+    InvalidLength(usize, usize, usize, usize),
+    #[error("The contribution's powers are not successive powers of the same τ")]
+    MalformedPowers,
+    #[error("The contribution's proof of knowledge is invalid")]
+    InvalidProof,
+    #[error("Contribution {0} is invalid: {1}")]
+    InvalidContribution(usize, Box<SrsError>),
+}
+
+/// `[τ^i]_1` for `i <= num_g1_powers` and `[τ^i]_2` for `i <= num_g2_powers`, over the curves'
+/// standard generators: the state of the ceremony after each contribution.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PowersOfTau<P: Pairing> {
+    pub g1_powers: Vec<P::G1Affine>,
+    pub g2_powers: Vec<P::G2Affine>,
+}
+
+impl<P: Pairing> PowersOfTau<P> {
+    /// The starting point of a ceremony, in which `τ = 1`.
+    pub fn new(num_g1_powers: usize, num_g2_powers: usize) -> Self {
+        Self {
+            g1_powers: vec![P::G1Affine::generator(); num_g1_powers + 1],
+            g2_powers: vec![P::G2Affine::generator(); num_g2_powers + 1],
+        }
+    }
+
+    pub fn into_srs(self) -> SRS<P> {
+        SRS::from_powers(self.g1_powers, self.g2_powers)
+    }
+
+    /// Checks that the powers are those of a single nonzero `τ`, i.e. that `[τ^i]_1` and
+    /// `[τ^i]_2` start from the generators, that `e([τ^i]_1, [τ]_2) = e([τ^{i+1}]_1, [1]_2)`,
+    /// and that `e([τ]_1, [τ^i]_2) = e([1]_1, [τ^{i+1}]_2)`. The latter two are each checked
+    /// for all `i` at once, on a random linear combination drawn from `rng`.
+    fn check_powers<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), SrsError> {
+        let g1 = P::G1Affine::generator();
+        let g2 = P::G2Affine::generator();
+        if self.g1_powers.len() < 2
+            || self.g2_powers.len() < 2
+            || self.g1_powers[0] != g1
+            || self.g2_powers[0] != g2
+            || self.g1_powers[1].is_zero()
+        {
+            return Err(SrsError::MalformedPowers);
+        }
+
+        let rho = P::ScalarField::rand(rng);
+        let max_len = self.g1_powers.len().max(self.g2_powers.len());
+        let rho_powers: Vec<P::ScalarField> =
+            std::iter::successors(Some(P::ScalarField::one()), |power| Some(*power * rho))
+                .take(max_len - 1)
+                .collect();
+        let combine_g1 = |bases: &[P::G1Affine]| {
+            <P::G1 as VariableBaseMSM>::msm(bases, &rho_powers[..bases.len()]).unwrap()
+        };
+        let combine_g2 = |bases: &[P::G2Affine]| {
+            <P::G2 as VariableBaseMSM>::msm(bases, &rho_powers[..bases.len()]).unwrap()
+        };
+        let (g1_lower, g1_upper) = par::join(
+            || combine_g1(&self.g1_powers[..self.g1_powers.len() - 1]),
+            || combine_g1(&self.g1_powers[1..]),
+        );
+        let (g2_lower, g2_upper) = par::join(
+            || combine_g2(&self.g2_powers[..self.g2_powers.len() - 1]),
+            || combine_g2(&self.g2_powers[1..]),
+        );
+
+        let g1_check = P::multi_pairing(
+            [g1_lower, -g1_upper],
+            [self.g2_powers[1].into_group(), g2.into_group()],
+        );
+        let g2_check = P::multi_pairing(
+            [self.g1_powers[1].into_group(), -g1.into_group()],
+            [g2_lower, g2_upper],
+        );
+        if g1_check.is_zero() && g2_check.is_zero() {
+            Ok(())
+        } else {
+            Err(SrsError::MalformedPowers)
+        }
+    }
+}
+
+/// A proof that a contribution multiplied `τ` by an `x` known to the contributor: `[s]_1` and
+/// `[s x]_1` for a random `s`, and `[x r]_2` for a point `[r]_2` hashed from the previous
+/// powers and `[s]_1, [s x]_1`.
+#[derive(Clone, Copy, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ContributionProof<P: Pairing> {
+    pub s_g1: P::G1Affine,
+    pub s_x_g1: P::G1Affine,
+    pub x_r_g2: P::G2Affine,
+}
+
+/// The powers after a contribution, and the proof that they were obtained from the previous
+/// ones.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution<P: Pairing> {
+    pub powers: PowersOfTau<P>,
+    pub proof: ContributionProof<P>,
+}
+
+/// `[r]_2` for the proof of knowledge, whose discrete logarithm no one knows: the generator
+/// derived (see [`PedersenGenerators`]) from a hash of `[τ]_1, [τ]_2` before the contribution
+/// and `[s]_1, [s x]_1`.
+fn proof_of_knowledge_base<P: Pairing>(
+    previous: &PowersOfTau<P>,
+    s_g1: &P::G1Affine,
+    s_x_g1: &P::G1Affine,
+) -> P::G2
+where
+    P::ScalarField: JoltField,
+{
+    let mut transcript = KeccakTranscript::new(b"Jolt powers of tau");
+    transcript.append_point(&previous.g1_powers[1].into_group());
+    transcript.append_point(&previous.g2_powers[1].into_group());
+    transcript.append_point(&s_g1.into_group());
+    transcript.append_point(&s_x_g1.into_group());
+    let challenge: P::ScalarField = transcript.challenge_scalar();
+    let mut label = vec![];
+    challenge.serialize_compressed(&mut label).unwrap();
+    PedersenGenerators::<P::G2>::new(1, &label).generators[0]
+}
+
+/// Contributes a secret `x`, drawn from `rng`, to the ceremony: multiplies each `[τ^i]` in
+/// `powers` by `x^i`. `x` is not retained.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "srs::contribute")
+)]
+pub fn contribute<P: Pairing, R: RngCore + CryptoRng>(
+    powers: &PowersOfTau<P>,
+    rng: &mut R,
+) -> Contribution<P>
+where
+    P::ScalarField: JoltField,
+{
+    let mut x = P::ScalarField::rand(rng);
+    while x.is_zero() {
+        x = P::ScalarField::rand(rng);
+    }
+    let mut s = P::ScalarField::rand(rng);
+    while s.is_zero() {
+        s = P::ScalarField::rand(rng);
+    }
+
+    let max_len = powers.g1_powers.len().max(powers.g2_powers.len());
+    let x_powers: Vec<P::ScalarField> =
+        std::iter::successors(Some(P::ScalarField::one()), |power| Some(*power * x))
+            .take(max_len)
+            .collect();
+    let (g1_powers, g2_powers) = par::join(
+        || {
+            let g1_powers: Vec<P::G1> = powers
+                .g1_powers
+                .par_iter()
+                .zip(x_powers.par_iter())
+                .map(|(point, x_power)| *point * *x_power)
+                .collect();
+            P::G1::normalize_batch(&g1_powers)
+        },
+        || {
+            let g2_powers: Vec<P::G2> = powers
+                .g2_powers
+                .par_iter()
+                .zip(x_powers.par_iter())
+                .map(|(point, x_power)| *point * *x_power)
+                .collect();
+            P::G2::normalize_batch(&g2_powers)
+        },
+    );
+
+    let s_g1 = (P::G1Affine::generator() * s).into_affine();
+    let s_x_g1 = (s_g1 * x).into_affine();
+    let r_g2 = proof_of_knowledge_base(powers, &s_g1, &s_x_g1);
+    Contribution {
+        powers: PowersOfTau {
+            g1_powers,
+            g2_powers,
+        },
+        proof: ContributionProof {
+            s_g1,
+            s_x_g1,
+            x_r_g2: (r_g2 * x).into_affine(),
+        },
+    }
+}
+
+/// Verifies that `contribution` was obtained from `previous` by a contributor who knew the `x`
+/// they multiplied `τ` by, and that its powers are well formed. `rng` must be unpredictable to
+/// the contributor.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, name = "srs::verify_contribution")
+)]
+pub fn verify_contribution<P: Pairing, R: RngCore + CryptoRng>(
+    previous: &PowersOfTau<P>,
+    contribution: &Contribution<P>,
+    rng: &mut R,
+) -> Result<(), SrsError>
+where
+    P::ScalarField: JoltField,
+{
+    let powers = &contribution.powers;
+    if powers.g1_powers.len() != previous.g1_powers.len()
+        || powers.g2_powers.len() != previous.g2_powers.len()
+    {
+        return Err(SrsError::InvalidLength(
+            powers.g1_powers.len(),
+            powers.g2_powers.len(),
+            previous.g1_powers.len(),
+            previous.g2_powers.len(),
+        ));
+    }
+    powers.check_powers(rng)?;
+
+    let ContributionProof {
+        s_g1,
+        s_x_g1,
+        x_r_g2,
+    } = contribution.proof;
+    if s_g1.is_zero() || s_x_g1.is_zero() {
+        return Err(SrsError::InvalidProof);
+    }
+    let r_g2 = proof_of_knowledge_base(previous, &s_g1, &s_x_g1);
+    // The contributor knows `x` such that `[s x]_1 = x [s]_1` and `[x r]_2 = x [r]_2`...
+    let knowledge_check = P::multi_pairing(
+        [s_g1.into_group(), -s_x_g1.into_group()],
+        [x_r_g2.into_group(), r_g2],
+    );
+    // ...and `[τ']_1 = x [τ]_1`.
+    let update_check = P::multi_pairing(
+        [
+            powers.g1_powers[1].into_group(),
+            -previous.g1_powers[1].into_group(),
+        ],
+        [r_g2, x_r_g2.into_group()],
+    );
+    if knowledge_check.is_zero() && update_check.is_zero() {
+        Ok(())
+    } else {
+        Err(SrsError::InvalidProof)
+    }
+}
+
+/// Verifies a whole ceremony for `num_g1_powers` G1 and `num_g2_powers` G2 powers, starting from
+/// [`PowersOfTau::new`], and returns its final powers.
+pub fn verify_chain<'a, P: Pairing, R: RngCore + CryptoRng>(
+    num_g1_powers: usize,
+    num_g2_powers: usize,
+    contributions: &'a [Contribution<P>],
+    rng: &mut R,
+) -> Result<&'a PowersOfTau<P>, SrsError>
+where
+    P::ScalarField: JoltField,
+{
+    let initial = PowersOfTau::new(num_g1_powers, num_g2_powers);
+    let mut previous = &initial;
+    for (i, contribution) in contributions.iter().enumerate() {
+        verify_contribution(previous, contribution, rng)
+            .map_err(|err| SrsError::InvalidContribution(i, Box::new(err)))?;
+        previous = &contribution.powers;
+    }
+    contributions
+        .last()
+        .map(|contribution| &contribution.powers)
+        .ok_or(SrsError::NoContributions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+    use ark_std::test_rng;
+
+    #[test]
+    fn ceremony() {
+        let mut rng = test_rng();
+        let mut contributions: Vec<Contribution<Bn254>> = vec![];
+        let mut powers = PowersOfTau::new(8, 2);
+        for _ in 0..3 {
+            let contribution = contribute(&powers, &mut rng);
+            powers = contribution.powers.clone();
+            contributions.push(contribution);
+        }
+        assert_eq!(
+            verify_chain(8, 2, &contributions, &mut rng).unwrap(),
+            &powers
+        );
+        assert!(matches!(
+            verify_chain::<Bn254, _>(8, 2, &[], &mut rng),
+            Err(SrsError::NoContributions)
+        ));
+
+        // A contribution skipped in the chain
+        assert!(matches!(
+            verify_chain(8, 2, &contributions[1..], &mut rng),
+            Err(SrsError::InvalidContribution(0, _))
+        ));
+
+        // Powers that are not of a single τ
+        let mut tampered = contributions.clone();
+        tampered[1].powers.g1_powers[5] =
+            (tampered[1].powers.g1_powers[5] * Fr::from(2u64)).into_affine();
+        assert!(matches!(
+            verify_chain(8, 2, &tampered, &mut rng),
+            Err(SrsError::InvalidContribution(1, err)) if matches!(*err, SrsError::MalformedPowers)
+        ));
+
+        // A proof of knowledge of another contribution
+        let mut tampered = contributions.clone();
+        tampered[2].proof = tampered[1].proof;
+        assert!(matches!(
+            verify_chain(8, 2, &tampered, &mut rng),
+            Err(SrsError::InvalidContribution(2, err)) if matches!(*err, SrsError::InvalidProof)
+        ));
+    }
+}
@@ -10,25 +10,61 @@ use crate::utils::mul_0_1_optimized;
 use crate::utils::thread::unsafe_allocate_zero_vec;
 use crate::utils::{
     errors::ProofVerifyError,
+    field_encoding::{append_point, append_points, ToFieldElements},
     transcript::{AppendToTranscript, Transcript},
 };
+use crate::verifier::zeromorph::{
+    eval_and_quotient_scalars, verify_zeromorph_opening, zeromorph_pairing_checks,
+};
+use ark_bn254::Bn254;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
-use ark_ff::{batch_inversion, Field};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_ff::Field;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
 use ark_std::{One, Zero};
 use itertools::izip;
 use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
 use rand_core::{CryptoRng, RngCore};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
 use std::sync::Arc;
-use tracing::trace_span;
+use thiserror::Error;
 
-use rayon::prelude::*;
+use crate::utils::par::{self, prelude::*};
 
 use super::{
-    commitment_scheme::{BatchType, CommitShape, CommitmentScheme},
-    kzg::{KZGProverKey, KZGVerifierKey, UnivariateKZG, SRS},
+    commitment_scheme::{
+        AdditivelyHomomorphic, AggregatableCommitmentScheme, BatchType, CommitShape,
+        CommitmentScheme,
+    },
+    kzg::{
+        self, CommitMode, KZGProverKey, KZGVerifierKey, PairingAccumulator, PreparedKZGVerifierKey,
+        UnivariateKZG, SRS,
+    },
+    ptau::PtauError,
 };
 
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum ZeromorphError {
+    /// returned when trimming the SRS for polynomials with no coefficients
+    #[error("ZeroDegree")]
+    ZeroDegree,
+    /// returned when no commit shapes are given to size the keys for
+    #[error("NoShapes")]
+    NoShapes,
+    /// returned when the SRS has fewer G1 powers (first field) than required (second field)
+    #[error("InsufficientG1Powers: SRS has {0}, {1} required")]
+    InsufficientG1Powers(usize, usize),
+    /// returned when the SRS has fewer G2 powers (first field) than required (second field)
+    #[error("InsufficientG2Powers: SRS has {0}, {1} required")]
+    InsufficientG2Powers(usize, usize),
+    /// returned when an SRS file cannot be read
+    #[error("InvalidSrsFile: {0}")]
+    InvalidSrsFile(String),
+}
+
 pub struct ZeromorphSRS<P: Pairing>(Arc<SRS<P>>);
 
 impl<P: Pairing> ZeromorphSRS<P> {
@@ -36,37 +72,208 @@ impl<P: Pairing> ZeromorphSRS<P> {
         Self(Arc::new(SRS::setup(rng, max_degree, max_degree)))
     }
 
+    /// Wraps an SRS, checking that it has at least as many G2 powers as G1 powers, which the
+    /// shifts `[tau^(N_max - 2^k)]_2` of the verifier key index into.
+    pub fn from_srs(srs: SRS<P>) -> Result<Self, ZeromorphError> {
+        if srs.g2_powers.len() < srs.g1_powers.len() {
+            return Err(ZeromorphError::InsufficientG2Powers(
+                srs.g2_powers.len(),
+                srs.g1_powers.len(),
+            ));
+        }
+        Ok(Self(Arc::new(srs)))
+    }
+
+    /// Writes the SRS to a file that [`ZeromorphSRS::load`] can read lazily.
+    pub fn save<Q: AsRef<Path>>(&self, path: Q) -> Result<(), SerializationError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.0.write_powers(&mut writer)?;
+        Ok(writer.flush()?)
+    }
+
+    /// Loads from an SRS file only the powers needed to commit to the given shapes, the same
+    /// way `Zeromorph::setup` sizes the SRS it generates.
+    pub fn load<Q: AsRef<Path>>(path: Q, shapes: &[CommitShape]) -> Result<Self, ZeromorphError> {
+        let max_len = shapes
+            .iter()
+            .map(|shape| shape.input_length)
+            .max()
+            .ok_or(ZeromorphError::NoShapes)?;
+        let srs = SRS::load_prefix(path, max_len, max_len)
+            .map_err(|err| ZeromorphError::InvalidSrsFile(err.to_string()))?;
+        Self::from_srs(srs)
+    }
+
     pub fn trim(self, max_degree: usize) -> (ZeromorphProverKey<P>, ZeromorphVerifierKey<P>) {
+        self.try_trim(max_degree).unwrap()
+    }
+
+    /// Derives the keys for polynomials with up to `max_degree` coefficients, checking that
+    /// the SRS has enough G1 and G2 powers for them.
+    pub fn try_trim(
+        &self,
+        max_degree: usize,
+    ) -> Result<(ZeromorphProverKey<P>, ZeromorphVerifierKey<P>), ZeromorphError> {
+        let num_g1_powers = self.0.g1_powers.len();
+        if max_degree == 0 {
+            return Err(ZeromorphError::ZeroDegree);
+        }
+        if max_degree >= num_g1_powers {
+            return Err(ZeromorphError::InsufficientG1Powers(
+                num_g1_powers,
+                max_degree + 1,
+            ));
+        }
+        // The shifts `N_max - 2^k` must index into the G2 powers
+        if self.0.g2_powers.len() < num_g1_powers {
+            return Err(ZeromorphError::InsufficientG2Powers(
+                self.0.g2_powers.len(),
+                num_g1_powers,
+            ));
+        }
+        let offset = num_g1_powers - max_degree;
+
         let (commit_pp, kzg_vk) = SRS::trim(self.0.clone(), max_degree);
-        let offset = self.0.g1_powers.len() - max_degree;
         let tau_N_max_sub_2_N = self.0.g2_powers[offset];
-        let open_pp = KZGProverKey::new(self.0, offset, max_degree);
-        (
+        let tau_N_sub_2_m = iter::successors(Some(1usize), |len| Some(len << 1))
+            .take_while(|len| *len <= max_degree)
+            .map(|len| self.0.g2_powers[num_g1_powers - len])
+            .collect();
+        let open_pp = KZGProverKey::new(self.0.clone(), offset, max_degree);
+        Ok((
             ZeromorphProverKey { commit_pp, open_pp },
             ZeromorphVerifierKey {
                 kzg_vk,
                 tau_N_max_sub_2_N,
+                tau_N_sub_2_m,
             },
-        )
+        ))
+    }
+
+    /// Derives a pair of keys per shape, each sized to the shape's input length rather than to
+    /// the largest one, so that the degree checks are as tight as possible for every batch.
+    pub fn trim_to_shapes(
+        &self,
+        shapes: &[CommitShape],
+    ) -> Result<Vec<(ZeromorphProverKey<P>, ZeromorphVerifierKey<P>)>, ZeromorphError> {
+        if shapes.is_empty() {
+            return Err(ZeromorphError::NoShapes);
+        }
+        shapes
+            .iter()
+            .map(|shape| self.try_trim(shape.input_length))
+            .collect()
+    }
+}
+
+impl ZeromorphSRS<Bn254> {
+    /// Loads the SRS from a `.ptau` file of a powers-of-tau ceremony rather than generating it
+    /// from an RNG, so that the trapdoor is not known to anyone.
+    pub fn from_ptau<Q: AsRef<Path>>(path: Q, max_degree: usize) -> Result<Self, PtauError> {
+        // The ceremony's G1 and G2 powers are read up to the same degree
+        Ok(Self(Arc::new(SRS::from_ptau(
+            path, max_degree, max_degree,
+        )?)))
     }
 }
 
 //TODO: adapt interface to have prover and verifier key
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ZeromorphProverKey<P: Pairing> {
     pub commit_pp: KZGProverKey<P>,
     pub open_pp: KZGProverKey<P>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ZeromorphVerifierKey<P: Pairing> {
     pub kzg_vk: KZGVerifierKey<P>,
     pub tau_N_max_sub_2_N: P::G2Affine,
+    /// `tau_N_sub_2_m[m]` is `[X^(N - 2^m)]_2`, used to check that a commitment is to a
+    /// polynomial with at most `m` variables (see [`Zeromorph::batch_open_variable`]), and that
+    /// the `q_hat` of an opening at `m` coordinates has fewer than `2^m` coefficients.
+    pub tau_N_sub_2_m: Vec<P::G2Affine>,
+}
+
+impl<P: Pairing> ZeromorphVerifierKey<P> {
+    pub fn prepare(&self) -> PreparedZeromorphVerifierKey<P> {
+        PreparedZeromorphVerifierKey {
+            kzg_vk: self.kzg_vk.prepare(),
+            neg_tau_N_max_sub_2_N_prepared: P::G2Prepared::from(
+                -self.tau_N_max_sub_2_N.into_group(),
+            ),
+            tau_N_sub_2_m_prepared: self
+                .tau_N_sub_2_m
+                .iter()
+                .map(|tau| P::G2Prepared::from(*tau))
+                .collect(),
+        }
+    }
+}
+
+/// A [`ZeromorphVerifierKey`] with its G2 elements prepared for pairing, for reuse across
+/// verifications.
+#[derive(Clone, Debug)]
+pub struct PreparedZeromorphVerifierKey<P: Pairing> {
+    pub kzg_vk: PreparedKZGVerifierKey<P>,
+    pub neg_tau_N_max_sub_2_N_prepared: P::G2Prepared,
+    pub tau_N_sub_2_m_prepared: Vec<P::G2Prepared>,
+}
+
+impl<P: Pairing> PreparedZeromorphVerifierKey<P> {
+    /// The G2 elements Zeromorph's pairing checks pair against:
+    /// `[-tau_N_max_sub_2_N, beta_g2, g2, tau_N_sub_2_m[0], tau_N_sub_2_m[1], ...]`.
+    pub fn g2_prepared(&self) -> Vec<P::G2Prepared> {
+        [
+            self.neg_tau_N_max_sub_2_N_prepared.clone(),
+            self.kzg_vk.beta_g2_prepared.clone(),
+            self.kzg_vk.g2_prepared.clone(),
+        ]
+        .into_iter()
+        .chain(self.tau_N_sub_2_m_prepared.iter().cloned())
+        .collect()
+    }
+
+    /// Returns an empty [`PairingAccumulator`] over the G2 elements of
+    /// [`PreparedZeromorphVerifierKey::g2_prepared`].
+    pub fn pairing_accumulator(&self) -> PairingAccumulator<P> {
+        PairingAccumulator::new(self.g2_prepared())
+    }
 }
 
-#[derive(Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ZeromorphCommitment<P: Pairing>(P::G1Affine);
 
+/// See [`kzg::serialize_commitment`].
+impl<P: Pairing> CanonicalSerialize for ZeromorphCommitment<P> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        kzg::serialize_commitment(&self.0, writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        kzg::commitment_serialized_size(&self.0, compress)
+    }
+}
+
+impl<P: Pairing> Valid for ZeromorphCommitment<P> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.0.check()
+    }
+}
+
+impl<P: Pairing> CanonicalDeserialize for ZeromorphCommitment<P> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        kzg::deserialize_commitment(reader, compress, validate).map(Self)
+    }
+}
+
 impl<P: Pairing> Default for ZeromorphCommitment<P> {
     fn default() -> Self {
         Self(P::G1Affine::zero())
@@ -83,7 +290,27 @@ impl<P: Pairing> AppendToTranscript for ZeromorphCommitment<P> {
 pub struct ZeromorphProof<P: Pairing> {
     pub pi: P::G1Affine,
     pub q_hat_com: P::G1Affine,
+    /// `[X^(N - 2^n) * q_hat]_1`, showing that `q_hat` has fewer than `2^n` coefficients
+    /// for an opening at a point of `n` coordinates.
+    pub q_hat_degree_check_com: P::G1Affine,
     pub q_k_com: Vec<P::G1Affine>,
+    /// Degree-check commitments `[X^(N - 2^m_i) * f_i]_1`, one for each polynomial of a
+    /// [`Zeromorph::batch_open_variable`] with fewer variables than the opening point.
+    /// Empty for all other openings.
+    pub degree_check_coms: Vec<P::G1Affine>,
+}
+
+impl<P: Pairing> ToFieldElements<P::ScalarField> for ZeromorphProof<P>
+where
+    <P as Pairing>::ScalarField: field::JoltField,
+{
+    fn append_field_elements(&self, out: &mut Vec<P::ScalarField>) {
+        append_point(&self.pi, out);
+        append_point(&self.q_hat_com, out);
+        append_point(&self.q_hat_degree_check_com, out);
+        append_points(&self.q_k_com, out);
+        append_points(&self.degree_check_coms, out);
+    }
 }
 
 fn compute_multilinear_quotients<P: Pairing>(
@@ -158,67 +385,13 @@ where
     (UniPoly::from_coeff(q_hat), 1 << (num_vars - 1))
 }
 
-fn eval_and_quotient_scalars<P: Pairing>(
-    y_challenge: P::ScalarField,
-    x_challenge: P::ScalarField,
-    z_challenge: P::ScalarField,
-    challenges: &[P::ScalarField],
-) -> (P::ScalarField, (Vec<P::ScalarField>, Vec<P::ScalarField>))
-where
-    <P as Pairing>::ScalarField: field::JoltField,
-{
-    let num_vars = challenges.len();
-
-    // squares of x = [x, x^2, .. x^{2^k}, .. x^{2^num_vars}]
-    let squares_of_x: Vec<_> = iter::successors(Some(x_challenge), |&x| Some(x.square()))
-        .take(num_vars + 1)
-        .collect();
-
-    let offsets_of_x = {
-        let mut offsets_of_x = squares_of_x
-            .iter()
-            .rev()
-            .skip(1)
-            .scan(P::ScalarField::one(), |acc, pow_x| {
-                *acc *= *pow_x;
-                Some(*acc)
-            })
-            .collect::<Vec<_>>();
-        offsets_of_x.reverse();
-        offsets_of_x
-    };
-
-    let vs = {
-        let v_numer = squares_of_x[num_vars] - P::ScalarField::one();
-        let mut v_denoms = squares_of_x
-            .iter()
-            .map(|squares_of_x| *squares_of_x - P::ScalarField::one())
-            .collect::<Vec<_>>();
-        batch_inversion(&mut v_denoms);
-        v_denoms
-            .iter()
-            .map(|v_denom| v_numer * *v_denom)
-            .collect::<Vec<_>>()
-    };
-
-    let q_scalars = izip!(
-        iter::successors(Some(P::ScalarField::one()), |acc| Some(*acc * y_challenge))
-            .take(num_vars),
-        offsets_of_x,
-        squares_of_x,
-        &vs,
-        &vs[1..],
-        challenges.iter().rev()
-    )
-    .map(|(power_of_y, offset_of_x, square_of_x, v_i, v_j, u_i)| {
-        (
-            -(power_of_y * offset_of_x),
-            -(z_challenge * (square_of_x * *v_j - *u_i * *v_i)),
-        )
-    })
-    .unzip();
-    // -vs[0] * z = -z * (x^(2^num_vars) - 1) / (x - 1) = -z Φ_n(x)
-    (-vs[0] * z_challenge, q_scalars)
+/// The factor by which zero-padding a polynomial with `num_vars` variables to `point.len()`
+/// variables (in the high-order positions) scales its evaluation at `point`.
+fn padding_factor<F: Field>(point: &[F], num_vars: usize) -> F {
+    point[..point.len() - num_vars]
+        .iter()
+        .map(|r| F::one() - *r)
+        .product()
 }
 
 #[derive(Clone)]
@@ -251,7 +424,30 @@ where
         ))
     }
 
-    #[tracing::instrument(skip_all, name = "Zeromorph::open")]
+    /// Commits to the polynomial whose evaluations are yielded chunk-by-chunk by `chunks`,
+    /// without materializing them in a `DensePolynomial`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Zeromorph::commit_streaming")
+    )]
+    pub fn commit_streaming<I>(
+        pp: &ZeromorphProverKey<P>,
+        chunks: I,
+    ) -> Result<ZeromorphCommitment<P>, ProofVerifyError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[P::ScalarField]>,
+    {
+        Ok(ZeromorphCommitment(UnivariateKZG::commit_streaming(
+            &pp.commit_pp,
+            chunks,
+        )?))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Zeromorph::open")
+    )]
     pub fn open(
         pp: &ZeromorphProverKey<P>,
         poly: &DensePolynomial<P::ScalarField>,
@@ -296,6 +492,20 @@ where
         let q_hat_com = UnivariateKZG::commit_offset(&pp.commit_pp, &q_hat, offset)?;
         transcript.append_point(&q_hat_com.into_group());
 
+        // Commit to q_hat shifted to the top of the SRS, which bounds its degree by 2^n rather
+        // than by the size of the key. Being determined by C_q, the commitment need not be
+        // absorbed into the transcript.
+        let open_g1_powers = pp.open_pp.g1_powers();
+        let shift = open_g1_powers.len().checked_sub(1 << point.len()).ok_or(
+            ProofVerifyError::KeyLengthError(open_g1_powers.len(), 1 << point.len()),
+        )?;
+        let q_hat_degree_check_com = <P::G1 as VariableBaseMSM>::msm(
+            &open_g1_powers[shift + offset..][..q_hat.coeffs.len() - offset],
+            &q_hat.coeffs[offset..],
+        )
+        .unwrap()
+        .into_affine();
+
         // Get x and z challenges
         let x_challenge = transcript.challenge_scalar();
         let z_challenge = transcript.challenge_scalar();
@@ -326,11 +536,118 @@ where
         Ok(ZeromorphProof {
             pi,
             q_hat_com,
+            q_hat_degree_check_com,
             q_k_com,
+            degree_check_coms: vec![],
         })
     }
 
-    #[tracing::instrument(skip_all, name = "Zeromorph::batch_open")]
+    /// Computes `sum_i rho_powers[i] * polynomials[i]` over `n` evaluations, treating each
+    /// polynomial as zero-padded to `n` evaluations.
+    fn batch_polynomials(
+        polynomials: &[&DensePolynomial<P::ScalarField>],
+        rho_powers: &[P::ScalarField],
+        n: usize,
+    ) -> Vec<P::ScalarField> {
+        #[cfg(feature = "tracing")]
+        let _enter = tracing::trace_span!("f_batched").entered();
+        let num_chunks = par::current_num_threads().next_power_of_two().min(n);
+        let chunk_size = n / num_chunks;
+        (0..num_chunks)
+            .into_par_iter()
+            .flat_map_iter(|chunk_index| {
+                let mut chunk = unsafe_allocate_zero_vec::<P::ScalarField>(chunk_size);
+                for (coeff, poly) in rho_powers.iter().zip(polynomials.iter()) {
+                    let poly_evals = poly.evals_ref();
+                    if chunk_index * chunk_size >= poly_evals.len() {
+                        continue;
+                    }
+                    for (rlc, poly_eval) in chunk
+                        .iter_mut()
+                        .zip(poly_evals[chunk_index * chunk_size..].iter())
+                    {
+                        *rlc += mul_0_1_optimized(poly_eval, coeff);
+                    }
+                }
+                chunk
+            })
+            .collect()
+    }
+
+    /// Batch-opens `polynomials` at `point`, where the polynomials may have fewer variables
+    /// than `point`. A polynomial `f_i` with `m_i` variables is opened at the last `m_i`
+    /// coordinates of `point`, i.e. `evals[i] = f_i(point[point.len() - m_i..])`.
+    ///
+    /// Such an `f_i` is batched as if zero-padded to `point.len()` variables, which leaves its
+    /// commitment unchanged but scales its evaluation by `prod_{j < point.len() - m_i} (1 - point[j])`.
+    /// The padding is only sound if `f_i` really has at most `m_i` variables, so the proof also
+    /// carries a degree-check commitment `[X^(N - 2^m_i) * f_i]_1` for each of them.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Zeromorph::batch_open_variable")
+    )]
+    pub fn batch_open_variable(
+        pk: &ZeromorphProverKey<P>,
+        polynomials: &[&DensePolynomial<P::ScalarField>],
+        point: &[P::ScalarField],
+        evals: &[P::ScalarField],
+        transcript: &mut ProofTranscript,
+    ) -> Result<ZeromorphProof<P>, ProofVerifyError> {
+        if polynomials.len() != evals.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                polynomials.len(),
+                evals.len(),
+            ));
+        }
+        let num_vars = point.len();
+        if let Some(poly) = polynomials
+            .iter()
+            .find(|poly| poly.get_num_vars() > num_vars)
+        {
+            return Err(ProofVerifyError::InvalidInputLength(
+                num_vars,
+                poly.get_num_vars(),
+            ));
+        }
+
+        let open_g1_powers = pk.open_pp.g1_powers();
+        let degree_check_coms: Vec<P::G1Affine> = polynomials
+            .par_iter()
+            .filter(|poly| poly.get_num_vars() < num_vars)
+            .map(|poly| {
+                let bases = &open_g1_powers[open_g1_powers.len() - poly.len()..];
+                <P::G1 as VariableBaseMSM>::msm(bases, poly.evals_ref())
+                    .map(|com| com.into_affine())
+                    .map_err(|_| ProofVerifyError::KeyLengthError(bases.len(), poly.len()))
+            })
+            .collect::<Result<_, _>>()?;
+        let degree_check_groups: Vec<P::G1> =
+            degree_check_coms.iter().map(|c| c.into_group()).collect();
+        transcript.append_points(&degree_check_groups);
+
+        let rho: P::ScalarField = transcript.challenge_scalar();
+        let rho_powers: Vec<P::ScalarField> =
+            iter::successors(Some(P::ScalarField::one()), |acc| Some(*acc * rho))
+                .take(polynomials.len())
+                .collect();
+
+        let batched_evaluation = izip!(&rho_powers, polynomials, evals)
+            .map(|(scalar, poly, eval)| {
+                *scalar * padding_factor(point, poly.get_num_vars()) * *eval
+            })
+            .sum();
+        let f_batched = Self::batch_polynomials(polynomials, &rho_powers, 1 << num_vars);
+
+        let poly = DensePolynomial::new(f_batched);
+        let mut proof = Self::open(pk, &poly, point, &batched_evaluation, transcript)?;
+        proof.degree_check_coms = degree_check_coms;
+        Ok(proof)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Zeromorph::batch_open")
+    )]
     fn batch_open(
         pk: &ZeromorphProverKey<P>,
         polynomials: &[&DensePolynomial<P::ScalarField>],
@@ -355,27 +672,7 @@ where
             .map(|(scalar, eval)| *scalar * *eval)
             .sum();
 
-        let span = trace_span!("f_batched");
-        let enter = span.enter();
-        let num_chunks = rayon::current_num_threads().next_power_of_two();
-        let chunk_size = n / num_chunks;
-        let f_batched = (0..num_chunks)
-            .into_par_iter()
-            .flat_map_iter(|chunk_index| {
-                let mut chunk = unsafe_allocate_zero_vec::<P::ScalarField>(chunk_size);
-                for (coeff, poly) in rho_powers.iter().zip(polynomials.iter()) {
-                    for (rlc, poly_eval) in chunk
-                        .iter_mut()
-                        .zip(poly.evals_ref()[chunk_index * chunk_size..].iter())
-                    {
-                        *rlc += mul_0_1_optimized(poly_eval, coeff);
-                    }
-                }
-                chunk
-            })
-            .collect::<Vec<_>>();
-        drop(enter);
-        drop(span);
+        let f_batched = Self::batch_polynomials(polynomials, &rho_powers, n);
 
         let poly = DensePolynomial::new(f_batched);
         Zeromorph::<P, ProofTranscript>::open(pk, &poly, point, &batched_evaluation, transcript)
@@ -383,7 +680,7 @@ where
     }
 
     fn batch_verify(
-        vk: &ZeromorphVerifierKey<P>,
+        vk: &PreparedZeromorphVerifierKey<P>,
         commitments: &[&ZeromorphCommitment<P>],
         point: &[P::ScalarField],
         evals: &[P::ScalarField],
@@ -404,7 +701,96 @@ where
                 (batched_evaluation, batched_commitment)
             },
         );
-        Zeromorph::<P, ProofTranscript>::verify(
+        Zeromorph::<P, ProofTranscript>::verify_prepared(
+            vk,
+            &ZeromorphCommitment(batched_commitment.into_affine()),
+            point,
+            &batched_eval,
+            batch_proof,
+            transcript,
+        )
+    }
+
+    /// Verifies a [`Zeromorph::batch_open_variable`] proof, where `num_vars[i]` is the number
+    /// of variables of the polynomial committed to in `commitments[i]`.
+    pub fn batch_verify_variable(
+        vk: &PreparedZeromorphVerifierKey<P>,
+        commitments: &[&ZeromorphCommitment<P>],
+        num_vars: &[usize],
+        point: &[P::ScalarField],
+        evals: &[P::ScalarField],
+        batch_proof: &ZeromorphProof<P>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        if commitments.len() != evals.len() || commitments.len() != num_vars.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                commitments.len(),
+                evals.len(),
+            ));
+        }
+        if let Some(m) = num_vars.iter().find(|m| **m > point.len()) {
+            return Err(ProofVerifyError::InvalidInputLength(point.len(), *m));
+        }
+        let num_degree_checks = num_vars.iter().filter(|m| **m < point.len()).count();
+        if batch_proof.degree_check_coms.len() != num_degree_checks {
+            return Err(ProofVerifyError::InvalidInputLength(
+                num_degree_checks,
+                batch_proof.degree_check_coms.len(),
+            ));
+        }
+
+        let degree_check_groups: Vec<P::G1> = batch_proof
+            .degree_check_coms
+            .iter()
+            .map(|c| c.into_group())
+            .collect();
+        transcript.append_points(&degree_check_groups);
+
+        let rho: P::ScalarField = transcript.challenge_scalar();
+        let rho_powers: Vec<P::ScalarField> =
+            iter::successors(Some(P::ScalarField::one()), |acc| Some(*acc * rho))
+                .take(commitments.len())
+                .collect();
+
+        // Degree checks: e(C_i, [X^(N - 2^m_i)]_2) == e(D_i, [1]_2) for each m_i < n, batched
+        // with the powers of rho. Commitments of the same size share a G2 element.
+        let mut degree_check_lhs: Vec<P::G1> = vec![P::G1::zero(); vk.tau_N_sub_2_m_prepared.len()];
+        let mut degree_check_rhs = P::G1::zero();
+        let mut degree_check_coms = degree_check_groups.iter();
+        for ((scalar, commitment), m) in rho_powers.iter().zip(commitments).zip(num_vars) {
+            if *m == point.len() {
+                continue;
+            }
+            let lhs = degree_check_lhs
+                .get_mut(*m)
+                .ok_or(ProofVerifyError::InternalError)?;
+            *lhs += commitment.0 * scalar;
+            degree_check_rhs += *degree_check_coms.next().unwrap() * scalar;
+        }
+        let (g1, g2): (Vec<P::G1>, Vec<P::G2Prepared>) = degree_check_lhs
+            .into_iter()
+            .zip(vk.tau_N_sub_2_m_prepared.iter().cloned())
+            .filter(|(lhs, _)| !lhs.is_zero())
+            .chain(iter::once((
+                -degree_check_rhs,
+                vk.kzg_vk.g2_prepared.clone(),
+            )))
+            .unzip();
+        if !P::multi_pairing(g1, g2).is_zero() {
+            return Err(ProofVerifyError::InternalError);
+        }
+
+        let (batched_eval, batched_commitment) = izip!(&rho_powers, commitments, num_vars, evals)
+            .fold(
+                (P::ScalarField::zero(), P::G1::zero()),
+                |(batched_eval, batched_commitment), (scalar, commitment, m, eval)| {
+                    (
+                        batched_eval + *scalar * padding_factor(point, *m) * *eval,
+                        batched_commitment + commitment.0 * scalar,
+                    )
+                },
+            );
+        Zeromorph::<P, ProofTranscript>::verify_prepared(
             vk,
             &ZeromorphCommitment(batched_commitment.into_affine()),
             point,
@@ -422,60 +808,73 @@ where
         proof: &ZeromorphProof<P>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
-        let protocol_name = Self::protocol_name();
-        transcript.append_message(protocol_name);
-
-        let q_comms: Vec<P::G1> = proof.q_k_com.iter().map(|c| c.into_group()).collect();
-        q_comms.iter().for_each(|c| transcript.append_point(c));
-
-        // Challenge y
-        let y_challenge: P::ScalarField = transcript.challenge_scalar();
-
-        // Receive commitment C_q_hat
-        transcript.append_point(&proof.q_hat_com.into_group());
+        Self::verify_prepared(&vk.prepare(), comm, point, eval, proof, transcript)
+    }
 
-        // Get x and z challenges
-        let x_challenge = transcript.challenge_scalar();
-        let z_challenge = transcript.challenge_scalar();
+    /// Same as [`Zeromorph::verify`], with a verifier key prepared ahead of time
+    /// (see [`ZeromorphVerifierKey::prepare`]).
+    pub fn verify_prepared(
+        vk: &PreparedZeromorphVerifierKey<P>,
+        comm: &ZeromorphCommitment<P>,
+        point: &[P::ScalarField],
+        eval: &P::ScalarField,
+        proof: &ZeromorphProof<P>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        verify_zeromorph_opening::<P, ProofTranscript>(
+            &vk.kzg_vk.vk.g1,
+            &vk.g2_prepared(),
+            &comm.0,
+            point,
+            eval,
+            &proof.pi,
+            &proof.q_hat_com,
+            &proof.q_hat_degree_check_com,
+            &proof.q_k_com,
+            transcript,
+        )
+    }
 
-        // Compute batched degree and ZM-identity quotient polynomial pi
-        let (eval_scalar, (mut q_scalars, zmpoly_q_scalars)): (
-            P::ScalarField,
-            (Vec<P::ScalarField>, Vec<P::ScalarField>),
-        ) = eval_and_quotient_scalars::<P>(y_challenge, x_challenge, z_challenge, point);
-        q_scalars
-            .iter_mut()
-            .zip(zmpoly_q_scalars)
-            .for_each(|(scalar, zm_poly_q_scalar)| {
-                *scalar += zm_poly_q_scalar;
-            });
-        let scalars = [
-            vec![P::ScalarField::one(), z_challenge, eval_scalar * *eval],
-            q_scalars,
-        ]
-        .concat();
-        let bases = [
-            vec![proof.q_hat_com, comm.0, vk.kzg_vk.g1],
-            proof.q_k_com.clone(),
-        ]
-        .concat();
-        let zeta_z_com = <P::G1 as VariableBaseMSM>::msm(&bases, &scalars)
-            .unwrap()
-            .into_affine();
-
-        // e(pi, [tau]_2 - x * [1]_2) == e(C_{\zeta,Z}, -[X^(N_max - 2^n - 1)]_2) <==> e(C_{\zeta,Z} - x * pi, [X^{N_max - 2^n - 1}]_2) * e(-pi, [tau_2]) == 1
-        let pairing = P::multi_pairing(
-            [zeta_z_com, proof.pi],
-            [
-                (-vk.tau_N_max_sub_2_N.into_group()).into_affine(),
-                (vk.kzg_vk.beta_g2.into_group() - (vk.kzg_vk.g2 * x_challenge)).into(),
-            ],
-        );
-        if pairing.is_zero() {
-            Ok(())
-        } else {
-            Err(ProofVerifyError::InternalError)
+    /// Same as [`Zeromorph::verify_prepared`], but defers the pairing check to `accumulator`
+    /// (see [`PreparedZeromorphVerifierKey::pairing_accumulator`]). The proof is only
+    /// verified once the accumulator is.
+    pub fn verify_deferred(
+        vk: &PreparedZeromorphVerifierKey<P>,
+        comm: &ZeromorphCommitment<P>,
+        point: &[P::ScalarField],
+        eval: &P::ScalarField,
+        proof: &ZeromorphProof<P>,
+        transcript: &mut ProofTranscript,
+        accumulator: &mut PairingAccumulator<P>,
+    ) -> Result<(), ProofVerifyError> {
+        for pairing_check in Self::verify_inner(vk, comm, point, eval, proof, transcript)? {
+            accumulator.accumulate(pairing_check);
         }
+        Ok(())
+    }
+
+    /// Computes the G1 elements of the verifier's two pairing checks, the ZM-identity check
+    /// and the degree check on `q_hat`, against [`PreparedZeromorphVerifierKey::g2_prepared`].
+    fn verify_inner(
+        vk: &PreparedZeromorphVerifierKey<P>,
+        comm: &ZeromorphCommitment<P>,
+        point: &[P::ScalarField],
+        eval: &P::ScalarField,
+        proof: &ZeromorphProof<P>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<[Vec<P::G1>; 2], ProofVerifyError> {
+        zeromorph_pairing_checks::<P, ProofTranscript>(
+            &vk.kzg_vk.vk.g1,
+            vk.tau_N_sub_2_m_prepared.len(),
+            &comm.0,
+            point,
+            eval,
+            &proof.pi,
+            &proof.q_hat_com,
+            &proof.q_hat_degree_check_com,
+            &proof.q_k_com,
+            transcript,
+        )
     }
 }
 
@@ -485,7 +884,7 @@ where
     <P as Pairing>::ScalarField: field::JoltField,
 {
     type Field = P::ScalarField;
-    type Setup = (ZeromorphProverKey<P>, ZeromorphVerifierKey<P>);
+    type Setup = (ZeromorphProverKey<P>, PreparedZeromorphVerifierKey<P>);
     type Commitment = ZeromorphCommitment<P>;
     type Proof = ZeromorphProof<P>;
     type BatchedProof = ZeromorphProof<P>;
@@ -493,12 +892,25 @@ where
     fn setup(shapes: &[CommitShape]) -> Self::Setup {
         let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
 
-        ZeromorphSRS(Arc::new(SRS::setup(
+        let (pk, vk) = ZeromorphSRS(Arc::new(SRS::setup(
             &mut ChaCha20Rng::from_seed(*b"ZEROMORPH_POLY_COMMITMENT_SCHEME"),
             max_len,
             max_len,
         )))
-        .trim(max_len)
+        .trim(max_len);
+        let pk = ZeromorphProverKey {
+            commit_pp: pk.commit_pp.with_default_precomputed_bases(shapes),
+            open_pp: pk.open_pp,
+        };
+        (pk, vk.prepare())
+    }
+
+    fn verifier_setup(setup: &Self::Setup) -> Self::Setup {
+        let pk = ZeromorphProverKey {
+            commit_pp: KZGProverKey::empty(),
+            open_pp: KZGProverKey::empty(),
+        };
+        (pk, setup.1.clone())
     }
 
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
@@ -517,8 +929,12 @@ where
     fn batch_commit(
         evals: &[&[Self::Field]],
         gens: &Self::Setup,
-        _batch_type: BatchType,
+        batch_type: BatchType,
     ) -> Vec<Self::Commitment> {
+        let mode = match batch_type {
+            BatchType::SmallScalars => CommitMode::SmallScalars,
+            _ => CommitMode::Default,
+        };
         // TODO: assert lengths are valid
         evals
             .par_iter()
@@ -530,8 +946,7 @@ where
                     evals.len()
                 );
                 ZeromorphCommitment(
-                    UnivariateKZG::commit(&gens.0.commit_pp, &UniPoly::from_coeff(evals.to_vec()))
-                        .unwrap(),
+                    UnivariateKZG::commit_slice_with_mode(&gens.0.commit_pp, evals, mode).unwrap(),
                 )
             })
             .collect::<Vec<_>>()
@@ -544,6 +959,20 @@ where
         )
     }
 
+    fn commit_streaming<I>(len: usize, chunks: I, setup: &Self::Setup) -> Self::Commitment
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[Self::Field]>,
+    {
+        assert!(
+            setup.0.commit_pp.g1_powers().len() > len,
+            "COMMIT KEY LENGTH ERROR {}, {}",
+            setup.0.commit_pp.g1_powers().len(),
+            len
+        );
+        Zeromorph::<P, ProofTranscript>::commit_streaming(&setup.0, chunks).unwrap()
+    }
+
     fn prove(
         setup: &Self::Setup,
         poly: &DensePolynomial<Self::Field>,
@@ -572,18 +1001,6 @@ where
         )
     }
 
-    fn combine_commitments(
-        commitments: &[&Self::Commitment],
-        coeffs: &[Self::Field],
-    ) -> Self::Commitment {
-        let combined_commitment: P::G1 = commitments
-            .iter()
-            .zip(coeffs.iter())
-            .map(|(commitment, coeff)| commitment.0 * coeff)
-            .sum();
-        ZeromorphCommitment(combined_commitment.into_affine())
-    }
-
     fn verify(
         proof: &Self::Proof,
         setup: &Self::Setup,
@@ -592,7 +1009,7 @@ where
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
         commitment: &Self::Commitment,
     ) -> Result<(), ProofVerifyError> {
-        Zeromorph::<P, ProofTranscript>::verify(
+        Zeromorph::<P, ProofTranscript>::verify_prepared(
             &setup.1,
             commitment,
             opening_point,
@@ -620,18 +1037,104 @@ where
         )
     }
 
+    fn verify_linear_combination(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Result<(), ProofVerifyError> {
+        let commitment = <Self as AdditivelyHomomorphic<ProofTranscript>>::combine_commitments(
+            commitments,
+            coeffs,
+        );
+        <Self as CommitmentScheme<ProofTranscript>>::verify(
+            proof,
+            setup,
+            transcript,
+            opening_point,
+            opening,
+            &commitment,
+        )
+    }
+
     fn protocol_name() -> &'static [u8] {
         b"zeromorph"
     }
 }
 
+impl<P: Pairing, ProofTranscript: Transcript> AdditivelyHomomorphic<ProofTranscript>
+    for Zeromorph<P, ProofTranscript>
+where
+    <P as Pairing>::ScalarField: field::JoltField,
+{
+    fn combine_commitments(
+        commitments: &[&Self::Commitment],
+        coeffs: &[Self::Field],
+    ) -> Self::Commitment {
+        // A single MSM, so that batching many openings costs the verifier one MSM. Commitments
+        // to zero polynomials do not contribute to it.
+        let (bases, coeffs): (Vec<_>, Vec<_>) = commitments
+            .iter()
+            .zip(coeffs.iter())
+            .filter(|(commitment, _)| !commitment.0.is_zero())
+            .map(|(commitment, coeff)| (commitment.0, *coeff))
+            .unzip();
+        let combined_commitment = <P::G1 as VariableBaseMSM>::msm(&bases, &coeffs).unwrap();
+        ZeromorphCommitment(combined_commitment.into_affine())
+    }
+}
+
+impl<P: Pairing, ProofTranscript: Transcript> AggregatableCommitmentScheme<ProofTranscript>
+    for Zeromorph<P, ProofTranscript>
+where
+    <P as Pairing>::ScalarField: field::JoltField,
+{
+    type Accumulator = PairingAccumulator<P>;
+
+    fn accumulator(setup: &Self::Setup) -> Self::Accumulator {
+        setup.1.pairing_accumulator()
+    }
+
+    fn verify_deferred(
+        proof: &Self::Proof,
+        setup: &Self::Setup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitment: &Self::Commitment,
+        accumulator: &mut Self::Accumulator,
+    ) -> Result<(), ProofVerifyError> {
+        Zeromorph::<P, ProofTranscript>::verify_deferred(
+            &setup.1,
+            commitment,
+            opening_point,
+            opening,
+            proof,
+            transcript,
+            accumulator,
+        )
+    }
+
+    fn verify_accumulator<R: RngCore + CryptoRng>(
+        accumulator: Self::Accumulator,
+        rng: &mut R,
+    ) -> Result<(), ProofVerifyError> {
+        accumulator.verify(rng)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::poly::commitment::ptau::tests::write_ptau;
     use crate::utils::math::Math;
     use crate::utils::transcript::{KeccakTranscript, Transcript};
     use ark_bn254::{Bn254, Fr};
     use ark_ff::{BigInt, Zero};
+    use ark_serialize::{Compress, Validate};
     use ark_std::{test_rng, UniformRand};
     use rand_core::SeedableRng;
 
@@ -938,6 +1441,405 @@ mod test {
         }
     }
 
+    #[test]
+    fn zeromorph_commit_streaming() {
+        const NUM_VARS: usize = 6;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let poly = DensePolynomial::random(NUM_VARS, &mut rng);
+        let setup =
+            <Zeromorph<Bn254, KeccakTranscript> as CommitmentScheme<KeccakTranscript>>::setup(&[
+                CommitShape::new(1 << NUM_VARS, BatchType::Small),
+            ]);
+
+        let commitment = <Zeromorph<Bn254, KeccakTranscript> as CommitmentScheme<
+            KeccakTranscript,
+        >>::commit(&poly, &setup);
+        // Chunks of uneven sizes, as a trace generator might yield them
+        let chunks = poly.evals_ref().chunks(13);
+        let streamed_commitment = <Zeromorph<Bn254, KeccakTranscript> as CommitmentScheme<
+            KeccakTranscript,
+        >>::commit_streaming(1 << NUM_VARS, chunks, &setup);
+        assert_eq!(commitment, streamed_commitment);
+    }
+
+    #[test]
+    fn zeromorph_from_ptau() {
+        const NUM_VARS: usize = 4;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let tau = Fr::rand(&mut rng);
+        let powers: Vec<Fr> = iter::successors(Some(Fr::one()), |x| Some(*x * tau))
+            .take((1 << NUM_VARS) + 1)
+            .collect();
+        let g1_powers: Vec<_> = powers
+            .iter()
+            .map(|x| (<Bn254 as Pairing>::G1Affine::generator() * x).into_affine())
+            .collect();
+        let g2_powers: Vec<_> = powers
+            .iter()
+            .map(|x| (<Bn254 as Pairing>::G2Affine::generator() * x).into_affine())
+            .collect();
+        let path = std::env::temp_dir().join("jolt_zeromorph_from_ptau_test.ptau");
+        std::fs::write(&path, write_ptau(5, &g1_powers, &g2_powers)).unwrap();
+
+        let srs = ZeromorphSRS::<Bn254>::from_ptau(&path, 1 << NUM_VARS).unwrap();
+        let (pk, vk) = srs.trim(1 << NUM_VARS);
+
+        let poly = DensePolynomial::random(NUM_VARS, &mut rng);
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+        let commitment = Zeromorph::<Bn254, KeccakTranscript>::commit(&pk, &poly).unwrap();
+
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let proof = Zeromorph::<Bn254, KeccakTranscript>::open(
+            &pk,
+            &poly,
+            &point,
+            &eval,
+            &mut prover_transcript,
+        )
+        .unwrap();
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        Zeromorph::<Bn254, KeccakTranscript>::verify(
+            &vk,
+            &commitment,
+            &point,
+            &eval,
+            &proof,
+            &mut verifier_transcript,
+        )
+        .unwrap();
+
+        // The ceremony only has 2^5 G2 powers
+        let path = std::env::temp_dir().join("jolt_zeromorph_from_ptau_short_test.ptau");
+        std::fs::write(&path, write_ptau(5, &g1_powers, &g2_powers)).unwrap();
+        let result = ZeromorphSRS::<Bn254>::from_ptau(&path, 1 << 5);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(PtauError::InsufficientPowers(32, 33))));
+    }
+
+    #[test]
+    fn zeromorph_load_srs_prefix() {
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path();
+        ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << 8)
+            .save(path)
+            .unwrap();
+        assert_eq!(
+            ZeromorphSRS::<Bn254>::load(path, &[]).err(),
+            Some(ZeromorphError::NoShapes)
+        );
+
+        const NUM_VARS: usize = 5;
+        let srs = ZeromorphSRS::<Bn254>::load(
+            path,
+            &[
+                CommitShape::new(1 << 3, BatchType::Small),
+                CommitShape::new(1 << NUM_VARS, BatchType::Big),
+            ],
+        )
+        .unwrap();
+        assert_eq!(srs.0.g1_powers.len(), (1 << NUM_VARS) + 1);
+        let (pk, vk) = srs.trim(1 << NUM_VARS);
+
+        let poly = DensePolynomial::random(NUM_VARS, &mut rng);
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+        let commitment = Zeromorph::<Bn254, KeccakTranscript>::commit(&pk, &poly).unwrap();
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let proof = Zeromorph::<Bn254, KeccakTranscript>::open(
+            &pk,
+            &poly,
+            &point,
+            &eval,
+            &mut prover_transcript,
+        )
+        .unwrap();
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        Zeromorph::<Bn254, KeccakTranscript>::verify(
+            &vk,
+            &commitment,
+            &point,
+            &eval,
+            &proof,
+            &mut verifier_transcript,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn zeromorph_trim_to_shapes() {
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << 6);
+
+        let keys = srs
+            .trim_to_shapes(&[
+                CommitShape::new(1 << 4, BatchType::Small),
+                CommitShape::new(1 << 6, BatchType::Big),
+            ])
+            .unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].0.commit_pp.g1_powers().len(), (1 << 4) + 1);
+        assert_eq!(keys[1].0.commit_pp.g1_powers().len(), (1 << 6) + 1);
+
+        for (num_vars, (pk, vk)) in [4, 6].into_iter().zip(keys.iter()) {
+            let poly = DensePolynomial::random(num_vars, &mut rng);
+            let point: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+            let eval = poly.evaluate(&point);
+            let commitment = Zeromorph::<Bn254, KeccakTranscript>::commit(pk, &poly).unwrap();
+            let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+            let proof = Zeromorph::<Bn254, KeccakTranscript>::open(
+                pk,
+                &poly,
+                &point,
+                &eval,
+                &mut prover_transcript,
+            )
+            .unwrap();
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            Zeromorph::<Bn254, KeccakTranscript>::verify(
+                vk,
+                &commitment,
+                &point,
+                &eval,
+                &proof,
+                &mut verifier_transcript,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            srs.trim_to_shapes(&[CommitShape::new(1 << 7, BatchType::Big)])
+                .err(),
+            Some(ZeromorphError::InsufficientG1Powers(
+                (1 << 6) + 1,
+                (1 << 7) + 1
+            ))
+        );
+        assert_eq!(
+            srs.trim_to_shapes(&[]).err(),
+            Some(ZeromorphError::NoShapes)
+        );
+        assert_eq!(srs.try_trim(0).err(), Some(ZeromorphError::ZeroDegree));
+    }
+
+    #[test]
+    fn zeromorph_rejects_short_g2_srs() {
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let srs = SRS::<Bn254>::setup(&mut rng, 1 << 6, 1 << 3);
+        assert_eq!(
+            ZeromorphSRS::from_srs(srs.clone()).err(),
+            Some(ZeromorphError::InsufficientG2Powers(
+                (1 << 3) + 1,
+                (1 << 6) + 1
+            ))
+        );
+
+        // An SRS that bypasses `from_srs` is still rejected when trimmed
+        let srs = ZeromorphSRS(Arc::new(srs));
+        assert_eq!(
+            srs.try_trim(1 << 2).err(),
+            Some(ZeromorphError::InsufficientG2Powers(
+                (1 << 3) + 1,
+                (1 << 6) + 1
+            ))
+        );
+    }
+
+    #[test]
+    fn zeromorph_serialize_keys() {
+        const NUM_VARS: usize = 5;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << (NUM_VARS + 1));
+        let (pk, vk) = srs.trim(1 << NUM_VARS);
+
+        let poly = DensePolynomial::random(NUM_VARS, &mut rng);
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut pk_bytes = vec![];
+            pk.serialize_with_mode(&mut pk_bytes, compress).unwrap();
+            assert_eq!(pk_bytes.len(), pk.serialized_size(compress));
+            let mut vk_bytes = vec![];
+            vk.serialize_with_mode(&mut vk_bytes, compress).unwrap();
+            let deserialized_pk = ZeromorphProverKey::<Bn254>::deserialize_with_mode(
+                &pk_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+            let deserialized_vk = ZeromorphVerifierKey::<Bn254>::deserialize_with_mode(
+                &vk_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+
+            // The opening key starts at an offset into the SRS, and must deserialize to a key
+            // over the same window, with tables derived from that window
+            assert_eq!(deserialized_pk.open_pp.g1_powers(), pk.open_pp.g1_powers());
+            let mut reserialized_pk_bytes = vec![];
+            deserialized_pk
+                .serialize_with_mode(&mut reserialized_pk_bytes, compress)
+                .unwrap();
+            assert_eq!(reserialized_pk_bytes, pk_bytes);
+
+            let commitment = Zeromorph::<Bn254, KeccakTranscript>::commit(&pk, &poly).unwrap();
+            assert_eq!(
+                Zeromorph::<Bn254, KeccakTranscript>::commit(&deserialized_pk, &poly).unwrap(),
+                commitment
+            );
+            let mut commitment_bytes = vec![];
+            commitment
+                .serialize_with_mode(&mut commitment_bytes, compress)
+                .unwrap();
+            let commitment = ZeromorphCommitment::<Bn254>::deserialize_with_mode(
+                &commitment_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+
+            let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+            let proof = Zeromorph::<Bn254, KeccakTranscript>::open(
+                &deserialized_pk,
+                &poly,
+                &point,
+                &eval,
+                &mut prover_transcript,
+            )
+            .unwrap();
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            Zeromorph::<Bn254, KeccakTranscript>::verify(
+                &deserialized_vk,
+                &commitment,
+                &point,
+                &eval,
+                &proof,
+                &mut verifier_transcript,
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn zeromorph_deferred_verify() {
+        const NUM_VARS: usize = 5;
+        const NUM_PROOFS: usize = 4;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << NUM_VARS);
+        let (pk, vk) = srs.trim(1 << NUM_VARS);
+        let vk = vk.prepare();
+
+        let instances: Vec<_> = (0..NUM_PROOFS)
+            .map(|_| {
+                let poly = DensePolynomial::random(NUM_VARS, &mut rng);
+                let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+                let eval = poly.evaluate(&point);
+                let commitment = Zeromorph::<Bn254, KeccakTranscript>::commit(&pk, &poly).unwrap();
+                let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+                let proof = Zeromorph::<Bn254, KeccakTranscript>::open(
+                    &pk,
+                    &poly,
+                    &point,
+                    &eval,
+                    &mut prover_transcript,
+                )
+                .unwrap();
+                (commitment, point, eval, proof)
+            })
+            .collect();
+
+        let mut accumulator = vk.pairing_accumulator();
+        for (commitment, point, eval, proof) in instances.iter() {
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            Zeromorph::<Bn254, KeccakTranscript>::verify_deferred(
+                &vk,
+                commitment,
+                point,
+                eval,
+                proof,
+                &mut verifier_transcript,
+                &mut accumulator,
+            )
+            .unwrap();
+        }
+        // An identity check and a degree check per proof
+        assert_eq!(accumulator.len(), 2 * NUM_PROOFS);
+        assert!(accumulator.clone().verify(&mut rng).is_ok());
+
+        // A single bad claim invalidates the whole accumulator
+        let (commitment, point, eval, proof) = &instances[0];
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        Zeromorph::<Bn254, KeccakTranscript>::verify_deferred(
+            &vk,
+            commitment,
+            point,
+            &(*eval + Fr::one()),
+            proof,
+            &mut verifier_transcript,
+            &mut accumulator,
+        )
+        .unwrap();
+        assert!(accumulator.verify(&mut rng).is_err());
+    }
+
+    #[test]
+    fn zeromorph_q_hat_degree_check() {
+        const NUM_VARS: usize = 4;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        // A key for polynomials twice the size of the one opened, whose identity check alone
+        // would allow a q_hat of twice the size
+        let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << (NUM_VARS + 1));
+        let (pk, vk) = srs.trim(1 << (NUM_VARS + 1));
+        let vk = vk.prepare();
+
+        let poly = DensePolynomial::random(NUM_VARS, &mut rng);
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+        let commitment = Zeromorph::<Bn254, KeccakTranscript>::commit(&pk, &poly).unwrap();
+        let proof = Zeromorph::<Bn254, KeccakTranscript>::open(
+            &pk,
+            &poly,
+            &point,
+            &eval,
+            &mut KeccakTranscript::new(b"TestEval"),
+        )
+        .unwrap();
+        let verify = |proof: &ZeromorphProof<Bn254>| {
+            let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+            Zeromorph::<Bn254, KeccakTranscript>::verify_prepared(
+                &vk,
+                &commitment,
+                &point,
+                &eval,
+                proof,
+                &mut verifier_transcript,
+            )
+        };
+        assert!(verify(&proof).is_ok());
+
+        // Any other degree-check commitment is rejected, by both verifiers
+        let mut bad_proof = proof.clone();
+        bad_proof.q_hat_degree_check_com =
+            (proof.q_hat_degree_check_com + proof.q_hat_com).into_affine();
+        assert!(verify(&bad_proof).is_err());
+
+        let mut accumulator = vk.pairing_accumulator();
+        Zeromorph::<Bn254, KeccakTranscript>::verify_deferred(
+            &vk,
+            &commitment,
+            &point,
+            &eval,
+            &bad_proof,
+            &mut KeccakTranscript::new(b"TestEval"),
+            &mut accumulator,
+        )
+        .unwrap();
+        assert!(accumulator.verify(&mut rng).is_err());
+    }
+
     #[test]
     fn batched_zeromorph_commit_prove_verify() {
         for num_polys in [1, 2, 5, 7] {
@@ -952,6 +1854,7 @@ mod test {
 
                 let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << num_vars);
                 let (pk, vk) = srs.trim(1 << num_vars);
+                let vk = vk.prepare();
                 let commitments: Vec<_> = polys
                     .iter()
                     .map(|poly| Zeromorph::<Bn254, KeccakTranscript>::commit(&pk, poly).unwrap())
@@ -1009,4 +1912,86 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn batched_zeromorph_variable_num_vars() {
+        const NUM_VARS: usize = 6;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(NUM_VARS as u64);
+        let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << NUM_VARS);
+        let (pk, vk) = srs.trim(1 << NUM_VARS);
+        let vk = vk.prepare();
+
+        let num_vars = [6, 3, 4, 3, 6, 1];
+        let polys: Vec<_> = num_vars
+            .iter()
+            .map(|m| DensePolynomial::random(*m, &mut rng))
+            .collect();
+        let point: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::rand(&mut rng)).collect();
+        let evals: Vec<Fr> = polys
+            .iter()
+            .map(|poly| poly.evaluate(&point[NUM_VARS - poly.get_num_vars()..]))
+            .collect();
+        let commitments: Vec<_> = polys
+            .iter()
+            .map(|poly| Zeromorph::<Bn254, KeccakTranscript>::commit(&pk, poly).unwrap())
+            .collect();
+        let commitments_refs: Vec<_> = commitments.iter().collect();
+        let polys_refs: Vec<_> = polys.iter().collect();
+
+        let mut prover_transcript = KeccakTranscript::new(b"TestEval");
+        let proof = Zeromorph::<Bn254, KeccakTranscript>::batch_open_variable(
+            &pk,
+            &polys_refs,
+            &point,
+            &evals,
+            &mut prover_transcript,
+        )
+        .unwrap();
+        assert_eq!(proof.degree_check_coms.len(), 4);
+
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        Zeromorph::<Bn254, KeccakTranscript>::batch_verify_variable(
+            &vk,
+            &commitments_refs,
+            &num_vars,
+            &point,
+            &evals,
+            &proof,
+            &mut verifier_transcript,
+        )
+        .unwrap();
+        let p_transcipt_squeeze: Fr = prover_transcript.challenge_scalar();
+        let v_transcipt_squeeze: Fr = verifier_transcript.challenge_scalar();
+        assert_eq!(p_transcipt_squeeze, v_transcipt_squeeze);
+
+        // A wrong evaluation fails the batched opening
+        let mut bad_evals = evals.clone();
+        bad_evals[1] += Fr::one();
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        assert!(Zeromorph::<Bn254, KeccakTranscript>::batch_verify_variable(
+            &vk,
+            &commitments_refs,
+            &num_vars,
+            &point,
+            &bad_evals,
+            &proof,
+            &mut verifier_transcript,
+        )
+        .is_err());
+
+        // Claiming fewer variables than a polynomial has fails the degree check
+        let mut bad_num_vars = num_vars;
+        bad_num_vars[2] = 3;
+        let mut verifier_transcript = KeccakTranscript::new(b"TestEval");
+        assert!(Zeromorph::<Bn254, KeccakTranscript>::batch_verify_variable(
+            &vk,
+            &commitments_refs,
+            &bad_num_vars,
+            &point,
+            &evals,
+            &proof,
+            &mut verifier_transcript,
+        )
+        .is_err());
+    }
 }
@@ -1,3 +1,4 @@
+use crate::utils::par::{prelude::*, slice::Chunks};
 use crate::{
     field::JoltField,
     subprotocols::{
@@ -6,7 +7,6 @@ use crate::{
     },
     utils::{thread::unsafe_allocate_zero_vec, transcript::Transcript},
 };
-use rayon::{prelude::*, slice::Chunks};
 
 #[cfg(test)]
 use super::dense_mlpoly::DensePolynomial;
@@ -113,7 +113,10 @@ impl<F: JoltField> Bindable<F> for DenseInterleavedPolynomial<F> {
     ///   |  |\  \   |  |\  \
     ///   0  1 2  3  4  5 6  7
     /// Left nodes have even indices, right nodes have odd indices.
-    #[tracing::instrument(skip_all, name = "DenseInterleavedPolynomial::bind")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "DenseInterleavedPolynomial::bind")
+    )]
     fn bind(&mut self, r: F) {
         #[cfg(test)]
         let (mut left_before_binding, mut right_before_binding) = self.uninterleave();
@@ -208,7 +211,10 @@ impl<F: JoltField, ProofTranscript: Transcript> BatchedCubicSumcheck<F, ProofTra
     ///                           |  |  |  |
     ///    left(0, 0, 0, ..., x_b=0) |  |  right(0, 0, 0, ..., x_b=1)
     ///     right(0, 0, 0, ..., x_b=0)  left(0, 0, 0, ..., x_b=1)
-    #[tracing::instrument(skip_all, name = "DenseInterleavedPolynomial::compute_cubic")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "DenseInterleavedPolynomial::compute_cubic")
+    )]
     fn compute_cubic(&self, eq_poly: &SplitEqPolynomial<F>, previous_round_claim: F) -> UniPoly<F> {
         // We use the Dao-Thaler optimization for the EQ polynomial, so there are two cases we
         // must handle. For details, refer to Section 2.2 of https://eprint.iacr.org/2024/1210.pdf
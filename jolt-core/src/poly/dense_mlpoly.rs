@@ -1,14 +1,29 @@
 #![allow(clippy::too_many_arguments)]
+use crate::poly::buffer_pool::BufferPool;
 use crate::poly::eq_poly::EqPolynomial;
-use crate::utils::thread::{drop_in_background_thread, unsafe_allocate_zero_vec};
+use crate::utils::thread::unsafe_allocate_zero_vec;
 use crate::utils::{self, compute_dotproduct, compute_dotproduct_low_optimized};
 
-use crate::field::JoltField;
+use crate::field::{JoltField, OptimizedMul};
 use crate::utils::math::Math;
+use crate::utils::par::{self, prelude::*};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use core::ops::Index;
 use rand_core::{CryptoRng, RngCore};
-use rayon::prelude::*;
+
+/// The order in which the variables of a multilinear polynomial are bound, e.g. over the
+/// rounds of a sumcheck.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BindingOrder {
+    /// Bind the most significant variable first, i.e. the first half of the evaluations is
+    /// paired with the second half (see [`DensePolynomial::bound_poly_var_top`]).
+    #[default]
+    HighToLow,
+    /// Bind the least significant variable first, i.e. adjacent evaluations are paired
+    /// (see [`DensePolynomial::bound_poly_var_bot`]). This suits layouts where the variable
+    /// bound first is the innermost index of the data, e.g. step-major witness data.
+    LowToHigh,
+}
 
 #[derive(Default, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct DensePolynomial<F: JoltField> {
@@ -46,6 +61,39 @@ impl<F: JoltField> DensePolynomial<F> {
         }
     }
 
+    /// Computes the linear combination of `polynomials` with the given coefficients. The
+    /// polynomials may be of different sizes: smaller ones are padded with zeros.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "DensePolynomial::linear_combination")
+    )]
+    pub fn linear_combination(polynomials: &[&Self], coeffs: &[F]) -> Self {
+        assert_eq!(polynomials.len(), coeffs.len());
+        let max_len = polynomials.iter().map(|poly| poly.len()).max().unwrap();
+        // Only use as many chunks as there are threads, or the total number of evaluations
+        let num_chunks = par::current_num_threads().next_power_of_two().min(max_len);
+        let chunk_size = (max_len / num_chunks).max(1);
+
+        let evals: Vec<F> = (0..num_chunks)
+            .into_par_iter()
+            .flat_map_iter(|chunk_index| {
+                let index = chunk_index * chunk_size;
+
+                let mut chunk = unsafe_allocate_zero_vec(chunk_size);
+                for (coeff, poly) in coeffs.iter().zip(polynomials.iter()) {
+                    if index >= poly.len() {
+                        continue;
+                    }
+                    for (rlc, eval) in chunk.iter_mut().zip(poly.Z[index..].iter()) {
+                        *rlc += coeff.mul_01_optimized(*eval);
+                    }
+                }
+                chunk
+            })
+            .collect();
+        Self::new(evals)
+    }
+
     pub fn get_num_vars(&self) -> usize {
         self.num_vars
     }
@@ -58,6 +106,12 @@ impl<F: JoltField> DensePolynomial<F> {
         self.len == 0
     }
 
+    /// Returns the evaluations' buffer to the global [`BufferPool`], for reuse by later
+    /// polynomials rather than being freed.
+    pub fn recycle(self) {
+        BufferPool::global().recycle(self.Z);
+    }
+
     pub fn bound_poly_var_top(&mut self, r: &F) {
         let n = self.len() / 2;
         let (left, right) = self.Z.split_at_mut(n);
@@ -66,6 +120,7 @@ impl<F: JoltField> DensePolynomial<F> {
             *a += *r * (*b - *a);
         });
 
+        self.Z.truncate(n);
         self.num_vars -= 1;
         self.len = n;
     }
@@ -80,6 +135,7 @@ impl<F: JoltField> DensePolynomial<F> {
                 *a += *r * (*b - *a);
             });
 
+        self.Z.truncate(n);
         self.num_vars -= 1;
         self.len = n;
     }
@@ -100,13 +156,14 @@ impl<F: JoltField> DensePolynomial<F> {
                 }
             });
 
+        self.Z.truncate(n);
         self.num_vars -= 1;
         self.len = n;
     }
 
     /// Bounds the polynomial's most significant index bit to 'r' optimized for a
     /// high P(eval = 0).
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn bound_poly_var_top_zero_optimized(&mut self, r: &F) {
         let n = self.len() / 2;
 
@@ -119,12 +176,12 @@ impl<F: JoltField> DensePolynomial<F> {
                 *a += *r * (*b - *a);
             });
 
-        self.Z.resize(n, F::zero());
+        self.Z.truncate(n);
         self.num_vars -= 1;
         self.len = n;
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn new_poly_from_bound_poly_var_top(&self, r: &F) -> Self {
         let n = self.len() / 2;
         let mut new_evals: Vec<F> = unsafe_allocate_zero_vec(n);
@@ -148,7 +205,7 @@ impl<F: JoltField> DensePolynomial<F> {
         }
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn new_poly_from_bound_poly_var_top_flags(&self, r: &F) -> Self {
         let n = self.len() / 2;
         let mut new_evals: Vec<F> = unsafe_allocate_zero_vec(n);
@@ -188,35 +245,81 @@ impl<F: JoltField> DensePolynomial<F> {
         }
     }
 
-    /// Note: does not truncate
-    #[tracing::instrument(skip_all)]
+    /// Binds the next variable (according to `order`) to `r`.
+    pub fn bind(&mut self, r: &F, order: BindingOrder) {
+        match order {
+            BindingOrder::HighToLow => self.bound_poly_var_top_par(r),
+            BindingOrder::LowToHigh => self.bound_poly_var_bot_par(r),
+        }
+    }
+
+    /// Returns the evaluations `(p(0, x), p(1, x))` of the variable that `order` binds next,
+    /// at the `i`-th assignment `x` of the remaining variables.
+    #[inline(always)]
+    pub fn sumcheck_evals_pair(&self, i: usize, order: BindingOrder) -> (F, F) {
+        match order {
+            BindingOrder::HighToLow => (self.Z[i], self.Z[i + self.len / 2]),
+            BindingOrder::LowToHigh => (self.Z[2 * i], self.Z[2 * i + 1]),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn bound_poly_var_bot(&mut self, r: &F) {
         let n = self.len() / 2;
         for i in 0..n {
             self.Z[i] = self.Z[2 * i] + *r * (self.Z[2 * i + 1] - self.Z[2 * i]);
         }
 
+        self.Z.truncate(n);
         self.num_vars -= 1;
         self.len = n;
     }
 
+    pub fn bound_poly_var_bot_par(&mut self, r: &F) {
+        self.bind_bot_in_place(|low, high| low + *r * (high - low));
+    }
+
     pub fn bound_poly_var_bot_01_optimized(&mut self, r: &F) {
-        let n = self.len() / 2;
-        let mut new_z = unsafe_allocate_zero_vec(n);
-        new_z.par_iter_mut().enumerate().for_each(|(i, z)| {
-            let m = self.Z[2 * i + 1] - self.Z[2 * i];
-            *z = if m.is_zero() {
-                self.Z[2 * i]
+        self.bind_bot_in_place(|low, high| {
+            let m = high - low;
+            if m.is_zero() {
+                low
             } else if m.is_one() {
-                self.Z[2 * i] + r
+                low + r
             } else {
-                self.Z[2 * i] + *r * m
+                low + *r * m
             }
         });
+    }
 
-        let old_Z = std::mem::replace(&mut self.Z, new_z);
-        drop_in_background_thread(old_Z);
+    /// Binds the least significant variable in parallel, without reallocating: each chunk of
+    /// pairs is bound into its own first half, and the halves are then moved down together.
+    fn bind_bot_in_place<Func>(&mut self, bind_pair: Func)
+    where
+        Func: Fn(F, F) -> F + Sync,
+    {
+        const CHUNK_SIZE: usize = 1 << 12;
+        let n = self.len() / 2;
+        let chunk_size = CHUNK_SIZE.min(self.len());
+
+        self.Z[..self.len]
+            .par_chunks_mut(chunk_size)
+            .for_each(|chunk| {
+                for i in 0..chunk.len() / 2 {
+                    chunk[i] = bind_pair(chunk[2 * i], chunk[2 * i + 1]);
+                }
+            });
+        // The destination of each chunk's half precedes its source, and follows the earlier
+        // chunks' destinations
+        let half_chunk = chunk_size / 2;
+        for chunk in 1..self.len / chunk_size {
+            self.Z.copy_within(
+                chunk * chunk_size..chunk * chunk_size + half_chunk,
+                chunk * half_chunk,
+            );
+        }
 
+        self.Z.truncate(n);
         self.num_vars -= 1;
         self.len = n;
     }
@@ -230,6 +333,68 @@ impl<F: JoltField> DensePolynomial<F> {
         compute_dotproduct(&self.Z, &chis)
     }
 
+    /// Evaluates at `r` the polynomial whose evaluations are the concatenation of `chunks`,
+    /// e.g. read in turn from a memory-mapped trace file, holding only one chunk in memory.
+    ///
+    /// The chunks must have the same power-of-two length `2^k`. As chunk `j` holds the
+    /// evaluations whose top variables are `j`, the result is
+    /// `sum_j eq(r_high, j) * <chunk_j, eq(r_low, .)>`, with `r_low` the last `k` entries of `r`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "DensePolynomial::evaluate_streaming")
+    )]
+    pub fn evaluate_streaming<C, I>(chunks: I, r: &[F]) -> F
+    where
+        C: AsRef<[F]>,
+        I: IntoIterator<Item = C>,
+    {
+        let mut chunks = chunks.into_iter().peekable();
+        let chunk_len = chunks.peek().map_or(1, |chunk| chunk.as_ref().len());
+        assert!(
+            chunk_len.is_power_of_two() && chunk_len.log_2() <= r.len(),
+            "chunks of length {chunk_len} cannot make up a polynomial of {} variables",
+            r.len()
+        );
+        let (r_high, r_low) = r.split_at(r.len() - chunk_len.log_2());
+        let eq_low = EqPolynomial::evals(r_low);
+
+        let mut num_chunks = 0;
+        let mut eval = F::zero();
+        for (j, chunk) in chunks.enumerate() {
+            let chunk = chunk.as_ref();
+            assert_eq!(chunk.len(), chunk_len, "chunk {j} has a different length");
+            // eq(r_high, j), with r_high[0] as j's most significant bit
+            let eq_high: F = r_high
+                .iter()
+                .enumerate()
+                .map(|(i, r_i)| {
+                    if (j >> (r_high.len() - 1 - i)) & 1 == 1 {
+                        *r_i
+                    } else {
+                        F::one() - r_i
+                    }
+                })
+                .product();
+            eval += eq_high * compute_dotproduct(chunk, &eq_low);
+            num_chunks += 1;
+        }
+        assert_eq!(num_chunks, r_high.len().pow2(), "wrong number of chunks");
+        eval
+    }
+
+    /// Evaluates the polynomial at `r`, where `r[i]` is the value of the `i`-th variable
+    /// bound according to `order`. `evaluate_with_order(r, BindingOrder::HighToLow)` is
+    /// the same as `evaluate(r)`.
+    pub fn evaluate_with_order(&self, r: &[F], order: BindingOrder) -> F {
+        match order {
+            BindingOrder::HighToLow => self.evaluate(r),
+            BindingOrder::LowToHigh => {
+                let r_rev: Vec<F> = r.iter().rev().cloned().collect();
+                self.evaluate(&r_rev)
+            }
+        }
+    }
+
     pub fn evaluate_at_chi(&self, chis: &[F]) -> F {
         compute_dotproduct(&self.Z, chis)
     }
@@ -247,7 +412,10 @@ impl<F: JoltField> DensePolynomial<F> {
         self.Z.as_ref()
     }
 
-    #[tracing::instrument(skip_all, name = "DensePolynomial::from")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "DensePolynomial::from")
+    )]
     pub fn from_usize(Z: &[usize]) -> Self {
         DensePolynomial::new(
             (0..Z.len())
@@ -256,7 +424,10 @@ impl<F: JoltField> DensePolynomial<F> {
         )
     }
 
-    #[tracing::instrument(skip_all, name = "DensePolynomial::from")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "DensePolynomial::from")
+    )]
     pub fn from_u64(Z: &[u64]) -> Self {
         DensePolynomial::new(
             (0..Z.len())
@@ -296,6 +467,8 @@ mod tests {
     use super::*;
     use ark_bn254::Fr;
     use ark_std::test_rng;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
 
     fn evaluate_with_LR<F: JoltField>(Z: &[F], r: &[F]) -> F {
         let ell = r.len();
@@ -471,6 +644,64 @@ mod tests {
         assert_eq!(R, R2);
     }
 
+    #[test]
+    fn in_place_binding() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        // Spans several chunks of the parallel bottom binding
+        let poly = DensePolynomial::<Fr>::random(14, &mut rng);
+        let r: Vec<Fr> = (0..3).map(|_| Fr::random(&mut rng)).collect();
+
+        let (mut serial, mut parallel, mut optimized) = (poly.clone(), poly.clone(), poly.clone());
+        let ptr = parallel.Z.as_ptr();
+        for r in r.iter() {
+            serial.bound_poly_var_bot(r);
+            parallel.bound_poly_var_bot_par(r);
+            optimized.bound_poly_var_bot_01_optimized(r);
+        }
+        assert_eq!(parallel, serial);
+        assert_eq!(optimized, serial);
+        assert_eq!(parallel.Z.len(), 1 << 11);
+        assert_eq!(parallel.Z.as_ptr(), ptr);
+
+        let (mut top, mut top_par) = (poly.clone(), poly.clone());
+        for r in r.iter() {
+            top.bound_poly_var_top(r);
+            top_par.bound_poly_var_top_par(r);
+        }
+        assert_eq!(top, top_par);
+        assert_eq!(top.Z.len(), 1 << 11);
+    }
+
+    #[test]
+    fn streaming_evaluation() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let poly = DensePolynomial::<Fr>::random(6, &mut rng);
+        let r: Vec<Fr> = (0..6).map(|_| Fr::random(&mut rng)).collect();
+        let eval = poly.evaluate(&r);
+        for chunk_len in [1, 4, 64] {
+            let chunks = poly
+                .evals_ref()
+                .chunks(chunk_len)
+                .map(|chunk| chunk.to_vec());
+            assert_eq!(DensePolynomial::evaluate_streaming(chunks, &r), eval);
+        }
+    }
+
+    #[test]
+    fn binding_order() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let poly = DensePolynomial::<Fr>::random(4, &mut rng);
+        let r: Vec<Fr> = (0..4).map(|_| Fr::random(&mut rng)).collect();
+        for order in [BindingOrder::HighToLow, BindingOrder::LowToHigh] {
+            let mut bound = poly.clone();
+            for r_i in r.iter() {
+                bound.bind(r_i, order);
+            }
+            assert_eq!(bound.len(), 1);
+            assert_eq!(bound[0], poly.evaluate_with_order(&r, order));
+        }
+    }
+
     #[test]
     fn evaluation() {
         let num_evals = 4;
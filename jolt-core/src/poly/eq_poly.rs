@@ -1,7 +1,9 @@
 use crate::field::JoltField;
-use rayon::prelude::*;
+use crate::utils::par::prelude::*;
 
+use crate::poly::buffer_pool::BufferPool;
 use crate::utils::{math::Math, thread::unsafe_allocate_zero_vec};
+use crate::verifier::eq_eval;
 
 pub struct EqPolynomial<F> {
     r: Vec<F>,
@@ -15,25 +17,30 @@ impl<F: JoltField> EqPolynomial<F> {
     }
 
     pub fn evaluate(&self, rx: &[F]) -> F {
-        assert_eq!(self.r.len(), rx.len());
-        (0..rx.len())
-            .map(|i| self.r[i] * rx[i] + (F::one() - self.r[i]) * (F::one() - rx[i]))
-            .product()
+        eq_eval(&self.r, rx)
     }
 
-    #[tracing::instrument(skip_all, name = "EqPolynomial::evals")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "EqPolynomial::evals")
+    )]
     pub fn evals(r: &[F]) -> Vec<F> {
+        Self::evals_with_scaling(r, F::one())
+    }
+
+    /// Computes `scaling_factor * eq(r, x)` for all `x`, for the cost of `evals`.
+    pub fn evals_with_scaling(r: &[F], scaling_factor: F) -> Vec<F> {
         let ell = r.len();
 
         match ell {
-            0..=PARALLEL_THRESHOLD => Self::evals_serial(r, ell),
-            _ => Self::evals_parallel(r, ell),
+            0..=PARALLEL_THRESHOLD => Self::evals_serial(r, ell, scaling_factor),
+            _ => Self::evals_split(r, scaling_factor),
         }
     }
 
     /// Computes evals serially. Uses less memory (and fewer allocations) than `evals_parallel`.
-    fn evals_serial(r: &[F], ell: usize) -> Vec<F> {
-        let mut evals: Vec<F> = vec![F::one(); ell.pow2()];
+    fn evals_serial(r: &[F], ell: usize, scaling_factor: F) -> Vec<F> {
+        let mut evals: Vec<F> = vec![scaling_factor; ell.pow2()];
         let mut size = 1;
         for j in 0..ell {
             // in each iteration, we double the size of chis
@@ -48,9 +55,36 @@ impl<F: JoltField> EqPolynomial<F> {
         evals
     }
 
+    /// Computes evals as the tensor product of the evals of the two halves of `r`, each of
+    /// which fits in cache, writing each entry of the result once with a single multiplication.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "EqPolynomial::evals_split")
+    )]
+    fn evals_split(r: &[F], scaling_factor: F) -> Vec<F> {
+        let (r_high, r_low) = r.split_at(r.len() / 2);
+        // The scaling factor is folded into the smaller table
+        let high = Self::evals_serial(r_high, r_high.len(), scaling_factor);
+        let low = Self::evals_serial(r_low, r_low.len(), F::one());
+
+        let mut evals: Vec<F> = BufferPool::global().take(r.len().pow2());
+        evals
+            .par_chunks_mut(low.len())
+            .zip(high.par_iter())
+            .for_each(|(chunk, high)| {
+                for (eval, low) in chunk.iter_mut().zip(low.iter()) {
+                    *eval = *high * *low;
+                }
+            });
+        evals
+    }
+
     /// Computes evals in parallel. Uses more memory and allocations than `evals_serial`, but
     /// evaluates biggest layers of the dynamic programming tree in parallel.
-    #[tracing::instrument(skip_all, "EqPolynomial::evals_parallel")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, "EqPolynomial::evals_parallel")
+    )]
     pub fn evals_parallel(r: &[F], ell: usize) -> Vec<F> {
         let final_size = (2usize).pow(ell as u32);
         let mut evals: Vec<F> = unsafe_allocate_zero_vec(final_size);
@@ -75,7 +109,10 @@ impl<F: JoltField> EqPolynomial<F> {
         evals
     }
 
-    #[tracing::instrument(skip_all, name = "EqPolynomial::compute_factored_evals")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "EqPolynomial::compute_factored_evals")
+    )]
     pub fn compute_factored_evals(&self, L_size: usize) -> (Vec<F>, Vec<F>) {
         let ell = self.r.len();
         let left_num_vars = L_size.log_2();
@@ -86,3 +123,36 @@ impl<F: JoltField> EqPolynomial<F> {
         (L, R)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_std::test_rng;
+
+    #[test]
+    fn evals_strategies_agree() {
+        let mut rng = test_rng();
+        let factor = Fr::random(&mut rng);
+        for ell in [0, 1, 5, PARALLEL_THRESHOLD + 1, PARALLEL_THRESHOLD + 2] {
+            let r: Vec<Fr> = (0..ell).map(|_| Fr::random(&mut rng)).collect();
+            let serial = EqPolynomial::evals_serial(&r, ell, Fr::from(1u64));
+            assert_eq!(serial, EqPolynomial::evals_parallel(&r, ell));
+            assert_eq!(serial, EqPolynomial::evals(&r));
+
+            let scaled = EqPolynomial::evals_with_scaling(&r, factor);
+            assert_eq!(
+                scaled,
+                serial.iter().map(|eval| *eval * factor).collect::<Vec<_>>()
+            );
+        }
+
+        // The first variable is the most significant
+        let r = vec![Fr::from(3u64), Fr::from(5u64)];
+        let evals = EqPolynomial::evals(&r);
+        assert_eq!(
+            evals[2],
+            EqPolynomial::new(r).evaluate(&[Fr::from(1u64), Fr::from(0u64)])
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Masking polynomials for sum-checks with hidden round polynomials.
+//!
+//! To hide the round polynomials of the sum-check of some `f`, the prover commits to a random
+//! `g(x) = sum_i g_i(x_i)` and its sum `G` over the hypercube, and proves
+//! `sum_x f(x) + rho * g(x) = claim + rho * G` for a challenge `rho` instead. Each `g_i` has the
+//! degree of the round polynomials, which are thus masked by those of `g`. At the end of the
+//! sum-check, the `g_i(r_i)` are opened from the commitment.
+//!
+//! The coefficients are committed to as a single multilinear polynomial: `coeffs[i * s + k]` is
+//! the coefficient of `x^k` in `g_i`, where `s = 2^m` is the smallest power of two above the
+//! degree. Its evaluation at `(bits(i), z_(m - 1), ..., z_0)`, where
+//! `z_j = r_i^(2^j) / (1 + r_i^(2^j))`, is `g_i(r_i) / prod_j (1 + r_i^(2^j))`.
+use rand_core::RngCore;
+
+use crate::field::JoltField;
+use crate::utils::{index_to_field_bitvector, math::Math};
+
+use super::{dense_mlpoly::DensePolynomial, unipoly::UniPoly};
+
+#[derive(Clone, Debug)]
+pub struct MaskingPolynomial<F: JoltField> {
+    num_vars: usize,
+    degree: usize,
+    coeffs: Vec<F>,
+}
+
+impl<F: JoltField> MaskingPolynomial<F> {
+    pub fn random<R: RngCore>(num_vars: usize, degree: usize, rng: &mut R) -> Self {
+        assert!(num_vars > 0);
+        let num_slots = Self::num_slots(degree);
+        let mut coeffs = vec![F::zero(); num_vars.next_power_of_two() * num_slots];
+        for univariate in coeffs.chunks_mut(num_slots).take(num_vars) {
+            for coeff in univariate[..=degree].iter_mut() {
+                *coeff = F::random(rng);
+            }
+        }
+        Self {
+            num_vars,
+            degree,
+            coeffs,
+        }
+    }
+
+    fn num_slots(degree: usize) -> usize {
+        (degree + 1).next_power_of_two()
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// The coefficients of the univariates, as committed to.
+    pub fn coeffs_poly(&self) -> DensePolynomial<F> {
+        DensePolynomial::new(self.coeffs.clone())
+    }
+
+    /// Evaluates `g_i(x)`.
+    pub fn evaluate_univariate(&self, i: usize, x: &F) -> F {
+        let num_slots = Self::num_slots(self.degree);
+        UniPoly::eval_with_coeffs(&self.coeffs[i * num_slots..(i + 1) * num_slots], x)
+    }
+
+    /// Returns `g_i(r_i)` for every variable `i`; their sum is `g(r)`.
+    pub fn evaluate_univariates(&self, r: &[F]) -> Vec<F> {
+        assert_eq!(r.len(), self.num_vars);
+        r.iter()
+            .enumerate()
+            .map(|(i, r_i)| self.evaluate_univariate(i, r_i))
+            .collect()
+    }
+
+    /// `g_i(0) + g_i(1)`, the sum of `g_i` over its variable.
+    fn univariate_sum(&self, i: usize) -> F {
+        self.evaluate_univariate(i, &F::zero()) + self.evaluate_univariate(i, &F::one())
+    }
+
+    /// The sum of `g` over the hypercube, `2^(n - 1) * sum_i (g_i(0) + g_i(1))`.
+    pub fn sum(&self) -> F {
+        let sum: F = (0..self.num_vars).map(|i| self.univariate_sum(i)).sum();
+        sum * F::from_u64(1 << (self.num_vars - 1)).unwrap()
+    }
+
+    /// Evaluates the round polynomial of `g` at `0, 1, ..., degree`, for the round following those
+    /// bound to `r`.
+    pub fn round_evals(&self, r: &[F]) -> Vec<F> {
+        let round = r.len();
+        assert!(round < self.num_vars);
+        let num_free_vars = self.num_vars - round - 1;
+
+        let bound: F = r
+            .iter()
+            .enumerate()
+            .map(|(i, r_i)| self.evaluate_univariate(i, r_i))
+            .sum();
+        // Each free univariate is summed over the other free variables
+        let free = if num_free_vars == 0 {
+            F::zero()
+        } else {
+            (round + 1..self.num_vars)
+                .map(|i| self.univariate_sum(i))
+                .sum::<F>()
+                * F::from_u64(1 << (num_free_vars - 1)).unwrap()
+        };
+        let scale = F::from_u64(1 << num_free_vars).unwrap();
+
+        (0..=self.degree as u64)
+            .map(|x| {
+                let x = F::from_u64(x).unwrap();
+                (bound + self.evaluate_univariate(round, &x)) * scale + free
+            })
+            .collect()
+    }
+
+    /// The point at which to open the committed coefficients of a masking polynomial with
+    /// `num_vars` variables and the given `degree` for `g_i(r_i) = eval`, and their evaluation
+    /// there.
+    pub fn opening(num_vars: usize, degree: usize, i: usize, r_i: F, eval: F) -> (Vec<F>, F) {
+        let mut point = index_to_field_bitvector(i, num_vars.next_power_of_two().log_2());
+        // The multilinear extension weighs x^k by prod_j z_j^(k_j) * (1 - z_j)^(1 - k_j), which
+        // is r_i^k / prod_j (1 + r_i^(2^j)) for these z_j
+        let mut scale = F::one();
+        let mut z: Vec<F> = std::iter::successors(Some(r_i), |power| Some(power.square()))
+            .take(Self::num_slots(degree).log_2())
+            .map(|power| {
+                let denominator = F::one() + power;
+                scale *= denominator;
+                power
+                    * denominator
+                        .inverse()
+                        .expect("the sum-check point is a root of 1 + x^(2^j)")
+            })
+            .collect();
+        z.reverse();
+        point.extend(z);
+        let scale_inverse = scale
+            .inverse()
+            .expect("the scale is a product of non-zero terms");
+        (point, eval * scale_inverse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_std::{test_rng, One, UniformRand, Zero};
+
+    #[test]
+    fn sums_and_openings() {
+        let mut rng = test_rng();
+        let num_vars = 3;
+        let degree = 3;
+        let mask = MaskingPolynomial::<Fr>::random(num_vars, degree, &mut rng);
+
+        // Brute-force the sum over the hypercube
+        let expected_sum: Fr = (0..1 << num_vars)
+            .map(|x: usize| {
+                (0..num_vars)
+                    .map(|i| {
+                        let x_i = if (x >> i) & 1 == 1 {
+                            Fr::one()
+                        } else {
+                            Fr::zero()
+                        };
+                        mask.evaluate_univariate(i, &x_i)
+                    })
+                    .sum::<Fr>()
+            })
+            .sum();
+        assert_eq!(mask.sum(), expected_sum);
+
+        let r: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+        let mut claim = mask.sum();
+        for round in 0..num_vars {
+            let evals = mask.round_evals(&r[..round]);
+            assert_eq!(evals.len(), degree + 1);
+            assert_eq!(evals[0] + evals[1], claim);
+            claim = UniPoly::from_evals(&evals).evaluate(&r[round]);
+        }
+        let univariate_evals = mask.evaluate_univariates(&r);
+        assert_eq!(univariate_evals.iter().sum::<Fr>(), claim);
+
+        let coeffs = mask.coeffs_poly();
+        for (i, (r_i, eval)) in r.iter().zip(univariate_evals.iter()).enumerate() {
+            let (point, opening) =
+                MaskingPolynomial::<Fr>::opening(num_vars, degree, i, *r_i, *eval);
+            assert_eq!(coeffs.evaluate(&point), opening);
+        }
+
+        // Degree 2 univariates take as many slots as degree 3 ones
+        let mask = MaskingPolynomial::<Fr>::random(num_vars, 2, &mut rng);
+        let coeffs = mask.coeffs_poly();
+        for (i, (r_i, eval)) in r.iter().zip(mask.evaluate_univariates(&r)).enumerate() {
+            let (point, opening) = MaskingPolynomial::<Fr>::opening(num_vars, 2, i, *r_i, eval);
+            assert_eq!(coeffs.evaluate(&point), opening);
+        }
+    }
+}
@@ -1,8 +1,10 @@
+pub mod buffer_pool;
 pub mod commitment;
 pub mod dense_interleaved_poly;
 pub mod dense_mlpoly;
 pub mod eq_poly;
 pub mod identity_poly;
+pub mod masking_poly;
 pub mod opening_proof;
 pub mod sparse_interleaved_poly;
 pub mod split_eq_poly;
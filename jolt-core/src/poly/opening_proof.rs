@@ -1,15 +1,23 @@
 //! This is a port of the sumcheck-based batch opening proof protocol implemented
 //! in Nova: https://github.com/microsoft/Nova/blob/2772826ba296b66f1cd5deecf7aca3fd1d10e1f4/src/spartan/snark.rs#L410-L424
-//! For additively homomorphic commitment schemes (including Zeromorph, HyperKZG) we
-//! can use a sumcheck to reduce multiple opening proofs (multiple polynomials, not
-//! necessarily of the same size, each opened at a different point) into a single opening.
-
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use rayon::prelude::*;
+//! We use a sumcheck to reduce multiple opening proofs (multiple polynomials, not
+//! necessarily of the same size, each opened at a different point) into a single opening
+//! of a linear combination of the polynomials. For additively homomorphic commitment
+//! schemes (including Zeromorph, HyperKZG) this is an opening of a single polynomial,
+//! against the combination of the commitments. Other schemes (e.g. Ligero) instead open
+//! each of the committed polynomials, so the prover's accumulator retains them.
+
+use crate::utils::par::{self, prelude::*};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use std::io::{Read, Write};
 use std::marker::PhantomData;
 
 use super::{
-    commitment::commitment_scheme::CommitmentScheme,
+    commitment::commitment_scheme::{
+        AdditivelyHomomorphic, AggregatableCommitmentScheme, CommitmentScheme,
+    },
     dense_mlpoly::DensePolynomial,
     eq_poly::EqPolynomial,
     unipoly::{CompressedUniPoly, UniPoly},
@@ -43,10 +51,12 @@ pub struct ProverOpening<F: JoltField> {
     pub opening_point: Vec<F>,
     /// The claimed opening.
     pub claim: F,
-    #[cfg(test)]
-    /// If this is a batched opening, this `Vec` contains the individual
+    /// If this is a batched opening and the accumulator retains the opened polynomials
+    /// (see [`ProverOpeningAccumulator::for_scheme`]), this `Vec` contains the individual
     /// polynomials in the batch.
     batch: Vec<DensePolynomial<F>>,
+    /// The coefficients of the polynomials in `batch` in `polynomial`.
+    batch_coeffs: Vec<F>,
 }
 
 /// An opening that the verifier must verify.
@@ -62,10 +72,10 @@ where
     PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 {
-    /// The commitments to the opened polynomial. May be a random linear combination
-    /// of multiple (additively homomorphic) polynomials, all being opened at the
-    /// same point.
-    pub commitment: PCS::Commitment,
+    /// The commitments to the opened polynomials. The opened polynomial is their
+    /// linear combination with coefficients `coeffs`.
+    pub commitments: Vec<PCS::Commitment>,
+    pub coeffs: Vec<F>,
     /// The point at which the polynomial is being evaluated.
     pub opening_point: Vec<F>,
     /// The claimed opening.
@@ -84,21 +94,82 @@ impl<F: JoltField> ProverOpening<F> {
             eq_poly,
             opening_point,
             claim,
-            #[cfg(test)]
             batch: vec![],
+            batch_coeffs: vec![],
         }
     }
 }
 
+// Serialized for prover checkpoints. `eq_poly` is not serialized, since it is determined by
+// `opening_point`.
+impl<F: JoltField> CanonicalSerialize for ProverOpening<F> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.polynomial.serialize_with_mode(&mut writer, compress)?;
+        self.opening_point
+            .serialize_with_mode(&mut writer, compress)?;
+        self.claim.serialize_with_mode(&mut writer, compress)?;
+        self.batch.serialize_with_mode(&mut writer, compress)?;
+        self.batch_coeffs
+            .serialize_with_mode(&mut writer, compress)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.polynomial.serialized_size(compress)
+            + self.opening_point.serialized_size(compress)
+            + self.claim.serialized_size(compress)
+            + self.batch.serialized_size(compress)
+            + self.batch_coeffs.serialized_size(compress)
+    }
+}
+
+impl<F: JoltField> Valid for ProverOpening<F> {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl<F: JoltField> CanonicalDeserialize for ProverOpening<F> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let polynomial = DensePolynomial::deserialize_with_mode(&mut reader, compress, validate)?;
+        let opening_point = Vec::<F>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let claim = F::deserialize_with_mode(&mut reader, compress, validate)?;
+        let batch = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let batch_coeffs = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(ProverOpening {
+            polynomial,
+            eq_poly: DensePolynomial::new(EqPolynomial::evals(&opening_point)),
+            opening_point,
+            claim,
+            batch,
+            batch_coeffs,
+        })
+    }
+}
+
 impl<F, PCS, ProofTranscript> VerifierOpening<F, PCS, ProofTranscript>
 where
     F: JoltField,
     PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 {
-    fn new(commitment: PCS::Commitment, opening_point: Vec<F>, claim: F) -> Self {
+    fn new(
+        commitments: Vec<PCS::Commitment>,
+        coeffs: Vec<F>,
+        opening_point: Vec<F>,
+        claim: F,
+    ) -> Self {
         VerifierOpening {
-            commitment,
+            commitments,
+            coeffs,
             opening_point,
             claim,
         }
@@ -109,6 +180,8 @@ where
 /// so that they can all be reduced to a single opening proof using sumcheck.
 pub struct ProverOpeningAccumulator<F: JoltField, ProofTranscript: Transcript> {
     openings: Vec<ProverOpening<F>>,
+    /// Whether to retain the polynomials opened, as well as their linear combination.
+    retain_polynomials: bool,
     _marker: PhantomData<ProofTranscript>,
 }
 
@@ -148,10 +221,64 @@ impl<F: JoltField, ProofTranscript: Transcript> Default
     }
 }
 
+impl<F: JoltField, ProofTranscript: Transcript> CanonicalSerialize
+    for ProverOpeningAccumulator<F, ProofTranscript>
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.openings.serialize_with_mode(&mut writer, compress)?;
+        self.retain_polynomials
+            .serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.openings.serialized_size(compress) + self.retain_polynomials.serialized_size(compress)
+    }
+}
+
+impl<F: JoltField, ProofTranscript: Transcript> Valid
+    for ProverOpeningAccumulator<F, ProofTranscript>
+{
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl<F: JoltField, ProofTranscript: Transcript> CanonicalDeserialize
+    for ProverOpeningAccumulator<F, ProofTranscript>
+{
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self {
+            openings: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            retain_polynomials: bool::deserialize_with_mode(&mut reader, compress, validate)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
 impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, ProofTranscript> {
     pub fn new() -> Self {
         Self {
             openings: vec![],
+            retain_polynomials: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an empty accumulator whose openings can be proven with `PCS`. Unlike
+    /// [`ProverOpeningAccumulator::new`], this retains the opened polynomials if `PCS`
+    /// opens each commitment (see [`CommitmentScheme::OPENS_EACH_COMMITMENT`]).
+    pub fn for_scheme<PCS: CommitmentScheme<ProofTranscript, Field = F>>() -> Self {
+        Self {
+            openings: vec![],
+            retain_polynomials: PCS::OPENS_EACH_COMMITMENT,
             _marker: PhantomData,
         }
     }
@@ -160,13 +287,22 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
         self.openings.len()
     }
 
+    /// Appends the openings accumulated by `other`, e.g. by a stage of the prover
+    /// that was run on its own accumulator.
+    pub fn extend(&mut self, other: Self) {
+        self.openings.extend(other.openings);
+    }
+
     /// Adds openings to the accumulator. The given `polynomials` are opened at
     /// `opening_point`, yielding the claimed evaluations `claims`. `eq_poly` is
     /// the multilinear extension EQ(x, opening_point), which is typically an
     /// intermediate value in computing `claims`. Multiple polynomials opened at
     /// a single point can be batched into a single polynomial opened at the same
     /// point. This function performs this batching before appending to `self.openings`.
-    #[tracing::instrument(skip_all, name = "ProverOpeningAccumulator::append")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ProverOpeningAccumulator::append")
+    )]
     pub fn append(
         &mut self,
         polynomials: &[&DensePolynomial<F>],
@@ -210,7 +346,7 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
         // Compute the random linear combination of the polynomials
         let total_evals_len = 1 << opening_point.len();
         // Only use as many chunks as there are threads, or the total number of evaluations
-        let num_chunks = rayon::current_num_threads()
+        let num_chunks = par::current_num_threads()
             .next_power_of_two()
             .min(total_evals_len);
         let chunk_size = (total_evals_len / num_chunks).max(1);
@@ -235,25 +371,25 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
             .collect::<Vec<_>>();
         let batched_poly = DensePolynomial::new(f_batched);
 
-        #[cfg(test)]
-        {
-            let mut opening =
-                ProverOpening::new(batched_poly, eq_poly, opening_point, batched_claim);
-            for poly in polynomials.into_iter() {
-                opening.batch.push(DensePolynomial::clone(poly));
-            }
-            self.openings.push(opening);
-        }
-        #[cfg(not(test))]
-        {
-            let opening = ProverOpening::new(batched_poly, eq_poly, opening_point, batched_claim);
-            self.openings.push(opening);
+        let mut opening = ProverOpening::new(batched_poly, eq_poly, opening_point, batched_claim);
+        // In testing, the polynomials are retained so that the verifier can compare its
+        // openings to the prover's (see `VerifierOpeningAccumulator::compare_to`)
+        if self.retain_polynomials || cfg!(test) {
+            opening.batch = polynomials
+                .iter()
+                .map(|poly| DensePolynomial::clone(poly))
+                .collect();
+            opening.batch_coeffs = rho_powers;
         }
+        self.openings.push(opening);
     }
 
     /// Reduces the multiple openings accumulated into a single opening proof,
     /// using a single sumcheck.
-    #[tracing::instrument(skip_all, name = "ProverOpeningAccumulator::reduce_and_prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ProverOpeningAccumulator::reduce_and_prove")
+    )]
     pub fn reduce_and_prove<PCS: CommitmentScheme<ProofTranscript, Field = F>>(
         &mut self,
         pcs_setup: &PCS::Setup,
@@ -278,44 +414,40 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
             gamma_powers.push(gamma_powers[i - 1] * gamma);
         }
 
-        let max_len = self
-            .openings
-            .iter()
-            .map(|opening| opening.polynomial.len())
-            .max()
-            .unwrap();
-        // Only use as many chunks as there are threads, or the total number of evaluations
-        let num_chunks = rayon::current_num_threads()
-            .next_power_of_two()
-            .min(max_len);
-        let chunk_size = (max_len / num_chunks).max(1);
-
-        // Compute random linear combination of the polynomials, accounting for the fact
-        // that they may be of different sizes
-        let joint_poly: Vec<F> = (0..num_chunks)
-            .into_par_iter()
-            .flat_map_iter(|chunk_index| {
-                let index = chunk_index * chunk_size;
-
-                let mut chunk = unsafe_allocate_zero_vec(chunk_size);
-                for (coeff, opening) in gamma_powers.iter().zip(self.openings.iter()) {
-                    let poly_len = opening.polynomial.Z.len();
-                    if index >= poly_len {
-                        continue;
-                    }
-                    let poly_evals = &opening.polynomial.Z[index..];
-
-                    for (rlc, poly_eval) in chunk.iter_mut().zip(poly_evals.iter()) {
-                        *rlc += coeff.mul_01_optimized(*poly_eval);
-                    }
-                }
-                chunk
-            })
-            .collect();
-        let joint_poly = DensePolynomial::new(joint_poly);
-
-        // Reduced opening proof
-        let joint_opening_proof = PCS::prove(pcs_setup, &joint_poly, &r_sumcheck, transcript);
+        // Reduced opening proof, of the random linear combination of the polynomials
+        let joint_opening_proof = if PCS::OPENS_EACH_COMMITMENT {
+            // The commitments the verifier holds are to the polynomials in each batch
+            let (polynomials, coeffs): (Vec<_>, Vec<_>) = self
+                .openings
+                .iter()
+                .zip(gamma_powers.iter())
+                .flat_map(|(opening, gamma)| {
+                    assert!(
+                        !opening.batch.is_empty(),
+                        "opened polynomials were not retained; use `ProverOpeningAccumulator::for_scheme`"
+                    );
+                    opening
+                        .batch
+                        .iter()
+                        .zip(opening.batch_coeffs.iter())
+                        .map(move |(poly, rho)| (poly, *gamma * rho))
+                })
+                .unzip();
+            PCS::prove_linear_combination(pcs_setup, &polynomials, &coeffs, &r_sumcheck, transcript)
+        } else {
+            let polynomials: Vec<_> = self
+                .openings
+                .iter()
+                .map(|opening| &opening.polynomial)
+                .collect();
+            PCS::prove_linear_combination(
+                pcs_setup,
+                &polynomials,
+                &gamma_powers,
+                &r_sumcheck,
+                transcript,
+            )
+        };
 
         ReducedOpeningProof {
             sumcheck_proof,
@@ -325,7 +457,10 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
     }
 
     /// Proves the sumcheck used to prove the reduction of many openings into one.
-    #[tracing::instrument(skip_all, name = "prove_batch_opening_reduction")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "prove_batch_opening_reduction")
+    )]
     pub fn prove_batch_opening_reduction(
         &mut self,
         coeffs: &[F],
@@ -388,7 +523,10 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
 
     /// Computes the univariate (quadratic) polynomial that serves as the
     /// prover's message in each round of the sumchecj in `prove_batch_opening_reduction`.
-    #[tracing::instrument(skip_all, name = "compute_quadratic")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "compute_quadratic")
+    )]
     fn compute_quadratic(
         &self,
         coeffs: &[F],
@@ -444,7 +582,7 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
     /// In the 0th round, `bound_polys` is all `None`, and is overwritten
     /// with the results of binding the polynomials in `self.openings`.
     /// In subsequent rounds, `bound_polys` is itself bound to `r_j`.
-    #[tracing::instrument(skip_all, name = "bind")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "bind"))]
     fn bind(
         &mut self,
         remaining_sumcheck_rounds: usize,
@@ -458,13 +596,13 @@ impl<F: JoltField, ProofTranscript: Transcript> ProverOpeningAccumulator<F, Proo
                 if remaining_sumcheck_rounds <= opening.opening_point.len() {
                     match bound_poly {
                         Some(bound_poly) => {
-                            rayon::join(
+                            par::join(
                                 || opening.eq_poly.bound_poly_var_top(&r_j),
                                 || bound_poly.bound_poly_var_top(&r_j),
                             );
                         }
                         None => {
-                            *bound_poly = rayon::join(
+                            *bound_poly = par::join(
                                 || opening.eq_poly.bound_poly_var_top(&r_j),
                                 || Some(opening.polynomial.new_poly_from_bound_poly_var_top(&r_j)),
                             )
@@ -524,8 +662,7 @@ where
     /// `claims`.
     /// Multiple polynomials opened at a single point can be batched into a single
     /// polynomial opened at the same point. This function performs the verifier side
-    /// of this batching by homomorphically combining the commitments before appending
-    /// to `self.openings`.
+    /// of this batching, by recording the coefficients the commitments are combined with.
     pub fn append(
         &mut self,
         commitments: &[&PCS::Commitment],
@@ -546,8 +683,6 @@ where
             .map(|(scalar, eval)| *scalar * *eval)
             .sum();
 
-        let joint_commitment = PCS::combine_commitments(commitments, &rho_powers);
-
         #[cfg(test)]
         'test: {
             if self.prover_openings.is_none() {
@@ -580,16 +715,14 @@ where
                     i
                 );
             }
-            let prover_joint_commitment =
-                PCS::commit(&prover_opening.polynomial, self.pcs_setup.as_ref().unwrap());
-            assert_eq!(
-                prover_joint_commitment, joint_commitment,
-                "joint commitment mismatch"
-            );
         }
 
         self.openings.push(VerifierOpening::new(
-            joint_commitment,
+            commitments
+                .iter()
+                .map(|commitment| (*commitment).clone())
+                .collect(),
+            rho_powers,
             opening_point,
             batched_claim,
         ));
@@ -603,6 +736,57 @@ where
         reduced_opening_proof: &ReducedOpeningProof<F, PCS, ProofTranscript>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
+        let (r_sumcheck, joint_claim, commitments, coeffs) =
+            self.reduce(reduced_opening_proof, transcript)?;
+
+        // Verify the reduced opening proof
+        PCS::verify_linear_combination(
+            &reduced_opening_proof.joint_opening_proof,
+            pcs_setup,
+            transcript,
+            &r_sumcheck,
+            &joint_claim,
+            &commitments,
+            &coeffs,
+        )
+    }
+
+    /// Same as [`VerifierOpeningAccumulator::reduce_and_verify`], but defers the final check
+    /// of the reduced opening proof to `pcs_accumulator`.
+    pub fn reduce_and_verify_deferred(
+        &self,
+        pcs_setup: &PCS::Setup,
+        reduced_opening_proof: &ReducedOpeningProof<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+        pcs_accumulator: &mut PCS::Accumulator,
+    ) -> Result<(), ProofVerifyError>
+    where
+        PCS: AggregatableCommitmentScheme<ProofTranscript>,
+    {
+        let (r_sumcheck, joint_claim, commitments, coeffs) =
+            self.reduce(reduced_opening_proof, transcript)?;
+        let joint_commitment = PCS::combine_commitments(&commitments, &coeffs);
+
+        PCS::verify_deferred(
+            &reduced_opening_proof.joint_opening_proof,
+            pcs_setup,
+            transcript,
+            &r_sumcheck,
+            &joint_claim,
+            &joint_commitment,
+            pcs_accumulator,
+        )
+    }
+
+    /// Verifies the batch opening reduction, returning the point and claim of the single
+    /// opening it reduces the accumulated openings to, and the commitments and coefficients
+    /// of the linear combination opened.
+    #[allow(clippy::type_complexity)]
+    fn reduce(
+        &self,
+        reduced_opening_proof: &ReducedOpeningProof<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(Vec<F>, F, Vec<&PCS::Commitment>, Vec<F>), ProofVerifyError> {
         let num_sumcheck_rounds = self
             .openings
             .iter()
@@ -652,15 +836,20 @@ where
             gamma_powers.push(gamma_powers[i - 1] * gamma);
         }
 
-        // Compute joint commitment = ∑ᵢ γⁱ⋅ commitmentᵢ
-        let joint_commitment = PCS::combine_commitments(
-            &self
-                .openings
-                .iter()
-                .map(|opening| &opening.commitment)
-                .collect::<Vec<_>>(),
-            &gamma_powers,
-        );
+        // The joint commitment is ∑ᵢ γⁱ⋅ commitmentᵢ, where commitmentᵢ is itself the linear
+        // combination of the commitments in the i-th opening
+        let (commitments, coeffs): (Vec<_>, Vec<_>) = self
+            .openings
+            .iter()
+            .zip(gamma_powers.iter())
+            .flat_map(|(opening, gamma)| {
+                opening
+                    .commitments
+                    .iter()
+                    .zip(opening.coeffs.iter())
+                    .map(move |(commitment, rho)| (commitment, *gamma * rho))
+            })
+            .unzip();
         // Compute joint claim = ∑ᵢ γⁱ⋅ claimᵢ
         let joint_claim: F = gamma_powers
             .iter()
@@ -675,15 +864,7 @@ where
             })
             .sum();
 
-        // Verify the reduced opening proof
-        PCS::verify(
-            &reduced_opening_proof.joint_opening_proof,
-            pcs_setup,
-            transcript,
-            &r_sumcheck,
-            &joint_claim,
-            &joint_commitment,
-        )
+        Ok((r_sumcheck, joint_claim, commitments, coeffs))
     }
 
     /// Verifies the sumcheck proven in `ProverOpeningAccumulator::prove_batch_opening_reduction`.
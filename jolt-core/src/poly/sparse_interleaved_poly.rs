@@ -2,6 +2,7 @@ use super::{
     dense_interleaved_poly::DenseInterleavedPolynomial, dense_mlpoly::DensePolynomial,
     split_eq_poly::SplitEqPolynomial, unipoly::UniPoly,
 };
+use crate::utils::par::prelude::*;
 use crate::{
     field::{JoltField, OptimizedMul},
     subprotocols::{
@@ -10,7 +11,6 @@ use crate::{
     },
     utils::{math::Math, transcript::Transcript},
 };
-use rayon::prelude::*;
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct SparseCoefficient<F: JoltField> {
@@ -119,7 +119,10 @@ impl<F: JoltField> SparseInterleavedPolynomial<F> {
         DensePolynomial::new_padded(self.coalesce())
     }
 
-    #[tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::coalesce")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::coalesce")
+    )]
     /// Coalesces a `SparseInterleavedPolynomial` into a `DenseInterleavedPolynomial`.
     pub fn coalesce(&self) -> Vec<F> {
         if let Some(coalesced) = &self.coalesced {
@@ -205,7 +208,10 @@ impl<F: JoltField> SparseInterleavedPolynomial<F> {
     ///      Λ        Λ        Λ        Λ
     ///     / \      / \      / \      /  \
     ///   L0   R0  L1   R1  L2   R2  L3   R3   <- This layer
-    #[tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::layer_output")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::layer_output")
+    )]
     pub fn layer_output(&self) -> Self {
         if let Some(coalesced) = &self.coalesced {
             Self {
@@ -271,7 +277,10 @@ impl<F: JoltField> Bindable<F> for SparseInterleavedPolynomial<F> {
     ///
     /// If `self` is not coalesced, we basically do the same thing but with the
     /// sparse vectors in `self.coeffs`, and many more cases to check 😬
-    #[tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::bind")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::bind")
+    )]
     fn bind(&mut self, r: F) {
         #[cfg(test)]
         let (mut left_before_binding, mut right_before_binding) = self.uninterleave();
@@ -447,7 +456,10 @@ impl<F: JoltField, ProofTranscript: Transcript> BatchedCubicSumcheck<F, ProofTra
     ///
     /// If `self` is not coalesced, we basically do the same thing but with with the
     /// sparse vectors in `self.coeffs`, some fancy optimizations, and many more cases to check 😬
-    #[tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::compute_cubic")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SparseInterleavedPolynomial::compute_cubic")
+    )]
     fn compute_cubic(&self, eq_poly: &SplitEqPolynomial<F>, previous_round_claim: F) -> UniPoly<F> {
         if let Some(coalesced) = &self.coalesced {
             return BatchedCubicSumcheck::<F, ProofTranscript>::compute_cubic(
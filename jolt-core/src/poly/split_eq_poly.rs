@@ -2,6 +2,7 @@
 //! https://eprint.iacr.org/2024/1210.pdf
 #[cfg(test)]
 use super::dense_mlpoly::DensePolynomial;
+use crate::utils::par;
 use crate::{field::JoltField, poly::eq_poly::EqPolynomial};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,11 +15,14 @@ pub struct SplitEqPolynomial<F> {
 }
 
 impl<F: JoltField> SplitEqPolynomial<F> {
-    #[tracing::instrument(skip_all, name = "SplitEqPolynomial::new")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SplitEqPolynomial::new")
+    )]
     pub fn new(w: &[F]) -> Self {
         let m = w.len() / 2;
         let (w2, w1) = w.split_at(m);
-        let (E2, E1) = rayon::join(|| EqPolynomial::evals(w2), || EqPolynomial::evals(w1));
+        let (E2, E1) = par::join(|| EqPolynomial::evals(w2), || EqPolynomial::evals(w1));
         let E1_len = E1.len();
         let E2_len = E2.len();
         Self {
@@ -42,7 +46,20 @@ impl<F: JoltField> SplitEqPolynomial<F> {
         }
     }
 
-    #[tracing::instrument(skip_all, name = "SplitEqPolynomial::bind")]
+    /// Returns the evaluation of the (partially bound) EQ polynomial at `index`, without merging
+    /// `E1` and `E2`.
+    pub fn get(&self, index: usize) -> F {
+        if self.E1_len == 1 {
+            self.E2[index]
+        } else {
+            self.E2[index / self.E1_len] * self.E1[index % self.E1_len]
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "SplitEqPolynomial::bind")
+    )]
     pub fn bind(&mut self, r: F) {
         if self.E1_len == 1 {
             // E_1 is already completely bound, so we bind E_2
@@ -110,6 +127,9 @@ mod tests {
 
             let merged = split_eq.merge();
             assert_eq!(regular_eq.Z[..regular_eq.len()], merged.Z[..merged.len()]);
+            for i in 0..merged.len() {
+                assert_eq!(split_eq.get(i), merged[i]);
+            }
         }
     }
 }
@@ -3,11 +3,12 @@ use crate::field::JoltField;
 use std::cmp::Ordering;
 use std::ops::{AddAssign, Index, IndexMut, Mul, MulAssign};
 
+use crate::utils::field_encoding::{append_scalars, ToFieldElements};
 use crate::utils::gaussian_elimination::gaussian_elimination;
-use crate::utils::transcript::{AppendToTranscript, Transcript};
-use ark_serialize::*;
+use crate::utils::par::{self, prelude::*};
+pub use crate::verifier::sumcheck::CompressedUniPoly;
+use ark_ff::FftField;
 use rand_core::{CryptoRng, RngCore};
-use rayon::iter::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 
 // ax^2 + bx + c stored as vec![c,b,a]
 // ax^3 + bx^2 + cx + d stored as vec![d,c,b,a]
@@ -16,11 +17,10 @@ pub struct UniPoly<F> {
     pub coeffs: Vec<F>,
 }
 
-// ax^2 + bx + c stored as vec![c,a]
-// ax^3 + bx^2 + cx + d stored as vec![d,b,a]
-#[derive(CanonicalSerialize, CanonicalDeserialize, Debug)]
-pub struct CompressedUniPoly<F: JoltField> {
-    pub coeffs_except_linear_term: Vec<F>,
+impl<F: JoltField> ToFieldElements<F> for CompressedUniPoly<F> {
+    fn append_field_elements(&self, out: &mut Vec<F>) {
+        append_scalars(&self.coeffs_except_linear_term, out);
+    }
 }
 
 impl<F: JoltField> UniPoly<F> {
@@ -57,7 +57,10 @@ impl<F: JoltField> UniPoly<F> {
 
     /// Divide self by another polynomial, and returns the
     /// quotient and remainder.
-    #[tracing::instrument(skip_all, name = "UniPoly::divide_with_remainder")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniPoly::divide_with_remainder")
+    )]
     pub fn divide_with_remainder(&self, divisor: &Self) -> Option<(Self, Self)> {
         if self.is_zero() {
             Some((Self::zero(), Self::zero()))
@@ -87,6 +90,42 @@ impl<F: JoltField> UniPoly<F> {
         }
     }
 
+    /// Divides self by `X - a` with synthetic division, returning the quotient and the
+    /// remainder, which is `self(a)`.
+    pub fn divide_by_linear(&self, a: &F) -> (Self, F) {
+        if self.coeffs.is_empty() {
+            return (Self::zero(), F::zero());
+        }
+        let mut quotient = vec![F::zero(); self.coeffs.len() - 1];
+        let mut running = F::zero();
+        for i in (0..self.coeffs.len()).rev() {
+            running = running * *a + self.coeffs[i];
+            if i > 0 {
+                quotient[i - 1] = running;
+            }
+        }
+        (Self::from_coeff(quotient), running)
+    }
+
+    /// Divides self by the vanishing polynomial `X^n - 1` of the order-`n` subgroup, returning
+    /// the quotient and the remainder, of degree less than `n`.
+    pub fn divide_by_vanishing(&self, n: usize) -> (Self, Self) {
+        assert!(n > 0);
+        if self.coeffs.len() <= n {
+            return (Self::zero(), self.clone());
+        }
+        // With q(X) * (X^n - 1) = p(X) - r(X), the coefficients satisfy
+        // q[i] = p[i + n] + q[i + n], from the top down
+        let mut quotient = vec![F::zero(); self.coeffs.len() - n];
+        for i in (0..quotient.len()).rev() {
+            quotient[i] = self.coeffs[i + n] + quotient.get(i + n).copied().unwrap_or(F::zero());
+        }
+        let remainder = (0..n)
+            .map(|i| self.coeffs[i] + quotient.get(i).copied().unwrap_or(F::zero()))
+            .collect();
+        (Self::from_coeff(quotient), Self::from_coeff(remainder))
+    }
+
     fn is_zero(&self) -> bool {
         self.coeffs.is_empty() || self.coeffs.iter().all(|c| c == &F::zero())
     }
@@ -115,12 +154,18 @@ impl<F: JoltField> UniPoly<F> {
         (0..self.coeffs.len()).map(|i| self.coeffs[i]).sum()
     }
 
-    #[tracing::instrument(skip_all, name = "UniPoly::evaluate")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniPoly::evaluate")
+    )]
     pub fn evaluate(&self, r: &F) -> F {
         Self::eval_with_coeffs(&self.coeffs, r)
     }
 
-    #[tracing::instrument(skip_all, name = "UniPoly::eval_with_coeffs")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniPoly::eval_with_coeffs")
+    )]
     pub fn eval_with_coeffs(coeffs: &[F], r: &F) -> F {
         let mut eval = coeffs[0];
         let mut power = *r;
@@ -152,6 +197,90 @@ impl<F: JoltField> UniPoly<F> {
     }
 }
 
+/// Products below this degree are computed naively, which is cheaper than the FFTs.
+const FFT_MUL_THRESHOLD: usize = 64;
+
+impl<F: JoltField + FftField> UniPoly<F> {
+    /// Multiplies two polynomials with radix-2 FFTs over the field's two-adic subgroups.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniPoly::mul_fft")
+    )]
+    pub fn mul_fft(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Self::zero();
+        }
+        let result_len = self.coeffs.len() + other.coeffs.len() - 1;
+        if self.coeffs.len().min(other.coeffs.len()) < FFT_MUL_THRESHOLD {
+            let mut result = vec![F::zero(); result_len];
+            for (i, a) in self.coeffs.iter().enumerate() {
+                for (j, b) in other.coeffs.iter().enumerate() {
+                    result[i + j] += *a * *b;
+                }
+            }
+            return Self::from_coeff(result);
+        }
+
+        let domain_size = result_len.next_power_of_two();
+        let omega = <F as FftField>::get_root_of_unity(domain_size as u64)
+            .expect("the field has no subgroup large enough for the product");
+        let mut a = self.coeffs.clone();
+        let mut b = other.coeffs.clone();
+        a.resize(domain_size, F::zero());
+        b.resize(domain_size, F::zero());
+        par::join(
+            || fft_in_place(&mut a, omega),
+            || fft_in_place(&mut b, omega),
+        );
+        a.par_iter_mut()
+            .zip(b.par_iter())
+            .for_each(|(a, b)| *a *= *b);
+
+        // The inverse FFT is the FFT at omega^-1, scaled by 1/n
+        fft_in_place(&mut a, JoltField::inverse(&omega).unwrap());
+        let n_inv =
+            JoltField::inverse(&<F as JoltField>::from_u64(domain_size as u64).unwrap()).unwrap();
+        a.truncate(result_len);
+        a.par_iter_mut().for_each(|coeff| *coeff *= n_inv);
+        Self::from_coeff(a)
+    }
+}
+
+/// Replaces `values` with their evaluations at the powers of `omega`, a primitive
+/// `values.len()`-th root of unity, with an iterative Cooley-Tukey FFT.
+fn fft_in_place<F: JoltField>(values: &mut [F], omega: F) {
+    let n = values.len();
+    debug_assert!(n.is_power_of_two());
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let mut half = 1;
+    while half < n {
+        // A primitive (2 * half)-th root of unity
+        let mut step_root = omega;
+        for _ in 0..(n / (2 * half)).trailing_zeros() {
+            step_root = step_root.square();
+        }
+        let twiddles: Vec<F> = std::iter::successors(Some(F::one()), |w| Some(*w * step_root))
+            .take(half)
+            .collect();
+        values.par_chunks_mut(2 * half).for_each(|block| {
+            let (low, high) = block.split_at_mut(half);
+            for ((low, high), twiddle) in low.iter_mut().zip(high.iter_mut()).zip(&twiddles) {
+                let t = *high * *twiddle;
+                *high = *low - t;
+                *low += t;
+            }
+        });
+        half *= 2;
+    }
+}
+
 /*
 impl<F: JoltField> AddAssign<&F> for UniPoly<F> {
     fn add_assign(&mut self, rhs: &F) {
@@ -227,38 +356,6 @@ impl<F: JoltField> CompressedUniPoly<F> {
         assert_eq!(self.coeffs_except_linear_term.len() + 1, coeffs.len());
         UniPoly { coeffs }
     }
-
-    // In the verifier we do not have to check that f(0) + f(1) = hint as we can just
-    // recover the linear term assuming the prover did it right, then eval the poly
-    pub fn eval_from_hint(&self, hint: &F, x: &F) -> F {
-        let mut linear_term =
-            *hint - self.coeffs_except_linear_term[0] - self.coeffs_except_linear_term[0];
-        for i in 1..self.coeffs_except_linear_term.len() {
-            linear_term -= self.coeffs_except_linear_term[i];
-        }
-
-        let mut running_point = *x;
-        let mut running_sum = self.coeffs_except_linear_term[0] + *x * linear_term;
-        for i in 1..self.coeffs_except_linear_term.len() {
-            running_point = running_point * x;
-            running_sum += self.coeffs_except_linear_term[i] * running_point;
-        }
-        running_sum
-    }
-
-    pub fn degree(&self) -> usize {
-        self.coeffs_except_linear_term.len()
-    }
-}
-
-impl<F: JoltField> AppendToTranscript for CompressedUniPoly<F> {
-    fn append_to_transcript<ProofTranscript: Transcript>(&self, transcript: &mut ProofTranscript) {
-        transcript.append_message(b"UniPoly_begin");
-        for i in 0..self.coeffs_except_linear_term.len() {
-            transcript.append_scalar(&self.coeffs_except_linear_term[i]);
-        }
-        transcript.append_message(b"UniPoly_end");
-    }
 }
 
 #[cfg(test)]
@@ -364,4 +461,41 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_mul_fft() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        for (a_len, b_len) in [(1, 1), (3, 70), (64, 64), (100, 157), (256, 1)] {
+            let a = UniPoly::<Fr>::random(a_len, rng);
+            let b = UniPoly::<Fr>::random(b_len, rng);
+            assert_eq!(a.mul_fft(&b), naive_mul(&a, &b));
+        }
+    }
+
+    #[test]
+    fn test_divide_by_linear_and_vanishing() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let point = Fr::from(7u64);
+        for degree in 1..20 {
+            let poly = UniPoly::<Fr>::random(degree, rng);
+
+            let (quotient, remainder) = poly.divide_by_linear(&point);
+            let divisor = UniPoly::from_coeff(vec![-point, Fr::from(1u64)]);
+            assert_eq!(remainder, poly.evaluate(&point));
+            let mut prod = naive_mul(&divisor, &quotient);
+            prod += &UniPoly::from_coeff(vec![remainder]);
+            assert_eq!(prod, poly);
+
+            for n in [1, 2, 4, 8] {
+                let (quotient, remainder) = poly.divide_by_vanishing(n);
+                assert!(remainder.coeffs.len() <= n);
+                let mut vanishing = vec![Fr::from(0u64); n + 1];
+                vanishing[0] = -Fr::from(1u64);
+                vanishing[n] = Fr::from(1u64);
+                let mut prod = naive_mul(&UniPoly::from_coeff(vanishing), &quotient);
+                prod += &remainder;
+                assert_eq!(prod, poly);
+            }
+        }
+    }
 }
@@ -1,14 +1,18 @@
 use super::{
     inputs::ConstraintInput,
-    key::{NonUniformR1CS, NonUniformR1CSConstraint, SparseEqualityItem},
+    key::{NonUniformR1CS, NonUniformR1CSConstraint, SparseEqualityItem, StepKind},
     ops::{Term, Variable, LC},
     special_polys::SparsePolynomial,
 };
+use crate::utils::par::{self, prelude::*};
 use crate::utils::transcript::Transcript;
 use crate::{
-    field::JoltField,
+    field::{JoltField, OptimizedMul},
     jolt::vm::JoltPolynomials,
-    poly::{commitment::commitment_scheme::CommitmentScheme, dense_mlpoly::DensePolynomial},
+    poly::{
+        commitment::commitment_scheme::CommitmentScheme, dense_mlpoly::DensePolynomial,
+        eq_poly::EqPolynomial, split_eq_poly::SplitEqPolynomial,
+    },
     r1cs::key::{SparseConstraints, UniformR1CS},
     utils::{
         math::Math,
@@ -16,8 +20,8 @@ use crate::{
         thread::{par_flatten_triple, unsafe_allocate_sparse_zero_vec, unsafe_allocate_zero_vec},
     },
 };
-use rayon::prelude::*;
-use std::{collections::BTreeMap, marker::PhantomData};
+use std::{collections::BTreeMap, fmt, marker::PhantomData, ops::Range};
+use thiserror::Error;
 
 /// Constraints over a single row. Each variable points to a single item in Z and the corresponding coefficient.
 #[derive(Clone)]
@@ -28,7 +32,6 @@ struct Constraint {
 }
 
 impl Constraint {
-    #[cfg(test)]
     fn pretty_fmt<const C: usize, I: ConstraintInput, F: JoltField>(
         &self,
         f: &mut String,
@@ -135,7 +138,7 @@ impl<F: JoltField> AuxComputation<F> {
             .collect();
 
         let mut aux_poly: Vec<F> = unsafe_allocate_zero_vec(batch_size);
-        let num_threads = rayon::current_num_threads();
+        let num_threads = par::current_num_threads();
         let chunk_size = batch_size.div_ceil(num_threads);
 
         aux_poly
@@ -171,7 +174,10 @@ impl<F: JoltField> AuxComputation<F> {
     /// Computes auxiliary variable for batch_size steps using the evaluations of each
     /// linear combination (represented by self.symbolic_inputs).
     /// inputs: self.symbolic_inputs.len() inputs each of size batch_size
-    #[tracing::instrument(skip_all, name = "AuxComputation::compute_batch")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "AuxComputation::compute_batch")
+    )]
     fn compute_batch(&self, inputs: Vec<&[F]>, batch_size: usize) -> Vec<F> {
         assert_eq!(inputs.len(), self.symbolic_inputs.len());
         assert!(inputs.iter().all(|input| input.len() == batch_size));
@@ -179,7 +185,7 @@ impl<F: JoltField> AuxComputation<F> {
         // Split into num_threads chunks and copy the corresponding inputs from each step
         // in the batch to a buffer owend by each thread's chunk to minimize allocs.
 
-        let num_threads = rayon::current_num_threads();
+        let num_threads = par::current_num_threads();
         let chunk_size = batch_size.div_ceil(num_threads);
         let mut results: Vec<F> = unsafe_allocate_zero_vec(batch_size);
 
@@ -187,8 +193,8 @@ impl<F: JoltField> AuxComputation<F> {
             .par_chunks_mut(chunk_size)
             .enumerate()
             .for_each(|(chunk_index, chunk)| {
-                let span = tracing::span!(tracing::Level::DEBUG, "chunk");
-                let _enter = span.enter();
+                #[cfg(feature = "tracing")]
+                let _enter = tracing::span!(tracing::Level::DEBUG, "chunk").entered();
                 let mut input_buffer: Vec<F> = unsafe_allocate_zero_vec(inputs.len());
 
                 chunk
@@ -211,6 +217,7 @@ pub struct R1CSBuilder<const C: usize, F: JoltField, I: ConstraintInput> {
     _inputs: PhantomData<I>,
     constraints: Vec<Constraint>,
     aux_computations: BTreeMap<usize, AuxComputation<F>>,
+    step_kinds: Vec<StepKind>,
 }
 
 impl<const C: usize, F: JoltField, I: ConstraintInput> Default for R1CSBuilder<C, F, I> {
@@ -225,6 +232,38 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> R1CSBuilder<C, F, I> {
             _inputs: PhantomData,
             constraints: vec![],
             aux_computations: BTreeMap::new(),
+            step_kinds: vec![],
+        }
+    }
+
+    /// Adds the constraints built by `build` as a block which is only enforced at the steps
+    /// where `selector` is non-zero. Steps of other kinds need not satisfy them, nor be padded
+    /// to do so. The selector must be a bytecode flag, so that the prover cannot choose it,
+    /// and is constrained to be binary.
+    pub fn constrain_step_kind(&mut self, selector: I, build: impl FnOnce(&mut Self)) {
+        assert!(
+            selector.is_bytecode_flag(),
+            "step kind selector {:?} is not bound to the bytecode",
+            selector
+        );
+        self.constrain_binary(selector);
+
+        let num_step_kinds = self.step_kinds.len();
+        let start_row = self.constraints.len();
+        build(self);
+        assert_eq!(
+            self.step_kinds.len(),
+            num_step_kinds,
+            "step kinds cannot be nested"
+        );
+
+        let end_row = self.constraints.len();
+        if end_row > start_row {
+            self.step_kinds.push(StepKind {
+                selector: selector.to_index::<C>(),
+                start_row,
+                end_row,
+            });
         }
     }
 
@@ -450,7 +489,7 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> R1CSBuilder<C, F, I> {
             lc.terms().iter().for_each(|term| {
                 match term.0 {
                     Variable::Input(inner) | Variable::Auxiliary(inner) => {
-                        sparse.vars.push((row_index, inner, F::from_i64(term.1)))
+                        sparse.push_var(row_index, inner, F::from_i64(term.1))
                     }
                     Variable::Constant => {}
                 };
@@ -466,9 +505,9 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> R1CSBuilder<C, F, I> {
             update_sparse(row_index, &constraint.c, &mut c_sparse);
         }
 
-        assert_eq!(a_sparse.vars.len(), a_len);
-        assert_eq!(b_sparse.vars.len(), b_len);
-        assert_eq!(c_sparse.vars.len(), c_len);
+        assert_eq!(a_sparse.num_vars(), a_len);
+        assert_eq!(b_sparse.num_vars(), b_len);
+        assert_eq!(c_sparse.num_vars(), c_len);
 
         UniformR1CS::<F> {
             a: a_sparse,
@@ -476,6 +515,7 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> R1CSBuilder<C, F, I> {
             c: c_sparse,
             num_vars: I::num_inputs::<C>(),
             num_rows: self.constraints.len(),
+            step_kinds: self.step_kinds.clone(),
         }
     }
 }
@@ -514,6 +554,56 @@ impl OffsetEqConstraint {
             (LC::new(vec![]), false),
         )
     }
+
+    fn pretty_fmt<const C: usize, I: ConstraintInput>(&self, f: &mut String) -> fmt::Result {
+        use std::fmt::Write as _;
+
+        // Terms of the next step are primed
+        let write_offset_lc = |f: &mut String, lc: &OffsetLC| -> fmt::Result {
+            lc.1.pretty_fmt::<C, I>(f)?;
+            if lc.0 {
+                write!(f, "'")?;
+            }
+            Ok(())
+        };
+        write!(f, "(")?;
+        write_offset_lc(f, &self.a)?;
+        write!(f, " - ")?;
+        write_offset_lc(f, &self.b)?;
+        write!(f, ") ⋅ ")?;
+        write_offset_lc(f, &self.cond)?;
+        write!(f, " == 0")
+    }
+}
+
+/// Identifies a constraint of a `CombinedUniformBuilder`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintIndex {
+    Uniform(usize),
+    OffsetEq(usize),
+}
+
+impl fmt::Display for ConstraintIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintIndex::Uniform(index) => write!(f, "uniform constraint {index}"),
+            ConstraintIndex::OffsetEq(index) => write!(f, "offset equality constraint {index}"),
+        }
+    }
+}
+
+/// A constraint which the witness fails to satisfy, as found by
+/// `CombinedUniformBuilder::check_satisfied`.
+#[derive(Clone, Debug, PartialEq, Error)]
+#[error("{constraint} violated at step {step}: Az = {az}, Bz = {bz}, Cz = {cz}\n{description}")]
+pub struct UnsatisfiedConstraint<F: JoltField> {
+    pub constraint: ConstraintIndex,
+    pub step: usize,
+    /// The constraint, followed by the values of its variables at `step` for uniform constraints
+    pub description: String,
+    pub az: F,
+    pub bz: F,
+    pub cz: F,
 }
 
 // TODO(sragss): Detailed documentation with wiki.
@@ -540,7 +630,7 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
         }
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn compute_aux(&self, jolt_polynomials: &mut JoltPolynomials<F>) {
         let flattened_vars = I::flatten::<C>();
         for (aux_index, aux_compute) in self.uniform_builder.aux_computations.iter() {
@@ -635,7 +725,7 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
         NonUniformR1CS { constraints }
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn compute_spartan_Az_Bz_Cz<
         PCS: CommitmentScheme<ProofTranscript, Field = F>,
         ProofTranscript: Transcript,
@@ -650,8 +740,8 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
         let uniform_constraint_rows = self.uniform_repeat_constraint_rows();
 
         // uniform_constraints: Xz[0..uniform_constraint_rows]
-        let span = tracing::span!(tracing::Level::DEBUG, "uniform constraints");
-        let _enter = span.enter();
+        #[cfg(feature = "tracing")]
+        let _enter = tracing::span!(tracing::Level::DEBUG, "uniform constraints").entered();
         #[allow(clippy::type_complexity)]
         let uni_constraint_evals: Vec<(Vec<(F, usize)>, Vec<(F, usize)>, Vec<(F, usize)>)> = self
             .uniform_builder
@@ -661,17 +751,27 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
             .map(|(constraint_index, constraint)| {
                 let mut dense_output_buffer = unsafe_allocate_zero_vec(self.uniform_repeat);
 
-                let mut evaluate_lc_chunk = |lc: &LC| {
+                // The A and C rows of a step kind are scaled by its selector, so they are dropped
+                // at the steps of other kinds
+                let selector = self.step_kind_selector(flattened_polynomials, constraint_index);
+
+                let mut evaluate_lc_chunk = |lc: &LC, gated: bool| {
                     if !lc.terms().is_empty() {
                         lc.evaluate_batch_mut(flattened_polynomials, &mut dense_output_buffer);
 
                         // Take only the non-zero elements and represent them as sparse tuples (eval, dense_index)
                         let mut sparse = Vec::with_capacity(self.uniform_repeat); // overshoot
                         for (local_index, item) in dense_output_buffer.iter().enumerate() {
+                            let item = match selector {
+                                Some(selector) if gated => {
+                                    mul_0_1_optimized(item, &selector[local_index])
+                                }
+                                _ => *item,
+                            };
                             if !item.is_zero() {
                                 let global_index =
                                     constraint_index * self.uniform_repeat + local_index;
-                                sparse.push((*item, global_index));
+                                sparse.push((item, global_index));
                             }
                         }
                         sparse
@@ -680,9 +780,9 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
                     }
                 };
 
-                let a_chunk: Vec<(F, usize)> = evaluate_lc_chunk(&constraint.a);
-                let b_chunk: Vec<(F, usize)> = evaluate_lc_chunk(&constraint.b);
-                let c_chunk: Vec<(F, usize)> = evaluate_lc_chunk(&constraint.c);
+                let a_chunk: Vec<(F, usize)> = evaluate_lc_chunk(&constraint.a, true);
+                let b_chunk: Vec<(F, usize)> = evaluate_lc_chunk(&constraint.b, false);
+                let c_chunk: Vec<(F, usize)> = evaluate_lc_chunk(&constraint.c, true);
 
                 (a_chunk, b_chunk, c_chunk)
             })
@@ -697,8 +797,8 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
         // offset_equality_constraints: Xz[uniform_constraint_rows..uniform_constraint_rows + 1]
         // (a - b) * condition == 0
         // For the final step we will not compute the offset terms, and will assume the condition to be set to 0
-        let span = tracing::span!(tracing::Level::DEBUG, "non-uniform constraints");
-        let _enter = span.enter();
+        #[cfg(feature = "tracing")]
+        let _enter = tracing::span!(tracing::Level::DEBUG, "non-uniform constraints").entered();
 
         for (constr_i, constr) in self.offset_equality_constraints.iter().enumerate() {
             let condition_evals = constr
@@ -744,6 +844,7 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
                 }
             });
         }
+        #[cfg(feature = "tracing")]
         drop(_enter);
 
         let num_vars = self.constraint_rows().next_power_of_two().log_2();
@@ -757,6 +858,335 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
         (az_poly, bz_poly, cz_poly)
     }
 
+    /// Evaluates Az, Bz and Cz at the rows starting at `start`, writing them to `az`, `bz` and
+    /// `cz`. The rows must lie within the steps of a single constraint. The constant variable is
+    /// `u`, which is 1 but for relaxed instances. The A and C rows of a step kind are scaled by
+    /// its selector.
+    fn evaluate_spartan_rows(
+        &self,
+        flattened_polynomials: &[&DensePolynomial<F>],
+        start: usize,
+        u: F,
+        az: &mut [F],
+        bz: &mut [F],
+        cz: &mut [F],
+    ) {
+        let constraint_index = start / self.uniform_repeat;
+        let first_step = start % self.uniform_repeat;
+        debug_assert!(first_step + az.len() <= self.uniform_repeat);
+        let num_uniform_constraints = self.uniform_builder.constraints.len();
+
+        let rows = az.iter_mut().zip(bz.iter_mut()).zip(cz.iter_mut());
+        if let Some(constraint) = self.uniform_builder.constraints.get(constraint_index) {
+            let selector = self.step_kind_selector(flattened_polynomials, constraint_index);
+            for (step, ((a, b), c)) in (first_step..).zip(rows) {
+                *b = constraint
+                    .b
+                    .evaluate_row_relaxed(flattened_polynomials, step, u);
+                let gate = selector.map_or(F::one(), |selector| selector[step]);
+                if gate.is_zero() {
+                    (*a, *c) = (F::zero(), F::zero());
+                    continue;
+                }
+                *a = constraint
+                    .a
+                    .evaluate_row_relaxed(flattened_polynomials, step, u)
+                    * gate;
+                *c = constraint
+                    .c
+                    .evaluate_row_relaxed(flattened_polynomials, step, u)
+                    * gate;
+            }
+        } else if let Some(constr) = self
+            .offset_equality_constraints
+            .get(constraint_index - num_uniform_constraints)
+        {
+            // (a - b) * condition == 0, where the offset terms of the final step are only their
+            // constants, as in `compute_spartan_Az_Bz_Cz`
+            let evaluate = |lc: &OffsetLC, step: usize| {
+                let step = step + lc.0 as usize;
+                if step < self.uniform_repeat {
+                    lc.1.evaluate_row_relaxed(flattened_polynomials, step, u)
+                } else {
+                    lc.1.constant_term_field::<F>() * u
+                }
+            };
+            for (step, ((a, b), c)) in (first_step..).zip(rows) {
+                *a = evaluate(&constr.a, step) - evaluate(&constr.b, step);
+                *b = evaluate(&constr.cond, step);
+                *c = F::zero();
+            }
+        } else {
+            // Padding
+            for ((a, b), c) in rows {
+                (*a, *b, *c) = (F::zero(), F::zero(), F::zero());
+            }
+        }
+    }
+
+    /// Splits the blocks of `block_size` rows covering the constraints into ranges to be
+    /// processed in parallel.
+    fn spartan_row_block_chunks(&self, block_size: usize) -> Vec<Range<usize>> {
+        assert!(block_size <= self.uniform_repeat);
+        let num_blocks = self.constraint_rows() / block_size;
+        // num_threads * 16 enables better work stealing
+        let chunk_size = num_blocks.div_ceil(par::current_num_threads() * 16).max(1);
+        (0..num_blocks)
+            .step_by(chunk_size)
+            .map(|start| start..(start + chunk_size).min(num_blocks))
+            .collect()
+    }
+
+    /// Computes Az, Bz and Cz with their lowest `r.len()` variables bound to `r`, as
+    /// `compute_spartan_Az_Bz_Cz` followed by as many rounds of the outer sum-check would.
+    /// The rows are evaluated one block of `2^r.len()` at a time, so the unbound polynomials are
+    /// never materialized.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub fn compute_spartan_Az_Bz_Cz_bound(
+        &self,
+        flattened_polynomials: &[&DensePolynomial<F>],
+        r: &[F],
+    ) -> (
+        SparsePolynomial<F>,
+        SparsePolynomial<F>,
+        SparsePolynomial<F>,
+    ) {
+        let block_size = r.len().pow2();
+        let weights = block_weights(r);
+
+        #[allow(clippy::type_complexity)]
+        let chunks: Vec<(Vec<(F, usize)>, Vec<(F, usize)>, Vec<(F, usize)>)> = self
+            .spartan_row_block_chunks(block_size)
+            .into_par_iter()
+            .map(|blocks| {
+                let mut az = vec![F::zero(); block_size];
+                let mut bz = vec![F::zero(); block_size];
+                let mut cz = vec![F::zero(); block_size];
+                let (mut az_sparse, mut bz_sparse, mut cz_sparse) = (vec![], vec![], vec![]);
+                for block in blocks {
+                    self.evaluate_spartan_rows(
+                        flattened_polynomials,
+                        block * block_size,
+                        F::one(),
+                        &mut az,
+                        &mut bz,
+                        &mut cz,
+                    );
+                    for (sparse, evals) in [
+                        (&mut az_sparse, &az),
+                        (&mut bz_sparse, &bz),
+                        (&mut cz_sparse, &cz),
+                    ] {
+                        let bound = bind_block(&weights, evals);
+                        if !bound.is_zero() {
+                            sparse.push((bound, block));
+                        }
+                    }
+                }
+                (az_sparse, bz_sparse, cz_sparse)
+            })
+            .collect();
+        let (az_sparse, bz_sparse, cz_sparse) =
+            par_flatten_triple(chunks, unsafe_allocate_sparse_zero_vec, 0);
+
+        let num_vars = self.constraint_rows().next_power_of_two().log_2() - r.len();
+        (
+            SparsePolynomial::new(num_vars, az_sparse),
+            SparsePolynomial::new(num_vars, bz_sparse),
+            SparsePolynomial::new(num_vars, cz_sparse),
+        )
+    }
+
+    /// Computes the evaluations at 0, 2 and 3 of the outer sum-check's round polynomial for the
+    /// round following those bound to `r`, streaming over the rows like
+    /// `compute_spartan_Az_Bz_Cz_bound`. `eq_tau` must already be bound to `r`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub fn compute_spartan_streaming_round(
+        &self,
+        flattened_polynomials: &[&DensePolynomial<F>],
+        eq_tau: &SplitEqPolynomial<F>,
+        r: &[F],
+    ) -> (F, F, F) {
+        // Each block holds the rows of both values of the round's variable
+        let half_block_size = r.len().pow2();
+        let block_size = 2 * half_block_size;
+        let weights = block_weights(r);
+
+        self.spartan_row_block_chunks(block_size)
+            .into_par_iter()
+            .map(|blocks| {
+                let mut az = vec![F::zero(); block_size];
+                let mut bz = vec![F::zero(); block_size];
+                let mut cz = vec![F::zero(); block_size];
+                let mut eval_point_0 = F::zero();
+                let mut eval_point_2 = F::zero();
+                let mut eval_point_3 = F::zero();
+                for block in blocks {
+                    self.evaluate_spartan_rows(
+                        flattened_polynomials,
+                        block * block_size,
+                        F::one(),
+                        &mut az,
+                        &mut bz,
+                        &mut cz,
+                    );
+                    let bind = |evals: &[F]| {
+                        let (low, high) = evals.split_at(half_block_size);
+                        (bind_block(&weights, low), bind_block(&weights, high))
+                    };
+                    let (a_low, a_high) = bind(&az);
+                    let (b_low, b_high) = bind(&bz);
+                    let (c_low, c_high) = bind(&cz);
+                    let (eq_low, eq_high) = (eq_tau.get(2 * block), eq_tau.get(2 * block + 1));
+
+                    eval_point_0 += eq_low * (a_low * b_low - c_low);
+
+                    let m_eq = eq_high - eq_low;
+                    let m_a = a_high - a_low;
+                    let m_b = b_high - b_low;
+                    let m_c = c_high - c_low;
+
+                    let (eq, a, b, c) = (eq_high + m_eq, a_high + m_a, b_high + m_b, c_high + m_c);
+                    eval_point_2 += eq * (a * b - c);
+
+                    let (eq, a, b, c) = (eq + m_eq, a + m_a, b + m_b, c + m_c);
+                    eval_point_3 += eq * (a * b - c);
+                }
+                (eval_point_0, eval_point_2, eval_point_3)
+            })
+            .reduce(
+                || (F::zero(), F::zero(), F::zero()),
+                |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2),
+            )
+    }
+
+    /// Computes the dense Az, Bz and Cz of a relaxed instance, whose constant variable is `u`,
+    /// padded with zeros to a power of two rows. The rows of the offset constraints at the last
+    /// step are zero: there, the constraints refer to the first step of the next chunk of the
+    /// trace, and are checked by `offset_constraints_hold` instead.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub fn compute_relaxed_Az_Bz_Cz(
+        &self,
+        flattened_polynomials: &[&DensePolynomial<F>],
+        u: F,
+    ) -> (Vec<F>, Vec<F>, Vec<F>) {
+        // Scaling rows by a selector is not linear in the witness, so cannot be folded
+        assert!(
+            self.uniform_builder.step_kinds.is_empty(),
+            "constraints with step kinds cannot be relaxed"
+        );
+        let num_rows = self.constraint_rows().next_power_of_two();
+        let mut az = unsafe_allocate_zero_vec(num_rows);
+        let mut bz = unsafe_allocate_zero_vec(num_rows);
+        let mut cz = unsafe_allocate_zero_vec(num_rows);
+        let constraint_rows = self.constraint_rows();
+        az[..constraint_rows]
+            .par_chunks_mut(self.uniform_repeat)
+            .zip(bz[..constraint_rows].par_chunks_mut(self.uniform_repeat))
+            .zip(cz[..constraint_rows].par_chunks_mut(self.uniform_repeat))
+            .enumerate()
+            .for_each(|(constraint_index, ((az, bz), cz))| {
+                self.evaluate_spartan_rows(
+                    flattened_polynomials,
+                    constraint_index * self.uniform_repeat,
+                    u,
+                    az,
+                    bz,
+                    cz,
+                )
+            });
+        let num_uniform_constraints = self.uniform_builder.constraints.len();
+        for i in 0..self.offset_equality_constraints.len() {
+            let last_row = (num_uniform_constraints + i + 1) * self.uniform_repeat - 1;
+            (az[last_row], bz[last_row]) = (F::zero(), F::zero());
+        }
+        (az, bz, cz)
+    }
+
+    /// Whether the offset constraints hold between the last step of a chunk of the trace and the
+    /// first step of the next, given the values of every variable at those steps. At the end of
+    /// the trace there is no next step, and the offset terms are only their constants, as in
+    /// `compute_spartan_Az_Bz_Cz`.
+    pub fn offset_constraints_hold(&self, last_step: &[F], next_step: Option<&[F]>) -> bool {
+        let evaluate = |lc: &OffsetLC| match (lc.0, next_step) {
+            (false, _) => lc.1.evaluate_assignment(last_step),
+            (true, Some(next_step)) => lc.1.evaluate_assignment(next_step),
+            (true, None) => lc.1.constant_term_field(),
+        };
+        self.offset_equality_constraints.iter().all(|constr| {
+            ((evaluate(&constr.a) - evaluate(&constr.b)) * evaluate(&constr.cond)).is_zero()
+        })
+    }
+
+    /// The selector of the step kind whose block contains uniform constraint `constraint_index`,
+    /// if any.
+    fn step_kind_selector<'a>(
+        &self,
+        flattened_polynomials: &[&'a DensePolynomial<F>],
+        constraint_index: usize,
+    ) -> Option<&'a DensePolynomial<F>> {
+        self.uniform_builder
+            .step_kinds
+            .iter()
+            .find(|kind| (kind.start_row..kind.end_row).contains(&constraint_index))
+            .map(|kind| flattened_polynomials[kind.selector])
+    }
+
+    /// Checks that the witness satisfies every constraint at every step, and otherwise reports
+    /// the first violated constraint with its evaluations. A witness which does not satisfy the
+    /// constraints is only caught by the verifier's sum-check, so this is meant for debugging
+    /// witness generation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub fn check_satisfied(
+        &self,
+        flattened_polynomials: &[&DensePolynomial<F>],
+    ) -> Result<(), UnsatisfiedConstraint<F>> {
+        let num_uniform_constraints = self.uniform_builder.constraints.len();
+        let num_constraints = num_uniform_constraints + self.offset_equality_constraints.len();
+        let violation = (0..num_constraints)
+            .into_par_iter()
+            .find_map_first(|constraint_index| {
+                let mut az = unsafe_allocate_zero_vec(self.uniform_repeat);
+                let mut bz = unsafe_allocate_zero_vec(self.uniform_repeat);
+                let mut cz = unsafe_allocate_zero_vec(self.uniform_repeat);
+                self.evaluate_spartan_rows(
+                    flattened_polynomials,
+                    constraint_index * self.uniform_repeat,
+                    F::one(),
+                    &mut az,
+                    &mut bz,
+                    &mut cz,
+                );
+                // The rows of a step kind hold trivially at the steps of other kinds
+                let step =
+                    (0..self.uniform_repeat).find(|&step| az[step] * bz[step] != cz[step])?;
+
+                let mut description = String::new();
+                let constraint = if constraint_index < num_uniform_constraints {
+                    let _ = self.uniform_builder.constraints[constraint_index]
+                        .pretty_fmt::<C, I, F>(&mut description, flattened_polynomials, step);
+                    ConstraintIndex::Uniform(constraint_index)
+                } else {
+                    let index = constraint_index - num_uniform_constraints;
+                    let _ = self.offset_equality_constraints[index]
+                        .pretty_fmt::<C, I>(&mut description);
+                    ConstraintIndex::OffsetEq(index)
+                };
+                Some(UnsatisfiedConstraint {
+                    constraint,
+                    step,
+                    description,
+                    az: az[step],
+                    bz: bz[step],
+                    cz: cz[step],
+                })
+            });
+        match violation {
+            Some(violation) => Err(violation),
+            None => Ok(()),
+        }
+    }
+
     #[cfg(test)]
     pub fn assert_valid(
         &self,
@@ -800,8 +1230,143 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> CombinedUniformBuilder<C,
     }
 }
 
+/// The weights of the rows of a block when its low variables are bound to `r`, lowest first as
+/// in the outer sum-check.
+fn block_weights<F: JoltField>(r: &[F]) -> Vec<F> {
+    let r_rev: Vec<F> = r.iter().rev().copied().collect();
+    EqPolynomial::evals(&r_rev)
+}
+
+fn bind_block<F: JoltField>(weights: &[F], evals: &[F]) -> F {
+    weights
+        .iter()
+        .zip(evals.iter())
+        .map(|(weight, eval)| eval.mul_01_optimized(*weight))
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::jolt::vm::rv32i_vm::{C, RV32I};
+    use crate::poly::commitment::mock::MockCommitScheme;
+    use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+    use ark_std::{test_rng, UniformRand, Zero};
+    use common::rv_trace::CircuitFlags;
+
+    type JoltR1CSInputs = crate::r1cs::inputs::JoltR1CSInputs<RV32I>;
+
+    const NUM_STEPS: usize = 8;
+
+    /// A builder with a product, an equality and an offset equality constraint, with a
+    /// satisfying witness.
+    fn builder_and_witness() -> (
+        CombinedUniformBuilder<C, Fr, JoltR1CSInputs>,
+        Vec<DensePolynomial<Fr>>,
+    ) {
+        let (x, y, z) = (
+            JoltR1CSInputs::RS1_Read,
+            JoltR1CSInputs::RS2_Read,
+            JoltR1CSInputs::RD_Write,
+        );
+        let mut uniform_builder = R1CSBuilder::<C, Fr, JoltR1CSInputs>::new();
+        uniform_builder.constrain_prod(x, y, z);
+        uniform_builder.constrain_eq(JoltR1CSInputs::RAM_Write, JoltR1CSInputs::RAM_Read);
+        // The condition is never set, so the offset terms are unconstrained
+        let condition = JoltR1CSInputs::OpFlags(CircuitFlags::Jump);
+        let builder = CombinedUniformBuilder::construct(
+            uniform_builder,
+            NUM_STEPS,
+            vec![OffsetEqConstraint::new(
+                (condition, false),
+                (z, true),
+                (x, false),
+            )],
+        );
+
+        let mut rng = test_rng();
+        let mut random_poly =
+            || DensePolynomial::new((0..NUM_STEPS).map(|_| Fr::rand(&mut rng)).collect());
+        let mut polys: Vec<DensePolynomial<Fr>> = JoltR1CSInputs::flatten::<C>()
+            .iter()
+            .map(|_| DensePolynomial::new(vec![Fr::zero(); NUM_STEPS]))
+            .collect();
+        let (xs, ys, ram) = (random_poly(), random_poly(), random_poly());
+        polys[z.to_index::<C>()] = DensePolynomial::new(
+            xs.evals_ref()
+                .iter()
+                .zip(ys.evals_ref())
+                .map(|(x, y)| x * y)
+                .collect(),
+        );
+        polys[x.to_index::<C>()] = xs;
+        polys[y.to_index::<C>()] = ys;
+        polys[JoltR1CSInputs::RAM_Write.to_index::<C>()] = ram.clone();
+        polys[JoltR1CSInputs::RAM_Read.to_index::<C>()] = ram;
+        (builder, polys)
+    }
+
+    #[test]
+    fn streaming_matches_materialized() {
+        let (builder, polys) = builder_and_witness();
+        let poly_refs: Vec<&DensePolynomial<Fr>> = polys.iter().collect();
+        let mut rng = test_rng();
+
+        let (mut az, mut bz, mut cz) = builder
+            .compute_spartan_Az_Bz_Cz::<MockCommitScheme<Fr, KeccakTranscript>, KeccakTranscript>(
+                &poly_refs,
+            );
+        let tau: Vec<Fr> = (0..builder.constraint_rows().next_power_of_two().log_2())
+            .map(|_| Fr::rand(&mut rng))
+            .collect();
+        let mut eq_tau = SplitEqPolynomial::new(&tau);
+        let mut r = vec![];
+        for _ in 0..NUM_STEPS.log_2() {
+            assert_eq!(
+                builder.compute_spartan_streaming_round(&poly_refs, &eq_tau, &r),
+                SumcheckInstanceProof::<Fr, KeccakTranscript>::compute_eval_points_spartan_cubic(
+                    &eq_tau, &az, &bz, &cz
+                )
+            );
+
+            let r_i = Fr::rand(&mut rng);
+            r.push(r_i);
+            eq_tau.bind(r_i);
+            az.bound_poly_var_bot_par(&r_i);
+            bz.bound_poly_var_bot_par(&r_i);
+            cz.bound_poly_var_bot_par(&r_i);
+
+            let (az_bound, bz_bound, cz_bound) =
+                builder.compute_spartan_Az_Bz_Cz_bound(&poly_refs, &r);
+            assert_eq!(az_bound.to_dense(), az.clone().to_dense());
+            assert_eq!(bz_bound.to_dense(), bz.clone().to_dense());
+            assert_eq!(cz_bound.to_dense(), cz.clone().to_dense());
+        }
+    }
+
+    #[test]
+    fn check_satisfied() {
+        let (builder, mut polys) = builder_and_witness();
+        assert_eq!(
+            builder.check_satisfied(&polys.iter().collect::<Vec<_>>()),
+            Ok(())
+        );
+
+        let ram_write = JoltR1CSInputs::RAM_Write.to_index::<C>();
+        polys[ram_write].Z[5] += Fr::from(1u64);
+        let violation = builder
+            .check_satisfied(&polys.iter().collect::<Vec<_>>())
+            .unwrap_err();
+        assert_eq!(violation.constraint, ConstraintIndex::Uniform(1));
+        assert_eq!(violation.step, 5);
+        assert_eq!(violation.az, Fr::from(1u64));
+        assert_eq!(violation.bz, Fr::from(1u64));
+        assert_eq!(violation.cz, Fr::zero());
+        assert!(violation.description.contains("RAM_Write"));
+    }
+
     // use super::*;
 
     // use ark_bn254::Fr;
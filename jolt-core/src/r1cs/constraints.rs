@@ -1,5 +1,9 @@
-use common::{constants::REGISTER_COUNT, rv_trace::CircuitFlags};
-use strum::IntoEnumIterator;
+use common::{
+    constants::REGISTER_COUNT,
+    rv_trace::{CircuitFlags, NUM_CIRCUIT_FLAGS},
+};
+use std::marker::PhantomData;
+use strum::{EnumCount, IntoEnumIterator};
 
 use crate::{
     field::JoltField,
@@ -9,7 +13,7 @@ use crate::{
             mulu::MULUInstruction, sll::SLLInstruction, sra::SRAInstruction, srl::SRLInstruction,
             sub::SUBInstruction,
             virtual_assert_aligned_memory_access::AssertAlignedMemoryAccessInstruction,
-            virtual_move::MOVEInstruction, virtual_movsign::MOVSIGNInstruction,
+            virtual_move::MOVEInstruction, virtual_movsign::MOVSIGNInstruction, JoltInstructionSet,
         },
         vm::rv32i_vm::RV32I,
     },
@@ -55,136 +59,209 @@ pub trait R1CSConstraints<const C: usize, F: JoltField> {
     fn non_uniform_constraints() -> Vec<OffsetEqConstraint>;
 }
 
-pub struct JoltRV32IMConstraints;
-impl<const C: usize, F: JoltField> R1CSConstraints<C, F> for JoltRV32IMConstraints {
-    type Inputs = JoltR1CSInputs;
+/// An instruction set that the RV32IM constraints apply to: one that contains the RV32I
+/// instructions whose lookup queries `JoltRV32IMConstraints` constrain specifically. Any other
+/// instructions, e.g. application-specific ones registered with `instruction_set!`, only get
+/// an instruction flag, and their queries are the interleaved operand chunks.
+pub trait RV32IMInstructionSet:
+    JoltInstructionSet
+    + From<ADDInstruction<32>>
+    + From<SUBInstruction<32>>
+    + From<MULInstruction<32>>
+    + From<MULUInstruction<32>>
+    + From<MULHUInstruction<32>>
+    + From<MOVSIGNInstruction<32>>
+    + From<MOVEInstruction<32>>
+    + From<SLLInstruction<32>>
+    + From<SRLInstruction<32>>
+    + From<SRAInstruction<32>>
+    + From<AssertAlignedMemoryAccessInstruction<32, 2>>
+    + From<AssertAlignedMemoryAccessInstruction<32, 4>>
+{
+}
+
+impl<T> RV32IMInstructionSet for T where
+    T: JoltInstructionSet
+        + From<ADDInstruction<32>>
+        + From<SUBInstruction<32>>
+        + From<MULInstruction<32>>
+        + From<MULUInstruction<32>>
+        + From<MULHUInstruction<32>>
+        + From<MOVSIGNInstruction<32>>
+        + From<MOVEInstruction<32>>
+        + From<SLLInstruction<32>>
+        + From<SRLInstruction<32>>
+        + From<SRAInstruction<32>>
+        + From<AssertAlignedMemoryAccessInstruction<32, 2>>
+        + From<AssertAlignedMemoryAccessInstruction<32, 4>>
+{
+}
+
+/// Jolt's constraints for RV32IM programs, with an instruction flag for each instruction of
+/// `InstructionSet`.
+pub struct JoltRV32IMConstraints<InstructionSet = RV32I>(PhantomData<InstructionSet>);
+impl<const C: usize, F: JoltField, InstructionSet: RV32IMInstructionSet> R1CSConstraints<C, F>
+    for JoltRV32IMConstraints<InstructionSet>
+{
+    type Inputs = JoltR1CSInputs<InstructionSet>;
 
     fn uniform_constraints(cs: &mut R1CSBuilder<C, F, Self::Inputs>, memory_start: u64) {
-        for flag in RV32I::iter() {
-            cs.constrain_binary(JoltR1CSInputs::InstructionFlags(flag));
+        assert!(
+            NUM_CIRCUIT_FLAGS + InstructionSet::COUNT <= 64,
+            "the circuit and instruction flags must fit in the 64-bit bytecode bitflags"
+        );
+        for flag in InstructionSet::iter() {
+            cs.constrain_binary(JoltR1CSInputs::<InstructionSet>::InstructionFlags(flag));
         }
         for flag in CircuitFlags::iter() {
-            cs.constrain_binary(JoltR1CSInputs::OpFlags(flag));
+            cs.constrain_binary(JoltR1CSInputs::<InstructionSet>::OpFlags(flag));
         }
 
         let flags = CircuitFlags::iter()
-            .map(|flag| JoltR1CSInputs::OpFlags(flag).into())
-            .chain(RV32I::iter().map(|flag| JoltR1CSInputs::InstructionFlags(flag).into()))
+            .map(|flag| JoltR1CSInputs::<InstructionSet>::OpFlags(flag).into())
+            .chain(
+                InstructionSet::iter()
+                    .map(|flag| JoltR1CSInputs::<InstructionSet>::InstructionFlags(flag).into()),
+            )
             .collect();
-        cs.constrain_pack_be(flags, JoltR1CSInputs::Bytecode_Bitflags, 1);
+        cs.constrain_pack_be(
+            flags,
+            JoltR1CSInputs::<InstructionSet>::Bytecode_Bitflags,
+            1,
+        );
 
-        let real_pc =
-            4i64 * JoltR1CSInputs::Bytecode_ELFAddress + (PC_START_ADDRESS - PC_NOOP_SHIFT);
+        let real_pc = 4i64 * JoltR1CSInputs::<InstructionSet>::Bytecode_ELFAddress
+            + (PC_START_ADDRESS - PC_NOOP_SHIFT);
         let x = cs.allocate_if_else(
-            JoltR1CSInputs::Aux(AuxVariable::LeftLookupOperand),
-            JoltR1CSInputs::OpFlags(CircuitFlags::LeftOperandIsPC),
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::LeftLookupOperand),
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::LeftOperandIsPC),
             real_pc,
-            JoltR1CSInputs::RS1_Read,
+            JoltR1CSInputs::<InstructionSet>::RS1_Read,
         );
         let y = cs.allocate_if_else(
-            JoltR1CSInputs::Aux(AuxVariable::RightLookupOperand),
-            JoltR1CSInputs::OpFlags(CircuitFlags::RightOperandIsImm),
-            JoltR1CSInputs::Bytecode_Imm,
-            JoltR1CSInputs::RS2_Read,
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::RightLookupOperand),
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::RightOperandIsImm),
+            JoltR1CSInputs::<InstructionSet>::Bytecode_Imm,
+            JoltR1CSInputs::<InstructionSet>::RS2_Read,
         );
 
-        let is_load_or_store = JoltR1CSInputs::OpFlags(CircuitFlags::Load)
-            + JoltR1CSInputs::OpFlags(CircuitFlags::Store);
+        let is_load_or_store = JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Load)
+            + JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Store);
         let memory_start: i64 = memory_start.try_into().unwrap();
         cs.constrain_eq_conditional(
             is_load_or_store,
-            JoltR1CSInputs::RS1_Read + JoltR1CSInputs::Bytecode_Imm,
-            4 * JoltR1CSInputs::RAM_Address + memory_start - 4 * REGISTER_COUNT as i64,
+            JoltR1CSInputs::<InstructionSet>::RS1_Read
+                + JoltR1CSInputs::<InstructionSet>::Bytecode_Imm,
+            4 * JoltR1CSInputs::<InstructionSet>::RAM_Address + memory_start
+                - 4 * REGISTER_COUNT as i64,
         );
 
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::OpFlags(CircuitFlags::Load),
-            JoltR1CSInputs::RAM_Read,
-            JoltR1CSInputs::RAM_Write,
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Load),
+            JoltR1CSInputs::<InstructionSet>::RAM_Read,
+            JoltR1CSInputs::<InstructionSet>::RAM_Write,
         );
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::OpFlags(CircuitFlags::Load),
-            JoltR1CSInputs::RAM_Read,
-            JoltR1CSInputs::RD_Write,
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Load),
+            JoltR1CSInputs::<InstructionSet>::RAM_Read,
+            JoltR1CSInputs::<InstructionSet>::RD_Write,
         );
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::OpFlags(CircuitFlags::Store),
-            JoltR1CSInputs::RS2_Read,
-            JoltR1CSInputs::RAM_Write,
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Store),
+            JoltR1CSInputs::<InstructionSet>::RS2_Read,
+            JoltR1CSInputs::<InstructionSet>::RAM_Write,
         );
 
         let query_chunks: Vec<Variable> = (0..C)
-            .map(|i| Variable::Input(JoltR1CSInputs::ChunksQuery(i).to_index::<C>()))
+            .map(|i| {
+                Variable::Input(JoltR1CSInputs::<InstructionSet>::ChunksQuery(i).to_index::<C>())
+            })
             .collect();
-        let packed_query =
-            R1CSBuilder::<C, F, JoltR1CSInputs>::pack_be(query_chunks.clone(), LOG_M);
+        let packed_query = R1CSBuilder::<C, F, Self::Inputs>::pack_be(query_chunks.clone(), LOG_M);
 
         // For the `AssertAlignedMemoryAccessInstruction` lookups, we add the `rs1` and `imm` values
         // to obtain the memory address being accessed.
-        let add_operands = JoltR1CSInputs::InstructionFlags(ADDInstruction::default().into())
-            + JoltR1CSInputs::InstructionFlags(
-                AssertAlignedMemoryAccessInstruction::<32, 2>::default().into(),
-            )
-            + JoltR1CSInputs::InstructionFlags(
-                AssertAlignedMemoryAccessInstruction::<32, 4>::default().into(),
-            );
+        let add_operands = JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            ADDInstruction::<32>::default().into(),
+        ) + JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            AssertAlignedMemoryAccessInstruction::<32, 2>::default().into(),
+        ) + JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            AssertAlignedMemoryAccessInstruction::<32, 4>::default().into(),
+        );
         cs.constrain_eq_conditional(add_operands, packed_query.clone(), x + y);
         // Converts from unsigned to twos-complement representation
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::InstructionFlags(SUBInstruction::default().into()),
+            JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+                SUBInstruction::<32>::default().into(),
+            ),
             packed_query.clone(),
             x - y + (0xffffffffi64 + 1),
         );
-        let is_mul = JoltR1CSInputs::InstructionFlags(MULInstruction::default().into())
-            + JoltR1CSInputs::InstructionFlags(MULUInstruction::default().into())
-            + JoltR1CSInputs::InstructionFlags(MULHUInstruction::default().into());
-        let product = cs.allocate_prod(JoltR1CSInputs::Aux(AuxVariable::Product), x, y);
+        let is_mul = JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            MULInstruction::<32>::default().into(),
+        ) + JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            MULUInstruction::<32>::default().into(),
+        ) + JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            MULHUInstruction::<32>::default().into(),
+        );
+        let product = cs.allocate_prod(
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::Product),
+            x,
+            y,
+        );
         cs.constrain_eq_conditional(is_mul, packed_query.clone(), product);
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::InstructionFlags(MOVSIGNInstruction::default().into())
-                + JoltR1CSInputs::InstructionFlags(MOVEInstruction::default().into()),
+            JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+                MOVSIGNInstruction::<32>::default().into(),
+            ) + JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+                MOVEInstruction::<32>::default().into(),
+            ),
             packed_query.clone(),
             x,
         );
 
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::OpFlags(CircuitFlags::Assert),
-            JoltR1CSInputs::LookupOutput,
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Assert),
+            JoltR1CSInputs::<InstructionSet>::LookupOutput,
             1,
         );
 
         let x_chunks: Vec<Variable> = (0..C)
-            .map(|i| Variable::Input(JoltR1CSInputs::ChunksX(i).to_index::<C>()))
+            .map(|i| Variable::Input(JoltR1CSInputs::<InstructionSet>::ChunksX(i).to_index::<C>()))
             .collect();
         let y_chunks: Vec<Variable> = (0..C)
-            .map(|i| Variable::Input(JoltR1CSInputs::ChunksY(i).to_index::<C>()))
+            .map(|i| Variable::Input(JoltR1CSInputs::<InstructionSet>::ChunksY(i).to_index::<C>()))
             .collect();
-        let x_concat = R1CSBuilder::<C, F, JoltR1CSInputs>::pack_be(x_chunks.clone(), OPERAND_SIZE);
-        let y_concat = R1CSBuilder::<C, F, JoltR1CSInputs>::pack_be(y_chunks.clone(), OPERAND_SIZE);
+        let x_concat = R1CSBuilder::<C, F, Self::Inputs>::pack_be(x_chunks.clone(), OPERAND_SIZE);
+        let y_concat = R1CSBuilder::<C, F, Self::Inputs>::pack_be(y_chunks.clone(), OPERAND_SIZE);
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::OpFlags(CircuitFlags::ConcatLookupQueryChunks),
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::ConcatLookupQueryChunks),
             x_concat,
             x,
         );
         cs.constrain_eq_conditional(
-            JoltR1CSInputs::OpFlags(CircuitFlags::ConcatLookupQueryChunks),
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::ConcatLookupQueryChunks),
             y_concat,
             y,
         );
 
         // if is_shift ? chunks_query[i] == zip(chunks_x[i], chunks_y[C-1]) : chunks_query[i] == zip(chunks_x[i], chunks_y[i])
-        let is_shift = JoltR1CSInputs::InstructionFlags(SLLInstruction::default().into())
-            + JoltR1CSInputs::InstructionFlags(SRLInstruction::default().into())
-            + JoltR1CSInputs::InstructionFlags(SRAInstruction::default().into());
+        let is_shift = JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            SLLInstruction::<32>::default().into(),
+        ) + JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            SRLInstruction::<32>::default().into(),
+        ) + JoltR1CSInputs::<InstructionSet>::InstructionFlags(
+            SRAInstruction::<32>::default().into(),
+        );
         for i in 0..C {
             let relevant_chunk_y = cs.allocate_if_else(
-                JoltR1CSInputs::Aux(AuxVariable::RelevantYChunk(i)),
+                JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::RelevantYChunk(i)),
                 is_shift.clone(),
                 y_chunks[C - 1],
                 y_chunks[i],
             );
             cs.constrain_eq_conditional(
-                JoltR1CSInputs::OpFlags(CircuitFlags::ConcatLookupQueryChunks),
+                JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::ConcatLookupQueryChunks),
                 query_chunks[i],
                 x_chunks[i] * (1i64 << 8) + relevant_chunk_y,
             );
@@ -192,46 +269,46 @@ impl<const C: usize, F: JoltField> R1CSConstraints<C, F> for JoltRV32IMConstrain
 
         // if (rd != 0 && update_rd_with_lookup_output == 1) constrain(rd_val == LookupOutput)
         let rd_nonzero_and_lookup_to_rd = cs.allocate_prod(
-            JoltR1CSInputs::Aux(AuxVariable::WriteLookupOutputToRD),
-            JoltR1CSInputs::Bytecode_RD,
-            JoltR1CSInputs::OpFlags(CircuitFlags::WriteLookupOutputToRD),
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::WriteLookupOutputToRD),
+            JoltR1CSInputs::<InstructionSet>::Bytecode_RD,
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::WriteLookupOutputToRD),
         );
         cs.constrain_eq_conditional(
             rd_nonzero_and_lookup_to_rd,
-            JoltR1CSInputs::RD_Write,
-            JoltR1CSInputs::LookupOutput,
+            JoltR1CSInputs::<InstructionSet>::RD_Write,
+            JoltR1CSInputs::<InstructionSet>::LookupOutput,
         );
         // if (rd != 0 && is_jump_instr == 1) constrain(rd_val == 4 * PC)
         let rd_nonzero_and_jmp = cs.allocate_prod(
-            JoltR1CSInputs::Aux(AuxVariable::WritePCtoRD),
-            JoltR1CSInputs::Bytecode_RD,
-            JoltR1CSInputs::OpFlags(CircuitFlags::Jump),
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::WritePCtoRD),
+            JoltR1CSInputs::<InstructionSet>::Bytecode_RD,
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Jump),
         );
         cs.constrain_eq_conditional(
             rd_nonzero_and_jmp,
-            4 * JoltR1CSInputs::Bytecode_ELFAddress + PC_START_ADDRESS,
-            JoltR1CSInputs::RD_Write,
+            4 * JoltR1CSInputs::<InstructionSet>::Bytecode_ELFAddress + PC_START_ADDRESS,
+            JoltR1CSInputs::<InstructionSet>::RD_Write,
         );
 
         let next_pc_jump = cs.allocate_if_else(
-            JoltR1CSInputs::Aux(AuxVariable::NextPCJump),
-            JoltR1CSInputs::OpFlags(CircuitFlags::Jump),
-            JoltR1CSInputs::LookupOutput + 4,
-            4 * JoltR1CSInputs::Bytecode_ELFAddress + PC_START_ADDRESS + 4
-                - 4 * JoltR1CSInputs::OpFlags(CircuitFlags::DoNotUpdatePC),
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::NextPCJump),
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Jump),
+            JoltR1CSInputs::<InstructionSet>::LookupOutput + 4,
+            4 * JoltR1CSInputs::<InstructionSet>::Bytecode_ELFAddress + PC_START_ADDRESS + 4
+                - 4 * JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::DoNotUpdatePC),
         );
 
         let should_branch = cs.allocate_prod(
-            JoltR1CSInputs::Aux(AuxVariable::ShouldBranch),
-            JoltR1CSInputs::OpFlags(CircuitFlags::Branch),
-            JoltR1CSInputs::LookupOutput,
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::ShouldBranch),
+            JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Branch),
+            JoltR1CSInputs::<InstructionSet>::LookupOutput,
         );
         let _next_pc = cs.allocate_if_else(
-            JoltR1CSInputs::Aux(AuxVariable::NextPC),
+            JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::NextPC),
             should_branch,
-            4 * JoltR1CSInputs::Bytecode_ELFAddress
+            4 * JoltR1CSInputs::<InstructionSet>::Bytecode_ELFAddress
                 + PC_START_ADDRESS
-                + JoltR1CSInputs::Bytecode_Imm,
+                + JoltR1CSInputs::<InstructionSet>::Bytecode_Imm,
             next_pc_jump,
         );
     }
@@ -240,10 +317,13 @@ impl<const C: usize, F: JoltField> R1CSConstraints<C, F> for JoltRV32IMConstrain
         // If the next instruction's ELF address is not zero (i.e. it's
         // not padding), then check the PC update.
         let pc_constraint = OffsetEqConstraint::new(
-            (JoltR1CSInputs::Bytecode_ELFAddress, true),
-            (JoltR1CSInputs::Aux(AuxVariable::NextPC), false),
+            (JoltR1CSInputs::<InstructionSet>::Bytecode_ELFAddress, true),
+            (
+                JoltR1CSInputs::<InstructionSet>::Aux(AuxVariable::NextPC),
+                false,
+            ),
             (
-                4 * JoltR1CSInputs::Bytecode_ELFAddress + PC_START_ADDRESS,
+                4 * JoltR1CSInputs::<InstructionSet>::Bytecode_ELFAddress + PC_START_ADDRESS,
                 true,
             ),
         );
@@ -256,9 +336,12 @@ impl<const C: usize, F: JoltField> R1CSConstraints<C, F> for JoltRV32IMConstrain
         // trace should always end with some return handling, which shouldn't involve
         // any virtual sequences.
         let virtual_sequence_constraint = OffsetEqConstraint::new(
-            (JoltR1CSInputs::OpFlags(CircuitFlags::Virtual), false),
-            (JoltR1CSInputs::Bytecode_A, true),
-            (JoltR1CSInputs::Bytecode_A + 1, false),
+            (
+                JoltR1CSInputs::<InstructionSet>::OpFlags(CircuitFlags::Virtual),
+                false,
+            ),
+            (JoltR1CSInputs::<InstructionSet>::Bytecode_A, true),
+            (JoltR1CSInputs::<InstructionSet>::Bytecode_A + 1, false),
         );
 
         vec![pc_constraint, virtual_sequence_constraint]
@@ -0,0 +1,845 @@
+//! Nova-style folding of instances of the uniform constraints, so that a long execution can be
+//! proven chunk by chunk while only ever holding two chunks' witnesses.
+//!
+//! Each chunk of the trace is relaxed to the instance `Az ∘ Bz = u·Cz + E`, with `u = 1` and
+//! `E = 0`, and folded into the running instance with Nova's non-interactive folding scheme: the
+//! prover commits to the cross term `T`, and both parties take the random linear combination of
+//! the two instances by a challenge `r`.
+//!
+//! The offset constraints at the last step of a chunk refer to the first step of the next, so
+//! are left out of the relaxed instances. Instead, a `BoundaryProof` opens the values at the
+//! first and last steps of each chunk, and the verifier checks the offset constraints between
+//! consecutive chunks on those values. The final relaxed instance is then compressed by a
+//! `RelaxedSpartanProof`.
+use std::marker::PhantomData;
+
+use crate::utils::par::prelude::*;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::field::JoltField;
+use crate::poly::commitment::commitment_scheme::{
+    AdditivelyHomomorphic, BatchType, CommitmentScheme,
+};
+use crate::poly::dense_mlpoly::{BindingOrder, DensePolynomial};
+use crate::poly::eq_poly::EqPolynomial;
+use crate::poly::opening_proof::{
+    ProverOpeningAccumulator, ReducedOpeningProof, VerifierOpeningAccumulator,
+};
+use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::index_to_field_bitvector;
+use crate::utils::math::Math;
+use crate::utils::thread::unsafe_allocate_zero_vec;
+use crate::utils::transcript::{AppendToTranscript, Transcript};
+
+use super::builder::CombinedUniformBuilder;
+use super::inputs::ConstraintInput;
+use super::key::{StepPoint, UniformSpartanKey};
+use super::spartan::SpartanError;
+
+/// A relaxed instance `Az ∘ Bz = u·Cz + E` of the uniform constraints.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, PartialEq)]
+pub struct RelaxedR1CSInstance<
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+> {
+    /// Commitments to the witness polynomials, in the order of `ConstraintInput::flatten`
+    pub witness_commitments: Vec<PCS::Commitment>,
+    pub error_commitment: PCS::Commitment,
+    pub u: F,
+}
+
+impl<F, PCS, ProofTranscript> RelaxedR1CSInstance<F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: AdditivelyHomomorphic<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    fn append_to_transcript(&self, transcript: &mut ProofTranscript) {
+        for commitment in self.witness_commitments.iter() {
+            commitment.append_to_transcript(transcript);
+        }
+        self.error_commitment.append_to_transcript(transcript);
+        transcript.append_scalar(&self.u);
+    }
+
+    fn fold(&self, other: &Self, cross_term_commitment: &PCS::Commitment, r: F) -> Self {
+        let witness_commitments = self
+            .witness_commitments
+            .iter()
+            .zip(other.witness_commitments.iter())
+            .map(|(running, incoming)| {
+                PCS::combine_commitments(&[running, incoming], &[F::one(), r])
+            })
+            .collect();
+        let error_commitment = PCS::combine_commitments(
+            &[
+                &self.error_commitment,
+                cross_term_commitment,
+                &other.error_commitment,
+            ],
+            &[F::one(), r, r.square()],
+        );
+        Self {
+            witness_commitments,
+            error_commitment,
+            u: self.u + r * other.u,
+        }
+    }
+}
+
+/// The witness of a `RelaxedR1CSInstance`.
+pub struct RelaxedR1CSWitness<F: JoltField> {
+    /// The witness polynomials, in the order of `ConstraintInput::flatten`
+    pub witness: Vec<DensePolynomial<F>>,
+    /// The error vector, over the constraint rows padded to a power of two
+    pub error: DensePolynomial<F>,
+}
+
+impl<F: JoltField> RelaxedR1CSWitness<F> {
+    /// Relaxes the witness of a chunk of the trace, with an error of zero.
+    pub fn new<const C: usize, I: ConstraintInput>(
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
+        witness: Vec<DensePolynomial<F>>,
+    ) -> Self {
+        let num_rows = constraint_builder.constraint_rows().next_power_of_two();
+        Self {
+            witness,
+            error: DensePolynomial::new(vec![F::zero(); num_rows]),
+        }
+    }
+
+    /// Commits to the witness and the error, for the instance with scalar `u`.
+    pub fn commit<PCS, ProofTranscript>(
+        &self,
+        u: F,
+        generators: &PCS::Setup,
+    ) -> RelaxedR1CSInstance<F, PCS, ProofTranscript>
+    where
+        PCS: CommitmentScheme<ProofTranscript, Field = F>,
+        ProofTranscript: Transcript,
+    {
+        let witness_refs: Vec<&DensePolynomial<F>> = self.witness.iter().collect();
+        RelaxedR1CSInstance {
+            witness_commitments: PCS::batch_commit_polys_ref(
+                &witness_refs,
+                generators,
+                BatchType::Big,
+            ),
+            error_commitment: PCS::commit(&self.error, generators),
+            u,
+        }
+    }
+
+    fn relaxed_Az_Bz_Cz<const C: usize, I: ConstraintInput>(
+        &self,
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
+        u: F,
+    ) -> (Vec<F>, Vec<F>, Vec<F>) {
+        let witness_refs: Vec<&DensePolynomial<F>> = self.witness.iter().collect();
+        constraint_builder.compute_relaxed_Az_Bz_Cz(&witness_refs, u)
+    }
+
+    /// Whether this witness satisfies the relaxed instance with scalar `u`.
+    pub fn is_satisfied<const C: usize, I: ConstraintInput>(
+        &self,
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
+        u: F,
+    ) -> bool {
+        let (az, bz, cz) = self.relaxed_Az_Bz_Cz(constraint_builder, u);
+        az.par_iter()
+            .zip(bz.par_iter())
+            .zip(cz.par_iter())
+            .zip(self.error.evals_ref().par_iter())
+            .all(|(((a, b), c), e)| *a * *b == u * *c + *e)
+    }
+
+    fn fold(&self, other: &Self, cross_term: &[F], r: F) -> Self {
+        let witness = self
+            .witness
+            .par_iter()
+            .zip(other.witness.par_iter())
+            .map(|(running, incoming)| {
+                DensePolynomial::new(
+                    running
+                        .evals_ref()
+                        .iter()
+                        .zip(incoming.evals_ref().iter())
+                        .map(|(running, incoming)| *running + r * *incoming)
+                        .collect(),
+                )
+            })
+            .collect();
+        let r_squared = r.square();
+        let error = self
+            .error
+            .evals_ref()
+            .par_iter()
+            .zip(cross_term.par_iter())
+            .zip(other.error.evals_ref().par_iter())
+            .map(|((running, cross_term), incoming)| {
+                *running + r * *cross_term + r_squared * *incoming
+            })
+            .collect();
+        Self {
+            witness,
+            error: DensePolynomial::new(error),
+        }
+    }
+}
+
+/// Folds an incoming relaxed instance into a running one. The proof is the commitment to the
+/// cross term `T = Az_1 ∘ Bz_2 + Az_2 ∘ Bz_1 - u_1·Cz_2 - u_2·Cz_1`.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct FoldingProof<
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+> {
+    pub cross_term_commitment: PCS::Commitment,
+    _marker: PhantomData<F>,
+}
+
+impl<F, PCS, ProofTranscript> FoldingProof<F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: AdditivelyHomomorphic<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    /// Folds `incoming` into `running`, returning the proof along with the folded instance and
+    /// witness.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "FoldingProof::prove")
+    )]
+    pub fn prove<const C: usize, I: ConstraintInput>(
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
+        generators: &PCS::Setup,
+        running: (
+            &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+            &RelaxedR1CSWitness<F>,
+        ),
+        incoming: (
+            &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+            &RelaxedR1CSWitness<F>,
+        ),
+        transcript: &mut ProofTranscript,
+    ) -> (
+        Self,
+        RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        RelaxedR1CSWitness<F>,
+    ) {
+        let (running_instance, running_witness) = running;
+        let (incoming_instance, incoming_witness) = incoming;
+        let (u_1, u_2) = (running_instance.u, incoming_instance.u);
+
+        let (az_1, bz_1, cz_1) = running_witness.relaxed_Az_Bz_Cz(constraint_builder, u_1);
+        let (az_2, bz_2, cz_2) = incoming_witness.relaxed_Az_Bz_Cz(constraint_builder, u_2);
+        let cross_term: Vec<F> = (0..az_1.len())
+            .into_par_iter()
+            .map(|i| az_1[i] * bz_2[i] + az_2[i] * bz_1[i] - u_1 * cz_2[i] - u_2 * cz_1[i])
+            .collect();
+        drop((az_1, bz_1, cz_1, az_2, bz_2, cz_2));
+
+        let proof = Self {
+            cross_term_commitment: PCS::commit(
+                &DensePolynomial::new(cross_term.clone()),
+                generators,
+            ),
+            _marker: PhantomData,
+        };
+        let r = proof.challenge(running_instance, incoming_instance, transcript);
+
+        let instance = running_instance.fold(incoming_instance, &proof.cross_term_commitment, r);
+        let witness = running_witness.fold(incoming_witness, &cross_term, r);
+        (proof, instance, witness)
+    }
+
+    /// Returns the instance folding `incoming` into `running`.
+    pub fn verify(
+        &self,
+        running: &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        incoming: &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) -> RelaxedR1CSInstance<F, PCS, ProofTranscript> {
+        let r = self.challenge(running, incoming, transcript);
+        running.fold(incoming, &self.cross_term_commitment, r)
+    }
+
+    fn challenge(
+        &self,
+        running: &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        incoming: &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) -> F {
+        running.append_to_transcript(transcript);
+        incoming.append_to_transcript(transcript);
+        self.cross_term_commitment.append_to_transcript(transcript);
+        transcript.challenge_scalar()
+    }
+}
+
+/// The values of every variable at the first and last steps of a chunk of the trace, opened
+/// against the witness commitments of the chunk's instance.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct BoundaryProof<
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+> {
+    /// The values at the first step, in the order of `ConstraintInput::flatten`
+    pub first_step: Vec<F>,
+    /// The values at the last step, in the order of `ConstraintInput::flatten`
+    pub last_step: Vec<F>,
+    opening_proof: ReducedOpeningProof<F, PCS, ProofTranscript>,
+}
+
+impl<F, PCS, ProofTranscript> BoundaryProof<F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    /// Opens the first and last steps of the witness of a chunk.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BoundaryProof::prove")
+    )]
+    pub fn prove(
+        generators: &PCS::Setup,
+        witness: &RelaxedR1CSWitness<F>,
+        transcript: &mut ProofTranscript,
+    ) -> Self {
+        let witness_refs: Vec<&DensePolynomial<F>> = witness.witness.iter().collect();
+        let num_steps = witness_refs[0].len();
+        let first_step: Vec<F> = witness_refs.iter().map(|poly| poly[0]).collect();
+        let last_step: Vec<F> = witness_refs
+            .iter()
+            .map(|poly| poly[num_steps - 1])
+            .collect();
+        transcript.append_scalars(&first_step);
+        transcript.append_scalars(&last_step);
+
+        let mut opening_accumulator = ProverOpeningAccumulator::for_scheme::<PCS>();
+        for (step, evals) in [(0, &first_step), (num_steps - 1, &last_step)] {
+            let point = index_to_field_bitvector(step, num_steps.log_2());
+            opening_accumulator.append(
+                &witness_refs,
+                DensePolynomial::new(EqPolynomial::evals(&point)),
+                point,
+                &evals.iter().collect::<Vec<_>>(),
+                transcript,
+            );
+        }
+        let opening_proof = opening_accumulator.reduce_and_prove::<PCS>(generators, transcript);
+
+        Self {
+            first_step,
+            last_step,
+            opening_proof,
+        }
+    }
+
+    /// Verifies the opened values against the witness commitments of the chunk's `instance`.
+    pub fn verify<const C: usize, I: ConstraintInput>(
+        &self,
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
+        generators: &PCS::Setup,
+        instance: &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        let num_vars = instance.witness_commitments.len();
+        for evals in [&self.first_step, &self.last_step] {
+            if evals.len() != num_vars {
+                return Err(ProofVerifyError::InvalidInputLength(num_vars, evals.len()));
+            }
+        }
+        transcript.append_scalars(&self.first_step);
+        transcript.append_scalars(&self.last_step);
+
+        let num_steps = constraint_builder.uniform_repeat();
+        let commitments: Vec<&PCS::Commitment> = instance.witness_commitments.iter().collect();
+        let mut opening_accumulator: VerifierOpeningAccumulator<F, PCS, ProofTranscript> =
+            VerifierOpeningAccumulator::new();
+        for (step, evals) in [(0, &self.first_step), (num_steps - 1, &self.last_step)] {
+            opening_accumulator.append(
+                &commitments,
+                index_to_field_bitvector(step, num_steps.log_2()),
+                &evals.iter().collect::<Vec<_>>(),
+                transcript,
+            );
+        }
+        opening_accumulator.reduce_and_verify(generators, &self.opening_proof, transcript)
+    }
+
+    /// Whether the offset constraints hold between the last step of this chunk and the first
+    /// step of the `next`, or at the end of the trace if there is no next chunk.
+    pub fn links_to<const C: usize, I: ConstraintInput>(
+        &self,
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
+        next: Option<&Self>,
+    ) -> bool {
+        constraint_builder
+            .offset_constraints_hold(&self.last_step, next.map(|next| next.first_step.as_slice()))
+    }
+}
+
+/// Proves that a relaxed instance is satisfied, compressing the instance folded from the chunks
+/// of a trace. This is Spartan for relaxed R1CS: the outer sum-check is over
+/// `Az ∘ Bz - u·Cz - E`, with `E` opened at its point, and the inner sum-check is over the
+/// witness vector `z = (W, u)`.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct RelaxedSpartanProof<
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+> {
+    outer_sumcheck_proof: SumcheckInstanceProof<F, ProofTranscript>,
+    outer_sumcheck_claims: (F, F, F),
+    error_eval: F,
+    inner_sumcheck_proof: SumcheckInstanceProof<F, ProofTranscript>,
+    claimed_witness_evals: Vec<F>,
+    opening_proof: ReducedOpeningProof<F, PCS, ProofTranscript>,
+}
+
+impl<F, PCS, ProofTranscript> RelaxedSpartanProof<F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: AdditivelyHomomorphic<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "RelaxedSpartanProof::prove")
+    )]
+    pub fn prove<const C: usize, I: ConstraintInput>(
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
+        key: &UniformSpartanKey<C, I, F>,
+        generators: &PCS::Setup,
+        instance: &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        witness: &RelaxedR1CSWitness<F>,
+        transcript: &mut ProofTranscript,
+    ) -> Self {
+        transcript.append_scalar(&key.digest());
+        instance.append_to_transcript(transcript);
+        let u = instance.u;
+
+        let num_rounds_x = key.num_rows_total().log_2();
+        let num_rounds_y = key.num_cols_total().log_2();
+
+        // outer sum-check
+        let tau = (0..num_rounds_x)
+            .map(|_i| transcript.challenge_scalar())
+            .collect::<Vec<F>>();
+        let (az, bz, cz) = witness.relaxed_Az_Bz_Cz(constraint_builder, u);
+        let mut polys = vec![
+            DensePolynomial::new(EqPolynomial::evals(&tau)),
+            DensePolynomial::new(az),
+            DensePolynomial::new(bz),
+            DensePolynomial::new(cz),
+            witness.error.clone(),
+        ];
+        let (outer_sumcheck_proof, r_x, outer_evals) =
+            SumcheckInstanceProof::prove_arbitrary_with_order(
+                &F::zero(),
+                num_rounds_x,
+                &mut polys,
+                |evals: &[F]| evals[0] * (evals[1] * evals[2] - u * evals[3] - evals[4]),
+                3,
+                BindingOrder::HighToLow,
+                None,
+                transcript,
+            );
+        let (outer_sumcheck_claims, error_eval) = (
+            (outer_evals[1], outer_evals[2], outer_evals[3]),
+            outer_evals[4],
+        );
+        transcript.append_scalars(&outer_evals[1..]);
+
+        let mut opening_accumulator = ProverOpeningAccumulator::for_scheme::<PCS>();
+        opening_accumulator.append(
+            &[&witness.error],
+            DensePolynomial::new(EqPolynomial::evals(&r_x)),
+            r_x.clone(),
+            &[&error_eval],
+            transcript,
+        );
+
+        // inner sum-check, over the rows of Az, Bz and Cz
+        let r_inner_sumcheck_RLC: F = transcript.challenge_scalar();
+        let (claim_Az, claim_Bz, claim_Cz) = outer_sumcheck_claims;
+        let claim_inner_joint =
+            claim_Az + r_inner_sumcheck_RLC * claim_Bz + r_inner_sumcheck_RLC.square() * claim_Cz;
+
+        let (rx_con, rx_ts) = r_x.split_at(r_x.len() - key.num_steps.log_2());
+        let rx_ts = StepPoint::new(rx_ts);
+        let mut poly_ABC = key.evaluate_r1cs_mle_rlc(rx_con, &rx_ts, r_inner_sumcheck_RLC);
+        key.remove_last_step_offset_rows(rx_con, &rx_ts, r_inner_sumcheck_RLC, &mut poly_ABC);
+
+        // z = [W, u, 0, ...], laid out as in `UniformSpartanProof`
+        let mut z = unsafe_allocate_zero_vec(key.num_cols_total());
+        z.par_chunks_mut(key.num_steps)
+            .zip(witness.witness.par_iter())
+            .for_each(|(chunk, poly)| chunk.copy_from_slice(poly.evals_ref()));
+        z[key.num_vars_total()] = u;
+
+        let mut polys = vec![DensePolynomial::new(poly_ABC), DensePolynomial::new(z)];
+        let (inner_sumcheck_proof, r_y, _claims_inner) =
+            SumcheckInstanceProof::prove_arbitrary_with_order(
+                &claim_inner_joint,
+                num_rounds_y,
+                &mut polys,
+                |evals: &[F]| evals[0] * evals[1],
+                2,
+                BindingOrder::HighToLow,
+                None,
+                transcript,
+            );
+        polys.into_iter().for_each(DensePolynomial::recycle);
+
+        let n_prefix = key.uniform_r1cs.num_vars.next_power_of_two().log_2() + 1;
+        let r_col_step = &r_y[n_prefix..];
+        let chi = EqPolynomial::evals(r_col_step);
+        let witness_refs: Vec<&DensePolynomial<F>> = witness.witness.iter().collect();
+        let claimed_witness_evals: Vec<F> = witness_refs
+            .par_iter()
+            .map(|poly| poly.evaluate_at_chi_low_optimized(&chi))
+            .collect();
+        opening_accumulator.append(
+            &witness_refs,
+            DensePolynomial::new(chi),
+            r_col_step.to_vec(),
+            &claimed_witness_evals.iter().collect::<Vec<_>>(),
+            transcript,
+        );
+        let opening_proof = opening_accumulator.reduce_and_prove::<PCS>(generators, transcript);
+
+        Self {
+            outer_sumcheck_proof,
+            outer_sumcheck_claims,
+            error_eval,
+            inner_sumcheck_proof,
+            claimed_witness_evals,
+            opening_proof,
+        }
+    }
+
+    pub fn verify<const C: usize, I: ConstraintInput>(
+        &self,
+        key: &UniformSpartanKey<C, I, F>,
+        generators: &PCS::Setup,
+        instance: &RelaxedR1CSInstance<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), SpartanError> {
+        // Scaling rows by a selector is not linear in the witness, so cannot be folded
+        assert!(
+            key.uniform_r1cs.step_kinds.is_empty(),
+            "constraints with step kinds cannot be relaxed"
+        );
+        let num_vars = key.uniform_r1cs.num_vars;
+        if self.claimed_witness_evals.len() != num_vars
+            || instance.witness_commitments.len() != num_vars
+        {
+            return Err(SpartanError::InvalidWitnessLength);
+        }
+
+        transcript.append_scalar(&key.digest());
+        instance.append_to_transcript(transcript);
+        let u = instance.u;
+
+        let num_rounds_x = key.num_rows_total().log_2();
+        let num_rounds_y = key.num_cols_total().log_2();
+
+        // outer sum-check
+        let tau = (0..num_rounds_x)
+            .map(|_i| transcript.challenge_scalar())
+            .collect::<Vec<F>>();
+        let (claim_outer_final, r_x) = self
+            .outer_sumcheck_proof
+            .verify(F::zero(), num_rounds_x, 3, transcript)
+            .map_err(|_| SpartanError::InvalidOuterSumcheckProof)?;
+
+        let (claim_Az, claim_Bz, claim_Cz) = self.outer_sumcheck_claims;
+        let taus_bound_rx = EqPolynomial::new(tau).evaluate(&r_x);
+        let claim_outer_final_expected =
+            taus_bound_rx * (claim_Az * claim_Bz - u * claim_Cz - self.error_eval);
+        if claim_outer_final != claim_outer_final_expected {
+            return Err(SpartanError::InvalidOuterSumcheckClaim);
+        }
+        transcript.append_scalars([claim_Az, claim_Bz, claim_Cz, self.error_eval].as_slice());
+
+        let mut opening_accumulator: VerifierOpeningAccumulator<F, PCS, ProofTranscript> =
+            VerifierOpeningAccumulator::new();
+        opening_accumulator.append(
+            &[&instance.error_commitment],
+            r_x.clone(),
+            &[&self.error_eval],
+            transcript,
+        );
+
+        // inner sum-check
+        let r_inner_sumcheck_RLC: F = transcript.challenge_scalar();
+        let r_inner_sumcheck_RLC_sq = r_inner_sumcheck_RLC.square();
+        let claim_inner_joint =
+            claim_Az + r_inner_sumcheck_RLC * claim_Bz + r_inner_sumcheck_RLC_sq * claim_Cz;
+        let (claim_inner_final, r_y) = self
+            .inner_sumcheck_proof
+            .verify(claim_inner_joint, num_rounds_y, 2, transcript)
+            .map_err(|_| SpartanError::InvalidInnerSumcheckProof)?;
+
+        let eval_Z = key.evaluate_relaxed_z_mle(&self.claimed_witness_evals, &r_y, u);
+        let (rx_con, rx_ts) = r_x.split_at(r_x.len() - key.num_steps.log_2());
+        let rx_ts = StepPoint::new(rx_ts);
+        let (eval_a, eval_b, eval_c) = key.evaluate_r1cs_matrix_mles(rx_con, &rx_ts, &r_y);
+        let (last_step_a, last_step_b) = key.evaluate_last_step_offset_rows(rx_con, &rx_ts, &r_y);
+        let claim_inner_final_expected = (eval_a - last_step_a
+            + r_inner_sumcheck_RLC * (eval_b - last_step_b)
+            + r_inner_sumcheck_RLC_sq * eval_c)
+            * eval_Z;
+        if claim_inner_final != claim_inner_final_expected {
+            return Err(SpartanError::InvalidInnerSumcheckClaim);
+        }
+
+        let n_prefix = num_vars.next_power_of_two().log_2() + 1;
+        opening_accumulator.append(
+            &instance.witness_commitments.iter().collect::<Vec<_>>(),
+            r_y[n_prefix..].to_vec(),
+            &self.claimed_witness_evals.iter().collect::<Vec<_>>(),
+            transcript,
+        );
+        opening_accumulator
+            .reduce_and_verify(generators, &self.opening_proof, transcript)
+            .map_err(|_| SpartanError::InvalidPCSProof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jolt::vm::rv32i_vm::{C, RV32I};
+    use crate::poly::commitment::mock::MockCommitScheme;
+    use crate::r1cs::builder::{OffsetEqConstraint, R1CSBuilder};
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+    use ark_std::{test_rng, UniformRand, Zero};
+    use common::rv_trace::CircuitFlags;
+
+    type JoltR1CSInputs = crate::r1cs::inputs::JoltR1CSInputs<RV32I>;
+
+    type PCS = MockCommitScheme<Fr, KeccakTranscript>;
+
+    const X: JoltR1CSInputs = JoltR1CSInputs::RS1_Read;
+    const Y: JoltR1CSInputs = JoltR1CSInputs::RS2_Read;
+    const Z: JoltR1CSInputs = JoltR1CSInputs::RD_Write;
+    const JUMP: JoltR1CSInputs = JoltR1CSInputs::OpFlags(CircuitFlags::Jump);
+
+    /// `x * y == z`, `RAM_Write == RAM_Read + 3`, and `z' == x` at jumps.
+    fn builder(num_steps: usize) -> CombinedUniformBuilder<C, Fr, JoltR1CSInputs> {
+        let mut uniform_builder = R1CSBuilder::<C, Fr, JoltR1CSInputs>::new();
+        uniform_builder.constrain_prod(X, Y, Z);
+        // Has a constant term, which is scaled by u
+        uniform_builder.constrain_eq(JoltR1CSInputs::RAM_Write, JoltR1CSInputs::RAM_Read + 3);
+        CombinedUniformBuilder::construct(
+            uniform_builder,
+            num_steps,
+            vec![OffsetEqConstraint::new(
+                (JUMP, false),
+                (Z, true),
+                (X, false),
+            )],
+        )
+    }
+
+    #[test]
+    fn fold_chunks() {
+        let num_steps = 8;
+        let builder = builder(num_steps);
+
+        let mut rng = test_rng();
+        let mut chunk = || {
+            let mut random_poly =
+                || DensePolynomial::new((0..num_steps).map(|_| Fr::rand(&mut rng)).collect());
+            let mut polys: Vec<DensePolynomial<Fr>> = JoltR1CSInputs::flatten::<C>()
+                .iter()
+                .map(|_| DensePolynomial::new(vec![Fr::zero(); num_steps]))
+                .collect();
+            let (xs, ys, ram) = (random_poly(), random_poly(), random_poly());
+            polys[Z.to_index::<C>()] = DensePolynomial::new(
+                xs.evals_ref()
+                    .iter()
+                    .zip(ys.evals_ref())
+                    .map(|(x, y)| x * y)
+                    .collect(),
+            );
+            polys[X.to_index::<C>()] = xs;
+            polys[Y.to_index::<C>()] = ys;
+            polys[JoltR1CSInputs::RAM_Read.to_index::<C>()] = DensePolynomial::new(
+                ram.evals_ref()
+                    .iter()
+                    .map(|ram| *ram + Fr::from(3u64))
+                    .collect(),
+            );
+            polys[JoltR1CSInputs::RAM_Write.to_index::<C>()] = ram;
+            let witness = RelaxedR1CSWitness::new(&builder, polys);
+            let instance = witness.commit::<PCS, KeccakTranscript>(Fr::from(1u64), &());
+            (instance, witness)
+        };
+
+        let (mut running_instance, mut running_witness) = chunk();
+        assert!(running_witness.is_satisfied(&builder, running_instance.u));
+        let mut prover_transcript = KeccakTranscript::new(b"folding");
+        let mut verifier_transcript = KeccakTranscript::new(b"folding");
+        let mut verifier_instance = copy_instance(&running_instance);
+        for _ in 0..3 {
+            let (incoming_instance, incoming_witness) = chunk();
+            let (proof, instance, witness) = FoldingProof::prove(
+                &builder,
+                &(),
+                (&running_instance, &running_witness),
+                (&incoming_instance, &incoming_witness),
+                &mut prover_transcript,
+            );
+            verifier_instance = proof.verify(
+                &verifier_instance,
+                &incoming_instance,
+                &mut verifier_transcript,
+            );
+
+            assert!(witness.is_satisfied(&builder, instance.u));
+            assert_eq!(verifier_instance, instance);
+            assert_eq!(
+                witness.commit::<PCS, KeccakTranscript>(instance.u, &()),
+                instance
+            );
+            (running_instance, running_witness) = (instance, witness);
+        }
+
+        // A witness folded from an unsatisfying chunk does not satisfy the folded instance
+        let (incoming_instance, mut incoming_witness) = chunk();
+        incoming_witness.witness[Z.to_index::<C>()].Z[3] += Fr::from(1u64);
+        let (_, instance, witness) = FoldingProof::prove(
+            &builder,
+            &(),
+            (&running_instance, &running_witness),
+            (&incoming_instance, &incoming_witness),
+            &mut prover_transcript,
+        );
+        assert!(!witness.is_satisfied(&builder, instance.u));
+    }
+
+    #[test]
+    fn compress_folded_chunks() {
+        let (num_steps, num_chunks) = (8, 4);
+        let builder = builder(num_steps);
+        let key = UniformSpartanKey::from_builder(&builder);
+
+        // A trace jumping at every step but the last, so that z' == x links each chunk to the
+        // next
+        let mut rng = test_rng();
+        let trace_len = num_steps * num_chunks;
+        let xs: Vec<Fr> = (0..trace_len).map(|_| Fr::rand(&mut rng)).collect();
+        let zs: Vec<Fr> = std::iter::once(Fr::rand(&mut rng))
+            .chain(xs[..trace_len - 1].iter().copied())
+            .collect();
+        let chunks: Vec<RelaxedR1CSWitness<Fr>> = (0..num_chunks)
+            .map(|chunk| {
+                let steps = chunk * num_steps..(chunk + 1) * num_steps;
+                let mut polys: Vec<DensePolynomial<Fr>> = JoltR1CSInputs::flatten::<C>()
+                    .iter()
+                    .map(|_| DensePolynomial::new(vec![Fr::zero(); num_steps]))
+                    .collect();
+                polys[X.to_index::<C>()] = DensePolynomial::new(xs[steps.clone()].to_vec());
+                polys[Z.to_index::<C>()] = DensePolynomial::new(zs[steps.clone()].to_vec());
+                polys[Y.to_index::<C>()] = DensePolynomial::new(
+                    steps
+                        .clone()
+                        .map(|step| zs[step] * xs[step].inverse().unwrap())
+                        .collect(),
+                );
+                polys[JUMP.to_index::<C>()] = DensePolynomial::new(
+                    steps
+                        .map(|step| Fr::from((step != trace_len - 1) as u64))
+                        .collect(),
+                );
+                polys[JoltR1CSInputs::RAM_Write.to_index::<C>()] =
+                    DensePolynomial::new(vec![Fr::from(3u64); num_steps]);
+                RelaxedR1CSWitness::new(&builder, polys)
+            })
+            .collect();
+
+        let mut prover_transcript = KeccakTranscript::new(b"folding");
+        let mut verifier_transcript = KeccakTranscript::new(b"folding");
+        let mut instances = vec![];
+        let mut boundary_proofs = vec![];
+        for witness in chunks.iter() {
+            let instance = witness.commit::<PCS, KeccakTranscript>(Fr::from(1u64), &());
+            let proof = BoundaryProof::prove(&(), witness, &mut prover_transcript);
+            proof
+                .verify(&builder, &(), &instance, &mut verifier_transcript)
+                .unwrap();
+            instances.push(instance);
+            boundary_proofs.push(proof);
+        }
+        let next_proofs = boundary_proofs.iter().skip(1).map(Some).chain([None]);
+        for (proof, next) in boundary_proofs.iter().zip(next_proofs) {
+            assert!(proof.links_to(&builder, next));
+        }
+        // Chunks out of order are not linked
+        assert!(!boundary_proofs[0].links_to(&builder, Some(&boundary_proofs[2])));
+        assert!(!boundary_proofs[0].links_to(&builder, None));
+
+        let mut running_witness = RelaxedR1CSWitness {
+            witness: chunks[0].witness.clone(),
+            error: chunks[0].error.clone(),
+        };
+        let mut running_instance = copy_instance(&instances[0]);
+        let mut verifier_instance = copy_instance(&instances[0]);
+        for (incoming_instance, incoming_witness) in instances.iter().zip(chunks.iter()).skip(1) {
+            let (proof, instance, witness) = FoldingProof::prove(
+                &builder,
+                &(),
+                (&running_instance, &running_witness),
+                (incoming_instance, incoming_witness),
+                &mut prover_transcript,
+            );
+            verifier_instance = proof.verify(
+                &verifier_instance,
+                incoming_instance,
+                &mut verifier_transcript,
+            );
+            (running_instance, running_witness) = (instance, witness);
+        }
+        assert!(running_witness.is_satisfied(&builder, running_instance.u));
+
+        let proof = RelaxedSpartanProof::prove(
+            &builder,
+            &key,
+            &(),
+            &running_instance,
+            &running_witness,
+            &mut prover_transcript,
+        );
+        let mut tampered_transcript = verifier_transcript.clone();
+        proof
+            .verify(&key, &(), &verifier_instance, &mut verifier_transcript)
+            .unwrap();
+
+        // The proof does not verify for another u
+        let mut tampered_instance = copy_instance(&verifier_instance);
+        tampered_instance.u += Fr::from(1u64);
+        assert!(proof
+            .verify(&key, &(), &tampered_instance, &mut tampered_transcript)
+            .is_err());
+    }
+
+    fn copy_instance(
+        instance: &RelaxedR1CSInstance<Fr, PCS, KeccakTranscript>,
+    ) -> RelaxedR1CSInstance<Fr, PCS, KeccakTranscript> {
+        RelaxedR1CSInstance {
+            witness_commitments: instance.witness_commitments.clone(),
+            error_commitment: instance.error_commitment.clone(),
+            u: instance.u,
+        }
+    }
+}
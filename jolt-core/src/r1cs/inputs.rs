@@ -7,18 +7,19 @@
 use crate::impl_r1cs_input_lc_conversions;
 use crate::jolt::instruction::JoltInstructionSet;
 use crate::jolt::vm::rv32i_vm::RV32I;
-use crate::jolt::vm::{JoltCommitments, JoltStuff, JoltTraceStep};
+use crate::jolt::vm::{JoltCommitments, JoltStuff, JoltTrace};
 use crate::lasso::memory_checking::{Initializable, StructuredPolynomialData};
 use crate::poly::commitment::commitment_scheme::CommitmentScheme;
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::opening_proof::VerifierOpeningAccumulator;
-use crate::utils::thread::unsafe_allocate_zero_vec;
+use crate::utils::thread::{transpose_chunks_mut, unsafe_allocate_zero_vec};
 use crate::utils::transcript::Transcript;
 
 use super::key::UniformSpartanKey;
 use super::spartan::{SpartanError, UniformSpartanProof};
 
 use crate::field::JoltField;
+use crate::utils::par::{self, prelude::*};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::log2;
 use common::rv_trace::{CircuitFlags, NUM_CIRCUIT_FLAGS};
@@ -158,37 +159,53 @@ pub type R1CSCommitments<PCS: CommitmentScheme<ProofTranscript>, ProofTranscript
     R1CSStuff<PCS::Commitment>;
 
 impl<F: JoltField> R1CSPolynomials<F> {
-    #[tracing::instrument(skip_all, name = "R1CSPolynomials::new")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "R1CSPolynomials::new")
+    )]
     pub fn new<
         const C: usize,
         const M: usize,
         InstructionSet: JoltInstructionSet,
         I: ConstraintInput,
     >(
-        trace: &[JoltTraceStep<InstructionSet>],
+        trace: &(impl JoltTrace<InstructionSet> + ?Sized),
     ) -> Self {
         let log_M = log2(M) as usize;
 
-        let mut chunks_x = vec![unsafe_allocate_zero_vec(trace.len()); C];
-        let mut chunks_y = vec![unsafe_allocate_zero_vec(trace.len()); C];
-        let mut circuit_flags = vec![unsafe_allocate_zero_vec(trace.len()); NUM_CIRCUIT_FLAGS];
-
-        // TODO(moodlezoup): Can be parallelized
-        for (step_index, step) in trace.iter().enumerate() {
-            if let Some(instr) = &step.instruction_lookup {
-                let (x, y) = instr.operand_chunks(C, log_M);
-                for i in 0..C {
-                    chunks_x[i][step_index] = F::from_u64(x[i]).unwrap();
-                    chunks_y[i][step_index] = F::from_u64(y[i]).unwrap();
+        let num_steps = trace.len();
+        // Laid out as chunks_x || chunks_y || circuit_flags, so that every thread can fill its
+        // range of steps in all of them at once.
+        let mut columns: Vec<Vec<F>> = (0..2 * C + NUM_CIRCUIT_FLAGS)
+            .map(|_| unsafe_allocate_zero_vec(num_steps))
+            .collect();
+        let chunk_size = num_steps.div_ceil(par::current_num_threads()).max(1);
+        transpose_chunks_mut(&mut columns, chunk_size)
+            .into_par_iter()
+            .enumerate()
+            .for_each(|(chunk_index, mut chunk)| {
+                let (chunks_x, rest) = chunk.split_at_mut(C);
+                let (chunks_y, circuit_flags) = rest.split_at_mut(C);
+                for offset in 0..chunks_x[0].len() {
+                    let step = trace.step(chunk_index * chunk_size + offset);
+                    if let Some(instr) = &step.instruction_lookup {
+                        let (x, y) = instr.operand_chunks(C, log_M);
+                        for i in 0..C {
+                            chunks_x[i][offset] = F::from_u64(x[i]).unwrap();
+                            chunks_y[i][offset] = F::from_u64(y[i]).unwrap();
+                        }
+                    }
+
+                    for j in 0..NUM_CIRCUIT_FLAGS {
+                        if step.circuit_flags[j] {
+                            circuit_flags[j][offset] = F::one();
+                        }
+                    }
                 }
-            }
-
-            for j in 0..NUM_CIRCUIT_FLAGS {
-                if step.circuit_flags[j] {
-                    circuit_flags[j][step_index] = F::one();
-                }
-            }
-        }
+            });
+        let circuit_flags = columns.split_off(2 * C);
+        let chunks_y = columns.split_off(C);
+        let chunks_x = columns;
 
         Self {
             chunks_x: chunks_x
@@ -212,27 +229,35 @@ impl<F: JoltField> R1CSPolynomials<F> {
 }
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct R1CSProof<const C: usize, I: ConstraintInput, F: JoltField, ProofTranscript: Transcript>
-{
+pub struct R1CSProof<
+    const C: usize,
+    I: ConstraintInput,
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+> {
     pub key: UniformSpartanKey<C, I, F>,
-    pub proof: UniformSpartanProof<C, I, F, ProofTranscript>,
+    pub proof: UniformSpartanProof<C, I, F, PCS, ProofTranscript>,
     pub _marker: PhantomData<ProofTranscript>,
 }
 
-impl<const C: usize, I: ConstraintInput, F: JoltField, ProofTranscript: Transcript>
-    R1CSProof<C, I, F, ProofTranscript>
+impl<const C: usize, I, F, PCS, ProofTranscript> R1CSProof<C, I, F, PCS, ProofTranscript>
+where
+    I: ConstraintInput,
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
 {
-    #[tracing::instrument(skip_all, name = "R1CSProof::verify")]
-    pub fn verify<PCS>(
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "R1CSProof::verify")
+    )]
+    pub fn verify(
         &self,
         commitments: &JoltCommitments<PCS, ProofTranscript>,
         opening_accumulator: &mut VerifierOpeningAccumulator<F, PCS, ProofTranscript>,
         transcript: &mut ProofTranscript,
-    ) -> Result<(), SpartanError>
-    where
-        PCS: CommitmentScheme<ProofTranscript, Field = F>,
-        ProofTranscript: Transcript,
-    {
+    ) -> Result<(), SpartanError> {
         self.proof
             .verify(&self.key, commitments, opening_accumulator, transcript)
     }
@@ -259,6 +284,12 @@ pub trait ConstraintInput: Clone + Copy + Debug + PartialEq + Sync + Send + 'sta
         Self::flatten::<C>()[index]
     }
 
+    /// Whether this input is a flag packed into the bytecode bitflags, and so is bound to
+    /// the preprocessed bytecode.
+    fn is_bytecode_flag(&self) -> bool {
+        false
+    }
+
     /// Converts a constraint input to its index in the canonical
     /// ordering over inputs given by `ConstraintInput::flatten`.
     fn to_index<const C: usize>(&self) -> usize {
@@ -283,9 +314,11 @@ pub trait ConstraintInput: Clone + Copy + Debug + PartialEq + Sync + Send + 'sta
     ) -> &'a mut T;
 }
 
+/// The inputs to Jolt's R1CS constraints, with an instruction flag for each instruction in
+/// `InstructionSet`.
 #[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Debug, PartialEq, EnumIter)]
-pub enum JoltR1CSInputs {
+pub enum JoltR1CSInputs<InstructionSet: JoltInstructionSet = RV32I> {
     Bytecode_A, // Virtual address
     // Bytecode_V
     Bytecode_ELFAddress,
@@ -309,7 +342,7 @@ pub enum JoltR1CSInputs {
     ChunksY(usize),
 
     OpFlags(CircuitFlags),
-    InstructionFlags(RV32I),
+    InstructionFlags(InstructionSet),
     Aux(AuxVariable),
 }
 
@@ -327,16 +360,21 @@ pub enum AuxVariable {
     NextPC,
 }
 
-impl_r1cs_input_lc_conversions!(JoltR1CSInputs, 4);
-impl ConstraintInput for JoltR1CSInputs {
+impl_r1cs_input_lc_conversions!(
+    impl<InstructionSet: JoltInstructionSet> JoltR1CSInputs<InstructionSet>,
+    4
+);
+impl<InstructionSet: JoltInstructionSet> ConstraintInput for JoltR1CSInputs<InstructionSet> {
     fn flatten<const C: usize>() -> Vec<Self> {
-        JoltR1CSInputs::iter()
+        Self::iter()
             .flat_map(|variant| match variant {
                 Self::ChunksQuery(_) => (0..C).map(Self::ChunksQuery).collect(),
                 Self::ChunksX(_) => (0..C).map(Self::ChunksX).collect(),
                 Self::ChunksY(_) => (0..C).map(Self::ChunksY).collect(),
                 Self::OpFlags(_) => CircuitFlags::iter().map(Self::OpFlags).collect(),
-                Self::InstructionFlags(_) => RV32I::iter().map(Self::InstructionFlags).collect(),
+                Self::InstructionFlags(_) => {
+                    InstructionSet::iter().map(Self::InstructionFlags).collect()
+                }
                 Self::Aux(_) => AuxVariable::iter()
                     .flat_map(|aux| match aux {
                         AuxVariable::RelevantYChunk(_) => (0..C)
@@ -350,6 +388,10 @@ impl ConstraintInput for JoltR1CSInputs {
             .collect()
     }
 
+    fn is_bytecode_flag(&self) -> bool {
+        matches!(self, Self::OpFlags(_) | Self::InstructionFlags(_))
+    }
+
     fn get_ref<'a, T: CanonicalSerialize + CanonicalDeserialize + Sync>(
         &self,
         jolt: &'a JoltStuff<T>,
@@ -376,7 +418,7 @@ impl ConstraintInput for JoltR1CSInputs {
             JoltR1CSInputs::ChunksY(i) => &jolt.r1cs.chunks_y[*i],
             JoltR1CSInputs::OpFlags(i) => &jolt.r1cs.circuit_flags[*i as usize],
             JoltR1CSInputs::InstructionFlags(i) => {
-                &jolt.instruction_lookups.instruction_flags[RV32I::enum_index(i)]
+                &jolt.instruction_lookups.instruction_flags[InstructionSet::enum_index(i)]
             }
             Self::Aux(aux) => match aux {
                 AuxVariable::LeftLookupOperand => &aux_polynomials.left_lookup_operand,
@@ -424,6 +466,8 @@ mod tests {
 
     use super::*;
 
+    type JoltR1CSInputs = super::JoltR1CSInputs<RV32I>;
+
     #[test]
     fn from_index_to_index() {
         const C: usize = 4;
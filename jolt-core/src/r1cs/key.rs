@@ -5,8 +5,9 @@ use sha3::Sha3_256;
 
 use crate::{
     field::JoltField,
-    poly::eq_poly::EqPolynomial,
+    poly::{buffer_pool::BufferPool, eq_poly::EqPolynomial},
     r1cs::special_polys::{eq_plus_one, SparsePolynomial},
+    subprotocols::univariate_skip::lagrange_evals,
     utils::{index_to_field_bitvector, mul_0_1_optimized, thread::unsafe_allocate_zero_vec},
 };
 
@@ -15,7 +16,7 @@ use sha3::Digest;
 
 use crate::utils::math::Math;
 
-use rayon::prelude::*;
+use crate::utils::par::prelude::*;
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct UniformSpartanKey<const C: usize, I: ConstraintInput, F: JoltField> {
@@ -37,11 +38,16 @@ pub struct UniformSpartanKey<const C: usize, I: ConstraintInput, F: JoltField> {
 /// (row, col, value)
 pub type Coeff<F> = (usize, usize, F);
 
-/// Sparse representation of a single R1CS matrix.
-#[derive(CanonicalSerialize, CanonicalDeserialize)]
+/// Sparse representation of a single R1CS matrix. The non-constant coefficients are stored in
+/// compressed sparse row (CSR) layout, so that each row can be processed independently.
+#[derive(Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct SparseConstraints<F: JoltField> {
-    /// Non-zero, non-constant coefficients
-    pub vars: Vec<Coeff<F>>,
+    /// The non-zero, non-constant coefficients of `row` are at indices
+    /// `row_offsets[row]..row_offsets[row + 1]` of `cols` and `vals`. Trailing empty rows are
+    /// omitted.
+    pub row_offsets: Vec<usize>,
+    pub cols: Vec<usize>,
+    pub vals: Vec<F>,
 
     /// Non-zero constant coefficients stored as (uniform_row_index, coeff)
     pub consts: Vec<(usize, F)>,
@@ -49,16 +55,99 @@ pub struct SparseConstraints<F: JoltField> {
 
 impl<F: JoltField> SparseConstraints<F> {
     pub fn empty_with_capacity(vars: usize, consts: usize) -> Self {
+        let mut row_offsets = Vec::with_capacity(consts + 1);
+        row_offsets.push(0);
         Self {
-            vars: Vec::with_capacity(vars),
+            row_offsets,
+            cols: Vec::with_capacity(vars),
+            vals: Vec::with_capacity(vars),
             consts: Vec::with_capacity(consts),
         }
     }
+
+    /// Appends a non-constant coefficient. Coefficients must be pushed in row order.
+    pub fn push_var(&mut self, row: usize, col: usize, val: F) {
+        assert!(
+            row + 2 >= self.row_offsets.len(),
+            "coefficients must be pushed in row order"
+        );
+        self.row_offsets.resize(row + 2, self.cols.len());
+        self.cols.push(col);
+        self.vals.push(val);
+        *self.row_offsets.last_mut().unwrap() += 1;
+    }
+
+    /// Number of non-zero, non-constant coefficients.
+    pub fn num_vars(&self) -> usize {
+        self.cols.len()
+    }
+
+    /// Iterates over the non-constant coefficients of `row`, as (col, value) pairs.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (usize, &F)> {
+        let range = match self.row_offsets.get(row + 1) {
+            Some(end) => self.row_offsets[row]..*end,
+            None => 0..0,
+        };
+        self.cols[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.vals[range].iter())
+    }
+
+    /// Iterates over all non-constant coefficients in row order.
+    pub fn vars(&self) -> impl Iterator<Item = Coeff<F>> + '_ {
+        (0..self.row_offsets.len() - 1)
+            .flat_map(move |row| self.row(row).map(move |(col, val)| (row, col, *val)))
+    }
+
+    /// Computes `M z`, where the constant column of `M` multiplies an implicit 1. `z` must cover
+    /// every column referenced by the matrix.
+    pub fn multiply_vec(&self, num_rows: usize, z: &[F]) -> Vec<F> {
+        let mut result: Vec<F> = (0..num_rows)
+            .into_par_iter()
+            .map(|row| {
+                self.row(row)
+                    .map(|(col, val)| mul_0_1_optimized(val, &z[col]))
+                    .sum()
+            })
+            .collect();
+        for (row, val) in self.consts.iter() {
+            result[*row] += val;
+        }
+        result
+    }
+
+    /// Computes `sum_row eq_rows[row] * sum_col M[row][col] * eq_cols[col]` over the non-constant
+    /// coefficients.
+    pub fn evaluate_vars(&self, eq_rows: &[F], eq_cols: &[F]) -> F {
+        (0..self.row_offsets.len() - 1)
+            .into_par_iter()
+            .filter(|row| !eq_rows[*row].is_zero())
+            .map(|row| {
+                self.row(row)
+                    .map(|(col, val)| mul_0_1_optimized(val, &eq_cols[col]))
+                    .sum::<F>()
+                    * eq_rows[row]
+            })
+            .sum()
+    }
+
+    /// Adds `eq_rows^T M` to `evals` over the non-constant coefficients.
+    pub fn add_vec_matrix_product(&self, eq_rows: &[F], evals: &mut [F]) {
+        for (row, eq_row) in eq_rows.iter().enumerate().take(self.row_offsets.len() - 1) {
+            if eq_row.is_zero() {
+                continue;
+            }
+            for (col, val) in self.row(row) {
+                evals[col] += mul_0_1_optimized(val, eq_row);
+            }
+        }
+    }
 }
 
 /// Sparse representation of all 3 uniform R1CS matrices. Uniform matrices can be repeated over a number of steps
 /// and efficiently evaluated by taking advantage of the structure.
-#[derive(CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct UniformR1CS<F: JoltField> {
     pub a: SparseConstraints<F>,
     pub b: SparseConstraints<F>,
@@ -69,6 +158,142 @@ pub struct UniformR1CS<F: JoltField> {
 
     /// Unpadded number of rows in uniform instance.
     pub num_rows: usize,
+
+    /// Blocks of rows only enforced at the steps of a given kind.
+    pub step_kinds: Vec<StepKind>,
+}
+
+/// A block of uniform constraints which is only enforced at the steps where `selector` is
+/// non-zero, so that steps of different kinds (e.g. precompiles) can be interleaved without each
+/// satisfying the constraints of every other kind. The A and C rows of the block are scaled by
+/// the (binary) selector, so that they vanish at the steps of other kinds.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct StepKind {
+    /// Index of the selector in the flattened inputs.
+    pub selector: usize,
+
+    /// The block spans the uniform rows `start_row..end_row`.
+    pub start_row: usize,
+    pub end_row: usize,
+}
+
+impl StepKind {
+    /// Zeroes the rows outside of the block.
+    fn restrict_rows<F: JoltField>(&self, eq_rx_constr: &[F]) -> Vec<F> {
+        let mut eq_rx_block = vec![F::zero(); eq_rx_constr.len()];
+        eq_rx_block[self.start_row..self.end_row]
+            .copy_from_slice(&eq_rx_constr[self.start_row..self.end_row]);
+        eq_rx_block
+    }
+
+    /// Computes `sum_row eq_rows[row] * M[row][const]` over the rows of the block.
+    fn evaluate_consts<F: JoltField>(
+        &self,
+        constraints: &SparseConstraints<F>,
+        eq_rows: &[F],
+    ) -> F {
+        constraints
+            .consts
+            .iter()
+            .filter(|(row, _)| (self.start_row..self.end_row).contains(row))
+            .map(|(row, val)| mul_0_1_optimized(val, &eq_rows[*row]))
+            .sum()
+    }
+}
+
+/// The steps' part of the row point at which the outer sum-check leaves A, B and C. With the
+/// univariate skip, the lowest `num_skipped_vars` step variables are bound together to `r_0`, and
+/// weighted by the Lagrange basis of `{0, ..., 2^num_skipped_vars - 1}` at `r_0` rather than by
+/// eq (see `subprotocols::univariate_skip`).
+#[derive(Clone, Debug)]
+pub struct StepPoint<F: JoltField> {
+    /// The step variables bound by multilinear rounds, highest first
+    pub r_step: Vec<F>,
+    /// The number of skipped variables, and `r_0`
+    pub skipped: Option<(usize, F)>,
+}
+
+impl<F: JoltField> StepPoint<F> {
+    pub fn new(r_step: &[F]) -> Self {
+        Self {
+            r_step: r_step.to_vec(),
+            skipped: None,
+        }
+    }
+
+    pub fn with_skip(r_step: &[F], num_skipped_vars: usize, r_0: F) -> Self {
+        Self {
+            r_step: r_step.to_vec(),
+            skipped: Some((num_skipped_vars, r_0)),
+        }
+    }
+
+    /// The number of step variables, including the skipped ones.
+    pub fn num_vars(&self) -> usize {
+        self.r_step.len() + self.num_skipped_vars()
+    }
+
+    fn num_skipped_vars(&self) -> usize {
+        self.skipped
+            .map_or(0, |(num_skipped_vars, _)| num_skipped_vars)
+    }
+
+    /// The Lagrange basis at `r_0`, or `[1]` if no variables are skipped.
+    fn skipped_basis(&self) -> Vec<F> {
+        match self.skipped {
+            Some((num_skipped_vars, r_0)) => lagrange_evals(num_skipped_vars.pow2(), r_0),
+            None => vec![F::one()],
+        }
+    }
+
+    /// The weights of all steps, which are `eq(r_step, step)` without the skip.
+    pub fn weights(&self) -> Vec<F> {
+        let eq_high = EqPolynomial::evals(&self.r_step);
+        let basis = self.skipped_basis();
+        let num_skipped_vars = self.num_skipped_vars();
+        (0..self.num_vars().pow2())
+            .into_par_iter()
+            .map(|step| eq_high[step >> num_skipped_vars] * basis[step % basis.len()])
+            .collect()
+    }
+
+    /// The weight of a single step.
+    pub fn weight(&self, step: usize) -> F {
+        let basis = self.skipped_basis();
+        let high = index_to_field_bitvector(step >> self.num_skipped_vars(), self.r_step.len());
+        EqPolynomial::new(self.r_step.clone()).evaluate(&high) * basis[step % basis.len()]
+    }
+
+    /// Computes `sum_step weight(step) * eq(r_col_step, step)`.
+    pub fn evaluate_eq(&self, r_col_step: &[F]) -> F {
+        let (col_high, col_low) = r_col_step.split_at(self.r_step.len());
+        let eq_low = EqPolynomial::evals(col_low);
+        let skipped_eq: F = self
+            .skipped_basis()
+            .iter()
+            .zip(eq_low.iter())
+            .map(|(l, eq)| *l * eq)
+            .sum();
+        EqPolynomial::new(self.r_step.clone()).evaluate(col_high) * skipped_eq
+    }
+
+    /// Computes `sum_step weight(step) * eq(r_col_step, step + 1)`, over all but the last step.
+    pub fn evaluate_eq_plus_one(&self, r_col_step: &[F]) -> F {
+        let (col_high, col_low) = r_col_step.split_at(self.r_step.len());
+        let eq_low = EqPolynomial::evals(col_low);
+        let basis = self.skipped_basis();
+        // Adding one only moves the skipped variables, but from the last of them, where it
+        // carries into the high variables
+        let skipped_eq_plus_one: F = basis
+            .iter()
+            .zip(eq_low.iter().skip(1))
+            .map(|(l, eq)| *l * eq)
+            .sum();
+        EqPolynomial::new(self.r_step.clone()).evaluate(col_high) * skipped_eq_plus_one
+            + eq_plus_one(&self.r_step, col_high, self.r_step.len())
+                * basis[basis.len() - 1]
+                * eq_low[0]
+    }
 }
 
 /// NonUniformR1CSConstraint only supports a single additional equality constraint. 'a' holds the equality (something minus something),
@@ -141,7 +366,7 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
         let total_rows = constraint_builder.constraint_rows().next_power_of_two();
         let num_steps = constraint_builder.uniform_repeat().next_power_of_two();
 
-        let vk_digest = Self::digest(&uniform_r1cs, &offset_eq_r1cs, num_steps);
+        let vk_digest = Self::compute_digest(&uniform_r1cs, &offset_eq_r1cs, total_rows, num_steps);
 
         Self {
             _inputs: PhantomData,
@@ -153,6 +378,12 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
         }
     }
 
+    /// A digest binding the R1CS matrices and their dimensions, to be appended to the
+    /// transcript by both the prover and the verifier before any challenge depends on them.
+    pub fn digest(&self) -> F {
+        self.vk_digest
+    }
+
     fn full_z_len(&self) -> usize {
         2 * self.num_steps * self.uniform_r1cs.num_vars.next_power_of_two()
     }
@@ -172,15 +403,18 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
     }
 
     /// Evaluates A(r_x, y) + r_rlc * B(r_x, y) + r_rlc^2 * C(r_x, y) where r_x = r_constr || r_step for all y.
-    #[tracing::instrument(skip_all, name = "UniformSpartanKey::evaluate_r1cs_mle_rlc")]
-    pub fn evaluate_r1cs_mle_rlc(&self, r_constr: &[F], r_step: &[F], r_rlc: F) -> Vec<F> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniformSpartanKey::evaluate_r1cs_mle_rlc")
+    )]
+    pub fn evaluate_r1cs_mle_rlc(&self, r_constr: &[F], r_step: &StepPoint<F>, r_rlc: F) -> Vec<F> {
         assert_eq!(
             r_constr.len(),
             (self.uniform_r1cs.num_rows + 1).next_power_of_two().log_2()
         );
-        assert_eq!(r_step.len(), self.num_steps.log_2());
+        assert_eq!(r_step.num_vars(), self.num_steps.log_2());
 
-        let eq_rx_step = EqPolynomial::evals(r_step);
+        let eq_rx_step = r_step.weights();
         let eq_rx_constr = EqPolynomial::evals(r_constr);
         let first_non_uniform_row = self.uniform_r1cs.num_rows;
         let constant_column = self.uniform_r1cs.num_vars;
@@ -190,46 +424,64 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
         // 2. Expand this RLC to the full column y by multiplying by eq(rx_step, step_index) for each step
         // 3. Add the non uniform constraint rows
 
-        let compute_repeated =
-            |constraints: &SparseConstraints<F>, non_uni_constants: Option<Vec<F>>| -> Vec<F> {
-                // +1 for constant
-                let mut evals = unsafe_allocate_zero_vec(self.uniform_r1cs.num_vars + 1);
-                for (row, col, val) in constraints.vars.iter() {
-                    evals[*col] += mul_0_1_optimized(val, &eq_rx_constr[*row]);
-                }
+        // The rows of the step kinds' blocks are scaled by their selectors in A and C, which is
+        // accounted for by `evaluate_step_kind_mle_rlc`
+        let eq_rx_constr_gated = self.gate_step_kind_rows(&eq_rx_constr);
 
-                for (row, val) in constraints.consts.iter() {
-                    evals[constant_column] += mul_0_1_optimized(val, &eq_rx_constr[*row]);
-                }
+        let compute_repeated = |constraints: &SparseConstraints<F>,
+                                eq_rx_constr: &[F],
+                                non_uni_constants: Option<Vec<F>>|
+         -> Vec<F> {
+            // +1 for constant
+            let mut evals = unsafe_allocate_zero_vec(self.uniform_r1cs.num_vars + 1);
+            constraints.add_vec_matrix_product(eq_rx_constr, &mut evals);
 
-                if let Some(non_uni_constants) = non_uni_constants {
-                    for (i, non_uni_constant) in non_uni_constants.iter().enumerate() {
-                        evals[constant_column] +=
-                            eq_rx_constr[first_non_uniform_row + i] * non_uni_constant;
-                    }
+            for (row, val) in constraints.consts.iter() {
+                evals[constant_column] += mul_0_1_optimized(val, &eq_rx_constr[*row]);
+            }
+
+            if let Some(non_uni_constants) = non_uni_constants {
+                for (i, non_uni_constant) in non_uni_constants.iter().enumerate() {
+                    evals[constant_column] +=
+                        eq_rx_constr[first_non_uniform_row + i] * non_uni_constant;
                 }
+            }
 
-                evals
-            };
+            evals
+        };
 
         let (eq_constants, condition_constants) = self.offset_eq_r1cs.constants();
-        let sm_a_r = compute_repeated(&self.uniform_r1cs.a, Some(eq_constants));
-        let sm_b_r = compute_repeated(&self.uniform_r1cs.b, Some(condition_constants));
-        let sm_c_r = compute_repeated(&self.uniform_r1cs.c, None);
+        let sm_a_r = compute_repeated(
+            &self.uniform_r1cs.a,
+            &eq_rx_constr_gated,
+            Some(eq_constants),
+        );
+        let sm_b_r = compute_repeated(
+            &self.uniform_r1cs.b,
+            &eq_rx_constr,
+            Some(condition_constants),
+        );
+        let sm_c_r = compute_repeated(&self.uniform_r1cs.c, &eq_rx_constr_gated, None);
 
         let r_rlc_sq = r_rlc.square();
-        let sm_rlc = sm_a_r
+        let mut sm_rlc = sm_a_r
             .iter()
             .zip(sm_b_r.iter())
             .zip(sm_c_r.iter())
             .map(|((a, b), c)| *a + mul_0_1_optimized(b, &r_rlc) + mul_0_1_optimized(c, &r_rlc_sq))
             .collect::<Vec<F>>();
 
-        let mut rlc = unsafe_allocate_zero_vec(self.num_cols_total());
+        // A gated constant is the selector itself
+        for kind in self.uniform_r1cs.step_kinds.iter() {
+            sm_rlc[kind.selector] += kind.evaluate_consts(&self.uniform_r1cs.a, &eq_rx_constr)
+                + r_rlc_sq * kind.evaluate_consts(&self.uniform_r1cs.c, &eq_rx_constr);
+        }
+
+        let mut rlc = BufferPool::global().take(self.num_cols_total());
 
         {
-            let span = tracing::span!(tracing::Level::INFO, "big_rlc_computation");
-            let _guard = span.enter();
+            #[cfg(feature = "tracing")]
+            let _guard = tracing::span!(tracing::Level::INFO, "big_rlc_computation").entered();
             rlc.par_chunks_mut(self.num_steps)
                 .take(self.uniform_r1cs.num_vars)
                 .enumerate()
@@ -268,8 +520,8 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
         };
 
         {
-            let span = tracing::span!(tracing::Level::INFO, "update_non_uniform");
-            let _guard = span.enter();
+            #[cfg(feature = "tracing")]
+            let _guard = tracing::span!(tracing::Level::INFO, "update_non_uniform").entered();
             for (i, constraint) in self.offset_eq_r1cs.constraints.iter().enumerate() {
                 update_non_uni(&mut rlc, &constraint.eq, i, F::one());
                 update_non_uni(&mut rlc, &constraint.condition, i, r_rlc);
@@ -279,9 +531,102 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
         rlc
     }
 
+    /// For each step kind, evaluates the non-constant coefficients of the rows of its block in
+    /// A(r_x, y) + r_rlc^2 * C(r_x, y) for all y, which are scaled by its selector.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniformSpartanKey::evaluate_step_kind_mle_rlc")
+    )]
+    pub fn evaluate_step_kind_mle_rlc(
+        &self,
+        r_constr: &[F],
+        r_step: &StepPoint<F>,
+        r_rlc: F,
+    ) -> Vec<Vec<F>> {
+        let eq_rx_step = r_step.weights();
+        let eq_rx_constr = EqPolynomial::evals(r_constr);
+        let r_rlc_sq = r_rlc.square();
+
+        self.uniform_r1cs
+            .step_kinds
+            .iter()
+            .map(|kind| {
+                let eq_rx_block = kind.restrict_rows(&eq_rx_constr);
+                let mut sm_a_r = unsafe_allocate_zero_vec(self.uniform_r1cs.num_vars);
+                let mut sm_c_r = unsafe_allocate_zero_vec(self.uniform_r1cs.num_vars);
+                self.uniform_r1cs
+                    .a
+                    .add_vec_matrix_product(&eq_rx_block, &mut sm_a_r);
+                self.uniform_r1cs
+                    .c
+                    .add_vec_matrix_product(&eq_rx_block, &mut sm_c_r);
+
+                let mut rlc = unsafe_allocate_zero_vec(self.num_cols_total());
+                rlc.par_chunks_mut(self.num_steps)
+                    .zip(sm_a_r.par_iter().zip(sm_c_r.par_iter()))
+                    .for_each(|(var_chunk, (a, c))| {
+                        let sm_rlc = *a + mul_0_1_optimized(c, &r_rlc_sq);
+                        if !sm_rlc.is_zero() {
+                            for (step_index, item) in var_chunk.iter_mut().enumerate() {
+                                *item = mul_0_1_optimized(&eq_rx_step[step_index], &sm_rlc);
+                            }
+                        }
+                    });
+                rlc
+            })
+            .collect()
+    }
+
+    /// Zeroes the rows of the step kinds' blocks.
+    fn gate_step_kind_rows(&self, eq_rx_constr: &[F]) -> Vec<F> {
+        let mut eq_rx_constr = eq_rx_constr.to_vec();
+        for kind in self.uniform_r1cs.step_kinds.iter() {
+            eq_rx_constr[kind.start_row..kind.end_row]
+                .iter_mut()
+                .for_each(|eq| *eq = F::zero());
+        }
+        eq_rx_constr
+    }
+
+    /// Removes from `rlc`, as computed by `evaluate_r1cs_mle_rlc`, the rows of the offset
+    /// constraints at the last step. These are the rows zeroed by
+    /// `CombinedUniformBuilder::compute_relaxed_Az_Bz_Cz`.
+    pub fn remove_last_step_offset_rows(
+        &self,
+        r_constr: &[F],
+        r_step: &StepPoint<F>,
+        r_rlc: F,
+        rlc: &mut [F],
+    ) {
+        let eq_rx_constr = EqPolynomial::evals(r_constr);
+        let last_step = self.num_steps - 1;
+        let eq_rx_last_step = r_step.weight(last_step);
+
+        for (i, constraint) in self.offset_eq_r1cs.constraints.iter().enumerate() {
+            let eq_rx_row = eq_rx_constr[self.uniform_r1cs.num_rows + i] * eq_rx_last_step;
+            for (item, r) in [(&constraint.eq, F::one()), (&constraint.condition, r_rlc)] {
+                let weight = eq_rx_row * r;
+                // The offset terms have no column at the last step
+                for (col, _, coeff) in item.offset_vars.iter().filter(|(_, offset, _)| !offset) {
+                    rlc[*col * self.num_steps + last_step] -= weight * *coeff;
+                }
+                rlc[self.num_vars_total()] -= weight * item.constant;
+            }
+        }
+    }
+
     /// Evaluates the full expanded witness vector at 'r' using evaluations of segments.
-    #[tracing::instrument(skip_all, name = "UniformSpartanKey::evaluate_z_mle")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniformSpartanKey::evaluate_z_mle")
+    )]
     pub fn evaluate_z_mle(&self, segment_evals: &[F], r: &[F]) -> F {
+        self.evaluate_relaxed_z_mle(segment_evals, r, F::one())
+    }
+
+    /// Evaluates the expanded witness vector of a relaxed instance, whose constant variable is
+    /// `u`, at 'r' using evaluations of segments.
+    pub fn evaluate_relaxed_z_mle(&self, segment_evals: &[F], r: &[F], u: F) -> F {
         assert_eq!(self.uniform_r1cs.num_vars, segment_evals.len());
         assert_eq!(r.len(), self.full_z_len().log_2());
 
@@ -301,59 +646,75 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
         let const_poly = SparsePolynomial::new(self.num_vars_total().log_2(), vec![(F::one(), 0)]);
         let eval_const = const_poly.evaluate(r_rest);
 
-        (F::one() - r_const) * eval_variables + r_const * eval_const
+        (F::one() - r_const) * eval_variables + r_const * eval_const * u
     }
 
-    /// Evaluates A(r), B(r), C(r) efficiently using their small uniform representations.
-    #[tracing::instrument(skip_all, name = "UniformSpartanKey::evaluate_r1cs_matrix_mles")]
-    pub fn evaluate_r1cs_matrix_mles(&self, r: &[F]) -> (F, F, F) {
+    /// Evaluates A(r_x, r_col), B(r_x, r_col), C(r_x, r_col), where r_x = r_constr || r_step,
+    /// efficiently using their small uniform representations.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UniformSpartanKey::evaluate_r1cs_matrix_mles")
+    )]
+    pub fn evaluate_r1cs_matrix_mles(
+        &self,
+        r_constr: &[F],
+        r_step: &StepPoint<F>,
+        r_col: &[F],
+    ) -> (F, F, F) {
         let total_rows_bits = self.num_rows_total().log_2();
         let total_cols_bits = self.num_cols_total().log_2();
         let steps_bits: usize = self.num_steps.log_2();
         let constraint_rows_bits = (self.uniform_r1cs.num_rows + 1).next_power_of_two().log_2();
         let uniform_cols_bits = self.uniform_r1cs.num_vars.next_power_of_two().log_2();
-        assert_eq!(r.len(), total_rows_bits + total_cols_bits);
+        assert_eq!(r_constr.len() + r_step.num_vars(), total_rows_bits);
+        assert_eq!(r_col.len(), total_cols_bits);
         assert_eq!(total_rows_bits - steps_bits, constraint_rows_bits);
 
-        // Deconstruct 'r' into representitive bits
-        let (r_row, r_col) = r.split_at(total_rows_bits);
-        let (r_row_constr, r_row_step) = r_row.split_at(constraint_rows_bits);
+        // Deconstruct 'r_col' into representitive bits
         let (r_col_var, r_col_step) = r_col.split_at(uniform_cols_bits + 1);
-        assert_eq!(r_row_step.len(), r_col_step.len());
+        assert_eq!(r_step.num_vars(), r_col_step.len());
 
-        let eq_rx_ry_step = EqPolynomial::new(r_row_step.to_vec()).evaluate(r_col_step);
-        let eq_rx_constr = EqPolynomial::evals(r_row_constr);
+        let eq_rx_ry_step = r_step.evaluate_eq(r_col_step);
+        let eq_rx_constr = EqPolynomial::evals(r_constr);
         let eq_ry_var = EqPolynomial::evals(r_col_var);
 
         let constant_column = index_to_field_bitvector(self.num_cols_total() / 2, total_cols_bits);
         let col_eq_constant = EqPolynomial::new(r_col.to_vec()).evaluate(&constant_column);
 
-        let compute_uniform_matrix_mle = |constraints: &SparseConstraints<F>| -> F {
-            let mut full_mle_evaluation: F = constraints
-                .vars
-                .iter()
-                .map(|(row, col, coeff)| *coeff * eq_rx_constr[*row] * eq_ry_var[*col])
-                .sum::<F>()
-                * eq_rx_ry_step;
-
-            full_mle_evaluation += constraints
-                .consts
-                .iter()
-                .map(|(constraint_row, constant_coeff)| {
-                    *constant_coeff * eq_rx_constr[*constraint_row]
-                })
-                .sum::<F>()
-                * col_eq_constant;
+        // The rows of the step kinds' blocks are scaled by their selectors in A and C, which is
+        // accounted for by `evaluate_step_kind_matrix_mles`
+        let eq_rx_constr_gated = self.gate_step_kind_rows(&eq_rx_constr);
+
+        let compute_uniform_matrix_mle =
+            |constraints: &SparseConstraints<F>, eq_rx_constr: &[F]| -> F {
+                let mut full_mle_evaluation: F =
+                    constraints.evaluate_vars(eq_rx_constr, &eq_ry_var) * eq_rx_ry_step;
+
+                full_mle_evaluation += constraints
+                    .consts
+                    .iter()
+                    .map(|(constraint_row, constant_coeff)| {
+                        *constant_coeff * eq_rx_constr[*constraint_row]
+                    })
+                    .sum::<F>()
+                    * col_eq_constant;
+
+                full_mle_evaluation
+            };
 
-            full_mle_evaluation
-        };
+        let mut a_mle = compute_uniform_matrix_mle(&self.uniform_r1cs.a, &eq_rx_constr_gated);
+        let mut b_mle = compute_uniform_matrix_mle(&self.uniform_r1cs.b, &eq_rx_constr);
+        let mut c_mle = compute_uniform_matrix_mle(&self.uniform_r1cs.c, &eq_rx_constr_gated);
 
-        let mut a_mle = compute_uniform_matrix_mle(&self.uniform_r1cs.a);
-        let mut b_mle = compute_uniform_matrix_mle(&self.uniform_r1cs.b);
-        let c_mle = compute_uniform_matrix_mle(&self.uniform_r1cs.c);
+        // A gated constant is the selector itself
+        for kind in self.uniform_r1cs.step_kinds.iter() {
+            let eq_selector = eq_ry_var[kind.selector] * eq_rx_ry_step;
+            a_mle += kind.evaluate_consts(&self.uniform_r1cs.a, &eq_rx_constr) * eq_selector;
+            c_mle += kind.evaluate_consts(&self.uniform_r1cs.c, &eq_rx_constr) * eq_selector;
+        }
 
         // Non-uniform constraints
-        let eq_step_offset_1 = eq_plus_one(r_row_step, r_col_step, steps_bits);
+        let eq_step_offset_1 = r_step.evaluate_eq_plus_one(r_col_step);
         let compute_non_uniform = |non_uni: &SparseEqualityItem<F>| -> F {
             let mut non_uni_mle = non_uni
                 .offset_vars
@@ -379,7 +740,7 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
             let non_uni_constraint_index =
                 index_to_field_bitvector(self.uniform_r1cs.num_rows + i, constraint_rows_bits);
             let row_constr_eq_non_uni =
-                EqPolynomial::new(r_row_constr.to_vec()).evaluate(&non_uni_constraint_index);
+                EqPolynomial::new(r_constr.to_vec()).evaluate(&non_uni_constraint_index);
 
             assert_eq!(
                 row_constr_eq_non_uni,
@@ -393,16 +754,95 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
         (a_mle, b_mle, c_mle)
     }
 
-    /// Returns the digest of the r1cs shape
-    fn digest(uniform_r1cs: &UniformR1CS<F>, offset_eq: &NonUniformR1CS<F>, num_steps: usize) -> F {
-        let mut hash_bytes = Vec::new();
+    /// Evaluates the rows of the offset constraints at the last step in A(r) and B(r), which are
+    /// removed from the matrices of relaxed instances (see `remove_last_step_offset_rows`).
+    pub fn evaluate_last_step_offset_rows(
+        &self,
+        r_constr: &[F],
+        r_step: &StepPoint<F>,
+        r_col: &[F],
+    ) -> (F, F) {
+        let total_cols_bits = self.num_cols_total().log_2();
+        let steps_bits = self.num_steps.log_2();
+        let uniform_cols_bits = self.uniform_r1cs.num_vars.next_power_of_two().log_2();
+        assert_eq!(r_col.len(), total_cols_bits);
+
+        let (r_col_var, r_col_step) = r_col.split_at(uniform_cols_bits + 1);
+
+        let last_step = index_to_field_bitvector(self.num_steps - 1, steps_bits);
+        let eq_rx_last_step = r_step.weight(self.num_steps - 1);
+        let eq_ry_last_step = EqPolynomial::new(r_col_step.to_vec()).evaluate(&last_step);
+        let eq_rx_constr = EqPolynomial::evals(r_constr);
+        let eq_ry_var = EqPolynomial::evals(r_col_var);
+
+        let constant_column = index_to_field_bitvector(self.num_cols_total() / 2, total_cols_bits);
+        let col_eq_constant = EqPolynomial::new(r_col.to_vec()).evaluate(&constant_column);
+
+        let evaluate_last_step = |item: &SparseEqualityItem<F>| -> F {
+            item.offset_vars
+                .iter()
+                .filter(|(_, offset, _)| !offset)
+                .map(|(col, _, coeff)| *coeff * eq_ry_var[*col] * eq_ry_last_step)
+                .sum::<F>()
+                + item.constant * col_eq_constant
+        };
+
+        self.offset_eq_r1cs.constraints.iter().enumerate().fold(
+            (F::zero(), F::zero()),
+            |(a_mle, b_mle), (i, constraint)| {
+                let eq_rx_row = eq_rx_constr[self.uniform_r1cs.num_rows + i] * eq_rx_last_step;
+                (
+                    a_mle + evaluate_last_step(&constraint.eq) * eq_rx_row,
+                    b_mle + evaluate_last_step(&constraint.condition) * eq_rx_row,
+                )
+            },
+        )
+    }
+
+    /// For each step kind, evaluates the non-constant coefficients of the rows of its block in
+    /// A(r_x, r_col) and C(r_x, r_col), which are scaled by its selector.
+    pub fn evaluate_step_kind_matrix_mles(
+        &self,
+        r_constr: &[F],
+        r_step: &StepPoint<F>,
+        r_col: &[F],
+    ) -> Vec<(F, F)> {
+        let uniform_cols_bits = self.uniform_r1cs.num_vars.next_power_of_two().log_2();
+        assert_eq!(r_col.len(), self.num_cols_total().log_2());
+
+        let (r_col_var, r_col_step) = r_col.split_at(uniform_cols_bits + 1);
+
+        let eq_rx_ry_step = r_step.evaluate_eq(r_col_step);
+        let eq_rx_constr = EqPolynomial::evals(r_constr);
+        let eq_ry_var = EqPolynomial::evals(r_col_var);
+
+        self.uniform_r1cs
+            .step_kinds
+            .iter()
+            .map(|kind| {
+                let eq_rx_block = kind.restrict_rows(&eq_rx_constr);
+                (
+                    self.uniform_r1cs.a.evaluate_vars(&eq_rx_block, &eq_ry_var) * eq_rx_ry_step,
+                    self.uniform_r1cs.c.evaluate_vars(&eq_rx_block, &eq_ry_var) * eq_rx_ry_step,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the digest of the r1cs shape: a hash of the canonical serialization of the
+    /// uniform and offset matrices, the number of chunks `C`, and the padded dimensions.
+    fn compute_digest(
+        uniform_r1cs: &UniformR1CS<F>,
+        offset_eq: &NonUniformR1CS<F>,
+        num_cons_total: usize,
+        num_steps: usize,
+    ) -> F {
+        let mut hash_bytes = b"Jolt UniformSpartanKey".to_vec();
         uniform_r1cs.serialize_compressed(&mut hash_bytes).unwrap();
-        let mut offset_eq_bytes = Vec::new();
-        offset_eq
-            .serialize_compressed(&mut offset_eq_bytes)
-            .unwrap();
-        hash_bytes.extend(offset_eq_bytes);
-        hash_bytes.extend(num_steps.to_be_bytes().to_vec());
+        offset_eq.serialize_compressed(&mut hash_bytes).unwrap();
+        for size in [C, num_cons_total, num_steps] {
+            hash_bytes.extend((size as u64).to_be_bytes());
+        }
         let mut hasher = Sha3_256::new();
         hasher.update(hash_bytes);
 
@@ -428,6 +868,226 @@ impl<const C: usize, F: JoltField, I: ConstraintInput> UniformSpartanKey<C, I, F
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jolt::vm::rv32i_vm::{C, RV32I};
+    use crate::poly::commitment::mock::MockCommitScheme;
+    use crate::poly::dense_mlpoly::DensePolynomial;
+    use crate::r1cs::builder::{OffsetEqConstraint, R1CSBuilder};
+    use crate::r1cs::constraints::{JoltRV32IMConstraints, R1CSConstraints};
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+    use ark_std::{test_rng, UniformRand, Zero};
+    use common::rv_trace::CircuitFlags;
+
+    type JoltR1CSInputs = crate::r1cs::inputs::JoltR1CSInputs<RV32I>;
+
+    fn key(
+        padded_trace_length: usize,
+        memory_start: u64,
+    ) -> UniformSpartanKey<C, JoltR1CSInputs, Fr> {
+        let builder = <JoltRV32IMConstraints as R1CSConstraints<C, Fr>>::construct_constraints(
+            padded_trace_length,
+            memory_start,
+        );
+        UniformSpartanKey::from_builder(&builder)
+    }
+
+    #[test]
+    fn digest_binds_shape() {
+        let digest = key(1 << 8, 0x8000_0000).digest();
+        assert_eq!(key(1 << 8, 0x8000_0000).digest(), digest);
+        // The number of steps and the constants of the constraints are both bound
+        assert_ne!(key(1 << 9, 0x8000_0000).digest(), digest);
+        assert_ne!(key(1 << 8, 0x8000_1000).digest(), digest);
+    }
+
+    #[test]
+    fn step_kind_rows_scaled_by_selector() {
+        let num_steps = 4;
+        let selector = JoltR1CSInputs::OpFlags(CircuitFlags::Assert);
+        let mut uniform_builder = R1CSBuilder::<C, Fr, JoltR1CSInputs>::new();
+        // The selector is constrained to be binary at row 0
+        uniform_builder.constrain_step_kind(selector, |cs| {
+            cs.constrain_eq(JoltR1CSInputs::RD_Write, JoltR1CSInputs::RS1_Read);
+            cs.constrain_eq(JoltR1CSInputs::RAM_Write, JoltR1CSInputs::RAM_Read + 1);
+        });
+        uniform_builder.constrain_binary(JoltR1CSInputs::OpFlags(CircuitFlags::Jump));
+        let builder = CombinedUniformBuilder::construct(
+            uniform_builder,
+            num_steps,
+            vec![OffsetEqConstraint::empty()],
+        );
+        let key = UniformSpartanKey::<C, JoltR1CSInputs, Fr>::from_builder(&builder);
+        assert_eq!(
+            key.uniform_r1cs.step_kinds,
+            vec![StepKind {
+                selector: selector.to_index::<C>(),
+                start_row: 1,
+                end_row: 3
+            }]
+        );
+
+        let mut polys: Vec<DensePolynomial<Fr>> = JoltR1CSInputs::flatten::<C>()
+            .iter()
+            .map(|_| DensePolynomial::new(vec![Fr::zero(); num_steps]))
+            .collect();
+        let selector_values: Vec<Fr> = [1u64, 0, 0, 1].into_iter().map(Fr::from).collect();
+        polys[selector.to_index::<C>()] = DensePolynomial::new(selector_values);
+        // The block's constraints only hold at the steps of its kind
+        polys[JoltR1CSInputs::RD_Write.to_index::<C>()] =
+            DensePolynomial::new([5u64, 6, 7, 8].into_iter().map(Fr::from).collect());
+        polys[JoltR1CSInputs::RS1_Read.to_index::<C>()] =
+            DensePolynomial::new([5u64, 0, 0, 8].into_iter().map(Fr::from).collect());
+        polys[JoltR1CSInputs::RAM_Write.to_index::<C>()] =
+            DensePolynomial::new([1u64, 0, 0, 1].into_iter().map(Fr::from).collect());
+        let poly_refs: Vec<&DensePolynomial<Fr>> = polys.iter().collect();
+        builder.check_satisfied(&poly_refs).unwrap();
+
+        // The block's rows are dropped from Az and Cz at steps 1 and 2
+        let (az, bz, cz) = builder
+            .compute_spartan_Az_Bz_Cz::<MockCommitScheme<Fr, KeccakTranscript>, KeccakTranscript>(
+                &poly_refs,
+            );
+        let (az, bz, cz) = (az.to_dense(), bz.to_dense(), cz.to_dense());
+        for row in 1..3 {
+            for step in 1..3 {
+                assert!(az[row * num_steps + step].is_zero());
+                assert!(cz[row * num_steps + step].is_zero());
+            }
+        }
+
+        let mut rng = test_rng();
+        let r_x: Vec<Fr> = (0..key.num_rows_total().log_2())
+            .map(|_| Fr::rand(&mut rng))
+            .collect();
+        let r_y: Vec<Fr> = (0..key.num_cols_total().log_2())
+            .map(|_| Fr::rand(&mut rng))
+            .collect();
+        let r_rlc = Fr::rand(&mut rng);
+        let (r_constr, r_step) = r_x.split_at(r_x.len() - num_steps.log_2());
+        let r_step = StepPoint::new(r_step);
+
+        // The inner sum-check's polynomials sum to the outer sum-check's claims
+        let poly_abc = DensePolynomial::new(key.evaluate_r1cs_mle_rlc(r_constr, &r_step, r_rlc));
+        let polys_abc_k = key.evaluate_step_kind_mle_rlc(r_constr, &r_step, r_rlc);
+        let mut z = vec![Fr::zero(); key.num_cols_total()];
+        for (var, poly) in polys.iter().enumerate() {
+            z[var * num_steps..(var + 1) * num_steps].copy_from_slice(poly.evals_ref());
+        }
+        z[key.num_vars_total()] = Fr::from(1u64);
+        let selector_evals = polys[selector.to_index::<C>()].evals_ref();
+        let inner_sum: Fr = (0..key.num_cols_total())
+            .map(|y| (poly_abc[y] + polys_abc_k[0][y] * selector_evals[y % num_steps]) * z[y])
+            .sum();
+        let outer_claims =
+            az.evaluate(&r_x) + r_rlc * bz.evaluate(&r_x) + r_rlc * r_rlc * cz.evaluate(&r_x);
+        assert_eq!(inner_sum, outer_claims);
+
+        // The verifier evaluates the same polynomials at r_y
+        let (eval_a, eval_b, eval_c) = key.evaluate_r1cs_matrix_mles(r_constr, &r_step, &r_y);
+        assert_eq!(
+            eval_a + r_rlc * eval_b + r_rlc * r_rlc * eval_c,
+            poly_abc.evaluate(&r_y)
+        );
+        let (eval_a_k, eval_c_k) = key.evaluate_step_kind_matrix_mles(r_constr, &r_step, &r_y)[0];
+        assert_eq!(
+            eval_a_k + r_rlc * r_rlc * eval_c_k,
+            DensePolynomial::new(polys_abc_k[0].clone()).evaluate(&r_y)
+        );
+    }
+
+    #[test]
+    fn step_point_skip() {
+        let mut rng = test_rng();
+        let r_step: Vec<Fr> = (0..2).map(|_| Fr::rand(&mut rng)).collect();
+        let r_col_step: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let point = StepPoint::with_skip(&r_step, 2, Fr::rand(&mut rng));
+        let weights = point.weights();
+        assert_eq!(weights.len(), 1 << 4);
+        for (step, weight) in weights.iter().enumerate() {
+            assert_eq!(point.weight(step), *weight);
+        }
+
+        // The closed forms agree with the sums over the steps
+        let eq_col = EqPolynomial::evals(&r_col_step);
+        let eq: Fr = weights
+            .iter()
+            .zip(eq_col.iter())
+            .map(|(w, eq)| *w * eq)
+            .sum();
+        assert_eq!(point.evaluate_eq(&r_col_step), eq);
+        let eq_plus_one: Fr = weights
+            .iter()
+            .zip(eq_col.iter().skip(1))
+            .map(|(w, eq)| *w * eq)
+            .sum();
+        assert_eq!(point.evaluate_eq_plus_one(&r_col_step), eq_plus_one);
+
+        // Without the skip, the weights are eq
+        let point = StepPoint::new(&r_col_step);
+        assert_eq!(point.weights(), eq_col);
+    }
+
+    #[test]
+    fn sparse_constraints_csr() {
+        // Row 1 is empty, and rows 4 and 5 only have constants
+        let coeffs: Vec<Coeff<Fr>> = vec![
+            (0, 0, Fr::from(2u64)),
+            (0, 3, Fr::from(5u64)),
+            (2, 1, -Fr::from(1u64)),
+            (3, 0, Fr::from(7u64)),
+            (3, 2, Fr::from(1u64)),
+            (3, 3, Fr::from(4u64)),
+        ];
+        let mut sparse = SparseConstraints::empty_with_capacity(coeffs.len(), 2);
+        for (row, col, val) in coeffs.iter() {
+            sparse.push_var(*row, *col, *val);
+        }
+        sparse.consts.push((2, Fr::from(3u64)));
+        sparse.consts.push((5, Fr::from(6u64)));
+        assert_eq!(sparse.num_vars(), coeffs.len());
+        assert_eq!(sparse.vars().collect::<Vec<_>>(), coeffs);
+        assert_eq!(sparse.row(1).count(), 0);
+        assert_eq!(sparse.row(4).count(), 0);
+
+        let num_rows = 6;
+        let z: Vec<Fr> = (0..4u64).map(|i| Fr::from(i * i + 3)).collect();
+        let mut expected_mz = vec![Fr::zero(); num_rows];
+        for (row, col, val) in coeffs.iter() {
+            expected_mz[*row] += *val * z[*col];
+        }
+        expected_mz[2] += Fr::from(3u64);
+        expected_mz[5] += Fr::from(6u64);
+        assert_eq!(sparse.multiply_vec(num_rows, &z), expected_mz);
+
+        let eq_rows: Vec<Fr> = (0..num_rows as u64).map(|i| Fr::from(i + 11)).collect();
+        let eq_cols: Vec<Fr> = (0..4u64).map(|i| Fr::from(2 * i + 1)).collect();
+        let expected: Fr = coeffs
+            .iter()
+            .map(|(row, col, val)| *val * eq_rows[*row] * eq_cols[*col])
+            .sum();
+        assert_eq!(sparse.evaluate_vars(&eq_rows, &eq_cols), expected);
+
+        let mut evals = vec![Fr::zero(); 4];
+        sparse.add_vec_matrix_product(&eq_rows, &mut evals);
+        let mut expected_evals = vec![Fr::zero(); 4];
+        for (row, col, val) in coeffs.iter() {
+            expected_evals[*col] += *val * eq_rows[*row];
+        }
+        assert_eq!(evals, expected_evals);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sparse_constraints_row_order() {
+        let mut sparse = SparseConstraints::<Fr>::empty_with_capacity(2, 0);
+        sparse.push_var(1, 0, Fr::from(1u64));
+        sparse.push_var(0, 0, Fr::from(1u64));
+    }
+}
+
 // #[cfg(test)]
 // mod test {
 //     use super::*;
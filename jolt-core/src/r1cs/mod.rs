@@ -2,6 +2,7 @@ pub mod inputs;
 
 pub mod builder;
 pub mod constraints;
+pub mod folding;
 pub mod key;
 pub mod ops;
 pub mod spartan;
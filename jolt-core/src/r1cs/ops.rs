@@ -1,16 +1,14 @@
 //! Defines the Linear Combination (LC) object and associated operations.
 //! A LinearCombination is a vector of Terms, where each Term is a pair of a Variable and a coefficient.
 
-#[cfg(test)]
 use super::inputs::ConstraintInput;
+use crate::utils::par::prelude::*;
 use crate::{
     field::{JoltField, OptimizedMul},
     poly::dense_mlpoly::DensePolynomial,
     utils::thread::unsafe_allocate_zero_vec,
 };
-use rayon::prelude::*;
 use std::fmt::Debug;
-#[cfg(test)]
 use std::fmt::Write as _;
 use std::hash::Hash;
 
@@ -24,7 +22,6 @@ pub enum Variable {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Term(pub Variable, pub i64);
 impl Term {
-    #[cfg(test)]
     fn pretty_fmt<const C: usize, I: ConstraintInput>(&self, f: &mut String) -> std::fmt::Result {
         match self.0 {
             Variable::Input(var_index) | Variable::Auxiliary(var_index) => match self.1.abs() {
@@ -108,6 +105,20 @@ impl LC {
         result
     }
 
+    /// Evaluates the linear combination given the values of every variable, in the order of
+    /// `ConstraintInput::flatten`.
+    pub fn evaluate_assignment<F: JoltField>(&self, assignment: &[F]) -> F {
+        self.terms()
+            .iter()
+            .map(|term| match term.0 {
+                Variable::Input(var_index) | Variable::Auxiliary(var_index) => {
+                    F::from_i64(term.1) * assignment[var_index]
+                }
+                Variable::Constant => F::from_i64(term.1),
+            })
+            .sum()
+    }
+
     pub fn evaluate_batch<F: JoltField>(
         &self,
         flattened_polynomials: &[&DensePolynomial<F>],
@@ -123,21 +134,40 @@ impl LC {
         flattened_polynomials: &[&DensePolynomial<F>],
         output: &mut [F],
     ) {
-        output.par_iter_mut().enumerate().for_each(|(i, eval)| {
-            *eval = self
-                .terms()
-                .iter()
-                .map(|term| match term.0 {
-                    Variable::Input(var_index) | Variable::Auxiliary(var_index) => {
-                        F::from_i64(term.1).mul_01_optimized(flattened_polynomials[var_index][i])
-                    }
-                    Variable::Constant => F::from_i64(term.1),
-                })
-                .sum()
-        });
+        output
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, eval)| *eval = self.evaluate_row(flattened_polynomials, i));
+    }
+
+    /// Evaluates the linear combination at a single step.
+    pub fn evaluate_row<F: JoltField>(
+        &self,
+        flattened_polynomials: &[&DensePolynomial<F>],
+        row: usize,
+    ) -> F {
+        self.evaluate_row_relaxed(flattened_polynomials, row, F::one())
+    }
+
+    /// Evaluates the linear combination at a single step, with the constant variable set to `u`
+    /// as in a relaxed R1CS instance.
+    pub fn evaluate_row_relaxed<F: JoltField>(
+        &self,
+        flattened_polynomials: &[&DensePolynomial<F>],
+        row: usize,
+        u: F,
+    ) -> F {
+        self.terms()
+            .iter()
+            .map(|term| match term.0 {
+                Variable::Input(var_index) | Variable::Auxiliary(var_index) => {
+                    F::from_i64(term.1).mul_01_optimized(flattened_polynomials[var_index][row])
+                }
+                Variable::Constant => F::from_i64(term.1).mul_1_optimized(u),
+            })
+            .sum()
     }
 
-    #[cfg(test)]
     pub fn pretty_fmt<const C: usize, I: ConstraintInput>(
         &self,
         f: &mut String,
@@ -328,17 +358,18 @@ impl std::ops::Mul<Variable> for i64 {
     }
 }
 
-/// Conversions and arithmetic for concrete ConstraintInput
+/// Conversions and arithmetic for concrete ConstraintInput. Inputs that are generic can be
+/// given with their type parameters, e.g. `impl<I: Bound> Inputs<I>`.
 #[macro_export]
 macro_rules! impl_r1cs_input_lc_conversions {
-    ($ConcreteInput:ty, $C:expr) => {
-        impl Into<$crate::r1cs::ops::Variable> for $ConcreteInput {
+    (impl<$($T:ident: $bound:path),*> $ConcreteInput:ty, $C:expr) => {
+        impl<$($T: $bound),*> Into<$crate::r1cs::ops::Variable> for $ConcreteInput {
             fn into(self) -> $crate::r1cs::ops::Variable {
                 $crate::r1cs::ops::Variable::Input(self.to_index::<$C>())
             }
         }
 
-        impl Into<$crate::r1cs::ops::Term> for $ConcreteInput {
+        impl<$($T: $bound),*> Into<$crate::r1cs::ops::Term> for $ConcreteInput {
             fn into(self) -> $crate::r1cs::ops::Term {
                 $crate::r1cs::ops::Term(
                     $crate::r1cs::ops::Variable::Input(self.to_index::<$C>()),
@@ -347,7 +378,7 @@ macro_rules! impl_r1cs_input_lc_conversions {
             }
         }
 
-        impl Into<$crate::r1cs::ops::LC> for $ConcreteInput {
+        impl<$($T: $bound),*> Into<$crate::r1cs::ops::LC> for $ConcreteInput {
             fn into(self) -> $crate::r1cs::ops::LC {
                 $crate::r1cs::ops::Term(
                     $crate::r1cs::ops::Variable::Input(self.to_index::<$C>()),
@@ -357,7 +388,7 @@ macro_rules! impl_r1cs_input_lc_conversions {
             }
         }
 
-        impl Into<$crate::r1cs::ops::LC> for Vec<$ConcreteInput> {
+        impl<$($T: $bound),*> Into<$crate::r1cs::ops::LC> for Vec<$ConcreteInput> {
             fn into(self) -> $crate::r1cs::ops::LC {
                 let terms: Vec<$crate::r1cs::ops::Term> =
                     self.into_iter().map(Into::into).collect();
@@ -365,27 +396,31 @@ macro_rules! impl_r1cs_input_lc_conversions {
             }
         }
 
-        impl<T: Into<$crate::r1cs::ops::LC>> std::ops::Add<T> for $ConcreteInput {
+        impl<Rhs: Into<$crate::r1cs::ops::LC> $(, $T: $bound)*> std::ops::Add<Rhs>
+            for $ConcreteInput
+        {
             type Output = $crate::r1cs::ops::LC;
 
-            fn add(self, rhs: T) -> Self::Output {
+            fn add(self, rhs: Rhs) -> Self::Output {
                 let lhs_lc: $crate::r1cs::ops::LC = self.into();
                 let rhs_lc: $crate::r1cs::ops::LC = rhs.into();
                 lhs_lc + rhs_lc
             }
         }
 
-        impl<T: Into<$crate::r1cs::ops::LC>> std::ops::Sub<T> for $ConcreteInput {
+        impl<Rhs: Into<$crate::r1cs::ops::LC> $(, $T: $bound)*> std::ops::Sub<Rhs>
+            for $ConcreteInput
+        {
             type Output = $crate::r1cs::ops::LC;
 
-            fn sub(self, rhs: T) -> Self::Output {
+            fn sub(self, rhs: Rhs) -> Self::Output {
                 let lhs_lc: $crate::r1cs::ops::LC = self.into();
                 let rhs_lc: $crate::r1cs::ops::LC = rhs.into();
                 lhs_lc - rhs_lc
             }
         }
 
-        impl std::ops::Mul<i64> for $ConcreteInput {
+        impl<$($T: $bound),*> std::ops::Mul<i64> for $ConcreteInput {
             type Output = $crate::r1cs::ops::Term;
 
             fn mul(self, rhs: i64) -> Self::Output {
@@ -396,7 +431,7 @@ macro_rules! impl_r1cs_input_lc_conversions {
             }
         }
 
-        impl std::ops::Mul<$ConcreteInput> for i64 {
+        impl<$($T: $bound),*> std::ops::Mul<$ConcreteInput> for i64 {
             type Output = $crate::r1cs::ops::Term;
 
             fn mul(self, rhs: $ConcreteInput) -> Self::Output {
@@ -406,7 +441,7 @@ macro_rules! impl_r1cs_input_lc_conversions {
                 )
             }
         }
-        impl std::ops::Add<$ConcreteInput> for i64 {
+        impl<$($T: $bound),*> std::ops::Add<$ConcreteInput> for i64 {
             type Output = $crate::r1cs::ops::LC;
 
             fn add(self, rhs: $ConcreteInput) -> Self::Output {
@@ -419,4 +454,7 @@ macro_rules! impl_r1cs_input_lc_conversions {
             }
         }
     };
+    ($ConcreteInput:ty, $C:expr) => {
+        $crate::impl_r1cs_input_lc_conversions!(impl<> $ConcreteInput, $C);
+    };
 }
@@ -6,23 +6,29 @@ use crate::field::JoltField;
 use crate::jolt::vm::JoltCommitments;
 use crate::jolt::vm::JoltPolynomials;
 use crate::poly::commitment::commitment_scheme::CommitmentScheme;
+use crate::poly::dense_mlpoly::BindingOrder;
+use crate::poly::masking_poly::MaskingPolynomial;
 use crate::poly::opening_proof::ProverOpeningAccumulator;
 use crate::poly::opening_proof::VerifierOpeningAccumulator;
 use crate::poly::split_eq_poly::SplitEqPolynomial;
-use crate::r1cs::key::UniformSpartanKey;
+use crate::r1cs::key::{StepPoint, UniformSpartanKey};
 use crate::utils::math::Math;
-use crate::utils::thread::drop_in_background_thread;
+use crate::utils::prover_config::ProverConfig;
+use crate::utils::thread::{drop_in_background_thread, unsafe_allocate_zero_vec};
 
-use crate::utils::transcript::Transcript;
+use crate::utils::transcript::{AppendToTranscript, Transcript};
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
 
-use rayon::prelude::*;
+use crate::utils::par::prelude::*;
 use thiserror::Error;
 
 use crate::{
     poly::{dense_mlpoly::DensePolynomial, eq_poly::EqPolynomial},
-    subprotocols::sumcheck::SumcheckInstanceProof,
+    subprotocols::{
+        sumcheck::SumcheckInstanceProof,
+        univariate_skip::{lagrange_kernel, UnivariateSkipProof},
+    },
 };
 
 use super::builder::CombinedUniformBuilder;
@@ -61,33 +67,162 @@ pub enum SpartanError {
     /// returned when an invalid PCS proof is provided
     #[error("InvalidPCSProof")]
     InvalidPCSProof,
+
+    /// returned by the prover, in debug builds, when the witness does not satisfy a constraint
+    #[error("UnsatisfiedConstraint: {0}")]
+    UnsatisfiedConstraint(String),
+}
+
+/// Outer sum-checks with at least this many rounds stream their first rounds from the witness
+/// rather than materializing Az, Bz and Cz in full, which would take several times the memory of
+/// the trace.
+const STREAMING_MIN_ROUNDS: usize = 24;
+
+/// The number of rounds streamed, dividing the memory taken by Az, Bz and Cz by
+/// `2^NUM_STREAMING_ROUNDS` at the cost of as many extra passes over the witness.
+const NUM_STREAMING_ROUNDS: usize = 2;
+
+/// The number of step variables the univariate skip binds in the first round of the outer
+/// sum-check, if the trace has as many.
+const NUM_SKIPPED_VARS: usize = 2;
+
+/// The masking polynomial of a masked sum-check: its commitment and sum, and the
+/// evaluations `g_i(r_i)` of its univariates at the sum-check point.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct SumcheckMaskProof<
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+> {
+    pub(crate) commitment: PCS::Commitment,
+    pub(crate) sum: F,
+    pub(crate) evals: Vec<F>,
+}
+
+impl<F, PCS, ProofTranscript> SumcheckMaskProof<F, PCS, ProofTranscript>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+{
+    /// Commits to `mask` and its sum, and returns the challenge `rho` by which the mask is
+    /// scaled in the sum-check.
+    fn commit(
+        mask: &MaskingPolynomial<F>,
+        generators: &PCS::Setup,
+        transcript: &mut ProofTranscript,
+    ) -> (Self, F) {
+        let proof = Self {
+            commitment: PCS::commit(&mask.coeffs_poly(), generators),
+            sum: mask.sum(),
+            evals: vec![],
+        };
+        let rho = proof.challenge(transcript);
+        (proof, rho)
+    }
+
+    fn challenge(&self, transcript: &mut ProofTranscript) -> F {
+        self.commitment.append_to_transcript(transcript);
+        transcript.append_scalar(&self.sum);
+        transcript.challenge_scalar()
+    }
+
+    /// Appends the openings of `g_i(r_i)` to `opening_accumulator`, for the sum-check point `r`
+    /// in round order.
+    fn prove_evals(
+        &mut self,
+        mask: &MaskingPolynomial<F>,
+        r: &[F],
+        opening_accumulator: &mut ProverOpeningAccumulator<F, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) {
+        self.evals = mask.evaluate_univariates(r);
+        transcript.append_scalars(&self.evals);
+        let coeffs = mask.coeffs_poly();
+        for (i, (r_i, eval)) in r.iter().zip(self.evals.iter()).enumerate() {
+            let (point, opening) =
+                MaskingPolynomial::opening(mask.num_vars(), mask.degree(), i, *r_i, *eval);
+            opening_accumulator.append(
+                &[&coeffs],
+                DensePolynomial::new(EqPolynomial::evals(&point)),
+                point,
+                &[&opening],
+                transcript,
+            );
+        }
+    }
+
+    /// Appends the claimed `g_i(r_i)` to `opening_accumulator`, and returns `g(r)`.
+    fn verify_evals(
+        &self,
+        degree: usize,
+        r: &[F],
+        opening_accumulator: &mut VerifierOpeningAccumulator<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<F, SpartanError> {
+        if self.evals.len() != r.len() {
+            return Err(SpartanError::InvalidWitnessLength);
+        }
+        transcript.append_scalars(&self.evals);
+        for (i, (r_i, eval)) in r.iter().zip(self.evals.iter()).enumerate() {
+            let (point, opening) = MaskingPolynomial::opening(r.len(), degree, i, *r_i, *eval);
+            opening_accumulator.append(&[&self.commitment], point, &[&opening], transcript);
+        }
+        Ok(self.evals.iter().sum())
+    }
+}
+
+/// The masks of both sum-checks of a proof with masked round polynomials.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct SpartanMasks<
+    F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
+    ProofTranscript: Transcript,
+> {
+    pub(crate) outer: SumcheckMaskProof<F, PCS, ProofTranscript>,
+    pub(crate) inner: SumcheckMaskProof<F, PCS, ProofTranscript>,
 }
 
 /// A succinct proof of knowledge of a witness to a relaxed R1CS instance
 /// The proof is produced using Spartan's combination of the sum-check and
 /// the commitment to a vector viewed as a polynomial commitment
+///
+/// If the round polynomials are masked, both sum-checks are masked by committed random
+/// polynomials, so that their round polynomials reveal nothing about the witness. This does not
+/// make the proof zero-knowledge: the final claims, and the witness evaluations opened through
+/// the accumulator, are still sent in the clear, and the masks are committed to with the
+/// (non-hiding) PCS.
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct UniformSpartanProof<
     const C: usize,
     I: ConstraintInput,
     F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 > {
     _inputs: PhantomData<I>,
     pub(crate) outer_sumcheck_proof: SumcheckInstanceProof<F, ProofTranscript>,
+    /// The outer sum-check, if its first rounds are skipped, in which case
+    /// `outer_sumcheck_proof` is empty
+    pub(crate) outer_univariate_skip: Option<UnivariateSkipProof<F, ProofTranscript>>,
     pub(crate) outer_sumcheck_claims: (F, F, F),
     pub(crate) inner_sumcheck_proof: SumcheckInstanceProof<F, ProofTranscript>,
     pub(crate) claimed_witness_evals: Vec<F>,
-    _marker: PhantomData<ProofTranscript>,
+    /// Masks of the sum-checks, if the round polynomials are masked
+    pub(crate) masks: Option<SpartanMasks<F, PCS, ProofTranscript>>,
 }
 
-impl<const C: usize, I, F, ProofTranscript> UniformSpartanProof<C, I, F, ProofTranscript>
+impl<const C: usize, I, F, PCS, ProofTranscript> UniformSpartanProof<C, I, F, PCS, ProofTranscript>
 where
     I: ConstraintInput,
     F: JoltField,
+    PCS: CommitmentScheme<ProofTranscript, Field = F>,
     ProofTranscript: Transcript,
 {
-    #[tracing::instrument(skip_all, name = "Spartan::setup")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Spartan::setup")
+    )]
     pub fn setup(
         constraint_builder: &CombinedUniformBuilder<C, F, I>,
         padded_num_steps: usize,
@@ -99,48 +234,149 @@ where
         UniformSpartanKey::from_builder(constraint_builder)
     }
 
-    #[tracing::instrument(skip_all, name = "Spartan::prove")]
-    pub fn prove<PCS>(
+    /// Proves that the witness polynomials, already committed as part of `polynomials`,
+    /// satisfy the constraints. Their evaluations at the inner sum-check point are not opened
+    /// here but appended to `opening_accumulator`, which proves them in one batch with the
+    /// rest of Jolt's openings.
+    ///
+    /// If `mask_round_polys` is set, the sum-checks are masked, with the masks committed to using
+    /// `generators`. If `univariate_skip` is set, the first rounds of the outer sum-check are
+    /// skipped (see `subprotocols::univariate_skip`), which materializes Az, Bz and Cz in full;
+    /// it cannot be combined with masking.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Spartan::prove")
+    )]
+    pub fn prove(
         constraint_builder: &CombinedUniformBuilder<C, F, I>,
         key: &UniformSpartanKey<C, I, F>,
+        generators: &PCS::Setup,
         polynomials: &JoltPolynomials<F>,
+        mask_round_polys: bool,
+        univariate_skip: bool,
         opening_accumulator: &mut ProverOpeningAccumulator<F, ProofTranscript>,
         transcript: &mut ProofTranscript,
-    ) -> Result<Self, SpartanError>
-    where
-        PCS: CommitmentScheme<ProofTranscript, Field = F>,
-    {
+    ) -> Result<Self, SpartanError> {
+        assert!(
+            !(mask_round_polys && univariate_skip),
+            "the univariate skip does not support masked round polynomials"
+        );
         let flattened_polys: Vec<&DensePolynomial<F>> = I::flatten::<C>()
             .iter()
             .map(|var| var.get_ref(polynomials))
             .collect();
 
+        // An unsatisfying witness otherwise only fails at verification, far from its cause
+        if cfg!(debug_assertions) {
+            constraint_builder
+                .check_satisfied(&flattened_polys)
+                .map_err(|violation| SpartanError::UnsatisfiedConstraint(violation.to_string()))?;
+        }
+
         let num_rounds_x = key.num_rows_total().log_2();
         let num_rounds_y = key.num_cols_total().log_2();
 
+        // The masks are random (or derived from the configured seed), so must not be derived
+        // from the transcript
+        let mut rng = mask_round_polys.then(|| ProverConfig::current().rng(b"spartan masks"));
+
         // outer sum-check
-        let tau = (0..num_rounds_x)
-            .map(|_i| transcript.challenge_scalar())
-            .collect::<Vec<F>>();
-        let mut eq_tau = SplitEqPolynomial::new(&tau);
-
-        let (mut az, mut bz, mut cz) =
-            constraint_builder.compute_spartan_Az_Bz_Cz::<PCS, ProofTranscript>(&flattened_polys);
-
-        let (outer_sumcheck_proof, outer_sumcheck_r, outer_sumcheck_claims) =
-            SumcheckInstanceProof::prove_spartan_cubic(
-                &F::zero(), // claim is zero
-                num_rounds_x,
-                &mut eq_tau,
-                &mut az,
-                &mut bz,
-                &mut cz,
-                transcript,
-            );
-        let outer_sumcheck_r: Vec<F> = outer_sumcheck_r.into_iter().rev().collect();
-        drop_in_background_thread((az, bz, cz, eq_tau));
+        let mut outer_mask = None;
+        let (outer_sumcheck_proof, outer_univariate_skip, rx_con, rx_ts, outer_sumcheck_claims) =
+            if univariate_skip {
+                let (proof, rx_con, rx_ts, claims) = Self::prove_outer_univariate_skip(
+                    constraint_builder,
+                    key,
+                    &flattened_polys,
+                    transcript,
+                );
+                (
+                    SumcheckInstanceProof::new(vec![]),
+                    Some(proof),
+                    rx_con,
+                    rx_ts,
+                    claims,
+                )
+            } else {
+                let tau = (0..num_rounds_x)
+                    .map(|_i| transcript.challenge_scalar())
+                    .collect::<Vec<F>>();
+
+                let num_streaming_rounds = if num_rounds_x >= STREAMING_MIN_ROUNDS {
+                    NUM_STREAMING_ROUNDS.min(constraint_builder.uniform_repeat().log_2())
+                } else {
+                    0
+                };
+
+                outer_mask = rng.as_mut().map(|rng| {
+                    let mask = MaskingPolynomial::random(num_rounds_x, 3, rng);
+                    let (proof, rho) = SumcheckMaskProof::commit(&mask, generators, transcript);
+                    (mask, proof, rho)
+                });
+                let outer_claim = outer_mask
+                    .as_ref()
+                    .map_or(F::zero(), |(_, proof, rho)| *rho * proof.sum);
+                let outer_mask_rho = outer_mask.as_ref().map(|(mask, _, rho)| (mask, *rho));
+
+                let (outer_sumcheck_proof, outer_sumcheck_r, outer_sumcheck_claims) =
+                    if num_streaming_rounds > 0 {
+                        let mut eq_tau = SplitEqPolynomial::new(&tau);
+                        let outer_sumcheck = SumcheckInstanceProof::prove_spartan_cubic_streaming(
+                            &outer_claim, // zero, unless masked
+                            num_rounds_x,
+                            num_streaming_rounds,
+                            &mut eq_tau,
+                            |eq_tau, r| {
+                                constraint_builder.compute_spartan_streaming_round(
+                                    &flattened_polys,
+                                    eq_tau,
+                                    r,
+                                )
+                            },
+                            |r| {
+                                constraint_builder
+                                    .compute_spartan_Az_Bz_Cz_bound(&flattened_polys, r)
+                            },
+                            outer_mask_rho,
+                            transcript,
+                        );
+                        drop_in_background_thread(eq_tau);
+                        outer_sumcheck
+                    } else {
+                        let (mut az, mut bz, mut cz) = constraint_builder
+                            .compute_spartan_Az_Bz_Cz::<PCS, ProofTranscript>(&flattened_polys);
+                        let mut eq_tau = SplitEqPolynomial::new(&tau);
+                        let outer_sumcheck = SumcheckInstanceProof::prove_spartan_cubic(
+                            &outer_claim, // zero, unless masked
+                            num_rounds_x,
+                            &mut eq_tau,
+                            &mut az,
+                            &mut bz,
+                            &mut cz,
+                            outer_mask_rho,
+                            transcript,
+                        );
+                        drop_in_background_thread((az, bz, cz, eq_tau));
+                        outer_sumcheck
+                    };
+                if let Some((mask, proof, _)) = &mut outer_mask {
+                    proof.prove_evals(mask, &outer_sumcheck_r, opening_accumulator, transcript);
+                }
+                let outer_sumcheck_r: Vec<F> = outer_sumcheck_r.into_iter().rev().collect();
+
+                let (rx_con, rx_ts) =
+                    outer_sumcheck_r.split_at(outer_sumcheck_r.len() - key.num_steps.log_2());
+                (
+                    outer_sumcheck_proof,
+                    None,
+                    rx_con.to_vec(),
+                    StepPoint::new(rx_ts),
+                    outer_sumcheck_claims,
+                )
+            };
 
         ProofTranscript::append_scalars(transcript, &outer_sumcheck_claims);
+
         // claims from the end of sum-check
         // claim_Az is the (scalar) value v_A = \sum_y A(r_x, y) * z(r_x) where r_x is the sumcheck randomness
         let (claim_Az, claim_Bz, claim_Cz): (F, F, F) = (
@@ -155,25 +391,76 @@ where
             + r_inner_sumcheck_RLC * claim_Bz
             + r_inner_sumcheck_RLC * r_inner_sumcheck_RLC * claim_Cz;
 
+        // The rows of the step kinds' blocks are scaled by their selectors, which raises the
+        // degree of the inner sum-check
+        let step_kinds = &key.uniform_r1cs.step_kinds;
+        let inner_degree = if step_kinds.is_empty() { 2 } else { 3 };
+        let mut inner_mask = rng.as_mut().map(|rng| {
+            let mask = MaskingPolynomial::random(num_rounds_y, inner_degree, rng);
+            let (proof, rho) = SumcheckMaskProof::commit(&mask, generators, transcript);
+            (mask, proof, rho)
+        });
+        let inner_claim = claim_inner_joint
+            + inner_mask
+                .as_ref()
+                .map_or(F::zero(), |(_, proof, rho)| *rho * proof.sum);
+
         // this is the polynomial extended from the vector r_A * A(r_x, y) + r_B * B(r_x, y) + r_C * C(r_x, y) for all y
-        let num_steps_bits = constraint_builder
-            .uniform_repeat()
-            .next_power_of_two()
-            .ilog2();
-        let (rx_con, rx_ts) =
-            outer_sumcheck_r.split_at(outer_sumcheck_r.len() - num_steps_bits as usize);
         let mut poly_ABC =
-            DensePolynomial::new(key.evaluate_r1cs_mle_rlc(rx_con, rx_ts, r_inner_sumcheck_RLC));
-
-        let (inner_sumcheck_proof, inner_sumcheck_r, _claims_inner) =
-            SumcheckInstanceProof::prove_spartan_quadratic(
-                &claim_inner_joint, // r_A * v_A + r_B * v_B + r_C * v_C
-                num_rounds_y,
-                &mut poly_ABC, // r_A * A(r_x, y) + r_B * B(r_x, y) + r_C * C(r_x, y) for all y
-                &flattened_polys,
-                transcript,
-            );
-        drop_in_background_thread(poly_ABC);
+            DensePolynomial::new(key.evaluate_r1cs_mle_rlc(&rx_con, &rx_ts, r_inner_sumcheck_RLC));
+        let inner_mask_rho = inner_mask.as_ref().map(|(mask, _, rho)| (mask, *rho));
+
+        let (inner_sumcheck_proof, inner_sumcheck_r) = if step_kinds.is_empty() {
+            let (inner_sumcheck_proof, inner_sumcheck_r, _claims_inner) =
+                SumcheckInstanceProof::prove_spartan_quadratic(
+                    &inner_claim, // r_A * v_A + r_B * v_B + r_C * v_C, plus the mask's sum
+                    num_rounds_y,
+                    &mut poly_ABC, // r_A * A(r_x, y) + r_B * B(r_x, y) + r_C * C(r_x, y) for all y
+                    &flattened_polys,
+                    inner_mask_rho,
+                    transcript,
+                );
+            poly_ABC.recycle();
+            (inner_sumcheck_proof, inner_sumcheck_r)
+        } else {
+            // (ABC(r_x, y) + sum_k ABC_k(r_x, y) * S_k(y)) * Z(y), where ABC_k holds the rows of
+            // step kind k and S_k(y) is its selector at the step of y
+            let mut polys = vec![poly_ABC, Self::materialize_z(key, &flattened_polys)];
+            for (kind, poly_ABC_k) in step_kinds.iter().zip(key.evaluate_step_kind_mle_rlc(
+                &rx_con,
+                &rx_ts,
+                r_inner_sumcheck_RLC,
+            )) {
+                let selector = flattened_polys[kind.selector].evals_ref();
+                let selector_y: Vec<F> = (0..key.num_cols_total())
+                    .into_par_iter()
+                    .map(|y| selector[y % key.num_steps])
+                    .collect();
+                polys.push(DensePolynomial::new(poly_ABC_k));
+                polys.push(DensePolynomial::new(selector_y));
+            }
+            let (inner_sumcheck_proof, inner_sumcheck_r, _claims_inner) =
+                SumcheckInstanceProof::prove_arbitrary_with_order(
+                    &inner_claim,
+                    num_rounds_y,
+                    &mut polys,
+                    |evals: &[F]| {
+                        let abc = evals[2..]
+                            .chunks_exact(2)
+                            .fold(evals[0], |abc, abc_k| abc + abc_k[0] * abc_k[1]);
+                        abc * evals[1]
+                    },
+                    3,
+                    BindingOrder::HighToLow,
+                    inner_mask_rho,
+                    transcript,
+                );
+            polys.into_iter().for_each(DensePolynomial::recycle);
+            (inner_sumcheck_proof, inner_sumcheck_r)
+        };
+        if let Some((mask, proof, _)) = &mut inner_mask {
+            proof.prove_evals(mask, &inner_sumcheck_r, opening_accumulator, transcript);
+        }
 
         // Requires 'r_col_segment_bits' to index the (const, segment). Within that segment we index the step using 'r_col_step'
         let r_col_segment_bits = key.uniform_r1cs.num_vars.next_power_of_two().log_2() + 1;
@@ -202,49 +489,142 @@ where
         Ok(UniformSpartanProof {
             _inputs: PhantomData,
             outer_sumcheck_proof,
+            outer_univariate_skip,
             outer_sumcheck_claims,
             inner_sumcheck_proof,
             claimed_witness_evals,
-            _marker: PhantomData,
+            masks: outer_mask
+                .zip(inner_mask)
+                .map(|((_, outer, _), (_, inner, _))| SpartanMasks { outer, inner }),
         })
     }
 
-    #[tracing::instrument(skip_all, name = "Spartan::verify")]
-    pub fn verify<PCS>(
-        &self,
+    /// The outer sum-check, with its lowest step variables skipped. Returns the proof, the point
+    /// `r_x = r_constr || r_step`, and the claims.
+    fn prove_outer_univariate_skip(
+        constraint_builder: &CombinedUniformBuilder<C, F, I>,
         key: &UniformSpartanKey<C, I, F>,
-        commitments: &JoltCommitments<PCS, ProofTranscript>,
-        opening_accumulator: &mut VerifierOpeningAccumulator<F, PCS, ProofTranscript>,
+        flattened_polys: &[&DensePolynomial<F>],
         transcript: &mut ProofTranscript,
-    ) -> Result<(), SpartanError>
-    where
-        PCS: CommitmentScheme<ProofTranscript, Field = F>,
-        ProofTranscript: Transcript,
-    {
+    ) -> (
+        UnivariateSkipProof<F, ProofTranscript>,
+        Vec<F>,
+        StepPoint<F>,
+        Vec<F>,
+    ) {
         let num_rounds_x = key.num_rows_total().log_2();
-        let num_rounds_y = key.num_cols_total().log_2();
-
-        // outer sum-check
-        let tau = (0..num_rounds_x)
+        let num_skipped_vars = NUM_SKIPPED_VARS.min(key.num_steps.log_2());
+        let tau_rest = (0..num_rounds_x - num_skipped_vars)
             .map(|_i| transcript.challenge_scalar())
             .collect::<Vec<F>>();
+        let tau_0: F = transcript.challenge_scalar();
+
+        let (az, bz, cz) =
+            constraint_builder.compute_spartan_Az_Bz_Cz::<PCS, ProofTranscript>(flattened_polys);
+        let (az, bz, cz) = (az.to_dense(), bz.to_dense(), cz.to_dense());
+        let (proof, r_0, r_rest, claims) = UnivariateSkipProof::prove(
+            &F::zero(),
+            num_skipped_vars,
+            tau_0,
+            &tau_rest,
+            az.evals_ref(),
+            bz.evals_ref(),
+            cz.evals_ref(),
+            transcript,
+        );
+        drop_in_background_thread((az, bz, cz));
 
-        let (claim_outer_final, r_x) = self
-            .outer_sumcheck_proof
-            .verify(F::zero(), num_rounds_x, 3, transcript)
-            .map_err(|_| SpartanError::InvalidOuterSumcheckProof)?;
+        // The remaining rounds are bound from the lowest variable
+        let r_rest: Vec<F> = r_rest.into_iter().rev().collect();
+        let (rx_con, rx_ts) =
+            r_rest.split_at(r_rest.len() - (key.num_steps.log_2() - num_skipped_vars));
+        (
+            proof,
+            rx_con.to_vec(),
+            StepPoint::with_skip(rx_ts, num_skipped_vars, r_0),
+            claims,
+        )
+    }
 
-        // Outer sumcheck is bound from the top, reverse the fiat shamir randomness
-        let r_x: Vec<F> = r_x.into_iter().rev().collect();
+    /// The full witness vector Z = [W, 1, 0, ...], laid out as in the inner sum-check.
+    fn materialize_z(
+        key: &UniformSpartanKey<C, I, F>,
+        flattened_polys: &[&DensePolynomial<F>],
+    ) -> DensePolynomial<F> {
+        let mut z = unsafe_allocate_zero_vec(key.num_cols_total());
+        z.par_chunks_mut(key.num_steps)
+            .zip(flattened_polys.par_iter())
+            .for_each(|(chunk, poly)| chunk.copy_from_slice(poly.evals_ref()));
+        z[key.num_vars_total()] = F::one();
+        DensePolynomial::new(z)
+    }
 
-        // verify claim_outer_final
-        let (claim_Az, claim_Bz, claim_Cz) = self.outer_sumcheck_claims;
-        let taus_bound_rx = EqPolynomial::new(tau).evaluate(&r_x);
-        let claim_outer_final_expected = taus_bound_rx * (claim_Az * claim_Bz - claim_Cz);
-        if claim_outer_final != claim_outer_final_expected {
-            return Err(SpartanError::InvalidOuterSumcheckClaim);
+    /// Verifies the sum-checks, and appends the claimed witness evaluations to
+    /// `opening_accumulator` to be checked against `commitments` in the batched opening.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Spartan::verify")
+    )]
+    pub fn verify(
+        &self,
+        key: &UniformSpartanKey<C, I, F>,
+        commitments: &JoltCommitments<PCS, ProofTranscript>,
+        opening_accumulator: &mut VerifierOpeningAccumulator<F, PCS, ProofTranscript>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), SpartanError> {
+        let num_rounds_x = key.num_rows_total().log_2();
+        let num_rounds_y = key.num_cols_total().log_2();
+
+        if self.claimed_witness_evals.len() != key.uniform_r1cs.num_vars {
+            return Err(SpartanError::InvalidWitnessLength);
         }
 
+        // outer sum-check
+        let (rx_con, rx_ts) = match &self.outer_univariate_skip {
+            Some(univariate_skip) => {
+                self.verify_outer_univariate_skip(univariate_skip, key, transcript)?
+            }
+            None => {
+                let tau = (0..num_rounds_x)
+                    .map(|_i| transcript.challenge_scalar())
+                    .collect::<Vec<F>>();
+
+                let outer_mask = self.masks.as_ref().map(|masks| {
+                    let rho = masks.outer.challenge(transcript);
+                    (&masks.outer, rho)
+                });
+                let outer_claim = outer_mask.map_or(F::zero(), |(mask, rho)| rho * mask.sum);
+
+                let (claim_outer_final, r_x) = self
+                    .outer_sumcheck_proof
+                    .verify(outer_claim, num_rounds_x, 3, transcript)
+                    .map_err(|_| SpartanError::InvalidOuterSumcheckProof)?;
+
+                // The mask's contribution to the final claim
+                let outer_mask_final = match outer_mask {
+                    Some((mask, rho)) => {
+                        rho * mask.verify_evals(3, &r_x, opening_accumulator, transcript)?
+                    }
+                    None => F::zero(),
+                };
+
+                // Outer sumcheck is bound from the top, reverse the fiat shamir randomness
+                let r_x: Vec<F> = r_x.into_iter().rev().collect();
+
+                // verify claim_outer_final
+                let (claim_Az, claim_Bz, claim_Cz) = self.outer_sumcheck_claims;
+                let taus_bound_rx = EqPolynomial::new(tau).evaluate(&r_x);
+                let claim_outer_final_expected =
+                    taus_bound_rx * (claim_Az * claim_Bz - claim_Cz) + outer_mask_final;
+                if claim_outer_final != claim_outer_final_expected {
+                    return Err(SpartanError::InvalidOuterSumcheckClaim);
+                }
+
+                let (rx_con, rx_ts) = r_x.split_at(r_x.len() - key.num_steps.log_2());
+                (rx_con.to_vec(), StepPoint::new(rx_ts))
+            }
+        };
+
         transcript.append_scalars(
             [
                 self.outer_sumcheck_claims.0,
@@ -260,25 +640,58 @@ where
             + r_inner_sumcheck_RLC * self.outer_sumcheck_claims.1
             + r_inner_sumcheck_RLC * r_inner_sumcheck_RLC * self.outer_sumcheck_claims.2;
 
+        let inner_mask = self.masks.as_ref().map(|masks| {
+            let rho = masks.inner.challenge(transcript);
+            (&masks.inner, rho)
+        });
+        let inner_claim = inner_mask.map_or(claim_inner_joint, |(mask, rho)| {
+            claim_inner_joint + rho * mask.sum
+        });
+
+        // The rows of the step kinds' blocks are scaled by their selectors, which raises the
+        // degree of the inner sum-check
+        let step_kinds = &key.uniform_r1cs.step_kinds;
+        let inner_degree = if step_kinds.is_empty() { 2 } else { 3 };
         let (claim_inner_final, inner_sumcheck_r) = self
             .inner_sumcheck_proof
-            .verify(claim_inner_joint, num_rounds_y, 2, transcript)
+            .verify(inner_claim, num_rounds_y, inner_degree, transcript)
             .map_err(|_| SpartanError::InvalidInnerSumcheckProof)?;
 
+        let inner_mask_final = match inner_mask {
+            Some((mask, rho)) => {
+                rho * mask.verify_evals(
+                    inner_degree,
+                    &inner_sumcheck_r,
+                    opening_accumulator,
+                    transcript,
+                )?
+            }
+            None => F::zero(),
+        };
+
         // n_prefix = n_segments + 1
         let n_prefix = key.uniform_r1cs.num_vars.next_power_of_two().log_2() + 1;
 
         let eval_Z = key.evaluate_z_mle(&self.claimed_witness_evals, &inner_sumcheck_r);
 
-        let r_y = inner_sumcheck_r.clone();
-        let r = [r_x, r_y].concat();
-        let (eval_a, eval_b, eval_c) = key.evaluate_r1cs_matrix_mles(&r);
+        let (eval_a, eval_b, eval_c) =
+            key.evaluate_r1cs_matrix_mles(&rx_con, &rx_ts, &inner_sumcheck_r);
+        let r_inner_sumcheck_RLC_sq = r_inner_sumcheck_RLC * r_inner_sumcheck_RLC;
 
+        // The selectors are witness variables, evaluated at the step of r_y
         let left_expected = eval_a
             + r_inner_sumcheck_RLC * eval_b
-            + r_inner_sumcheck_RLC * r_inner_sumcheck_RLC * eval_c;
+            + r_inner_sumcheck_RLC_sq * eval_c
+            + step_kinds
+                .iter()
+                .zip(key.evaluate_step_kind_matrix_mles(&rx_con, &rx_ts, &inner_sumcheck_r))
+                .map(|(kind, (eval_a_k, eval_c_k))| {
+                    (eval_a_k + r_inner_sumcheck_RLC_sq * eval_c_k)
+                        * self.claimed_witness_evals[kind.selector]
+                })
+                .sum::<F>();
         let right_expected = eval_Z;
-        let claim_inner_final_expected = left_expected * right_expected;
+        let claim_inner_final_expected = left_expected * right_expected + inner_mask_final;
         if claim_inner_final != claim_inner_final_expected {
             return Err(SpartanError::InvalidInnerSumcheckClaim);
         }
@@ -297,59 +710,52 @@ where
 
         Ok(())
     }
-}
 
-// #[cfg(test)]
-// mod test {
-//     use ark_bn254::Fr;
-//     use ark_std::One;
-
-//     use crate::poly::commitment::{commitment_scheme::CommitShape, hyrax::HyraxScheme};
-
-//     use super::*;
-
-//     #[test]
-//     fn integration() {
-//         let (builder, key) = simp_test_builder_key();
-//         let witness_segments: Vec<Vec<Fr>> = vec![
-//             vec![Fr::one(), Fr::from(5), Fr::from(9), Fr::from(13)], /* Q */
-//             vec![Fr::one(), Fr::from(5), Fr::from(9), Fr::from(13)], /* R */
-//             vec![Fr::one(), Fr::from(5), Fr::from(9), Fr::from(13)], /* S */
-//         ];
-
-//         // Create a witness and commit
-//         let witness_segments_ref: Vec<&[Fr]> = witness_segments
-//             .iter()
-//             .map(|segment| segment.as_slice())
-//             .collect();
-//         let gens = HyraxScheme::setup(&[CommitShape::new(16, BatchType::Small)]);
-//         let witness_commitment =
-//             HyraxScheme::batch_commit(&witness_segments_ref, &gens, BatchType::Small);
-
-//         // Prove spartan!
-//         let mut prover_transcript = ProofTranscript::new(b"stuff");
-//         let proof =
-//             UniformSpartanProof::<Fr, HyraxScheme<ark_bn254::G1Projective>>::prove_precommitted::<
-//                 SimpTestIn,
-//             >(
-//                 &gens,
-//                 builder,
-//                 &key,
-//                 witness_segments,
-//                 todo!("opening accumulator"),
-//                 &mut prover_transcript,
-//             )
-//             .unwrap();
-
-//         let mut verifier_transcript = ProofTranscript::new(b"stuff");
-//         let witness_commitment_ref: Vec<&_> = witness_commitment.iter().collect();
-//         proof
-//             .verify_precommitted(
-//                 &key,
-//                 witness_commitment_ref,
-//                 &gens,
-//                 &mut verifier_transcript,
-//             )
-//             .expect("Spartan verifier failed");
-//     }
-// }
+    /// Verifies the outer sum-check with its lowest step variables skipped, returning the point
+    /// `r_x = r_constr || r_step`.
+    fn verify_outer_univariate_skip(
+        &self,
+        univariate_skip: &UnivariateSkipProof<F, ProofTranscript>,
+        key: &UniformSpartanKey<C, I, F>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(Vec<F>, StepPoint<F>), SpartanError> {
+        // The skipped round is not masked
+        if self.masks.is_some() {
+            return Err(SpartanError::InvalidOuterSumcheckProof);
+        }
+
+        let num_rounds_x = key.num_rows_total().log_2();
+        let num_skipped_vars = NUM_SKIPPED_VARS.min(key.num_steps.log_2());
+        let tau_rest = (0..num_rounds_x - num_skipped_vars)
+            .map(|_i| transcript.challenge_scalar())
+            .collect::<Vec<F>>();
+        let tau_0: F = transcript.challenge_scalar();
+
+        let (claim_outer_final, r_0, r_rest) = univariate_skip
+            .verify(
+                F::zero(),
+                num_skipped_vars,
+                tau_0,
+                num_rounds_x - num_skipped_vars,
+                transcript,
+            )
+            .map_err(|_| SpartanError::InvalidOuterSumcheckProof)?;
+
+        // The remaining rounds are bound from the lowest variable
+        let r_rest: Vec<F> = r_rest.into_iter().rev().collect();
+        let (claim_Az, claim_Bz, claim_Cz) = self.outer_sumcheck_claims;
+        let claim_outer_final_expected = lagrange_kernel(num_skipped_vars.pow2(), tau_0, r_0)
+            * EqPolynomial::new(tau_rest).evaluate(&r_rest)
+            * (claim_Az * claim_Bz - claim_Cz);
+        if claim_outer_final != claim_outer_final_expected {
+            return Err(SpartanError::InvalidOuterSumcheckClaim);
+        }
+
+        let (rx_con, rx_ts) =
+            r_rest.split_at(r_rest.len() - (key.num_steps.log_2() - num_skipped_vars));
+        Ok((
+            rx_con.to_vec(),
+            StepPoint::with_skip(rx_ts, num_skipped_vars, r_0),
+        ))
+    }
+}
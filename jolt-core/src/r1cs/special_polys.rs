@@ -1,3 +1,4 @@
+use crate::utils::par::{self, prelude::*};
 use crate::{
     field::JoltField,
     utils::{
@@ -7,7 +8,6 @@ use crate::{
     },
 };
 use num_integer::Integer;
-use rayon::prelude::*;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct SparsePolynomial<F: JoltField> {
@@ -50,7 +50,7 @@ impl<F: JoltField> SparsePolynomial<F> {
     }
 
     /// Returns `n` chunks of roughly even size without separating siblings (adjacent dense indices). Additionally returns a vector of [low, high) dense index ranges.
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     #[allow(clippy::type_complexity)]
     fn chunk_no_split_siblings(&self, n: usize) -> (Vec<&[(F, usize)]>, Vec<(usize, usize)>) {
         if self.Z.len() < n * 2 {
@@ -86,13 +86,14 @@ impl<F: JoltField> SparsePolynomial<F> {
         (chunks, dense_ranges)
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn bound_poly_var_bot(&mut self, r: &F) {
         // TODO(sragss): Do this with a scan instead.
         let n = self.Z.len();
-        let span = tracing::span!(tracing::Level::DEBUG, "allocate");
-        let _enter = span.enter();
+        #[cfg(feature = "tracing")]
+        let _enter = tracing::span!(tracing::Level::DEBUG, "allocate").entered();
         let mut new_Z: Vec<(F, usize)> = Vec::with_capacity(n);
+        #[cfg(feature = "tracing")]
         drop(_enter);
         for (sparse_index, (value, dense_index)) in self.Z.iter().enumerate() {
             if dense_index.is_even() {
@@ -118,14 +119,14 @@ impl<F: JoltField> SparsePolynomial<F> {
         self.num_vars -= 1;
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn bound_poly_var_bot_par(&mut self, r: &F) {
         // TODO(sragss): better parallelism.
-        let (chunks, _range) = self.chunk_no_split_siblings(rayon::current_num_threads() * 8);
+        let (chunks, _range) = self.chunk_no_split_siblings(par::current_num_threads() * 8);
 
         // Calc chunk sizes post-binding for pre-allocation.
-        let count_span = tracing::span!(tracing::Level::DEBUG, "counting");
-        let count_enter = count_span.enter();
+        #[cfg(feature = "tracing")]
+        let count_enter = tracing::span!(tracing::Level::DEBUG, "counting").entered();
         let chunk_sizes: Vec<usize> = chunks
             .par_iter()
             .map(|chunk| {
@@ -140,12 +141,14 @@ impl<F: JoltField> SparsePolynomial<F> {
                     .count()
             })
             .collect();
+        #[cfg(feature = "tracing")]
         drop(count_enter);
 
-        let alloc_span = tracing::span!(tracing::Level::DEBUG, "alloc");
-        let alloc_enter = alloc_span.enter();
+        #[cfg(feature = "tracing")]
+        let alloc_enter = tracing::span!(tracing::Level::DEBUG, "alloc").entered();
         let total_len: usize = chunk_sizes.iter().sum();
         let mut new_Z: Vec<(F, usize)> = unsafe_allocate_sparse_zero_vec(total_len);
+        #[cfg(feature = "tracing")]
         drop(alloc_enter);
 
         let mut mutable_chunks: Vec<&mut [(F, usize)]> = Vec::with_capacity(chunk_sizes.len());
@@ -162,8 +165,8 @@ impl<F: JoltField> SparsePolynomial<F> {
             .into_par_iter()
             .zip(mutable_chunks.par_iter_mut())
             .for_each(|(chunk, mutable)| {
-                let span = tracing::span!(tracing::Level::DEBUG, "chunk");
-                let _enter = span.enter();
+                #[cfg(feature = "tracing")]
+                let _enter = tracing::span!(tracing::Level::DEBUG, "chunk").entered();
                 let mut write_index = 0;
                 for (sparse_index, (value, dense_index)) in chunk.iter().enumerate() {
                     if dense_index.is_even() {
@@ -213,8 +216,7 @@ impl<F: JoltField> SparsePolynomial<F> {
         }
     }
 
-    #[cfg(test)]
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn to_dense(self) -> crate::poly::dense_mlpoly::DensePolynomial<F> {
         use crate::utils::{math::Math, thread::unsafe_allocate_zero_vec};
 
@@ -237,7 +239,7 @@ pub struct SparseTripleIterator<'a, F: JoltField> {
 }
 
 impl<'a, F: JoltField> SparseTripleIterator<'a, F> {
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn chunks(
         a: &'a SparsePolynomial<F>,
         b: &'a SparsePolynomial<F>,
@@ -283,8 +285,8 @@ impl<'a, F: JoltField> SparseTripleIterator<'a, F> {
         let mut c_chunks: Vec<&[(F, usize)]> = vec![&[]; n];
         let mut a_sparse_i = 0;
         let mut c_sparse_i = 0;
-        let span = tracing::span!(tracing::Level::DEBUG, "a_c_chunking");
-        let _enter = span.enter();
+        #[cfg(feature = "tracing")]
+        let _enter = tracing::span!(tracing::Level::DEBUG, "a_c_chunking").entered();
         // Using b's dense_ranges as a guide, fill out (a_chunks, c_chunks)
         for (chunk_index, range) in dense_ranges.iter().enumerate().skip(1) {
             // Find the corresponding a, c chunks
@@ -310,6 +312,7 @@ impl<'a, F: JoltField> SparseTripleIterator<'a, F> {
                 c_chunks[chunk_index - 1] = &c.Z[c_start..c_sparse_i];
             }
         }
+        #[cfg(feature = "tracing")]
         drop(_enter);
         a_chunks[n - 1] = &a.Z[a_sparse_i..];
         c_chunks[n - 1] = &c.Z[c_sparse_i..];
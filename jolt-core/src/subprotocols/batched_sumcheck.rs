@@ -0,0 +1,335 @@
+//! Proves several sum-checks together, under one set of verifier challenges.
+//!
+//! The instances may differ in degree and number of rounds. The verifier draws a coefficient
+//! per instance, and each round's message is the combination of the instances' round
+//! polynomials, so the batch costs the rounds and the degree of its largest instance.
+//!
+//! An instance with `n` rounds, in a batch of `max_rounds`, sits out the first
+//! `max_rounds - n` rounds: its claim is scaled by `2^(max_rounds - n)`, it contributes the
+//! constant polynomial of half its running claim to each of these rounds, and its variables are
+//! bound to the last `n` challenges.
+use crate::field::JoltField;
+use crate::poly::dense_mlpoly::{BindingOrder, DensePolynomial};
+use crate::poly::unipoly::UniPoly;
+use crate::subprotocols::sumcheck::{arbitrary_round_evals, SumcheckConfig, SumcheckInstanceProof};
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::par::prelude::*;
+use crate::utils::transcript::{AppendToTranscript, Transcript};
+
+/// One sum-check in a batch. The prover drives it through `compute_prover_message` and `bind`;
+/// the verifier only needs its degree, rounds, and claims.
+pub trait SumcheckInstance<F: JoltField> {
+    /// Degree of the round polynomials
+    fn degree(&self) -> usize;
+
+    /// Number of variables bound by this sum-check
+    fn num_rounds(&self) -> usize;
+
+    /// The claimed sum
+    fn input_claim(&self) -> F;
+
+    /// Evaluations of this round's polynomial at `0, 1, ..., degree`, given the previous
+    /// round's claim.
+    fn compute_prover_message(&self, previous_claim: F) -> Vec<F>;
+
+    /// Binds this round's variable to the challenge `r_j`.
+    fn bind(&mut self, r_j: F);
+
+    /// The evaluation that the final claim must equal, at this instance's challenges `r`.
+    fn expected_output_claim(&self, r: &[F]) -> F;
+}
+
+pub enum BatchedSumcheck {}
+
+impl BatchedSumcheck {
+    /// Proves the sum-checks of `instances` together. Returns the proof and the challenges of
+    /// the batch; instance `i` is bound to the last `instances[i].num_rounds()` of them.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedSumcheck::prove")
+    )]
+    pub fn prove<F: JoltField, ProofTranscript: Transcript>(
+        mut instances: Vec<&mut dyn SumcheckInstance<F>>,
+        transcript: &mut ProofTranscript,
+    ) -> (SumcheckInstanceProof<F, ProofTranscript>, Vec<F>) {
+        let (max_rounds, max_degree) = Self::dimensions(instances.iter().map(|i| &**i));
+        let coeffs = Self::batching_coeffs(instances.iter().map(|i| &**i), transcript);
+
+        let mut claims: Vec<F> = instances
+            .iter()
+            .map(|instance| {
+                instance.input_claim()
+                    * F::from_u64(1 << (max_rounds - instance.num_rounds())).unwrap()
+            })
+            .collect();
+        let two_inverse = F::from_u64(2).unwrap().inverse().unwrap();
+        let mut r: Vec<F> = Vec::with_capacity(max_rounds);
+        let mut compressed_polys = Vec::with_capacity(max_rounds);
+
+        for round in 0..max_rounds {
+            let round_polys: Vec<UniPoly<F>> = instances
+                .iter()
+                .zip(claims.iter())
+                .map(|(instance, claim)| {
+                    if round < max_rounds - instance.num_rounds() {
+                        UniPoly::from_coeff(vec![*claim * two_inverse])
+                    } else {
+                        UniPoly::from_evals(&instance.compute_prover_message(*claim))
+                    }
+                })
+                .collect();
+
+            let batched_evals: Vec<F> = (0..=max_degree)
+                .map(|x| {
+                    let x = F::from_u64(x as u64).unwrap();
+                    round_polys
+                        .iter()
+                        .zip(coeffs.iter())
+                        .map(|(poly, coeff)| poly.evaluate(&x) * coeff)
+                        .sum()
+                })
+                .collect();
+            let compressed_poly = UniPoly::from_evals(&batched_evals).compress();
+            compressed_poly.append_to_transcript(transcript);
+            let r_j: F = transcript.challenge_scalar();
+            r.push(r_j);
+
+            for ((instance, claim), poly) in instances
+                .iter_mut()
+                .zip(claims.iter_mut())
+                .zip(round_polys.iter())
+            {
+                *claim = poly.evaluate(&r_j);
+                if round >= max_rounds - instance.num_rounds() {
+                    instance.bind(r_j);
+                }
+            }
+            compressed_polys.push(compressed_poly);
+        }
+
+        (SumcheckInstanceProof::new(compressed_polys), r)
+    }
+
+    /// Verifies a batched sum-check proof, including each instance's final check. Returns the
+    /// challenges of the batch.
+    pub fn verify<F: JoltField, ProofTranscript: Transcript>(
+        proof: &SumcheckInstanceProof<F, ProofTranscript>,
+        instances: Vec<&dyn SumcheckInstance<F>>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<Vec<F>, ProofVerifyError> {
+        let (max_rounds, max_degree) = Self::dimensions(instances.iter().copied());
+        let coeffs = Self::batching_coeffs(instances.iter().copied(), transcript);
+
+        let claim: F = instances
+            .iter()
+            .zip(coeffs.iter())
+            .map(|(instance, coeff)| {
+                instance.input_claim()
+                    * F::from_u64(1 << (max_rounds - instance.num_rounds())).unwrap()
+                    * coeff
+            })
+            .sum();
+        if proof.compressed_polys.len() != max_rounds {
+            return Err(ProofVerifyError::InvalidInputLength(
+                max_rounds,
+                proof.compressed_polys.len(),
+            ));
+        }
+        let (output_claim, r) = proof.verify(claim, max_rounds, max_degree, transcript)?;
+
+        let expected_output_claim: F = instances
+            .iter()
+            .zip(coeffs.iter())
+            .map(|(instance, coeff)| {
+                instance.expected_output_claim(&r[max_rounds - instance.num_rounds()..]) * coeff
+            })
+            .sum();
+        if output_claim != expected_output_claim {
+            return Err(ProofVerifyError::InternalError);
+        }
+
+        Ok(r)
+    }
+
+    fn dimensions<'a, F: JoltField + 'a>(
+        instances: impl Iterator<Item = &'a (dyn SumcheckInstance<F> + 'a)>,
+    ) -> (usize, usize) {
+        instances.fold((0, 0), |(rounds, degree), instance| {
+            (
+                rounds.max(instance.num_rounds()),
+                degree.max(instance.degree()),
+            )
+        })
+    }
+
+    fn batching_coeffs<'a, F: JoltField + 'a, ProofTranscript: Transcript>(
+        instances: impl ExactSizeIterator<Item = &'a (dyn SumcheckInstance<F> + 'a)>,
+        transcript: &mut ProofTranscript,
+    ) -> Vec<F> {
+        let num_instances = instances.len();
+        for instance in instances {
+            transcript.append_scalar(&instance.input_claim());
+        }
+        transcript.challenge_vector(num_instances)
+    }
+}
+
+/// A sum-check of `comb_func` over dense polynomials, as proven by
+/// [`SumcheckInstanceProof::prove_arbitrary`], for use in a batch.
+pub struct DenseSumcheckInstance<F: JoltField, Func> {
+    polys: Vec<DensePolynomial<F>>,
+    comb_func: Func,
+    degree: usize,
+    num_rounds: usize,
+    claim: F,
+}
+
+impl<F, Func> DenseSumcheckInstance<F, Func>
+where
+    F: JoltField,
+    Func: Fn(&[F]) -> F + Sync,
+{
+    pub fn new(polys: Vec<DensePolynomial<F>>, comb_func: Func, degree: usize, claim: F) -> Self {
+        let num_rounds = polys[0].get_num_vars();
+        debug_assert!(polys.iter().all(|poly| poly.get_num_vars() == num_rounds));
+        Self {
+            polys,
+            comb_func,
+            degree,
+            num_rounds,
+            claim,
+        }
+    }
+
+    /// Evaluations of the polynomials at the bound point, once all rounds are done.
+    pub fn final_evals(&self) -> Vec<F> {
+        self.polys.iter().map(|poly| poly[0]).collect()
+    }
+}
+
+impl<F, Func> SumcheckInstance<F> for DenseSumcheckInstance<F, Func>
+where
+    F: JoltField,
+    Func: Fn(&[F]) -> F + Sync,
+{
+    fn degree(&self) -> usize {
+        self.degree
+    }
+
+    fn num_rounds(&self) -> usize {
+        self.num_rounds
+    }
+
+    fn input_claim(&self) -> F {
+        self.claim
+    }
+
+    fn compute_prover_message(&self, _previous_claim: F) -> Vec<F> {
+        arbitrary_round_evals(
+            &self.polys,
+            &self.comb_func,
+            self.degree,
+            BindingOrder::HighToLow,
+            &SumcheckConfig::default(),
+        )
+    }
+
+    fn bind(&mut self, r_j: F) {
+        self.polys
+            .par_iter_mut()
+            .for_each(|poly| poly.bind(&r_j, BindingOrder::HighToLow));
+    }
+
+    fn expected_output_claim(&self, r: &[F]) -> F {
+        let evals: Vec<F> = self.polys.iter().map(|poly| poly.evaluate(r)).collect();
+        (self.comb_func)(&evals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    fn product_instance(
+        num_vars: usize,
+        num_polys: usize,
+        rng: &mut ChaCha20Rng,
+    ) -> DenseSumcheckInstance<Fr, impl Fn(&[Fr]) -> Fr + Sync> {
+        let polys: Vec<DensePolynomial<Fr>> = (0..num_polys)
+            .map(|_| DensePolynomial::random(num_vars, rng))
+            .collect();
+        let claim = (0..1 << num_vars)
+            .map(|i| polys.iter().map(|poly| poly[i]).product::<Fr>())
+            .sum();
+        DenseSumcheckInstance::new(polys, |vals: &[Fr]| vals.iter().product(), num_polys, claim)
+    }
+
+    #[test]
+    fn heterogeneous_instances() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let mut quadratic = product_instance(3, 2, &mut rng);
+        let mut cubic = product_instance(5, 3, &mut rng);
+        let (quadratic_polys, cubic_polys) = (quadratic.polys.clone(), cubic.polys.clone());
+
+        let mut prover_transcript = KeccakTranscript::new(b"test");
+        let (proof, r_prover) = BatchedSumcheck::prove(
+            vec![&mut quadratic as &mut dyn SumcheckInstance<Fr>, &mut cubic],
+            &mut prover_transcript,
+        );
+        assert_eq!(proof.compressed_polys.len(), 5);
+
+        // The instances are bound to their suffix of the challenges
+        for (poly, eval) in quadratic_polys.iter().zip(quadratic.final_evals()) {
+            assert_eq!(poly.evaluate(&r_prover[2..]), eval);
+        }
+        for (poly, eval) in cubic_polys.iter().zip(cubic.final_evals()) {
+            assert_eq!(poly.evaluate(&r_prover), eval);
+        }
+
+        let verifier_quadratic = DenseSumcheckInstance::new(
+            quadratic_polys,
+            |vals: &[Fr]| vals.iter().product(),
+            2,
+            quadratic.claim,
+        );
+        let verifier_cubic = DenseSumcheckInstance::new(
+            cubic_polys,
+            |vals: &[Fr]| vals.iter().product(),
+            3,
+            cubic.claim,
+        );
+        let mut verifier_transcript = KeccakTranscript::new(b"test");
+        let r = BatchedSumcheck::verify(
+            &proof,
+            vec![
+                &verifier_quadratic as &dyn SumcheckInstance<Fr>,
+                &verifier_cubic,
+            ],
+            &mut verifier_transcript,
+        )
+        .unwrap();
+        assert_eq!(r, r_prover);
+
+        // A wrong claim is rejected
+        let wrong_cubic = DenseSumcheckInstance::new(
+            verifier_cubic.polys.clone(),
+            |vals: &[Fr]| vals.iter().product(),
+            3,
+            verifier_cubic.claim + Fr::from(1u64),
+        );
+        let mut verifier_transcript = KeccakTranscript::new(b"test");
+        assert!(BatchedSumcheck::verify(
+            &proof,
+            vec![
+                &verifier_quadratic as &dyn SumcheckInstance<Fr>,
+                &wrong_cubic
+            ],
+            &mut verifier_transcript,
+        )
+        .is_err());
+    }
+}
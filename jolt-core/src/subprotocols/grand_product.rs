@@ -1,3 +1,9 @@
+//! Thaler's GKR protocol for batched grand products.
+//!
+//! Each grand product is a binary tree of multiplication gates over its leaves. The prover
+//! reduces a claim about a layer's evaluation to a claim about the next layer down with one
+//! sum-check, so only the leaves are ever committed to; the memory checker's multiset equality
+//! checks need no commitments to the intermediate product layers.
 use super::grand_product_quarks::QuarkGrandProductProof;
 use super::sumcheck::{BatchedCubicSumcheck, SumcheckInstanceProof};
 use crate::field::JoltField;
@@ -7,11 +13,11 @@ use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::opening_proof::{ProverOpeningAccumulator, VerifierOpeningAccumulator};
 use crate::poly::split_eq_poly::SplitEqPolynomial;
 use crate::utils::math::Math;
+use crate::utils::par::prelude::*;
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::Transcript;
 use ark_serialize::*;
 use itertools::Itertools;
-use rayon::prelude::*;
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct BatchedGrandProductLayerProof<F: JoltField, ProofTranscript: Transcript> {
@@ -72,7 +78,10 @@ where
     ) -> impl Iterator<Item = &'_ mut dyn BatchedGrandProductLayer<F, ProofTranscript>>;
 
     /// Computes a batched grand product proof, layer by layer.
-    #[tracing::instrument(skip_all, name = "BatchedGrandProduct::prove_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProduct::prove_grand_product")
+    )]
     fn prove_grand_product(
         &mut self,
         _opening_accumulator: Option<&mut ProverOpeningAccumulator<F, ProofTranscript>>,
@@ -259,7 +268,10 @@ where
     type Leaves = (Vec<F>, usize);
     type Config = ();
 
-    #[tracing::instrument(skip_all, name = "BatchedDenseGrandProduct::construct")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedDenseGrandProduct::construct")
+    )]
     fn construct(leaves: Self::Leaves) -> Self {
         let (leaves, batch_size) = leaves;
         assert!(leaves.len() % batch_size == 0);
@@ -277,7 +289,10 @@ where
 
         Self { layers }
     }
-    #[tracing::instrument(skip_all, name = "BatchedDenseGrandProduct::construct_with_config")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedDenseGrandProduct::construct_with_config")
+    )]
     fn construct_with_config(leaves: Self::Leaves, _config: Self::Config) -> Self {
         <Self as BatchedGrandProduct<F, PCS, ProofTranscript>>::construct(leaves)
     }
@@ -10,11 +10,11 @@ use crate::poly::eq_poly::EqPolynomial;
 use crate::poly::opening_proof::{ProverOpeningAccumulator, VerifierOpeningAccumulator};
 use crate::subprotocols::QuarkHybridLayerDepth;
 use crate::utils::math::Math;
+use crate::utils::par::prelude::*;
 use crate::utils::transcript::{AppendToTranscript, Transcript};
 use ark_serialize::*;
 use ark_std::{One, Zero};
 use itertools::Itertools;
-use rayon::prelude::*;
 use std::marker::PhantomData;
 use thiserror::Error;
 
@@ -56,7 +56,10 @@ where
     type Config = QuarkGrandProductConfig;
 
     /// Constructs the grand product circuit(s) from `leaves`
-    #[tracing::instrument(skip_all, name = "BatchedGrandProduct::construct")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProduct::construct")
+    )]
     fn construct(leaves: Self::Leaves) -> Self {
         <Self as BatchedGrandProduct<F, PCS, ProofTranscript>>::construct_with_config(
             leaves,
@@ -65,7 +68,10 @@ where
     }
 
     /// Constructs the grand product circuit(s) from `leaves` with the given `config`.
-    #[tracing::instrument(skip_all, name = "BatchedGrandProduct::construct_with_config")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProduct::construct_with_config")
+    )]
     fn construct_with_config(leaves: Self::Leaves, config: Self::Config) -> Self {
         let (leaves, batch_size) = leaves;
         assert!(leaves.len() % batch_size == 0);
@@ -147,7 +153,10 @@ where
         self.quark_poly.as_deref()
     }
 
-    #[tracing::instrument(skip_all, name = "BatchedGrandProduct::prove_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProduct::prove_grand_product")
+    )]
     fn prove_grand_product(
         &mut self,
         opening_accumulator: Option<&mut ProverOpeningAccumulator<F, ProofTranscript>>,
@@ -162,7 +171,10 @@ where
         )
     }
 
-    #[tracing::instrument(skip_all, name = "BatchedGrandProduct::verify_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProduct::verify_grand_product")
+    )]
     fn verify_grand_product(
         proof: &BatchedGrandProductProof<PCS, ProofTranscript>,
         claimed_outputs: &[F],
@@ -189,7 +201,10 @@ where
     ProofTranscript: Transcript,
 {
     /// Computes a batched grand product proof, layer by layer.
-    #[tracing::instrument(skip_all, name = "QuarkGrandProduct::prove_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "QuarkGrandProduct::prove_grand_product")
+    )]
     pub fn prove_quark_grand_product<PCS: CommitmentScheme<ProofTranscript, Field = F>>(
         grand_product: &mut impl BatchedGrandProduct<F, PCS, ProofTranscript>,
         opening_accumulator: Option<&mut ProverOpeningAccumulator<F, ProofTranscript>>,
@@ -237,7 +252,10 @@ where
     }
 
     /// Verifies the given grand product proof.
-    #[tracing::instrument(skip_all, name = "QuarkGrandProduct::verify_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "QuarkGrandProduct::verify_grand_product")
+    )]
     pub fn verify_quark_grand_product<G, PCS>(
         proof: &BatchedGrandProductProof<PCS, ProofTranscript>,
         claimed_outputs: &[F],
@@ -673,7 +691,8 @@ mod quark_grand_product_tests {
         let mut prover_transcript: KeccakTranscript = KeccakTranscript::new(b"test_transcript");
 
         let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << 9);
-        let setup = srs.trim(1 << 9);
+        let (pk, vk) = srs.trim(1 << 9);
+        let setup = (pk, vk.prepare());
 
         let mut hybrid_grand_product =
             <QuarkGrandProduct<Fr, KeccakTranscript> as BatchedGrandProduct<
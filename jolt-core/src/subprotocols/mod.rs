@@ -1,9 +1,11 @@
 #![allow(clippy::too_many_arguments)]
 
+pub mod batched_sumcheck;
 pub mod grand_product;
 pub mod grand_product_quarks;
 pub mod sparse_grand_product;
 pub mod sumcheck;
+pub mod univariate_skip;
 
 #[derive(Clone, Copy, Debug, Default)]
 pub enum QuarkHybridLayerDepth {
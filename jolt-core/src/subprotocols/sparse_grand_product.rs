@@ -14,9 +14,9 @@ use crate::poly::unipoly::UniPoly;
 use crate::subprotocols::grand_product_quarks::QuarkGrandProductBase;
 use crate::subprotocols::QuarkHybridLayerDepth;
 use crate::utils::math::Math;
+use crate::utils::par::prelude::*;
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::Transcript;
-use rayon::prelude::*;
 
 /// A special bottom layer of a grand product, where boolean flags are used to
 /// toggle the other inputs (fingerprints) going into the rest of the tree.
@@ -131,7 +131,10 @@ impl<F: JoltField> BatchedGrandProductToggleLayer<F> {
     ///   o      o     o      o    <-  output layer
     ///  / \    / \   / \    / \
     /// 🏴  o  🏳️ o  🏳️ o  🏴  o  <- toggle layer
-    #[tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::layer_output")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::layer_output")
+    )]
     fn layer_output(&self) -> SparseInterleavedPolynomial<F> {
         let values: Vec<_> = self
             .fingerprints
@@ -154,7 +157,10 @@ impl<F: JoltField> BatchedGrandProductToggleLayer<F> {
     /// Coalesces flags and fingerprints into one (dense) vector each.
     /// After a certain number of bindings, we can no longer process the k
     /// circuits in the batch in independently, at which point we coalesce.
-    #[tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::coalesce")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::coalesce")
+    )]
     fn coalesce(&mut self) {
         let mut coalesced_fingerprints: Vec<F> =
             self.fingerprints.iter().map(|f| f[0]).collect::<Vec<_>>();
@@ -196,7 +202,10 @@ impl<F: JoltField> Bindable<F> for BatchedGrandProductToggleLayer<F> {
     ///   value 1. For our sparse representation of flags, the absence of a node implies
     ///   that it has value 0. In other words, a flag with value 1 will be present in both
     ///   `self.flag_indices` and `self.flag_values`.
-    #[tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::bind")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::bind")
+    )]
     fn bind(&mut self, r: F) {
         #[cfg(test)]
         let (mut flags_before_binding, mut fingerprints_before_binding) = self.to_dense();
@@ -396,7 +405,10 @@ impl<F: JoltField, ProofTranscript: Transcript> BatchedCubicSumcheck<F, ProofTra
     /// 2. Flags/fingerprints are coalesced, and E1 isn't fully bound
     /// 3. Flags/fingerprints aren't coalesced, and E1 is fully bound
     /// 4. Flags/fingerprints aren't coalesced, and E1 isn't fully bound
-    #[tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::compute_cubic")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedGrandProductToggleLayer::compute_cubic")
+    )]
     fn compute_cubic(&self, eq_poly: &SplitEqPolynomial<F>, previous_round_claim: F) -> UniPoly<F> {
         if let Some(coalesced_flags) = &self.coalesced_flags {
             let coalesced_fingerpints = self.coalesced_fingerprints.as_ref().unwrap();
@@ -963,7 +975,10 @@ where
     type Leaves = (Vec<Vec<usize>>, Vec<Vec<F>>); // (flags, fingerprints)
     type Config = SparseGrandProductConfig;
 
-    #[tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::construct")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::construct")
+    )]
     fn construct(leaves: Self::Leaves) -> Self {
         <Self as BatchedGrandProduct<F, PCS, ProofTranscript>>::construct_with_config(
             leaves,
@@ -971,7 +986,10 @@ where
         )
     }
 
-    #[tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::construct_with_config")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::construct_with_config")
+    )]
     fn construct_with_config(leaves: Self::Leaves, config: Self::Config) -> Self {
         let (flags, fingerprints) = leaves;
         let batch_size = fingerprints.len();
@@ -1046,7 +1064,10 @@ where
     }
 
     /// Computes a batched grand product proof, layer by layer.
-    #[tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::prove_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::prove_grand_product")
+    )]
     fn prove_grand_product(
         &mut self,
         opening_accumulator: Option<&mut ProverOpeningAccumulator<F, ProofTranscript>>,
@@ -1062,7 +1083,10 @@ where
     }
 
     /// Verifies the given grand product proof.
-    #[tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::verify_grand_product")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "ToggledBatchedGrandProduct::verify_grand_product")
+    )]
     fn verify_grand_product(
         proof: &BatchedGrandProductProof<PCS, ProofTranscript>,
         claimed_outputs: &[F],
@@ -1284,7 +1308,8 @@ mod tests {
             .collect();
 
         let srs = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << 10);
-        let setup = srs.trim(1 << 10);
+        let (pk, vk) = srs.trim(1 << 10);
+        let setup = (pk, vk.prepare());
 
         // Construct circuit with configuration
         let mut circuit = <ToggledBatchedGrandProduct<Fr> as BatchedGrandProduct<
@@ -2,17 +2,20 @@
 #![allow(clippy::type_complexity)]
 
 use crate::field::JoltField;
-use crate::poly::dense_mlpoly::DensePolynomial;
+use crate::poly::dense_mlpoly::{BindingOrder, DensePolynomial};
+use crate::poly::eq_poly::EqPolynomial;
+use crate::poly::masking_poly::MaskingPolynomial;
 use crate::poly::split_eq_poly::SplitEqPolynomial;
 use crate::poly::unipoly::{CompressedUniPoly, UniPoly};
 use crate::r1cs::special_polys::{SparsePolynomial, SparseTripleIterator};
 use crate::utils::errors::ProofVerifyError;
+use crate::utils::field_encoding::{append_len, ToFieldElements};
 use crate::utils::math::Math;
 use crate::utils::mul_0_optimized;
+use crate::utils::par::{self, prelude::*};
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::{AppendToTranscript, Transcript};
 use ark_serialize::*;
-use rayon::prelude::*;
 use std::marker::PhantomData;
 
 pub trait Bindable<F: JoltField>: Sync {
@@ -31,7 +34,10 @@ where
     #[cfg(test)]
     fn sumcheck_sanity_check(&self, eq_poly: &SplitEqPolynomial<F>, round_claim: F);
 
-    #[tracing::instrument(skip_all, name = "BatchedCubicSumcheck::prove_sumcheck")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "BatchedCubicSumcheck::prove_sumcheck")
+    )]
     fn prove_sumcheck(
         &mut self,
         claim: &F,
@@ -90,13 +96,75 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
     /// Returns (SumcheckInstanceProof, r_eval_point, final_evals)
     /// - `r_eval_point`: Final random point of evaluation
     /// - `final_evals`: Each of the polys evaluated at `r_eval_point`
-    #[tracing::instrument(skip_all, name = "Sumcheck.prove")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Sumcheck.prove")
+    )]
     pub fn prove_arbitrary<Func>(
+        claim: &F,
+        num_rounds: usize,
+        polys: &mut Vec<DensePolynomial<F>>,
+        comb_func: Func,
+        combined_degree: usize,
+        transcript: &mut ProofTranscript,
+    ) -> (Self, Vec<F>, Vec<F>)
+    where
+        Func: Fn(&[F]) -> F + std::marker::Sync,
+    {
+        Self::prove_arbitrary_with_order(
+            claim,
+            num_rounds,
+            polys,
+            comb_func,
+            combined_degree,
+            BindingOrder::HighToLow,
+            None,
+            transcript,
+        )
+    }
+
+    /// Same as [`SumcheckInstanceProof::prove_arbitrary`], but binds the variables of `polys`
+    /// in the given `order`. The returned challenges `r` are in round order, so the final
+    /// evaluations are those of `poly.evaluate_with_order(&r, order)`.
+    ///
+    /// If a `mask` `(g, rho)` is given, proves the sum of `comb_func + rho * g` instead.
+    pub fn prove_arbitrary_with_order<Func>(
+        claim: &F,
+        num_rounds: usize,
+        polys: &mut Vec<DensePolynomial<F>>,
+        comb_func: Func,
+        combined_degree: usize,
+        order: BindingOrder,
+        mask: Option<(&MaskingPolynomial<F>, F)>,
+        transcript: &mut ProofTranscript,
+    ) -> (Self, Vec<F>, Vec<F>)
+    where
+        Func: Fn(&[F]) -> F + std::marker::Sync,
+    {
+        Self::prove_arbitrary_with_config(
+            claim,
+            num_rounds,
+            polys,
+            comb_func,
+            combined_degree,
+            order,
+            mask,
+            &SumcheckConfig::default(),
+            transcript,
+        )
+    }
+
+    /// Same as [`SumcheckInstanceProof::prove_arbitrary_with_order`], but splits each round's
+    /// evaluations into parallel work as given by `config`.
+    pub fn prove_arbitrary_with_config<Func>(
         _claim: &F,
         num_rounds: usize,
         polys: &mut Vec<DensePolynomial<F>>,
         comb_func: Func,
         combined_degree: usize,
+        order: BindingOrder,
+        mask: Option<(&MaskingPolynomial<F>, F)>,
+        config: &SumcheckConfig,
         transcript: &mut ProofTranscript,
     ) -> (Self, Vec<F>, Vec<F>)
     where
@@ -106,68 +174,13 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         let mut compressed_polys: Vec<CompressedUniPoly<F>> = Vec::new();
 
         for _round in 0..num_rounds {
-            // Vector storing evaluations of combined polynomials g(x) = P_0(x) * ... P_{num_polys} (x)
-            // for points {0, ..., |g(x)|}
-            let mut eval_points = vec![F::zero(); combined_degree + 1];
-
-            let mle_half = polys[0].len() / 2;
-
-            let accum: Vec<Vec<F>> = (0..mle_half)
-                .into_par_iter()
-                .map(|poly_term_i| {
-                    let mut accum = vec![F::zero(); combined_degree + 1];
-                    // Evaluate P({0, ..., |g(r)|})
-
-                    // TODO(#28): Optimize
-                    // Tricks can be used here for low order bits {0,1} but general premise is a running sum for each
-                    // of the m terms in the Dense multilinear polynomials. Formula is:
-                    // half = | D_{n-1} | / 2
-                    // D_n(index, r) = D_{n-1}[half + index] + r * (D_{n-1}[half + index] - D_{n-1}[index])
-
-                    // eval 0: bound_func is A(low)
-                    let params_zero: Vec<F> = polys.iter().map(|poly| poly[poly_term_i]).collect();
-                    accum[0] += comb_func(&params_zero);
-
-                    // TODO(#28): Can be computed from prev_round_claim - eval_point_0
-                    let params_one: Vec<F> = polys
-                        .iter()
-                        .map(|poly| poly[mle_half + poly_term_i])
-                        .collect();
-                    accum[1] += comb_func(&params_one);
-
-                    // D_n(index, r) = D_{n-1}[half + index] + r * (D_{n-1}[half + index] - D_{n-1}[index])
-                    // D_n(index, 0) = D_{n-1}[LOW]
-                    // D_n(index, 1) = D_{n-1}[HIGH]
-                    // D_n(index, 2) = D_{n-1}[HIGH] + (D_{n-1}[HIGH] - D_{n-1}[LOW])
-                    // D_n(index, 3) = D_{n-1}[HIGH] + (D_{n-1}[HIGH] - D_{n-1}[LOW]) + (D_{n-1}[HIGH] - D_{n-1}[LOW])
-                    // ...
-                    let mut existing_term = params_one;
-                    for eval_i in 2..(combined_degree + 1) {
-                        let mut poly_evals = vec![F::zero(); polys.len()];
-                        for poly_i in 0..polys.len() {
-                            let poly = &polys[poly_i];
-                            poly_evals[poly_i] = existing_term[poly_i]
-                                + poly[mle_half + poly_term_i]
-                                - poly[poly_term_i];
-                        }
-
-                        accum[eval_i] += comb_func(&poly_evals);
-                        existing_term = poly_evals;
-                    }
-                    accum
-                })
-                .collect();
-
-            eval_points
-                .par_iter_mut()
-                .enumerate()
-                .for_each(|(poly_i, eval_point)| {
-                    *eval_point = accum
-                        .par_iter()
-                        .take(mle_half)
-                        .map(|mle| mle[poly_i])
-                        .sum::<F>();
-                });
+            // Evaluations of the combined polynomial g(x) = comb_func(P_0(x), ..., P_{num_polys}(x))
+            // at the points {0, ..., |g(x)|}
+            let mut eval_points =
+                arbitrary_round_evals(polys, &comb_func, combined_degree, order, config);
+            if let Some((mask, rho)) = mask {
+                add_mask_evals(&mut eval_points, mask, rho, &r);
+            }
 
             let round_uni_poly = UniPoly::from_evals(&eval_points);
             let round_compressed_poly = round_uni_poly.compress();
@@ -178,9 +191,7 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
             r.push(r_j);
 
             // bound all tables to the verifier's challenege
-            polys
-                .par_iter_mut()
-                .for_each(|poly| poly.bound_poly_var_top(&r_j));
+            polys.par_iter_mut().for_each(|poly| poly.bind(&r_j, order));
             compressed_polys.push(round_compressed_poly);
         }
 
@@ -189,10 +200,204 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         (SumcheckInstanceProof::new(compressed_polys), r, final_evals)
     }
 
+    /// Proves the sum over the hypercube of `eq(tau, x) * (A(x) * B(x) - C(x))`, binding from
+    /// the top, where `A`, `B` and `C` have small integer evaluations (flags, booleans, `u32`
+    /// values), each less than `2^62` in absolute value.
+    ///
+    /// The first `num_small_rounds` rounds follow Dao and Thaler's small-value optimization:
+    /// rather than binding `A`, `B` and `C` to each challenge, they take products of the
+    /// unbound `i128` evaluations, weighted by the challenges' Lagrange basis afterwards. The
+    /// accumulators grow by a factor of 4 every round, so this pays off for 2 or 3 rounds, after
+    /// which the polynomials are bound to the challenges so far and the rounds continue over
+    /// the field.
+    ///
+    /// Returns (SumcheckInstanceProof, r, [A(r), B(r), C(r)])
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Sumcheck.prove_cubic_with_additive_term")
+    )]
+    pub fn prove_cubic_with_additive_term(
+        claim: &F,
+        tau: &[F],
+        az: &[i64],
+        bz: &[i64],
+        cz: &[i64],
+        num_small_rounds: usize,
+        transcript: &mut ProofTranscript,
+    ) -> (Self, Vec<F>, Vec<F>) {
+        let num_rounds = tau.len();
+        assert!(num_small_rounds <= num_rounds);
+        assert!(az.len() == num_rounds.pow2() && bz.len() == az.len() && cz.len() == az.len());
+        debug_assert!(az
+            .iter()
+            .chain(bz.iter())
+            .chain(cz.iter())
+            .all(|value| value.unsigned_abs() < 1 << 62));
+
+        let mut claim = *claim;
+        let mut r: Vec<F> = Vec::with_capacity(num_rounds);
+        let mut compressed_polys: Vec<CompressedUniPoly<F>> = Vec::with_capacity(num_rounds);
+        // eq(tau, r) over the rounds so far
+        let mut eq_prefix = F::one();
+
+        for round in 0..num_small_rounds {
+            let num_prefixes = round.pow2();
+            let num_suffix_bits = num_rounds - round - 1;
+            let eq_suffix = EqPolynomial::evals(&tau[round + 1..]);
+
+            // ab[t][a][b] = sum_x eq(tau, x) * A(a, t, x) * B(b, t, x), and
+            // c[t][a] = sum_x eq(tau, x) * C(a, t, x), for t in {0, 1, ∞}
+            let zeros = || {
+                (
+                    vec![vec![vec![F::zero(); num_prefixes]; num_prefixes]; 3],
+                    vec![vec![F::zero(); num_prefixes]; 2],
+                )
+            };
+            let (ab, c) = (0..num_suffix_bits.pow2())
+                .into_par_iter()
+                .fold(zeros, |(mut ab, mut c), x| {
+                    let at = |values: &[i64], a: usize, t: usize| {
+                        values[(a << (num_suffix_bits + 1)) | (t << num_suffix_bits) | x] as i128
+                    };
+                    let evals = |values: &[i64]| -> Vec<[i128; 3]> {
+                        (0..num_prefixes)
+                            .map(|a| {
+                                let (low, high) = (at(values, a, 0), at(values, a, 1));
+                                [low, high, high - low]
+                            })
+                            .collect()
+                    };
+                    let (a_evals, b_evals) = (evals(az), evals(bz));
+                    let weight = eq_suffix[x];
+                    for t in 0..3 {
+                        for (a, a_eval) in a_evals.iter().enumerate() {
+                            for (b, b_eval) in b_evals.iter().enumerate() {
+                                let product = a_eval[t] * b_eval[t];
+                                if product != 0 {
+                                    ab[t][a][b] += weight * F::from_i128(product);
+                                }
+                            }
+                        }
+                    }
+                    for (t, c_t) in c.iter_mut().enumerate() {
+                        for (a, c_ta) in c_t.iter_mut().enumerate() {
+                            let value = at(cz, a, t);
+                            if value != 0 {
+                                *c_ta += weight * F::from_i128(value);
+                            }
+                        }
+                    }
+                    (ab, c)
+                })
+                .reduce(zeros, |(mut ab, mut c), (other_ab, other_c)| {
+                    for (sum, other) in ab
+                        .iter_mut()
+                        .flatten()
+                        .flatten()
+                        .zip(other_ab.iter().flatten().flatten())
+                    {
+                        *sum += *other;
+                    }
+                    for (sum, other) in c.iter_mut().flatten().zip(other_c.iter().flatten()) {
+                        *sum += *other;
+                    }
+                    (ab, c)
+                });
+
+            // q(t) = sum_x eq(tau, x) * (A * B - C)(r, t, x), at t in {0, 1, ∞}
+            let lagrange = EqPolynomial::evals(&r);
+            let q: Vec<F> = (0..3)
+                .map(|t| {
+                    let ab_t: F = (0..num_prefixes)
+                        .flat_map(|a| (0..num_prefixes).map(move |b| (a, b)))
+                        .map(|(a, b)| lagrange[a] * lagrange[b] * ab[t][a][b])
+                        .sum();
+                    if t == 2 {
+                        ab_t
+                    } else {
+                        let c_t: F = (0..num_prefixes).map(|a| lagrange[a] * c[t][a]).sum();
+                        ab_t - c_t
+                    }
+                })
+                .collect();
+            let (q_0, q_1, q_infinity) = (q[0], q[1], q[2]);
+            let q_linear = q_1 - q_0 - q_infinity;
+
+            // s(t) = eq(tau, (r, t)) * q(t)
+            let tau_j = tau[round];
+            let evals: Vec<F> = (0..4u64)
+                .map(|t| {
+                    let t = F::from_u64(t).unwrap();
+                    let eq_t = eq_prefix * (tau_j * t + (F::one() - tau_j) * (F::one() - t));
+                    eq_t * (q_0 + q_linear * t + q_infinity * t * t)
+                })
+                .collect();
+            debug_assert_eq!(evals[0] + evals[1], claim);
+
+            let round_poly = UniPoly::from_evals(&evals);
+            let compressed_poly = round_poly.compress();
+            compressed_poly.append_to_transcript(transcript);
+            let r_j: F = transcript.challenge_scalar();
+            r.push(r_j);
+            claim = round_poly.evaluate(&r_j);
+            eq_prefix *= tau_j * r_j + (F::one() - tau_j) * (F::one() - r_j);
+            compressed_polys.push(compressed_poly);
+        }
+
+        // Bind the polynomials to the challenges of the small rounds, and continue over the field
+        let lagrange = EqPolynomial::evals(&r);
+        let num_suffix_bits = num_rounds - num_small_rounds;
+        let bind = |values: &[i64]| {
+            DensePolynomial::new(
+                (0..num_suffix_bits.pow2())
+                    .into_par_iter()
+                    .map(|x| {
+                        lagrange
+                            .iter()
+                            .enumerate()
+                            .map(|(a, weight)| {
+                                mul_0_optimized(
+                                    &F::from_i64(values[(a << num_suffix_bits) | x]),
+                                    weight,
+                                )
+                            })
+                            .sum()
+                    })
+                    .collect(),
+            )
+        };
+        let eq_poly = DensePolynomial::new(
+            EqPolynomial::evals(&tau[num_small_rounds..])
+                .into_iter()
+                .map(|eq| eq * eq_prefix)
+                .collect(),
+        );
+        let mut polys = vec![eq_poly, bind(az), bind(bz), bind(cz)];
+        let (proof, r_field, final_evals) = Self::prove_arbitrary(
+            &claim,
+            num_suffix_bits,
+            &mut polys,
+            |vals: &[F]| vals[0] * (vals[1] * vals[2] - vals[3]),
+            3,
+            transcript,
+        );
+        compressed_polys.extend(proof.compressed_polys);
+        r.extend(r_field);
+
+        (
+            SumcheckInstanceProof::new(compressed_polys),
+            r,
+            final_evals[1..].to_vec(),
+        )
+    }
+
     #[inline]
-    #[tracing::instrument(
-        skip_all,
-        name = "Spartan2::sumcheck::compute_eval_points_spartan_cubic"
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            name = "Spartan2::sumcheck::compute_eval_points_spartan_cubic"
+        )
     )]
     /// Binds from the bottom rather than the top.
     pub fn compute_eval_points_spartan_cubic(
@@ -221,7 +426,7 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
 
         // num_threads * 8 enables better work stealing
         let mut iterators =
-            SparseTripleIterator::chunks(poly_A, poly_B, poly_C, rayon::current_num_threads() * 16);
+            SparseTripleIterator::chunks(poly_A, poly_B, poly_C, par::current_num_threads() * 16);
 
         // We use the Dao-Thaler optimization for the EQ polynomial, so there are two cases we
         // must handle. For details, refer to Section 2.2 of https://eprint.iacr.org/2024/1210.pdf
@@ -240,8 +445,8 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
             iterators
                 .par_iter_mut()
                 .map(|iterator| {
-                    let span = tracing::span!(tracing::Level::DEBUG, "eval_par_inner");
-                    let _enter = span.enter();
+                    #[cfg(feature = "tracing")]
+                    let _enter = tracing::span!(tracing::Level::DEBUG, "eval_par_inner").entered();
                     let mut eval_point_0 = F::zero();
                     let mut eval_point_2 = F::zero();
                     let mut eval_point_3 = F::zero();
@@ -306,8 +511,8 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
             iterators
                 .par_iter_mut()
                 .map(|iterator| {
-                    let span = tracing::span!(tracing::Level::DEBUG, "eval_par_inner");
-                    let _enter = span.enter();
+                    #[cfg(feature = "tracing")]
+                    let _enter = tracing::span!(tracing::Level::DEBUG, "eval_par_inner").entered();
                     let mut eval_point_0 = F::zero();
                     let mut eval_point_2 = F::zero();
                     let mut eval_point_3 = F::zero();
@@ -376,7 +581,11 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         }
     }
 
-    #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_spartan_cubic")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_spartan_cubic")
+    )]
+    /// If a `mask` `(g, rho)` is given, `claim` must include `rho` times the sum of `g`.
     pub fn prove_spartan_cubic(
         claim: &F,
         num_rounds: usize,
@@ -384,6 +593,7 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         poly_A: &mut SparsePolynomial<F>,
         poly_B: &mut SparsePolynomial<F>,
         poly_C: &mut SparsePolynomial<F>,
+        mask: Option<(&MaskingPolynomial<F>, F)>,
         transcript: &mut ProofTranscript,
     ) -> (Self, Vec<F>, Vec<F>) {
         let mut r: Vec<F> = Vec::new();
@@ -391,33 +601,15 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         let mut claim_per_round = *claim;
 
         for _ in 0..num_rounds {
-            let poly = {
-                // Make an iterator returning the contributions to the evaluations
-                let (eval_point_0, eval_point_2, eval_point_3) =
-                    Self::compute_eval_points_spartan_cubic(poly_eq, poly_A, poly_B, poly_C);
-
-                let evals = [
-                    eval_point_0,
-                    claim_per_round - eval_point_0,
-                    eval_point_2,
-                    eval_point_3,
-                ];
-
-                UniPoly::from_evals(&evals)
-            };
-
-            let compressed_poly = poly.compress();
-
-            // append the prover's message to the transcript
-            compressed_poly.append_to_transcript(transcript);
-
-            //derive the verifier's challenge for the next round
-            let r_i = transcript.challenge_scalar();
-            r.push(r_i);
-            polys.push(compressed_poly);
-
-            // Set up next round
-            claim_per_round = poly.evaluate(&r_i);
+            let evals = Self::compute_eval_points_spartan_cubic(poly_eq, poly_A, poly_B, poly_C);
+            let r_i = Self::spartan_cubic_round(
+                evals,
+                &mut claim_per_round,
+                mask,
+                &mut r,
+                &mut polys,
+                transcript,
+            );
 
             // bound all tables to the verifier's challenege
             poly_eq.bind(r_i);
@@ -437,17 +629,125 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         )
     }
 
-    #[tracing::instrument(skip_all)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_spartan_cubic_streaming")
+    )]
+    /// The sum-check of `prove_spartan_cubic`, without materializing the unbound `A`, `B` and
+    /// `C`: the evaluations of the first `num_streaming_rounds` rounds are computed by
+    /// `streaming_round` from the challenges so far, after which `materialize` computes the
+    /// polynomials already bound to those challenges. This divides the memory they take by
+    /// `2^num_streaming_rounds`, at the cost of as many extra passes over the rows.
+    ///
+    /// If a `mask` `(g, rho)` is given, `claim` must include `rho` times the sum of `g`.
+    pub fn prove_spartan_cubic_streaming(
+        claim: &F,
+        num_rounds: usize,
+        num_streaming_rounds: usize,
+        poly_eq: &mut SplitEqPolynomial<F>,
+        streaming_round: impl Fn(&SplitEqPolynomial<F>, &[F]) -> (F, F, F),
+        materialize: impl FnOnce(
+            &[F],
+        ) -> (
+            SparsePolynomial<F>,
+            SparsePolynomial<F>,
+            SparsePolynomial<F>,
+        ),
+        mask: Option<(&MaskingPolynomial<F>, F)>,
+        transcript: &mut ProofTranscript,
+    ) -> (Self, Vec<F>, Vec<F>) {
+        assert!(num_streaming_rounds < num_rounds);
+        let mut r: Vec<F> = Vec::with_capacity(num_rounds);
+        let mut polys: Vec<CompressedUniPoly<F>> = Vec::with_capacity(num_rounds);
+        let mut claim_per_round = *claim;
+
+        for _ in 0..num_streaming_rounds {
+            let evals = streaming_round(poly_eq, &r);
+            let r_i = Self::spartan_cubic_round(
+                evals,
+                &mut claim_per_round,
+                mask,
+                &mut r,
+                &mut polys,
+                transcript,
+            );
+            poly_eq.bind(r_i);
+        }
+
+        let (mut poly_A, mut poly_B, mut poly_C) = materialize(&r);
+        for _ in num_streaming_rounds..num_rounds {
+            let evals = Self::compute_eval_points_spartan_cubic(poly_eq, &poly_A, &poly_B, &poly_C);
+            let r_i = Self::spartan_cubic_round(
+                evals,
+                &mut claim_per_round,
+                mask,
+                &mut r,
+                &mut polys,
+                transcript,
+            );
+
+            poly_eq.bind(r_i);
+            poly_A.bound_poly_var_bot_par(&r_i);
+            poly_B.bound_poly_var_bot_par(&r_i);
+            poly_C.bound_poly_var_bot_par(&r_i);
+        }
+
+        let final_evals = vec![
+            poly_A.final_eval(),
+            poly_B.final_eval(),
+            poly_C.final_eval(),
+        ];
+        drop_in_background_thread((poly_A, poly_B, poly_C));
+        (SumcheckInstanceProof::new(polys), r, final_evals)
+    }
+
+    /// Completes a round of the cubic Spartan sum-check given the evaluations of its
+    /// polynomial at 0, 2 and 3: masks them, appends the polynomial to the transcript, and
+    /// returns the verifier's challenge, which is also pushed to `r`.
+    fn spartan_cubic_round(
+        (eval_point_0, eval_point_2, eval_point_3): (F, F, F),
+        claim_per_round: &mut F,
+        mask: Option<(&MaskingPolynomial<F>, F)>,
+        r: &mut Vec<F>,
+        polys: &mut Vec<CompressedUniPoly<F>>,
+        transcript: &mut ProofTranscript,
+    ) -> F {
+        let mut evals = [eval_point_0, F::zero(), eval_point_2, eval_point_3];
+        if let Some((mask, rho)) = mask {
+            add_mask_evals(&mut evals, mask, rho, r);
+        }
+        evals[1] = *claim_per_round - evals[0];
+        let poly = UniPoly::from_evals(&evals);
+
+        let compressed_poly = poly.compress();
+
+        // append the prover's message to the transcript
+        compressed_poly.append_to_transcript(transcript);
+
+        //derive the verifier's challenge for the next round
+        let r_i = transcript.challenge_scalar();
+        r.push(r_i);
+        polys.push(compressed_poly);
+
+        // Set up next round
+        *claim_per_round = poly.evaluate(&r_i);
+        r_i
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     // A specialized sumcheck implementation with the 0th round unrolled from the rest of the
     // `for` loop. This allows us to pass in `witness_polynomials` by reference instead of
     // passing them in as a single `DensePolynomial`, which would require an expensive
     // concatenation. We defer the actual instantation of a `DensePolynomial` to the end of the
     // 0th round.
+    //
+    // If a `mask` `(g, rho)` is given, `claim` must include `rho` times the sum of `g`.
     pub fn prove_spartan_quadratic(
         claim: &F,
         num_rounds: usize,
         poly_A: &mut DensePolynomial<F>,
         witness_polynomials: &[&DensePolynomial<F>],
+        mask: Option<(&MaskingPolynomial<F>, F)>,
         transcript: &mut ProofTranscript,
     ) -> (Self, Vec<F>, Vec<F>) {
         let mut r: Vec<F> = Vec::with_capacity(num_rounds);
@@ -504,7 +804,11 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
                 &(F::from_u64(2).unwrap() - witness_value(0)),
             );
 
-            let evals = [eval_point_0, claim_per_round - eval_point_0, eval_point_2];
+            let mut evals = [eval_point_0, F::zero(), eval_point_2];
+            if let Some((mask, rho)) = mask {
+                add_mask_evals(&mut evals, mask, rho, &r);
+            }
+            evals[1] = claim_per_round - evals[0];
             UniPoly::from_evals(&evals)
         };
 
@@ -521,7 +825,7 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         claim_per_round = poly.evaluate(&r_i);
 
         // bound all tables to the verifier's challenge
-        let (_, mut poly_B) = rayon::join(
+        let (_, mut poly_B) = par::join(
             || poly_A.bound_poly_var_top_zero_optimized(&r_i),
             || {
                 // Simulates `poly_B.bound_poly_var_top(&r_i)` by
@@ -533,7 +837,7 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
                 let W_iter = (0..len).into_par_iter().map(witness_value);
                 let Z_iter = W_iter
                     .chain(one.into_par_iter())
-                    .chain(rayon::iter::repeatn(zero, len));
+                    .chain(par::iter::repeatn(zero, len));
                 let left_iter = Z_iter.clone().take(len);
                 let right_iter = Z_iter.skip(len).take(len);
                 let B = left_iter
@@ -551,7 +855,11 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
                 let (eval_point_0, eval_point_2) =
                     Self::compute_eval_points_spartan_quadratic(poly_A, &poly_B);
 
-                let evals = [eval_point_0, claim_per_round - eval_point_0, eval_point_2];
+                let mut evals = [eval_point_0, F::zero(), eval_point_2];
+                if let Some((mask, rho)) = mask {
+                    add_mask_evals(&mut evals, mask, rho, &r);
+                }
+                evals[1] = claim_per_round - evals[0];
                 UniPoly::from_evals(&evals)
             };
 
@@ -569,20 +877,23 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
             claim_per_round = poly.evaluate(&r_i);
 
             // bound all tables to the verifier's challenge
-            rayon::join(
+            par::join(
                 || poly_A.bound_poly_var_top_zero_optimized(&r_i),
                 || poly_B.bound_poly_var_top_zero_optimized(&r_i),
             );
         }
 
         let evals = vec![poly_A[0], poly_B[0]];
-        drop_in_background_thread(poly_B);
+        poly_B.recycle();
 
         (SumcheckInstanceProof::new(polys), r, evals)
     }
 
     #[inline]
-    #[tracing::instrument(skip_all, name = "Sumcheck::compute_eval_points_spartan_quadratic")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "Sumcheck::compute_eval_points_spartan_quadratic")
+    )]
     pub fn compute_eval_points_spartan_quadratic(
         poly_A: &DensePolynomial<F>,
         poly_B: &DensePolynomial<F>,
@@ -613,12 +924,107 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
     }
 }
 
+/// Tuning of the parallelism of each sum-check round's evaluations.
+#[derive(Clone, Copy, Debug)]
+pub struct SumcheckConfig {
+    /// Rounds with fewer evaluation pairs than this are computed on the current thread
+    pub min_par_len: usize,
+    /// Number of evaluation pairs in each parallel task
+    pub chunk_size: usize,
+}
+
+impl Default for SumcheckConfig {
+    fn default() -> Self {
+        Self {
+            min_par_len: 1 << 10,
+            chunk_size: 1 << 10,
+        }
+    }
+}
+
+/// Evaluates `sum_i comb_func(P_0(x, i), ..., P_k(x, i))` at `x = 0, ..., degree`, for the
+/// variable of `polys` bound next in `order`.
+pub(crate) fn arbitrary_round_evals<F, Func>(
+    polys: &[DensePolynomial<F>],
+    comb_func: &Func,
+    degree: usize,
+    order: BindingOrder,
+    config: &SumcheckConfig,
+) -> Vec<F>
+where
+    F: JoltField,
+    Func: Fn(&[F]) -> F + Sync,
+{
+    let mle_half = polys[0].len() / 2;
+
+    // D_n(index, r) = D_{n-1}[LOW] + r * (D_{n-1}[HIGH] - D_{n-1}[LOW]), so each evaluation
+    // point after the first is the previous one plus (D_{n-1}[HIGH] - D_{n-1}[LOW])
+    let accumulate = |range: std::ops::Range<usize>| {
+        let mut accum = vec![F::zero(); degree + 1];
+        let mut lows = vec![F::zero(); polys.len()];
+        let mut evals = vec![F::zero(); polys.len()];
+        let mut diffs = vec![F::zero(); polys.len()];
+        for poly_term_i in range {
+            for (i, poly) in polys.iter().enumerate() {
+                (lows[i], evals[i]) = poly.sumcheck_evals_pair(poly_term_i, order);
+                diffs[i] = evals[i] - lows[i];
+            }
+            accum[0] += comb_func(&lows);
+            accum[1] += comb_func(&evals);
+            for accum_i in accum.iter_mut().skip(2) {
+                for (eval, diff) in evals.iter_mut().zip(diffs.iter()) {
+                    *eval += *diff;
+                }
+                *accum_i += comb_func(&evals);
+            }
+        }
+        accum
+    };
+
+    if mle_half < config.min_par_len {
+        return accumulate(0..mle_half);
+    }
+    let chunk_size = config.chunk_size.max(1);
+    (0..mle_half.div_ceil(chunk_size))
+        .into_par_iter()
+        .map(|chunk| accumulate(chunk * chunk_size..mle_half.min((chunk + 1) * chunk_size)))
+        .reduce(
+            || vec![F::zero(); degree + 1],
+            |mut running, chunk| {
+                for (running, chunk) in running.iter_mut().zip(chunk.iter()) {
+                    *running += *chunk;
+                }
+                running
+            },
+        )
+}
+
+/// Adds `rho` times the evaluations of the masking polynomial's round polynomial, for the round
+/// following those bound to `r`, to the evaluations of a round polynomial at `0, 1, ...`.
+fn add_mask_evals<F: JoltField>(evals: &mut [F], mask: &MaskingPolynomial<F>, rho: F, r: &[F]) {
+    assert_eq!(mask.degree() + 1, evals.len());
+    for (eval, mask_eval) in evals.iter_mut().zip(mask.round_evals(r)) {
+        *eval += rho * mask_eval;
+    }
+}
+
 #[derive(CanonicalSerialize, CanonicalDeserialize, Debug)]
 pub struct SumcheckInstanceProof<F: JoltField, ProofTranscript: Transcript> {
     pub compressed_polys: Vec<CompressedUniPoly<F>>,
     _marker: PhantomData<ProofTranscript>,
 }
 
+impl<F: JoltField, ProofTranscript: Transcript> ToFieldElements<F>
+    for SumcheckInstanceProof<F, ProofTranscript>
+{
+    fn append_field_elements(&self, out: &mut Vec<F>) {
+        append_len(self.compressed_polys.len(), out);
+        for poly in self.compressed_polys.iter() {
+            poly.append_field_elements(out);
+        }
+    }
+}
+
 impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTranscript> {
     pub fn new(
         compressed_polys: Vec<CompressedUniPoly<F>>,
@@ -649,31 +1055,199 @@ impl<F: JoltField, ProofTranscript: Transcript> SumcheckInstanceProof<F, ProofTr
         degree_bound: usize,
         transcript: &mut ProofTranscript,
     ) -> Result<(F, Vec<F>), ProofVerifyError> {
-        let mut e = claim;
-        let mut r: Vec<F> = Vec::new();
+        crate::verifier::sumcheck::verify(
+            &self.compressed_polys,
+            claim,
+            num_rounds,
+            degree_bound,
+            transcript,
+        )
+    }
+}
 
-        // verify that there is a univariate polynomial for each round
-        assert_eq!(self.compressed_polys.len(), num_rounds);
-        for i in 0..self.compressed_polys.len() {
-            // verify degree bound
-            if self.compressed_polys[i].degree() != degree_bound {
-                return Err(ProofVerifyError::InvalidInputLength(
-                    degree_bound,
-                    self.compressed_polys[i].degree(),
-                ));
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::{RngCore, SeedableRng};
+
+    #[test]
+    fn prove_arbitrary_binding_orders() {
+        const NUM_VARS: usize = 4;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let polys = vec![
+            DensePolynomial::<Fr>::random(NUM_VARS, &mut rng),
+            DensePolynomial::<Fr>::random(NUM_VARS, &mut rng),
+        ];
+        let claim: Fr = polys[0]
+            .evals_ref()
+            .iter()
+            .zip(polys[1].evals_ref().iter())
+            .map(|(a, b)| *a * b)
+            .sum();
+
+        for order in [BindingOrder::HighToLow, BindingOrder::LowToHigh] {
+            let mut prover_transcript = KeccakTranscript::new(b"test");
+            let (proof, r_prover, final_evals) = SumcheckInstanceProof::prove_arbitrary_with_order(
+                &claim,
+                NUM_VARS,
+                &mut polys.clone(),
+                |vals: &[Fr]| vals[0] * vals[1],
+                2,
+                order,
+                None,
+                &mut prover_transcript,
+            );
 
-            // append the prover's message to the transcript
-            self.compressed_polys[i].append_to_transcript(transcript);
+            let mut verifier_transcript = KeccakTranscript::new(b"test");
+            let (e, r) = proof
+                .verify(claim, NUM_VARS, 2, &mut verifier_transcript)
+                .unwrap();
+            assert_eq!(r, r_prover);
+            assert_eq!(e, final_evals[0] * final_evals[1]);
+            for (poly, eval) in polys.iter().zip(final_evals.iter()) {
+                assert_eq!(poly.evaluate_with_order(&r, order), *eval);
+            }
+        }
+    }
 
-            //derive the verifier's challenge for the next round
-            let r_i = transcript.challenge_scalar();
-            r.push(r_i);
+    #[test]
+    fn prove_arbitrary_chunking() {
+        const NUM_VARS: usize = 6;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let polys: Vec<DensePolynomial<Fr>> = (0..3)
+            .map(|_| DensePolynomial::random(NUM_VARS, &mut rng))
+            .collect();
+        let claim: Fr = (0..NUM_VARS.pow2())
+            .map(|i| polys[0][i] * polys[1][i] * polys[2][i])
+            .sum();
+
+        let mut r_sequential = None;
+        for (min_par_len, chunk_size) in [(usize::MAX, 1), (0, 1), (0, 3), (4, 64)] {
+            let config = SumcheckConfig {
+                min_par_len,
+                chunk_size,
+            };
+            let mut prover_transcript = KeccakTranscript::new(b"test");
+            let (proof, r_prover, _) = SumcheckInstanceProof::prove_arbitrary_with_config(
+                &claim,
+                NUM_VARS,
+                &mut polys.clone(),
+                |vals: &[Fr]| vals[0] * vals[1] * vals[2],
+                3,
+                BindingOrder::LowToHigh,
+                None,
+                &config,
+                &mut prover_transcript,
+            );
 
-            // evaluate the claimed degree-ell polynomial at r_i using the hint
-            e = self.compressed_polys[i].eval_from_hint(&e, &r_i);
+            let mut verifier_transcript = KeccakTranscript::new(b"test");
+            let (_, r) = proof
+                .verify(claim, NUM_VARS, 3, &mut verifier_transcript)
+                .unwrap();
+            assert_eq!(r, r_prover);
+            match &r_sequential {
+                None => r_sequential = Some(r),
+                Some(r_sequential) => assert_eq!(&r, r_sequential),
+            }
         }
+    }
+
+    #[test]
+    fn prove_arbitrary_masked() {
+        const NUM_VARS: usize = 4;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let polys = vec![
+            DensePolynomial::<Fr>::random(NUM_VARS, &mut rng),
+            DensePolynomial::<Fr>::random(NUM_VARS, &mut rng),
+        ];
+        let claim: Fr = polys[0]
+            .evals_ref()
+            .iter()
+            .zip(polys[1].evals_ref().iter())
+            .map(|(a, b)| *a * b)
+            .sum();
+        let mask = MaskingPolynomial::random(NUM_VARS, 2, &mut rng);
+        let rho = Fr::from(7u64);
+        let masked_claim = claim + rho * mask.sum();
+
+        let mut prover_transcript = KeccakTranscript::new(b"test");
+        let (proof, r_prover, final_evals) = SumcheckInstanceProof::prove_arbitrary_with_order(
+            &masked_claim,
+            NUM_VARS,
+            &mut polys.clone(),
+            |vals: &[Fr]| vals[0] * vals[1],
+            2,
+            BindingOrder::LowToHigh,
+            Some((&mask, rho)),
+            &mut prover_transcript,
+        );
 
-        Ok((e, r))
+        let mut verifier_transcript = KeccakTranscript::new(b"test");
+        let (e, r) = proof
+            .verify(masked_claim, NUM_VARS, 2, &mut verifier_transcript)
+            .unwrap();
+        assert_eq!(r, r_prover);
+        let mask_eval: Fr = mask.evaluate_univariates(&r).iter().sum();
+        assert_eq!(e, final_evals[0] * final_evals[1] + rho * mask_eval);
+    }
+
+    #[test]
+    fn cubic_with_additive_term_small_rounds() {
+        const NUM_VARS: usize = 6;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let mut small_values = || -> Vec<i64> {
+            (0..NUM_VARS.pow2())
+                .map(|_| rng.next_u32() as i64 - (1 << 31))
+                .collect()
+        };
+        let (az, bz, cz) = (small_values(), small_values(), small_values());
+        let tau: Vec<Fr> = (0..NUM_VARS).map(|_| Fr::random(&mut rng)).collect();
+        let eq_evals = EqPolynomial::evals(&tau);
+        let claim: Fr = (0..NUM_VARS.pow2())
+            .map(|i| {
+                eq_evals[i] * (Fr::from_i64(az[i]) * Fr::from_i64(bz[i]) - Fr::from_i64(cz[i]))
+            })
+            .sum();
+        let to_poly = |values: &[i64]| {
+            DensePolynomial::new(values.iter().map(|value| Fr::from_i64(*value)).collect())
+        };
+
+        let mut r_field_only = None;
+        for num_small_rounds in [0, 1, 3, NUM_VARS] {
+            let mut prover_transcript = KeccakTranscript::new(b"test");
+            let (proof, r_prover, final_evals) =
+                SumcheckInstanceProof::prove_cubic_with_additive_term(
+                    &claim,
+                    &tau,
+                    &az,
+                    &bz,
+                    &cz,
+                    num_small_rounds,
+                    &mut prover_transcript,
+                );
+
+            let mut verifier_transcript = KeccakTranscript::new(b"test");
+            let (e, r) = proof
+                .verify(claim, NUM_VARS, 3, &mut verifier_transcript)
+                .unwrap();
+            assert_eq!(r, r_prover);
+            for (values, eval) in [&az, &bz, &cz].into_iter().zip(final_evals.iter()) {
+                assert_eq!(to_poly(values).evaluate(&r), *eval);
+            }
+            assert_eq!(
+                e,
+                EqPolynomial::new(tau.clone()).evaluate(&r)
+                    * (final_evals[0] * final_evals[1] - final_evals[2])
+            );
+
+            // The round polynomials, and so the challenges, don't depend on the small rounds
+            match &r_field_only {
+                None => r_field_only = Some(r),
+                Some(r_field_only) => assert_eq!(&r, r_field_only),
+            }
+        }
     }
 }
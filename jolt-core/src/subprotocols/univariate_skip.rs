@@ -0,0 +1,356 @@
+//! The univariate skip for zero-check sum-checks of the form
+//! `sum_x eq(tau, x) * (A(x) * B(x) - C(x)) = claim`, as in Spartan's outer sum-check.
+//!
+//! The lowest `k` variables are replaced by a single variable `Y` over the domain
+//! `D = {0, ..., 2^k - 1}`, with `A(Y, x')` the univariate extension of `A(y, x')` in `Y`. The
+//! first `k` rounds then become a single round: the prover sends
+//! `q(Y) = sum_x' eq(tau', x') * (A * B - C)(Y, x')`, of degree `2 * (2^k - 1)`, and the
+//! verifier checks `sum_{y in D} L_y(tau_0) * q(y) = claim`, where `L_y` is the Lagrange basis of
+//! `D` and `tau_0` takes the place of the `k` skipped entries of `tau`. The remaining rounds are
+//! an ordinary cubic sum-check, with `A`, `B` and `C` bound to the univariate challenge `r_0`.
+//!
+//! The polynomials' final claims are then at `(r_0, r')`, where the skipped variables are
+//! weighted by `L_y(r_0)` rather than `eq`, so an opening of a committed multilinear polynomial
+//! at that point has to go through the Lagrange weights. Spartan's outer sum-check only needs
+//! its claims for the inner sum-check, which weights the rows of A, B and C the same way (see
+//! `r1cs::key::StepPoint`).
+use crate::field::JoltField;
+use crate::poly::dense_mlpoly::{BindingOrder, DensePolynomial};
+use crate::poly::eq_poly::EqPolynomial;
+use crate::poly::unipoly::UniPoly;
+use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::math::Math;
+use crate::utils::par::prelude::*;
+use crate::utils::transcript::Transcript;
+use ark_serialize::*;
+
+/// The evaluations at `x` of the Lagrange basis of the domain `{0, ..., domain_size - 1}`.
+pub fn lagrange_evals<F: JoltField>(domain_size: usize, x: F) -> Vec<F> {
+    let points: Vec<F> = (0..domain_size)
+        .map(|i| F::from_u64(i as u64).unwrap())
+        .collect();
+    if let Some(i) = points.iter().position(|point| *point == x) {
+        let mut evals = vec![F::zero(); domain_size];
+        evals[i] = F::one();
+        return evals;
+    }
+
+    // L_i(x) = prod_{j != i} (x - j) / (i - j), with the numerators as the full product divided
+    // by (x - i), and the denominators as (-1)^(n - 1 - i) * i! * (n - 1 - i)!
+    let numerator: F = points.iter().map(|point| x - *point).product();
+    let mut factorials = vec![F::one(); domain_size];
+    for i in 1..domain_size {
+        factorials[i] = factorials[i - 1] * points[i];
+    }
+    (0..domain_size)
+        .map(|i| {
+            let mut denominator = factorials[i] * factorials[domain_size - 1 - i] * (x - points[i]);
+            if (domain_size - 1 - i) % 2 == 1 {
+                denominator = -denominator;
+            }
+            numerator * denominator.inverse().unwrap()
+        })
+        .collect()
+}
+
+/// `sum_{y in D} L_y(x) * L_y(y')`, the analogue of `eq(x, y')` for the domain `D` of size
+/// `domain_size`.
+pub fn lagrange_kernel<F: JoltField>(domain_size: usize, x: F, y: F) -> F {
+    lagrange_evals(domain_size, x)
+        .into_iter()
+        .zip(lagrange_evals(domain_size, y))
+        .map(|(x, y)| x * y)
+        .sum()
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug)]
+pub struct UnivariateSkipProof<F: JoltField, ProofTranscript: Transcript> {
+    /// Coefficients of `q(Y)`, the round polynomial of the skipped variables without its
+    /// `L(tau_0, Y)` factor
+    pub first_round_coeffs: Vec<F>,
+    /// The sum-check over the remaining variables
+    pub remaining_rounds: SumcheckInstanceProof<F, ProofTranscript>,
+}
+
+impl<F: JoltField, ProofTranscript: Transcript> UnivariateSkipProof<F, ProofTranscript> {
+    /// Proves `sum_x eq((tau_0, tau'), x) * (A(x) * B(x) - C(x)) = claim`, where the lowest
+    /// `num_skipped_vars` variables of `x` are skipped as a single univariate round.
+    ///
+    /// Returns (UnivariateSkipProof, r_0, r', [A(r_0, r'), B(r_0, r'), C(r_0, r')]), with `r'`
+    /// in round order, binding `x'` from its lowest variable.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "UnivariateSkipProof::prove")
+    )]
+    pub fn prove(
+        claim: &F,
+        num_skipped_vars: usize,
+        tau_0: F,
+        tau_rest: &[F],
+        az: &[F],
+        bz: &[F],
+        cz: &[F],
+        transcript: &mut ProofTranscript,
+    ) -> (Self, F, Vec<F>, Vec<F>) {
+        let domain_size = num_skipped_vars.pow2();
+        let num_rest = tau_rest.len().pow2();
+        assert!(az.len() == domain_size * num_rest && bz.len() == az.len() && cz.len() == az.len());
+
+        // q(Y) has degree 2 * (|D| - 1), so is determined by its evaluations at 0..=2 * (|D| - 1);
+        // those past the domain need the Lagrange basis at that point
+        let num_points = 2 * (domain_size - 1) + 1;
+        let extended_basis: Vec<Vec<F>> = (domain_size..num_points)
+            .map(|point| lagrange_evals(domain_size, F::from_u64(point as u64).unwrap()))
+            .collect();
+        let eq_rest = EqPolynomial::evals(tau_rest);
+        let q_evals: Vec<F> = (0..num_rest)
+            .into_par_iter()
+            .map(|x| {
+                let block = x * domain_size..(x + 1) * domain_size;
+                let (a, b, c) = (&az[block.clone()], &bz[block.clone()], &cz[block]);
+                let extend = |values: &[F], basis: &[F]| -> F {
+                    values.iter().zip(basis.iter()).map(|(v, l)| *v * l).sum()
+                };
+                let mut evals: Vec<F> = (0..domain_size).map(|y| a[y] * b[y] - c[y]).collect();
+                evals.extend(
+                    extended_basis
+                        .iter()
+                        .map(|basis| extend(a, basis) * extend(b, basis) - extend(c, basis)),
+                );
+                evals.iter_mut().for_each(|eval| *eval *= eq_rest[x]);
+                evals
+            })
+            .reduce(
+                || vec![F::zero(); num_points],
+                |mut running, evals| {
+                    for (running, eval) in running.iter_mut().zip(evals.iter()) {
+                        *running += *eval;
+                    }
+                    running
+                },
+            );
+        debug_assert_eq!(
+            lagrange_evals(domain_size, tau_0)
+                .iter()
+                .zip(q_evals.iter())
+                .map(|(l, q)| *l * q)
+                .sum::<F>(),
+            *claim
+        );
+        let first_round = UniPoly::from_evals(&q_evals);
+
+        transcript.append_scalars(&first_round.coeffs);
+        let r_0: F = transcript.challenge_scalar();
+        let eq_scale = lagrange_kernel(domain_size, tau_0, r_0);
+        let next_claim = eq_scale * first_round.evaluate(&r_0);
+
+        // Bind the skipped variables to r_0, and continue with the remaining variables
+        let basis = lagrange_evals(domain_size, r_0);
+        let bind = |values: &[F]| {
+            DensePolynomial::new(
+                values
+                    .par_chunks(domain_size)
+                    .map(|block| block.iter().zip(basis.iter()).map(|(v, l)| *v * l).sum())
+                    .collect(),
+            )
+        };
+        let eq_poly = DensePolynomial::new(eq_rest.into_iter().map(|eq| eq * eq_scale).collect());
+        let mut polys = vec![eq_poly, bind(az), bind(bz), bind(cz)];
+        let (remaining_rounds, r_rest, final_evals) =
+            SumcheckInstanceProof::prove_arbitrary_with_order(
+                &next_claim,
+                tau_rest.len(),
+                &mut polys,
+                |vals: &[F]| vals[0] * (vals[1] * vals[2] - vals[3]),
+                3,
+                BindingOrder::LowToHigh,
+                None,
+                transcript,
+            );
+
+        (
+            Self {
+                first_round_coeffs: first_round.coeffs,
+                remaining_rounds,
+            },
+            r_0,
+            r_rest,
+            final_evals[1..].to_vec(),
+        )
+    }
+
+    /// Verifies the skipped round and the remaining `num_remaining_rounds` cubic rounds.
+    ///
+    /// Returns (e, r_0, r'), where the caller must check
+    /// `e = L(tau_0, r_0) * eq(tau', r') * (A * B - C)(r_0, r')`. `r'` is in round order, so
+    /// lowest variable first: reversed against `tau'`.
+    pub fn verify(
+        &self,
+        claim: F,
+        num_skipped_vars: usize,
+        tau_0: F,
+        num_remaining_rounds: usize,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(F, F, Vec<F>), ProofVerifyError> {
+        let domain_size = num_skipped_vars.pow2();
+        let num_coeffs = 2 * (domain_size - 1) + 1;
+        if self.first_round_coeffs.len() != num_coeffs {
+            return Err(ProofVerifyError::InvalidInputLength(
+                num_coeffs,
+                self.first_round_coeffs.len(),
+            ));
+        }
+
+        let first_round = UniPoly::from_coeff(self.first_round_coeffs.clone());
+        let domain_sum: F = lagrange_evals(domain_size, tau_0)
+            .into_iter()
+            .enumerate()
+            .map(|(y, l)| l * first_round.evaluate(&F::from_u64(y as u64).unwrap()))
+            .sum();
+        if domain_sum != claim {
+            return Err(ProofVerifyError::InternalError);
+        }
+
+        transcript.append_scalars(&self.first_round_coeffs);
+        let r_0: F = transcript.challenge_scalar();
+        let next_claim = lagrange_kernel(domain_size, tau_0, r_0) * first_round.evaluate(&r_0);
+
+        let (e, r_rest) =
+            self.remaining_rounds
+                .verify(next_claim, num_remaining_rounds, 3, transcript)?;
+        Ok((e, r_0, r_rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn lagrange_basis() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let x = Fr::random(&mut rng);
+        let evals = lagrange_evals(4, x);
+        // The basis sums to 1, and interpolates x itself
+        assert_eq!(evals.iter().sum::<Fr>(), Fr::from(1u64));
+        let interpolated: Fr = evals
+            .iter()
+            .enumerate()
+            .map(|(i, l)| *l * Fr::from(i as u64))
+            .sum();
+        assert_eq!(interpolated, x);
+        assert_eq!(lagrange_evals(4, Fr::from(2u64))[2], Fr::from(1u64));
+    }
+
+    #[test]
+    fn skip_prove_verify() {
+        const NUM_SKIPPED_VARS: usize = 2;
+        const NUM_REST: usize = 3;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let len = 1 << (NUM_SKIPPED_VARS + NUM_REST);
+        // A satisfying witness, A * B = C
+        let az: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+        let bz: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+        let cz: Vec<Fr> = az.iter().zip(bz.iter()).map(|(a, b)| *a * b).collect();
+        let tau_0 = Fr::random(&mut rng);
+        let tau_rest: Vec<Fr> = (0..NUM_REST).map(|_| Fr::random(&mut rng)).collect();
+
+        let mut prover_transcript = KeccakTranscript::new(b"test");
+        let (proof, r_0_prover, r_rest_prover, final_evals) = UnivariateSkipProof::prove(
+            &Fr::from(0u64),
+            NUM_SKIPPED_VARS,
+            tau_0,
+            &tau_rest,
+            &az,
+            &bz,
+            &cz,
+            &mut prover_transcript,
+        );
+
+        let mut verifier_transcript = KeccakTranscript::new(b"test");
+        let (e, r_0, r_rest) = proof
+            .verify(
+                Fr::from(0u64),
+                NUM_SKIPPED_VARS,
+                tau_0,
+                NUM_REST,
+                &mut verifier_transcript,
+            )
+            .unwrap();
+        assert_eq!((r_0, &r_rest), (r_0_prover, &r_rest_prover));
+
+        // The final claims are the polynomials at (r_0, r'), with the skipped variables
+        // weighted by the Lagrange basis
+        let basis = lagrange_evals(1 << NUM_SKIPPED_VARS, r_0);
+        let r_rest_msb_first: Vec<Fr> = r_rest.iter().rev().copied().collect();
+        for (values, eval) in [&az, &bz, &cz].into_iter().zip(final_evals.iter()) {
+            let bound = DensePolynomial::new(
+                values
+                    .chunks(basis.len())
+                    .map(|block| block.iter().zip(basis.iter()).map(|(v, l)| *v * l).sum())
+                    .collect(),
+            );
+            assert_eq!(bound.evaluate(&r_rest_msb_first), *eval);
+        }
+        let expected = lagrange_kernel(1 << NUM_SKIPPED_VARS, tau_0, r_0)
+            * EqPolynomial::new(tau_rest.clone()).evaluate(&r_rest_msb_first)
+            * (final_evals[0] * final_evals[1] - final_evals[2]);
+        assert_eq!(e, expected);
+    }
+
+    #[test]
+    fn skip_rejects_unsatisfied() {
+        const NUM_SKIPPED_VARS: usize = 2;
+        const NUM_REST: usize = 2;
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let len = 1 << (NUM_SKIPPED_VARS + NUM_REST);
+        let az: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+        let bz: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+        let mut cz: Vec<Fr> = az.iter().zip(bz.iter()).map(|(a, b)| *a * b).collect();
+        cz[5] += Fr::from(1u64);
+        let tau_0 = Fr::random(&mut rng);
+        let tau_rest: Vec<Fr> = (0..NUM_REST).map(|_| Fr::random(&mut rng)).collect();
+
+        // The honest sum is no longer zero, so a proof of it fails against a claim of zero
+        let eq_rest = EqPolynomial::evals(&tau_rest);
+        let claim: Fr = lagrange_evals(1 << NUM_SKIPPED_VARS, tau_0)
+            .iter()
+            .enumerate()
+            .map(|(y, l)| {
+                *l * (0..1 << NUM_REST)
+                    .map(|x| {
+                        let i = (x << NUM_SKIPPED_VARS) | y;
+                        eq_rest[x] * (az[i] * bz[i] - cz[i])
+                    })
+                    .sum::<Fr>()
+            })
+            .sum();
+        assert_ne!(claim, Fr::from(0u64));
+        let mut prover_transcript = KeccakTranscript::new(b"test");
+        let (proof, ..) = UnivariateSkipProof::prove(
+            &claim,
+            NUM_SKIPPED_VARS,
+            tau_0,
+            &tau_rest,
+            &az,
+            &bz,
+            &cz,
+            &mut prover_transcript,
+        );
+        let mut verifier_transcript = KeccakTranscript::new(b"test");
+        assert!(proof
+            .verify(
+                Fr::from(0u64),
+                NUM_SKIPPED_VARS,
+                tau_0,
+                NUM_REST,
+                &mut verifier_transcript,
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Generators and checks for property-based tests of the prover and verifier, built on
+//! [proptest](https://docs.rs/proptest). Downstream crates can use them with the `test-utils`
+//! feature, e.g. to check that their own proofs reject tampering:
+//!
+//! ```ignore
+//! let bytes = proof.serialize_to_bytes()?;
+//! assert_byte_flips_rejected(&bytes, 32, |bytes| {
+//!     JoltHyperKZGProof::deserialize_from_bytes(bytes).is_ok_and(|proof| {
+//!         RV32IJoltVM::verify(preprocessing.clone(), proof.proof, proof.commitments, None).is_ok()
+//!     })
+//! });
+//! ```
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestRunner};
+
+use crate::field::JoltField;
+use crate::poly::dense_mlpoly::DensePolynomial;
+use crate::r1cs::key::{SparseConstraints, UniformR1CS};
+
+/// An arbitrary field element: either a small integer, as most of Jolt's witness values are,
+/// or a uniformly random element.
+pub fn arb_field<F: JoltField>() -> impl Strategy<Value = F> {
+    prop_oneof![
+        any::<u32>().prop_map(|n| F::from_u64(n as u64).unwrap()),
+        prop::collection::vec(any::<u8>(), F::NUM_BYTES).prop_map(|bytes| F::from_bytes(&bytes)),
+    ]
+}
+
+/// An arbitrary multilinear polynomial, in a number of variables drawn from `num_vars`.
+pub fn arb_dense_polynomial<F: JoltField>(
+    num_vars: impl Strategy<Value = usize>,
+) -> impl Strategy<Value = DensePolynomial<F>> {
+    num_vars
+        .prop_flat_map(|num_vars| prop::collection::vec(arb_field::<F>(), 1 << num_vars))
+        .prop_map(DensePolynomial::new)
+}
+
+/// A (single-step) uniform R1CS instance and an assignment `z` to its variables.
+#[derive(Debug)]
+pub struct R1CSInstance<F: JoltField> {
+    pub r1cs: UniformR1CS<F>,
+    pub z: Vec<F>,
+}
+
+impl<F: JoltField> R1CSInstance<F> {
+    /// Whether `(A z) ∘ (B z) = C z`.
+    pub fn is_satisfied(&self) -> bool {
+        let num_rows = self.r1cs.num_rows;
+        let az = self.r1cs.a.multiply_vec(num_rows, &self.z);
+        let bz = self.r1cs.b.multiply_vec(num_rows, &self.z);
+        let cz = self.r1cs.c.multiply_vec(num_rows, &self.z);
+        az.iter()
+            .zip(bz.iter())
+            .zip(cz.iter())
+            .all(|((a, b), c)| *a * b == *c)
+    }
+}
+
+/// An arbitrary linear combination of `num_inputs` variables, with an optional constant term.
+fn arb_lc<F: JoltField>(num_inputs: usize) -> impl Strategy<Value = (Vec<(usize, F)>, Option<F>)> {
+    (
+        prop::collection::vec((0..num_inputs, arb_field::<F>()), 0..=num_inputs),
+        prop::option::of(arb_field::<F>()),
+    )
+}
+
+/// An arbitrary R1CS instance with up to `max_inputs` free variables and `max_constraints`
+/// constraints, and a satisfying assignment. Each constraint multiplies two arbitrary linear
+/// combinations of the free variables into its own output variable; `z` holds the free
+/// variables followed by the outputs.
+pub fn arb_r1cs<F: JoltField>(
+    max_inputs: usize,
+    max_constraints: usize,
+) -> impl Strategy<Value = R1CSInstance<F>> {
+    (1..=max_inputs, 1..=max_constraints)
+        .prop_flat_map(|(num_inputs, num_constraints)| {
+            (
+                prop::collection::vec(arb_field::<F>(), num_inputs),
+                prop::collection::vec(
+                    (arb_lc::<F>(num_inputs), arb_lc::<F>(num_inputs)),
+                    num_constraints,
+                ),
+            )
+        })
+        .prop_map(|(inputs, constraints)| {
+            let num_inputs = inputs.len();
+            let num_rows = constraints.len();
+            let mut a = SparseConstraints::empty_with_capacity(0, 0);
+            let mut b = SparseConstraints::empty_with_capacity(0, 0);
+            let mut c = SparseConstraints::empty_with_capacity(0, 0);
+            for (row, (a_lc, b_lc)) in constraints.into_iter().enumerate() {
+                for (matrix, (vars, constant)) in [(&mut a, a_lc), (&mut b, b_lc)] {
+                    for (col, val) in vars {
+                        matrix.push_var(row, col, val);
+                    }
+                    if let Some(constant) = constant {
+                        matrix.consts.push((row, constant));
+                    }
+                }
+                c.push_var(row, num_inputs + row, F::one());
+            }
+
+            let mut z = inputs;
+            let az = a.multiply_vec(num_rows, &z);
+            let bz = b.multiply_vec(num_rows, &z);
+            z.extend(az.iter().zip(bz.iter()).map(|(a, b)| *a * b));
+            R1CSInstance {
+                r1cs: UniformR1CS {
+                    a,
+                    b,
+                    c,
+                    num_vars: z.len(),
+                    num_rows,
+                    step_kinds: vec![],
+                },
+                z,
+            }
+        })
+}
+
+/// Flips a random byte of `bytes` (to a random other value) in each of `cases` cases, and
+/// asserts that `accepts` rejects every mutated copy. `accepts` typically deserializes and
+/// verifies a proof; a panic counts as a rejection.
+pub fn assert_byte_flips_rejected(bytes: &[u8], cases: u32, accepts: impl Fn(&[u8]) -> bool) {
+    let mut runner = TestRunner::new(Config {
+        cases,
+        failure_persistence: None,
+        ..Config::default()
+    });
+    runner
+        .run(&(0..bytes.len(), 1..=u8::MAX), |(index, mask)| {
+            let mut mutated = bytes.to_vec();
+            mutated[index] ^= mask;
+            let accepted = catch_unwind(AssertUnwindSafe(|| accepts(&mutated))).unwrap_or(false);
+            prop_assert!(
+                !accepted,
+                "flipping byte {} with {:#04x} was accepted",
+                index,
+                mask
+            );
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+    use crate::utils::index_to_field_bitvector;
+    use crate::utils::transcript::{KeccakTranscript, Transcript};
+    use ark_bn254::Fr;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use proptest::strategy::ValueTree;
+
+    proptest! {
+        #[test]
+        fn r1cs_is_satisfied(instance in arb_r1cs::<Fr>(8, 8)) {
+            prop_assert!(instance.is_satisfied());
+        }
+
+        #[test]
+        fn r1cs_rejects_wrong_output(
+            mut instance in arb_r1cs::<Fr>(8, 8),
+            row in any::<prop::sample::Index>(),
+            delta in arb_field::<Fr>(),
+        ) {
+            prop_assume!(delta != Fr::from(0u64));
+            let num_inputs = instance.r1cs.num_vars - instance.r1cs.num_rows;
+            instance.z[num_inputs + row.index(instance.r1cs.num_rows)] += delta;
+            prop_assert!(!instance.is_satisfied());
+        }
+
+        #[test]
+        fn polynomial_evaluates_to_its_evals(
+            poly in arb_dense_polynomial::<Fr>(1..8usize),
+            index in any::<prop::sample::Index>(),
+        ) {
+            let index = index.index(poly.len());
+            let point: Vec<Fr> = index_to_field_bitvector(index, poly.get_num_vars());
+            prop_assert_eq!(poly.evaluate(&point), poly[index]);
+        }
+    }
+
+    #[test]
+    fn sumcheck_byte_flips_rejected() {
+        const NUM_VARS: usize = 6;
+        let mut runner = TestRunner::deterministic();
+        let polys: Vec<DensePolynomial<Fr>> = (0..2)
+            .map(|_| {
+                arb_dense_polynomial(Just(NUM_VARS))
+                    .new_tree(&mut runner)
+                    .unwrap()
+                    .current()
+            })
+            .collect();
+        let claim: Fr = (0..1 << NUM_VARS).map(|i| polys[0][i] * polys[1][i]).sum();
+        let (proof, _, _) = SumcheckInstanceProof::prove_arbitrary(
+            &claim,
+            NUM_VARS,
+            &mut polys.clone(),
+            |vals: &[Fr]| vals[0] * vals[1],
+            2,
+            &mut KeccakTranscript::new(b"test"),
+        );
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+
+        let accepts = |bytes: &[u8]| {
+            let Ok(proof) =
+                SumcheckInstanceProof::<Fr, KeccakTranscript>::deserialize_compressed(bytes)
+            else {
+                return false;
+            };
+            let mut transcript = KeccakTranscript::new(b"test");
+            proof
+                .verify(claim, NUM_VARS, 2, &mut transcript)
+                .is_ok_and(|(eval, r)| eval == polys[0].evaluate(&r) * polys[1].evaluate(&r))
+        };
+        assert!(accepts(&bytes));
+        assert_byte_flips_rejected(&bytes, 64, accepts);
+    }
+}
@@ -1,6 +1,16 @@
+use alloc::string::String;
 use core::fmt::Debug;
 use thiserror::Error;
 
+#[cfg(feature = "std")]
+use crate::jolt::vm::checkpoint::CheckpointError;
+#[cfg(feature = "std")]
+use crate::jolt::vm::distributed::DistributedError;
+#[cfg(feature = "std")]
+use crate::poly::commitment::zeromorph::ZeromorphError;
+#[cfg(feature = "std")]
+use crate::r1cs::spartan::SpartanError;
+
 #[derive(Error, Debug, Default)]
 pub enum ProofVerifyError {
     #[error("Invalid input length, expected length {0} but got {1}")]
@@ -18,4 +28,33 @@ pub enum ProofVerifyError {
     KeyLengthError(usize, usize),
     #[error("Invalid key length: {0}, expected power of 2")]
     InvalidKeyLength(usize),
+    #[error("The proof's memory layout does not match the preprocessing's")]
+    MemoryLayoutMismatch,
+    #[error("The proof's program I/O does not match the expected I/O")]
+    ProgramIOMismatch,
+}
+
+/// Errors returned by the prover, for inputs it cannot prove. Each variant identifies the
+/// stage (or the polynomial) at which proving failed.
+#[cfg(feature = "std")]
+#[derive(Error, Debug)]
+pub enum JoltError {
+    #[error("Program inputs have {0} bytes, but the memory layout allows at most {1}")]
+    InputTooLarge(usize, usize),
+    #[error("Program outputs have {0} bytes, but the memory layout allows at most {1}")]
+    OutputTooLarge(usize, usize),
+    #[error("The program I/O's memory layout does not match the preprocessing's")]
+    MemoryLayoutMismatch,
+    #[error("Bytecode witness: trace step {step} is at address {address:#x}, which is not in the preprocessed bytecode")]
+    UnknownBytecodeAddress { step: usize, address: usize },
+    #[error("R1CS proof failed: {0}")]
+    Spartan(#[from] SpartanError),
+    #[error("Zeromorph setup failed: {0}")]
+    Zeromorph(#[from] ZeromorphError),
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+    #[error("Distributed commitment failed: {0}")]
+    Distributed(#[from] DistributedError),
+    #[error(transparent)]
+    Verify(#[from] ProofVerifyError),
 }
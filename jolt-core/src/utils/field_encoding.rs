@@ -0,0 +1,115 @@
+//! Canonical encodings of proofs as field elements, for recursive verification.
+//!
+//! A verifier circuit over the proof's scalar field `F` absorbs the proof into an in-circuit
+//! (e.g. Poseidon) transcript. Scalars are encoded directly. Curve points, whose coordinates
+//! are not native to `F`, are encoded as their affine coordinates split into little-endian
+//! 128-bit limbs, with the point at infinity encoded as all-zero limbs. Variable-length vectors
+//! are prefixed with their length, so that the encoding is injective.
+use ark_ec::AffineRepr;
+use ark_serialize::CanonicalSerialize;
+
+use crate::field::JoltField;
+
+/// Bytes per limb of a non-native value, small enough for any limb to be a canonical element
+/// of the scalar fields Jolt uses
+const LIMB_BYTES: usize = 16;
+
+pub trait ToFieldElements<F: JoltField> {
+    /// Appends the encoding of `self` to `out`.
+    fn append_field_elements(&self, out: &mut Vec<F>);
+
+    fn to_field_elements(&self) -> Vec<F> {
+        let mut out = vec![];
+        self.append_field_elements(&mut out);
+        out
+    }
+}
+
+/// Appends a length prefix.
+pub fn append_len<F: JoltField>(len: usize, out: &mut Vec<F>) {
+    out.push(F::from_u64(len as u64).unwrap());
+}
+
+/// Appends `scalars`, prefixed with their number.
+pub fn append_scalars<F: JoltField>(scalars: &[F], out: &mut Vec<F>) {
+    append_len(scalars.len(), out);
+    out.extend_from_slice(scalars);
+}
+
+/// Appends the affine coordinates of `point` as limbs.
+pub fn append_point<F: JoltField, G: AffineRepr>(point: &G, out: &mut Vec<F>) {
+    match point.xy() {
+        Some((x, y)) => {
+            for coordinate in [x, y] {
+                let mut bytes = vec![];
+                coordinate.serialize_compressed(&mut bytes).unwrap();
+                append_limbs(&bytes, out);
+            }
+        }
+        None => {
+            let coordinate_bytes = G::generator().x().unwrap().compressed_size();
+            let num_limbs = 2 * coordinate_bytes.div_ceil(LIMB_BYTES);
+            out.extend(std::iter::repeat(F::zero()).take(num_limbs));
+        }
+    }
+}
+
+/// Appends `points`, prefixed with their number.
+pub fn append_points<F: JoltField, G: AffineRepr>(points: &[G], out: &mut Vec<F>) {
+    append_len(points.len(), out);
+    for point in points {
+        append_point(point, out);
+    }
+}
+
+/// Appends arbitrary bytes, prefixed with their number, as limbs.
+pub fn append_bytes<F: JoltField>(bytes: &[u8], out: &mut Vec<F>) {
+    append_len(bytes.len(), out);
+    append_limbs(bytes, out);
+}
+
+/// Splits little-endian `bytes` into limbs of `LIMB_BYTES`, the last one zero-padded.
+fn append_limbs<F: JoltField>(bytes: &[u8], out: &mut Vec<F>) {
+    let two_pow_64 = F::from_u64(u64::MAX).unwrap() + F::one();
+    out.extend(bytes.chunks(LIMB_BYTES).map(|limb| {
+        let mut padded = [0u8; LIMB_BYTES];
+        padded[..limb.len()].copy_from_slice(limb);
+        let low = u64::from_le_bytes(padded[..8].try_into().unwrap());
+        let high = u64::from_le_bytes(padded[8..].try_into().unwrap());
+        F::from_u64(high).unwrap() * two_pow_64 + F::from_u64(low).unwrap()
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::CurveGroup;
+    use ark_ff::{Field, PrimeField};
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn point_limbs() {
+        let mut rng = test_rng();
+        let point = ark_bn254::G1Projective::rand(&mut rng).into_affine();
+        let mut out: Vec<Fr> = vec![];
+        append_point(&point, &mut out);
+        append_point(&G1Affine::identity(), &mut out);
+        // Two 128-bit limbs per coordinate
+        assert_eq!(out.len(), 8);
+        assert!(out[4..].iter().all(|limb| *limb == Fr::from(0u64)));
+
+        // The limbs recompose to the coordinates
+        let two_pow_128 = Fr::from(2u64).pow([128]);
+        let x = out[0] + out[1] * two_pow_128;
+        let mut x_bytes = vec![];
+        point.x.serialize_compressed(&mut x_bytes).unwrap();
+        let mut expected = vec![];
+        Fr::from_le_bytes_mod_order(&x_bytes)
+            .serialize_compressed(&mut expected)
+            .unwrap();
+        let mut actual = vec![];
+        x.serialize_compressed(&mut actual).unwrap();
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::field::JoltField;
+use crate::utils::par::prelude::*;
+
 pub trait Math {
     fn square_root(self) -> usize;
     fn pow2(self) -> usize;
@@ -34,3 +37,76 @@ impl Math for usize {
         }
     }
 }
+
+/// Elements inverted serially, with a single field inversion, per rayon task
+const BATCH_INVERSION_CHUNK_SIZE: usize = 1 << 12;
+
+/// Inverts every element of `values` in place with Montgomery's trick, leaving zeros as they
+/// are. Chunks are inverted in parallel, at the cost of one field inversion each.
+pub fn batch_inverse<F: JoltField>(values: &mut [F]) {
+    batch_inverse_with_scratch(values, &mut vec![]);
+}
+
+/// [`batch_inverse`], with the prefix products written to `scratch` so that callers inverting
+/// repeatedly can reuse its allocation.
+pub fn batch_inverse_with_scratch<F: JoltField>(values: &mut [F], scratch: &mut Vec<F>) {
+    scratch.clear();
+    scratch.resize(values.len(), F::zero());
+    values
+        .par_chunks_mut(BATCH_INVERSION_CHUNK_SIZE)
+        .zip(scratch.par_chunks_mut(BATCH_INVERSION_CHUNK_SIZE))
+        .for_each(|(values, prefix_products)| batch_inverse_serial(values, prefix_products));
+}
+
+fn batch_inverse_serial<F: JoltField>(values: &mut [F], prefix_products: &mut [F]) {
+    let mut product = F::one();
+    for (value, prefix_product) in values.iter().zip(prefix_products.iter_mut()) {
+        *prefix_product = product;
+        if !value.is_zero() {
+            product *= *value;
+        }
+    }
+
+    // Peel the values off the inverse of their product, from the last one down
+    let mut product_inverse = product.inverse().unwrap();
+    for (value, prefix_product) in values.iter_mut().zip(prefix_products.iter()).rev() {
+        if !value.is_zero() {
+            let inverse = product_inverse * *prefix_product;
+            product_inverse *= *value;
+            *value = inverse;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_std::{test_rng, Zero};
+
+    #[test]
+    fn batch_inversion() {
+        let mut rng = test_rng();
+        let mut values: Vec<Fr> = (0..3 * BATCH_INVERSION_CHUNK_SIZE + 5)
+            .map(|i| {
+                if i % 7 == 0 {
+                    Fr::zero()
+                } else {
+                    <Fr as JoltField>::random(&mut rng)
+                }
+            })
+            .collect();
+        let expected: Vec<Fr> = values
+            .iter()
+            .map(|value| JoltField::inverse(value).unwrap_or(Fr::zero()))
+            .collect();
+
+        let mut scratch = vec![];
+        batch_inverse_with_scratch(&mut values, &mut scratch);
+        assert_eq!(values, expected);
+        assert_eq!(scratch.len(), values.len());
+
+        let mut empty: Vec<Fr> = vec![];
+        batch_inverse(&mut empty);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::utils::par::prelude::*;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha3::{Digest, Keccak256};
+
+use crate::utils::math::Math;
+
+pub type Digest32 = [u8; 32];
+
+/// Hashes the concatenation of two nodes into their parent.
+pub fn hash_nodes(left: &Digest32, right: &Digest32) -> Digest32 {
+    Keccak256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Hashes arbitrary serializable data into a leaf.
+pub fn hash_leaf<T: CanonicalSerialize + ?Sized>(data: &T) -> Digest32 {
+    let mut buf = vec![];
+    data.serialize_uncompressed(&mut buf).unwrap();
+    Keccak256::digest(&buf).into()
+}
+
+/// A binary Merkle tree over Keccak256, with a power-of-two number of leaves.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    /// `layers[0]` holds the leaf hashes and the last layer holds the root.
+    layers: Vec<Vec<Digest32>>,
+}
+
+/// The sibling hashes along the path from a leaf to the root, starting at the leaf layer.
+#[derive(Clone, Debug, Default, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MerklePath {
+    pub siblings: Vec<Digest32>,
+}
+
+impl MerkleTree {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, name = "MerkleTree::new")
+    )]
+    pub fn new(leaves: Vec<Digest32>) -> Self {
+        assert!(leaves.len().is_power_of_two());
+        let mut layers = Vec::with_capacity(leaves.len().log_2() + 1);
+        layers.push(leaves);
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .par_chunks(2)
+                .map(|pair| hash_nodes(&pair[0], &pair[1]))
+                .collect();
+            layers.push(next);
+        }
+        Self { layers }
+    }
+
+    pub fn root(&self) -> Digest32 {
+        self.layers.last().unwrap()[0]
+    }
+
+    pub fn num_leaves(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    pub fn path(&self, mut index: usize) -> MerklePath {
+        assert!(index < self.num_leaves());
+        let siblings = self.layers[..self.layers.len() - 1]
+            .iter()
+            .map(|layer| {
+                let sibling = layer[index ^ 1];
+                index >>= 1;
+                sibling
+            })
+            .collect();
+        MerklePath { siblings }
+    }
+}
+
+impl MerklePath {
+    /// Checks that `leaf` is the `index`-th leaf of the tree with the given `root`.
+    pub fn verify(&self, root: &Digest32, mut index: usize, leaf: Digest32) -> bool {
+        let mut node = leaf;
+        for sibling in self.siblings.iter() {
+            node = if index & 1 == 0 {
+                hash_nodes(&node, sibling)
+            } else {
+                hash_nodes(sibling, &node)
+            };
+            index >>= 1;
+        }
+        index == 0 && node == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merkle_paths() {
+        let leaves: Vec<Digest32> = (0..16u64).map(|i| hash_leaf(&i)).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let path = tree.path(i);
+            assert_eq!(path.siblings.len(), 4);
+            assert!(path.verify(&root, i, *leaf));
+            assert!(!path.verify(&root, i ^ 1, *leaf));
+            assert!(!path.verify(&root, i, hash_leaf(&100u64)));
+        }
+        // Indices beyond the tree must not verify against a shorter path
+        assert!(!tree.path(3).verify(&root, 3 + 16, leaves[3]));
+    }
+
+    #[test]
+    fn single_leaf() {
+        let leaf = hash_leaf(&7u64);
+        let tree = MerkleTree::new(vec![leaf]);
+        assert_eq!(tree.root(), leaf);
+        assert!(tree.path(0).verify(&tree.root(), 0, leaf));
+    }
+}
@@ -0,0 +1,361 @@
+//! Per-span metrics for the prover: wall time, peak allocation and MSM sizes.
+//!
+//! [`MetricsLayer`] is a `tracing` layer that aggregates, for every span name, the number of
+//! times the span was entered, the total time spent in it, the most memory allocated while in
+//! it, and the sum and maximum of its `size` field (which the MSMs record their number of bases
+//! in). Allocations are only counted if the binary installs [`CountingAllocator`] as its global
+//! allocator. After proving, the aggregated metrics can be exported as JSON, and the individual
+//! spans as a Chrome trace (viewable in chrome://tracing or [perfetto](https://ui.perfetto.dev)):
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator;
+//!
+//! let (metrics_layer, metrics) = MetricsLayer::new();
+//! tracing_subscriber::registry().with(metrics_layer).init();
+//! let (proof, commitments, _) = RV32IJoltVM::prove(io_device, trace, preprocessing)?;
+//! let report = metrics.report();
+//! std::fs::write("metrics.json", report.to_json())?;
+//! std::fs::write("metrics-trace.json", report.to_chrome_trace())?;
+//! ```
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// A global allocator that counts the bytes allocated through it, for [`MetricsLayer`]'s
+/// peak allocation metrics. It forwards to the system allocator.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Bytes currently allocated
+    pub fn allocated() -> usize {
+        ALLOCATED.load(Ordering::Relaxed)
+    }
+
+    /// Most bytes allocated at once since the peak was last reset
+    pub fn peak() -> usize {
+        PEAK_ALLOCATED.load(Ordering::Relaxed)
+    }
+
+    /// Resets the peak to the bytes currently allocated, returning the previous peak.
+    fn reset_peak() -> usize {
+        PEAK_ALLOCATED.swap(Self::allocated(), Ordering::Relaxed)
+    }
+
+    fn record_alloc(size: usize) {
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_ALLOCATED.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// The metrics aggregated over every invocation of a span
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SpanMetrics {
+    pub name: &'static str,
+    /// Number of times the span was entered
+    pub count: u64,
+    /// Total wall time spent in the span
+    pub total_time_ns: u64,
+    /// Most bytes allocated while in the span, on top of those allocated when it was entered.
+    /// The allocation count is process-wide, so it includes allocations made by spans running
+    /// concurrently on other threads.
+    pub peak_allocated_bytes: usize,
+    /// Sum of the span's `size` field, e.g. the number of bases of an MSM
+    pub total_size: u64,
+    /// Maximum of the span's `size` field
+    pub max_size: u64,
+}
+
+/// A single invocation of a span, for the Chrome trace
+#[derive(Clone, Debug)]
+struct SpanEvent {
+    name: &'static str,
+    thread: u64,
+    start_ns: u64,
+    duration_ns: u64,
+    peak_allocated_bytes: usize,
+    size: Option<u64>,
+}
+
+#[derive(Default)]
+struct Metrics {
+    spans: HashMap<&'static str, SpanMetrics>,
+    events: Vec<SpanEvent>,
+}
+
+/// The metrics collected by a [`MetricsLayer`], sorted by decreasing total time.
+#[derive(Clone, Debug, Serialize)]
+pub struct MetricsReport {
+    pub spans: Vec<SpanMetrics>,
+    #[serde(skip)]
+    events: Vec<SpanEvent>,
+}
+
+impl MetricsReport {
+    /// The per-span metrics, as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Every span invocation, in the Chrome trace event format
+    pub fn to_chrome_trace(&self) -> String {
+        let events: Vec<_> = self
+            .events
+            .iter()
+            .map(|event| {
+                serde_json::json!({
+                    "name": event.name,
+                    "cat": "jolt",
+                    "ph": "X",
+                    "pid": 1,
+                    "tid": event.thread,
+                    "ts": event.start_ns as f64 / 1000.0,
+                    "dur": event.duration_ns as f64 / 1000.0,
+                    "args": {
+                        "peak_allocated_bytes": event.peak_allocated_bytes,
+                        "size": event.size,
+                    },
+                })
+            })
+            .collect();
+        serde_json::json!({ "traceEvents": events }).to_string()
+    }
+}
+
+/// A handle to the metrics collected by a [`MetricsLayer`]
+#[derive(Clone)]
+pub struct MetricsHandle(Arc<Mutex<Metrics>>);
+
+impl MetricsHandle {
+    /// The metrics collected so far
+    pub fn report(&self) -> MetricsReport {
+        let metrics = self.0.lock().unwrap();
+        let mut spans: Vec<SpanMetrics> = metrics.spans.values().cloned().collect();
+        spans.sort_by(|a, b| {
+            b.total_time_ns
+                .cmp(&a.total_time_ns)
+                .then(a.name.cmp(b.name))
+        });
+        MetricsReport {
+            spans,
+            events: metrics.events.clone(),
+        }
+    }
+}
+
+/// A span's `size` field
+struct SpanSize(u64);
+
+/// The state of a span's current invocation
+struct SpanEntry {
+    entered: Instant,
+    allocated: usize,
+    outer_peak: usize,
+}
+
+struct SizeVisitor(Option<u64>);
+
+impl Visit for SizeVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "size" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_u64(field, value as u64);
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// A small integer identifying the current thread in the Chrome trace
+fn thread_index() -> u64 {
+    static NEXT_INDEX: AtomicU64 = AtomicU64::new(0);
+    thread_local! {
+        static INDEX: Cell<Option<u64>> = const { Cell::new(None) };
+    }
+    INDEX.with(|index| match index.get() {
+        Some(index) => index,
+        None => {
+            let next = NEXT_INDEX.fetch_add(1, Ordering::Relaxed);
+            index.set(Some(next));
+            next
+        }
+    })
+}
+
+/// A `tracing` layer collecting per-span metrics, see the [module documentation](self).
+pub struct MetricsLayer {
+    metrics: Arc<Mutex<Metrics>>,
+    start: Instant,
+}
+
+impl MetricsLayer {
+    /// Creates a layer, and a handle to report the metrics it collects.
+    pub fn new() -> (Self, MetricsHandle) {
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        let layer = Self {
+            metrics: Arc::clone(&metrics),
+            start: Instant::now(),
+        };
+        (layer, MetricsHandle(metrics))
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = SizeVisitor(None);
+        attrs.record(&mut visitor);
+        if let (Some(size), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(SpanSize(size));
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanEntry {
+                entered: Instant::now(),
+                allocated: CountingAllocator::allocated(),
+                outer_peak: CountingAllocator::reset_peak(),
+            });
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(entry) = extensions.remove::<SpanEntry>() else {
+            return;
+        };
+        let size = extensions.get_mut::<SpanSize>().map(|size| size.0);
+        drop(extensions);
+
+        let duration_ns = entry.entered.elapsed().as_nanos() as u64;
+        let peak = CountingAllocator::peak();
+        // The enclosing spans' peak includes this span's
+        PEAK_ALLOCATED.fetch_max(entry.outer_peak, Ordering::Relaxed);
+        let peak_allocated_bytes = peak.saturating_sub(entry.allocated);
+
+        let name = span.name();
+        let mut metrics = self.metrics.lock().unwrap();
+        let span_metrics = metrics.spans.entry(name).or_insert_with(|| SpanMetrics {
+            name,
+            ..Default::default()
+        });
+        span_metrics.count += 1;
+        span_metrics.total_time_ns += duration_ns;
+        span_metrics.peak_allocated_bytes =
+            span_metrics.peak_allocated_bytes.max(peak_allocated_bytes);
+        if let Some(size) = size {
+            span_metrics.total_size += size;
+            span_metrics.max_size = span_metrics.max_size.max(size);
+        }
+        let start_ns = entry.entered.duration_since(self.start).as_nanos() as u64;
+        metrics.events.push(SpanEvent {
+            name,
+            thread: thread_index(),
+            start_ns,
+            duration_ns,
+            peak_allocated_bytes,
+            size,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn aggregates_spans() {
+        let (layer, metrics) = MetricsLayer::new();
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let _prove = tracing::info_span!("prove").entered();
+            for size in [4usize, 16, 8] {
+                let _msm = tracing::info_span!("msm", size).entered();
+            }
+        });
+
+        let report = metrics.report();
+        assert_eq!(report.spans.len(), 2);
+        assert_eq!(report.spans[0].name, "prove");
+        let msm = &report.spans[1];
+        assert_eq!((msm.count, msm.total_size, msm.max_size), (3, 28, 16));
+        assert!(report.spans[0].total_time_ns >= msm.total_time_ns);
+
+        let trace: serde_json::Value = serde_json::from_str(&report.to_chrome_trace()).unwrap();
+        assert_eq!(trace["traceEvents"].as_array().unwrap().len(), 4);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["spans"][1]["max_size"], 16);
+    }
+
+    #[test]
+    fn counting_allocator() {
+        let layout = Layout::array::<u64>(1 << 10).unwrap();
+        let allocated = CountingAllocator::allocated();
+        unsafe {
+            let ptr = CountingAllocator.alloc(layout);
+            assert_eq!(CountingAllocator::allocated(), allocated + layout.size());
+            assert!(CountingAllocator::peak() >= allocated + layout.size());
+            CountingAllocator.dealloc(ptr, layout);
+        }
+        assert_eq!(CountingAllocator::allocated(), allocated);
+    }
+}
@@ -1,17 +1,39 @@
 #![allow(dead_code)]
 use crate::field::JoltField;
 
+#[cfg(feature = "std")]
+use crate::utils::par::prelude::*;
+#[cfg(feature = "std")]
 use ark_std::test_rng;
-use rayon::prelude::*;
 
 pub mod errors;
+#[cfg(feature = "std")]
+pub mod field_encoding;
+#[cfg(feature = "std")]
 pub mod gaussian_elimination;
+#[cfg(feature = "std")]
 pub mod instruction_utils;
+#[cfg(feature = "std")]
 pub mod math;
+#[cfg(feature = "std")]
+pub mod merkle_tree;
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod par;
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
 pub mod profiling;
+#[cfg(feature = "std")]
+pub mod prover_config;
+#[cfg(feature = "evm")]
 pub mod sol_types;
+#[cfg(feature = "std")]
 pub mod thread;
 pub mod transcript;
+#[cfg(feature = "std")]
+pub mod transcript_log;
+#[cfg(test)]
+pub(crate) mod transcript_snapshots;
 
 /// Converts an integer value to a bitvector (all values {0,1}) of field elements.
 /// Note: ordering has the MSB in the highest index. All of the following represent the integer 1:
@@ -29,6 +51,7 @@ pub mod transcript;
 /// assert_eq!(index_to_field_bitvector::<Fr>(1, 3), vec![zero, zero, one]);
 /// assert_eq!(index_to_field_bitvector::<Fr>(1, 7), vec![zero, zero, zero, zero, zero, zero, one]);
 /// ```
+#[cfg(feature = "std")]
 pub fn index_to_field_bitvector<F: JoltField>(value: usize, bits: usize) -> Vec<F> {
     assert!(value < 1 << bits);
 
@@ -44,7 +67,8 @@ pub fn index_to_field_bitvector<F: JoltField>(value: usize, bits: usize) -> Vec<
     bitvector
 }
 
-#[tracing::instrument(skip_all)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn compute_dotproduct<F: JoltField>(a: &[F], b: &[F]) -> F {
     a.par_iter()
         .zip_eq(b.par_iter())
@@ -53,7 +77,8 @@ pub fn compute_dotproduct<F: JoltField>(a: &[F], b: &[F]) -> F {
 }
 
 /// Compute dotproduct optimized for values being 0 / 1
-#[tracing::instrument(skip_all)]
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn compute_dotproduct_low_optimized<F: JoltField>(a: &[F], b: &[F]) -> F {
     a.par_iter()
         .zip_eq(b.par_iter())
@@ -113,6 +138,7 @@ pub fn split_bits(item: usize, num_bits: usize) -> (usize, usize) {
 }
 
 /// Generate a random point with `memory_bits` field elements.
+#[cfg(feature = "std")]
 pub fn gen_random_point<F: JoltField>(memory_bits: usize) -> Vec<F> {
     let mut rng = test_rng();
     let mut r_i: Vec<F> = Vec::with_capacity(memory_bits);
@@ -0,0 +1,437 @@
+//! rayon, or without the `rayon` feature, a sequential stand-in for the parts of its API that
+//! jolt-core uses, so that builds which only verify (see `jolt-verifier`) can leave rayon out.
+//! Code shared by the prover and the verifier imports rayon through here:
+//!
+//! ```ignore
+//! use crate::utils::par::prelude::*;
+//!
+//! let sum: F = coeffs.par_iter().map(|c| *c * *c).sum();
+//! ```
+//!
+//! The stand-in runs every parallel iterator as the corresponding `core` iterator on the
+//! calling thread, [`join`] runs its closures one after the other, and [`current_num_threads`]
+//! is 1.
+
+#[cfg(feature = "rayon")]
+pub use rayon::*;
+
+#[cfg(not(feature = "rayon"))]
+pub use sequential::*;
+
+#[cfg(not(feature = "rayon"))]
+mod sequential {
+    pub mod iter {
+        use core::iter::{
+            Chain, Cloned, Copied, Enumerate, Filter, FilterMap, FlatMap, Flatten, Map, Once,
+            Repeat, Rev, Skip, StepBy, Take, Zip,
+        };
+        use core::iter::{Product, Sum};
+
+        /// A parallel iterator that runs the iterator it wraps on the calling thread.
+        #[derive(Clone, Debug)]
+        pub struct SeqIter<I>(pub(super) I);
+
+        impl<I: Iterator> IntoIterator for SeqIter<I> {
+            type Item = I::Item;
+            type IntoIter = I;
+
+            fn into_iter(self) -> I {
+                self.0
+            }
+        }
+
+        /// The items of `chunks`, in `Vec`s of `chunk_size` (and the last one of what remains).
+        #[derive(Clone, Debug)]
+        pub struct VecChunks<I> {
+            iter: I,
+            chunk_size: usize,
+        }
+
+        impl<I: Iterator> Iterator for VecChunks<I> {
+            type Item = Vec<I::Item>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let chunk: Vec<_> = self.iter.by_ref().take(self.chunk_size).collect();
+                (!chunk.is_empty()).then_some(chunk)
+            }
+        }
+
+        pub fn repeatn<T: Clone>(element: T, n: usize) -> SeqIter<Take<Repeat<T>>> {
+            SeqIter(core::iter::repeat(element).take(n))
+        }
+
+        pub trait IntoParallelIterator {
+            type Iter: ParallelIterator<Item = Self::Item>;
+            type Item;
+
+            fn into_par_iter(self) -> Self::Iter;
+        }
+
+        impl<T: IntoIterator> IntoParallelIterator for T {
+            type Iter = SeqIter<T::IntoIter>;
+            type Item = T::Item;
+
+            fn into_par_iter(self) -> Self::Iter {
+                SeqIter(self.into_iter())
+            }
+        }
+
+        pub trait IntoParallelRefIterator<'data> {
+            type Iter: ParallelIterator<Item = Self::Item>;
+            type Item: 'data;
+
+            fn par_iter(&'data self) -> Self::Iter;
+        }
+
+        impl<'data, I: 'data + ?Sized> IntoParallelRefIterator<'data> for I
+        where
+            &'data I: IntoParallelIterator,
+        {
+            type Iter = <&'data I as IntoParallelIterator>::Iter;
+            type Item = <&'data I as IntoParallelIterator>::Item;
+
+            fn par_iter(&'data self) -> Self::Iter {
+                self.into_par_iter()
+            }
+        }
+
+        pub trait IntoParallelRefMutIterator<'data> {
+            type Iter: ParallelIterator<Item = Self::Item>;
+            type Item: 'data;
+
+            fn par_iter_mut(&'data mut self) -> Self::Iter;
+        }
+
+        impl<'data, I: 'data + ?Sized> IntoParallelRefMutIterator<'data> for I
+        where
+            &'data mut I: IntoParallelIterator,
+        {
+            type Iter = <&'data mut I as IntoParallelIterator>::Iter;
+            type Item = <&'data mut I as IntoParallelIterator>::Item;
+
+            fn par_iter_mut(&'data mut self) -> Self::Iter {
+                self.into_par_iter()
+            }
+        }
+
+        pub trait ParallelExtend<T> {
+            fn par_extend<I: IntoIterator<Item = T>>(&mut self, par_iter: I);
+        }
+
+        impl<T, C: Extend<T>> ParallelExtend<T> for C {
+            fn par_extend<I: IntoIterator<Item = T>>(&mut self, par_iter: I) {
+                self.extend(par_iter)
+            }
+        }
+
+        pub trait ParallelIterator: Sized {
+            type Item;
+            #[doc(hidden)]
+            type Seq: Iterator<Item = Self::Item>;
+
+            #[doc(hidden)]
+            fn into_seq(self) -> Self::Seq;
+
+            fn for_each<OP: FnMut(Self::Item)>(self, op: OP) {
+                self.into_seq().for_each(op)
+            }
+
+            fn count(self) -> usize {
+                self.into_seq().count()
+            }
+
+            fn map<R, F: FnMut(Self::Item) -> R>(self, map_op: F) -> SeqIter<Map<Self::Seq, F>> {
+                SeqIter(self.into_seq().map(map_op))
+            }
+
+            fn cloned<'a, T: 'a + Clone>(self) -> SeqIter<Cloned<Self::Seq>>
+            where
+                Self::Seq: Iterator<Item = &'a T>,
+            {
+                SeqIter(self.into_seq().cloned())
+            }
+
+            fn copied<'a, T: 'a + Copy>(self) -> SeqIter<Copied<Self::Seq>>
+            where
+                Self::Seq: Iterator<Item = &'a T>,
+            {
+                SeqIter(self.into_seq().copied())
+            }
+
+            fn filter<P: FnMut(&Self::Item) -> bool>(
+                self,
+                filter_op: P,
+            ) -> SeqIter<Filter<Self::Seq, P>> {
+                SeqIter(self.into_seq().filter(filter_op))
+            }
+
+            fn filter_map<R, P: FnMut(Self::Item) -> Option<R>>(
+                self,
+                filter_op: P,
+            ) -> SeqIter<FilterMap<Self::Seq, P>> {
+                SeqIter(self.into_seq().filter_map(filter_op))
+            }
+
+            fn flat_map<PI: IntoIterator, F: FnMut(Self::Item) -> PI>(
+                self,
+                map_op: F,
+            ) -> SeqIter<FlatMap<Self::Seq, PI, F>> {
+                SeqIter(self.into_seq().flat_map(map_op))
+            }
+
+            fn flat_map_iter<SI: IntoIterator, F: FnMut(Self::Item) -> SI>(
+                self,
+                map_op: F,
+            ) -> SeqIter<FlatMap<Self::Seq, SI, F>> {
+                SeqIter(self.into_seq().flat_map(map_op))
+            }
+
+            fn flatten(self) -> SeqIter<Flatten<Self::Seq>>
+            where
+                Self::Item: IntoIterator,
+            {
+                SeqIter(self.into_seq().flatten())
+            }
+
+            fn reduce<ID, OP>(self, identity: ID, op: OP) -> Self::Item
+            where
+                ID: Fn() -> Self::Item,
+                OP: FnMut(Self::Item, Self::Item) -> Self::Item,
+            {
+                self.into_seq().fold(identity(), op)
+            }
+
+            fn fold<T, ID, F>(self, identity: ID, fold_op: F) -> SeqIter<Once<T>>
+            where
+                ID: Fn() -> T,
+                F: FnMut(T, Self::Item) -> T,
+            {
+                SeqIter(core::iter::once(self.into_seq().fold(identity(), fold_op)))
+            }
+
+            fn sum<S: Sum<Self::Item>>(self) -> S {
+                self.into_seq().sum()
+            }
+
+            fn product<P: Product<Self::Item>>(self) -> P {
+                self.into_seq().product()
+            }
+
+            fn min(self) -> Option<Self::Item>
+            where
+                Self::Item: Ord,
+            {
+                self.into_seq().min()
+            }
+
+            fn min_by_key<K: Ord, F: FnMut(&Self::Item) -> K>(self, f: F) -> Option<Self::Item> {
+                self.into_seq().min_by_key(f)
+            }
+
+            fn max(self) -> Option<Self::Item>
+            where
+                Self::Item: Ord,
+            {
+                self.into_seq().max()
+            }
+
+            fn max_by_key<K: Ord, F: FnMut(&Self::Item) -> K>(self, f: F) -> Option<Self::Item> {
+                self.into_seq().max_by_key(f)
+            }
+
+            fn chain<C: IntoIterator<Item = Self::Item>>(
+                self,
+                chain: C,
+            ) -> SeqIter<Chain<Self::Seq, C::IntoIter>> {
+                SeqIter(self.into_seq().chain(chain))
+            }
+
+            fn find_map_first<R, P: FnMut(Self::Item) -> Option<R>>(
+                self,
+                predicate: P,
+            ) -> Option<R> {
+                self.into_seq().find_map(predicate)
+            }
+
+            fn any<P: FnMut(Self::Item) -> bool>(self, predicate: P) -> bool {
+                self.into_seq().any(predicate)
+            }
+
+            fn all<P: FnMut(Self::Item) -> bool>(self, predicate: P) -> bool {
+                self.into_seq().all(predicate)
+            }
+
+            fn collect<C: FromIterator<Self::Item>>(self) -> C {
+                self.into_seq().collect()
+            }
+
+            fn unzip<A, B, FromA, FromB>(self) -> (FromA, FromB)
+            where
+                Self::Seq: Iterator<Item = (A, B)>,
+                FromA: Default + Extend<A>,
+                FromB: Default + Extend<B>,
+            {
+                self.into_seq().unzip()
+            }
+        }
+
+        impl<I: Iterator> ParallelIterator for SeqIter<I> {
+            type Item = I::Item;
+            type Seq = I;
+
+            fn into_seq(self) -> I {
+                self.0
+            }
+        }
+
+        pub trait IndexedParallelIterator: ParallelIterator {
+            fn enumerate(self) -> SeqIter<Enumerate<Self::Seq>> {
+                SeqIter(self.into_seq().enumerate())
+            }
+
+            fn zip<Z: IntoIterator>(self, zip_op: Z) -> SeqIter<Zip<Self::Seq, Z::IntoIter>> {
+                SeqIter(self.into_seq().zip(zip_op))
+            }
+
+            /// Like [`IndexedParallelIterator::zip`], but panics if the lengths differ.
+            fn zip_eq<Z: IntoIterator>(
+                self,
+                zip_op: Z,
+            ) -> SeqIter<itertools::ZipEq<Self::Seq, Z::IntoIter>> {
+                SeqIter(itertools::zip_eq(self.into_seq(), zip_op))
+            }
+
+            fn skip(self, n: usize) -> SeqIter<Skip<Self::Seq>> {
+                SeqIter(self.into_seq().skip(n))
+            }
+
+            fn take(self, n: usize) -> SeqIter<Take<Self::Seq>> {
+                SeqIter(self.into_seq().take(n))
+            }
+
+            fn step_by(self, step: usize) -> SeqIter<StepBy<Self::Seq>> {
+                SeqIter(self.into_seq().step_by(step))
+            }
+
+            fn rev(self) -> SeqIter<Rev<Self::Seq>>
+            where
+                Self::Seq: DoubleEndedIterator,
+            {
+                SeqIter(self.into_seq().rev())
+            }
+
+            fn chunks(self, chunk_size: usize) -> SeqIter<VecChunks<Self::Seq>> {
+                assert!(chunk_size != 0, "chunk_size must not be zero");
+                SeqIter(VecChunks {
+                    iter: self.into_seq(),
+                    chunk_size,
+                })
+            }
+
+            fn collect_into_vec(self, target: &mut Vec<Self::Item>) {
+                target.clear();
+                target.extend(self.into_seq());
+            }
+        }
+
+        impl<I: Iterator> IndexedParallelIterator for SeqIter<I> {}
+    }
+
+    pub mod slice {
+        use super::iter::SeqIter;
+
+        pub type Chunks<'data, T> = SeqIter<core::slice::Chunks<'data, T>>;
+        pub type ChunksMut<'data, T> = SeqIter<core::slice::ChunksMut<'data, T>>;
+
+        pub trait ParallelSlice<T> {
+            fn par_chunks(&self, chunk_size: usize) -> Chunks<'_, T>;
+        }
+
+        impl<T> ParallelSlice<T> for [T] {
+            fn par_chunks(&self, chunk_size: usize) -> Chunks<'_, T> {
+                SeqIter(self.chunks(chunk_size))
+            }
+        }
+
+        pub trait ParallelSliceMut<T> {
+            fn par_chunks_mut(&mut self, chunk_size: usize) -> ChunksMut<'_, T>;
+        }
+
+        impl<T> ParallelSliceMut<T> for [T] {
+            fn par_chunks_mut(&mut self, chunk_size: usize) -> ChunksMut<'_, T> {
+                SeqIter(self.chunks_mut(chunk_size))
+            }
+        }
+    }
+
+    pub mod prelude {
+        pub use super::iter::{
+            IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+            IntoParallelRefMutIterator, ParallelExtend, ParallelIterator,
+        };
+        pub use super::slice::{ParallelSlice, ParallelSliceMut};
+    }
+
+    pub fn join<A, B, RA, RB>(oper_a: A, oper_b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB,
+    {
+        (oper_a(), oper_b())
+    }
+
+    pub fn spawn<F: FnOnce() + Send + 'static>(func: F) {
+        func()
+    }
+
+    pub fn current_num_threads() -> usize {
+        1
+    }
+
+    /// A thread pool whose only thread is the one that calls [`ThreadPool::install`].
+    pub struct ThreadPool {
+        start_handler: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    }
+
+    impl ThreadPool {
+        pub fn install<OP: FnOnce() -> R, R>(&self, op: OP) -> R {
+            if let Some(start_handler) = &self.start_handler {
+                start_handler(0);
+            }
+            op()
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct ThreadPoolBuildError;
+
+    #[derive(Default)]
+    pub struct ThreadPoolBuilder {
+        start_handler: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    }
+
+    impl ThreadPoolBuilder {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Has no effect: the pool always runs on the calling thread.
+        pub fn num_threads(self, _num_threads: usize) -> Self {
+            self
+        }
+
+        pub fn start_handler<H: Fn(usize) + Send + Sync + 'static>(
+            mut self,
+            start_handler: H,
+        ) -> Self {
+            self.start_handler = Some(Box::new(start_handler));
+            self
+        }
+
+        pub fn build(self) -> Result<ThreadPool, ThreadPoolBuildError> {
+            Ok(ThreadPool {
+                start_handler: self.start_handler,
+            })
+        }
+    }
+}
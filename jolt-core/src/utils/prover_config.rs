@@ -0,0 +1,148 @@
+//! Resource limits for the prover, so that it can share a machine with other workloads, and
+//! the seed of its randomness, so that its proofs can be reproduced.
+//!
+//! By default the prover uses rayon's global thread pool, sizes its MSM windows and buffer
+//! pools for throughput, and draws its randomness from the OS. [`ProverConfig::install`] instead runs the prover on a dedicated
+//! thread pool, whose threads (and those of any pool derived from it with
+//! [`ProverConfig::thread_pool`]) see the config through [`ProverConfig::current`]:
+//!
+//! ```ignore
+//! let config = ProverConfig {
+//!     num_threads: Some(8),
+//!     max_memory_bytes: Some(1 << 30),
+//!     ..Default::default()
+//! };
+//! let (proof, commitments, _) = config.install(|| RV32IJoltVM::prove(io_device, trace, preprocessing))?;
+//! ```
+use std::cell::Cell;
+
+use crate::utils::par::{self, ThreadPool, ThreadPoolBuilder};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sha3::{Digest, Keccak256};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProverConfig {
+    /// Number of threads the prover (including MSMs, sum-checks and witness generation) runs
+    /// on. Defaults to the size of rayon's global pool.
+    pub num_threads: Option<usize>,
+    /// Upper bound on the memory the prover's buffer pools retain between uses. Defaults to
+    /// no bound beyond the pools' buffer counts.
+    pub max_memory_bytes: Option<usize>,
+    /// Window size, in bits, of the Pippenger MSMs. Smaller windows use less memory for
+    /// buckets at the cost of more additions. Defaults to a size chosen per MSM.
+    pub msm_window_bits: Option<usize>,
+    /// Seed from which all of the prover's randomness (e.g. the masks of the sum-checks'
+    /// round polynomials) is derived, so that proving the same trace twice yields
+    /// byte-identical proofs. Defaults to fresh randomness for every proof. A seed must not be
+    /// reused across different traces if the masks are to hide anything.
+    pub seed: Option<[u8; 32]>,
+    /// Whether to mask the round polynomials of Spartan's sum-checks with committed random
+    /// polynomials. This does not make proofs zero-knowledge: the masks are committed to with
+    /// the (non-hiding) commitment scheme, and the final sum-check claims and the witness
+    /// evaluations are still revealed. Defaults to unmasked.
+    pub mask_round_polys: bool,
+    /// Whether to replace the first rounds of Spartan's outer sum-check by a single univariate
+    /// round, which shortens the proof and the verifier's work. The prover then holds Az, Bz and
+    /// Cz in full rather than streaming them. Cannot be combined with `mask_round_polys`.
+    /// Defaults to off.
+    pub univariate_skip: bool,
+}
+
+thread_local! {
+    static CURRENT: Cell<ProverConfig> = Cell::new(ProverConfig::default());
+}
+
+impl ProverConfig {
+    /// The config in effect on the current thread.
+    pub fn current() -> Self {
+        CURRENT.with(Cell::get)
+    }
+
+    /// Runs `op` on a dedicated thread pool of `num_threads` threads, under this config.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        let num_threads = self.num_threads.unwrap_or_else(par::current_num_threads);
+        assert!(num_threads > 0, "the prover needs at least one thread");
+        assert_ne!(
+            self.msm_window_bits,
+            Some(0),
+            "MSM windows must be nonempty"
+        );
+        assert!(
+            !(self.mask_round_polys && self.univariate_skip),
+            "the univariate skip does not support masked round polynomials"
+        );
+        self.thread_pool(num_threads).install(op)
+    }
+
+    /// A generator for the randomness the prover uses for `label`: derived from the `seed` and
+    /// `label` if a seed is set, and otherwise seeded from the OS.
+    pub fn rng(&self, label: &[u8]) -> ChaCha20Rng {
+        match self.seed {
+            Some(seed) => {
+                let mut hasher = Keccak256::new();
+                hasher.update(seed);
+                hasher.update(label);
+                ChaCha20Rng::from_seed(hasher.finalize().into())
+            }
+            None => {
+                let mut seed = [0u8; 32];
+                rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut seed);
+                ChaCha20Rng::from_seed(seed)
+            }
+        }
+    }
+
+    /// A thread pool of `num_threads` threads, running under this config.
+    pub fn thread_pool(&self, num_threads: usize) -> ThreadPool {
+        let config = *self;
+        ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .start_handler(move |_| CURRENT.with(|current| current.set(config)))
+            .build()
+            .expect("failed to build thread pool")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::par::prelude::*;
+
+    #[test]
+    fn install() {
+        let config = ProverConfig {
+            num_threads: Some(2),
+            max_memory_bytes: Some(1 << 20),
+            msm_window_bits: Some(4),
+            seed: Some([7; 32]),
+            mask_round_polys: true,
+            univariate_skip: false,
+        };
+        let (num_threads, configs) = config.install(|| {
+            let configs: Vec<_> = (0..64)
+                .into_par_iter()
+                .map(|_| ProverConfig::current())
+                .collect();
+            (par::current_num_threads(), configs)
+        });
+        assert_eq!(num_threads, 2);
+        assert!(configs.iter().all(|current| *current == config));
+        assert_eq!(ProverConfig::current(), ProverConfig::default());
+    }
+
+    #[test]
+    fn seeded_rng() {
+        use rand_core::RngCore;
+
+        let config = ProverConfig {
+            seed: Some([7; 32]),
+            ..Default::default()
+        };
+        let next = |config: ProverConfig, label: &[u8]| config.rng(label).next_u64();
+        assert_eq!(next(config, b"a"), next(config, b"a"));
+        assert_ne!(next(config, b"a"), next(config, b"b"));
+        let default = ProverConfig::default();
+        assert_ne!(next(default, b"a"), next(default, b"a"));
+    }
+}
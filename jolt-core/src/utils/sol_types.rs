@@ -1,9 +1,11 @@
-use ark_bn254::Bn254;
+use ark_bn254::{Bn254, G1Affine};
+use ark_ec::AffineRepr;
 use ark_ff::BigInteger;
 use ark_ff::PrimeField;
 
 use crate::field::JoltField;
 use crate::poly::commitment::hyperkzg::{HyperKZG, HyperKZGProof, HyperKZGVerifierKey};
+use crate::poly::commitment::zeromorph::ZeromorphProof;
 use crate::r1cs::inputs::JoltR1CSInputs;
 use crate::r1cs::spartan::UniformSpartanProof;
 use crate::subprotocols::grand_product::BatchedGrandProductLayerProof;
@@ -24,6 +26,16 @@ sol!(struct HyperKZGProofSol {
     uint256[] v_y;
 });
 
+sol!(struct ZeromorphProofSol {
+    uint256 pi_x;
+    uint256 pi_y;
+    uint256 q_hat_com_x;
+    uint256 q_hat_com_y;
+    uint256 q_hat_degree_check_com_x;
+    uint256 q_hat_degree_check_com_y;
+    uint256[] q_k_com; // G1 points represented pairwise
+});
+
 sol!(struct VK {
     uint256 VK_g1_x;
     uint256 VK_g1_y;
@@ -103,6 +115,33 @@ impl Into<HyperKZGProofSol> for &HyperKZGProof<Bn254> {
     }
 }
 
+impl Into<ZeromorphProofSol> for &ZeromorphProof<Bn254> {
+    fn into(self) -> ZeromorphProofSol {
+        let (pi_x, pi_y) = g1_into_uint256(&self.pi);
+        let (q_hat_com_x, q_hat_com_y) = g1_into_uint256(&self.q_hat_com);
+        let (q_hat_degree_check_com_x, q_hat_degree_check_com_y) =
+            g1_into_uint256(&self.q_hat_degree_check_com);
+        let q_k_com = self
+            .q_k_com
+            .iter()
+            .flat_map(|point| {
+                let (x, y) = g1_into_uint256(point);
+                [x, y]
+            })
+            .collect();
+
+        ZeromorphProofSol {
+            pi_x,
+            pi_y,
+            q_hat_com_x,
+            q_hat_com_y,
+            q_hat_degree_check_com_x,
+            q_hat_degree_check_com_y,
+            q_k_com,
+        }
+    }
+}
+
 impl Into<VK> for &HyperKZGVerifierKey<Bn254> {
     fn into(self) -> VK {
         let g1 = self.kzg_vk.g1;
@@ -152,6 +191,18 @@ impl<F: JoltField, ProofTranscript: Transcript> Into<SumcheckProof>
     }
 }
 
+/// The affine coordinates of a G1 point, with the point at infinity as `(0, 0)` as the
+/// precompiles expect.
+pub fn g1_into_uint256(point: &G1Affine) -> (U256, U256) {
+    match point.xy() {
+        Some((x, y)) => (
+            U256::from_be_slice(&x.into_bigint().to_bytes_be()),
+            U256::from_be_slice(&y.into_bigint().to_bytes_be()),
+        ),
+        None => (U256::ZERO, U256::ZERO),
+    }
+}
+
 pub fn into_uint256<F: JoltField>(from: F) -> U256 {
     let mut buf = vec![];
     from.serialize_uncompressed(&mut buf).unwrap();
@@ -160,9 +211,19 @@ pub fn into_uint256<F: JoltField>(from: F) -> U256 {
 
 const C: usize = 4;
 impl<ProofTranscript: Transcript> Into<SpartanProof>
-    for &UniformSpartanProof<C, JoltR1CSInputs, Fp<MontBackend<FrConfig, 4>, 4>, ProofTranscript>
+    for &UniformSpartanProof<
+        C,
+        JoltR1CSInputs,
+        Fp<MontBackend<FrConfig, 4>, 4>,
+        HyperKZG<Bn254, ProofTranscript>,
+        ProofTranscript,
+    >
 {
     fn into(self) -> SpartanProof {
+        assert!(
+            self.outer_univariate_skip.is_none(),
+            "The univariate skip is unsupported"
+        );
         let claimed_evals = self
             .claimed_witness_evals
             .iter()
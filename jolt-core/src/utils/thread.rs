@@ -1,4 +1,4 @@
-use rayon::prelude::*;
+use crate::utils::par::{self, prelude::*};
 use std::thread::{self, JoinHandle};
 
 use crate::field::JoltField;
@@ -8,7 +8,11 @@ where
     T: Send + 'static,
 {
     // h/t https://abrams.cc/rust-dropping-things-in-another-thread
-    rayon::spawn(move || drop(data));
+    #[cfg(not(target_arch = "wasm32"))]
+    par::spawn(move || drop(data));
+    // There are no threads to drop on
+    #[cfg(target_arch = "wasm32")]
+    drop(data);
 }
 
 pub fn allocate_vec_in_background<T: Clone + Send + 'static>(
@@ -18,7 +22,7 @@ pub fn allocate_vec_in_background<T: Clone + Send + 'static>(
     thread::spawn(move || vec![value; size])
 }
 
-#[tracing::instrument(skip_all)]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn unsafe_allocate_zero_vec<F: JoltField + Sized>(size: usize) -> Vec<F> {
     // https://stackoverflow.com/questions/59314686/how-to-efficiently-create-a-large-vector-of-items-initialized-to-the-same-value
 
@@ -45,7 +49,7 @@ pub fn unsafe_allocate_zero_vec<F: JoltField + Sized>(size: usize) -> Vec<F> {
     result
 }
 
-#[tracing::instrument(skip_all)]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn unsafe_allocate_sparse_zero_vec<F: JoltField + Sized>(size: usize) -> Vec<(F, usize)> {
     // Check for safety of 0 allocation
     unsafe {
@@ -70,7 +74,7 @@ pub fn unsafe_allocate_sparse_zero_vec<F: JoltField + Sized>(size: usize) -> Vec
     result
 }
 
-#[tracing::instrument(skip_all)]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn par_flatten_triple<T: Send + Sync + Copy, F: Fn(usize) -> Vec<T>>(
     triple: Vec<(Vec<T>, Vec<T>, Vec<T>)>,
     allocate: F,
@@ -132,6 +136,22 @@ where
     RB: Send,
     RC: Send,
 {
-    let (res_a, (res_b, res_c)) = rayon::join(oper_a, || rayon::join(oper_b, oper_c));
+    let (res_a, (res_b, res_c)) = par::join(oper_a, || par::join(oper_b, oper_c));
     (res_a, res_b, res_c)
 }
+
+/// Splits each of `buffers` into chunks of `chunk_size` and groups the `i`-th chunk of every
+/// buffer together, so that disjoint ranges of all the buffers can be filled in parallel.
+pub fn transpose_chunks_mut<T>(buffers: &mut [Vec<T>], chunk_size: usize) -> Vec<Vec<&mut [T]>> {
+    let num_buffers = buffers.len();
+    let mut chunks: Vec<Vec<&mut [T]>> = vec![];
+    for buffer in buffers.iter_mut() {
+        for (i, chunk) in buffer.chunks_mut(chunk_size).enumerate() {
+            if i == chunks.len() {
+                chunks.push(Vec::with_capacity(num_buffers));
+            }
+            chunks[i].push(chunk);
+        }
+    }
+    chunks
+}
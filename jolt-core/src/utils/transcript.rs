@@ -1,6 +1,13 @@
 use crate::field::JoltField;
+use alloc::vec;
+use alloc::vec::Vec;
 use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::Zero;
+#[cfg(feature = "poseidon")]
+use ark_ff::{BigInteger, PrimeField};
 use ark_serialize::CanonicalSerialize;
+#[cfg(feature = "poseidon")]
+use light_poseidon::{Poseidon, PoseidonHasher};
 use sha3::{Digest, Keccak256};
 
 /// Represents the current state of the protocol's Fiat-Shamir transcript.
@@ -58,6 +65,13 @@ impl KeccakTranscript {
         self.update_state(rand);
     }
 
+    #[cfg(test)]
+    /// The complete history of the transcript's `state`, starting from the initial
+    /// (label-derived) state.
+    pub(crate) fn state_history(&self) -> &[[u8; 32]] {
+        &self.state_history
+    }
+
     fn update_state(&mut self, new_state: [u8; 32]) {
         self.state = new_state;
         self.n_rounds += 1;
@@ -133,44 +147,214 @@ impl Transcript for KeccakTranscript {
     }
 
     fn append_scalar<F: JoltField>(&mut self, scalar: &F) {
-        let mut buf = vec![];
-        scalar.serialize_uncompressed(&mut buf).unwrap();
-        // Serialize uncompressed gives the scalar in LE byte order which is not
-        // a natural representation in the EVM for scalar math so we reverse
-        // to get an EVM compatible version.
+        self.append_bytes(&scalar_to_be_bytes(scalar));
+    }
+
+    fn append_point<G: CurveGroup>(&mut self, point: &G) {
+        self.append_bytes(&point_to_be_bytes(point));
+    }
+
+    fn challenge_scalar<F: JoltField>(&mut self) -> F {
+        let mut buf = vec![0u8; F::NUM_BYTES];
+        self.challenge_bytes(&mut buf);
+        // Because onchain we don't want to do the bit reversal to get the LE ordering
+        // we reverse here so that the random is BE ordering.
         buf = buf.into_iter().rev().collect();
-        self.append_bytes(&buf);
+        F::from_bytes(&buf)
     }
 
-    fn append_scalars<F: JoltField>(&mut self, scalars: &[F]) {
-        self.append_message(b"begin_append_vector");
-        for item in scalars.iter() {
-            self.append_scalar(item);
+    fn fork(&self, label: &'static [u8]) -> Self {
+        let mut fork = self.clone();
+        // The fork diverges from the expected history of the transcript it was forked from
+        #[cfg(test)]
+        {
+            fork.expected_state_history = None;
         }
-        self.append_message(b"end_append_vector");
+        fork.append_message(label);
+        fork
+    }
+}
+
+/// A transcript backed by Merlin's STROBE construction, for verifiers outside the EVM.
+#[cfg(feature = "merlin")]
+#[derive(Clone)]
+pub struct MerlinTranscript(merlin::Transcript);
+
+#[cfg(feature = "merlin")]
+impl Transcript for MerlinTranscript {
+    fn new(label: &'static [u8]) -> Self {
+        Self(merlin::Transcript::new(label))
+    }
+
+    #[cfg(test)]
+    /// Only `KeccakTranscript` records its state history, so there is nothing to compare.
+    fn compare_to(&mut self, _other: Self) {}
+
+    fn append_message(&mut self, msg: &'static [u8]) {
+        self.0.append_message(b"msg", msg);
+    }
+
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        self.0.append_message(b"bytes", bytes);
+    }
+
+    fn append_u64(&mut self, x: u64) {
+        self.0.append_u64(b"u64", x);
+    }
+
+    fn append_scalar<F: JoltField>(&mut self, scalar: &F) {
+        self.0
+            .append_message(b"scalar", &scalar_to_be_bytes(scalar));
     }
 
     fn append_point<G: CurveGroup>(&mut self, point: &G) {
-        // If we add the point at infinity then we hash over a region of zeros
-        if point.is_zero() {
-            self.append_bytes(&[0_u8; 64]);
-            return;
+        self.0.append_message(b"point", &point_to_be_bytes(point));
+    }
+
+    fn challenge_scalar<F: JoltField>(&mut self) -> F {
+        let mut buf = vec![0u8; F::NUM_BYTES];
+        self.0.challenge_bytes(b"challenge", &mut buf);
+        F::from_bytes(&buf)
+    }
+}
+
+/// A transcript whose state is a BN254 scalar, updated with the circom-compatible Poseidon
+/// hash, so that a verifier circuit over BN254 can replay it without emulating a bit-oriented
+/// hash. Bytes are absorbed 31 at a time, each chunk as one field element.
+#[cfg(feature = "poseidon")]
+#[derive(Clone)]
+pub struct PoseidonTranscript {
+    state: ark_bn254::Fr,
+    /// We absorb an ordinal with each invocation of the hash
+    n_rounds: u64,
+}
+
+/// Bytes per absorbed field element, so that every chunk is below the BN254 scalar modulus
+#[cfg(feature = "poseidon")]
+const POSEIDON_CHUNK_BYTES: usize = 31;
+
+#[cfg(feature = "poseidon")]
+impl PoseidonTranscript {
+    fn absorb(&mut self, element: ark_bn254::Fr) {
+        let mut hasher = Poseidon::<ark_bn254::Fr>::new_circom(3).unwrap();
+        self.state = hasher
+            .hash(&[self.state, ark_bn254::Fr::from(self.n_rounds), element])
+            .unwrap();
+        self.n_rounds += 1;
+    }
+
+    fn squeeze(&mut self) -> ark_bn254::Fr {
+        self.absorb(ark_bn254::Fr::zero());
+        self.state
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl Transcript for PoseidonTranscript {
+    fn new(label: &'static [u8]) -> Self {
+        assert!(label.len() <= POSEIDON_CHUNK_BYTES);
+        Self {
+            state: ark_bn254::Fr::from_be_bytes_mod_order(label),
+            n_rounds: 0,
         }
+    }
 
-        let aff = point.into_affine();
-        let mut x_bytes = vec![];
-        let mut y_bytes = vec![];
-        // The native serialize for the points are le encoded in x,y format and simply reversing
-        // can lead to errors so we extract the affine coordinates and the encode them be before writing
-        let x = aff.x().unwrap();
-        x.serialize_compressed(&mut x_bytes).unwrap();
-        x_bytes = x_bytes.into_iter().rev().collect();
-        let y = aff.y().unwrap();
-        y.serialize_compressed(&mut y_bytes).unwrap();
-        y_bytes = y_bytes.into_iter().rev().collect();
-
-        let hasher = self.hasher().chain_update(x_bytes).chain_update(y_bytes);
-        self.update_state(hasher.finalize().into());
+    #[cfg(test)]
+    /// Only `KeccakTranscript` records its state history, so there is nothing to compare.
+    fn compare_to(&mut self, _other: Self) {}
+
+    fn append_message(&mut self, msg: &'static [u8]) {
+        assert!(msg.len() <= POSEIDON_CHUNK_BYTES);
+        self.absorb(ark_bn254::Fr::from_be_bytes_mod_order(msg));
+    }
+
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        // The length makes the encoding injective despite the zero-padded last chunk
+        self.append_u64(bytes.len() as u64);
+        for chunk in bytes.chunks(POSEIDON_CHUNK_BYTES) {
+            self.absorb(ark_bn254::Fr::from_be_bytes_mod_order(chunk));
+        }
+    }
+
+    fn append_u64(&mut self, x: u64) {
+        self.absorb(ark_bn254::Fr::from(x));
+    }
+
+    fn append_scalar<F: JoltField>(&mut self, scalar: &F) {
+        self.append_bytes(&scalar_to_be_bytes(scalar));
+    }
+
+    fn append_point<G: CurveGroup>(&mut self, point: &G) {
+        self.append_bytes(&point_to_be_bytes(point));
+    }
+
+    fn challenge_scalar<F: JoltField>(&mut self) -> F {
+        // The low 31 bytes of each squeezed element are close to uniform
+        let mut buf = Vec::with_capacity(F::NUM_BYTES + POSEIDON_CHUNK_BYTES);
+        while buf.len() < F::NUM_BYTES {
+            let bytes = self.squeeze().into_bigint().to_bytes_le();
+            buf.extend_from_slice(&bytes[..POSEIDON_CHUNK_BYTES]);
+        }
+        buf.truncate(F::NUM_BYTES);
+        F::from_bytes(&buf)
+    }
+}
+
+/// The scalar's canonical encoding, big-endian as is natural for scalar math in the EVM.
+pub(crate) fn scalar_to_be_bytes<F: JoltField>(scalar: &F) -> Vec<u8> {
+    let mut buf = vec![];
+    scalar.serialize_uncompressed(&mut buf).unwrap();
+    // Serialize uncompressed gives the scalar in LE byte order, so we reverse it
+    buf.reverse();
+    buf
+}
+
+/// The point's affine coordinates, each big-endian, or 64 zero bytes for the point at infinity.
+pub(crate) fn point_to_be_bytes<G: CurveGroup>(point: &G) -> Vec<u8> {
+    if point.is_zero() {
+        return vec![0_u8; 64];
+    }
+
+    let aff = point.into_affine();
+    let mut x_bytes = vec![];
+    let mut y_bytes = vec![];
+    // The native serialize for the points are le encoded in x,y format and simply reversing
+    // can lead to errors so we extract the affine coordinates and the encode them be before writing
+    let x = aff.x().unwrap();
+    x.serialize_compressed(&mut x_bytes).unwrap();
+    x_bytes.reverse();
+    let y = aff.y().unwrap();
+    y.serialize_compressed(&mut y_bytes).unwrap();
+    y_bytes.reverse();
+    x_bytes.append(&mut y_bytes);
+    x_bytes
+}
+
+pub trait Transcript: Clone + Sync + Send + 'static {
+    fn new(label: &'static [u8]) -> Self;
+    #[cfg(test)]
+    fn compare_to(&mut self, other: Self);
+    fn append_message(&mut self, msg: &'static [u8]);
+    fn append_bytes(&mut self, bytes: &[u8]);
+    fn append_u64(&mut self, x: u64);
+    fn append_scalar<F: JoltField>(&mut self, scalar: &F);
+    fn append_point<G: CurveGroup>(&mut self, point: &G);
+    fn challenge_scalar<F: JoltField>(&mut self) -> F;
+
+    /// Derives an independent transcript from the current state, labeled by `label`. Used to
+    /// prove (and verify) independent parts of the protocol on separate transcripts.
+    fn fork(&self, label: &'static [u8]) -> Self {
+        let mut fork = self.clone();
+        fork.append_message(label);
+        fork
+    }
+
+    fn append_scalars<F: JoltField>(&mut self, scalars: &[F]) {
+        self.append_message(b"begin_append_vector");
+        for item in scalars.iter() {
+            self.append_scalar(item);
+        }
+        self.append_message(b"end_append_vector");
     }
 
     fn append_points<G: CurveGroup>(&mut self, points: &[G]) {
@@ -181,15 +365,6 @@ impl Transcript for KeccakTranscript {
         self.append_message(b"end_append_vector");
     }
 
-    fn challenge_scalar<F: JoltField>(&mut self) -> F {
-        let mut buf = vec![0u8; F::NUM_BYTES];
-        self.challenge_bytes(&mut buf);
-        // Because onchain we don't want to do the bit reversal to get the LE ordering
-        // we reverse here so that the random is BE ordering.
-        buf = buf.into_iter().rev().collect();
-        F::from_bytes(&buf)
-    }
-
     fn challenge_vector<F: JoltField>(&mut self, len: usize) -> Vec<F> {
         (0..len)
             .map(|_i| self.challenge_scalar())
@@ -207,23 +382,57 @@ impl Transcript for KeccakTranscript {
     }
 }
 
-pub trait Transcript: Clone + Sync + Send + 'static {
-    fn new(label: &'static [u8]) -> Self;
-    #[cfg(test)]
-    fn compare_to(&mut self, other: Self);
-    fn append_message(&mut self, msg: &'static [u8]);
-    fn append_bytes(&mut self, bytes: &[u8]);
-    fn append_u64(&mut self, x: u64);
-    fn append_scalar<F: JoltField>(&mut self, scalar: &F);
-    fn append_scalars<F: JoltField>(&mut self, scalars: &[F]);
-    fn append_point<G: CurveGroup>(&mut self, point: &G);
-    fn append_points<G: CurveGroup>(&mut self, points: &[G]);
-    fn challenge_scalar<F: JoltField>(&mut self) -> F;
-    fn challenge_vector<F: JoltField>(&mut self, len: usize) -> Vec<F>;
-    // Compute powers of scalar q : (1, q, q^2, ..., q^(len-1))
-    fn challenge_scalar_powers<F: JoltField>(&mut self, len: usize) -> Vec<F>;
-}
-
 pub trait AppendToTranscript {
     fn append_to_transcript<ProofTranscript: Transcript>(&self, transcript: &mut ProofTranscript);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::dense_mlpoly::DensePolynomial;
+    use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+    use ark_bn254::{Fr, G1Projective};
+    use ark_std::test_rng;
+
+    fn sumcheck_roundtrip<ProofTranscript: Transcript>() {
+        const NUM_VARS: usize = 4;
+        let mut rng = test_rng();
+        let polys: Vec<DensePolynomial<Fr>> = (0..2)
+            .map(|_| DensePolynomial::random(NUM_VARS, &mut rng))
+            .collect();
+        let claim: Fr = (0..1 << NUM_VARS).map(|i| polys[0][i] * polys[1][i]).sum();
+
+        let mut prover_transcript = ProofTranscript::new(b"test");
+        prover_transcript.append_point(&G1Projective::zero());
+        let (proof, r_prover, _) = SumcheckInstanceProof::prove_arbitrary(
+            &claim,
+            NUM_VARS,
+            &mut polys.clone(),
+            |vals: &[Fr]| vals[0] * vals[1],
+            2,
+            &mut prover_transcript,
+        );
+
+        let mut verifier_transcript = ProofTranscript::new(b"test");
+        verifier_transcript.append_point(&G1Projective::zero());
+        let (_, r) = proof
+            .verify(claim, NUM_VARS, 2, &mut verifier_transcript)
+            .unwrap();
+        assert_eq!(r, r_prover);
+
+        // Diverging transcripts give different challenges
+        verifier_transcript.append_u64(0);
+        let r_prover: Fr = prover_transcript.challenge_scalar();
+        let r: Fr = verifier_transcript.challenge_scalar();
+        assert_ne!(r, r_prover);
+    }
+
+    #[test]
+    fn transcript_instantiations() {
+        sumcheck_roundtrip::<KeccakTranscript>();
+        #[cfg(feature = "merlin")]
+        sumcheck_roundtrip::<MerlinTranscript>();
+        #[cfg(feature = "poseidon")]
+        sumcheck_roundtrip::<PoseidonTranscript>();
+    }
+}
@@ -0,0 +1,284 @@
+//! A transcript wrapper that records every operation, for debugging Fiat-Shamir mismatches.
+//!
+//! A prover and verifier whose transcripts diverge only notice when a sum-check or opening
+//! check fails, possibly much later. Running both with a `LoggingTranscript` and diffing the
+//! logs with [`first_divergence`] instead pinpoints the first operation on which they disagree,
+//! along with the call sites that issued it:
+//!
+//! ```ignore
+//! let divergence = first_divergence(prover_transcript.log(), verifier_transcript.log());
+//! if let Some(divergence) = divergence {
+//!     panic!("{divergence}");
+//! }
+//! ```
+use std::fmt;
+use std::panic::Location;
+
+use ark_ec::CurveGroup;
+use ark_serialize::CanonicalSerialize;
+use sha3::{Digest, Keccak256};
+
+use crate::field::JoltField;
+use crate::utils::transcript::{point_to_be_bytes, scalar_to_be_bytes, Transcript};
+
+/// One operation on a transcript
+#[derive(Clone, Debug)]
+pub struct TranscriptEntry {
+    /// Name of the `Transcript` method
+    pub operation: &'static str,
+    /// The message of `append_message`, which labels the operations that follow it
+    pub label: Option<&'static [u8]>,
+    /// Keccak256 of the appended data
+    pub data_hash: [u8; 32],
+    /// The serialized challenges, for squeezes
+    pub challenges: Option<Vec<u8>>,
+    /// Where the operation was issued
+    pub location: &'static Location<'static>,
+}
+
+impl TranscriptEntry {
+    /// Whether the entries append the same data or squeeze the same challenges, regardless of
+    /// where they were issued.
+    fn agrees_with(&self, other: &Self) -> bool {
+        self.operation == other.operation
+            && self.label == other.label
+            && self.data_hash == other.data_hash
+            && self.challenges == other.challenges
+    }
+}
+
+impl fmt::Display for TranscriptEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.operation)?;
+        if let Some(label) = self.label {
+            write!(f, "({:?})", String::from_utf8_lossy(label))?;
+        }
+        write!(f, " data {}", hex(&self.data_hash[..8]))?;
+        if let Some(challenges) = &self.challenges {
+            write!(f, " -> {}", hex(&challenges[..challenges.len().min(8)]))?;
+        }
+        write!(f, " at {}", self.location)
+    }
+}
+
+/// The first operation on which two transcript logs disagree. An entry is `None` if its log
+/// ended before the other.
+#[derive(Debug)]
+pub struct TranscriptDivergence<'a> {
+    pub index: usize,
+    pub left: Option<&'a TranscriptEntry>,
+    pub right: Option<&'a TranscriptEntry>,
+    /// The last `append_message` label before the divergence, if any
+    pub last_label: Option<&'static [u8]>,
+}
+
+impl fmt::Display for TranscriptDivergence<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transcripts diverge at operation {}", self.index)?;
+        if let Some(label) = self.last_label {
+            write!(f, " (after {:?})", String::from_utf8_lossy(label))?;
+        }
+        for (side, entry) in [("left", self.left), ("right", self.right)] {
+            match entry {
+                Some(entry) => write!(f, "\n  {side}: {entry}")?,
+                None => write!(f, "\n  {side}: <end of log>")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares two transcript logs, e.g. the prover's and the verifier's, and returns the first
+/// operation on which they disagree.
+pub fn first_divergence<'a>(
+    left: &'a [TranscriptEntry],
+    right: &'a [TranscriptEntry],
+) -> Option<TranscriptDivergence<'a>> {
+    let index = (0..left.len().max(right.len())).find(|&i| match (left.get(i), right.get(i)) {
+        (Some(left), Some(right)) => !left.agrees_with(right),
+        _ => true,
+    })?;
+    let last_label = left[..index.min(left.len())]
+        .iter()
+        .rev()
+        .find_map(|entry| entry.label);
+    Some(TranscriptDivergence {
+        index,
+        left: left.get(index),
+        right: right.get(index),
+        last_label,
+    })
+}
+
+/// Wraps a transcript, recording each operation to a log before forwarding it.
+#[derive(Clone)]
+pub struct LoggingTranscript<T: Transcript> {
+    inner: T,
+    log: Vec<TranscriptEntry>,
+}
+
+impl<T: Transcript> LoggingTranscript<T> {
+    pub fn log(&self) -> &[TranscriptEntry] {
+        &self.log
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    #[track_caller]
+    fn record(&mut self, operation: &'static str, data: &[u8], challenges: Option<Vec<u8>>) {
+        self.log.push(TranscriptEntry {
+            operation,
+            label: None,
+            data_hash: Keccak256::digest(data).into(),
+            challenges,
+            location: Location::caller(),
+        });
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn serialize_challenges<F: JoltField>(challenges: &[F]) -> Vec<u8> {
+    let mut bytes = vec![];
+    for challenge in challenges {
+        challenge.serialize_compressed(&mut bytes).unwrap();
+    }
+    bytes
+}
+
+impl<T: Transcript> Transcript for LoggingTranscript<T> {
+    #[track_caller]
+    fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Self {
+            inner: T::new(label),
+            log: vec![],
+        };
+        transcript.record("new", label, None);
+        transcript.log[0].label = Some(label);
+        transcript
+    }
+
+    #[cfg(test)]
+    fn compare_to(&mut self, other: Self) {
+        self.inner.compare_to(other.inner);
+    }
+
+    #[track_caller]
+    fn append_message(&mut self, msg: &'static [u8]) {
+        self.record("append_message", msg, None);
+        self.log.last_mut().unwrap().label = Some(msg);
+        self.inner.append_message(msg);
+    }
+
+    #[track_caller]
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        self.record("append_bytes", bytes, None);
+        self.inner.append_bytes(bytes);
+    }
+
+    #[track_caller]
+    fn append_u64(&mut self, x: u64) {
+        self.record("append_u64", &x.to_be_bytes(), None);
+        self.inner.append_u64(x);
+    }
+
+    #[track_caller]
+    fn append_scalar<F: JoltField>(&mut self, scalar: &F) {
+        self.record("append_scalar", &scalar_to_be_bytes(scalar), None);
+        self.inner.append_scalar(scalar);
+    }
+
+    #[track_caller]
+    fn append_scalars<F: JoltField>(&mut self, scalars: &[F]) {
+        let data: Vec<u8> = scalars.iter().flat_map(scalar_to_be_bytes).collect();
+        self.record("append_scalars", &data, None);
+        self.inner.append_scalars(scalars);
+    }
+
+    #[track_caller]
+    fn append_point<G: CurveGroup>(&mut self, point: &G) {
+        self.record("append_point", &point_to_be_bytes(point), None);
+        self.inner.append_point(point);
+    }
+
+    #[track_caller]
+    fn append_points<G: CurveGroup>(&mut self, points: &[G]) {
+        let data: Vec<u8> = points.iter().flat_map(point_to_be_bytes).collect();
+        self.record("append_points", &data, None);
+        self.inner.append_points(points);
+    }
+
+    #[track_caller]
+    fn challenge_scalar<F: JoltField>(&mut self) -> F {
+        let challenge: F = self.inner.challenge_scalar();
+        self.record(
+            "challenge_scalar",
+            &[],
+            Some(serialize_challenges(&[challenge])),
+        );
+        challenge
+    }
+
+    #[track_caller]
+    fn challenge_vector<F: JoltField>(&mut self, len: usize) -> Vec<F> {
+        let challenges: Vec<F> = self.inner.challenge_vector(len);
+        self.record(
+            "challenge_vector",
+            &len.to_be_bytes(),
+            Some(serialize_challenges(&challenges)),
+        );
+        challenges
+    }
+
+    #[track_caller]
+    fn challenge_scalar_powers<F: JoltField>(&mut self, len: usize) -> Vec<F> {
+        let powers: Vec<F> = self.inner.challenge_scalar_powers(len);
+        // The first two powers, 1 and q, determine the rest
+        self.record(
+            "challenge_scalar_powers",
+            &len.to_be_bytes(),
+            Some(serialize_challenges(&powers[..len.min(2)])),
+        );
+        powers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::transcript::KeccakTranscript;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn pinpoints_divergence() {
+        let mut prover = LoggingTranscript::<KeccakTranscript>::new(b"test");
+        let mut verifier = LoggingTranscript::<KeccakTranscript>::new(b"test");
+        for transcript in [&mut prover, &mut verifier] {
+            transcript.append_message(b"claims");
+            transcript.append_scalars(&[Fr::from(1u64), Fr::from(2u64)]);
+            let _: Fr = transcript.challenge_scalar();
+        }
+        assert!(first_divergence(prover.log(), verifier.log()).is_none());
+
+        prover.append_u64(3);
+        verifier.append_u64(4);
+        let prover_challenge: Fr = prover.challenge_scalar();
+        let verifier_challenge: Fr = verifier.challenge_scalar();
+        assert_ne!(prover_challenge, verifier_challenge);
+
+        let divergence = first_divergence(prover.log(), verifier.log()).unwrap();
+        assert_eq!(divergence.index, 4);
+        assert_eq!(divergence.left.unwrap().operation, "append_u64");
+        assert_eq!(divergence.last_label, Some(&b"claims"[..]));
+        assert!(divergence.to_string().contains(file!()));
+
+        // A log that ends early diverges where it ends
+        let divergence = first_divergence(&prover.log()[..3], verifier.log()).unwrap();
+        assert_eq!(divergence.index, 3);
+        assert!(divergence.left.is_none());
+    }
+}
@@ -0,0 +1,268 @@
+//! Fiat-Shamir transcript snapshot tests.
+//!
+//! Each test runs a protocol on a small, fixed instance (generated from a fixed seed)
+//! and compares the full history of the prover's transcript state against a fixture in
+//! `jolt-core/fixtures/transcripts`. Any change to the order or labels of transcript
+//! operations changes the challenges, and thus breaks compatibility with existing
+//! proofs and verifiers (e.g. the Solidity verifier) –– these tests make such changes
+//! explicit.
+//!
+//! The Spartan and full Jolt proof snapshots run the fibonacci guest, and so live with the
+//! other end-to-end tests in `jolt::vm::rv32i_vm`.
+//!
+//! If a change to the transcript is intentional, regenerate the fixtures with:
+//!     JOLT_UPDATE_TRANSCRIPT_SNAPSHOTS=1 cargo test -p jolt-core transcript_snapshot
+
+use std::fs;
+use std::path::PathBuf;
+
+use ark_bn254::{Bn254, Fr, G1Projective};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
+
+use crate::field::JoltField;
+use crate::jolt::instruction::xor::XORInstruction;
+use crate::lasso::surge::{SurgePreprocessing, SurgeProof};
+use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use crate::poly::commitment::hyperkzg::{HyperKZG, HyperKZGSRS};
+use crate::poly::commitment::hyrax::HyraxScheme;
+use crate::poly::commitment::zeromorph::{Zeromorph, ZeromorphSRS};
+use crate::poly::dense_mlpoly::DensePolynomial;
+use crate::poly::opening_proof::ProverOpeningAccumulator;
+use crate::subprotocols::grand_product::{BatchedDenseGrandProduct, BatchedGrandProduct};
+use crate::subprotocols::grand_product_quarks::{QuarkGrandProduct, QuarkGrandProductConfig};
+use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+use crate::subprotocols::QuarkHybridLayerDepth;
+use crate::utils::transcript::{KeccakTranscript, Transcript};
+
+const UPDATE_SNAPSHOTS_ENV: &str = "JOLT_UPDATE_TRANSCRIPT_SNAPSHOTS";
+const SEED: u64 = 0x4a4f4c54;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join("transcripts")
+        .join(format!("{name}.txt"))
+}
+
+/// Compares the state history of `transcript` against the fixture `name`, panicking
+/// with the index of the first diverging transcript state if they differ.
+pub(crate) fn assert_transcript_snapshot(name: &str, transcript: &KeccakTranscript) {
+    let history: Vec<String> = transcript
+        .state_history()
+        .iter()
+        .map(|state| state.iter().map(|byte| format!("{byte:02x}")).collect())
+        .collect();
+    let path = fixture_path(name);
+
+    if std::env::var(UPDATE_SNAPSHOTS_ENV).is_ok() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, history.join("\n") + "\n").unwrap();
+        return;
+    }
+
+    let fixture = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "transcript snapshot fixture {} could not be read ({err}). \
+             Generate it with `{UPDATE_SNAPSHOTS_ENV}=1 cargo test -p jolt-core transcript_snapshot` \
+             and commit the resulting file",
+            path.display(),
+        )
+    });
+    let expected: Vec<&str> = fixture.lines().collect();
+    for (i, (actual, expected)) in history.iter().zip(expected.iter()).enumerate() {
+        assert_eq!(
+            actual, expected,
+            "transcript snapshot {name} diverges at state {i}"
+        );
+    }
+    assert_eq!(
+        history.len(),
+        expected.len(),
+        "transcript snapshot {name} has a different number of states"
+    );
+}
+
+fn random_poly(num_vars: usize, rng: &mut ChaCha20Rng) -> DensePolynomial<Fr> {
+    DensePolynomial::new(
+        std::iter::repeat_with(|| Fr::random(rng))
+            .take(1 << num_vars)
+            .collect(),
+    )
+}
+
+#[test]
+fn sumcheck() {
+    const NUM_VARS: usize = 4;
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let mut polys = vec![
+        random_poly(NUM_VARS, &mut rng),
+        random_poly(NUM_VARS, &mut rng),
+    ];
+    let claim: Fr = polys[0]
+        .evals_ref()
+        .iter()
+        .zip(polys[1].evals_ref().iter())
+        .map(|(a, b)| *a * b)
+        .sum();
+
+    let mut transcript = KeccakTranscript::new(b"snapshot_sumcheck");
+    let _ = SumcheckInstanceProof::<Fr, KeccakTranscript>::prove_arbitrary(
+        &claim,
+        NUM_VARS,
+        &mut polys,
+        |vals: &[Fr]| vals[0] * vals[1],
+        2,
+        &mut transcript,
+    );
+    assert_transcript_snapshot("sumcheck", &transcript);
+}
+
+#[test]
+fn dense_grand_product() {
+    const LAYER_SIZE: usize = 1 << 4;
+    const BATCH_SIZE: usize = 2;
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let leaves: Vec<Fr> = std::iter::repeat_with(|| Fr::random(&mut rng))
+        .take(LAYER_SIZE * BATCH_SIZE)
+        .collect();
+
+    let mut transcript = KeccakTranscript::new(b"snapshot_dense_grand_product");
+    let mut circuit = <BatchedDenseGrandProduct<Fr> as BatchedGrandProduct<
+        Fr,
+        Zeromorph<Bn254, KeccakTranscript>,
+        KeccakTranscript,
+    >>::construct((leaves, BATCH_SIZE));
+    let _ = <BatchedDenseGrandProduct<Fr> as BatchedGrandProduct<
+        Fr,
+        Zeromorph<Bn254, KeccakTranscript>,
+        KeccakTranscript,
+    >>::prove_grand_product(&mut circuit, None, &mut transcript, None);
+    assert_transcript_snapshot("dense_grand_product", &transcript);
+}
+
+#[test]
+fn quark_grand_product() {
+    const LAYER_SIZE: usize = 1 << 6;
+    const BATCH_SIZE: usize = 2;
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let leaves: Vec<Fr> = std::iter::repeat_with(|| Fr::random(&mut rng))
+        .take(LAYER_SIZE * BATCH_SIZE)
+        .collect();
+    let (pk, vk) = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << 7).trim(1 << 7);
+    let setup = (pk, vk.prepare());
+
+    let mut transcript = KeccakTranscript::new(b"snapshot_quark_grand_product");
+    let mut accumulator: ProverOpeningAccumulator<Fr, KeccakTranscript> =
+        ProverOpeningAccumulator::new();
+    let mut circuit = <QuarkGrandProduct<Fr, KeccakTranscript> as BatchedGrandProduct<
+        Fr,
+        Zeromorph<Bn254, KeccakTranscript>,
+        KeccakTranscript,
+    >>::construct_with_config(
+        (leaves, BATCH_SIZE),
+        QuarkGrandProductConfig {
+            hybrid_layer_depth: QuarkHybridLayerDepth::Min,
+        },
+    );
+    let _ = <QuarkGrandProduct<Fr, KeccakTranscript> as BatchedGrandProduct<
+        Fr,
+        Zeromorph<Bn254, KeccakTranscript>,
+        KeccakTranscript,
+    >>::prove_grand_product(
+        &mut circuit,
+        Some(&mut accumulator),
+        &mut transcript,
+        Some(&setup),
+    );
+    let _ =
+        accumulator.reduce_and_prove::<Zeromorph<Bn254, KeccakTranscript>>(&setup, &mut transcript);
+    assert_transcript_snapshot("quark_grand_product", &transcript);
+}
+
+#[test]
+fn zeromorph_open() {
+    const NUM_VARS: usize = 5;
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let poly = random_poly(NUM_VARS, &mut rng);
+    let point: Vec<Fr> = std::iter::repeat_with(|| Fr::random(&mut rng))
+        .take(NUM_VARS)
+        .collect();
+    let eval = poly.evaluate(&point);
+    let (pk, _) = ZeromorphSRS::<Bn254>::setup(&mut rng, 1 << NUM_VARS).trim(1 << NUM_VARS);
+
+    let mut transcript = KeccakTranscript::new(b"snapshot_zeromorph");
+    Zeromorph::<Bn254, KeccakTranscript>::open(&pk, &poly, &point, &eval, &mut transcript).unwrap();
+    assert_transcript_snapshot("zeromorph_open", &transcript);
+}
+
+#[test]
+fn hyperkzg_open() {
+    const NUM_VARS: usize = 5;
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let poly = random_poly(NUM_VARS, &mut rng);
+    let point: Vec<Fr> = std::iter::repeat_with(|| Fr::random(&mut rng))
+        .take(NUM_VARS)
+        .collect();
+    let eval = poly.evaluate(&point);
+    let (pk, _) = HyperKZGSRS::<Bn254>::setup(&mut rng, 1 << NUM_VARS).trim(1 << NUM_VARS);
+
+    let mut transcript = KeccakTranscript::new(b"snapshot_hyperkzg");
+    HyperKZG::<Bn254, KeccakTranscript>::open(&pk, &poly, &point, &eval, &mut transcript).unwrap();
+    assert_transcript_snapshot("hyperkzg_open", &transcript);
+}
+
+#[test]
+fn hyrax_batch_open() {
+    const NUM_VARS: usize = 6;
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let polys = [
+        random_poly(NUM_VARS, &mut rng),
+        random_poly(NUM_VARS, &mut rng),
+    ];
+    let point: Vec<Fr> = std::iter::repeat_with(|| Fr::random(&mut rng))
+        .take(NUM_VARS)
+        .collect();
+    let evals: Vec<Fr> = polys.iter().map(|poly| poly.evaluate(&point)).collect();
+    let setup = <HyraxScheme<G1Projective, KeccakTranscript> as CommitmentScheme<
+        KeccakTranscript,
+    >>::setup(&[CommitShape::new(1 << NUM_VARS, BatchType::Big)]);
+
+    let mut transcript = KeccakTranscript::new(b"snapshot_hyrax");
+    let _ = HyraxScheme::<G1Projective, KeccakTranscript>::batch_prove(
+        &setup,
+        &polys.iter().collect::<Vec<_>>(),
+        &point,
+        &evals,
+        BatchType::Big,
+        &mut transcript,
+    );
+    assert_transcript_snapshot("hyrax_batch_open", &transcript);
+}
+
+#[test]
+fn surge_memory_checking() {
+    const WORD_SIZE: usize = 32;
+    const C: usize = 4;
+    const M: usize = 1 << 16;
+    const NUM_OPS: usize = 64;
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let ops = std::iter::repeat_with(|| {
+        XORInstruction::<WORD_SIZE>(rng.next_u32() as u64, rng.next_u32() as u64)
+    })
+    .take(NUM_OPS)
+    .collect();
+
+    let preprocessing = SurgePreprocessing::preprocess();
+    let generators =
+        HyperKZG::<_, KeccakTranscript>::setup(&[CommitShape::new(M, BatchType::SurgeReadWrite)]);
+    let (_, debug_info) = SurgeProof::<
+        Fr,
+        HyperKZG<Bn254, KeccakTranscript>,
+        XORInstruction<WORD_SIZE>,
+        C,
+        M,
+        KeccakTranscript,
+    >::prove(&preprocessing, &generators, ops);
+    assert_transcript_snapshot("surge_memory_checking", &debug_info.unwrap().transcript);
+}
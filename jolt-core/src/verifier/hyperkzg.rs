@@ -0,0 +1,177 @@
+use alloc::vec::Vec;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, VariableBaseMSM};
+use ark_std::{One, Zero};
+
+use crate::field::JoltField;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::transcript::Transcript;
+
+/// The G1 inputs of the pairing check of a HyperKZG opening of `commitment` to `evaluation` at
+/// `point`, to be paired against `[1]_2` and `[beta]_2` respectively. `com`, `w` and `v` are
+/// the proof's commitments to the folded polynomials, its KZG witnesses and its evaluations.
+///
+/// Performs all of the verifier's checks except for the pairing check itself.
+#[allow(clippy::too_many_arguments)]
+pub fn hyperkzg_pairing_check<P: Pairing, ProofTranscript: Transcript>(
+    g1: &P::G1Affine,
+    commitment: &P::G1Affine,
+    point: &[P::ScalarField],
+    evaluation: &P::ScalarField,
+    com: &[P::G1Affine],
+    w: &[P::G1Affine],
+    v: &[Vec<P::ScalarField>],
+    transcript: &mut ProofTranscript,
+) -> Result<[P::G1; 2], ProofVerifyError>
+where
+    P::ScalarField: JoltField,
+{
+    let ell = point.len();
+
+    // we do not need to add x to the transcript, because in our context x was
+    // obtained from the transcript
+    transcript.append_points(&com.iter().map(|g| g.into_group()).collect::<Vec<P::G1>>());
+    let r: P::ScalarField = transcript.challenge_scalar();
+
+    if r == P::ScalarField::zero() || *commitment == P::G1Affine::zero() {
+        return Err(ProofVerifyError::InternalError);
+    }
+    // com_0 = C, followed by the commitments to the folded polynomials
+    let com = [&[*commitment], com].concat();
+
+    let u = [r, -r, r * r];
+
+    // Setup vectors (Y, ypos, yneg) from v
+    if v.len() != 3 || w.len() != 3 {
+        return Err(ProofVerifyError::InternalError);
+    }
+    if v[0].len() != ell || v[1].len() != ell || v[2].len() != ell {
+        return Err(ProofVerifyError::InternalError);
+    }
+    let ypos = &v[0];
+    let yneg = &v[1];
+    let mut Y = v[2].to_vec();
+    Y.push(*evaluation);
+
+    // Check consistency of (Y, ypos, yneg)
+    let two = P::ScalarField::from(2u64);
+    for i in 0..ell {
+        if two * r * Y[i + 1]
+            != r * (P::ScalarField::one() - point[ell - i - 1]) * (ypos[i] + yneg[i])
+                + point[ell - i - 1] * (ypos[i] - yneg[i])
+        {
+            return Err(ProofVerifyError::InternalError);
+        }
+        // Note that we don't make any checks about Y[0] here, but our batching
+        // check below requires it
+    }
+
+    // Check commitments to (Y, ypos, yneg) are valid
+    Ok(kzg_batch_pairing_check::<P, ProofTranscript>(
+        g1, &com, w, &u, v, transcript,
+    ))
+}
+
+/// The G1 inputs of the pairing check of the batched KZG openings of the polynomials committed
+/// to in `C`, each at the three points `u`, with witnesses `W`. The key is hashed in the
+/// transcript already, so it is not added here.
+fn kzg_batch_pairing_check<P: Pairing, ProofTranscript: Transcript>(
+    g1: &P::G1Affine,
+    C: &[P::G1Affine],
+    W: &[P::G1Affine],
+    u: &[P::ScalarField; 3],
+    v: &[Vec<P::ScalarField>],
+    transcript: &mut ProofTranscript,
+) -> [P::G1; 2]
+where
+    P::ScalarField: JoltField,
+{
+    let k = C.len();
+
+    transcript.append_scalars(&v.iter().flatten().cloned().collect::<Vec<P::ScalarField>>());
+    let q_powers: Vec<P::ScalarField> = transcript.challenge_scalar_powers(k);
+
+    transcript.append_points(&W.iter().map(|g| g.into_group()).collect::<Vec<P::G1>>());
+    let d_0: P::ScalarField = transcript.challenge_scalar();
+    let d_1 = d_0 * d_0;
+
+    // We write a special case for t=3, since this what is required for
+    // hyperkzg. Following the paper directly, we must compute:
+    // let L0 = C_B - vk.G * B_u[0] + W[0] * u[0];
+    // let L1 = C_B - vk.G * B_u[1] + W[1] * u[1];
+    // let L2 = C_B - vk.G * B_u[2] + W[2] * u[2];
+    // let R0 = -W[0];
+    // let R1 = -W[1];
+    // let R2 = -W[2];
+    // let L = L0 + L1*d_0 + L2*d_1;
+    // let R = R0 + R1*d_0 + R2*d_1;
+    //
+    // We group terms to reduce the number of scalar mults (to seven):
+    // In Rust, we could use MSMs for these, and speed up verification.
+    //
+    // Note, that while computing L, the intermediate computation of C_B together with computing
+    // L0, L1, L2 can be replaced by single MSM of C with the powers of q multiplied by (1 + d_0 + d_1)
+    // with additionally concatenated inputs for scalars/bases.
+
+    let q_power_multiplier: P::ScalarField = P::ScalarField::one() + d_0 + d_1;
+
+    let q_powers_multiplied: Vec<P::ScalarField> = q_powers
+        .iter()
+        .map(|q_power| *q_power * q_power_multiplier)
+        .collect();
+
+    // Compute the batched openings
+    // compute B(u_i) = v[i][0] + q*v[i][1] + ... + q^(t-1) * v[i][t-1]
+    let B_u = v
+        .iter()
+        .map(|v_i| v_i.iter().zip(q_powers.iter()).map(|(a, b)| *a * *b).sum())
+        .collect::<Vec<P::ScalarField>>();
+
+    let L = <P::G1 as VariableBaseMSM>::msm(
+        &[&C[..k], &[W[0], W[1], W[2], *g1]].concat(),
+        &[
+            &q_powers_multiplied[..k],
+            &[
+                u[0],
+                (u[1] * d_0),
+                (u[2] * d_1),
+                -(B_u[0] + d_0 * B_u[1] + d_1 * B_u[2]),
+            ],
+        ]
+        .concat(),
+    )
+    .unwrap();
+
+    let R = W[0] + W[1] * d_0 + W[2] * d_1;
+
+    // Check that e(L, vk.H) == e(R, vk.tau_H)
+    [L, -R]
+}
+
+/// Checks a HyperKZG opening proof, given the key's `[1]_1` and its `[1]_2` and `[beta]_2`
+/// prepared for the pairing.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_hyperkzg_opening<P: Pairing, ProofTranscript: Transcript>(
+    g1: &P::G1Affine,
+    g2_prepared: P::G2Prepared,
+    beta_g2_prepared: P::G2Prepared,
+    commitment: &P::G1Affine,
+    point: &[P::ScalarField],
+    evaluation: &P::ScalarField,
+    com: &[P::G1Affine],
+    w: &[P::G1Affine],
+    v: &[Vec<P::ScalarField>],
+    transcript: &mut ProofTranscript,
+) -> Result<(), ProofVerifyError>
+where
+    P::ScalarField: JoltField,
+{
+    let pairing_check = hyperkzg_pairing_check::<P, ProofTranscript>(
+        g1, commitment, point, evaluation, com, w, v, transcript,
+    )?;
+    if P::multi_pairing(pairing_check, [g2_prepared, beta_g2_prepared]).is_zero() {
+        Ok(())
+    } else {
+        Err(ProofVerifyError::InternalError)
+    }
+}
@@ -0,0 +1,28 @@
+//! The verification-only code paths, which need only `core` and `alloc`.
+//!
+//! Built with `--no-default-features --features verifier`, this (with the fields, the errors
+//! and the Keccak transcript of [`crate::utils::transcript`]) is all of the crate, so that
+//! embedded devices and other zkVM guests can check the pieces of a Jolt proof: the Fiat-Shamir
+//! transcript, sum-check rounds, evaluations of the equality polynomial, and KZG, HyperKZG and
+//! Zeromorph openings. The prover-side types, e.g. `SumcheckInstanceProof`, `UnivariateKZG`,
+//! `HyperKZG` and `Zeromorph`, verify by calling into this module, so that both builds check
+//! proofs identically.
+//!
+//! The openings are checked against the keys' and proofs' group elements rather than their
+//! types, which live with the prover. Spartan and the rest of the Jolt verifier still need `std`.
+use crate::field::JoltField;
+
+pub mod hyperkzg;
+pub mod pairing;
+pub mod sumcheck;
+pub mod zeromorph;
+
+/// Evaluates the multilinear extension of equality,
+/// eq(r, rx) = ∏_i (r_i * rx_i + (1 - r_i) * (1 - rx_i)).
+pub fn eq_eval<F: JoltField>(r: &[F], rx: &[F]) -> F {
+    assert_eq!(r.len(), rx.len());
+    r.iter()
+        .zip(rx.iter())
+        .map(|(r_i, rx_i)| *r_i * rx_i + (F::one() - r_i) * (F::one() - rx_i))
+        .product()
+}
@@ -0,0 +1,39 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_std::Zero;
+
+/// The G1 inputs of the pairing check of a KZG opening of `commitment` to `evaluation` at
+/// `point`, to be paired against `[1]_2` and `[beta]_2` respectively.
+///
+/// e(C - [v]_1, [1]_2) == e(W, [beta - z]_2) <==> e(C - [v]_1 + z * W, [1]_2) * e(-W, [beta]_2) == 1,
+/// which only pairs against the fixed (and thus preparable) G2 elements of the key.
+pub fn kzg_pairing_check<P: Pairing>(
+    g1: &P::G1Affine,
+    commitment: &P::G1Affine,
+    point: &P::ScalarField,
+    proof: &P::G1Affine,
+    evaluation: &P::ScalarField,
+) -> [P::G1; 2] {
+    [
+        commitment.into_group() - g1.into_group() * evaluation + proof.into_group() * point,
+        -proof.into_group(),
+    ]
+}
+
+/// Checks a KZG opening proof, given the key's `[1]_1` and its `[1]_2` and `[beta]_2` prepared
+/// for the pairing.
+pub fn verify_kzg_opening<P: Pairing>(
+    g1: &P::G1Affine,
+    g2_prepared: P::G2Prepared,
+    beta_g2_prepared: P::G2Prepared,
+    commitment: &P::G1Affine,
+    point: &P::ScalarField,
+    proof: &P::G1Affine,
+    evaluation: &P::ScalarField,
+) -> bool {
+    P::multi_pairing(
+        kzg_pairing_check::<P>(g1, commitment, point, proof, evaluation),
+        [g2_prepared, beta_g2_prepared],
+    )
+    .is_zero()
+}
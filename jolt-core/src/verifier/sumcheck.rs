@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+use ark_serialize::*;
+
+use crate::field::JoltField;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::transcript::{AppendToTranscript, Transcript};
+
+// ax^2 + bx + c stored as vec![c,a]
+// ax^3 + bx^2 + cx + d stored as vec![d,b,a]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug)]
+pub struct CompressedUniPoly<F: JoltField> {
+    pub coeffs_except_linear_term: Vec<F>,
+}
+
+impl<F: JoltField> CompressedUniPoly<F> {
+    // In the verifier we do not have to check that f(0) + f(1) = hint as we can just
+    // recover the linear term assuming the prover did it right, then eval the poly
+    pub fn eval_from_hint(&self, hint: &F, x: &F) -> F {
+        let mut linear_term =
+            *hint - self.coeffs_except_linear_term[0] - self.coeffs_except_linear_term[0];
+        for i in 1..self.coeffs_except_linear_term.len() {
+            linear_term -= self.coeffs_except_linear_term[i];
+        }
+
+        let mut running_point = *x;
+        let mut running_sum = self.coeffs_except_linear_term[0] + *x * linear_term;
+        for i in 1..self.coeffs_except_linear_term.len() {
+            running_point = running_point * x;
+            running_sum += self.coeffs_except_linear_term[i] * running_point;
+        }
+        running_sum
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coeffs_except_linear_term.len()
+    }
+}
+
+impl<F: JoltField> AppendToTranscript for CompressedUniPoly<F> {
+    fn append_to_transcript<ProofTranscript: Transcript>(&self, transcript: &mut ProofTranscript) {
+        transcript.append_message(b"UniPoly_begin");
+        for i in 0..self.coeffs_except_linear_term.len() {
+            transcript.append_scalar(&self.coeffs_except_linear_term[i]);
+        }
+        transcript.append_message(b"UniPoly_end");
+    }
+}
+
+/// Verify the round polynomials of a sumcheck proof.
+/// Note: Verification does not execute the final check of sumcheck protocol: g_v(r_v) = oracle_g(r),
+/// as the oracle is not passed in. Expected that the caller will implement.
+///
+/// Params
+/// - `round_polys`: The prover's (compressed) univariate polynomial of each round
+/// - `claim`: Claimed evaluation
+/// - `num_rounds`: Number of rounds of sumcheck, or number of variables to bind
+/// - `degree_bound`: Maximum allowed degree of the combined univariate polynomial
+/// - `transcript`: Fiat-shamir transcript
+///
+/// Returns (e, r)
+/// - `e`: Claimed evaluation at random point
+/// - `r`: Evaluation point
+pub fn verify<F: JoltField, ProofTranscript: Transcript>(
+    round_polys: &[CompressedUniPoly<F>],
+    claim: F,
+    num_rounds: usize,
+    degree_bound: usize,
+    transcript: &mut ProofTranscript,
+) -> Result<(F, Vec<F>), ProofVerifyError> {
+    let mut e = claim;
+    let mut r: Vec<F> = Vec::new();
+
+    // verify that there is a univariate polynomial for each round
+    assert_eq!(round_polys.len(), num_rounds);
+    for poly in round_polys.iter() {
+        // verify degree bound
+        if poly.degree() != degree_bound {
+            return Err(ProofVerifyError::InvalidInputLength(
+                degree_bound,
+                poly.degree(),
+            ));
+        }
+
+        // append the prover's message to the transcript
+        poly.append_to_transcript(transcript);
+
+        //derive the verifier's challenge for the next round
+        let r_i = transcript.challenge_scalar();
+        r.push(r_i);
+
+        // evaluate the claimed degree-ell polynomial at r_i using the hint
+        e = poly.eval_from_hint(&e, &r_i);
+    }
+
+    Ok((e, r))
+}
@@ -0,0 +1,213 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, VariableBaseMSM};
+use ark_ff::batch_inversion;
+use ark_std::{One, Zero};
+use core::iter;
+
+use crate::field::JoltField;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::transcript::Transcript;
+
+/// The scalars of the verifier's ZM-identity check, for the challenges `y`, `x` and `z` and an
+/// opening at `challenges`: the evaluation's scalar, and for each quotient `q_k` its scalars
+/// in the batched degree check and in the ZM-identity.
+pub fn eval_and_quotient_scalars<P: Pairing>(
+    y_challenge: P::ScalarField,
+    x_challenge: P::ScalarField,
+    z_challenge: P::ScalarField,
+    challenges: &[P::ScalarField],
+) -> (P::ScalarField, (Vec<P::ScalarField>, Vec<P::ScalarField>))
+where
+    P::ScalarField: JoltField,
+{
+    let num_vars = challenges.len();
+
+    // squares of x = [x, x^2, .. x^{2^k}, .. x^{2^num_vars}]
+    let squares_of_x: Vec<_> = iter::successors(Some(x_challenge), |&x| Some(x.square()))
+        .take(num_vars + 1)
+        .collect();
+
+    let offsets_of_x = {
+        let mut offsets_of_x = squares_of_x
+            .iter()
+            .rev()
+            .skip(1)
+            .scan(P::ScalarField::one(), |acc, pow_x| {
+                *acc *= *pow_x;
+                Some(*acc)
+            })
+            .collect::<Vec<_>>();
+        offsets_of_x.reverse();
+        offsets_of_x
+    };
+
+    let vs = {
+        let v_numer = squares_of_x[num_vars] - P::ScalarField::one();
+        let mut v_denoms = squares_of_x
+            .iter()
+            .map(|squares_of_x| *squares_of_x - P::ScalarField::one())
+            .collect::<Vec<_>>();
+        batch_inversion(&mut v_denoms);
+        v_denoms
+            .iter()
+            .map(|v_denom| v_numer * *v_denom)
+            .collect::<Vec<_>>()
+    };
+
+    let q_scalars = iter::successors(Some(P::ScalarField::one()), |acc| Some(*acc * y_challenge))
+        .take(num_vars)
+        .zip(offsets_of_x)
+        .zip(squares_of_x)
+        .zip(vs.iter().zip(&vs[1..]))
+        .zip(challenges.iter().rev())
+        .map(
+            |((((power_of_y, offset_of_x), square_of_x), (v_i, v_j)), u_i)| {
+                (
+                    -(power_of_y * offset_of_x),
+                    -(z_challenge * (square_of_x * *v_j - *u_i * *v_i)),
+                )
+            },
+        )
+        .unzip();
+    // -vs[0] * z = -z * (x^(2^num_vars) - 1) / (x - 1) = -z Φ_n(x)
+    (-vs[0] * z_challenge, q_scalars)
+}
+
+/// The G1 inputs of the two pairing checks of a Zeromorph opening of `commitment` to
+/// `evaluation` at `point`, the ZM-identity check and the degree check on `q_hat`. Both are to be
+/// paired against `[-X^(N_max - 2^N)]_2, [beta]_2, [1]_2` and the `num_degree_checks` elements
+/// `[X^(N - 2^m)]_2` of the key, and zero where a check doesn't use an element.
+///
+/// Performs all of the verifier's checks except for the pairing checks themselves.
+#[allow(clippy::too_many_arguments)]
+pub fn zeromorph_pairing_checks<P: Pairing, ProofTranscript: Transcript>(
+    g1: &P::G1Affine,
+    num_degree_checks: usize,
+    commitment: &P::G1Affine,
+    point: &[P::ScalarField],
+    evaluation: &P::ScalarField,
+    pi: &P::G1Affine,
+    q_hat_com: &P::G1Affine,
+    q_hat_degree_check_com: &P::G1Affine,
+    q_k_com: &[P::G1Affine],
+    transcript: &mut ProofTranscript,
+) -> Result<[Vec<P::G1>; 2], ProofVerifyError>
+where
+    P::ScalarField: JoltField,
+{
+    if point.len() >= num_degree_checks {
+        return Err(ProofVerifyError::InvalidInputLength(
+            num_degree_checks - 1,
+            point.len(),
+        ));
+    }
+    if q_k_com.len() != point.len() {
+        return Err(ProofVerifyError::InvalidInputLength(
+            point.len(),
+            q_k_com.len(),
+        ));
+    }
+
+    transcript.append_message(b"Zeromorph");
+
+    q_k_com
+        .iter()
+        .for_each(|c| transcript.append_point(&c.into_group()));
+
+    // Challenge y
+    let y_challenge: P::ScalarField = transcript.challenge_scalar();
+
+    // Receive commitment C_q_hat
+    transcript.append_point(&q_hat_com.into_group());
+
+    // Get x and z challenges
+    let x_challenge = transcript.challenge_scalar();
+    let z_challenge = transcript.challenge_scalar();
+
+    // Compute batched degree and ZM-identity quotient polynomial pi
+    let (eval_scalar, (mut q_scalars, zmpoly_q_scalars)): (
+        P::ScalarField,
+        (Vec<P::ScalarField>, Vec<P::ScalarField>),
+    ) = eval_and_quotient_scalars::<P>(y_challenge, x_challenge, z_challenge, point);
+    q_scalars
+        .iter_mut()
+        .zip(zmpoly_q_scalars)
+        .for_each(|(scalar, zm_poly_q_scalar)| {
+            *scalar += zm_poly_q_scalar;
+        });
+    let scalars = [
+        vec![
+            P::ScalarField::one(),
+            z_challenge,
+            eval_scalar * *evaluation,
+        ],
+        q_scalars,
+    ]
+    .concat();
+    let bases = [vec![*q_hat_com, *commitment, *g1], q_k_com.to_vec()].concat();
+    let zeta_z_com = <P::G1 as VariableBaseMSM>::msm(&bases, &scalars).unwrap();
+
+    // e(pi, [tau]_2 - x * [1]_2) == e(C_{\zeta,Z}, [X^(N_max - 2^n - 1)]_2)
+    // <==> e(C_{\zeta,Z}, -[X^(N_max - 2^n - 1)]_2) * e(pi, [tau]_2) * e(-x * pi, [1]_2) == 1,
+    // which only pairs against the fixed (and thus preparable) G2 elements of the key.
+    let mut identity_check = vec![P::G1::zero(); 3 + num_degree_checks];
+    identity_check[..3].copy_from_slice(&[zeta_z_com, pi.into_group(), -(*pi * x_challenge)]);
+
+    // e(C_q, [X^(N_max - 2^n)]_2) == e([X^(N_max - 2^n) * q_hat]_1, [1]_2), which the
+    // prover can only satisfy if q_hat has fewer than 2^n coefficients. The identity check
+    // alone only bounds the degree by the size of the key.
+    let mut degree_check = vec![P::G1::zero(); 3 + num_degree_checks];
+    degree_check[2] = -q_hat_degree_check_com.into_group();
+    degree_check[3 + point.len()] = q_hat_com.into_group();
+
+    Ok([identity_check, degree_check])
+}
+
+/// Checks a Zeromorph opening proof, given the G2 elements of the key prepared for the pairing,
+/// in the order of [`zeromorph_pairing_checks`].
+#[allow(clippy::too_many_arguments)]
+pub fn verify_zeromorph_opening<P: Pairing, ProofTranscript: Transcript>(
+    g1: &P::G1Affine,
+    g2_prepared: &[P::G2Prepared],
+    commitment: &P::G1Affine,
+    point: &[P::ScalarField],
+    evaluation: &P::ScalarField,
+    pi: &P::G1Affine,
+    q_hat_com: &P::G1Affine,
+    q_hat_degree_check_com: &P::G1Affine,
+    q_k_com: &[P::G1Affine],
+    transcript: &mut ProofTranscript,
+) -> Result<(), ProofVerifyError>
+where
+    P::ScalarField: JoltField,
+{
+    if g2_prepared.len() < 3 {
+        return Err(ProofVerifyError::InternalError);
+    }
+    let pairing_checks = zeromorph_pairing_checks::<P, ProofTranscript>(
+        g1,
+        g2_prepared.len() - 3,
+        commitment,
+        point,
+        evaluation,
+        pi,
+        q_hat_com,
+        q_hat_degree_check_com,
+        q_k_com,
+        transcript,
+    )?;
+    for pairing_check in pairing_checks {
+        // Most of the G1 elements of the degree check are zero
+        let (g1, g2): (Vec<P::G1>, Vec<P::G2Prepared>) = pairing_check
+            .into_iter()
+            .zip(g2_prepared.iter().cloned())
+            .filter(|(g1, _)| !g1.is_zero())
+            .unzip();
+        if !P::multi_pairing(g1, g2).is_zero() {
+            return Err(ProofVerifyError::InternalError);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Verification of serialized RV32I proofs, with JavaScript bindings for the browser and node.
+//!
+//! Build the verifier for the web with
+//!
+//! ```sh
+//! cargo build -p jolt-core --target wasm32-unknown-unknown --no-default-features --features wasm
+//! ```
+//!
+//! Without the `host` feature, the guest compilation, the networked SRS download and the async
+//! runtime are left out, and so is rayon, since `wasm32-unknown-unknown` has no threads: the
+//! parallel iterators run sequentially on the calling thread (see [`crate::utils::par`]).
+//! Buffers that the native prover frees on another thread are freed in place (see
+//! [`drop_in_background_thread`]).
+//!
+//! The verifier takes the program's serialized [`JoltVerifierPreprocessing`], which the host
+//! gets from [`JoltPreprocessing::verifier_preprocessing`], so that the commitment scheme's setup
+//! is not regenerated on every verification. [`VerifierProgram`] is what the host preprocesses.
+//!
+//! [`JoltVerifierPreprocessing`]: crate::jolt::vm::JoltVerifierPreprocessing
+//!
+//! [`drop_in_background_thread`]: crate::utils::thread::drop_in_background_thread
+use ark_bn254::Fr;
+use common::rv_trace::{ELFInstruction, MemoryLayout};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::jolt::vm::rv32i_vm::{
+    JoltHyperKZGProof, ProofTranscript, RV32IJoltVM, Serializable, C, PCS,
+};
+use crate::jolt::vm::{Jolt, JoltPreprocessing, JoltVerifierPreprocessing};
+
+/// What the verifier needs to redo a program's preprocessing: its decoded bytecode and memory,
+/// and the size bounds the prover's preprocessing used.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifierProgram {
+    pub bytecode: Vec<ELFInstruction>,
+    pub memory_layout: MemoryLayout,
+    pub memory_init: Vec<(u64, u8)>,
+    pub max_bytecode_size: usize,
+    pub max_memory_address: usize,
+    pub max_trace_length: usize,
+}
+
+impl VerifierProgram {
+    pub fn preprocess(self) -> JoltPreprocessing<C, Fr, PCS, ProofTranscript> {
+        RV32IJoltVM::preprocess(
+            self.bytecode,
+            self.memory_layout,
+            self.memory_init,
+            self.max_bytecode_size,
+            self.max_memory_address,
+            self.max_trace_length,
+        )
+    }
+
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// The verifier key of a program: the serialized [`JoltVerifierPreprocessing`] that
+/// [`verify_serialized`] takes.
+pub fn serialize_verifier_preprocessing(
+    preprocessing: &JoltPreprocessing<C, Fr, PCS, ProofTranscript>,
+) -> Result<Vec<u8>> {
+    preprocessing.verifier_preprocessing().serialize_to_bytes()
+}
+
+/// Verifies a serialized [`JoltHyperKZGProof`] against the program's serialized
+/// [`JoltVerifierPreprocessing`].
+pub fn verify_serialized(verifier_preprocessing: &[u8], proof: &[u8]) -> Result<()> {
+    let preprocessing =
+        JoltVerifierPreprocessing::<C, Fr, PCS, ProofTranscript>::deserialize_from_bytes(
+            verifier_preprocessing,
+        )?;
+    let proof = JoltHyperKZGProof::deserialize_from_bytes(proof)?;
+    RV32IJoltVM::verify_with_verifier_preprocessing(
+        preprocessing,
+        proof.proof,
+        proof.commitments,
+        None,
+    )
+    .map_err(|err| eyre!("invalid proof: {err}"))
+}
+
+#[cfg(feature = "wasm")]
+mod bindings {
+    use wasm_bindgen::prelude::*;
+
+    /// Verifies a proof, both arguments being the bytes serialized by the host. Throws the reason
+    /// if the proof doesn't verify.
+    #[wasm_bindgen(js_name = verifyJoltProof)]
+    pub fn verify_jolt_proof(verifier_preprocessing: &[u8], proof: &[u8]) -> Result<(), JsValue> {
+        super::verify_serialized(verifier_preprocessing, proof)
+            .map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+}
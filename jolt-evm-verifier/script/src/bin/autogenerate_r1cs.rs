@@ -19,10 +19,10 @@ fn partial_autogenerate<F: JoltField>(
 
     print!("Fr running = Fr.wrap(0);\nFr rv = Fr.wrap(0);\n");
 
-    for (row, col, coeff) in constraints.vars.iter() {
+    for (row, col, coeff) in constraints.vars() {
         println!(
             "rv += Fr.wrap(0x{})*row[{:?}]*col[{:?}];",
-            hex::encode(bytes(*coeff)),
+            hex::encode(bytes(coeff)),
             row,
             col
         );
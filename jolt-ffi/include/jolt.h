@@ -0,0 +1,45 @@
+/*
+ * C bindings for proving and verifying RV32I programs with Jolt.
+ *
+ * Buffers returned by the library are opaque JoltBytes handles, to be released with
+ * jolt_bytes_free. A function that fails returns NULL (or false) and records a message that
+ * jolt_last_error returns.
+ */
+#ifndef JOLT_H
+#define JOLT_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct JoltBytes JoltBytes;
+
+/* The verifier key of the program `elf`. */
+JoltBytes *jolt_verifier_key(const uint8_t *elf, size_t elf_len);
+
+/* Proves an execution of the program `elf` on the serialized `inputs`. */
+JoltBytes *jolt_prove(const uint8_t *elf, size_t elf_len, const uint8_t *inputs,
+                      size_t inputs_len);
+
+/* Whether `proof` is a valid proof of the program with verifier key `vk`, whose execution
+ * returned `outputs` without panicking. */
+bool jolt_verify(const uint8_t *vk, size_t vk_len, const uint8_t *proof, size_t proof_len,
+                 const uint8_t *outputs, size_t outputs_len);
+
+const uint8_t *jolt_bytes_data(const JoltBytes *bytes);
+size_t jolt_bytes_len(const JoltBytes *bytes);
+void jolt_bytes_free(JoltBytes *bytes);
+
+/* The message of the last error on this thread, or NULL. Valid until the next failing call
+ * on this thread. */
+const char *jolt_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* JOLT_H */
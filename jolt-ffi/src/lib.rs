@@ -0,0 +1,237 @@
+//! C bindings for proving and verifying RV32I programs with Jolt, for hosts not written in Rust.
+//! The C declarations are in `include/jolt.h`.
+//!
+//! A program is given as its ELF and its input as bytes, serialized as the guest expects them
+//! (with `postcard` for guests built with the SDK). The verifier key of a program, from
+//! `jolt_verifier_key`, is what the verifier needs to redo the program's preprocessing.
+//!
+//! Byte buffers returned to the host are opaque `JoltBytes` handles, read with
+//! `jolt_bytes_data` and `jolt_bytes_len` and released with `jolt_bytes_free`. A function that
+//! fails returns a null handle (or `false`) and records a message that `jolt_last_error` returns.
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::constants::{DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE};
+use common::rv_trace::{JoltDevice, MemoryLayout};
+use eyre::Result;
+use jolt_core::host::Program;
+use jolt_core::jolt::vm::rv32i_vm::{JoltHyperKZGProof, RV32IJoltVM, Serializable, RV32I};
+use jolt_core::jolt::vm::{Jolt, JoltTraceStep};
+use jolt_core::wasm::VerifierProgram;
+
+// The same bounds as the preprocessing of the SDK
+const MAX_BYTECODE_SIZE: usize = 1 << 20;
+const MAX_MEMORY_ADDRESS: usize = 1 << 20;
+const MAX_TRACE_LENGTH: usize = 1 << 24;
+
+/// The serialized verifier key of the program `elf`.
+pub fn verifier_key(elf: &[u8]) -> Result<Vec<u8>> {
+    verifier_program(elf).serialize_to_bytes()
+}
+
+/// Executes the program `elf` on `inputs`, returning its I/O and its trace.
+pub fn trace(elf: &[u8], inputs: &[u8]) -> Result<(JoltDevice, Vec<JoltTraceStep<RV32I>>)> {
+    // The tracer reads the program from a file
+    let elf_file = TempElf::new(elf)?;
+    let mut program = Program::new("jolt-ffi");
+    program.elf = Some(elf_file.path.clone());
+    program.set_input_bytes(inputs);
+    Ok(program.trace()?)
+}
+
+/// Proves an execution of the program `elf` on `inputs`, returning the serialized proof.
+pub fn prove(elf: &[u8], inputs: &[u8]) -> Result<Vec<u8>> {
+    let preprocessing = verifier_program(elf).preprocess();
+    let (io_device, trace) = trace(elf, inputs)?;
+    let (proof, commitments, _) = RV32IJoltVM::prove(io_device, trace, preprocessing)?;
+    JoltHyperKZGProof { proof, commitments }.serialize_to_bytes()
+}
+
+/// Whether `proof` is a valid proof of the program with verifier key `vk`, whose execution
+/// returned `outputs` without panicking. Errors only if either fails to deserialize.
+pub fn verify(vk: &[u8], proof: &[u8], outputs: &[u8]) -> Result<bool> {
+    let preprocessing = VerifierProgram::deserialize_from_bytes(vk)?.preprocess();
+    let proof = JoltHyperKZGProof::deserialize_from_bytes(proof)?;
+    let program_io = &proof.proof.program_io;
+    if program_io.panic || program_io.outputs != outputs {
+        return Ok(false);
+    }
+    Ok(RV32IJoltVM::verify(preprocessing, proof.proof, proof.commitments, None).is_ok())
+}
+
+fn verifier_program(elf: &[u8]) -> VerifierProgram {
+    let (bytecode, memory_init) = tracer::decode(elf);
+    VerifierProgram {
+        bytecode,
+        memory_layout: MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE),
+        memory_init,
+        max_bytecode_size: MAX_BYTECODE_SIZE,
+        max_memory_address: MAX_MEMORY_ADDRESS,
+        max_trace_length: MAX_TRACE_LENGTH,
+    }
+}
+
+/// An ELF written to the temporary directory, removed on drop.
+struct TempElf {
+    path: PathBuf,
+}
+
+impl TempElf {
+    fn new(elf: &[u8]) -> Result<Self> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "jolt-ffi-{}-{}.elf",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, elf)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for TempElf {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A byte buffer owned by the library.
+pub struct JoltBytes(Vec<u8>);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, recording its error or panic (which must not unwind into the host) as the last
+/// error.
+fn catch<T>(f: impl FnOnce() -> Result<T>) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(err)) => {
+            set_last_error(err.to_string());
+            None
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "panicked".to_string());
+            set_last_error(format!("panicked: {message}"));
+            None
+        }
+    }
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+fn into_handle(bytes: Option<Vec<u8>>) -> *mut JoltBytes {
+    match bytes {
+        Some(bytes) => Box::into_raw(Box::new(JoltBytes(bytes))),
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn jolt_verifier_key(elf: *const u8, elf_len: usize) -> *mut JoltBytes {
+    let elf = bytes(elf, elf_len);
+    into_handle(catch(|| verifier_key(elf)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn jolt_prove(
+    elf: *const u8,
+    elf_len: usize,
+    inputs: *const u8,
+    inputs_len: usize,
+) -> *mut JoltBytes {
+    let (elf, inputs) = (bytes(elf, elf_len), bytes(inputs, inputs_len));
+    into_handle(catch(|| prove(elf, inputs)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn jolt_verify(
+    vk: *const u8,
+    vk_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    outputs: *const u8,
+    outputs_len: usize,
+) -> bool {
+    let (vk, proof) = (bytes(vk, vk_len), bytes(proof, proof_len));
+    let outputs = bytes(outputs, outputs_len);
+    catch(|| verify(vk, proof, outputs)).unwrap_or(false)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn jolt_bytes_data(bytes: *const JoltBytes) -> *const u8 {
+    (*bytes).0.as_ptr()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn jolt_bytes_len(bytes: *const JoltBytes) -> usize {
+    (*bytes).0.len()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn jolt_bytes_free(bytes: *mut JoltBytes) {
+    if !bytes.is_null() {
+        drop(Box::from_raw(bytes));
+    }
+}
+
+/// The message of the last error on this thread, or null. Valid until the next failing call on
+/// this thread.
+#[no_mangle]
+pub extern "C" fn jolt_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn malformed_bytes() {
+        let garbage = [1u8, 2, 3];
+        let verified = unsafe {
+            jolt_verify(
+                garbage.as_ptr(),
+                garbage.len(),
+                garbage.as_ptr(),
+                garbage.len(),
+                ptr::null(),
+                0,
+            )
+        };
+        assert!(!verified);
+        assert!(!jolt_last_error().is_null());
+        let message = unsafe { CStr::from_ptr(jolt_last_error()) };
+        assert!(!message.to_bytes().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! Python bindings for proving and verifying RV32I programs with Jolt, so that experiments can
+//! be scripted without a Rust harness. Build and install the `jolt` module from this directory
+//! with `maturin develop --release`, then:
+//!
+//! ```python
+//! import jolt
+//!
+//! elf = open("guest.elf", "rb").read()
+//! proof = jolt.prove(elf, inputs)
+//! assert jolt.verify(jolt.verifier_key(elf), proof, proof.outputs)
+//! proof = jolt.Proof.from_bytes(proof.to_bytes())
+//! ```
+//!
+//! As with `jolt-ffi`, inputs and outputs are bytes, serialized as the guest expects them.
+use std::fmt::Display;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use jolt_core::jolt::vm::rv32i_vm::{JoltHyperKZGProof, Serializable};
+
+fn value_error(err: impl Display) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// A serialized proof, with accessors for the execution it claims.
+#[pyclass(module = "jolt")]
+#[derive(Clone)]
+pub struct Proof {
+    bytes: Vec<u8>,
+}
+
+impl Proof {
+    fn deserialize(&self) -> PyResult<JoltHyperKZGProof> {
+        JoltHyperKZGProof::deserialize_from_bytes(&self.bytes).map_err(value_error)
+    }
+}
+
+#[pymethods]
+impl Proof {
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        let proof = Self {
+            bytes: bytes.to_vec(),
+        };
+        proof.deserialize()?;
+        Ok(proof)
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.bytes)
+    }
+
+    #[getter]
+    fn inputs<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let proof = self.deserialize()?;
+        Ok(PyBytes::new_bound(py, &proof.proof.program_io.inputs))
+    }
+
+    #[getter]
+    fn outputs<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let proof = self.deserialize()?;
+        Ok(PyBytes::new_bound(py, &proof.proof.program_io.outputs))
+    }
+
+    #[getter]
+    fn panic(&self) -> PyResult<bool> {
+        Ok(self.deserialize()?.proof.program_io.panic)
+    }
+
+    #[getter]
+    fn trace_length(&self) -> PyResult<usize> {
+        Ok(self.deserialize()?.proof.trace_length)
+    }
+
+    fn __len__(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+/// An execution of a program.
+#[pyclass(module = "jolt")]
+pub struct Trace {
+    inputs: Vec<u8>,
+    outputs: Vec<u8>,
+    #[pyo3(get)]
+    panic: bool,
+    #[pyo3(get)]
+    length: usize,
+}
+
+#[pymethods]
+impl Trace {
+    #[getter]
+    fn inputs<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.inputs)
+    }
+
+    #[getter]
+    fn outputs<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.outputs)
+    }
+}
+
+/// The verifier key of the program `elf`.
+#[pyfunction]
+fn verifier_key<'py>(py: Python<'py>, elf: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let vk = py
+        .allow_threads(|| jolt_ffi::verifier_key(elf))
+        .map_err(value_error)?;
+    Ok(PyBytes::new_bound(py, &vk))
+}
+
+/// Executes the program `elf` on `inputs`.
+#[pyfunction]
+fn trace(py: Python<'_>, elf: &[u8], inputs: &[u8]) -> PyResult<Trace> {
+    let (io_device, trace) = py
+        .allow_threads(|| jolt_ffi::trace(elf, inputs))
+        .map_err(value_error)?;
+    Ok(Trace {
+        inputs: io_device.inputs,
+        outputs: io_device.outputs,
+        panic: io_device.panic,
+        length: trace.len(),
+    })
+}
+
+/// Proves an execution of the program `elf` on `inputs`.
+#[pyfunction]
+fn prove(py: Python<'_>, elf: &[u8], inputs: &[u8]) -> PyResult<Proof> {
+    let bytes = py
+        .allow_threads(|| jolt_ffi::prove(elf, inputs))
+        .map_err(value_error)?;
+    Ok(Proof { bytes })
+}
+
+/// Whether `proof` is a valid proof of the program with verifier key `vk`, whose execution
+/// returned `outputs` without panicking.
+#[pyfunction]
+fn verify(py: Python<'_>, vk: &[u8], proof: &Proof, outputs: &[u8]) -> PyResult<bool> {
+    py.allow_threads(|| jolt_ffi::verify(vk, &proof.bytes, outputs))
+        .map_err(value_error)
+}
+
+#[pymodule]
+fn jolt(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Proof>()?;
+    m.add_class::<Trace>()?;
+    m.add_function(wrap_pyfunction!(verifier_key, m)?)?;
+    m.add_function(wrap_pyfunction!(trace, m)?)?;
+    m.add_function(wrap_pyfunction!(prove, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    Ok(())
+}
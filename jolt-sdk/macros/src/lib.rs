@@ -62,8 +62,10 @@ impl MacroBuilder {
         let build_fn = self.make_build_fn();
         let execute_fn = self.make_execute_function();
         let analyze_fn = self.make_analyze_function();
+        let profile_fn = self.make_profile_function();
         let preprocess_fn = self.make_preprocess_func();
         let prove_fn = self.make_prove_func();
+        let verify_io_fn = self.make_verify_io_func();
 
         let main_fn = if let Some(func) = self.get_func_selector() {
             if *self.get_func_name() == func {
@@ -79,8 +81,10 @@ impl MacroBuilder {
             #build_fn
             #execute_fn
             #analyze_fn
+            #profile_fn
             #preprocess_fn
             #prove_fn
+            #verify_io_fn
             #main_fn
         }
         .into()
@@ -172,15 +176,49 @@ impl MacroBuilder {
                 #set_mem_size
                 #(#set_program_args;)*
 
-                program.trace_analyze::<jolt::F>()
+                program
+                    .trace_analyze::<jolt::F>()
+                    .unwrap_or_else(|err| panic!("{err}"))
+             }
+        }
+    }
+
+    fn make_profile_function(&self) -> TokenStream2 {
+        let set_mem_size = self.make_set_linker_parameters();
+        let guest_name = self.get_guest_name();
+        let imports = self.make_imports();
+        let set_std = self.make_set_std();
+
+        let fn_name = self.get_func_name();
+        let fn_name_str = fn_name.to_string();
+        let profile_fn_name = Ident::new(&format!("profile_{}", fn_name), fn_name.span());
+        let inputs = &self.func.sig.inputs;
+        let set_program_args = self.func_args.iter().map(|(name, _)| {
+            quote! {
+                program.set_input(&#name);
+            }
+        });
+
+        quote! {
+             #[cfg(not(target_arch = "wasm32"))]
+             #[cfg(not(feature = "guest"))]
+             pub fn #profile_fn_name(#inputs) -> jolt::host::Profile {
+                #imports
+
+                let mut program = Program::new(#guest_name);
+                program.set_func(#fn_name_str);
+                #set_std
+                #set_mem_size
+                #(#set_program_args;)*
+
+                program.profile().unwrap_or_else(|err| panic!("{err}"))
              }
         }
     }
 
     fn make_preprocess_func(&self) -> TokenStream2 {
         let attributes = parse_attributes(&self.attr);
-        let max_input_size = proc_macro2::Literal::u64_unsuffixed(attributes.max_input_size);
-        let max_output_size = proc_macro2::Literal::u64_unsuffixed(attributes.max_output_size);
+        let max_bytecode_size = proc_macro2::Literal::u64_unsuffixed(attributes.max_bytecode_size);
         let set_mem_size = self.make_set_linker_parameters();
         let guest_name = self.get_guest_name();
         let imports = self.make_imports();
@@ -202,16 +240,17 @@ impl MacroBuilder {
                 #set_std
                 #set_mem_size
                 let (bytecode, memory_init) = program.decode();
-                let memory_layout = MemoryLayout::new(#max_input_size, #max_output_size);
+                let memory_layout = program.memory_layout();
+                let max_memory_address = memory_layout.max_memory_address() as usize;
 
-                // TODO(moodlezoup): Feed in size parameters via macro
+                // TODO(moodlezoup): Feed in the trace length via macro
                 let preprocessing: JoltPreprocessing<4, jolt::F, jolt::PCS, jolt::ProofTranscript> =
                     RV32IJoltVM::preprocess(
                         bytecode,
                         memory_layout,
                         memory_init,
-                        1 << 20,
-                        1 << 20,
+                        #max_bytecode_size,
+                        max_memory_address,
                         1 << 24
                     );
 
@@ -254,7 +293,9 @@ impl MacroBuilder {
 
                 #(#set_program_args;)*
 
-                let (io_device, trace) = program.trace();
+                let (io_device, trace) = program
+                    .trace()
+                    .unwrap_or_else(|err| panic!("tracing failed: {err}"));
 
                 let output_bytes = io_device.outputs.clone();
 
@@ -262,7 +303,8 @@ impl MacroBuilder {
                     io_device,
                     trace,
                     preprocessing,
-                );
+                )
+                .unwrap_or_else(|err| panic!("proving failed: {err}"));
 
                 #handle_return
 
@@ -276,6 +318,48 @@ impl MacroBuilder {
         }
     }
 
+    fn make_verify_io_func(&self) -> TokenStream2 {
+        let output_ty = match &self.func.sig.output {
+            ReturnType::Default => quote! { () },
+            ReturnType::Type(_, ty) => quote! { #ty },
+        };
+
+        let input_names = self.func_args.iter().map(|(name, _)| name);
+        let input_types = self.func_args.iter().map(|(_, ty)| ty);
+        // Encoded as `Program::set_input` does
+        let encode_inputs = self.func_args.iter().map(|(name, _)| {
+            quote! {
+                input_bytes.extend(jolt::postcard::to_stdvec(&#name).unwrap());
+            }
+        });
+
+        let fn_name = self.get_func_name();
+        let imports = self.make_imports();
+
+        let verify_io_fn_name = syn::Ident::new(&format!("verify_io_{}", fn_name), fn_name.span());
+        quote! {
+            /// Verifies `proof`, and that it proves that the function returns `output` when
+            /// called on the given inputs.
+            #[cfg(all(not(target_arch = "wasm32"), not(feature = "guest")))]
+            pub fn #verify_io_fn_name(
+                preprocessing: jolt::JoltPreprocessing<4, jolt::F, jolt::PCS, jolt::ProofTranscript>,
+                #(#input_names: #input_types,)*
+                output: #output_ty,
+                proof: jolt::JoltHyperKZGProof,
+            ) -> bool {
+                #imports
+
+                let mut input_bytes = Vec::new();
+                #(#encode_inputs)*
+                let output_bytes = jolt::postcard::to_stdvec(&output).unwrap();
+
+                jolt::program_io_matches(&proof.proof.program_io, &input_bytes, &output_bytes)
+                    && RV32IJoltVM::verify(preprocessing, proof.proof, proof.commitments, None)
+                        .is_ok()
+            }
+        }
+    }
+
     fn make_main_func(&self) -> TokenStream2 {
         let attributes = parse_attributes(&self.attr);
         let memory_layout =
@@ -414,7 +498,6 @@ impl MacroBuilder {
                 RV32IJoltProof,
                 BytecodeRow,
                 MemoryOp,
-                MemoryLayout,
                 MEMORY_OPS_PER_INSTRUCTION,
                 instruction::add::ADDInstruction,
                 tracer,
@@ -541,6 +624,8 @@ impl MacroBuilder {
     fn make_wasm_function(&self) -> TokenStream2 {
         let fn_name = self.get_func_name();
         let verify_wasm_fn_name = Ident::new(&format!("verify_{}", fn_name), fn_name.span());
+        let max_bytecode_size =
+            proc_macro2::Literal::u64_unsuffixed(parse_attributes(&self.attr).max_bytecode_size);
 
         quote! {
             #[wasm_bindgen]
@@ -554,7 +639,7 @@ impl MacroBuilder {
                 let preprocessing = RV32IJoltVM::preprocess(
                     decoded_preprocessing_data.bytecode,
                     decoded_preprocessing_data.memory_init,
-                    1 << 20,
+                    #max_bytecode_size,
                     1 << 20,
                     1 << 24,
                 );
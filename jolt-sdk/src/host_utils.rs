@@ -4,7 +4,7 @@ pub use jolt_core::{field::JoltField, poly::commitment::hyperkzg::HyperKZG};
 
 pub use common::{
     constants::MEMORY_OPS_PER_INSTRUCTION,
-    rv_trace::{MemoryLayout, MemoryOp, RV32IM},
+    rv_trace::{JoltDevice, MemoryConfig, MemoryLayout, MemoryOp, RV32IM},
 };
 pub use jolt_core::host;
 pub use jolt_core::jolt::instruction;
@@ -13,6 +13,20 @@ pub use jolt_core::jolt::vm::{
     rv32i_vm::{
         JoltHyperKZGProof, ProofTranscript, RV32IJoltProof, RV32IJoltVM, Serializable, PCS, RV32I,
     },
-    Jolt, JoltCommitments, JoltPreprocessing, JoltProof,
+    Jolt, JoltCommitments, JoltPreprocessing, JoltProof, JoltVerifierPreprocessing,
 };
+pub use jolt_core::utils::prover_config::ProverConfig;
 pub use tracer;
+
+/// Checks that a proof's public I/O consists of the given serialized inputs and outputs, and
+/// that the guest did not panic. The output region is zero-initialized, so the outputs may be
+/// followed by zeros, which do not change what they deserialize to.
+pub fn program_io_matches(program_io: &JoltDevice, inputs: &[u8], outputs: &[u8]) -> bool {
+    !program_io.panic
+        && program_io.inputs == inputs
+        && program_io.outputs.len() >= outputs.len()
+        && program_io.outputs[..outputs.len()] == *outputs
+        && program_io.outputs[outputs.len()..]
+            .iter()
+            .all(|byte| *byte == 0)
+}
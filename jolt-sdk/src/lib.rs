@@ -12,3 +12,9 @@ pub use host_utils::*;
 
 pub mod alloc;
 pub use alloc::*;
+#[cfg(feature = "getrandom")]
+mod random;
+#[cfg(feature = "getrandom")]
+pub use random::seed_getrandom;
+pub mod syscall;
+pub use syscall::log;
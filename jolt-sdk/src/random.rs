@@ -0,0 +1,54 @@
+//! A `getrandom` backend for guests, so that crates which seed themselves from it (e.g. `ahash`,
+//! and through it `hashbrown`) can be used in guests.
+//!
+//! A guest has no source of randomness: its execution must be reproducible, and syscalls cannot
+//! write to guest memory. The backend therefore fails with `Error::UNSUPPORTED` until the guest
+//! opts in to deterministic randomness with [`seed_getrandom`], after which it returns a
+//! pseudorandom stream derived from the seed. The guest's inputs are part of the proof, so the
+//! stream is known to anyone who knows the seed's source: the bytes must never be used as secrets.
+
+/// Seeds the bytes `getrandom` returns in the guest. On the host, `getrandom` uses the OS, and
+/// this does nothing.
+pub fn seed_getrandom(seed: u64) {
+    #[cfg(target_arch = "riscv32")]
+    guest::STATE.0.set(Some(seed));
+    #[cfg(not(target_arch = "riscv32"))]
+    let _ = seed;
+}
+
+#[cfg(target_arch = "riscv32")]
+mod guest {
+    use core::cell::Cell;
+    use getrandom::{register_custom_getrandom, Error};
+
+    pub(super) struct State(pub(super) Cell<Option<u64>>);
+
+    // Guests are single-threaded
+    unsafe impl Sync for State {}
+
+    /// The SplitMix64 state, or `None` if the guest has not called `seed_getrandom`
+    pub(super) static STATE: State = State(Cell::new(None));
+
+    fn jolt_getrandom(dest: &mut [u8]) -> Result<(), Error> {
+        let Some(mut state) = STATE.0.get() else {
+            return Err(Error::UNSUPPORTED);
+        };
+        for chunk in dest.chunks_mut(8) {
+            let bytes = next_u64(&mut state).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+        STATE.0.set(Some(state));
+        Ok(())
+    }
+
+    /// SplitMix64
+    fn next_u64(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    register_custom_getrandom!(jolt_getrandom);
+}
@@ -0,0 +1,25 @@
+//! Syscalls from the guest to the host, made with `ecall`. See `common::constants` for the ABI.
+
+// Must match `common::constants::SYSCALL_LOG`
+#[cfg(target_arch = "riscv32")]
+const SYSCALL_LOG: usize = 1;
+
+/// Prints `message` on the host running the guest. Logging does not change the guest's
+/// execution, so it is proven as a no-op and is not part of the proof's public I/O.
+pub fn log(message: &str) {
+    #[cfg(target_arch = "riscv32")]
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in("a7") SYSCALL_LOG,
+            in("a0") message.as_ptr(),
+            in("a1") message.len(),
+        );
+    }
+
+    #[cfg(all(not(target_arch = "riscv32"), feature = "host"))]
+    print!("{message}");
+
+    #[cfg(all(not(target_arch = "riscv32"), not(feature = "host")))]
+    let _ = message;
+}
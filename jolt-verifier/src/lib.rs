@@ -0,0 +1,45 @@
+//! Verification of Jolt proofs of RV32I programs, for services that only verify.
+//!
+//! This crate builds `jolt-core` with only its `std` feature, which leaves out the guest
+//! toolchain and the tracer, the SRS download and its async runtime, memory-mapped trace files
+//! and the profiling layers, the parallel and assembly backends of arkworks, rayon and tracing.
+//! The polynomial and sum-check code that the verifier shares with the prover then runs
+//! sequentially and without spans, which is cheap at verification sizes.
+//!
+//! The prover distributes the program's verifier key, from
+//! `JoltPreprocessing::verifier_preprocessing`, and its proofs as [`JoltHyperKZGProof`]s:
+//!
+//! ```ignore
+//! let key = VerifierKey::deserialize_from_bytes(&key_bytes)?;
+//! let proof = JoltHyperKZGProof::deserialize_from_bytes(&proof_bytes)?;
+//! let outputs = &proof.proof.program_io.outputs;
+//! jolt_verifier::verify(key, proof)?;
+//! ```
+use ark_bn254::Fr;
+
+pub use jolt_core::jolt::vm::rv32i_vm::{
+    JoltHyperKZGProof, ProofTranscript, RV32IJoltVM, Serializable, C, M, PCS,
+};
+pub use jolt_core::jolt::vm::{Jolt, JoltVerifierPreprocessing};
+pub use jolt_core::utils::errors::ProofVerifyError;
+
+/// What the verifier needs of a program's preprocessing.
+pub type VerifierKey = JoltVerifierPreprocessing<C, Fr, PCS, ProofTranscript>;
+
+/// Verifies `proof` of the program with verifier key `key`. The program's inputs and outputs,
+/// which the caller should check against what it expects, are in `proof.proof.program_io`.
+pub fn verify(key: VerifierKey, proof: JoltHyperKZGProof) -> Result<(), ProofVerifyError> {
+    RV32IJoltVM::verify_with_verifier_preprocessing(key, proof.proof, proof.commitments, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_bytes() {
+        let garbage = [1u8, 2, 3];
+        assert!(VerifierKey::deserialize_from_bytes(&garbage).is_err());
+        assert!(JoltHyperKZGProof::deserialize_from_bytes(&garbage).is_err());
+    }
+}
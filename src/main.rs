@@ -1,8 +1,10 @@
 mod build_wasm;
+mod prove;
 
 use std::{
     fs::{self, File},
     io::Write,
+    path::PathBuf,
 };
 
 use clap::{Parser, Subcommand};
@@ -11,7 +13,8 @@ use rand::prelude::SliceRandom;
 use sysinfo::System;
 
 use build_wasm::{build_wasm, modify_cargo_toml};
-use jolt_core::host::toolchain;
+use jolt_core::host::{toolchain, Program};
+use prove::{BoundsArgs, ProgramArgs, SetupArgs};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -36,6 +39,55 @@ enum Command {
     UninstallToolchain,
     /// Handles preprocessing and generates WASM compatible files
     BuildWasm,
+    /// Runs a guest ELF in the Jolt emulator under GDB
+    Debug {
+        /// Path to the guest ELF
+        elf: PathBuf,
+        /// Guest input, as hex-encoded bytes serialized as the guest expects them
+        #[arg(short, long, default_value = "")]
+        input: String,
+        /// Address to wait for GDB on
+        #[arg(short, long, default_value = "127.0.0.1:1234")]
+        address: String,
+    },
+    /// Runs a guest ELF in the Jolt emulator, printing its cycle count and outputs
+    Trace {
+        #[command(flatten)]
+        program: ProgramArgs,
+    },
+    /// Proves an execution of a guest ELF, writing the proof and the program's verifier key
+    Prove {
+        #[command(flatten)]
+        program: ProgramArgs,
+        #[command(flatten)]
+        bounds: BoundsArgs,
+        #[command(flatten)]
+        setup: SetupArgs,
+        /// Where to write the proof
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Where to write the verifier key
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+    },
+    /// Verifies a proof written by `prove`, printing the outputs it attests to
+    Verify {
+        /// Path to the proof
+        proof: PathBuf,
+        /// Path to the program's verifier key
+        vk: PathBuf,
+        #[command(flatten)]
+        setup: SetupArgs,
+    },
+    /// Times the tracing, preprocessing, proving and verification of a guest ELF
+    Bench {
+        #[command(flatten)]
+        program: ProgramArgs,
+        #[command(flatten)]
+        bounds: BoundsArgs,
+        #[command(flatten)]
+        setup: SetupArgs,
+    },
 }
 
 fn main() {
@@ -45,6 +97,27 @@ fn main() {
         Command::InstallToolchain => install_toolchain(),
         Command::UninstallToolchain => uninstall_toolchain(),
         Command::BuildWasm => build_wasm(),
+        Command::Debug {
+            elf,
+            input,
+            address,
+        } => debug(elf, input, address),
+        Command::Trace { program } => prove::trace(program).expect("tracing failed"),
+        Command::Prove {
+            program,
+            bounds,
+            setup,
+            proof,
+            vk,
+        } => prove::prove(program, bounds, setup, proof, vk).expect("proving failed"),
+        Command::Verify { proof, vk, setup } => {
+            prove::verify(proof, vk, setup).expect("verification failed")
+        }
+        Command::Bench {
+            program,
+            bounds,
+            setup,
+        } => prove::bench(program, bounds, setup).expect("benchmark failed"),
     }
 }
 
@@ -57,6 +130,28 @@ fn create_project(name: String, wasm: bool) {
     }
 }
 
+fn debug(elf: PathBuf, input: String, address: String) {
+    let input = parse_hex(&input).expect("input must be hex-encoded");
+    let mut program = Program::new("debug");
+    program.elf = Some(elf);
+    program.set_input_bytes(&input);
+    println!("waiting for GDB on {address}");
+    let io_device = program.debug(&address).expect("debugging failed");
+    println!("outputs: {}", hex_string(&io_device.outputs));
+    println!("panic: {}", io_device.panic);
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>> {
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..(i + 2).min(hex.len())], 16))
+        .collect::<Result<_, _>>()?)
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
 fn install_toolchain() {
     if let Err(err) = toolchain::install_toolchain() {
         panic!("toolchain install failed: {}", err);
@@ -0,0 +1,313 @@
+//! The `trace`, `prove`, `verify` and `bench` subcommands, which run a guest ELF outside of a
+//! host built with the SDK.
+//!
+//! `prove` writes the proof and the program's verifier key, a serialized [`VerifierProgram`]
+//! from which `verify` redoes the preprocessing. Both must be given the same commitment scheme
+//! and SRS.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use ark_bn254::{Bn254, Fr};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::{Args, ValueEnum};
+use common::constants::{
+    DEFAULT_MAX_BYTECODE_SIZE, DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE,
+    DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
+};
+use eyre::{bail, eyre, Result};
+use jolt_core::host::Program;
+use jolt_core::jolt::vm::rv32i_vm::{ProofTranscript, RV32IJoltProof, RV32IJoltVM, C, M};
+use jolt_core::jolt::vm::{Jolt, JoltCommitments, JoltPreprocessing};
+use jolt_core::poly::commitment::commitment_scheme::{CommitShape, CommitmentScheme};
+use jolt_core::poly::commitment::hyperkzg::{HyperKZG, HyperKZGSRS};
+use jolt_core::poly::commitment::zeromorph::{Zeromorph, ZeromorphSRS};
+use jolt_core::wasm::VerifierProgram;
+
+use crate::{hex_string, parse_hex};
+
+// The same bound as the preprocessing of the SDK
+const DEFAULT_MAX_TRACE_LENGTH: usize = 1 << 24;
+
+#[derive(Args)]
+pub struct ProgramArgs {
+    /// Path to the guest ELF
+    elf: PathBuf,
+    /// Guest input, as hex-encoded bytes serialized as the guest expects them
+    #[arg(short, long, default_value = "")]
+    input: String,
+    /// Size of the guest's memory, in bytes
+    #[arg(long, default_value_t = DEFAULT_MEMORY_SIZE)]
+    memory_size: u64,
+    /// Size of the guest's stack, in bytes
+    #[arg(long, default_value_t = DEFAULT_STACK_SIZE)]
+    stack_size: u64,
+    /// Maximum size of the guest's input, in bytes
+    #[arg(long, default_value_t = DEFAULT_MAX_INPUT_SIZE)]
+    max_input_size: u64,
+    /// Maximum size of the guest's output, in bytes
+    #[arg(long, default_value_t = DEFAULT_MAX_OUTPUT_SIZE)]
+    max_output_size: u64,
+}
+
+impl ProgramArgs {
+    fn program(&self) -> Result<Program> {
+        let mut program = Program::new("jolt");
+        program.elf = Some(self.elf.clone());
+        program.set_memory_size(self.memory_size);
+        program.set_stack_size(self.stack_size);
+        program.set_max_input_size(self.max_input_size);
+        program.set_max_output_size(self.max_output_size);
+        program.set_input_bytes(&parse_hex(&self.input)?);
+        Ok(program)
+    }
+}
+
+/// The bounds the preprocessing supports, which determine the size of the SRS.
+#[derive(Args)]
+pub struct BoundsArgs {
+    /// Maximum number of instructions in the program, after expanding virtual sequences
+    #[arg(long, default_value_t = DEFAULT_MAX_BYTECODE_SIZE as usize)]
+    max_bytecode_size: usize,
+    /// Maximum number of cycles of an execution
+    #[arg(long, default_value_t = DEFAULT_MAX_TRACE_LENGTH)]
+    max_trace_length: usize,
+}
+
+#[derive(Args)]
+pub struct SetupArgs {
+    /// Polynomial commitment scheme
+    #[arg(long, value_enum, default_value_t = Pcs::HyperKzg)]
+    pcs: Pcs,
+    /// SRS to commit with: a `.ptau` file of a powers-of-tau ceremony, or a file written by
+    /// `HyperKZGSRS::save` / `ZeromorphSRS::save`. Without it, the SRS is generated from a
+    /// fixed seed, so its trapdoor is public: only use that for testing
+    #[arg(long)]
+    srs: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Pcs {
+    #[value(name = "hyperkzg")]
+    HyperKzg,
+    Zeromorph,
+}
+
+/// A commitment scheme whose setup can be loaded from an SRS file.
+trait SrsSetup: CommitmentScheme<ProofTranscript, Field = Fr> {
+    fn load_setup(srs: &Path, shapes: &[CommitShape]) -> Result<Self::Setup>;
+}
+
+impl SrsSetup for HyperKZG<Bn254, ProofTranscript> {
+    fn load_setup(srs: &Path, shapes: &[CommitShape]) -> Result<Self::Setup> {
+        let max_len = max_input_length(shapes);
+        let srs = if is_ptau(srs) {
+            HyperKZGSRS::from_ptau(srs, max_len)?
+        } else {
+            HyperKZGSRS::load(srs, shapes)?
+        };
+        let (pk, vk) = srs.trim(max_len);
+        Ok((pk, vk.prepare()))
+    }
+}
+
+impl SrsSetup for Zeromorph<Bn254, ProofTranscript> {
+    fn load_setup(srs: &Path, shapes: &[CommitShape]) -> Result<Self::Setup> {
+        let max_len = max_input_length(shapes);
+        let srs = if is_ptau(srs) {
+            ZeromorphSRS::from_ptau(srs, max_len)?
+        } else {
+            ZeromorphSRS::load(srs, shapes)?
+        };
+        let (pk, vk) = srs.try_trim(max_len)?;
+        Ok((pk, vk.prepare()))
+    }
+}
+
+fn max_input_length(shapes: &[CommitShape]) -> usize {
+    shapes.iter().map(|shape| shape.input_length).max().unwrap()
+}
+
+fn is_ptau(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "ptau")
+}
+
+/// Runs the program without proving it.
+pub fn trace(args: ProgramArgs) -> Result<()> {
+    let mut program = args.program()?;
+    let (io_device, trace) = program.trace()?;
+    println!("cycles: {}", trace.len());
+    println!("outputs: {}", hex_string(&io_device.outputs));
+    println!("panic: {}", io_device.panic);
+    Ok(())
+}
+
+/// Proves an execution of the program, writing the proof to `proof_path` and the program's
+/// verifier key to `vk_path`.
+pub fn prove(
+    args: ProgramArgs,
+    bounds: BoundsArgs,
+    setup: SetupArgs,
+    proof_path: PathBuf,
+    vk_path: PathBuf,
+) -> Result<()> {
+    let mut program = args.program()?;
+    let verifier_program = verifier_program(&mut program, &bounds);
+    fs::write(&vk_path, verifier_program.serialize_to_bytes()?)?;
+    let srs = setup.srs.as_deref();
+    let proof = match setup.pcs {
+        Pcs::HyperKzg => {
+            prove_with::<HyperKZG<Bn254, ProofTranscript>>(&mut program, verifier_program, srs)?
+        }
+        Pcs::Zeromorph => {
+            prove_with::<Zeromorph<Bn254, ProofTranscript>>(&mut program, verifier_program, srs)?
+        }
+    };
+    fs::write(&proof_path, proof)?;
+    println!("proof written to {}", proof_path.display());
+    println!("verifier key written to {}", vk_path.display());
+    Ok(())
+}
+
+/// Verifies the proof at `proof_path` of the program with the verifier key at `vk_path`.
+pub fn verify(proof_path: PathBuf, vk_path: PathBuf, setup: SetupArgs) -> Result<()> {
+    let verifier_program = VerifierProgram::deserialize_from_bytes(&fs::read(vk_path)?)?;
+    let proof = fs::read(proof_path)?;
+    let srs = setup.srs.as_deref();
+    match setup.pcs {
+        Pcs::HyperKzg => {
+            verify_with::<HyperKZG<Bn254, ProofTranscript>>(verifier_program, &proof, srs)
+        }
+        Pcs::Zeromorph => {
+            verify_with::<Zeromorph<Bn254, ProofTranscript>>(verifier_program, &proof, srs)
+        }
+    }
+}
+
+/// Times the tracing, preprocessing, proving and verification of the program.
+pub fn bench(args: ProgramArgs, bounds: BoundsArgs, setup: SetupArgs) -> Result<()> {
+    let mut program = args.program()?;
+    let verifier_program = verifier_program(&mut program, &bounds);
+    let srs = setup.srs.as_deref();
+    match setup.pcs {
+        Pcs::HyperKzg => {
+            bench_with::<HyperKZG<Bn254, ProofTranscript>>(&mut program, verifier_program, srs)
+        }
+        Pcs::Zeromorph => {
+            bench_with::<Zeromorph<Bn254, ProofTranscript>>(&mut program, verifier_program, srs)
+        }
+    }
+}
+
+fn verifier_program(program: &mut Program, bounds: &BoundsArgs) -> VerifierProgram {
+    let (bytecode, memory_init) = program.decode();
+    let memory_layout = program.memory_layout();
+    VerifierProgram {
+        bytecode,
+        max_memory_address: memory_layout.max_memory_address() as usize,
+        memory_layout,
+        memory_init,
+        max_bytecode_size: bounds.max_bytecode_size,
+        max_trace_length: bounds.max_trace_length,
+    }
+}
+
+fn preprocess<PCS: SrsSetup>(
+    program: VerifierProgram,
+    srs: Option<&Path>,
+) -> Result<JoltPreprocessing<C, Fr, PCS, ProofTranscript>> {
+    let Some(srs) = srs else {
+        return Ok(RV32IJoltVM::preprocess(
+            program.bytecode,
+            program.memory_layout,
+            program.memory_init,
+            program.max_bytecode_size,
+            program.max_memory_address,
+            program.max_trace_length,
+        ));
+    };
+    let shapes = <RV32IJoltVM as Jolt<Fr, PCS, C, M, ProofTranscript>>::commitment_shapes(
+        program.max_bytecode_size,
+        program.max_memory_address,
+        program.max_trace_length,
+    );
+    Ok(RV32IJoltVM::preprocess_with_setup(
+        program.bytecode,
+        program.memory_layout,
+        program.memory_init,
+        program.max_bytecode_size,
+        PCS::load_setup(srs, &shapes)?,
+    ))
+}
+
+fn prove_with<PCS: SrsSetup>(
+    program: &mut Program,
+    verifier_program: VerifierProgram,
+    srs: Option<&Path>,
+) -> Result<Vec<u8>> {
+    let max_trace_length = verifier_program.max_trace_length;
+    let preprocessing = preprocess::<PCS>(verifier_program, srs)?;
+    let (io_device, trace) = program.trace()?;
+    if trace.len() > max_trace_length {
+        bail!(
+            "the execution takes {} cycles, more than max_trace_length = {}",
+            trace.len(),
+            max_trace_length
+        );
+    }
+    let (proof, commitments, _) = RV32IJoltVM::prove(io_device, trace, preprocessing)?;
+    let mut bytes = Vec::new();
+    (proof, commitments).serialize_compressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn verify_with<PCS: SrsSetup>(
+    verifier_program: VerifierProgram,
+    proof: &[u8],
+    srs: Option<&Path>,
+) -> Result<()> {
+    let preprocessing = preprocess::<PCS>(verifier_program, srs)?;
+    let (proof, commitments) = <(
+        RV32IJoltProof<Fr, PCS, ProofTranscript>,
+        JoltCommitments<PCS, ProofTranscript>,
+    )>::deserialize_compressed(proof)?;
+    println!("outputs: {}", hex_string(&proof.program_io.outputs));
+    println!("panic: {}", proof.program_io.panic);
+    RV32IJoltVM::verify(preprocessing, proof, commitments, None)
+        .map_err(|err| eyre!("invalid proof: {err}"))?;
+    println!("proof is valid");
+    Ok(())
+}
+
+fn bench_with<PCS: SrsSetup>(
+    program: &mut Program,
+    verifier_program: VerifierProgram,
+    srs: Option<&Path>,
+) -> Result<()> {
+    let start = Instant::now();
+    let (io_device, trace) = program.trace()?;
+    println!(
+        "trace:          {:?} ({} cycles)",
+        start.elapsed(),
+        trace.len()
+    );
+
+    let start = Instant::now();
+    let preprocessing = preprocess::<PCS>(verifier_program.clone(), srs)?;
+    println!("preprocessing:  {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let (proof, commitments, _) = RV32IJoltVM::prove(io_device, trace, preprocessing)?;
+    println!("prove:          {:?}", start.elapsed());
+    let proof_size = proof.compressed_size() + commitments.compressed_size();
+    println!("proof size:     {} bytes", proof_size);
+
+    // The verifier redoes the preprocessing, as it would from a verifier key
+    let preprocessing = preprocess::<PCS>(verifier_program, srs)?;
+    let start = Instant::now();
+    RV32IJoltVM::verify(preprocessing, proof, commitments, None)
+        .map_err(|err| eyre!("invalid proof: {err}"))?;
+    println!("verify:         {:?}", start.elapsed());
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! Tracing a program in fixed-size chunks, each with the register state and memory values it
+//! starts from.
+//!
+//! This is only the tracing side of continuations. Jolt does not prove chunks independently:
+//! there are no per-chunk proofs, no commitments that carry the register file and memory from
+//! one chunk to the next, and no aggregation of chunk proofs, so a trace is still proven as a
+//! whole.
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use common::rv_trace::{JoltDevice, MemoryConfig, MemoryState, RVTraceRow};
+
+use crate::emulator::Emulator;
+use crate::setup_emulator;
+
+/// The machine state a chunk of a trace starts from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkState {
+    pub pc: u64,
+    /// The general-purpose registers, zero-extended from XLEN bits
+    pub registers: [u64; 32],
+}
+
+/// A fixed number of consecutive rows of a trace, with the state they start from.
+#[derive(Clone, Debug)]
+pub struct TraceChunk {
+    pub index: usize,
+    pub start: ChunkState,
+    pub rows: Vec<RVTraceRow>,
+}
+
+impl TraceChunk {
+    /// The value of each memory address the chunk accesses before the chunk runs, sorted by
+    /// address: the value read or overwritten by its first access.
+    pub fn memory_init(&self) -> Vec<(u64, u64)> {
+        let mut memory = BTreeMap::new();
+        for row in &self.rows {
+            let (address, value) = match row.memory_state {
+                Some(MemoryState::Read { address, value }) => (address, value),
+                Some(MemoryState::Write {
+                    address, pre_value, ..
+                }) => (address, pre_value),
+                None => continue,
+            };
+            memory.entry(address).or_insert(value);
+        }
+        memory.into_iter().collect()
+    }
+}
+
+/// Runs a program `chunk_size` instructions at a time, so that long executions can be traced
+/// without holding the whole trace in memory.
+pub struct TraceChunks {
+    emulator: Emulator,
+    chunk_size: usize,
+    index: usize,
+    prev_pc: u64,
+    terminated: bool,
+}
+
+impl TraceChunks {
+    pub fn new(
+        elf: &PathBuf,
+        inputs: &[u8],
+        memory_config: &MemoryConfig,
+        chunk_size: usize,
+    ) -> Self {
+        assert!(
+            chunk_size > 0,
+            "Chunks must contain at least one instruction"
+        );
+        Self {
+            emulator: setup_emulator(elf, inputs, memory_config),
+            chunk_size,
+            index: 0,
+            prev_pc: 0,
+            terminated: false,
+        }
+    }
+
+    /// The program's inputs and the outputs it has written so far; complete once the iterator
+    /// is exhausted.
+    pub fn device(&self) -> &JoltDevice {
+        &self.emulator.get_cpu().get_mmu().jolt_device
+    }
+
+    fn state(&self) -> ChunkState {
+        let cpu = self.emulator.get_cpu();
+        ChunkState {
+            pc: cpu.read_pc(),
+            registers: cpu.x.map(|value| cpu.unsigned_data(value)),
+        }
+    }
+}
+
+impl Iterator for TraceChunks {
+    type Item = TraceChunk;
+
+    fn next(&mut self) -> Option<TraceChunk> {
+        if self.terminated {
+            return None;
+        }
+        let start = self.state();
+        for _ in 0..self.chunk_size {
+            let pc = self.emulator.get_cpu().read_pc();
+            self.emulator.tick();
+
+            // The same termination heuristic as `trace`
+            if self.prev_pc == pc {
+                self.terminated = true;
+                break;
+            }
+            self.prev_pc = pc;
+        }
+
+        let rows = std::mem::take(
+            &mut *self
+                .emulator
+                .get_mut_cpu()
+                .tracer
+                .rows
+                .try_borrow_mut()
+                .unwrap(),
+        );
+        let chunk = TraceChunk {
+            index: self.index,
+            start,
+            rows,
+        };
+        self.index += 1;
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rv_trace::{ELFInstruction, RV32IM};
+
+    fn row(memory_state: Option<MemoryState>) -> RVTraceRow {
+        RVTraceRow {
+            instruction: ELFInstruction {
+                address: 0x100,
+                opcode: RV32IM::SW,
+                rs1: None,
+                rs2: None,
+                rd: None,
+                imm: None,
+                virtual_sequence_remaining: None,
+            },
+            register_state: Default::default(),
+            memory_state,
+            advice_value: None,
+        }
+    }
+
+    #[test]
+    fn memory_init_uses_first_access() {
+        let chunk = TraceChunk {
+            index: 0,
+            start: ChunkState {
+                pc: 0x100,
+                registers: [0; 32],
+            },
+            rows: vec![
+                row(Some(MemoryState::Write {
+                    address: 0x2000,
+                    pre_value: 1,
+                    post_value: 2,
+                })),
+                row(None),
+                row(Some(MemoryState::Read {
+                    address: 0x2000,
+                    value: 2,
+                })),
+                row(Some(MemoryState::Read {
+                    address: 0x1000,
+                    value: 7,
+                })),
+            ],
+        };
+
+        assert_eq!(chunk.memory_init(), vec![(0x1000, 7), (0x2000, 1)]);
+    }
+}
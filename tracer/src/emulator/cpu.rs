@@ -7,6 +7,7 @@ use std::rc::Rc;
 use std::str::FromStr;
 
 use crate::trace::Tracer;
+use common::constants::SYSCALL_LOG;
 use common::rv_trace::*;
 
 use self::fnv::FnvHashMap;
@@ -327,7 +328,7 @@ impl Cpu {
             }
             false => {
                 self.pc = self.pc.wrapping_add(2); // 16-bit length compressed instruction
-                self.uncompress(original_word & 0xffff)
+                Self::uncompress(original_word & 0xffff, &self.xlen)
             }
         };
 
@@ -875,7 +876,7 @@ impl Cpu {
     }
 
     // @TODO: Rename to better name?
-    fn unsigned_data(&self, value: i64) -> u64 {
+    pub fn unsigned_data(&self, value: i64) -> u64 {
         (value as u64) & self.unsigned_data_mask
     }
 
@@ -887,8 +888,10 @@ impl Cpu {
         }
     }
 
+    /// Expands a 16-bit compressed (RVC) instruction to its 32-bit counterpart. Encodings that
+    /// differ between RV32C and RV64C (C.JAL and C.ADDIW) are expanded according to `xlen`.
     // @TODO: Optimize
-    fn uncompress(&self, halfword: u32) -> u32 {
+    pub fn uncompress(halfword: u32, xlen: &Xlen) -> u32 {
         let op = halfword & 0x3; // [1:0]
         let funct3 = (halfword >> 13) & 0x7; // [15:13]
 
@@ -1014,8 +1017,12 @@ impl Cpu {
                         // @TODO: Support HINTs
                         // r == 0 and imm != 0 is HINTs
                     }
+                    1 if matches!(xlen, Xlen::Bit32) => {
+                        // C.JAL
+                        // jal x1, imm
+                        return (compressed_jump_imm(halfword) << 12) | (1 << 7) | 0x6f;
+                    }
                     1 => {
-                        // @TODO: Support C.JAL in 32-bit mode
                         // C.ADDIW
                         // addiw r, r, imm
                         let r = (halfword >> 7) & 0x1f;
@@ -1204,23 +1211,7 @@ impl Cpu {
                     5 => {
                         // C.J
                         // jal x0, imm
-                        let offset = match halfword & 0x1000 {
-								0x1000 => 0xfffff000,
-								_ => 0
-							} | // offset[31:12] <= [12]
-							((halfword >> 1) & 0x800) | // offset[11] <= [12]
-							((halfword >> 7) & 0x10) | // offset[4] <= [11]
-							((halfword >> 1) & 0x300) | // offset[9:8] <= [10:9]
-							((halfword << 2) & 0x400) | // offset[10] <= [8]
-							((halfword >> 1) & 0x40) | // offset[6] <= [7]
-							((halfword << 1) & 0x80) | // offset[7] <= [6]
-							((halfword >> 2) & 0xe) | // offset[3:1] <= [5:3]
-							((halfword << 3) & 0x20); // offset[5] <= [2]
-                        let imm = ((offset >> 1) & 0x80000) | // imm[19] <= offset[20]
-							((offset << 8) & 0x7fe00) | // imm[18:9] <= offset[10:1]
-							((offset >> 3) & 0x100) | // imm[8] <= offset[11]
-							((offset >> 12) & 0xff); // imm[7:0] <= offset[19:12]
-                        return (imm << 12) | 0x6f;
+                        return (compressed_jump_imm(halfword) << 12) | 0x6f;
                     }
                     6 => {
                         // C.BEQZ
@@ -1451,6 +1442,11 @@ impl Cpu {
         s
     }
 
+    /// Returns immutable `Mmu`
+    pub fn get_mmu(&self) -> &Mmu {
+        &self.mmu
+    }
+
     /// Returns mutable `Mmu`
     pub fn get_mut_mmu(&mut self) -> &mut Mmu {
         &mut self.mmu
@@ -1819,6 +1815,26 @@ fn normalize_register(value: usize) -> u64 {
     value.try_into().unwrap()
 }
 
+/// The 20-bit JAL immediate of a compressed C.J or C.JAL instruction.
+fn compressed_jump_imm(halfword: u32) -> u32 {
+    let offset = match halfword & 0x1000 {
+		0x1000 => 0xfffff000,
+		_ => 0
+	} | // offset[31:12] <= [12]
+	((halfword >> 1) & 0x800) | // offset[11] <= [12]
+	((halfword >> 7) & 0x10) | // offset[4] <= [11]
+	((halfword >> 1) & 0x300) | // offset[9:8] <= [10:9]
+	((halfword << 2) & 0x400) | // offset[10] <= [8]
+	((halfword >> 1) & 0x40) | // offset[6] <= [7]
+	((halfword << 1) & 0x80) | // offset[7] <= [6]
+	((halfword >> 2) & 0xe) | // offset[3:1] <= [5:3]
+	((halfword << 3) & 0x20); // offset[5] <= [2]
+    ((offset >> 1) & 0x80000) | // imm[19] <= offset[20]
+	((offset << 8) & 0x7fe00) | // imm[18:9] <= offset[10:1]
+	((offset >> 3) & 0x100) | // imm[8] <= offset[11]
+	((offset >> 12) & 0xff) // imm[7:0] <= offset[19:12]
+}
+
 fn trace_r(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstruction {
     let f = parse_format_r(word);
     ELFInstruction {
@@ -1936,7 +1952,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_i,
-        trace: None,
+        trace: Some(trace_i),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -1948,7 +1964,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xf800707f,
@@ -2476,7 +2492,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -2496,7 +2512,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xffffffff,
@@ -2514,19 +2530,22 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
         data: 0x00000073,
         name: "ECALL",
         operation: |cpu, _word, address| {
-            let exception_type = match cpu.privilege_mode {
-                PrivilegeMode::User => TrapType::EnvironmentCallFromUMode,
-                PrivilegeMode::Supervisor => TrapType::EnvironmentCallFromSMode,
-                PrivilegeMode::Machine => TrapType::EnvironmentCallFromMMode,
-                PrivilegeMode::Reserved => panic!("Unknown Privilege mode"),
-            };
-            Err(Trap {
-                trap_type: exception_type,
-                value: address,
-            })
+            // See `common::constants` for the syscall ABI
+            match cpu.unsigned_data(cpu.x[17]) {
+                SYSCALL_LOG => {
+                    let start = cpu.unsigned_data(cpu.x[10]);
+                    let len = cpu.unsigned_data(cpu.x[11]);
+                    let message: Vec<u8> = (start..start + len)
+                        .map(|address| cpu.mmu.peek(address).unwrap_or(0))
+                        .collect();
+                    print!("{}", String::from_utf8_lossy(&message));
+                    Ok(())
+                }
+                syscall => panic!("Unsupported syscall {syscall} at {address:#x}"),
+            }
         },
         disassemble: dump_empty,
-        trace: None,
+        trace: Some(trace_i),
     },
     Instruction {
         mask: 0xfe00007f,
@@ -2981,7 +3000,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_i_mem,
-        trace: None,
+        trace: Some(trace_i),
     },
     Instruction {
         mask: 0x0000707f,
@@ -3099,7 +3118,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_i_mem,
-        trace: None,
+        trace: Some(trace_i),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -3178,7 +3197,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xffffffff,
@@ -3288,7 +3307,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -3308,7 +3327,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0x0000707f,
@@ -3379,7 +3398,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
                 .store_doubleword(cpu.x[f.rs1].wrapping_add(f.imm) as u64, cpu.x[f.rs2] as u64)
         },
         disassemble: dump_format_s,
-        trace: None,
+        trace: Some(trace_s),
     },
     Instruction {
         mask: 0xfe007fff,
@@ -3444,7 +3463,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_i),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -3456,7 +3475,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -3558,7 +3577,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_i),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -3570,7 +3589,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xffffffff,
@@ -3651,7 +3670,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_i),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -3663,7 +3682,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -3687,7 +3706,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0x0000707f,
@@ -4110,11 +4129,14 @@ mod test_cpu {
         let mut cpu = create_cpu();
         // .uncompress() doesn't directly return an instruction but
         // it returns uncompressed word. Then you need to call .decode().
-        match cpu.decode(cpu.uncompress(0x20)) {
+        match cpu.decode(Cpu::uncompress(0x20, &cpu.xlen)) {
             Ok(inst) => assert_eq!(inst.name, "ADDI"),
             Err(_e) => panic!("Failed to decode"),
         };
         // @TODO: Should I test all compressed instructions?
+        // C.J, and C.JAL (which in RV64C encodes C.ADDIW instead)
+        assert_eq!(Cpu::uncompress(0xa001, &Xlen::Bit32), 0x6f);
+        assert_eq!(Cpu::uncompress(0x2001, &Xlen::Bit32), 0xef);
     }
 
     #[test]
@@ -549,14 +549,48 @@ impl Mmu {
         }
     }
 
+    /// Reads a byte of RAM or of the I/O region without tracing the access, e.g. for a
+    /// debugger. `None` if the address is mapped to neither.
+    pub fn peek(&mut self, address: u64) -> Option<u8> {
+        let device = &self.jolt_device;
+        if address >= DRAM_BASE {
+            self.memory
+                .validate_address(address)
+                .then(|| self.memory.read_byte(address))
+        } else if device.is_input(address)
+            || device.is_output(address)
+            || device.is_panic(address)
+            || device.is_termination(address)
+        {
+            Some(device.load(address))
+        } else {
+            None
+        }
+    }
+
+    /// Writes a byte of RAM without tracing the access, e.g. for a debugger. Returns whether
+    /// the address is in RAM.
+    pub fn poke(&mut self, address: u64, value: u8) -> bool {
+        let valid = address >= DRAM_BASE && self.memory.validate_address(address);
+        if valid {
+            self.memory.write_byte(address, value);
+        }
+        valid
+    }
+
+    /// The size in bytes of the memory words recorded in the trace, i.e. XLEN / 8.
+    fn word_bytes(&self) -> u64 {
+        match self.xlen {
+            Xlen::Bit32 => 4,
+            Xlen::Bit64 => 8,
+        }
+    }
+
     /// Records the memory word being accessed by a load instruction. The memory
     /// state is used in Jolt to construct the witnesses in `read_write_memory.rs`.
     fn trace_load(&mut self, effective_address: u64) {
-        let word_address = (effective_address >> 2) << 2;
-        let bytes = match self.xlen {
-            Xlen::Bit32 => 4,
-            Xlen::Bit64 => 8,
-        };
+        let bytes = self.word_bytes();
+        let word_address = effective_address - effective_address % bytes;
         if word_address < DRAM_BASE {
             if self.jolt_device.is_input(word_address) {
                 let mut value_bytes = [0u8; 8];
@@ -589,11 +623,8 @@ impl Mmu {
     /// construct the witnesses in `read_write_memory.rs`.
     fn trace_store_byte(&mut self, effective_address: u64, value: u64) {
         self.assert_effective_address(effective_address);
-        let bytes = match self.xlen {
-            Xlen::Bit32 => 4,
-            Xlen::Bit64 => 8,
-        };
-        let word_address = (effective_address >> 2) << 2;
+        let bytes = self.word_bytes();
+        let word_address = effective_address - effective_address % bytes;
 
         let pre_value = if effective_address < DRAM_BASE {
             let mut pre_value_bytes = [0u8; 8];
@@ -610,13 +641,8 @@ impl Mmu {
         };
 
         // Mask the value into the word
-        let post_value = match effective_address % 4 {
-            0 => value | (pre_value & 0xffffff00),
-            1 => (value << 8) | (pre_value & 0xffff00ff),
-            2 => (value << 16) | (pre_value & 0xff00ffff),
-            3 => (value << 24) | (pre_value & 0x00ffffff),
-            _ => unreachable!(),
-        };
+        let shift = 8 * (effective_address % bytes);
+        let post_value = (value << shift) | (pre_value & !(0xff << shift));
 
         self.tracer.push_memory(MemoryState::Write {
             address: word_address,
@@ -630,11 +656,8 @@ impl Mmu {
     /// construct the witnesses in `read_write_memory.rs`.
     fn trace_store_halfword(&mut self, effective_address: u64, value: u64) {
         self.assert_effective_address(effective_address);
-        let bytes = match self.xlen {
-            Xlen::Bit32 => 4,
-            Xlen::Bit64 => 8,
-        };
-        let word_address = (effective_address >> 2) << 2;
+        let bytes = self.word_bytes();
+        let word_address = effective_address - effective_address % bytes;
 
         let pre_value = if effective_address < DRAM_BASE {
             let mut pre_value_bytes = [0u8; 8];
@@ -651,13 +674,11 @@ impl Mmu {
         };
 
         // Mask the value into the word
-        let post_value = if effective_address % 4 == 2 {
-            (value << 16) | (pre_value & 0xffff)
-        } else if effective_address % 4 == 0 {
-            value | (pre_value & 0xffff0000)
-        } else {
+        if effective_address % 2 != 0 {
             panic!("Unaligned store {:x}", effective_address);
-        };
+        }
+        let shift = 8 * (effective_address % bytes);
+        let post_value = (value << shift) | (pre_value & !(0xffff << shift));
 
         self.tracer.push_memory(MemoryState::Write {
             address: word_address,
@@ -671,10 +692,7 @@ impl Mmu {
     /// in `read_write_memory.rs`.
     fn trace_store(&mut self, effective_address: u64, value: u64) {
         self.assert_effective_address(effective_address);
-        let bytes = match self.xlen {
-            Xlen::Bit32 => 4,
-            Xlen::Bit64 => 8,
-        };
+        let bytes = self.word_bytes();
 
         if effective_address < DRAM_BASE {
             let mut pre_value_bytes = [0u8; 8];
@@ -1260,6 +1278,7 @@ impl MemoryWrapper {
 mod test_mmu {
     use super::*;
     use crate::emulator::terminal::DummyTerminal;
+    use common::rv_trace::{ELFInstruction, RV32IM};
     use std::rc::Rc;
 
     const MEM_CAPACITY: u64 = 1024 * 1024;
@@ -1302,4 +1321,34 @@ mod test_mmu {
         let invalid_address = 1234;
         mmu.trace_store(invalid_address, 0xc50513);
     }
+    #[test]
+    fn test_store_byte_rv64_word() {
+        let mut mmu = setup_mmu(MEM_CAPACITY);
+        let address = DRAM_BASE + 8;
+        mmu.store_doubleword(address, 0x0123_4567_89ab_cdef)
+            .unwrap();
+
+        mmu.tracer.start_instruction(ELFInstruction {
+            address: DRAM_BASE,
+            opcode: RV32IM::SB,
+            rs1: Some(1),
+            rs2: Some(2),
+            rd: None,
+            imm: Some(5),
+            virtual_sequence_remaining: None,
+        });
+        mmu.store(address + 5, 0xff).unwrap();
+        mmu.tracer.end_instruction();
+
+        // The byte is masked into the 8-byte word containing it
+        let rows = mmu.tracer.rows.borrow();
+        assert_eq!(
+            rows.last().unwrap().memory_state,
+            Some(MemoryState::Write {
+                address,
+                pre_value: 0x0123_4567_89ab_cdef,
+                post_value: 0x0123_ff67_89ab_cdef,
+            })
+        );
+    }
 }
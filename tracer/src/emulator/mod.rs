@@ -17,6 +17,7 @@ pub mod terminal;
 use self::cpu::{Cpu, Xlen};
 use self::elf_analyzer::ElfAnalyzer;
 use self::terminal::Terminal;
+use crate::loader::ProgramImage;
 
 /// RISC-V emulator. It emulates RISC-V CPU and peripheral devices.
 ///
@@ -142,7 +143,13 @@ impl Emulator {
     // @TODO: Make ElfAnalyzer and move the core logic there.
     // @TODO: Returns `Err` if the passed contend doesn't seem ELF file
     pub fn setup_program(&mut self, data: Vec<u8>) {
-        let analyzer = ElfAnalyzer::new(data);
+        self.setup_program_with_memory(data, PROGRAM_MEMORY_CAPACITY)
+    }
+
+    /// Like `setup_program()`, but with `memory_capacity` bytes of RAM for the program
+    /// rather than the default.
+    pub fn setup_program_with_memory(&mut self, data: Vec<u8>, memory_capacity: u64) {
+        let analyzer = ElfAnalyzer::new(data.clone());
 
         if !analyzer.validate() {
             panic!("This file does not seem ELF file");
@@ -196,20 +203,13 @@ impl Emulator {
             self.cpu.get_mut_mmu().init_memory(TEST_MEMORY_CAPACITY);
         } else {
             self.is_test = false;
-            self.cpu.get_mut_mmu().init_memory(PROGRAM_MEMORY_CAPACITY);
+            self.cpu.get_mut_mmu().init_memory(memory_capacity);
         }
 
-        for header in &program_data_section_headers {
-            let sh_addr = header.sh_addr;
-            let sh_offset = header.sh_offset as usize;
-            let sh_size = header.sh_size as usize;
-            if sh_addr >= 0x80000000 && sh_offset > 0 && sh_size > 0 {
-                for j in 0..sh_size {
-                    self.cpu
-                        .get_mut_mmu()
-                        .store_raw(sh_addr + j as u64, analyzer.read_byte(sh_offset + j));
-                }
-            }
+        // Memory is zero-initialized, so only the initialized part of each segment is stored
+        let image = ProgramImage::load(&data, header.e_width as usize / 8);
+        for (address, byte) in image.memory_init() {
+            self.cpu.get_mut_mmu().store_raw(address, byte);
         }
 
         self.cpu.update_pc(header.e_entry);
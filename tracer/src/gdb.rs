@@ -0,0 +1,497 @@
+//! A GDB remote serial protocol server for the emulator, to debug a guest program as it runs
+//! under Jolt. Connect with a RISC-V GDB:
+//!
+//! ```text
+//! (gdb) file guest.elf
+//! (gdb) target remote 127.0.0.1:1234
+//! ```
+//!
+//! Supports reading and writing registers and memory, single-stepping, continuing, software and
+//! hardware breakpoints (both are kept by the server, not written to memory), and write, read
+//! and access watchpoints. The emulator traces memory a word at a time, so a watchpoint is hit
+//! by any access to a word that overlaps it.
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+
+use common::rv_trace::{JoltDevice, MemoryConfig, MemoryState};
+
+use crate::emulator::Emulator;
+
+const REGISTER_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "fp", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+const PC: usize = 32;
+// The number of instructions run between checks for an interrupt from GDB
+const INTERRUPT_CHECK_INTERVAL: usize = 1 << 16;
+
+/// Waits for GDB to connect on `address`, then runs the program `elf` on `inputs` under its
+/// control until it detaches or the program halts. Returns the program's I/O.
+pub fn debug(
+    elf: &PathBuf,
+    inputs: &[u8],
+    memory_config: &MemoryConfig,
+    address: impl ToSocketAddrs,
+) -> io::Result<JoltDevice> {
+    let emulator = crate::setup_emulator(elf, inputs, memory_config);
+    let (stream, _) = TcpListener::bind(address)?.accept()?;
+    let mut session = Session {
+        emulator,
+        reader: BufReader::new(stream.try_clone()?),
+        writer: BufWriter::new(stream.try_clone()?),
+        stream,
+        breakpoints: HashSet::new(),
+        watchpoints: Vec::new(),
+        halted: false,
+    };
+    session.serve()?;
+    Ok(session
+        .emulator
+        .get_mut_cpu()
+        .get_mut_mmu()
+        .jolt_device
+        .clone())
+}
+
+enum Stop {
+    Breakpoint,
+    /// A watchpoint of the given kind was hit by an access to the given address
+    Watchpoint(WatchKind, u64),
+    Interrupted,
+    Halted,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WatchKind {
+    Write,
+    Read,
+    Access,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Watchpoint {
+    kind: WatchKind,
+    address: u64,
+    length: u64,
+}
+
+impl Watchpoint {
+    /// The first watched address that `memory_state`, an access to the `word_bytes` bytes of a
+    /// word, touches, if it is an access of the watched kind.
+    fn hit(&self, memory_state: &MemoryState, word_bytes: u64) -> Option<u64> {
+        let (address, is_write) = match memory_state {
+            MemoryState::Read { address, .. } => (*address, false),
+            MemoryState::Write { address, .. } => (*address, true),
+        };
+        let kind_matches = match self.kind {
+            WatchKind::Write => is_write,
+            WatchKind::Read => !is_write,
+            WatchKind::Access => true,
+        };
+        let overlaps = address < self.address + self.length && self.address < address + word_bytes;
+        (kind_matches && overlaps).then(|| address.max(self.address))
+    }
+}
+
+struct Session {
+    emulator: Emulator,
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+    breakpoints: HashSet<u64>,
+    watchpoints: Vec<Watchpoint>,
+    halted: bool,
+}
+
+impl Session {
+    fn serve(&mut self) -> io::Result<()> {
+        while let Some(packet) = self.read_packet()? {
+            let (command, args) = packet.split_at(1.min(packet.len()));
+            let response = match command {
+                "?" => self.stop_reply(Stop::Breakpoint),
+                "g" => (0..=PC).map(|reg| self.register_hex(reg)).collect(),
+                "G" => self.write_registers(args),
+                "p" => usize::from_str_radix(args, 16)
+                    .ok()
+                    .filter(|reg| *reg <= PC)
+                    .map_or_else(|| "E01".to_string(), |reg| self.register_hex(reg)),
+                "P" => self.write_register(args),
+                "m" => self.read_memory(args),
+                "M" => self.write_memory(args),
+                "s" => {
+                    let stop = self.step();
+                    self.stop_reply(stop)
+                }
+                "c" => {
+                    let stop = self.resume()?;
+                    self.stop_reply(stop)
+                }
+                "Z" | "z" => self.update_breakpoint(command == "Z", args),
+                "H" => "OK".to_string(),
+                "D" => {
+                    self.write_packet("OK")?;
+                    return Ok(());
+                }
+                "k" => return Ok(()),
+                _ => self.query(&packet),
+            };
+            self.write_packet(&response)?;
+        }
+        Ok(())
+    }
+
+    fn query(&self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            "PacketSize=4000;qXfer:features:read+;swbreak+;hwbreak+".to_string()
+        } else if packet == "qAttached" {
+            "1".to_string()
+        } else if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            let xml = target_xml();
+            let (offset, length) = parse_pair(range).unwrap_or((0, 0));
+            let start = (offset as usize).min(xml.len());
+            let end = (start + length as usize).min(xml.len());
+            let prefix = if end == xml.len() { "l" } else { "m" };
+            format!("{prefix}{}", &xml[start..end])
+        } else {
+            // Unsupported
+            String::new()
+        }
+    }
+
+    fn xlen_bytes(&self) -> usize {
+        common::constants::XLEN / 8
+    }
+
+    fn register(&self, reg: usize) -> u64 {
+        let cpu = self.emulator.get_cpu();
+        match reg {
+            PC => cpu.read_pc(),
+            _ => cpu.read_register(reg as u8) as u64,
+        }
+    }
+
+    fn set_register(&mut self, reg: usize, value: u64) {
+        let cpu = self.emulator.get_mut_cpu();
+        match reg {
+            0 => {}
+            PC => cpu.update_pc(value),
+            _ => cpu.x[reg] = value as i64,
+        }
+    }
+
+    fn register_hex(&self, reg: usize) -> String {
+        hex(&self.register(reg).to_le_bytes()[..self.xlen_bytes()])
+    }
+
+    fn write_registers(&mut self, args: &str) -> String {
+        let Some(bytes) = unhex(args) else {
+            return "E01".to_string();
+        };
+        for (reg, value) in bytes.chunks(self.xlen_bytes()).take(PC + 1).enumerate() {
+            self.set_register(reg, le_value(value));
+        }
+        "OK".to_string()
+    }
+
+    fn write_register(&mut self, args: &str) -> String {
+        let parsed = args
+            .split_once('=')
+            .and_then(|(reg, value)| Some((usize::from_str_radix(reg, 16).ok()?, unhex(value)?)));
+        match parsed {
+            Some((reg, value)) if reg <= PC => {
+                self.set_register(reg, le_value(&value));
+                "OK".to_string()
+            }
+            _ => "E01".to_string(),
+        }
+    }
+
+    fn read_memory(&mut self, args: &str) -> String {
+        let Some((address, length)) = parse_pair(args) else {
+            return "E01".to_string();
+        };
+        let mmu = self.emulator.get_mut_cpu().get_mut_mmu();
+        let bytes: Option<Vec<u8>> = (address..address + length)
+            .map(|address| mmu.peek(address))
+            .collect();
+        bytes.map_or_else(|| "E01".to_string(), |bytes| hex(&bytes))
+    }
+
+    fn write_memory(&mut self, args: &str) -> String {
+        let parsed = args.split_once(':').and_then(|(range, data)| {
+            let (address, _) = parse_pair(range)?;
+            Some((address, unhex(data)?))
+        });
+        let Some((address, data)) = parsed else {
+            return "E01".to_string();
+        };
+        let mmu = self.emulator.get_mut_cpu().get_mut_mmu();
+        let written = data
+            .iter()
+            .enumerate()
+            .all(|(offset, byte)| mmu.poke(address + offset as u64, *byte));
+        if written {
+            "OK".to_string()
+        } else {
+            "E01".to_string()
+        }
+    }
+
+    fn update_breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let mut fields = args.split(',');
+        let (Some(kind), Some(address), Some(length)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return "E01".to_string();
+        };
+        let (Ok(address), Ok(length)) = (
+            u64::from_str_radix(address, 16),
+            u64::from_str_radix(length, 16),
+        ) else {
+            return "E01".to_string();
+        };
+        let watch_kind = match kind {
+            // Software and hardware breakpoints, whose length is the instruction's
+            "0" | "1" => {
+                if insert {
+                    self.breakpoints.insert(address);
+                } else {
+                    self.breakpoints.remove(&address);
+                }
+                return "OK".to_string();
+            }
+            "2" => WatchKind::Write,
+            "3" => WatchKind::Read,
+            "4" => WatchKind::Access,
+            // Unsupported
+            _ => return String::new(),
+        };
+        let watchpoint = Watchpoint {
+            kind: watch_kind,
+            address,
+            length,
+        };
+        if insert {
+            self.watchpoints.push(watchpoint);
+        } else if let Some(index) = self.watchpoints.iter().position(|w| *w == watchpoint) {
+            self.watchpoints.remove(index);
+        }
+        "OK".to_string()
+    }
+
+    /// Runs one instruction. The program has halted once it jumps to itself, as in `trace`.
+    fn step(&mut self) -> Stop {
+        if self.halted {
+            return Stop::Halted;
+        }
+        let pc = self.emulator.get_cpu().read_pc();
+        self.emulator.tick();
+        if self.emulator.get_cpu().read_pc() == pc {
+            self.halted = true;
+            return Stop::Halted;
+        }
+        self.watchpoint_hit().unwrap_or(Stop::Breakpoint)
+    }
+
+    /// The watchpoint hit by the memory access of the instruction that was just run, if any.
+    fn watchpoint_hit(&self) -> Option<Stop> {
+        if self.watchpoints.is_empty() {
+            return None;
+        }
+        let rows = self.emulator.get_cpu().tracer.rows.borrow();
+        let memory_state = rows.last()?.memory_state.as_ref()?;
+        let word_bytes = self.xlen_bytes() as u64;
+        self.watchpoints.iter().find_map(|watchpoint| {
+            let address = watchpoint.hit(memory_state, word_bytes)?;
+            Some(Stop::Watchpoint(watchpoint.kind, address))
+        })
+    }
+
+    fn resume(&mut self) -> io::Result<Stop> {
+        let mut count = 0;
+        loop {
+            match self.step() {
+                Stop::Breakpoint => {}
+                stop => return Ok(stop),
+            }
+            if self
+                .breakpoints
+                .contains(&self.emulator.get_cpu().read_pc())
+            {
+                return Ok(Stop::Breakpoint);
+            }
+            count += 1;
+            if count % INTERRUPT_CHECK_INTERVAL == 0 && self.interrupted()? {
+                return Ok(Stop::Interrupted);
+            }
+        }
+    }
+
+    /// Whether GDB has sent an interrupt (Ctrl-C) while the program was running.
+    fn interrupted(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut byte = [0u8];
+        let read = self.reader.read(&mut byte);
+        self.stream.set_nonblocking(false)?;
+        match read {
+            Ok(1) => Ok(byte[0] == 0x03),
+            Ok(_) => Ok(false),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn stop_reply(&mut self, stop: Stop) -> String {
+        match stop {
+            Stop::Breakpoint => "S05".to_string(),
+            Stop::Watchpoint(kind, address) => {
+                let name = match kind {
+                    WatchKind::Write => "watch",
+                    WatchKind::Read => "rwatch",
+                    WatchKind::Access => "awatch",
+                };
+                format!("T05{name}:{address:x};")
+            }
+            Stop::Interrupted => "S02".to_string(),
+            Stop::Halted => {
+                let panicked = self.emulator.get_mut_cpu().get_mut_mmu().jolt_device.panic;
+                format!("W{:02x}", panicked as u8)
+            }
+        }
+    }
+
+    /// Reads the next packet, acknowledging it. `None` once GDB disconnects.
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        let mut byte = [0u8];
+        loop {
+            if self.reader.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+            // Acknowledgements, and interrupts while the program is stopped, are ignored
+            if byte[0] == b'$' {
+                break;
+            }
+        }
+        let mut packet = Vec::new();
+        loop {
+            if self.reader.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+            if byte[0] == b'#' {
+                break;
+            }
+            packet.push(byte[0]);
+        }
+        let mut checksum = [0u8; 2];
+        self.reader.read_exact(&mut checksum)?;
+        self.writer.write_all(b"+")?;
+        self.writer.flush()?;
+        Ok(Some(String::from_utf8_lossy(&packet).into_owned()))
+    }
+
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.writer, "${data}#{checksum:02x}")?;
+        self.writer.flush()
+    }
+}
+
+fn target_xml() -> String {
+    let bits = common::constants::XLEN;
+    let mut xml = format!(
+        "<?xml version=\"1.0\"?><!DOCTYPE target SYSTEM \"gdb-target.dtd\"><target version=\"1.0\">\
+         <architecture>riscv:rv{bits}</architecture><feature name=\"org.gnu.gdb.riscv.cpu\">"
+    );
+    for (regnum, name) in REGISTER_NAMES.iter().enumerate() {
+        let kind = match *name {
+            "sp" | "fp" | "gp" | "tp" => "data_ptr",
+            _ => "int",
+        };
+        write!(
+            xml,
+            "<reg name=\"{name}\" bitsize=\"{bits}\" type=\"{kind}\" regnum=\"{regnum}\"/>"
+        )
+        .unwrap();
+    }
+    write!(
+        xml,
+        "<reg name=\"pc\" bitsize=\"{bits}\" type=\"code_ptr\" regnum=\"{PC}\"/></feature></target>"
+    )
+    .unwrap();
+    xml
+}
+
+/// Parses GDB's `address,length` argument.
+fn parse_pair(args: &str) -> Option<(u64, u64)> {
+    let (first, second) = args.split_once(',')?;
+    Some((
+        u64::from_str_radix(first, 16).ok()?,
+        u64::from_str_radix(second, 16).ok()?,
+    ))
+}
+
+fn le_value(bytes: &[u8]) -> u64 {
+    let mut value = [0u8; 8];
+    value[..bytes.len().min(8)].copy_from_slice(&bytes[..bytes.len().min(8)]);
+    u64::from_le_bytes(value)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        let bytes = [0x00, 0x13, 0xab, 0xff];
+        assert_eq!(hex(&bytes), "0013abff");
+        assert_eq!(unhex("0013abff").unwrap(), bytes);
+        assert_eq!(unhex("0g"), None);
+        assert_eq!(le_value(&[0x78, 0x56, 0x34, 0x12]), 0x12345678);
+        assert_eq!(parse_pair("80000000,4"), Some((0x80000000, 4)));
+    }
+
+    #[test]
+    fn watchpoint_hit() {
+        let watchpoint = Watchpoint {
+            kind: WatchKind::Write,
+            address: 0x80000006,
+            length: 2,
+        };
+        let write = |address| MemoryState::Write {
+            address,
+            pre_value: 0,
+            post_value: 1,
+        };
+        let read = |address| MemoryState::Read { address, value: 0 };
+
+        assert_eq!(watchpoint.hit(&write(0x80000004), 4), Some(0x80000006));
+        assert_eq!(watchpoint.hit(&write(0x80000008), 4), None);
+        assert_eq!(watchpoint.hit(&write(0x80000000), 4), None);
+        assert_eq!(watchpoint.hit(&read(0x80000004), 4), None);
+
+        let watchpoint = Watchpoint {
+            kind: WatchKind::Access,
+            ..watchpoint
+        };
+        assert_eq!(watchpoint.hit(&read(0x80000004), 4), Some(0x80000006));
+        assert_eq!(watchpoint.hit(&write(0x80000004), 4), Some(0x80000006));
+    }
+}
@@ -1,24 +1,32 @@
 #![allow(dead_code)]
 #![allow(clippy::legacy_numeric_constants)]
 
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{fmt, fs::File, io::Read, path::PathBuf};
 
 use common::{self, constants::RAM_START_ADDRESS};
 use emulator::{
-    cpu::{self, Xlen},
+    cpu::{self, Cpu, Xlen},
     default_terminal::DefaultTerminal,
     Emulator,
 };
 
-use object::{Object, ObjectSection, SectionKind};
+use object::{elf::EF_RISCV_RVC, FileFlags, Object, ObjectSection, SectionKind};
 
+mod chunks;
 mod decode;
 mod emulator;
+mod gdb;
+mod loader;
+mod profile;
 mod trace;
 
+pub use chunks::{ChunkState, TraceChunk, TraceChunks};
 pub use common::rv_trace::{
-    ELFInstruction, JoltDevice, MemoryState, RVTraceRow, RegisterState, RV32IM,
+    ELFInstruction, JoltDevice, MemoryConfig, MemoryState, RVTraceRow, RegisterState, RV32IM,
 };
+pub use gdb::debug;
+pub use loader::{LoadSegment, ProgramImage};
+pub use profile::Profile;
 
 use crate::decode::decode_raw;
 
@@ -26,28 +34,62 @@ use crate::decode::decode_raw;
 pub fn trace(
     elf: &PathBuf,
     inputs: &[u8],
-    input_size: u64,
-    output_size: u64,
+    memory_config: &MemoryConfig,
 ) -> (Vec<RVTraceRow>, JoltDevice) {
-    let term = DefaultTerminal::new();
-    let mut emulator = Emulator::new(Box::new(term));
-    emulator.update_xlen(get_xlen());
+    trace_with_max_cycles(elf, inputs, memory_config, None).unwrap()
+}
 
-    let mut jolt_device = JoltDevice::new(input_size, output_size);
-    jolt_device.inputs = inputs.to_vec();
-    emulator.get_mut_cpu().get_mut_mmu().jolt_device = jolt_device;
+/// The guest ran for the maximum number of cycles without terminating.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleLimitExceeded {
+    pub max_cycles: usize,
+    /// The address of the next instruction
+    pub pc: u64,
+    /// The addresses of the calls active when the guest was stopped, innermost first
+    pub backtrace: Vec<u64>,
+}
 
-    let mut elf_file = File::open(elf).unwrap();
+impl fmt::Display for CycleLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Guest exceeded {} cycles at pc {:#x}",
+            self.max_cycles, self.pc
+        )?;
+        for (frame, address) in self.backtrace.iter().enumerate() {
+            write!(f, "\n  #{frame} called from {address:#x}")?;
+        }
+        Ok(())
+    }
+}
 
-    let mut elf_contents = Vec::new();
-    elf_file.read_to_end(&mut elf_contents).unwrap();
+impl std::error::Error for CycleLimitExceeded {}
 
-    emulator.setup_program(elf_contents);
+/// Like [`trace`], but stops the guest with an error once it has run `max_cycles` instructions,
+/// rather than running forever if it does not terminate.
+#[tracing::instrument(skip_all)]
+pub fn trace_with_max_cycles(
+    elf: &PathBuf,
+    inputs: &[u8],
+    memory_config: &MemoryConfig,
+    max_cycles: Option<usize>,
+) -> Result<(Vec<RVTraceRow>, JoltDevice), CycleLimitExceeded> {
+    let mut emulator = setup_emulator(elf, inputs, memory_config);
 
     let mut prev_pc = 0;
+    let mut cycles = 0;
     loop {
         let pc = emulator.get_cpu().read_pc();
+        if max_cycles == Some(cycles) {
+            let rows = emulator.get_cpu().tracer.rows.borrow();
+            return Err(CycleLimitExceeded {
+                max_cycles: cycles,
+                pc,
+                backtrace: profile::backtrace(&rows),
+            });
+        }
         emulator.tick();
+        cycles += 1;
 
         // This is a trick to see if the program has terminated by throwing itself
         // into an infinite loop. It seems to be a good heuristic for now but we
@@ -66,7 +108,25 @@ pub fn trace(
 
     let device = emulator.get_mut_cpu().get_mut_mmu().jolt_device.clone();
 
-    (output, device)
+    Ok((output, device))
+}
+
+fn setup_emulator(elf: &PathBuf, inputs: &[u8], memory_config: &MemoryConfig) -> Emulator {
+    let term = DefaultTerminal::new();
+    let mut emulator = Emulator::new(Box::new(term));
+    emulator.update_xlen(get_xlen());
+
+    let mut jolt_device = JoltDevice::from_config(memory_config);
+    jolt_device.inputs = inputs.to_vec();
+    emulator.get_mut_cpu().get_mut_mmu().jolt_device = jolt_device;
+
+    let mut elf_file = File::open(elf).unwrap();
+
+    let mut elf_contents = Vec::new();
+    elf_file.read_to_end(&mut elf_contents).unwrap();
+
+    emulator.setup_program_with_memory(elf_contents, memory_config.ram_size);
+    emulator
 }
 
 #[tracing::instrument(skip_all)]
@@ -78,16 +138,30 @@ pub fn decode(elf: &[u8]) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
         .filter(|s| s.address() >= RAM_START_ADDRESS)
         .collect::<Vec<_>>();
 
+    // Binaries built for the C extension interleave 16-bit compressed instructions with 32-bit
+    // ones, so their text is decoded by instruction length rather than in 4-byte words.
+    let compressed = match obj.flags() {
+        FileFlags::Elf { e_flags, .. } => e_flags & EF_RISCV_RVC != 0,
+        _ => false,
+    };
+
     let mut instructions = Vec::new();
-    let mut data = Vec::new();
 
     for section in sections {
         let raw_data = section.data().unwrap();
 
         if let SectionKind::Text = section.kind() {
-            for (chunk, word) in raw_data.chunks(4).enumerate() {
-                let word = u32::from_le_bytes(word.try_into().unwrap());
-                let address = chunk as u64 * 4 + section.address();
+            let mut offset = 0;
+            while offset < raw_data.len() {
+                let address = offset as u64 + section.address();
+                let halfword = u16::from_le_bytes(raw_data[offset..offset + 2].try_into().unwrap());
+                let word = if compressed && halfword & 0x3 != 0x3 {
+                    offset += 2;
+                    Cpu::uncompress(halfword as u32, &get_xlen())
+                } else {
+                    offset += 4;
+                    u32::from_le_bytes(raw_data[offset - 4..offset].try_into().unwrap())
+                };
 
                 if let Ok(inst) = decode_raw(word) {
                     if let Some(trace) = inst.trace {
@@ -108,12 +182,9 @@ pub fn decode(elf: &[u8]) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
                 });
             }
         }
-        let address = section.address();
-        for (offset, byte) in raw_data.iter().enumerate() {
-            data.push((address + offset as u64, *byte));
-        }
     }
 
+    let data = ProgramImage::load(elf, common::constants::XLEN / 8).memory_init();
     (instructions, data)
 }
 
@@ -0,0 +1,124 @@
+use object::elf::{PF_R, PF_W, PF_X, R_RISCV_RELATIVE};
+use object::{Object, ObjectSection, ObjectSegment, RelocationKind, SectionKind, SegmentFlags};
+
+use common::constants::RAM_START_ADDRESS;
+
+/// The memory image of a program, as described by its ELF program headers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramImage {
+    pub entry: u64,
+    /// The loadable (`PT_LOAD`) segments in RAM, in the order they appear in the ELF.
+    pub segments: Vec<LoadSegment>,
+    /// The thread-local storage template (`.tdata` followed by `.tbss`), as `(address, size)`.
+    pub tls: Option<(u64, u64)>,
+}
+
+/// A loadable segment of a program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadSegment {
+    pub address: u64,
+    /// The size of the segment in memory. The bytes past `data` (e.g. `.bss`) are zero.
+    pub size: u64,
+    /// The initialized contents of the segment, with relocations applied.
+    pub data: Vec<u8>,
+    pub readable: bool,
+    pub writable: bool,
+    pub executable: bool,
+}
+
+impl LoadSegment {
+    /// The address range of the zero-initialized tail of the segment.
+    pub fn bss(&self) -> std::ops::Range<u64> {
+        self.address + self.data.len() as u64..self.address + self.size
+    }
+}
+
+impl ProgramImage {
+    /// Loads the memory image of `elf`. Segments below `RAM_START_ADDRESS` are not part of the
+    /// program's memory and are skipped. `R_RISCV_RELATIVE` relocations are applied for a load at
+    /// the linked address; other dynamic relocations are unsupported, since guests are statically
+    /// linked.
+    pub fn load(elf: &[u8], xlen_bytes: usize) -> Self {
+        let obj = object::File::parse(elf).unwrap();
+
+        let mut segments: Vec<LoadSegment> = obj
+            .segments()
+            .filter(|segment| segment.address() >= RAM_START_ADDRESS)
+            .map(|segment| {
+                let p_flags = match segment.flags() {
+                    SegmentFlags::Elf { p_flags } => p_flags,
+                    _ => PF_R | PF_W | PF_X,
+                };
+                LoadSegment {
+                    address: segment.address(),
+                    size: segment.size(),
+                    data: segment.data().unwrap().to_vec(),
+                    readable: p_flags & PF_R != 0,
+                    writable: p_flags & PF_W != 0,
+                    executable: p_flags & PF_X != 0,
+                }
+            })
+            .collect();
+
+        for (offset, relocation) in obj.dynamic_relocations().into_iter().flatten() {
+            match relocation.kind() {
+                RelocationKind::Elf(R_RISCV_RELATIVE) => {
+                    let value = relocation.addend().to_le_bytes();
+                    write_bytes(&mut segments, offset, &value[..xlen_bytes]);
+                }
+                kind => panic!("Unsupported relocation {kind:?} at {offset:#x}"),
+            }
+        }
+
+        let tls = obj
+            .sections()
+            .filter(|section| {
+                matches!(
+                    section.kind(),
+                    SectionKind::Tls | SectionKind::UninitializedTls
+                )
+            })
+            .map(|section| (section.address(), section.address() + section.size()))
+            .reduce(|(start, end), (s, e)| (start.min(s), end.max(e)))
+            .map(|(start, end)| (start, end - start));
+
+        Self {
+            entry: obj.entry(),
+            segments,
+            tls,
+        }
+    }
+
+    /// The initialized bytes of the program's memory. Zero-initialized memory is left out.
+    pub fn memory_init(&self) -> Vec<(u64, u8)> {
+        self.segments
+            .iter()
+            .flat_map(|segment| {
+                segment
+                    .data
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, byte)| (segment.address + offset as u64, *byte))
+            })
+            .collect()
+    }
+
+    /// The segment containing `address`, if any.
+    pub fn segment(&self, address: u64) -> Option<&LoadSegment> {
+        self.segments
+            .iter()
+            .find(|segment| (segment.address..segment.address + segment.size).contains(&address))
+    }
+}
+
+fn write_bytes(segments: &mut [LoadSegment], address: u64, bytes: &[u8]) {
+    let segment = segments
+        .iter_mut()
+        .find(|segment| {
+            segment.address <= address
+                && address + bytes.len() as u64 <= segment.address + segment.data.len() as u64
+        })
+        .unwrap_or_else(|| panic!("Relocation at {address:#x} is outside the loaded segments"));
+    let offset = (address - segment.address) as usize;
+    segment.data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
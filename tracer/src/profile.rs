@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use object::{Object, ObjectSymbol, SymbolKind};
+
+use common::rv_trace::{ELFInstruction, RVTraceRow, RV32IM};
+
+const RA: u64 = 1;
+// The alternate link register
+const T0: u64 = 5;
+const UNKNOWN: &str = "[unknown]";
+
+/// The number of instructions a program retired in each of its call stacks, as reconstructed from
+/// its trace and the ELF symbol table.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    functions: Vec<String>,
+    frames: Vec<Frame>,
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+    parent: Option<usize>,
+    function: usize,
+    /// Instructions retired in this frame itself, excluding its callees
+    cycles: usize,
+}
+
+impl Profile {
+    /// Attributes each instruction of `trace` to the function of the ELF `elf` containing it.
+    /// Calls and returns are found as in [`control_flow`]; a jump to another function without
+    /// linking (a tail call) replaces the current function in the stack.
+    pub fn new(elf: &[u8], trace: &[RVTraceRow]) -> Self {
+        Self::with_symbols(&Symbols::new(elf), trace)
+    }
+
+    fn with_symbols(symbols: &Symbols, trace: &[RVTraceRow]) -> Self {
+        let mut profile = Self {
+            functions: symbols.names.clone(),
+            frames: Vec::new(),
+        };
+        let mut children = HashMap::<(Option<usize>, usize), usize>::new();
+        // The frames of the callers of the current function
+        let mut stack: Vec<usize> = Vec::new();
+
+        for row in trace {
+            let instruction = &row.instruction;
+            let parent = stack.last().copied();
+            let function = symbols.lookup(instruction.address);
+            let frame = *children.entry((parent, function)).or_insert_with(|| {
+                profile.frames.push(Frame {
+                    parent,
+                    function,
+                    cycles: 0,
+                });
+                profile.frames.len() - 1
+            });
+            profile.frames[frame].cycles += 1;
+
+            match control_flow(instruction) {
+                ControlFlow::Call => stack.push(frame),
+                ControlFlow::Return => {
+                    stack.pop();
+                }
+                ControlFlow::Other => {}
+            }
+        }
+
+        profile
+    }
+
+    /// The total number of instructions retired.
+    pub fn cycles(&self) -> usize {
+        self.frames.iter().map(|frame| frame.cycles).sum()
+    }
+
+    /// The instructions retired in each function, excluding its callees, most first.
+    pub fn self_cycles(&self) -> Vec<(&str, usize)> {
+        let mut cycles = vec![0; self.functions.len()];
+        for frame in self.frames.iter() {
+            cycles[frame.function] += frame.cycles;
+        }
+        let mut cycles: Vec<_> = self
+            .functions
+            .iter()
+            .map(String::as_str)
+            .zip(cycles)
+            .filter(|(_, cycles)| *cycles > 0)
+            .collect();
+        cycles.sort_by(|a, b| b.1.cmp(&a.1));
+        cycles
+    }
+
+    /// The profile in the folded stacks format read by `flamegraph.pl` and `inferno`: one line
+    /// per call stack, with its functions from the outermost separated by `;`, then the number
+    /// of instructions retired in it.
+    pub fn folded(&self) -> String {
+        let mut folded = String::new();
+        for frame in self.frames.iter() {
+            let mut stack = vec![self.functions[frame.function].as_str()];
+            let mut parent = frame.parent;
+            while let Some(caller) = parent {
+                stack.push(&self.functions[self.frames[caller].function]);
+                parent = self.frames[caller].parent;
+            }
+            stack.reverse();
+            writeln!(folded, "{} {}", stack.join(";"), frame.cycles).unwrap();
+        }
+        folded
+    }
+}
+
+enum ControlFlow {
+    Call,
+    Return,
+    Other,
+}
+
+/// Calls are `JAL`/`JALR` that link into `ra` or `t0`, and returns are `JALR`s to them that
+/// do not link.
+fn control_flow(instruction: &ELFInstruction) -> ControlFlow {
+    if !matches!(instruction.opcode, RV32IM::JAL | RV32IM::JALR) {
+        return ControlFlow::Other;
+    }
+    if matches!(instruction.rd, Some(RA) | Some(T0)) {
+        ControlFlow::Call
+    } else if instruction.opcode == RV32IM::JALR && matches!(instruction.rs1, Some(RA) | Some(T0)) {
+        ControlFlow::Return
+    } else {
+        ControlFlow::Other
+    }
+}
+
+/// The addresses of the calls still active at the end of `trace`, innermost first.
+pub(crate) fn backtrace(trace: &[RVTraceRow]) -> Vec<u64> {
+    let mut stack = Vec::new();
+    for row in trace {
+        match control_flow(&row.instruction) {
+            ControlFlow::Call => stack.push(row.instruction.address),
+            ControlFlow::Return => {
+                stack.pop();
+            }
+            ControlFlow::Other => {}
+        }
+    }
+    stack.reverse();
+    stack
+}
+
+/// The function symbols of an ELF, sorted by address.
+struct Symbols {
+    /// The demangled function names, with [`UNKNOWN`] last
+    names: Vec<String>,
+    /// The start and end address of each function, indexing into `names`
+    ranges: Vec<(u64, u64, usize)>,
+}
+
+impl Symbols {
+    fn new(elf: &[u8]) -> Self {
+        let obj = object::File::parse(elf).unwrap();
+        let mut names = Vec::new();
+        let mut ranges = Vec::new();
+        for symbol in obj.symbols() {
+            if symbol.kind() != SymbolKind::Text || symbol.size() == 0 {
+                continue;
+            }
+            let Ok(name) = symbol.name() else {
+                continue;
+            };
+            ranges.push((
+                symbol.address(),
+                symbol.address() + symbol.size(),
+                names.len(),
+            ));
+            names.push(format!("{:#}", rustc_demangle::demangle(name)));
+        }
+        ranges.sort();
+        names.push(UNKNOWN.to_string());
+        Self { names, ranges }
+    }
+
+    fn lookup(&self, address: u64) -> usize {
+        let index = self
+            .ranges
+            .partition_point(|(start, _, _)| *start <= address);
+        match index.checked_sub(1).map(|index| self.ranges[index]) {
+            Some((_, end, function)) if address < end => function,
+            _ => self.names.len() - 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rv_trace::ELFInstruction;
+
+    fn row(address: u64, opcode: RV32IM, rs1: Option<u64>, rd: Option<u64>) -> RVTraceRow {
+        RVTraceRow {
+            instruction: ELFInstruction {
+                address,
+                opcode,
+                rs1,
+                rs2: None,
+                rd,
+                imm: None,
+                virtual_sequence_remaining: None,
+            },
+            register_state: Default::default(),
+            memory_state: None,
+            advice_value: None,
+        }
+    }
+
+    #[test]
+    fn call_stacks() {
+        let symbols = Symbols {
+            names: vec!["main".into(), "leaf".into(), UNKNOWN.into()],
+            ranges: vec![(0x100, 0x200, 0), (0x200, 0x300, 1)],
+        };
+        let trace = [
+            row(0x100, RV32IM::ADDI, Some(2), Some(2)),
+            row(0x104, RV32IM::JAL, None, Some(RA)),
+            row(0x200, RV32IM::ADD, Some(10), Some(10)),
+            row(0x204, RV32IM::ADD, Some(10), Some(10)),
+            row(0x208, RV32IM::JALR, Some(RA), Some(0)),
+            row(0x108, RV32IM::ADDI, Some(2), Some(2)),
+            row(0x400, RV32IM::ADDI, Some(2), Some(2)),
+        ];
+        let profile = Profile::with_symbols(&symbols, &trace);
+
+        assert_eq!(profile.cycles(), trace.len());
+        assert_eq!(profile.folded(), "main 3\nmain;leaf 3\n[unknown] 1\n");
+        assert_eq!(
+            profile.self_cycles(),
+            vec![("main", 3), ("leaf", 3), (UNKNOWN, 1)]
+        );
+        assert_eq!(backtrace(&trace[..4]), vec![0x104]);
+        assert!(backtrace(&trace).is_empty());
+    }
+}
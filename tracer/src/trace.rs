@@ -18,8 +18,6 @@ impl Tracer {
     }
 
     pub fn start_instruction(&self, inst: ELFInstruction) {
-        let mut inst = inst;
-        inst.address = inst.address as u32 as u64;
         *self.open.try_borrow_mut().unwrap() = true;
         self.rows.try_borrow_mut().unwrap().push(RVTraceRow {
             instruction: inst,